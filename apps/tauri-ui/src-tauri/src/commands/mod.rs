@@ -3,12 +3,12 @@ pub mod policy;
 pub mod tenant;
 
 pub use monitoring::{
-    check_quota_status, get_enforcer_ws_url, get_quota_metrics, list_all_quota_metrics,
-    query_audit_logs,
+    check_quota_status, get_enforcer_ws_url, get_quota_metrics, get_system_status,
+    list_all_quota_metrics, query_audit_logs,
 };
 pub use policy::{
     activate_policy_bundle, compile_policy_dsl, deploy_policy, get_policy_bundle,
-    list_policy_bundles, rollback_policy, test_policy,
+    list_policy_attributes, list_policy_bundles, rollback_policy, test_policy,
 };
 pub use tenant::{
     create_tenant, delete_tenant, get_tenant, list_tenants, set_quota_limits, update_tenant,
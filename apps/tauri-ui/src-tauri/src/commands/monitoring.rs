@@ -1,4 +1,4 @@
-use std::{net::IpAddr, time::Duration};
+use std::{collections::BTreeMap, net::IpAddr, time::Duration};
 
 use chrono::{DateTime, Utc};
 use reqwest::{Client, StatusCode, Url};
@@ -45,6 +45,51 @@ pub struct QuotaStatus {
     pub warning_threshold_reached: bool,
 }
 
+/// Reachability of one service and the integer counters it reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub reachable: bool,
+    /// `status` from the service's `/health` body (`healthy`, `degraded`,
+    /// `unhealthy`), or `unreachable` / `not_configured`.
+    pub status: String,
+    pub http_status: Option<u16>,
+    /// Integer fields of the `/health` body, such as the enforcer's
+    /// `tenant_count`, plus `tracked_tenants` for the quota tracker.
+    pub counters: BTreeMap<String, u64>,
+    pub error: Option<String>,
+}
+
+impl ServiceStatus {
+    fn unreachable(error: String) -> Self {
+        Self {
+            reachable: false,
+            status: "unreachable".to_string(),
+            http_status: None,
+            counters: BTreeMap::new(),
+            error: Some(error),
+        }
+    }
+
+    fn not_configured() -> Self {
+        Self {
+            reachable: false,
+            status: "not_configured".to_string(),
+            http_status: None,
+            counters: BTreeMap::new(),
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemStatus {
+    pub enforcer: ServiceStatus,
+    pub proxy: ServiceStatus,
+    pub audit_store: ServiceStatus,
+    pub quota_tracker: ServiceStatus,
+    pub bridge: ServiceStatus,
+}
+
 #[tauri::command]
 pub fn get_enforcer_ws_url() -> Result<String, String> {
     get_enforcer_ws_url_impl().map_err(|err| err.to_string())
@@ -123,6 +168,13 @@ pub async fn check_quota_status(tenant_id: String) -> Result<QuotaStatus, String
         .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub async fn get_system_status() -> Result<SystemStatus, String> {
+    get_system_status_impl()
+        .await
+        .map_err(|err| err.to_string())
+}
+
 async fn query_audit_logs_impl(
     tenant_id: String,
     start_time: Option<String>,
@@ -293,6 +345,101 @@ async fn check_quota_status_impl(tenant_id: &str) -> Result<QuotaStatus, Command
     Ok(status)
 }
 
+async fn get_system_status_impl() -> Result<SystemStatus, CommandError> {
+    let (config, client) = setup_client()?;
+    let status = collect_system_status(&config, &client).await;
+
+    info!(
+      enforcer = %status.enforcer.status,
+      proxy = %status.proxy.status,
+      audit_store = %status.audit_store.status,
+      quota_tracker = %status.quota_tracker.status,
+      bridge = %status.bridge.status,
+      "collected system status via Tauri command"
+    );
+
+    Ok(status)
+}
+
+/// Probes every service concurrently. A failing service is reported in its
+/// own entry rather than failing the whole call.
+async fn collect_system_status(config: &ServiceConfig, client: &Client) -> SystemStatus {
+    let (enforcer, proxy, audit_store, mut quota_tracker, bridge, tracked_tenants) = tokio::join!(
+        probe_service(client, Some(&config.enforcer_url)),
+        probe_service(client, Some(&config.proxy_url)),
+        probe_service(client, Some(&config.audit_store_url)),
+        probe_service(client, Some(&config.quota_tracker_url)),
+        probe_service(client, config.bridge_url.as_deref()),
+        count_quota_tenants(client, &config.quota_tracker_url),
+    );
+
+    if let Some(count) = tracked_tenants {
+        quota_tracker
+            .counters
+            .insert("tracked_tenants".to_string(), count);
+    }
+
+    SystemStatus {
+        enforcer,
+        proxy,
+        audit_store,
+        quota_tracker,
+        bridge,
+    }
+}
+
+async fn probe_service(client: &Client, base: Option<&str>) -> ServiceStatus {
+    let Some(base) = base else {
+        return ServiceStatus::not_configured();
+    };
+    let url = match build_url(base, "health") {
+        Ok(url) => url,
+        Err(err) => return ServiceStatus::unreachable(err.to_string()),
+    };
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(err) => return ServiceStatus::unreachable(CommandError::from(err).to_string()),
+    };
+
+    let http_status = response.status();
+    let body = response.json::<Value>().await.unwrap_or(Value::Null);
+    let status = body
+        .get("status")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            if http_status.is_success() {
+                "healthy".to_string()
+            } else {
+                "unhealthy".to_string()
+            }
+        });
+    let counters = body
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| value.as_u64().map(|count| (key.clone(), count)))
+        .collect();
+
+    ServiceStatus {
+        reachable: true,
+        status,
+        http_status: Some(http_status.as_u16()),
+        counters,
+        error: None,
+    }
+}
+
+async fn count_quota_tenants(client: &Client, base: &str) -> Option<u64> {
+    let url = build_url(base, "api/quota").ok()?;
+    let response = client.get(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let metrics = response.json::<Vec<Value>>().await.ok()?;
+    Some(metrics.len() as u64)
+}
+
 fn setup_client() -> Result<(ServiceConfig, Client), CommandError> {
     let config =
         ServiceConfig::from_env().map_err(|err| CommandError::ValidationError(err.to_string()))?;
@@ -413,7 +560,11 @@ fn normalize_host_for_client(host: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::normalize_decision;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
 
     #[test]
     fn normalizes_allow_variants() {
@@ -433,4 +584,72 @@ mod tests {
     fn preserves_unknown_values() {
         assert_eq!(normalize_decision("pending"), "pending");
     }
+
+    async fn health_server(status: u16, body: Value) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(status).set_body_json(body))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn system_status_reflects_each_service() {
+        let enforcer = health_server(200, json!({ "status": "healthy", "tenant_count": 3 })).await;
+        let proxy = health_server(503, json!({ "status": "unhealthy" })).await;
+        let quota_tracker = health_server(200, json!({ "status": "healthy" })).await;
+        Mock::given(method("GET"))
+            .and(path("/api/quota"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(
+                    json!([{ "tenant_id": "tenant-a" }, { "tenant_id": "tenant-b" }]),
+                ),
+            )
+            .mount(&quota_tracker)
+            .await;
+
+        // Nothing listens on the audit store's port once the listener is dropped.
+        let audit_store_url = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+
+        let config = ServiceConfig {
+            enforcer_url: enforcer.uri(),
+            proxy_url: proxy.uri(),
+            audit_store_url,
+            quota_tracker_url: quota_tracker.uri(),
+            bridge_url: None,
+            ..ServiceConfig::default()
+        };
+        let client = Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .unwrap();
+
+        let status = collect_system_status(&config, &client).await;
+
+        assert!(status.enforcer.reachable);
+        assert_eq!(status.enforcer.status, "healthy");
+        assert_eq!(status.enforcer.counters.get("tenant_count"), Some(&3));
+
+        assert!(status.proxy.reachable);
+        assert_eq!(status.proxy.status, "unhealthy");
+        assert_eq!(status.proxy.http_status, Some(503));
+
+        assert!(!status.audit_store.reachable);
+        assert_eq!(status.audit_store.status, "unreachable");
+        assert!(status.audit_store.error.is_some());
+
+        assert!(status.quota_tracker.reachable);
+        assert_eq!(
+            status.quota_tracker.counters.get("tracked_tenants"),
+            Some(&2)
+        );
+
+        assert!(!status.bridge.reachable);
+        assert_eq!(status.bridge.status, "not_configured");
+    }
 }
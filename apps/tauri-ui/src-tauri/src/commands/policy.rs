@@ -2,7 +2,10 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use chrono::Utc;
-use edge_policy_dsl::{compile_policy, PolicyDslError, PolicyMetadata};
+use edge_policy_dsl::{
+    available_attributes, compile_policy, content_bundle_id, AttributeDescriptor, PolicyDslError,
+    PolicyMetadata,
+};
 use reqwest::{Client, StatusCode, Url};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -131,19 +134,27 @@ pub fn compile_policy_dsl(
                         attribute: None,
                     }]
                 }
-                PolicyDslError::ValidationError { message, attribute } => {
+                PolicyDslError::ValidationError {
+                    message,
+                    attribute,
+                    location,
+                } => {
                     vec![CompilationError {
                         message,
-                        line: None,
-                        column: None,
+                        line: location.map(|loc| loc.0 as u32),
+                        column: location.map(|loc| loc.1 as u32),
                         attribute,
                     }]
                 }
-                PolicyDslError::InvalidAttribute { path, reason } => {
+                PolicyDslError::InvalidAttribute {
+                    path,
+                    reason,
+                    location,
+                } => {
                     vec![CompilationError {
                         message: format!("{reason}"),
-                        line: None,
-                        column: None,
+                        line: location.map(|loc| loc.0 as u32),
+                        column: location.map(|loc| loc.1 as u32),
                         attribute: Some(path),
                     }]
                 }
@@ -164,6 +175,12 @@ pub fn compile_policy_dsl(
     }
 }
 
+/// Attribute paths the DSL accepts, for editor autocompletion.
+#[tauri::command]
+pub fn list_policy_attributes() -> Vec<AttributeDescriptor> {
+    available_attributes()
+}
+
 #[tauri::command]
 pub async fn test_policy(tenant_id: String, input: Value) -> Result<TestPolicyResponse, String> {
     test_policy_impl(&tenant_id, input)
@@ -267,38 +284,34 @@ async fn deploy_policy_impl(
 
     let (config, client) = setup_client()?;
 
-    let bundle_id = Uuid::new_v4().to_string();
-    let created_at = Utc::now().to_rfc3339();
-
-    // Always create bundle as draft first
-    let payload = PolicyBundlePayload {
-        bundle_id: bundle_id.clone(),
-        tenant_id: tenant_id.to_string(),
-        version: 0,
-        rego_code: rego_code.clone(),
-        metadata: if metadata.is_null() {
-            None
-        } else {
-            Some(metadata.clone())
-        },
-        status: "draft".to_string(),
-        created_at: created_at.clone(),
-        activated_at: None,
+    let bundle_id = if config.content_bundle_ids {
+        content_bundle_id(tenant_id, &rego_code)
+    } else {
+        Uuid::new_v4().to_string()
     };
 
-    let url = build_url(&config.audit_store_url, "/api/bundles")?;
-    let response = client
-        .post(url)
-        .json(&payload)
-        .send()
-        .await
-        .map_err(CommandError::from)?;
-
-    if !response.status().is_success() {
-        return Err(map_api_error(response, "audit-store").await);
-    }
+    // A content-derived id that is already stored means identical Rego was
+    // deployed before; reuse that bundle instead of storing a duplicate.
+    let existing = if config.content_bundle_ids {
+        fetch_policy_bundle(&client, &config, &bundle_id).await?
+    } else {
+        None
+    };
 
-    let bundle: PolicyBundle = response.json().await.map_err(CommandError::from)?;
+    let bundle = match existing {
+        Some(bundle) => {
+            let bundle = reusable_bundle(bundle, tenant_id, &rego_code)?;
+            info!(
+              tenant_id = %tenant_id,
+              bundle_id = %bundle.bundle_id,
+              "bundle with identical content already stored"
+            );
+            bundle
+        }
+        None => {
+            create_draft_bundle(&client, &config, tenant_id, bundle_id, rego_code, metadata).await?
+        }
+    };
 
     // Only write .rego file and reload enforcer if activating
     if activate {
@@ -332,6 +345,45 @@ async fn deploy_policy_impl(
     })
 }
 
+async fn create_draft_bundle(
+    client: &Client,
+    config: &ServiceConfig,
+    tenant_id: &str,
+    bundle_id: String,
+    rego_code: String,
+    metadata: Value,
+) -> Result<PolicyBundle, CommandError> {
+    // Always create bundle as draft first
+    let payload = PolicyBundlePayload {
+        bundle_id,
+        tenant_id: tenant_id.to_string(),
+        version: 0,
+        rego_code,
+        metadata: if metadata.is_null() {
+            None
+        } else {
+            Some(metadata)
+        },
+        status: "draft".to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        activated_at: None,
+    };
+
+    let url = build_url(&config.audit_store_url, "/api/bundles")?;
+    let response = client
+        .post(url)
+        .json(&payload)
+        .send()
+        .await
+        .map_err(CommandError::from)?;
+
+    if !response.status().is_success() {
+        return Err(map_api_error(response, "audit-store").await);
+    }
+
+    response.json().await.map_err(CommandError::from)
+}
+
 async fn list_policy_bundles_impl(tenant_id: &str) -> Result<Vec<PolicyBundle>, CommandError> {
     let (config, client) = setup_client()?;
     let mut url = build_url(&config.audit_store_url, "/api/bundles")?;
@@ -477,6 +529,23 @@ async fn map_api_error(response: reqwest::Response, service: &str) -> CommandErr
     }
 }
 
+/// Returns `existing` when it holds exactly the tenant and Rego being
+/// deployed. A stored bundle under the same content-derived id with anything
+/// else is a checksum collision and must not be activated in their place.
+fn reusable_bundle(
+    existing: PolicyBundle,
+    tenant_id: &str,
+    rego_code: &str,
+) -> Result<PolicyBundle, CommandError> {
+    if existing.tenant_id != tenant_id || existing.rego_code != rego_code {
+        return Err(CommandError::Conflict(format!(
+            "bundle {} already stores different content",
+            existing.bundle_id
+        )));
+    }
+    Ok(existing)
+}
+
 async fn fetch_policy_bundle(
     client: &Client,
     config: &ServiceConfig,
@@ -559,3 +628,46 @@ async fn reload_enforcer(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_bundle(tenant_id: &str, rego_code: &str) -> PolicyBundle {
+        PolicyBundle {
+            bundle_id: content_bundle_id(tenant_id, rego_code),
+            tenant_id: tenant_id.to_string(),
+            version: 1,
+            rego_code: rego_code.to_string(),
+            metadata: None,
+            status: "draft".to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            activated_at: None,
+        }
+    }
+
+    #[test]
+    fn reuses_bundle_with_identical_content() {
+        let rego = "package tenants.tenant_a\n\nallow := true\n";
+        let bundle = reusable_bundle(stored_bundle("tenant-a", rego), "tenant-a", rego)
+            .expect("identical content is reused");
+
+        assert_eq!(bundle.bundle_id, content_bundle_id("tenant-a", rego));
+    }
+
+    #[test]
+    fn colliding_bundle_id_with_different_content_is_a_conflict() {
+        let rego = "package tenants.tenant_a\n\nallow := true\n";
+
+        // Simulate a collision: the stored bundle sits under the id being
+        // deployed but holds other Rego, or belongs to another tenant.
+        let other_rego = stored_bundle("tenant-a", "package tenants.tenant_a\n\nallow := false\n");
+        let other_tenant = stored_bundle("tenant-b", rego);
+
+        for existing in [other_rego, other_tenant] {
+            let err = reusable_bundle(existing, "tenant-a", rego)
+                .expect_err("different content must not be reused");
+            assert!(matches!(err, CommandError::Conflict(_)), "{err}");
+        }
+    }
+}
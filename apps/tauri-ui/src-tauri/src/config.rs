@@ -11,12 +11,19 @@ use tracing::info;
 pub struct ServiceConfig {
     pub audit_store_url: String,
     pub quota_tracker_url: String,
+    pub proxy_url: String,
+    /// Health endpoint base for the MQTT bridge, which has no HTTP listener
+    /// of its own; unset reports the bridge as not configured.
+    pub bridge_url: Option<String>,
     pub enforcer_url: String,
     pub enforcer_host: String,
     pub enforcer_port: u16,
     pub enforcer_use_tls: bool,
     pub enforcer_bundles_dir: PathBuf,
     pub request_timeout_secs: u64,
+    /// Derive deployed bundle ids from the Rego content instead of a random
+    /// UUID, so redeploying identical Rego reuses the stored bundle.
+    pub content_bundle_ids: bool,
 }
 
 impl Default for ServiceConfig {
@@ -24,12 +31,15 @@ impl Default for ServiceConfig {
         Self {
             audit_store_url: "http://127.0.0.1:8182".to_string(),
             quota_tracker_url: "http://127.0.0.1:8183".to_string(),
+            proxy_url: "http://127.0.0.1:8080".to_string(),
+            bridge_url: None,
             enforcer_url: "http://127.0.0.1:8181".to_string(),
             enforcer_host: "127.0.0.1".to_string(),
             enforcer_port: 8181,
             enforcer_use_tls: false,
             enforcer_bundles_dir: Self::default_bundles_dir(),
             request_timeout_secs: 10,
+            content_bundle_ids: false,
         }
     }
 }
@@ -57,6 +67,14 @@ impl ServiceConfig {
             config.quota_tracker_url = value;
         }
 
+        if let Ok(value) = env::var("PROXY_URL") {
+            config.proxy_url = value;
+        }
+
+        if let Ok(value) = env::var("BRIDGE_URL") {
+            config.bridge_url = Some(value).filter(|value| !value.trim().is_empty());
+        }
+
         if let Ok(value) = env::var("ENFORCER_URL") {
             config.enforcer_url = value;
         }
@@ -128,6 +146,11 @@ impl ServiceConfig {
                 .map_err(|err| anyhow!("invalid REQUEST_TIMEOUT_SECS value `{value}`: {err}",))?;
         }
 
+        if let Ok(value) = env::var("CONTENT_BUNDLE_IDS") {
+            config.content_bundle_ids = parse_bool(&value)
+                .map_err(|err| anyhow!("invalid CONTENT_BUNDLE_IDS flag `{value}`: {err}"))?;
+        }
+
         config.validate()?;
 
         // Log the resolved bundles directory
@@ -143,8 +166,12 @@ impl ServiceConfig {
         for (name, value) in [
             ("AUDIT_STORE_URL", &self.audit_store_url),
             ("QUOTA_TRACKER_URL", &self.quota_tracker_url),
+            ("PROXY_URL", &self.proxy_url),
             ("ENFORCER_URL", &self.enforcer_url),
-        ] {
+        ]
+        .into_iter()
+        .chain(self.bridge_url.as_ref().map(|url| ("BRIDGE_URL", url)))
+        {
             Url::parse(value).map_err(|err| anyhow!("invalid {name} `{value}`: {err}"))?;
         }
 
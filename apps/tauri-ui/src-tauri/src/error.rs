@@ -17,6 +17,8 @@ pub enum CommandError {
     SerializationError(String),
     #[error("resource not found: {0}")]
     NotFound(String),
+    #[error("conflict: {0}")]
+    Conflict(String),
 }
 
 impl Serialize for CommandError {
@@ -14,6 +14,7 @@ fn main() {
             edge_policy_tauri_ui::delete_tenant,
             edge_policy_tauri_ui::set_quota_limits,
             edge_policy_tauri_ui::compile_policy_dsl,
+            edge_policy_tauri_ui::list_policy_attributes,
             edge_policy_tauri_ui::test_policy,
             edge_policy_tauri_ui::deploy_policy,
             edge_policy_tauri_ui::list_policy_bundles,
@@ -24,7 +25,8 @@ fn main() {
             edge_policy_tauri_ui::get_quota_metrics,
             edge_policy_tauri_ui::list_all_quota_metrics,
             edge_policy_tauri_ui::get_enforcer_ws_url,
-            edge_policy_tauri_ui::check_quota_status
+            edge_policy_tauri_ui::check_quota_status,
+            edge_policy_tauri_ui::get_system_status
         ])
         .run(tauri::generate_context!())
         .expect("error while running Edge Policy Hub application");
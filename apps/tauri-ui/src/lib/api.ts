@@ -5,6 +5,7 @@ import type {
   UpdateTenantRequest,
 } from "../types/tenant";
 import type {
+  AttributeDescriptor,
   CompilePolicyResponse,
   DeployPolicyResponse,
   PolicyBundle,
@@ -17,6 +18,7 @@ import type {
   AuditLogFilter,
   QuotaMetrics,
   QuotaStatus,
+  SystemStatus,
 } from "../types/monitoring";
 
 function mapError(error: unknown): Error {
@@ -97,6 +99,10 @@ export async function compilePolicyDsl(
   });
 }
 
+export async function listPolicyAttributes() {
+  return callCommand<AttributeDescriptor[]>("list_policy_attributes");
+}
+
 export async function testPolicy(tenantId: string, input: AbacInput) {
   return callCommand<TestPolicyResponse>("test_policy", { tenant_id: tenantId, input });
 }
@@ -153,3 +159,7 @@ export async function listAllQuotaMetrics() {
 export async function checkQuotaStatus(tenantId: string) {
   return callCommand<QuotaStatus>("check_quota_status", { tenant_id: tenantId });
 }
+
+export async function getSystemStatus() {
+  return callCommand<SystemStatus>("get_system_status");
+}
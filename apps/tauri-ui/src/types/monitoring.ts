@@ -56,3 +56,19 @@ export interface AuditLogFilter {
   protocol?: string;
   limit?: number;
 }
+
+export interface ServiceStatus {
+  reachable: boolean;
+  status: string;
+  http_status?: number;
+  counters: Record<string, number>;
+  error?: string;
+}
+
+export interface SystemStatus {
+  enforcer: ServiceStatus;
+  proxy: ServiceStatus;
+  audit_store: ServiceStatus;
+  quota_tracker: ServiceStatus;
+  bridge: ServiceStatus;
+}
@@ -33,6 +33,15 @@ export interface CompilePolicyRequest {
   metadata?: PolicyMetadata;
 }
 
+export type AttributeValueType = "string" | "number" | "string_list" | "object";
+
+export interface AttributeDescriptor {
+  category: "Subject" | "Resource" | "Environment" | "Action";
+  path: string;
+  value_type: AttributeValueType;
+  description: string;
+}
+
 export interface CompilePolicyResponse {
   success: boolean;
   rego?: string;
@@ -0,0 +1,136 @@
+//! Shared enrichment of ABAC policy input for Edge Policy Hub services.
+//!
+//! The HTTP proxy and the MQTT bridge build differently shaped inputs, but
+//! both send them to the enforcer as JSON with `subject`, `resource` and
+//! `environment` objects. Enrichers work on that JSON right before the
+//! policy query, so filling in defaults and cleaning up attributes behaves
+//! the same in both services.
+
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+
+/// A single enrichment step applied to the serialized policy input.
+pub trait InputEnricher: Send + Sync {
+    fn enrich(&self, input: &mut Value);
+}
+
+/// Sets `environment.region` when the input leaves it missing, null or empty.
+#[derive(Debug, Clone)]
+pub struct DefaultRegion(pub String);
+
+impl InputEnricher for DefaultRegion {
+    fn enrich(&self, input: &mut Value) {
+        let Some(environment) = object_field(input, "environment") else {
+            return;
+        };
+        let has_region = environment
+            .get("region")
+            .and_then(Value::as_str)
+            .is_some_and(|region| !region.trim().is_empty());
+        if !has_region {
+            environment.insert("region".to_string(), Value::String(self.0.clone()));
+        }
+    }
+}
+
+/// Trims `subject.roles`, drops empty entries and removes duplicates while
+/// keeping the first occurrence of each role.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NormalizeRoles;
+
+impl InputEnricher for NormalizeRoles {
+    fn enrich(&self, input: &mut Value) {
+        let Some(roles) = input
+            .get_mut("subject")
+            .and_then(|subject| subject.get_mut("roles"))
+            .and_then(Value::as_array_mut)
+        else {
+            return;
+        };
+
+        let mut normalized: Vec<Value> = Vec::with_capacity(roles.len());
+        for role in roles.iter() {
+            let Some(role) = role.as_str().map(str::trim) else {
+                continue;
+            };
+            if !role.is_empty() && !normalized.iter().any(|seen| seen == role) {
+                normalized.push(Value::String(role.to_string()));
+            }
+        }
+        *roles = normalized;
+    }
+}
+
+/// Ordered list of enrichers run before every policy query.
+#[derive(Clone, Default)]
+pub struct InputEnrichment {
+    enrichers: Vec<Arc<dyn InputEnricher>>,
+}
+
+impl InputEnrichment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Role normalization, plus the default region when one is configured.
+    pub fn standard(default_region: Option<String>) -> Self {
+        let enrichment = Self::new().with_enricher(NormalizeRoles);
+        match default_region.filter(|region| !region.trim().is_empty()) {
+            Some(region) => enrichment.with_enricher(DefaultRegion(region)),
+            None => enrichment,
+        }
+    }
+
+    /// Append an enricher; enrichers run in the order they were added.
+    pub fn with_enricher(mut self, enricher: impl InputEnricher + 'static) -> Self {
+        self.enrichers.push(Arc::new(enricher));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.enrichers.is_empty()
+    }
+
+    pub fn apply(&self, input: &mut Value) {
+        for enricher in &self.enrichers {
+            enricher.enrich(input);
+        }
+    }
+}
+
+/// Returns `input[key]` as an object, creating it when the key is absent.
+fn object_field<'a>(input: &'a mut Value, key: &str) -> Option<&'a mut Map<String, Value>> {
+    input
+        .as_object_mut()?
+        .entry(key)
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn default_region_fills_missing_region_only() {
+        let enrichment = InputEnrichment::standard(Some("eu-west-1".to_string()));
+
+        let mut missing = json!({ "environment": { "time": "now" } });
+        enrichment.apply(&mut missing);
+        assert_eq!(missing["environment"]["region"], "eu-west-1");
+
+        let mut present = json!({ "environment": { "region": "us-east-1" } });
+        enrichment.apply(&mut present);
+        assert_eq!(present["environment"]["region"], "us-east-1");
+    }
+
+    #[test]
+    fn roles_are_trimmed_and_deduplicated() {
+        let mut input = json!({ "subject": { "roles": [" admin", "viewer", "", "admin", 7] } });
+        InputEnrichment::standard(None).apply(&mut input);
+        assert_eq!(input["subject"]["roles"], json!(["admin", "viewer"]));
+        assert!(input.get("environment").is_none());
+    }
+}
@@ -43,9 +43,16 @@ allow read sensor_data if subject.tenant_id == "tenant-eu" and subject.device_lo
 
     match compile_policy(invalid_attribute_source, "tenant-eu", None) {
         Ok(_) => println!("Invalid attribute example unexpectedly compiled"),
-        Err(PolicyDslError::InvalidAttribute { path, reason }) => {
-            println!("Invalid attribute `{path}`: {reason}");
-        }
+        Err(PolicyDslError::InvalidAttribute {
+            path,
+            reason,
+            location,
+        }) => match location {
+            Some((line, column)) => {
+                println!("Invalid attribute `{path}` at {line}:{column}: {reason}")
+            }
+            None => println!("Invalid attribute `{path}`: {reason}"),
+        },
         Err(err) => println!("Unexpected error: {err}"),
     }
 }
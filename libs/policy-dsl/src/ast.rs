@@ -10,7 +10,25 @@ pub struct Policy {
     pub conditions: Vec<Condition>,
 }
 
+impl Policy {
+    /// For a wildcard resource type such as `sensor_data.*`, the prefix every
+    /// matching `input.resource.type` starts with (`sensor_data.`).
+    pub fn resource_type_prefix(&self) -> Option<&str> {
+        self.resource_type
+            .strip_suffix('*')
+            .filter(|prefix| prefix.ends_with('.'))
+    }
+}
+
 /// Describes an individual condition that must be satisfied for the policy.
+///
+/// Presence checks (`exists subject.device_id`) use [`Operator::Exists`] with a
+/// boolean literal on the right: `true` asserts the attribute is present and
+/// `false` (written `not exists ...`) asserts it is absent.
+///
+/// Helper calls (`geo.allowed(resource.origin, environment.destination)`) are
+/// stored as an equality between the [`Expression::HelperCall`] and a boolean
+/// literal, with `false` produced by the `not` form.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Condition {
     pub left: Expression,
@@ -61,6 +79,7 @@ pub enum Operator {
     GreaterThan,
     GreaterThanOrEqual,
     In,
+    Exists,
     And,
     Or,
     Not,
@@ -76,6 +95,7 @@ impl Operator {
             Operator::GreaterThan => ">",
             Operator::GreaterThanOrEqual => ">=",
             Operator::In => "in",
+            Operator::Exists => "exists",
             Operator::And => "and",
             Operator::Or => "or",
             Operator::Not => "not",
@@ -83,6 +103,27 @@ impl Operator {
     }
 }
 
+impl Condition {
+    /// Builds a presence (`exists`) or absence (`not exists`) check for `path`.
+    pub fn exists(path: AttributePath, present: bool) -> Self {
+        Self {
+            left: Expression::AttributePath(path),
+            operator: Operator::Exists,
+            right: Expression::BooleanLiteral(present),
+        }
+    }
+
+    /// Builds a condition asserting that a helper call succeeds (`expected ==
+    /// true`) or fails (`expected == false`, written `not geo.allowed(...)`).
+    pub fn helper(call: HelperCall, expected: bool) -> Self {
+        Self {
+            left: Expression::HelperCall(call),
+            operator: Operator::Equal,
+            right: Expression::BooleanLiteral(expected),
+        }
+    }
+}
+
 impl fmt::Display for Operator {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
@@ -97,13 +138,60 @@ pub enum Expression {
     NumberLiteral(f64),
     BooleanLiteral(bool),
     ListLiteral(Vec<Expression>),
+    HelperCall(HelperCall),
+}
+
+/// A call to a shared Rego helper library such as `geo.allowed(a, b)`.
+///
+/// `module` names a package under `lib/` in the rego bundles crate; the
+/// generated policy calls it as `data.lib.<module>`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HelperCall {
+    pub module: String,
+    pub function: String,
+    pub args: Vec<Expression>,
 }
 
 /// An attribute path such as `subject.tenant_id`.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+///
+/// Equality ignores `span`, so a parsed path compares equal to one built by hand.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttributePath {
     pub category: AttributeCategory,
     pub field: String,
+    /// Location in the policy source, populated by the parser.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+impl PartialEq for AttributePath {
+    fn eq(&self, other: &Self) -> bool {
+        self.category == other.category && self.field == other.field
+    }
+}
+
+impl Eq for AttributePath {}
+
+impl fmt::Display for AttributePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.category.as_str(), self.field)
+    }
+}
+
+/// A 1-based line/column position in the policy source together with the
+/// length of the spanned text.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+}
+
+impl Span {
+    /// Returns the `(line, column)` pair used by [`crate::PolicyDslError`] locations.
+    pub fn location(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
 }
 
 /// High level attribute categories supported by the DSL.
@@ -2,9 +2,45 @@ use crate::PolicyDslError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::Path;
 
+/// Hex digits of the content checksum kept in a content-derived bundle id.
+pub const CONTENT_BUNDLE_ID_HEX_LEN: usize = 32;
+
+/// Bundle id derived from what is deployed rather than generated at random:
+/// `bundle-` followed by the first 32 hex digits (128 bits) of a SHA-256 over
+/// the tenant id and Rego source.
+///
+/// Deploying identical Rego for the same tenant always yields the same id, so
+/// a deploy path can recognise a bundle it has already stored and skip it.
+pub fn content_bundle_id(tenant_id: &str, rego: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(tenant_id.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(rego.as_bytes());
+    let checksum = format!("{:x}", hasher.finalize());
+    format!("bundle-{}", &checksum[..CONTENT_BUNDLE_ID_HEX_LEN])
+}
+
+/// Maps a subject onto a rollout bucket in `0..100` using a SHA-256 digest
+/// of the tenant id and subject key.
+///
+/// A subject whose bucket is below a canary's rollout percentage is served by
+/// the canary bundle. The audit store and the enforcer both select bundles
+/// with this function, so they agree on which side of a split a subject is.
+pub fn rollout_bucket(tenant_id: &str, subject_key: &str) -> u8 {
+    let digest = Sha256::new()
+        .chain_update(tenant_id.as_bytes())
+        .chain_update([0u8])
+        .chain_update(subject_key.as_bytes())
+        .finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % 100) as u8
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BundleMetadata {
     pub revision: String,
@@ -137,6 +173,7 @@ impl BundleBuilder {
             return Err(PolicyDslError::ValidationError {
                 message: "bundle must contain at least one policy".into(),
                 attribute: None,
+                location: None,
             });
         }
 
@@ -1,25 +1,251 @@
 use crate::ast::{
-    AttributeCategory, AttributePath, Condition, Effect, Expression, Operator, Policy,
+    AttributeCategory, AttributePath, Condition, Effect, Expression, HelperCall, Operator, Policy,
 };
+use crate::{CompileOptions, PolicyDslError};
+use std::collections::BTreeSet;
+
+/// Package prefix used when callers do not pick one: `package tenants.<tenant_id>`.
+pub const DEFAULT_PACKAGE_PREFIX: &str = "tenants";
+
+/// Rule emitted for policies calling `geo.allowed`, holding the tenant's
+/// residency matrix or `{}` when its data has none.
+pub const RESIDENCY_MATRIX_RULE: &str = "geo_residency_matrix";
+
+/// Generates the tenant's Rego module for `policy`.
+///
+/// Every attribute path is emitted under the `input` document of its
+/// category (`input.subject.*`, `input.resource.*`, ...). Paths without a
+/// category are rejected rather than emitted as ambiguous Rego.
+pub fn generate_rego(policy: &Policy, tenant_id: &str) -> Result<String, PolicyDslError> {
+    generate_rego_with(policy, tenant_id, &CompileOptions::default())
+}
+
+/// Like [`generate_rego`], honouring the package prefix and deny guard
+/// settings in `options`.
+pub fn generate_rego_with(
+    policy: &Policy,
+    tenant_id: &str,
+    options: &CompileOptions,
+) -> Result<String, PolicyDslError> {
+    check_attribute_categories(policy)?;
 
-pub fn generate_rego(policy: &Policy, tenant_id: &str) -> String {
     let mut sections = Vec::new();
-    sections.push(generate_package_declaration(tenant_id));
+    sections.push(generate_prefixed_package_declaration(
+        &options.package_prefix,
+        tenant_id,
+    )?);
     sections.push(generate_import_statement());
     sections.push(generate_default_rule(&policy.effect));
+    sections.extend(generate_helper_rules(policy, tenant_id));
     sections.push(generate_allow_rule(policy, tenant_id));
+    if options.emit_deny_guard && policy.effect == Effect::Deny {
+        sections.push(generate_deny_guard());
+    }
+
+    Ok(format_rego(&sections.join("\n\n")))
+}
+
+/// Normalizes Rego layout so equivalent modules are byte-identical.
+///
+/// Lines are re-indented by four spaces per open brace or bracket, trailing
+/// whitespace is removed, runs of blank lines collapse to one, blank lines
+/// directly inside a block are dropped and the module ends with one newline.
+/// Braces inside string literals and comments do not affect indentation.
+pub fn format_rego(source: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+
+    for raw in source.lines() {
+        let line = raw.trim();
+        if line.is_empty() {
+            let keep = lines.last().is_some_and(|prev| {
+                !prev.is_empty() && !prev.ends_with('{') && !prev.ends_with('[')
+            });
+            if keep {
+                lines.push(String::new());
+            }
+            continue;
+        }
+
+        let (opened, closed_first) = bracket_delta(line);
+        if closed_first > 0 && lines.last().is_some_and(|prev| prev.is_empty()) {
+            lines.pop();
+        }
+
+        let indent = depth.saturating_sub(closed_first);
+        lines.push(format!("{}{}", "    ".repeat(indent), line));
+        depth = (depth as isize + opened).max(0) as usize;
+    }
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
 
-    sections.join("\n\n")
+    let mut formatted = lines.join("\n");
+    formatted.push('\n');
+    formatted
+}
+
+/// Returns the net change in nesting for `line` and how many closing
+/// brackets it starts with, ignoring string literals and comments.
+fn bracket_delta(line: &str) -> (isize, usize) {
+    let mut delta = 0isize;
+    let mut leading_closes = 0usize;
+    let mut seen_other = false;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for ch in line.chars() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => {
+                in_string = true;
+                seen_other = true;
+            }
+            '#' => break,
+            '{' | '[' => {
+                delta += 1;
+                seen_other = true;
+            }
+            '}' | ']' => {
+                delta -= 1;
+                if !seen_other {
+                    leading_closes += 1;
+                }
+            }
+            c if c.is_whitespace() => {}
+            _ => seen_other = true,
+        }
+    }
+
+    (delta, leading_closes)
+}
+
+/// Ensures every attribute path in `policy` names a category and a field.
+pub fn check_attribute_categories(policy: &Policy) -> Result<(), PolicyDslError> {
+    for condition in &policy.conditions {
+        check_expression_categories(&condition.left)?;
+        check_expression_categories(&condition.right)?;
+    }
+    Ok(())
+}
+
+fn check_expression_categories(expression: &Expression) -> Result<(), PolicyDslError> {
+    match expression {
+        Expression::AttributePath(path) => {
+            let uncategorized = matches!(
+                &path.category,
+                AttributeCategory::Custom(category) if category.trim().is_empty()
+            );
+            if uncategorized || path.field.trim().is_empty() {
+                return Err(PolicyDslError::InvalidAttribute {
+                    path: format!("{}.{}", path.category.as_str(), path.field)
+                        .trim_matches('.')
+                        .to_string(),
+                    reason: "attribute paths must be written as `<category>.<field>`, e.g. `subject.tenant_id`"
+                        .into(),
+                    location: path.span.map(|span| span.location()),
+                });
+            }
+            Ok(())
+        }
+        Expression::ListLiteral(elements) => {
+            elements.iter().try_for_each(check_expression_categories)
+        }
+        Expression::HelperCall(call) => call.args.iter().try_for_each(check_expression_categories),
+        _ => Ok(()),
+    }
 }
 
 pub fn generate_package_declaration(tenant_id: &str) -> String {
-    format!("package tenants.{tenant_id}")
+    format!("package {DEFAULT_PACKAGE_PREFIX}.{tenant_id}")
+}
+
+/// Builds `package <prefix>.<tenant_id>`. A leading `data.` is dropped since
+/// Rego package paths are implicitly rooted at `data`.
+pub fn generate_prefixed_package_declaration(
+    package_prefix: &str,
+    tenant_id: &str,
+) -> Result<String, PolicyDslError> {
+    let prefix = package_prefix.trim();
+    let prefix = prefix.strip_prefix("data.").unwrap_or(prefix);
+    let valid = !prefix.is_empty()
+        && prefix.split('.').all(|segment| {
+            segment
+                .chars()
+                .next()
+                .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if !valid {
+        return Err(PolicyDslError::ValidationError {
+            message: format!(
+                "package prefix `{package_prefix}` must be dot-separated identifiers, e.g. `policies.prod`"
+            ),
+            attribute: None,
+            location: None,
+        });
+    }
+
+    Ok(format!("package {prefix}.{tenant_id}"))
 }
 
 pub fn generate_import_statement() -> String {
     "import rego.v1".to_string()
 }
 
+/// Returns the supporting rules the policy's helper calls need.
+///
+/// Helpers are called by their full `data.lib.<module>` path rather than
+/// through an import, since the enforcer's Rego engine does not resolve
+/// functions through import aliases. `geo.allowed` gets the tenant's
+/// residency matrix through [`RESIDENCY_MATRIX_RULE`], read from the fixed
+/// `data.tenants.<tenant_id>` path the enforcer loads `data.json` under.
+pub fn generate_helper_rules(policy: &Policy, tenant_id: &str) -> Vec<String> {
+    let mut calls = BTreeSet::new();
+    for condition in &policy.conditions {
+        collect_helper_calls(&condition.left, &mut calls);
+        collect_helper_calls(&condition.right, &mut calls);
+    }
+
+    let mut rules = Vec::new();
+    if calls.contains(&("geo", "allowed")) {
+        rules.push(format!("default {RESIDENCY_MATRIX_RULE} := {{}}"));
+        rules.push(format!(
+            "{RESIDENCY_MATRIX_RULE} := data.tenants.{tenant_id}.residency_matrix"
+        ));
+    }
+    rules
+}
+
+fn collect_helper_calls<'a>(expression: &'a Expression, calls: &mut BTreeSet<(&'a str, &'a str)>) {
+    match expression {
+        Expression::HelperCall(call) => {
+            calls.insert((call.module.as_str(), call.function.as_str()));
+            for arg in &call.args {
+                collect_helper_calls(arg, calls);
+            }
+        }
+        Expression::ListLiteral(elements) => {
+            for element in elements {
+                collect_helper_calls(element, calls);
+            }
+        }
+        _ => {}
+    }
+}
+
 pub fn generate_default_rule(effect: &Effect) -> String {
     match effect {
         Effect::Allow => "default allow := false\ndefault deny := true".to_string(),
@@ -44,10 +270,20 @@ pub fn generate_allow_rule(policy: &Policy, tenant_id: &str) -> String {
     lines.join("\n")
 }
 
+/// Makes a matching `deny` rule override the permissive `allow` default.
+pub fn generate_deny_guard() -> String {
+    "allow := false if deny".to_string()
+}
+
 pub fn generate_conditions(policy: &Policy, tenant_id: &str) -> Vec<String> {
     let mut conditions = Vec::new();
-    let existing_conditions: Vec<String> =
+    // A rule body is a conjunction, so its order does not change the decision.
+    // Sorting makes policies that differ only in condition order emit the
+    // same Rego, keeping bundle checksums reproducible.
+    let mut existing_conditions: Vec<String> =
         policy.conditions.iter().map(generate_condition).collect();
+    existing_conditions.sort();
+    existing_conditions.dedup();
 
     if !has_tenant_guard(policy, tenant_id) {
         conditions.push(format!(
@@ -67,10 +303,16 @@ pub fn generate_conditions(policy: &Policy, tenant_id: &str) -> Vec<String> {
         conditions.push(action_guard);
     }
 
-    let resource_guard = format!(
-        "input.resource.type == {}",
-        generate_expression(&Expression::StringLiteral(policy.resource_type.clone()))
-    );
+    let resource_guard = match policy.resource_type_prefix() {
+        Some(prefix) => format!(
+            "startswith(input.resource.type, {})",
+            generate_expression(&Expression::StringLiteral(prefix.to_string()))
+        ),
+        None => format!(
+            "input.resource.type == {}",
+            generate_expression(&Expression::StringLiteral(policy.resource_type.clone()))
+        ),
+    };
     if !existing_conditions.iter().any(|c| c == &resource_guard) {
         conditions.push(resource_guard);
     }
@@ -81,6 +323,25 @@ pub fn generate_conditions(policy: &Policy, tenant_id: &str) -> Vec<String> {
 }
 
 pub fn generate_condition(condition: &Condition) -> String {
+    if condition.operator == Operator::Exists {
+        let path = generate_expression(&condition.left);
+        return match condition.right {
+            Expression::BooleanLiteral(false) => format!("not {path}"),
+            _ => path,
+        };
+    }
+
+    if let (Expression::HelperCall(_), Operator::Equal, Expression::BooleanLiteral(expected)) =
+        (&condition.left, &condition.operator, &condition.right)
+    {
+        let call = generate_expression(&condition.left);
+        return if *expected {
+            call
+        } else {
+            format!("not {call}")
+        };
+    }
+
     format!(
         "{} {} {}",
         generate_expression(&condition.left),
@@ -98,6 +359,7 @@ pub fn generate_operator(operator: &Operator) -> String {
         Operator::GreaterThan => ">",
         Operator::GreaterThanOrEqual => ">=",
         Operator::In => "in",
+        Operator::Exists => "exists",
         Operator::And => "and",
         Operator::Or => "or",
         Operator::Not => "not",
@@ -115,19 +377,24 @@ pub fn generate_expression(expression: &Expression) -> String {
             let rendered: Vec<String> = elements.iter().map(generate_expression).collect();
             format!("[{}]", rendered.join(", "))
         }
+        Expression::HelperCall(call) => generate_helper_call(call),
     }
 }
 
-fn generate_attribute_path(path: &AttributePath) -> String {
-    match &path.category {
-        AttributeCategory::Subject => format!("input.subject.{}", path.field),
-        AttributeCategory::Resource => format!("input.resource.{}", path.field),
-        AttributeCategory::Environment => format!("input.environment.{}", path.field),
-        AttributeCategory::Action => format!("input.action.{}", path.field),
-        AttributeCategory::Custom(category) => {
-            format!("input.{}.{}", category, path.field)
+fn generate_helper_call(call: &HelperCall) -> String {
+    let mut args: Vec<String> = call.args.iter().map(generate_expression).collect();
+    let function = match (call.module.as_str(), call.function.as_str()) {
+        ("geo", "allowed") => {
+            args.insert(0, RESIDENCY_MATRIX_RULE.to_string());
+            "allowed_by"
         }
-    }
+        (_, function) => function,
+    };
+    format!("data.lib.{}.{}({})", call.module, function, args.join(", "))
+}
+
+fn generate_attribute_path(path: &AttributePath) -> String {
+    format!("input.{}.{}", path.category.as_str(), path.field)
 }
 
 fn has_tenant_guard(policy: &Policy, tenant_id: &str) -> bool {
@@ -138,6 +405,7 @@ fn has_tenant_guard(policy: &Policy, tenant_id: &str) -> bool {
                 Expression::AttributePath(AttributePath {
                     category: AttributeCategory::Subject,
                     field,
+                    ..
                 }),
                 Operator::Equal,
                 Expression::StringLiteral(value),
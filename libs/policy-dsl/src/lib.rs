@@ -12,14 +12,18 @@ use thiserror::Error;
 pub mod ast;
 pub mod bundle;
 pub mod codegen;
+pub mod lint;
 pub mod parser;
 pub mod validator;
 
 // Re-export key types
 pub use ast::{
-    Action, AttributeCategory, AttributePath, Condition, Effect, Expression, Operator, Policy,
+    Action, AttributeCategory, AttributePath, Condition, Effect, Expression, HelperCall, Operator,
+    Policy, Span,
 };
-pub use bundle::{BundleBuilder, BundleMetadata, PolicyBundle};
+pub use bundle::{content_bundle_id, rollout_bucket, BundleBuilder, BundleMetadata, PolicyBundle};
+pub use lint::{detect_conflicts, ConflictWarning, LintWarning};
+pub use validator::{attribute_usage, available_attributes, AttributeDescriptor, AttributeType};
 
 /// Policy metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +45,57 @@ impl Default for PolicyMetadata {
     }
 }
 
+/// Options controlling how a policy is compiled.
+///
+/// Built from [`Default`] with the `with_*` methods; the defaults reproduce
+/// what [`compile_policy`] has always emitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompileOptions {
+    /// Metadata attached to the compiled policy; defaults are filled in when unset.
+    pub metadata: Option<PolicyMetadata>,
+    /// Dotted package path the tenant id is appended to, e.g. `policies.prod`
+    /// for `package policies.prod.<tenant_id>`.
+    pub package_prefix: String,
+    /// For `deny` policies, also emit `allow := false if deny` so callers that
+    /// only query `allow` honour the denial.
+    pub emit_deny_guard: bool,
+    /// Run the linter and report its warnings.
+    pub lint: bool,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            metadata: None,
+            package_prefix: codegen::DEFAULT_PACKAGE_PREFIX.to_string(),
+            emit_deny_guard: false,
+            lint: true,
+        }
+    }
+}
+
+impl CompileOptions {
+    pub fn with_metadata(mut self, metadata: PolicyMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    pub fn with_package_prefix(mut self, package_prefix: impl Into<String>) -> Self {
+        self.package_prefix = package_prefix.into();
+        self
+    }
+
+    pub fn with_deny_guard(mut self, emit_deny_guard: bool) -> Self {
+        self.emit_deny_guard = emit_deny_guard;
+        self
+    }
+
+    pub fn with_lint(mut self, lint: bool) -> Self {
+        self.lint = lint;
+        self
+    }
+}
+
 /// Describes the compiled representation of a policy artifact.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompiledPolicy {
@@ -54,6 +109,16 @@ pub struct CompiledPolicy {
     pub metadata: PolicyMetadata,
 }
 
+/// Compilation output together with the parsed policy and its lint warnings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetailedCompilation {
+    pub compiled: CompiledPolicy,
+    /// Policy AST the Rego was generated from.
+    pub ast: Policy,
+    /// Non-fatal findings; empty when the policy has nothing to flag.
+    pub warnings: Vec<LintWarning>,
+}
+
 /// Errors emitted by the policy compiler.
 #[derive(Debug, Error)]
 pub enum PolicyDslError {
@@ -69,11 +134,16 @@ pub enum PolicyDslError {
     ValidationError {
         message: String,
         attribute: Option<String>,
+        location: Option<(usize, usize)>,
     },
 
     /// Invalid attribute path
     #[error("Invalid attribute: {path} - {reason}")]
-    InvalidAttribute { path: String, reason: String },
+    InvalidAttribute {
+        path: String,
+        reason: String,
+        location: Option<(usize, usize)>,
+    },
 
     /// Unsupported operator
     #[error("Unsupported operator: {operator}")]
@@ -114,6 +184,69 @@ pub fn compile_policy(
     tenant_id: &str,
     metadata: Option<PolicyMetadata>,
 ) -> Result<CompiledPolicy, PolicyDslError> {
+    compile_policy_with(
+        source,
+        tenant_id,
+        CompileOptions {
+            metadata,
+            ..CompileOptions::default()
+        },
+    )
+}
+
+/// Compiles `source` into a [`CompiledPolicy`] as configured by `options`.
+///
+/// # Example
+/// ```
+/// use edge_policy_dsl::{compile_policy_with, CompileOptions};
+///
+/// let dsl = r#"allow read sensor_data if subject.tenant_id == "tenant-a""#;
+/// let options = CompileOptions::default().with_package_prefix("policies.prod");
+/// let compiled = compile_policy_with(dsl, "tenant-a", options).unwrap();
+/// assert!(compiled.rego.starts_with("package policies.prod.tenant-a"));
+/// ```
+pub fn compile_policy_with(
+    source: &str,
+    tenant_id: &str,
+    options: CompileOptions,
+) -> Result<CompiledPolicy, PolicyDslError> {
+    compile_policy_detailed_with(source, tenant_id, options).map(|detailed| detailed.compiled)
+}
+
+/// Compiles `source` like [`compile_policy`], also returning the parsed AST
+/// and lint warnings so tooling does not have to parse the source again.
+///
+/// # Example
+/// ```
+/// use edge_policy_dsl::compile_policy_detailed;
+///
+/// let dsl = r#"allow read sensor_data if subject.tenant_id == "tenant-a""#;
+/// let detailed = compile_policy_detailed(dsl, "tenant-a", None).unwrap();
+/// assert_eq!(detailed.ast.conditions.len(), 1);
+/// assert!(detailed.warnings.is_empty());
+/// ```
+pub fn compile_policy_detailed(
+    source: &str,
+    tenant_id: &str,
+    metadata: Option<PolicyMetadata>,
+) -> Result<DetailedCompilation, PolicyDslError> {
+    compile_policy_detailed_with(
+        source,
+        tenant_id,
+        CompileOptions {
+            metadata,
+            ..CompileOptions::default()
+        },
+    )
+}
+
+/// Compiles `source` like [`compile_policy_with`], also returning the parsed
+/// AST and lint warnings.
+pub fn compile_policy_detailed_with(
+    source: &str,
+    tenant_id: &str,
+    options: CompileOptions,
+) -> Result<DetailedCompilation, PolicyDslError> {
     if tenant_id.is_empty() {
         return Err(PolicyDslError::TenantIdRequired);
     }
@@ -125,21 +258,31 @@ pub fn compile_policy(
     validator::validate_policy(&policy)?;
 
     // Generate Rego code
-    let rego = codegen::generate_rego(&policy, tenant_id);
+    let rego = codegen::generate_rego_with(&policy, tenant_id, &options)?;
 
     // Create compiled policy
-    let metadata = metadata.unwrap_or_default();
+    let metadata = options.metadata.unwrap_or_default();
     let name = format!(
         "{}-{}",
         policy.action.as_str(),
-        policy.resource_type.replace(' ', "_")
+        policy.resource_type.replace(' ', "_").replace('*', "any")
     );
 
-    Ok(CompiledPolicy {
-        name,
-        tenant_id: tenant_id.to_string(),
-        rego,
-        metadata,
+    let warnings = if options.lint {
+        lint::lint_policy(&policy)
+    } else {
+        Vec::new()
+    };
+
+    Ok(DetailedCompilation {
+        compiled: CompiledPolicy {
+            name,
+            tenant_id: tenant_id.to_string(),
+            rego,
+            metadata,
+        },
+        ast: policy,
+        warnings,
     })
 }
 
@@ -191,6 +334,108 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_compile_detailed_returns_parsed_ast_and_lints() {
+        let source = r#"allow read sensor_data if subject.custom_team == "ops" and subject.custom_team == "ops""#;
+        let detailed = compile_policy_detailed(source, "tenant-a", None).unwrap();
+
+        assert_eq!(detailed.ast, parser::parse_policy(source).unwrap());
+        assert_eq!(
+            detailed.compiled.rego,
+            compile_policy(source, "tenant-a", None).unwrap().rego
+        );
+
+        let codes: Vec<&str> = detailed
+            .warnings
+            .iter()
+            .map(|warning| warning.code.as_str())
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                "custom_attribute",
+                "custom_attribute",
+                "duplicate_condition"
+            ]
+        );
+        assert_eq!(
+            detailed.warnings[2].attribute.as_deref(),
+            Some("subject.custom_team")
+        );
+    }
+
+    #[test]
+    fn test_compile_package_prefix() {
+        let source = r#"allow read sensor_data if subject.tenant_id == "tenant-a""#;
+
+        let default = compile_policy(source, "tenant-a", None).unwrap();
+        assert_eq!(
+            default.rego.lines().next(),
+            Some("package tenants.tenant-a")
+        );
+
+        let options = CompileOptions::default().with_package_prefix("data.policies.prod");
+        let custom = compile_policy_with(source, "tenant-a", options).unwrap();
+        assert_eq!(
+            custom.rego.lines().next(),
+            Some("package policies.prod.tenant-a")
+        );
+        assert_eq!(
+            custom.rego.lines().skip(1).collect::<Vec<_>>(),
+            default.rego.lines().skip(1).collect::<Vec<_>>()
+        );
+
+        let invalid = CompileOptions::default().with_package_prefix("policies..prod");
+        assert!(matches!(
+            compile_policy_with(source, "tenant-a", invalid),
+            Err(PolicyDslError::ValidationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compile_options_apply_independently() {
+        let source = r#"deny write sensor_data if subject.custom_team == "ops""#;
+
+        let legacy = compile_policy(source, "tenant-a", None).unwrap();
+        let defaults = compile_policy_with(source, "tenant-a", CompileOptions::default()).unwrap();
+        assert_eq!(legacy.rego, defaults.rego);
+        assert!(!legacy.rego.contains("allow := false if deny"));
+
+        let guarded = compile_policy_with(
+            source,
+            "tenant-a",
+            CompileOptions::default().with_deny_guard(true),
+        )
+        .unwrap();
+        assert!(guarded.rego.contains("allow := false if deny"));
+        assert!(guarded.rego.starts_with("package tenants.tenant-a"));
+
+        let linted =
+            compile_policy_detailed_with(source, "tenant-a", CompileOptions::default()).unwrap();
+        assert!(!linted.warnings.is_empty());
+        let unlinted = compile_policy_detailed_with(
+            source,
+            "tenant-a",
+            CompileOptions::default().with_lint(false),
+        )
+        .unwrap();
+        assert!(unlinted.warnings.is_empty());
+        assert_eq!(unlinted.compiled.rego, legacy.rego);
+
+        let metadata = PolicyMetadata {
+            version: "3.1.0".to_string(),
+            ..PolicyMetadata::default()
+        };
+        let with_metadata = compile_policy_with(
+            source,
+            "tenant-a",
+            CompileOptions::default().with_metadata(metadata),
+        )
+        .unwrap();
+        assert_eq!(with_metadata.metadata.version, "3.1.0");
+        assert_eq!(with_metadata.rego, legacy.rego);
+    }
+
     #[test]
     fn test_compile_invalid_syntax() {
         let source = r#"invalid syntax here"#;
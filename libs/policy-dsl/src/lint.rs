@@ -0,0 +1,184 @@
+//! Non-fatal checks on a validated policy.
+//!
+//! Lints flag constructs that compile but are probably not what the author
+//! meant. They never block compilation. [`detect_conflicts`] applies the same
+//! idea across a set of policies.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{
+    AttributeCategory, AttributePath, Condition, Effect, Expression, Operator, Policy,
+};
+
+/// A non-fatal finding about a policy.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LintWarning {
+    /// Stable identifier of the lint, e.g. `duplicate_condition`.
+    pub code: String,
+    pub message: String,
+    /// Attribute the warning refers to, as `category.field`.
+    pub attribute: Option<String>,
+    /// `(line, column)` in the policy source when known.
+    pub location: Option<(usize, usize)>,
+}
+
+/// An allow policy and a deny policy that can both match the same request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConflictWarning {
+    /// Index of the allow policy in the slice given to [`detect_conflicts`].
+    pub allow_index: usize,
+    /// Index of the deny policy in the same slice.
+    pub deny_index: usize,
+    pub message: String,
+}
+
+/// Runs every lint against `policy`, returning warnings in condition order.
+pub fn lint_policy(policy: &Policy) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+
+    for (index, condition) in policy.conditions.iter().enumerate() {
+        if let Expression::AttributePath(path) = &condition.left {
+            if is_custom_field(path) {
+                warnings.push(LintWarning {
+                    code: "custom_attribute".into(),
+                    message: format!(
+                        "`{}` is not in the approved schema and is treated as a custom attribute",
+                        attribute_name(path)
+                    ),
+                    attribute: Some(attribute_name(path)),
+                    location: path.span.map(|span| span.location()),
+                });
+            }
+        }
+
+        if condition.operator == Operator::In
+            && matches!(&condition.right, Expression::ListLiteral(items) if items.is_empty())
+        {
+            warnings.push(condition_warning(
+                condition,
+                "empty_list",
+                "`in []` never matches, so the policy can never apply".into(),
+            ));
+        }
+
+        if policy.conditions[..index].contains(condition) {
+            warnings.push(condition_warning(
+                condition,
+                "duplicate_condition",
+                "condition repeats an earlier condition and has no effect".into(),
+            ));
+        }
+    }
+
+    warnings
+}
+
+fn is_custom_field(path: &AttributePath) -> bool {
+    !matches!(path.category, AttributeCategory::Custom(_)) && path.field.starts_with("custom_")
+}
+
+fn attribute_name(path: &AttributePath) -> String {
+    format!("{}.{}", path.category.as_str(), path.field)
+}
+
+fn condition_warning(condition: &Condition, code: &str, message: String) -> LintWarning {
+    let path = match &condition.left {
+        Expression::AttributePath(path) => Some(path),
+        _ => None,
+    };
+
+    LintWarning {
+        code: code.into(),
+        message,
+        attribute: path.map(attribute_name),
+        location: path.and_then(|path| path.span).map(|span| span.location()),
+    }
+}
+
+/// Finds allow/deny pairs whose action, resource type and conditions can all
+/// match the same input, ordered by allow index and then deny index.
+///
+/// The check is a heuristic. Two policies are taken to be disjoint only when
+/// they name different actions or resource types, or when both compare one
+/// attribute against literals that cannot hold together: `== "a"` and
+/// `== "b"`, `== "a"` and `!= "a"`, `in` lists with no value in common, or
+/// `exists` and `not exists`. Ranges and helper calls are assumed to overlap.
+pub fn detect_conflicts(policies: &[Policy]) -> Vec<ConflictWarning> {
+    let with_effect = |effect: Effect| {
+        policies
+            .iter()
+            .enumerate()
+            .filter(move |(_, policy)| policy.effect == effect)
+    };
+
+    let mut conflicts = Vec::new();
+    for (allow_index, allow) in with_effect(Effect::Allow) {
+        for (deny_index, deny) in with_effect(Effect::Deny) {
+            if allow.action == deny.action
+                && resource_types_overlap(allow, deny)
+                && !allow
+                    .conditions
+                    .iter()
+                    .any(|a| deny.conditions.iter().any(|b| conditions_exclude(a, b)))
+            {
+                conflicts.push(ConflictWarning {
+                    allow_index,
+                    deny_index,
+                    message: format!(
+                        "allow and deny policies for `{}` on `{}` can match the same request",
+                        allow.action.as_str(),
+                        allow.resource_type
+                    ),
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+fn resource_types_overlap(a: &Policy, b: &Policy) -> bool {
+    let covers = |policy: &Policy, resource_type: &str| match policy.resource_type_prefix() {
+        Some(prefix) => resource_type.starts_with(prefix),
+        None => policy.resource_type == resource_type,
+    };
+    covers(a, &b.resource_type) || covers(b, &a.resource_type)
+}
+
+/// Whether no input can satisfy both conditions.
+fn conditions_exclude(a: &Condition, b: &Condition) -> bool {
+    match (&a.left, &b.left) {
+        (Expression::AttributePath(left_a), Expression::AttributePath(left_b))
+            if left_a == left_b => {}
+        _ => return false,
+    }
+
+    if let (Some(values_a), Some(values_b)) = (pinned_values(a), pinned_values(b)) {
+        return !values_a.iter().any(|value| values_b.contains(value));
+    }
+
+    match (&a.operator, &b.operator) {
+        (Operator::Exists, Operator::Exists) => a.right != b.right,
+        (Operator::Equal, Operator::NotEqual) | (Operator::NotEqual, Operator::Equal) => {
+            is_literal(&a.right) && a.right == b.right
+        }
+        _ => false,
+    }
+}
+
+/// Literal values an `==` or `in` condition restricts its attribute to.
+fn pinned_values(condition: &Condition) -> Option<&[Expression]> {
+    match (&condition.operator, &condition.right) {
+        (Operator::Equal, value) if is_literal(value) => Some(std::slice::from_ref(value)),
+        (Operator::In, Expression::ListLiteral(items)) if items.iter().all(is_literal) => {
+            Some(items)
+        }
+        _ => None,
+    }
+}
+
+fn is_literal(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::StringLiteral(_) | Expression::NumberLiteral(_) | Expression::BooleanLiteral(_)
+    )
+}
@@ -1,68 +1,165 @@
 use crate::{
     ast::{
-        Action, AttributeCategory, AttributePath, Condition, Effect, Expression, Operator, Policy,
+        Action, AttributeCategory, AttributePath, Condition, Effect, Expression, HelperCall,
+        Operator, Policy, Span,
     },
     PolicyDslError,
 };
 use nom::{
     branch::alt,
     bytes::complete::{is_not, tag, tag_no_case, take_while, take_while1},
-    character::complete::{char, digit1, multispace0, one_of},
-    combinator::{cut, map, map_res, opt, recognize},
+    character::complete::{char, digit1, multispace0, multispace1, one_of},
+    combinator::{cut, map, map_res, not, opt, peek, recognize, verify},
     error::{convert_error, VerboseError},
     multi::{many0, separated_list0},
-    sequence::{delimited, preceded, separated_pair, tuple},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
     IResult,
 };
+use std::collections::HashMap;
 use std::num::ParseFloatError;
 
 type Res<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
 
+/// Words with a meaning of their own in conditions, which aliases may not use.
+const RESERVED_WORDS: &[&str] = &[
+    "allow", "and", "define", "defined", "deny", "exists", "false", "if", "in", "not", "or", "true",
+];
+
+/// Conditions each `define`d alias expands to.
+type Aliases<'a> = HashMap<&'a str, Vec<Condition>>;
+
+/// A parsed condition, or a reference to an alias expanded once parsing ends.
+enum Clause<'a> {
+    Condition(Condition),
+    Alias { name: &'a str, offset: usize },
+}
+
+/// Parses a single policy, optionally preceded by `define <name> = <condition>`
+/// aliases that its conditions may reference by name.
+///
+/// Aliases are expanded at parse time. Attribute spans inside an expanded
+/// alias point at its definition, so errors there are reported where the
+/// alias was written.
 pub fn parse_policy(source: &str) -> Result<Policy, PolicyDslError> {
     let cleaned = strip_comments(source);
-    let input = cleaned.trim();
-    if input.is_empty() {
+    // Only trailing whitespace is trimmed so that attribute spans and error
+    // locations line up with the original source.
+    let input = cleaned.trim_end();
+    if input.trim_start().is_empty() {
         return Err(PolicyDslError::ParseError {
             message: "policy source is empty".into(),
             location: None,
         });
     }
 
-    match policy_parser(input) {
-        Ok((remaining, mut policy)) => {
-            let remaining = remaining.trim();
-            if !remaining.is_empty() {
-                let offset = input.len() - remaining.len();
-                return Err(PolicyDslError::ParseError {
-                    message: format!("unexpected trailing input: {remaining:?}"),
-                    location: compute_location(input, offset),
-                });
-            }
-
-            // Ensure conditions vector exists even when missing `if`.
-            if policy.conditions.is_empty() {
-                policy.conditions = Vec::new();
-            }
-
-            Ok(policy)
-        }
-        Err(err) => {
-            let (message, location) = match err {
-                nom::Err::Error(e) | nom::Err::Failure(e) => {
-                    let message = convert_error(input, e.clone());
-                    let location = e.errors.first().and_then(|(fragment, _)| {
-                        let offset = input.len().saturating_sub(fragment.len());
-                        compute_location(input, offset)
-                    });
-                    (message, location)
-                }
-                nom::Err::Incomplete(_) => ("incomplete input".to_string(), None),
-            };
-            Err(PolicyDslError::ParseError { message, location })
+    let mut aliases = Aliases::new();
+    let rest = definitions_in(input, input, &mut aliases)?;
+    if rest.trim().is_empty() {
+        return Err(PolicyDslError::ParseError {
+            message: "policy source defines aliases but no rule".into(),
+            location: compute_location(input, input.len()),
+        });
+    }
+
+    let (remaining, (policy, clauses)) =
+        rule_in(input, rest).map_err(|err| parse_error(input, err))?;
+    let remaining = remaining.trim();
+    if !remaining.is_empty() {
+        let offset = input.len() - remaining.len();
+        return Err(PolicyDslError::ParseError {
+            message: format!("unexpected trailing input: {remaining:?}"),
+            location: compute_location(input, offset),
+        });
+    }
+
+    Ok(Policy {
+        conditions: expand_clauses(input, clauses, &aliases)?,
+        ..policy
+    })
+}
+
+/// Parses a sequence of policies sharing the aliases `define`d among them.
+///
+/// An alias may be referenced by any rule, or any later alias, after its
+/// definition.
+pub fn parse_policies(source: &str) -> Result<Vec<Policy>, PolicyDslError> {
+    let cleaned = strip_comments(source);
+    let input = cleaned.trim_end();
+
+    let mut aliases = Aliases::new();
+    let mut policies = Vec::new();
+    let mut rest = input;
+    loop {
+        rest = definitions_in(input, rest, &mut aliases)?;
+        if rest.trim().is_empty() {
+            break;
         }
+        let (remaining, (policy, clauses)) =
+            rule_in(input, rest).map_err(|err| parse_error(input, err))?;
+        policies.push(Policy {
+            conditions: expand_clauses(input, clauses, &aliases)?,
+            ..policy
+        });
+        rest = remaining;
     }
+
+    if policies.is_empty() {
+        return Err(PolicyDslError::ParseError {
+            message: "policy source contains no rules".into(),
+            location: None,
+        });
+    }
+    Ok(policies)
+}
+
+fn parse_error(input: &str, err: nom::Err<VerboseError<&str>>) -> PolicyDslError {
+    let (message, location) = match err {
+        nom::Err::Error(e) | nom::Err::Failure(e) => {
+            let message = convert_error(input, e.clone());
+            let location = e.errors.first().and_then(|(fragment, _)| {
+                let offset = input.len().saturating_sub(fragment.len());
+                compute_location(input, offset)
+            });
+            (message, location)
+        }
+        nom::Err::Incomplete(_) => ("incomplete input".to_string(), None),
+    };
+    PolicyDslError::ParseError { message, location }
 }
 
+/// Parses any `define <name> = <condition>` statements at the start of
+/// `input`, adding them to `aliases`, and returns the rest.
+fn definitions_in<'a>(
+    base: &'a str,
+    mut input: &'a str,
+    aliases: &mut Aliases<'a>,
+) -> Result<&'a str, PolicyDslError> {
+    loop {
+        if define_keyword(input).is_err() {
+            return Ok(input);
+        }
+
+        let (rest, (name, offset, clauses)) =
+            define_in(base, input).map_err(|err| parse_error(base, err))?;
+        let location = compute_location(base, offset);
+        if is_reserved(name) {
+            return Err(PolicyDslError::ParseError {
+                message: format!("`{name}` is a reserved word and cannot name an alias"),
+                location,
+            });
+        }
+        if aliases.contains_key(name) {
+            return Err(PolicyDslError::ParseError {
+                message: format!("alias `{name}` is already defined"),
+                location,
+            });
+        }
+
+        let conditions = expand_clauses(base, clauses, aliases)?;
+        aliases.insert(name, conditions);
+        input = rest;
+    }
+}
 fn strip_comments(source: &str) -> String {
     let mut result = Vec::new();
     for line in source.lines() {
@@ -77,11 +174,17 @@ fn strip_comments(source: &str) -> String {
     result.join("\n")
 }
 
+/// Parses a complete policy. Attribute spans are reported relative to `input`.
 pub fn policy_parser(input: &str) -> Res<'_, Policy> {
+    policy_in(input, input)
+}
+
+fn policy_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Policy> {
     let (input, effect) = ws(effect_parser)(input)?;
     let (input, action) = ws(action_parser)(input)?;
-    let (input, resource_type) = ws(identifier)(input)?;
-    let (input, conditions) = opt(preceded(ws(tag_no_case("if")), conditions_parser))(input)?;
+    let (input, resource_type) = ws(resource_type_parser)(input)?;
+    let (input, conditions) =
+        opt(preceded(ws(tag_no_case("if")), |i| conditions_in(base, i)))(input)?;
 
     Ok((
         input,
@@ -94,6 +197,43 @@ pub fn policy_parser(input: &str) -> Res<'_, Policy> {
     ))
 }
 
+fn define_keyword(input: &str) -> Res<'_, &str> {
+    preceded(multispace0, terminated(tag_no_case("define"), multispace1))(input)
+}
+
+/// Parses `define <name> = <conditions>`, returning the alias name, its
+/// offset in `base` and its conditions.
+fn define_in<'a>(base: &'a str, input: &'a str) -> Res<'a, (&'a str, usize, Vec<Clause<'a>>)> {
+    let (input, _) = define_keyword(input)?;
+    let offset = base.len() - input.len();
+    let (input, name) = cut(identifier)(input)?;
+    let (input, _) = cut(ws(char('=')))(input)?;
+    let (input, clauses) = cut(|i| clauses_in(base, i))(input)?;
+
+    Ok((input, (name, offset, clauses)))
+}
+
+/// Parses a policy whose conditions may reference aliases.
+fn rule_in<'a>(base: &'a str, input: &'a str) -> Res<'a, (Policy, Vec<Clause<'a>>)> {
+    let (input, effect) = ws(effect_parser)(input)?;
+    let (input, action) = ws(action_parser)(input)?;
+    let (input, resource_type) = ws(resource_type_parser)(input)?;
+    let (input, clauses) = opt(preceded(ws(tag_no_case("if")), |i| clauses_in(base, i)))(input)?;
+
+    Ok((
+        input,
+        (
+            Policy {
+                effect,
+                action,
+                resource_type: resource_type.to_string(),
+                conditions: Vec::new(),
+            },
+            clauses.unwrap_or_default(),
+        ),
+    ))
+}
+
 fn effect_parser(input: &str) -> Res<'_, Effect> {
     alt((
         map(tag_no_case("allow"), |_| Effect::Allow),
@@ -116,8 +256,23 @@ pub fn action_parser(input: &str) -> Res<'_, Action> {
     Ok((input, action))
 }
 
+/// Parses a resource type such as `sensor_data` or `sensor_data.*`.
+///
+/// `*` is accepted anywhere so that the validator can report misplaced
+/// wildcards instead of failing with a generic parse error.
+pub fn resource_type_parser(input: &str) -> Res<'_, &str> {
+    recognize(tuple((
+        take_while1(is_identifier_start),
+        take_while(|c| is_identifier_char(c) || matches!(c, '.' | '*')),
+    )))(input)
+}
+
 pub fn conditions_parser(input: &str) -> Res<'_, Vec<Condition>> {
-    let (mut input, first_condition) = ws(condition_parser)(input)?;
+    conditions_in(input, input)
+}
+
+fn conditions_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Vec<Condition>> {
+    let (mut input, first_condition) = ws(|i| condition_in(base, i))(input)?;
     let mut conditions = vec![first_condition];
 
     loop {
@@ -125,7 +280,7 @@ pub fn conditions_parser(input: &str) -> Res<'_, Vec<Condition>> {
         if connector.is_none() {
             break;
         }
-        let (next_input, condition) = ws(condition_parser)(next_input)?;
+        let (next_input, condition) = ws(|i| condition_in(base, i))(next_input)?;
         conditions.push(condition);
         input = next_input;
     }
@@ -133,10 +288,131 @@ pub fn conditions_parser(input: &str) -> Res<'_, Vec<Condition>> {
     Ok((input, conditions))
 }
 
+fn clauses_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Vec<Clause<'a>>> {
+    let (mut input, first_clause) = ws(|i| clause_in(base, i))(input)?;
+    let mut clauses = vec![first_clause];
+
+    loop {
+        let (next_input, connector) = opt(ws(alt((tag_no_case("and"), tag_no_case("or")))))(input)?;
+        if connector.is_none() {
+            break;
+        }
+        let (next_input, clause) = ws(|i| clause_in(base, i))(next_input)?;
+        clauses.push(clause);
+        input = next_input;
+    }
+
+    Ok((input, clauses))
+}
+
+fn clause_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Clause<'a>> {
+    alt((
+        |i| alias_reference_in(base, i),
+        map(|i| condition_in(base, i), Clause::Condition),
+    ))(input)
+}
+
+/// Parses a bare name that is not an attribute path, helper call or keyword.
+fn alias_reference_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Clause<'a>> {
+    let (input, _) = multispace0(input)?;
+    let offset = base.len() - input.len();
+    let (input, name) = terminated(
+        verify(identifier, |name: &str| !is_reserved(name)),
+        not(peek(one_of(".("))),
+    )(input)?;
+
+    Ok((input, Clause::Alias { name, offset }))
+}
+
+fn is_reserved(name: &str) -> bool {
+    RESERVED_WORDS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+/// Replaces alias references with the conditions they were defined as.
+fn expand_clauses(
+    base: &str,
+    clauses: Vec<Clause<'_>>,
+    aliases: &Aliases<'_>,
+) -> Result<Vec<Condition>, PolicyDslError> {
+    let mut conditions = Vec::with_capacity(clauses.len());
+    for clause in clauses {
+        match clause {
+            Clause::Condition(condition) => conditions.push(condition),
+            Clause::Alias { name, offset } => match aliases.get(name) {
+                Some(expansion) => conditions.extend(expansion.iter().cloned()),
+                None => {
+                    return Err(PolicyDslError::ParseError {
+                        message: format!("undefined alias `{name}`"),
+                        location: compute_location(base, offset),
+                    })
+                }
+            },
+        }
+    }
+    Ok(conditions)
+}
+
 pub fn condition_parser(input: &str) -> Res<'_, Condition> {
-    let (input, left) = ws(expression_parser)(input)?;
+    condition_in(input, input)
+}
+
+fn condition_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Condition> {
+    alt((
+        |i| exists_condition_in(base, i),
+        |i| helper_condition_in(base, i),
+        |i| comparison_condition_in(base, i),
+    ))(input)
+}
+
+/// Parses `exists <path>`, `defined <path>` and their `not` forms.
+fn exists_condition_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Condition> {
+    let (input, _) = multispace0(input)?;
+    let (input, negated) = opt(terminated(tag_no_case("not"), multispace1))(input)?;
+    let (input, _) = terminated(
+        alt((tag_no_case("exists"), tag_no_case("defined"))),
+        multispace1,
+    )(input)?;
+    let (input, path) = cut(ws(|i| attribute_path_in(base, i)))(input)?;
+
+    Ok((input, Condition::exists(path, negated.is_none())))
+}
+
+/// Parses a helper call such as `geo.allowed(resource.origin, environment.destination)`
+/// used directly as a condition, optionally negated with `not`.
+fn helper_condition_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Condition> {
+    let (input, _) = multispace0(input)?;
+    let (input, negated) = opt(terminated(tag_no_case("not"), multispace1))(input)?;
+    let (input, call) = helper_call_in(base, input)?;
+
+    Ok((input, Condition::helper(call, negated.is_none())))
+}
+
+pub fn helper_call_parser(input: &str) -> Res<'_, HelperCall> {
+    helper_call_in(input, input)
+}
+
+fn helper_call_in<'a>(base: &'a str, input: &'a str) -> Res<'a, HelperCall> {
+    let (input, (module, function)) = separated_pair(identifier, char('.'), identifier)(input)?;
+    let (input, args) = delimited(
+        ws(char('(')),
+        separated_list0(ws(char(',')), ws(|i| expression_in(base, i))),
+        cut(ws(char(')'))),
+    )(input)?;
+
+    Ok((
+        input,
+        HelperCall {
+            module: module.to_ascii_lowercase(),
+            function: function.to_string(),
+            args,
+        },
+    ))
+}
+
+fn comparison_condition_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Condition> {
+    let (input, left) = ws(|i| expression_in(base, i))(input)?;
     let (input, operator) = ws(operator_parser)(input)?;
-    let (input, right) = ws(expression_parser)(input)?;
+    let (input, right) = ws(|i| expression_in(base, i))(input)?;
 
     Ok((
         input,
@@ -161,19 +437,39 @@ pub fn operator_parser(input: &str) -> Res<'_, Operator> {
 }
 
 pub fn expression_parser(input: &str) -> Res<'_, Expression> {
+    expression_in(input, input)
+}
+
+fn expression_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Expression> {
     alt((
-        map(attribute_path_parser, Expression::AttributePath),
+        map(|i| attribute_path_in(base, i), Expression::AttributePath),
         map(string_literal_parser, Expression::StringLiteral),
         map(boolean_literal_parser, Expression::BooleanLiteral),
         map(number_literal_parser, Expression::NumberLiteral),
-        map(list_literal_parser, Expression::ListLiteral),
+        map(|i| list_literal_in(base, i), Expression::ListLiteral),
     ))(input)
 }
 
 pub fn attribute_path_parser(input: &str) -> Res<'_, AttributePath> {
+    attribute_path_in(input, input)
+}
+
+/// Parses an attribute path, recording its span relative to `base`.
+///
+/// `input` must be a suffix of `base`, which holds for every slice nom hands
+/// back while parsing `base`.
+fn attribute_path_in<'a>(base: &'a str, input: &'a str) -> Res<'a, AttributePath> {
+    let start = input;
     let (input, (category_str, field_str)) =
         separated_pair(identifier, char('.'), identifier)(input)?;
 
+    let offset = base.len() - start.len();
+    let span = compute_location(base, offset).map(|(line, column)| Span {
+        line,
+        column,
+        len: start.len() - input.len(),
+    });
+
     let category = match category_str.to_ascii_lowercase().as_str() {
         "subject" => AttributeCategory::Subject,
         "resource" => AttributeCategory::Resource,
@@ -187,6 +483,7 @@ pub fn attribute_path_parser(input: &str) -> Res<'_, AttributePath> {
         AttributePath {
             category,
             field: field_str.to_string(),
+            span,
         },
     ))
 }
@@ -224,9 +521,13 @@ pub fn boolean_literal_parser(input: &str) -> Res<'_, bool> {
 }
 
 pub fn list_literal_parser(input: &str) -> Res<'_, Vec<Expression>> {
+    list_literal_in(input, input)
+}
+
+fn list_literal_in<'a>(base: &'a str, input: &'a str) -> Res<'a, Vec<Expression>> {
     delimited(
         ws(char('[')),
-        separated_list0(ws(char(',')), ws(expression_parser)),
+        separated_list0(ws(char(',')), ws(|i| expression_in(base, i))),
         ws(char(']')),
     )(input)
 }
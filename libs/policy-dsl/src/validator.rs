@@ -1,54 +1,256 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    ast::{AttributeCategory, AttributePath, Condition, Expression, Operator, Policy},
+    ast::{AttributeCategory, AttributePath, Condition, Expression, HelperCall, Operator, Policy},
     PolicyDslError,
 };
+
+/// Value types an attribute is expected to hold.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeType {
+    String,
+    Number,
+    StringList,
+    Object,
+}
+
+/// An approved attribute, as offered for autocompletion in editors.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AttributeDescriptor {
+    pub category: AttributeCategory,
+    /// Dotted path as written in the DSL, e.g. `subject.tenant_id`.
+    pub path: String,
+    pub value_type: AttributeType,
+    pub description: String,
+}
+
+struct AttributeSpec {
+    field: &'static str,
+    value_type: AttributeType,
+    description: &'static str,
+}
+
+const fn attr(
+    field: &'static str,
+    value_type: AttributeType,
+    description: &'static str,
+) -> AttributeSpec {
+    AttributeSpec {
+        field,
+        value_type,
+        description,
+    }
+}
+
 /// Valid attribute fields by category as defined in the README.
-const SUBJECT_FIELDS: &[&str] = &[
-    "tenant_id",
-    "user_id",
-    "device_id",
-    "roles",
-    "clearance_level",
-    "device_location",
-    "department",
-    "region",
+const SUBJECT_FIELDS: &[AttributeSpec] = &[
+    attr("tenant_id", AttributeType::String, "Tenant namespace"),
+    attr("user_id", AttributeType::String, "User identifier"),
+    attr("device_id", AttributeType::String, "Device identifier"),
+    attr(
+        "roles",
+        AttributeType::StringList,
+        "Roles granted to the subject",
+    ),
+    attr(
+        "clearance_level",
+        AttributeType::Number,
+        "Numeric clearance level",
+    ),
+    attr(
+        "device_location",
+        AttributeType::String,
+        "ISO country code or location tag of the device",
+    ),
+    attr(
+        "department",
+        AttributeType::String,
+        "Organisational department",
+    ),
+    attr(
+        "region",
+        AttributeType::String,
+        "Region the subject belongs to",
+    ),
+];
+
+const RESOURCE_FIELDS: &[AttributeSpec] = &[
+    attr(
+        "type",
+        AttributeType::String,
+        "Domain object type, e.g. `sensor_data`",
+    ),
+    attr("id", AttributeType::String, "Resource identifier"),
+    attr(
+        "classification",
+        AttributeType::String,
+        "Sensitivity label, e.g. `public` or `restricted`",
+    ),
+    attr("region", AttributeType::String, "Residency region"),
+    attr(
+        "origin",
+        AttributeType::String,
+        "Region the data originates from",
+    ),
+    attr("owner_tenant", AttributeType::String, "Owning tenant"),
+    attr("owner_user", AttributeType::String, "Owning user"),
+    attr("sensitivity", AttributeType::String, "Sensitivity tier"),
 ];
 
-const RESOURCE_FIELDS: &[&str] = &[
-    "type",
-    "id",
-    "classification",
-    "region",
-    "owner_tenant",
-    "owner_user",
-    "sensitivity",
+const ACTION_FIELDS: &[AttributeSpec] = &[
+    attr("name", AttributeType::String, "Operation name"),
+    attr(
+        "method",
+        AttributeType::String,
+        "Protocol method, e.g. HTTP verb",
+    ),
+    attr(
+        "operation",
+        AttributeType::String,
+        "Protocol-specific operation",
+    ),
 ];
 
-const ACTION_FIELDS: &[&str] = &["name", "method", "operation"];
-
-const ENVIRONMENT_FIELDS: &[&str] = &[
-    "time",
-    "geo",
-    "network",
-    "risk_score",
-    "session_trust",
-    "country",
-    "asn",
-    "bandwidth_used",
+const ENVIRONMENT_FIELDS: &[AttributeSpec] = &[
+    attr(
+        "time",
+        AttributeType::String,
+        "ISO 8601 timestamp or HH:MM time of the request",
+    ),
+    attr("geo", AttributeType::Object, "Geolocation of the request"),
+    attr(
+        "network",
+        AttributeType::String,
+        "Network type, e.g. `vpn` or `private`",
+    ),
+    attr(
+        "risk_score",
+        AttributeType::Number,
+        "Risk score of the request",
+    ),
+    attr(
+        "session_trust",
+        AttributeType::Number,
+        "Trust score of the session",
+    ),
+    attr("country", AttributeType::String, "Request origin country"),
+    attr(
+        "asn",
+        AttributeType::Number,
+        "Autonomous system number of the client network",
+    ),
+    attr(
+        "bandwidth_used",
+        AttributeType::Number,
+        "Bandwidth counter reported by the quota tracker",
+    ),
+    attr(
+        "destination",
+        AttributeType::String,
+        "Region the data is being sent to",
+    ),
 ];
 
+/// Helper functions exposed by the rego bundle libraries as
+/// `(module, function, arity)`.
+const KNOWN_HELPERS: &[(&str, &str, usize)] = &[("geo", "allowed", 2)];
+
+/// Every approved attribute with its category, path, value type and
+/// description, in schema order. This is the vocabulary the validator accepts
+/// without the `custom_` prefix.
+pub fn available_attributes() -> Vec<AttributeDescriptor> {
+    [
+        (AttributeCategory::Subject, SUBJECT_FIELDS),
+        (AttributeCategory::Resource, RESOURCE_FIELDS),
+        (AttributeCategory::Action, ACTION_FIELDS),
+        (AttributeCategory::Environment, ENVIRONMENT_FIELDS),
+    ]
+    .into_iter()
+    .flat_map(|(category, specs)| {
+        specs.iter().map(move |spec| AttributeDescriptor {
+            path: format!("{}.{}", category.as_str(), spec.field),
+            category: category.clone(),
+            value_type: spec.value_type,
+            description: spec.description.to_string(),
+        })
+    })
+    .collect()
+}
+
+/// Every distinct attribute `policy` reads, in order of first reference.
+///
+/// Paths inside list literals and helper call arguments are included, so the
+/// result is the full set of input attributes the policy depends on.
+pub fn attribute_usage(policy: &Policy) -> Vec<AttributePath> {
+    let mut paths = Vec::new();
+    for condition in &policy.conditions {
+        collect_attribute_paths(&condition.left, &mut paths);
+        collect_attribute_paths(&condition.right, &mut paths);
+    }
+    paths
+}
+
+fn collect_attribute_paths(expression: &Expression, paths: &mut Vec<AttributePath>) {
+    match expression {
+        Expression::AttributePath(path) => {
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+        Expression::ListLiteral(elements) => {
+            for element in elements {
+                collect_attribute_paths(element, paths);
+            }
+        }
+        Expression::HelperCall(call) => {
+            for arg in &call.args {
+                collect_attribute_paths(arg, paths);
+            }
+        }
+        Expression::StringLiteral(_)
+        | Expression::NumberLiteral(_)
+        | Expression::BooleanLiteral(_) => {}
+    }
+}
+
 pub fn validate_policy(policy: &Policy) -> Result<(), PolicyDslError> {
     if policy.conditions.is_empty() {
         return Err(PolicyDslError::ValidationError {
             message: "policy must contain at least one condition".into(),
             attribute: None,
+            location: None,
         });
     }
 
+    validate_resource_type(&policy.resource_type)?;
     validate_conditions(&policy.conditions)?;
     Ok(())
 }
 
+/// Resource types may end in `.*` to match a family of types; a `*` anywhere
+/// else is rejected.
+pub fn validate_resource_type(resource_type: &str) -> Result<(), PolicyDslError> {
+    let name = resource_type.strip_suffix(".*").unwrap_or(resource_type);
+    if name.contains('*') {
+        return Err(PolicyDslError::ValidationError {
+            message: format!(
+                "resource type `{resource_type}` may only use `*` as a trailing `.*` wildcard"
+            ),
+            attribute: None,
+            location: None,
+        });
+    }
+    if name.split('.').any(str::is_empty) {
+        return Err(PolicyDslError::ValidationError {
+            message: format!("resource type `{resource_type}` contains an empty segment"),
+            attribute: None,
+            location: None,
+        });
+    }
+    Ok(())
+}
+
 pub fn validate_conditions(conditions: &[Condition]) -> Result<(), PolicyDslError> {
     for condition in conditions {
         validate_condition(condition)?;
@@ -57,16 +259,49 @@ pub fn validate_conditions(conditions: &[Condition]) -> Result<(), PolicyDslErro
 }
 
 pub fn validate_condition(condition: &Condition) -> Result<(), PolicyDslError> {
+    if let Expression::HelperCall(call) = &condition.left {
+        if condition.operator != Operator::Equal
+            || !matches!(condition.right, Expression::BooleanLiteral(_))
+        {
+            return Err(PolicyDslError::ValidationError {
+                message: format!(
+                    "helper `{}.{}` can only be used as a standalone condition",
+                    call.module, call.function
+                ),
+                attribute: None,
+                location: None,
+            });
+        }
+        return validate_helper_call(call);
+    }
+
     if !matches!(condition.left, Expression::AttributePath(_)) {
         return Err(PolicyDslError::ValidationError {
             message: "left-hand side of a condition must be an attribute path".into(),
             attribute: None,
+            location: None,
         });
     }
 
     validate_expression(&condition.left)?;
     validate_expression(&condition.right)?;
-    check_operator_compatibility(&condition.operator, &condition.right)?;
+    check_operator_compatibility(&condition.operator, &condition.right).map_err(|err| {
+        match (err, &condition.left) {
+            (
+                PolicyDslError::ValidationError {
+                    message,
+                    attribute: None,
+                    location: None,
+                },
+                Expression::AttributePath(path),
+            ) => PolicyDslError::ValidationError {
+                message,
+                attribute: Some(format!("{}.{}", path.category.as_str(), path.field)),
+                location: path.span.map(|span| span.location()),
+            },
+            (err, _) => err,
+        }
+    })?;
     Ok(())
 }
 
@@ -82,14 +317,54 @@ pub fn validate_expression(expression: &Expression) -> Result<(), PolicyDslError
             }
             Ok(())
         }
+        Expression::HelperCall(call) => validate_helper_call(call),
     }
 }
 
+pub fn validate_helper_call(call: &HelperCall) -> Result<(), PolicyDslError> {
+    let arity = KNOWN_HELPERS
+        .iter()
+        .find(|(module, function, _)| *module == call.module && *function == call.function)
+        .map(|(_, _, arity)| *arity)
+        .ok_or_else(|| PolicyDslError::ValidationError {
+            message: format!("unknown helper `{}.{}`", call.module, call.function),
+            attribute: None,
+            location: None,
+        })?;
+
+    if call.args.len() != arity {
+        return Err(PolicyDslError::ValidationError {
+            message: format!(
+                "helper `{}.{}` expects {arity} arguments, got {}",
+                call.module,
+                call.function,
+                call.args.len()
+            ),
+            attribute: None,
+            location: None,
+        });
+    }
+
+    for arg in &call.args {
+        if matches!(arg, Expression::HelperCall(_)) {
+            return Err(PolicyDslError::ValidationError {
+                message: "helper calls cannot be nested".into(),
+                attribute: None,
+                location: None,
+            });
+        }
+        validate_expression(arg)?;
+    }
+
+    Ok(())
+}
+
 pub fn validate_attribute_path(path: &AttributePath) -> Result<(), PolicyDslError> {
     if path.field.is_empty() {
         return Err(PolicyDslError::InvalidAttribute {
             path: format!("{}.{}", path.category.as_str(), path.field),
             reason: "attribute field cannot be empty".into(),
+            location: path.span.map(|span| span.location()),
         });
     }
 
@@ -101,7 +376,7 @@ pub fn validate_attribute_path(path: &AttributePath) -> Result<(), PolicyDslErro
         AttributeCategory::Custom(_) => &[][..],
     };
 
-    if !allowed.is_empty() && !allowed.contains(&path.field.as_str()) {
+    if !allowed.is_empty() && !allowed.iter().any(|spec| spec.field == path.field) {
         if path.field.starts_with("custom_") {
             tracing::warn!(
                 category = %path.category.as_str(),
@@ -113,6 +388,7 @@ pub fn validate_attribute_path(path: &AttributePath) -> Result<(), PolicyDslErro
                 path: format!("{}.{}", path.category.as_str(), path.field),
                 reason: "attribute not listed in approved schema; use `custom_` prefix for custom fields"
                     .into(),
+                location: path.span.map(|span| span.location()),
             });
         }
     }
@@ -130,11 +406,21 @@ pub fn check_operator_compatibility(
             _ => Err(PolicyDslError::ValidationError {
                 message: "operator `in` requires a list literal on the right-hand side".into(),
                 attribute: None,
+                location: None,
+            }),
+        },
+        Operator::Exists => match right {
+            Expression::BooleanLiteral(_) => Ok(()),
+            _ => Err(PolicyDslError::ValidationError {
+                message: "operator `exists` takes no right-hand operand".into(),
+                attribute: None,
+                location: None,
             }),
         },
         Operator::And | Operator::Or | Operator::Not => Err(PolicyDslError::ValidationError {
             message: format!("logical operator `{operator}` cannot be used as a comparison"),
             attribute: None,
+            location: None,
         }),
         _ => Ok(()),
     }
@@ -1,7 +1,9 @@
 //! Code generation tests for the policy DSL
 
 use edge_policy_dsl::ast::*;
-use edge_policy_dsl::codegen::generate_rego;
+use edge_policy_dsl::codegen::{format_rego, generate_rego};
+use edge_policy_dsl::compile_policy;
+use edge_policy_dsl::PolicyDslError;
 
 #[test]
 fn test_generate_simple_policy() {
@@ -13,13 +15,14 @@ fn test_generate_simple_policy() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "tenant_id".to_string(),
+                span: None,
             }),
             operator: Operator::Equal,
             right: Expression::StringLiteral("tenant-a".to_string()),
         }],
     };
 
-    let rego = generate_rego(&policy, "tenant-a");
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
 
     assert!(rego.contains("package tenants.tenant-a"));
     assert!(rego.contains("import rego.v1"));
@@ -39,6 +42,7 @@ fn test_generate_multiple_conditions() {
                 left: Expression::AttributePath(AttributePath {
                     category: AttributeCategory::Subject,
                     field: "tenant_id".to_string(),
+                    span: None,
                 }),
                 operator: Operator::Equal,
                 right: Expression::StringLiteral("tenant-a".to_string()),
@@ -47,6 +51,7 @@ fn test_generate_multiple_conditions() {
                 left: Expression::AttributePath(AttributePath {
                     category: AttributeCategory::Resource,
                     field: "region".to_string(),
+                    span: None,
                 }),
                 operator: Operator::Equal,
                 right: Expression::StringLiteral("EU".to_string()),
@@ -54,7 +59,7 @@ fn test_generate_multiple_conditions() {
         ],
     };
 
-    let rego = generate_rego(&policy, "tenant-a");
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
 
     assert!(rego.contains("input.subject.tenant_id == \"tenant-a\""));
     assert!(rego.contains("input.resource.region == \"EU\""));
@@ -70,6 +75,7 @@ fn test_generate_in_operator() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "device_location".to_string(),
+                span: None,
             }),
             operator: Operator::In,
             right: Expression::ListLiteral(vec![
@@ -79,7 +85,7 @@ fn test_generate_in_operator() {
         }],
     };
 
-    let rego = generate_rego(&policy, "tenant-a");
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
 
     assert!(rego.contains("input.subject.device_location in [\"DE\", \"FR\"]"));
 }
@@ -94,13 +100,14 @@ fn test_generate_numeric_comparison() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "clearance_level".to_string(),
+                span: None,
             }),
             operator: Operator::GreaterThanOrEqual,
             right: Expression::NumberLiteral(2.0),
         }],
     };
 
-    let rego = generate_rego(&policy, "tenant-a");
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
 
     assert!(rego.contains("input.subject.clearance_level >= 2"));
 }
@@ -115,6 +122,7 @@ fn test_generate_tenant_namespace() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "tenant_id".to_string(),
+                span: None,
             }),
             operator: Operator::Equal,
             right: Expression::StringLiteral("test".to_string()),
@@ -124,7 +132,7 @@ fn test_generate_tenant_namespace() {
     let tenants = vec!["tenant-a", "tenant-b", "tenant_123"];
 
     for tenant_id in tenants {
-        let rego = generate_rego(&policy, tenant_id);
+        let rego = generate_rego(&policy, tenant_id).unwrap();
         assert!(rego.contains(&format!("package tenants.{}", tenant_id)));
     }
 }
@@ -139,13 +147,14 @@ fn test_generate_deny_policy() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Environment,
                 field: "risk_score".to_string(),
+                span: None,
             }),
             operator: Operator::GreaterThan,
             right: Expression::NumberLiteral(0.8),
         }],
     };
 
-    let rego = generate_rego(&policy, "tenant-a");
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
 
     assert!(rego.contains("default deny := false"));
     assert!(rego.contains("deny if {"));
@@ -161,13 +170,14 @@ fn test_generate_escaped_strings() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "name".to_string(),
+                span: None,
             }),
             operator: Operator::Equal,
             right: Expression::StringLiteral("test\"quote".to_string()),
         }],
     };
 
-    let rego = generate_rego(&policy, "tenant-a");
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
 
     assert!(rego.contains("\\\""));
 }
@@ -201,13 +211,14 @@ fn test_generate_attribute_paths() {
                 left: Expression::AttributePath(AttributePath {
                     category,
                     field: field.to_string(),
+                    span: None,
                 }),
                 operator: Operator::Equal,
                 right: Expression::StringLiteral("value".to_string()),
             }],
         };
 
-        let rego = generate_rego(&policy, "tenant-a");
+        let rego = generate_rego(&policy, "tenant-a").unwrap();
         assert!(
             rego.contains(expected),
             "Expected {} in generated Rego",
@@ -226,13 +237,14 @@ fn test_generate_boolean_literal() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "active".to_string(),
+                span: None,
             }),
             operator: Operator::Equal,
             right: Expression::BooleanLiteral(true),
         }],
     };
 
-    let rego = generate_rego(&policy, "tenant-a");
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
 
     assert!(rego.contains("input.subject.active == true"));
 }
@@ -247,13 +259,213 @@ fn test_generate_decimal_number() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Environment,
                 field: "risk_score".to_string(),
+                span: None,
             }),
             operator: Operator::LessThan,
             right: Expression::NumberLiteral(0.5),
         }],
     };
 
-    let rego = generate_rego(&policy, "tenant-a");
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
 
     assert!(rego.contains("0.5"));
 }
+
+#[test]
+fn test_generate_exists_condition() {
+    let device_id = AttributePath {
+        category: AttributeCategory::Subject,
+        field: "device_id".to_string(),
+        span: None,
+    };
+    let policy = Policy {
+        effect: Effect::Allow,
+        action: Action::Read,
+        resource_type: "sensor_data".to_string(),
+        conditions: vec![Condition::exists(device_id, true)],
+    };
+
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
+
+    assert!(rego.contains("    input.subject.device_id\n"));
+    assert!(!rego.contains("input.subject.device_id =="));
+}
+
+#[test]
+fn test_generate_not_exists_condition() {
+    let device_id = AttributePath {
+        category: AttributeCategory::Subject,
+        field: "device_id".to_string(),
+        span: None,
+    };
+    let policy = Policy {
+        effect: Effect::Deny,
+        action: Action::Write,
+        resource_type: "sensor_data".to_string(),
+        conditions: vec![Condition::exists(device_id, false)],
+    };
+
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
+
+    assert!(rego.contains("not input.subject.device_id"));
+}
+
+#[test]
+fn test_generate_helper_call_passes_residency_matrix() {
+    let call = HelperCall {
+        module: "geo".to_string(),
+        function: "allowed".to_string(),
+        args: vec![
+            Expression::AttributePath(AttributePath {
+                category: AttributeCategory::Resource,
+                field: "origin".to_string(),
+                span: None,
+            }),
+            Expression::AttributePath(AttributePath {
+                category: AttributeCategory::Environment,
+                field: "destination".to_string(),
+                span: None,
+            }),
+        ],
+    };
+    let policy = Policy {
+        effect: Effect::Allow,
+        action: Action::Custom("transfer".to_string()),
+        resource_type: "data".to_string(),
+        conditions: vec![Condition::helper(call, true)],
+    };
+
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
+
+    assert!(!rego.contains("import data.lib"));
+    assert!(rego.contains("default geo_residency_matrix := {}\n"));
+    assert!(rego.contains("geo_residency_matrix := data.tenants.tenant-a.residency_matrix\n"));
+    assert!(rego.contains(
+        "    data.lib.geo.allowed_by(geo_residency_matrix, input.resource.origin, input.environment.destination)\n"
+    ));
+}
+
+#[test]
+fn test_generate_without_helpers_has_no_lib_import() {
+    let policy = Policy {
+        effect: Effect::Allow,
+        action: Action::Read,
+        resource_type: "sensor_data".to_string(),
+        conditions: vec![],
+    };
+
+    let rego = generate_rego(&policy, "tenant-a").unwrap();
+
+    assert!(!rego.contains("import data.lib"));
+}
+
+fn single_condition_policy(category: AttributeCategory, field: &str) -> Policy {
+    Policy {
+        effect: Effect::Allow,
+        action: Action::Read,
+        resource_type: "sensor_data".to_string(),
+        conditions: vec![Condition {
+            left: Expression::AttributePath(AttributePath {
+                category,
+                field: field.to_string(),
+                span: None,
+            }),
+            operator: Operator::Equal,
+            right: Expression::StringLiteral("value".to_string()),
+        }],
+    }
+}
+
+#[test]
+fn test_each_category_maps_to_its_input_prefix() {
+    let cases = [
+        (AttributeCategory::Subject, "input.subject.department"),
+        (AttributeCategory::Resource, "input.resource.department"),
+        (
+            AttributeCategory::Environment,
+            "input.environment.department",
+        ),
+        (AttributeCategory::Action, "input.action.department"),
+        (
+            AttributeCategory::Custom("device".to_string()),
+            "input.device.department",
+        ),
+    ];
+
+    for (category, expected) in cases {
+        let rego =
+            generate_rego(&single_condition_policy(category, "department"), "tenant-a").unwrap();
+        assert!(
+            rego.contains(&format!("{expected} == \"value\"")),
+            "missing {expected} in:\n{rego}"
+        );
+    }
+}
+
+#[test]
+fn test_uncategorized_attribute_path_is_rejected() {
+    let policy = single_condition_policy(AttributeCategory::Custom(String::new()), "department");
+
+    match generate_rego(&policy, "tenant-a") {
+        Err(PolicyDslError::InvalidAttribute { path, .. }) => assert_eq!(path, "department"),
+        other => panic!("expected InvalidAttribute, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_format_rego_normalizes_layout() {
+    let messy = "package tenants.tenant-a   \r\n\r\n\r\nimport rego.v1\n\nallow if {\n\n  input.subject.name == \"a { b\"  \n        count([\n1,\n  2]) > 0\n\n}\n\n\n";
+
+    assert_eq!(
+        format_rego(messy),
+        "package tenants.tenant-a\n\nimport rego.v1\n\nallow if {\n    input.subject.name == \"a { b\"\n    count([\n        1,\n        2]) > 0\n}\n"
+    );
+    assert_eq!(format_rego(&format_rego(messy)), format_rego(messy));
+}
+
+#[test]
+fn test_equivalent_sources_compile_to_identical_rego() {
+    let compact =
+        r#"allow read sensor_data if subject.tenant_id == "tenant-a" and resource.region == "EU""#;
+    let spaced = r#"
+        # same policy, different layout
+        allow   read sensor_data
+            if subject.tenant_id == "tenant-a"
+            and resource.region == "EU"
+    "#;
+
+    let first = compile_policy(compact, "tenant-a", None).unwrap();
+    let second = compile_policy(spaced, "tenant-a", None).unwrap();
+
+    assert_eq!(first.rego, second.rego);
+    assert_eq!(first.rego, format_rego(&first.rego));
+}
+
+#[test]
+fn test_condition_order_does_not_change_generated_rego() {
+    let source = r#"allow read sensor_data if subject.tenant_id == "tenant-a" and resource.region == "EU" and environment.risk_score < 50"#;
+    let reordered = r#"allow read sensor_data if environment.risk_score < 50 and resource.region == "EU" and subject.tenant_id == "tenant-a""#;
+
+    let first = compile_policy(source, "tenant-a", None).unwrap();
+    let second = compile_policy(source, "tenant-a", None).unwrap();
+    let third = compile_policy(reordered, "tenant-a", None).unwrap();
+
+    assert_eq!(first.rego.as_bytes(), second.rego.as_bytes());
+    assert_eq!(first.rego.as_bytes(), third.rego.as_bytes());
+}
+
+#[test]
+fn test_wildcard_resource_type_compiles_to_prefix_match() {
+    let dsl = r#"allow read sensor_data.* if subject.tenant_id == "tenant-a""#;
+    let compiled = compile_policy(dsl, "tenant-a", None).unwrap();
+
+    assert!(compiled
+        .rego
+        .contains("startswith(input.resource.type, \"sensor_data.\")"));
+    assert!(!compiled.rego.contains("input.resource.type =="));
+
+    let policy = edge_policy_dsl::parser::parse_policy(dsl).unwrap();
+    let prefix = policy.resource_type_prefix().unwrap();
+    assert!("sensor_data.temperature".starts_with(prefix));
+    assert!(!"sensor_database".starts_with(prefix));
+}
@@ -1,6 +1,8 @@
 //! End-to-end integration tests
 
-use edge_policy_dsl::{compile_policy, BundleBuilder, PolicyDslError, PolicyMetadata};
+use edge_policy_dsl::{
+    compile_policy, content_bundle_id, BundleBuilder, PolicyDslError, PolicyMetadata,
+};
 use tempfile::tempdir;
 
 #[test]
@@ -172,6 +174,27 @@ fn test_bundle_with_data() {
     assert!(data_content.contains("operator"));
 }
 
+#[test]
+fn test_identical_deploys_share_content_bundle_id() {
+    let dsl = r#"allow read sensor_data if subject.tenant_id == "tenant-a""#;
+    let first = compile_policy(dsl, "tenant-a", None).unwrap();
+    let second = compile_policy(dsl, "tenant-a", None).unwrap();
+
+    let id = content_bundle_id("tenant-a", &first.rego);
+    assert_eq!(id, content_bundle_id("tenant-a", &second.rego));
+    assert_eq!(id.len(), "bundle-".len() + 32);
+    assert!(id["bundle-".len()..].chars().all(|c| c.is_ascii_hexdigit()));
+
+    let changed = compile_policy(
+        r#"allow write sensor_data if subject.tenant_id == "tenant-a""#,
+        "tenant-a",
+        None,
+    )
+    .unwrap();
+    assert_ne!(id, content_bundle_id("tenant-a", &changed.rego));
+    assert_ne!(id, content_bundle_id("tenant-b", &first.rego));
+}
+
 #[test]
 fn test_end_to_end_dsl_to_bundle() {
     let dsl = r#"allow read sensor_data if subject.tenant_id == "tenant-test" and resource.region == "EU""#;
@@ -245,3 +268,21 @@ fn test_multiple_policies_same_tenant() {
     assert!(tenant_dir.join("policy1.rego").exists());
     assert!(tenant_dir.join("policy2.rego").exists());
 }
+
+#[test]
+fn test_compile_exists_condition() {
+    let dsl =
+        r#"allow read sensor_data if subject.tenant_id == "tenant-a" and exists subject.device_id"#;
+    let compiled = compile_policy(dsl, "tenant-a", None).expect("exists should compile");
+    assert!(compiled.rego.contains("    input.subject.device_id\n"));
+}
+
+#[test]
+fn test_compile_residency_matrix_transfer() {
+    let dsl = r#"allow transfer data if geo.allowed(resource.origin, environment.destination)"#;
+    let compiled = compile_policy(dsl, "tenant-a", None).expect("helper call should compile");
+
+    assert!(compiled.rego.contains(
+        "data.lib.geo.allowed_by(geo_residency_matrix, input.resource.origin, input.environment.destination)"
+    ));
+}
@@ -149,3 +149,160 @@ fn test_parse_whitespace_handling() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_parse_records_attribute_spans() {
+    let input = "# region guard\nallow read sensor_data if\n  subject.tenant_id == \"tenant-a\"";
+    let policy = parse_policy(input).unwrap();
+
+    let Expression::AttributePath(path) = &policy.conditions[0].left else {
+        panic!("Expected AttributePath");
+    };
+    let span = path.span.expect("parsed attribute should carry a span");
+    assert_eq!((span.line, span.column, span.len), (3, 3, 17));
+}
+
+#[test]
+fn test_parse_exists_condition() {
+    let input =
+        r#"allow read sensor_data if exists subject.device_id and subject.tenant_id == "tenant-a""#;
+    let policy = parse_policy(input).unwrap();
+
+    assert_eq!(policy.conditions.len(), 2);
+    let condition = &policy.conditions[0];
+    assert_eq!(condition.operator, Operator::Exists);
+    assert_eq!(condition.right, Expression::BooleanLiteral(true));
+    match &condition.left {
+        Expression::AttributePath(path) => {
+            assert_eq!(path.category, AttributeCategory::Subject);
+            assert_eq!(path.field, "device_id");
+        }
+        other => panic!("Expected AttributePath, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_not_exists_condition() {
+    let input = r#"deny write sensor_data if not exists subject.device_id"#;
+    let policy = parse_policy(input).unwrap();
+
+    let condition = &policy.conditions[0];
+    assert_eq!(condition.operator, Operator::Exists);
+    assert_eq!(condition.right, Expression::BooleanLiteral(false));
+}
+
+#[test]
+fn test_parse_defined_alias() {
+    let input = r#"allow read sensor_data if defined resource.region"#;
+    let policy = parse_policy(input).unwrap();
+
+    assert_eq!(policy.conditions[0].operator, Operator::Exists);
+}
+
+#[test]
+fn test_parse_exists_requires_attribute_path() {
+    let input = r#"allow read sensor_data if exists "device_id""#;
+    assert!(parse_policy(input).is_err());
+}
+
+#[test]
+fn test_parse_helper_call_condition() {
+    let input = r#"allow transfer data if geo.allowed(resource.origin, environment.destination)"#;
+    let policy = parse_policy(input).unwrap();
+
+    assert_eq!(policy.action, Action::Custom("transfer".to_string()));
+    let condition = &policy.conditions[0];
+    assert_eq!(condition.operator, Operator::Equal);
+    assert_eq!(condition.right, Expression::BooleanLiteral(true));
+    match &condition.left {
+        Expression::HelperCall(call) => {
+            assert_eq!(call.module, "geo");
+            assert_eq!(call.function, "allowed");
+            assert_eq!(call.args.len(), 2);
+        }
+        other => panic!("Expected HelperCall, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_negated_helper_call_condition() {
+    let input =
+        r#"deny transfer data if not geo.allowed(resource.origin, environment.destination)"#;
+    let policy = parse_policy(input).unwrap();
+
+    assert_eq!(
+        policy.conditions[0].right,
+        Expression::BooleanLiteral(false)
+    );
+}
+
+#[test]
+fn test_parse_helper_call_requires_closing_paren() {
+    let input = r#"allow transfer data if geo.allowed(resource.origin, environment.destination"#;
+    assert!(parse_policy(input).is_err());
+}
+
+#[test]
+fn test_parse_policies_expands_alias_in_each_rule() {
+    let input = r#"define same_tenant = subject.tenant_id == resource.owner_tenant
+define eu_device = same_tenant and subject.device_location in ["DE", "FR"]
+
+allow read sensor_data if same_tenant
+deny write sensor_data if eu_device and resource.region != "EU""#;
+    let policies = edge_policy_dsl::parser::parse_policies(input).unwrap();
+    assert_eq!(policies.len(), 2);
+
+    let same_tenant = &policies[0].conditions;
+    assert_eq!(same_tenant.len(), 1);
+    assert_eq!(same_tenant[0].operator, Operator::Equal);
+
+    let conditions = &policies[1].conditions;
+    assert_eq!(conditions.len(), 3);
+    assert_eq!(conditions[0], same_tenant[0]);
+    assert_eq!(conditions[1].operator, Operator::In);
+    assert_eq!(conditions[2].operator, Operator::NotEqual);
+
+    // Spans of expanded conditions point at the alias definition
+    match &conditions[0].left {
+        Expression::AttributePath(path) => {
+            assert_eq!(path.span.map(|span| span.location()), Some((1, 22)));
+        }
+        other => panic!("Expected AttributePath, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_policy_accepts_leading_aliases() {
+    let input = r#"define same_tenant = subject.tenant_id == resource.owner_tenant
+allow read sensor_data if same_tenant and resource.region == "EU""#;
+    let policy = parse_policy(input).unwrap();
+    assert_eq!(policy.conditions.len(), 2);
+}
+
+#[test]
+fn test_parse_undefined_alias_is_an_error() {
+    let input = "define same_tenant = subject.tenant_id == resource.owner_tenant\nallow read sensor_data if same_tenat";
+    match parse_policy(input).unwrap_err() {
+        edge_policy_dsl::PolicyDslError::ParseError { message, location } => {
+            assert!(
+                message.contains("undefined alias `same_tenat`"),
+                "{message}"
+            );
+            assert_eq!(location, Some((2, 27)));
+        }
+        other => panic!("Expected ParseError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_alias_definition_errors() {
+    let redefined = "define a = subject.x == 1\ndefine a = subject.y == 2\nallow read data if a";
+    assert!(parse_policy(redefined).is_err());
+
+    let reserved = "define exists = subject.x == 1\nallow read data if exists";
+    assert!(parse_policy(reserved).is_err());
+
+    // Aliases must be defined before use
+    let forward = "allow read data if later\ndefine later = subject.x == 1";
+    assert!(edge_policy_dsl::parser::parse_policies(forward).is_err());
+}
@@ -1,7 +1,9 @@
 //! Validator tests for the policy DSL
 
 use edge_policy_dsl::ast::*;
-use edge_policy_dsl::validator::validate_policy;
+use edge_policy_dsl::parser::parse_policy;
+use edge_policy_dsl::validator::{attribute_usage, validate_policy};
+use edge_policy_dsl::{detect_conflicts, PolicyDslError};
 
 #[test]
 fn test_validate_valid_policy() {
@@ -14,6 +16,7 @@ fn test_validate_valid_policy() {
                 left: Expression::AttributePath(AttributePath {
                     category: AttributeCategory::Subject,
                     field: "tenant_id".to_string(),
+                    span: None,
                 }),
                 operator: Operator::Equal,
                 right: Expression::StringLiteral("tenant-a".to_string()),
@@ -22,6 +25,7 @@ fn test_validate_valid_policy() {
                 left: Expression::AttributePath(AttributePath {
                     category: AttributeCategory::Resource,
                     field: "region".to_string(),
+                    span: None,
                 }),
                 operator: Operator::Equal,
                 right: Expression::StringLiteral("EU".to_string()),
@@ -43,6 +47,7 @@ fn test_validate_in_operator_with_list() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "device_location".to_string(),
+                span: None,
             }),
             operator: Operator::In,
             right: Expression::ListLiteral(vec![
@@ -66,6 +71,7 @@ fn test_validate_in_operator_with_non_list() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "tenant_id".to_string(),
+                span: None,
             }),
             operator: Operator::In,
             right: Expression::StringLiteral("tenant-a".to_string()),
@@ -96,6 +102,7 @@ fn test_validate_all_valid_subject_fields() {
                 left: Expression::AttributePath(AttributePath {
                     category: AttributeCategory::Subject,
                     field: field.to_string(),
+                    span: None,
                 }),
                 operator: Operator::Equal,
                 right: Expression::StringLiteral("value".to_string()),
@@ -127,6 +134,7 @@ fn test_validate_all_valid_resource_fields() {
                 left: Expression::AttributePath(AttributePath {
                     category: AttributeCategory::Resource,
                     field: field.to_string(),
+                    span: None,
                 }),
                 operator: Operator::Equal,
                 right: Expression::StringLiteral("value".to_string()),
@@ -160,6 +168,7 @@ fn test_validate_all_valid_environment_fields() {
                 left: Expression::AttributePath(AttributePath {
                     category: AttributeCategory::Environment,
                     field: field.to_string(),
+                    span: None,
                 }),
                 operator: Operator::Equal,
                 right: Expression::StringLiteral("value".to_string()),
@@ -182,6 +191,7 @@ fn test_validate_custom_attribute_allowed() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "custom_field".to_string(),
+                span: None,
             }),
             operator: Operator::Equal,
             right: Expression::StringLiteral("value".to_string()),
@@ -203,6 +213,7 @@ fn test_validate_nested_list_literal() {
             left: Expression::AttributePath(AttributePath {
                 category: AttributeCategory::Subject,
                 field: "roles".to_string(),
+                span: None,
             }),
             operator: Operator::In,
             right: Expression::ListLiteral(vec![
@@ -215,3 +226,169 @@ fn test_validate_nested_list_literal() {
     let result = validate_policy(&policy);
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_validation_error_reports_attribute_location() {
+    let source = "allow read sensor_data if subject.tenant_id == \"tenant-a\"\n    and resource.bogus_field == \"x\"";
+    let policy = parse_policy(source).expect("policy should parse");
+
+    let err = validate_policy(&policy).expect_err("unknown attribute should be rejected");
+    match err {
+        PolicyDslError::InvalidAttribute { path, location, .. } => {
+            assert_eq!(path, "resource.bogus_field");
+            assert_eq!(location, Some((2, 9)));
+        }
+        other => panic!("expected invalid attribute error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_operator_error_reports_left_attribute_location() {
+    let source = r#"allow read sensor_data if subject.roles in "admin""#;
+    let policy = parse_policy(source).expect("policy should parse");
+
+    let err = validate_policy(&policy).expect_err("`in` without a list should be rejected");
+    match err {
+        PolicyDslError::ValidationError {
+            attribute,
+            location,
+            ..
+        } => {
+            assert_eq!(attribute.as_deref(), Some("subject.roles"));
+            assert_eq!(location, Some((1, 27)));
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_validate_helper_call_arity() {
+    let policy = parse_policy(r#"allow transfer data if geo.allowed(resource.origin)"#).unwrap();
+
+    match validate_policy(&policy) {
+        Err(PolicyDslError::ValidationError { message, .. }) => {
+            assert!(message.contains("expects 2 arguments"), "{message}");
+        }
+        other => panic!("Expected arity error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_validate_unknown_helper() {
+    let policy = parse_policy(
+        r#"allow transfer data if geo.nearby(resource.origin, environment.destination)"#,
+    )
+    .unwrap();
+
+    assert!(matches!(
+        validate_policy(&policy),
+        Err(PolicyDslError::ValidationError { .. })
+    ));
+}
+
+#[test]
+fn test_available_attributes_follow_validator_schema() {
+    use edge_policy_dsl::validator::validate_attribute_path;
+    use edge_policy_dsl::{available_attributes, AttributeType};
+
+    let attributes = available_attributes();
+    let tenant_id = attributes
+        .iter()
+        .find(|attribute| attribute.path == "subject.tenant_id")
+        .expect("subject.tenant_id should be offered");
+    assert_eq!(tenant_id.category, AttributeCategory::Subject);
+    assert_eq!(tenant_id.value_type, AttributeType::String);
+    assert!(!tenant_id.description.is_empty());
+
+    let roles = attributes
+        .iter()
+        .find(|attribute| attribute.path == "subject.roles")
+        .unwrap();
+    assert_eq!(roles.value_type, AttributeType::StringList);
+
+    for attribute in &attributes {
+        let (_, field) = attribute.path.split_once('.').unwrap();
+        let path = AttributePath {
+            category: attribute.category.clone(),
+            field: field.to_string(),
+            span: None,
+        };
+        assert!(
+            validate_attribute_path(&path).is_ok(),
+            "{} should pass validation",
+            attribute.path
+        );
+    }
+}
+
+#[test]
+fn test_validate_rejects_wildcard_inside_resource_type() {
+    let policy = parse_policy(r#"allow read sen*sor if subject.tenant_id == "tenant-a""#)
+        .expect("policy should parse");
+
+    let err = validate_policy(&policy).expect_err("misplaced wildcard should be rejected");
+    match err {
+        PolicyDslError::ValidationError { message, .. } => {
+            assert!(message.contains("sen*sor"), "unexpected message: {message}");
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_attribute_usage_collects_each_path_once() {
+    let policy = parse_policy(
+        r#"allow read sensor_data if subject.tenant_id == "tenant-a" and resource.type in ["sensor_data", "telemetry"] or geo.allowed(resource.origin, environment.destination) and subject.tenant_id != resource.owner_tenant"#,
+    )
+    .expect("policy should parse");
+    validate_policy(&policy).expect("policy should validate");
+
+    let paths: Vec<String> = attribute_usage(&policy)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "subject.tenant_id",
+            "resource.type",
+            "resource.origin",
+            "environment.destination",
+            "resource.owner_tenant",
+        ]
+    );
+}
+
+#[test]
+fn test_detect_conflicts_reports_overlapping_allow_and_deny() {
+    let policies: Vec<Policy> = [
+        r#"allow read sensor_data if subject.tenant_id == "tenant-a""#,
+        r#"deny read sensor_data if subject.tenant_id == "tenant-a" and resource.region == "eu-west""#,
+        r#"allow write sensor_data if subject.tenant_id == "tenant-a""#,
+        r#"deny write sensor_data if subject.tenant_id == "tenant-b""#,
+    ]
+    .iter()
+    .map(|source| parse_policy(source).expect("policy should parse"))
+    .collect();
+
+    let conflicts = detect_conflicts(&policies);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!((conflicts[0].allow_index, conflicts[0].deny_index), (0, 1));
+    assert!(conflicts[0].message.contains("`read` on `sensor_data`"));
+
+    assert!(detect_conflicts(&policies[2..]).is_empty());
+}
+
+#[test]
+fn test_validation_error_inside_alias_reports_definition_location() {
+    let source = "define bad = resource.bogus_field == \"x\"\nallow read sensor_data if bad";
+    let policy = parse_policy(source).expect("policy should parse");
+
+    match validate_policy(&policy).expect_err("unknown attribute should be rejected") {
+        PolicyDslError::InvalidAttribute { path, location, .. } => {
+            assert_eq!(path, "resource.bogus_field");
+            assert_eq!(location, Some((1, 14)));
+        }
+        other => panic!("expected invalid attribute error, got {other:?}"),
+    }
+}
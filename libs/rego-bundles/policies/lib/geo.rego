@@ -17,7 +17,7 @@ eu_countries := {
 #   country_code: Two-letter ISO country code (e.g., "DE", "FR")
 # Returns: true if country is an EU member state
 # Usage: geo.is_eu_country(input.environment.country)
-is_eu_country(country_code) {
+is_eu_country(country_code) if {
 	country_code in eu_countries
 }
 
@@ -26,7 +26,7 @@ is_eu_country(country_code) {
 #   geo: Object with country field (e.g., {"country": "DE", "city": "Berlin"})
 # Returns: true if geo.country is in EU
 # Usage: geo.is_eu_location(input.subject.location)
-is_eu_location(geo) {
+is_eu_location(geo) if {
 	geo.country in eu_countries
 }
 
@@ -36,17 +36,55 @@ is_eu_location(geo) {
 #   subject_location: Location region string
 # Returns: true if both regions match
 # Usage: geo.validate_data_residency(input.resource.region, input.subject.region)
-validate_data_residency(resource_region, subject_location) {
+validate_data_residency(resource_region, subject_location) if {
 	resource_region == subject_location
 }
 
+# Check whether data may be transferred from an origin to a destination region
+# Parameters:
+#   origin: Region or country the data originates from (e.g., "EU")
+#   destination: Region or country the data is sent to (e.g., "UK")
+# Returns: true if origin equals destination, or the tenant residency matrix
+#          lists destination as a permitted target for origin
+# Usage: geo.allowed(input.resource.origin, input.environment.destination)
+# Note: The matrix is read from the tenant's data.json as
+#       {"residency_matrix": {"EU": ["UK"], "UK": ["EU", "US"]}}
+allowed(origin, destination) if {
+	origin == destination
+}
+
+allowed(origin, destination) if {
+	destination in residency_matrix[origin]
+}
+
+# Residency matrix for the tenant making the request (data.tenants.{tenant_id})
+residency_matrix := data.tenants[input.subject.tenant_id].residency_matrix
+
+# Check a transfer against a residency matrix passed in by the caller
+# Parameters:
+#   matrix: Residency matrix object (e.g., {"EU": ["UK"]})
+#   origin: Region or country the data originates from
+#   destination: Region or country the data is sent to
+# Returns: true if origin equals destination, or matrix lists destination for origin
+# Usage: geo.allowed_by(data.tenants.tenant_a.residency_matrix, input.resource.origin, input.environment.destination)
+# Note: DSL-generated policies call this form. Looking the matrix up by the
+#       requesting tenant reads all of data.tenants, which includes the
+#       calling tenant's own rules and is rejected as recursive by the enforcer
+allowed_by(matrix, origin, destination) if {
+	origin == destination
+}
+
+allowed_by(matrix, origin, destination) if {
+	destination in matrix[origin]
+}
+
 # Check if a country code is valid ISO 3166-1 alpha-2 format
 # Parameters:
 #   code: Country code string to validate
 # Returns: true if code is exactly 2 uppercase letters (A-Z)
 # Usage: geo.is_iso_alpha2("DE")
 # Note: This only validates format, not whether the code is assigned
-is_iso_alpha2(code) {
+is_iso_alpha2(code) if {
 	count(code) == 2
 	upper(code) == code
 	regex.match(`^[A-Z]{2}$`, code)
@@ -58,6 +96,6 @@ is_iso_alpha2(code) {
 # Returns: Uppercase version of the code
 # Usage: normalized := geo.normalize_country_code("de")
 # Note: Use this before passing codes to is_eu_country for case-insensitive checks
-normalize_country_code(code) := result {
+normalize_country_code(code) := result if {
 	result := upper(code)
 }
@@ -11,7 +11,7 @@ import rego.v1
 #   limit_gb: Maximum allowed bandwidth in GB
 # Returns: true if used >= limit
 # Usage: quota.bandwidth_exceeded(input.environment.bandwidth_used, 100)
-bandwidth_exceeded(used_gb, limit_gb) {
+bandwidth_exceeded(used_gb, limit_gb) if {
 	used_gb >= limit_gb
 }
 
@@ -21,7 +21,7 @@ bandwidth_exceeded(used_gb, limit_gb) {
 #   limit_gb: Maximum allowed bandwidth in GB
 # Returns: true if used < limit
 # Usage: quota.bandwidth_within_limit(input.environment.bandwidth_used, 100)
-bandwidth_within_limit(used_gb, limit_gb) {
+bandwidth_within_limit(used_gb, limit_gb) if {
 	used_gb < limit_gb
 }
 
@@ -31,7 +31,7 @@ bandwidth_within_limit(used_gb, limit_gb) {
 #   limit: Maximum allowed message count
 # Returns: true if count >= limit
 # Usage: quota.message_count_exceeded(input.environment.messages_sent, 50000)
-message_count_exceeded(count, limit) {
+message_count_exceeded(count, limit) if {
 	count >= limit
 }
 
@@ -42,11 +42,11 @@ message_count_exceeded(count, limit) {
 # Returns: Percentage value (0-100+), or 0 if limit is 0
 # Note: If limit is 0 or negative, returns 0 to avoid division by zero
 # Usage: percentage := quota.quota_percentage(input.environment.bandwidth_used, 100)
-quota_percentage(used, limit) := 0 {
+quota_percentage(used, limit) := 0 if {
 	limit <= 0
 }
 
-quota_percentage(used, limit) := result {
+quota_percentage(used, limit) := result if {
 	limit > 0
 	result := (used / limit) * 100
 }
@@ -57,7 +57,7 @@ quota_percentage(used, limit) := result {
 #   limit: Maximum allowed amount
 # Returns: Remaining quota amount
 # Usage: remaining := quota.quota_remaining(input.environment.bandwidth_used, 100)
-quota_remaining(used, limit) := result {
+quota_remaining(used, limit) := result if {
 	result := limit - used
 }
 
@@ -69,7 +69,7 @@ quota_remaining(used, limit) := result {
 # Returns: true if usage percentage exceeds threshold, false if limit is 0 or negative
 # Usage: quota.is_approaching_limit(input.environment.bandwidth_used, 100, 80)
 # Note: Quota data should be injected via data.tenants.{tenant_id}.quotas or passed as parameters
-is_approaching_limit(used, limit, threshold_percent) {
+is_approaching_limit(used, limit, threshold_percent) if {
 	limit > 0
 	percentage := quota_percentage(used, limit)
 	percentage > threshold_percent
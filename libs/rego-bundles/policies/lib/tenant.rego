@@ -11,7 +11,7 @@ import rego.v1
 #   resource_owner_tenant: Tenant ID that owns the resource
 # Returns: true if both tenant IDs match exactly
 # Usage: tenant.matches(input.subject.tenant_id, input.resource.owner_tenant)
-matches(subject_tenant_id, resource_owner_tenant) {
+matches(subject_tenant_id, resource_owner_tenant) if {
 	subject_tenant_id == resource_owner_tenant
 }
 
@@ -21,7 +21,7 @@ matches(subject_tenant_id, resource_owner_tenant) {
 #   resource: Resource object with owner_tenant and owner_user
 # Returns: true if both tenant and user IDs match
 # Usage: tenant.is_owner(input.subject, input.resource)
-is_owner(subject, resource) {
+is_owner(subject, resource) if {
 	subject.tenant_id == resource.owner_tenant
 	subject.user_id == resource.owner_user
 }
@@ -32,7 +32,7 @@ is_owner(subject, resource) {
 #   required_level: Minimum required clearance level
 # Returns: true if subject.clearance_level >= required_level
 # Usage: tenant.has_clearance(input.subject, 2)
-has_clearance(subject, required_level) {
+has_clearance(subject, required_level) if {
 	subject.clearance_level >= required_level
 }
 
@@ -42,7 +42,7 @@ has_clearance(subject, required_level) {
 #   role_name: Role name to check for
 # Returns: true if role_name is in subject.roles
 # Usage: tenant.has_role(input.subject, "admin")
-has_role(subject, role_name) {
+has_role(subject, role_name) if {
 	role_name in subject.roles
 }
 
@@ -52,7 +52,7 @@ has_role(subject, role_name) {
 #   role_list: Array of role names to check
 # Returns: true if subject has at least one role from the list
 # Usage: tenant.has_any_role(input.subject, ["admin", "operator"])
-has_any_role(subject, role_list) {
+has_any_role(subject, role_list) if {
 	some role in role_list
 	role in subject.roles
 }
@@ -64,7 +64,7 @@ has_any_role(subject, role_list) {
 #   resource_tenant: Tenant ID that owns the resource
 # Returns: true only if both are non-empty and match exactly
 # Usage: tenant.validate_tenant_boundary(input.subject.tenant_id, input.resource.owner_tenant)
-validate_tenant_boundary(subject_tenant, resource_tenant) {
+validate_tenant_boundary(subject_tenant, resource_tenant) if {
 	subject_tenant != ""
 	resource_tenant != ""
 	subject_tenant == resource_tenant
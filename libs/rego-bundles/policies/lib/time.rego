@@ -10,7 +10,7 @@ import rego.v1
 #   timestamp: ISO 8601 timestamp string (e.g., "2025-10-16T14:30:00Z")
 # Returns: true if hour is between 9 and 16 (inclusive)
 # Usage: time.is_business_hours(input.environment.time)
-is_business_hours(timestamp) {
+is_business_hours(timestamp) if {
 	parsed := parse_iso8601(timestamp)
 	ns_per_second := 1000000000
 	ns_per_hour := ns_per_second * 3600
@@ -25,7 +25,7 @@ is_business_hours(timestamp) {
 #   timestamp: ISO 8601 timestamp string
 # Returns: true if day is Monday through Friday
 # Usage: time.is_weekday(input.environment.time)
-is_weekday(timestamp) {
+is_weekday(timestamp) if {
 	parsed := parse_iso8601(timestamp)
 	ns_per_second := 1000000000
 	ns_per_day := ns_per_second * 86400
@@ -44,7 +44,7 @@ is_weekday(timestamp) {
 #   end_time: Window end time (ISO 8601)
 # Returns: true if timestamp is between start and end
 # Usage: time.is_within_window(input.environment.time, "2025-10-16T10:00:00Z", "2025-10-16T14:00:00Z")
-is_within_window(timestamp, start_time, end_time) {
+is_within_window(timestamp, start_time, end_time) if {
 	parsed := parse_iso8601(timestamp)
 	start := parse_iso8601(start_time)
 	end := parse_iso8601(end_time)
@@ -58,7 +58,7 @@ is_within_window(timestamp, start_time, end_time) {
 #   max_age_seconds: Maximum age in seconds
 # Returns: true if timestamp is older than max_age_seconds
 # Usage: time.is_expired(input.session.created_at, 3600)
-is_expired(timestamp, max_age_seconds) {
+is_expired(timestamp, max_age_seconds) if {
 	parsed := parse_iso8601(timestamp)
 	now := time.now_ns()
 	age_ns := now - parsed
@@ -73,6 +73,6 @@ is_expired(timestamp, max_age_seconds) {
 # Returns: Nanoseconds since Unix epoch
 # Usage: parsed_ns := time.parse_iso8601("2025-10-16T14:30:00Z")
 # Note: Timestamps should be in ISO 8601 format with timezone (Z for UTC or +HH:MM offset)
-parse_iso8601(timestamp_string) := result {
+parse_iso8601(timestamp_string) := result if {
 	result := time.parse_rfc3339_ns(timestamp_string)
 }
@@ -16,17 +16,20 @@
 # Expected behavior:
 #   All checks must pass for allow to be true:
 #   1. Tenant isolation (hard boundary)
-#   2. Clearance level >= 2
+#   2. Clearance level >= min_clearance_level
 #   3. Data residency (EU resources require EU location)
-#   4. Cost guardrail (writes require quota available)
+#   4. Cost guardrail (writes require bandwidth under bandwidth_limit_gb)
 #   5. Time window (business hours or admin override)
 #
 # Customization:
-#   - Adjust bandwidth limit (default 100 GB)
+#   - Adjust bandwidth_limit_gb (default 100 GB)
+#   - Adjust min_clearance_level (default 2)
 #   - Add/remove time restrictions
-#   - Modify clearance level requirements
 #   - Add additional guardrail checks
 #
+#   instantiate_combined_guardrails() in the rego-bundles crate renders this
+#   template for a tenant, replacing the package and both limits.
+#
 # Performance note:
 #   Rego short-circuits on first failing check, so order matters.
 #   Cheapest checks (tenant isolation) are evaluated first.
@@ -61,16 +64,21 @@ import data.lib.quota
 import data.lib.tenant
 import data.lib.time
 
+# Limits applied by the checks below
+min_clearance_level := 2
+
+bandwidth_limit_gb := 100
+
 # Default deny for security
 default allow := false
 
 # Main allow rule with all checks combined
-allow {
+allow if {
 	# 1. Tenant isolation (cheapest check first)
 	tenant.validate_tenant_boundary(input.subject.tenant_id, input.resource.owner_tenant)
 
 	# 2. Clearance level check
-	tenant.has_clearance(input.subject, 2)
+	tenant.has_clearance(input.subject, min_clearance_level)
 
 	# 3. Data residency check (EU resources require EU location)
 	data_residency_check
@@ -84,12 +92,12 @@ allow {
 
 # Helper rule: Data residency validation
 # Returns true if resource is non-EU OR subject is in EU
-data_residency_check {
+data_residency_check if {
 	# Non-EU resources have no geographic restrictions
 	input.resource.region != "EU"
 }
 
-data_residency_check {
+data_residency_check if {
 	# EU resources require EU location
 	input.resource.region == "EU"
 	geo.is_eu_country(input.environment.country)
@@ -97,47 +105,47 @@ data_residency_check {
 
 # Helper rule: Quota validation
 # Returns true if action is read OR quota not exceeded
-quota_check {
+quota_check if {
 	# Reads don't count against quota
 	input.action == "read"
 }
 
-quota_check {
-	# Writes require available quota (100 GB limit)
+quota_check if {
+	# Writes require available quota
 	input.action in ["write", "upload", "publish"]
-	quota.bandwidth_within_limit(input.environment.bandwidth_used, 100)
+	quota.bandwidth_within_limit(input.environment.bandwidth_used, bandwidth_limit_gb)
 }
 
 # Helper rule: Time window validation
 # Returns true if business hours OR admin role
-time_check {
+time_check if {
 	# During business hours on weekdays
 	time.is_business_hours(input.environment.time)
 	time.is_weekday(input.environment.time)
 }
 
-time_check {
+time_check if {
 	# Admin override for time restrictions
 	tenant.has_role(input.subject, "admin")
 }
 
 # Collect all violation reasons for debugging and audit logs
-deny_reasons[reason] {
+deny_reasons contains reason if {
 	not tenant.validate_tenant_boundary(input.subject.tenant_id, input.resource.owner_tenant)
 	reason := "Cross-tenant access"
 }
 
-deny_reasons[reason] {
+deny_reasons contains reason if {
 	not data_residency_check
 	reason := "Data residency violation"
 }
 
-deny_reasons[reason] {
+deny_reasons contains reason if {
 	not quota_check
 	reason := "Quota exceeded"
 }
 
-deny_reasons[reason] {
+deny_reasons contains reason if {
 	not time_check
 	reason := "Outside business hours"
 }
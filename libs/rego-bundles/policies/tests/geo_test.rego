@@ -49,6 +49,40 @@ test_validate_data_residency_mismatch {
 	not validate_data_residency("US", "EU")
 }
 
+# Residency matrix fixture: EU data may go to UK, but not to US
+residency_fixture := {"tenant-a": {"residency_matrix": {"EU": ["UK"], "UK": ["EU", "US"]}}}
+
+# Test allowed with a transfer pair listed in the matrix
+test_allowed_transfer_in_matrix {
+	allowed("EU", "UK") with data.tenants as residency_fixture
+		with input as {"subject": {"tenant_id": "tenant-a"}}
+}
+
+# Test allowed rejects a transfer pair missing from the matrix
+test_allowed_transfer_not_in_matrix {
+	not allowed("EU", "US") with data.tenants as residency_fixture
+		with input as {"subject": {"tenant_id": "tenant-a"}}
+}
+
+# Test allowed permits transfers within the same region without a matrix entry
+test_allowed_same_region {
+	allowed("US", "US") with data.tenants as residency_fixture
+		with input as {"subject": {"tenant_id": "tenant-a"}}
+}
+
+# Test allowed does not read another tenant's matrix
+test_allowed_other_tenant_matrix {
+	not allowed("EU", "UK") with data.tenants as residency_fixture
+		with input as {"subject": {"tenant_id": "tenant-b"}}
+}
+
+# Test allowed_by with a matrix passed in by the caller
+test_allowed_by_explicit_matrix {
+	allowed_by({"EU": ["UK"]}, "EU", "UK")
+	allowed_by({}, "US", "US")
+	not allowed_by({"EU": ["UK"]}, "EU", "US")
+}
+
 # Test eu_countries set completeness
 test_eu_countries_set_completeness {
 	count(eu_countries) == 27
@@ -0,0 +1,3 @@
+package lib.latin1
+
+# caf 
@@ -28,12 +28,101 @@
 //! let template = load_template_policy("data_residency").expect("template not found");
 //! ```
 
-use include_dir::{include_dir, Dir};
+use include_dir::{include_dir, Dir, File};
 use std::collections::HashMap;
+use std::fmt;
 use tracing::warn;
 
 static POLICIES: Dir = include_dir!("$CARGO_MANIFEST_DIR/policies");
 
+/// Error returned when an embedded policy exists but cannot be loaded as text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyLoadError {
+    /// The embedded file is not valid UTF-8.
+    InvalidUtf8 {
+        /// Path of the file inside the bundle, e.g. `lib/geo.rego`
+        path: String,
+        /// Number of leading bytes that are valid UTF-8
+        valid_up_to: usize,
+    },
+}
+
+impl fmt::Display for PolicyLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyLoadError::InvalidUtf8 { path, valid_up_to } => write!(
+                f,
+                "embedded policy {path} is not valid UTF-8 (invalid byte at offset {valid_up_to})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PolicyLoadError {}
+
+/// Template rendered by [`instantiate_combined_guardrails`].
+pub const COMBINED_GUARDRAILS_TEMPLATE: &str = "combined_guardrails";
+
+/// Limits substituted into the `combined_guardrails` template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuardrailLimits {
+    /// Lowest `subject.clearance_level` that is allowed
+    pub min_clearance_level: u32,
+    /// Bandwidth in GB after which writes are refused
+    pub bandwidth_limit_gb: u64,
+}
+
+impl Default for GuardrailLimits {
+    fn default() -> Self {
+        Self {
+            min_clearance_level: 2,
+            bandwidth_limit_gb: 100,
+        }
+    }
+}
+
+/// Error returned when a template cannot be rendered for a tenant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// Tenant IDs may only contain ASCII letters, digits, `-` and `_`.
+    InvalidTenantId(String),
+    /// The template is not embedded.
+    NotFound(&'static str),
+    /// The template no longer has a line the renderer replaces, e.g. `package`.
+    MissingPlaceholder(&'static str),
+    /// The template is embedded but cannot be read as text.
+    Load(PolicyLoadError),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::InvalidTenantId(tenant_id) => write!(
+                f,
+                "invalid tenant id `{tenant_id}`: only ASCII letters, digits, `-` and `_` are allowed"
+            ),
+            TemplateError::NotFound(name) => write!(f, "template {name} is not embedded"),
+            TemplateError::MissingPlaceholder(placeholder) => {
+                write!(f, "template has no `{placeholder}` line to replace")
+            }
+            TemplateError::Load(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// An embedded `.rego` file returned by [`find_policies_containing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyFile {
+    /// Directory the file belongs to
+    pub category: PolicyCategory,
+    /// File name without extension, e.g. `quota`
+    pub name: &'static str,
+    /// Path inside the bundle, e.g. `lib/quota.rego`
+    pub path: &'static str,
+}
+
 /// Policy category for filtering embedded policies
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PolicyCategory {
@@ -127,64 +216,134 @@ pub fn load_template(name: &str) -> Option<&'static str> {
 ///
 /// Example: ["geo", "quota", "tenant", "time"]
 pub fn list_helpers() -> Vec<&'static str> {
-    POLICIES
-        .files()
-        .filter_map(|file| {
-            let path = file.path();
-            // Check if file is in lib/ subdirectory
-            if path.starts_with("lib/") && path.extension()? == "rego" {
-                // Extract filename without extension
-                path.file_stem()?.to_str()
-            } else {
-                None
-            }
-        })
-        .collect()
+    list_by_category(PolicyCategory::Helper)
 }
 
 /// Loads a helper module by name from lib/ subdirectory.
 ///
 /// Example: `load_helper("geo")` loads `lib/geo.rego`
+///
+/// Returns `None` both when the helper is missing and when it is not valid
+/// UTF-8; use [`try_load_helper`] to tell the two apart.
 pub fn load_helper(name: &str) -> Option<&'static str> {
-    let path = format!("lib/{}.rego", name);
-    let file = POLICIES.get_file(&path)?;
-    let contents = file.contents_utf8();
-    if contents.is_none() {
-        warn!(target: "edge-policy-rego-bundles", "helper module {name} is not valid UTF-8");
-    }
-    contents
+    try_load_helper(name).unwrap_or_else(|err| {
+        warn!(target: "edge-policy-rego-bundles", "helper module {name}: {err}");
+        None
+    })
+}
+
+/// Loads a helper module by name, distinguishing a missing helper (`Ok(None)`)
+/// from one that is not valid UTF-8 (`Err`).
+pub fn try_load_helper(name: &str) -> Result<Option<&'static str>, PolicyLoadError> {
+    load_utf8(&POLICIES, &helper_path(name))
+}
+
+/// Returns the raw bytes of a helper module, or `None` if it does not exist.
+pub fn load_helper_bytes(name: &str) -> Option<&'static [u8]> {
+    load_bytes(&POLICIES, &helper_path(name))
 }
 
 /// Returns the list of template policy names from templates/ subdirectory.
 ///
 /// Example: ["data_residency", "cost_guardrail", "multi_tenant_separation", "combined_guardrails"]
 pub fn list_template_policies() -> Vec<&'static str> {
-    POLICIES
-        .files()
-        .filter_map(|file| {
-            let path = file.path();
-            // Check if file is in templates/ subdirectory
-            if path.starts_with("templates/") && path.extension()? == "rego" {
-                // Extract filename without extension
-                path.file_stem()?.to_str()
-            } else {
-                None
-            }
-        })
-        .collect()
+    list_by_category(PolicyCategory::Template)
 }
 
 /// Loads a template policy by name from templates/ subdirectory.
 ///
 /// Example: `load_template_policy("data_residency")` loads `templates/data_residency.rego`
+///
+/// Returns `None` both when the template is missing and when it is not valid
+/// UTF-8; use [`try_load_template_policy`] to tell the two apart.
 pub fn load_template_policy(name: &str) -> Option<&'static str> {
-    let path = format!("templates/{}.rego", name);
-    let file = POLICIES.get_file(&path)?;
-    let contents = file.contents_utf8();
-    if contents.is_none() {
-        warn!(target: "edge-policy-rego-bundles", "template policy {name} is not valid UTF-8");
+    try_load_template_policy(name).unwrap_or_else(|err| {
+        warn!(target: "edge-policy-rego-bundles", "template policy {name}: {err}");
+        None
+    })
+}
+
+/// Loads a template policy by name, distinguishing a missing template
+/// (`Ok(None)`) from one that is not valid UTF-8 (`Err`).
+pub fn try_load_template_policy(name: &str) -> Result<Option<&'static str>, PolicyLoadError> {
+    load_utf8(&POLICIES, &template_path(name))
+}
+
+/// Returns the raw bytes of a template policy, or `None` if it does not exist.
+pub fn load_template_bytes(name: &str) -> Option<&'static [u8]> {
+    load_bytes(&POLICIES, &template_path(name))
+}
+
+/// Renders the `combined_guardrails` template as a deployable policy for
+/// `tenant_id`.
+///
+/// The package becomes `tenants.<tenant_id>` (quoted as
+/// `tenants["<tenant_id>"]` when the ID is not a Rego identifier) and the
+/// clearance and bandwidth limits are taken from `limits`. The policy still
+/// imports `data.lib.*`, so the tenant bundle must ship the helpers from
+/// [`load_all_helpers`] alongside it.
+pub fn instantiate_combined_guardrails(
+    tenant_id: &str,
+    limits: &GuardrailLimits,
+) -> Result<String, TemplateError> {
+    if tenant_id.is_empty()
+        || !tenant_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(TemplateError::InvalidTenantId(tenant_id.to_string()));
+    }
+
+    let template = try_load_template_policy(COMBINED_GUARDRAILS_TEMPLATE)
+        .map_err(TemplateError::Load)?
+        .ok_or(TemplateError::NotFound(COMBINED_GUARDRAILS_TEMPLATE))?;
+
+    let replacements = [
+        ("package", tenant_package(tenant_id)),
+        (
+            "min_clearance_level :=",
+            format!("min_clearance_level := {}", limits.min_clearance_level),
+        ),
+        (
+            "bandwidth_limit_gb :=",
+            format!("bandwidth_limit_gb := {}", limits.bandwidth_limit_gb),
+        ),
+    ];
+    let mut replaced = [false; 3];
+    let mut rendered = String::with_capacity(template.len());
+    for line in template.lines() {
+        let target = replacements
+            .iter()
+            .position(|(prefix, _)| line.starts_with(&format!("{prefix} ")));
+        match target {
+            Some(index) if !replaced[index] => {
+                replaced[index] = true;
+                rendered.push_str(&replacements[index].1);
+            }
+            _ => rendered.push_str(line),
+        }
+        rendered.push('\n');
+    }
+
+    if let Some(index) = replaced.iter().position(|done| !done) {
+        return Err(TemplateError::MissingPlaceholder(replacements[index].0));
+    }
+    Ok(rendered)
+}
+
+fn tenant_package(tenant_id: &str) -> String {
+    let is_identifier = tenant_id
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && tenant_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        format!("package tenants.{tenant_id}")
+    } else {
+        format!("package tenants[\"{tenant_id}\"]")
     }
-    contents
 }
 
 /// Loads all helper modules into a HashMap.
@@ -203,7 +362,8 @@ pub fn load_all_helpers() -> HashMap<String, &'static str> {
 
 /// Returns files filtered by category.
 ///
-/// Filters embedded policies by category (Helper, Template, or Test).
+/// Filters embedded policies by category (Helper, Template, or Test) and
+/// returns their names without extension, sorted.
 pub fn list_by_category(category: PolicyCategory) -> Vec<&'static str> {
     let prefix = match category {
         PolicyCategory::Helper => "lib/",
@@ -211,15 +371,195 @@ pub fn list_by_category(category: PolicyCategory) -> Vec<&'static str> {
         PolicyCategory::Test => "tests/",
     };
 
-    POLICIES
-        .files()
+    let mut names: Vec<&'static str> = rego_files(&POLICIES)
+        .into_iter()
         .filter_map(|file| {
             let path = file.path();
-            if path.starts_with(prefix) && path.extension()? == "rego" {
+            if path.starts_with(prefix) {
                 path.file_stem()?.to_str()
             } else {
                 None
             }
         })
-        .collect()
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+/// Returns every embedded `.rego` file whose contents contain `needle`,
+/// compared case-insensitively, sorted by path.
+///
+/// Useful for finding which modules define or reference a rule, e.g.
+/// `find_policies_containing("data.lib.quota")`. Files that are not valid
+/// UTF-8 are skipped.
+pub fn find_policies_containing(needle: &str) -> Vec<PolicyFile> {
+    let needle = needle.to_lowercase();
+    let mut matches: Vec<PolicyFile> = rego_files(&POLICIES)
+        .into_iter()
+        .filter(|file| {
+            file.contents_utf8()
+                .is_some_and(|contents| contents.to_lowercase().contains(&needle))
+        })
+        .filter_map(policy_file)
+        .collect();
+    matches.sort_by_key(|file| file.path);
+    matches
+}
+
+/// Collects `.rego` files from `dir` and all of its subdirectories.
+fn rego_files(dir: &'static Dir<'static>) -> Vec<&'static File<'static>> {
+    let mut files: Vec<_> = dir
+        .files()
+        .filter(|file| file.path().extension().is_some_and(|ext| ext == "rego"))
+        .collect();
+    for subdir in dir.dirs() {
+        files.extend(rego_files(subdir));
+    }
+    files
+}
+
+fn policy_file(file: &'static File<'static>) -> Option<PolicyFile> {
+    let path = file.path();
+    let category = if path.starts_with("lib/") {
+        PolicyCategory::Helper
+    } else if path.starts_with("templates/") {
+        PolicyCategory::Template
+    } else if path.starts_with("tests/") {
+        PolicyCategory::Test
+    } else {
+        return None;
+    };
+
+    Some(PolicyFile {
+        category,
+        name: path.file_stem()?.to_str()?,
+        path: path.to_str()?,
+    })
+}
+
+fn helper_path(name: &str) -> String {
+    format!("lib/{}.rego", name)
+}
+
+fn template_path(name: &str) -> String {
+    format!("templates/{}.rego", name)
+}
+
+fn load_bytes(dir: &'static Dir<'static>, path: &str) -> Option<&'static [u8]> {
+    dir.get_file(path).map(|file| file.contents())
+}
+
+fn load_utf8(
+    dir: &'static Dir<'static>,
+    path: &str,
+) -> Result<Option<&'static str>, PolicyLoadError> {
+    let Some(bytes) = load_bytes(dir, path) else {
+        return Ok(None);
+    };
+    std::str::from_utf8(bytes)
+        .map(Some)
+        .map_err(|err| PolicyLoadError::InvalidUtf8 {
+            path: path.to_string(),
+            valid_up_to: err.valid_up_to(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bundle-shaped fixtures that must never ship in `POLICIES`.
+    static FIXTURES: Dir = include_dir!("$CARGO_MANIFEST_DIR/src/fixtures");
+
+    #[test]
+    fn missing_policy_is_not_an_error() {
+        assert_eq!(try_load_helper("does_not_exist"), Ok(None));
+        assert_eq!(try_load_template_policy("does_not_exist"), Ok(None));
+        assert!(load_helper_bytes("does_not_exist").is_none());
+        assert!(load_template_bytes("does_not_exist").is_none());
+        assert!(load_helper("does_not_exist").is_none());
+    }
+
+    #[test]
+    fn embedded_policies_load_as_text_and_bytes() {
+        let geo = try_load_helper("geo")
+            .unwrap()
+            .expect("geo helper is embedded");
+        assert_eq!(load_helper_bytes("geo"), Some(geo.as_bytes()));
+        assert!(try_load_template_policy("data_residency")
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn every_helper_is_listed_and_compiles() {
+        assert_eq!(list_helpers(), vec!["geo", "quota", "tenant", "time"]);
+        assert!(list_template_policies().contains(&"data_residency"));
+
+        for (name, source) in load_all_helpers() {
+            regorus::Engine::new()
+                .add_policy(format!("lib/{name}.rego"), source.to_string())
+                .unwrap_or_else(|err| panic!("helper {name} should compile: {err}"));
+        }
+    }
+
+    #[test]
+    fn content_search_finds_rule_in_expected_files() {
+        let matches = find_policies_containing("IS_APPROACHING_LIMIT");
+        let paths: Vec<_> = matches.iter().map(|file| file.path).collect();
+        assert_eq!(paths, vec!["lib/quota.rego", "tests/quota_test.rego"]);
+
+        assert_eq!(matches[0].category, PolicyCategory::Helper);
+        assert_eq!(matches[0].name, "quota");
+        assert_eq!(matches[1].category, PolicyCategory::Test);
+
+        assert!(find_policies_containing("no_such_rule_anywhere").is_empty());
+    }
+
+    #[test]
+    fn combined_guardrails_renders_for_tenant() {
+        let limits = GuardrailLimits {
+            min_clearance_level: 3,
+            bandwidth_limit_gb: 250,
+        };
+        let rego = instantiate_combined_guardrails("tenant-x", &limits).unwrap();
+
+        assert!(rego.contains("package tenants[\"tenant-x\"]\n"));
+        assert!(!rego.contains("package templates."));
+        assert!(rego.contains("min_clearance_level := 3\n"));
+        assert!(rego.contains("bandwidth_limit_gb := 250\n"));
+        for helper in ["geo", "quota", "tenant", "time"] {
+            assert!(rego.contains(&format!("import data.lib.{helper}\n")));
+        }
+        regorus::Engine::new()
+            .add_policy("tenant-x.rego".to_string(), rego)
+            .expect("rendered template should be valid Rego");
+
+        let rego =
+            instantiate_combined_guardrails("tenant_x", &GuardrailLimits::default()).unwrap();
+        assert!(rego.contains("package tenants.tenant_x\n"));
+        assert!(rego.contains("min_clearance_level := 2\n"));
+
+        assert_eq!(
+            instantiate_combined_guardrails("tenant x", &limits),
+            Err(TemplateError::InvalidTenantId("tenant x".to_string()))
+        );
+    }
+
+    #[test]
+    fn non_utf8_policy_is_reported_as_corrupt() {
+        let path = "lib/latin1.rego";
+        assert!(load_bytes(&FIXTURES, path).is_some());
+
+        match load_utf8(&FIXTURES, path) {
+            Err(PolicyLoadError::InvalidUtf8 {
+                path: reported,
+                valid_up_to,
+            }) => {
+                assert_eq!(reported, path);
+                assert_eq!(valid_up_to, "package lib.latin1\n\n# caf".len());
+            }
+            other => panic!("expected invalid UTF-8 error, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,104 @@
+//! `KEY=VALUE` files re-read by services on SIGHUP.
+//!
+//! The file is parsed into a map rather than copied into the process
+//! environment, so reloading never calls `std::env::set_var` while other
+//! threads may be reading the environment. Callers layer the values over
+//! their usual variable lookup with [`EnvFile::over`].
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("failed to read env file '{}'", path.display())]
+pub struct EnvFileError {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+}
+
+/// Variables read from an env file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvFile {
+    vars: BTreeMap<String, String>,
+}
+
+impl EnvFile {
+    /// Read and parse the file at `path`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, EnvFileError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|source| EnvFileError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self::parse(&contents))
+    }
+
+    /// Parse `KEY=VALUE` lines. Blank lines and `#` comments are skipped,
+    /// surrounding double quotes are stripped from values and malformed lines
+    /// are logged and ignored.
+    pub fn parse(contents: &str) -> Self {
+        let mut vars = BTreeMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once('=') {
+                Some((key, value)) => {
+                    vars.insert(
+                        key.trim().to_string(),
+                        value.trim().trim_matches('"').to_string(),
+                    );
+                }
+                None => tracing::warn!(line, "ignoring malformed line in env file"),
+            }
+        }
+        Self { vars }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
+
+    /// Look up `name` in the file, falling back to `env`.
+    pub fn over<'a>(
+        &'a self,
+        env: &'a dyn Fn(&str) -> Option<String>,
+    ) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| self.get(name).map(str::to_string).or_else(|| env(name))
+    }
+}
+
+/// Read `name` from the process environment; the lookup services pass to
+/// their configuration loaders outside of tests.
+pub fn process_env(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_values_and_skips_comments_and_malformed_lines() {
+        let file =
+            EnvFile::parse("# reload settings\n\nLOG_LEVEL = \"debug\"\nnot a pair\nPORT=8080\n");
+
+        assert_eq!(file.get("LOG_LEVEL"), Some("debug"));
+        assert_eq!(file.get("PORT"), Some("8080"));
+        assert_eq!(file.get("not a pair"), None);
+    }
+
+    #[test]
+    fn file_values_take_precedence_over_the_fallback() {
+        let file = EnvFile::parse("LOG_LEVEL=debug\n");
+        let fallback = |name: &str| (name != "UNSET").then(|| format!("env:{name}"));
+        let lookup = file.over(&fallback);
+
+        assert_eq!(lookup("LOG_LEVEL").as_deref(), Some("debug"));
+        assert_eq!(lookup("PORT").as_deref(), Some("env:PORT"));
+        assert_eq!(lookup("UNSET"), None);
+    }
+}
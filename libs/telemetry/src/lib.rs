@@ -0,0 +1,173 @@
+//! Shared tracing setup for Edge Policy Hub services.
+//!
+//! Every service installs the same subscriber stack: a reloadable
+//! [`EnvFilter`] followed by a formatting layer. The output format is chosen
+//! with the `LOG_FORMAT` environment variable; `compact` (the default) keeps
+//! the human-readable single-line output, while `json` emits one JSON object
+//! per line for log aggregators.
+
+use std::str::FromStr;
+
+mod env_file;
+
+pub use env_file::{process_env, EnvFile, EnvFileError};
+
+use thiserror::Error;
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::TryInitError;
+use tracing_subscriber::{fmt, reload, EnvFilter, Layer, Registry};
+
+/// Environment variable selecting the log output format.
+pub const LOG_FORMAT_VAR: &str = "LOG_FORMAT";
+
+/// Handle used to swap the active log filter at runtime.
+pub type LogReloadHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Output format for log lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Single-line human-readable output.
+    #[default]
+    Compact,
+    /// Newline-delimited JSON objects.
+    Json,
+}
+
+#[derive(Debug, Error)]
+#[error("unsupported {LOG_FORMAT_VAR} '{0}' (expected 'compact' or 'json')")]
+pub struct UnknownLogFormat(String);
+
+impl LogFormat {
+    /// Read the format from `LOG_FORMAT`, defaulting to compact when unset.
+    pub fn from_env() -> Result<Self, UnknownLogFormat> {
+        match std::env::var(LOG_FORMAT_VAR) {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = UnknownLogFormat;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "compact" | "text" => Ok(Self::Compact),
+            "json" => Ok(Self::Json),
+            _ => Err(UnknownLogFormat(value.to_string())),
+        }
+    }
+}
+
+/// Build the formatting layer for `format`, writing to `writer`.
+pub fn fmt_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Compact => fmt::layer()
+            .with_writer(writer)
+            .with_target(false)
+            .compact()
+            .boxed(),
+        LogFormat::Json => fmt::layer()
+            .with_writer(writer)
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    }
+}
+
+/// Install the global subscriber, returning a handle for reloading the filter.
+///
+/// Fails if a global subscriber has already been set.
+pub fn try_init_tracing(
+    filter: EnvFilter,
+    format: LogFormat,
+) -> Result<LogReloadHandle, TryInitError> {
+    let (filter, handle) = reload::Layer::new(filter);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer(format, std::io::stdout))
+        .try_init()?;
+
+    Ok(handle)
+}
+
+/// Install the global subscriber, panicking if one has already been set.
+pub fn init_tracing(filter: EnvFilter, format: LogFormat) -> LogReloadHandle {
+    try_init_tracing(filter, format).expect("failed to install tracing subscriber")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct CapturedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for CapturedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for CapturedOutput {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn parses_supported_formats() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!(" JSON ".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("compact".parse::<LogFormat>().unwrap(), LogFormat::Compact);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn json_format_emits_one_parseable_object_per_line() {
+        let output = CapturedOutput::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(EnvFilter::new("info"))
+            .with(fmt_layer(LogFormat::Json, output.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", tenant_id = "tenant-a");
+            let _entered = span.enter();
+            tracing::info!(status = 200, "request completed");
+            tracing::warn!("quota nearly exhausted");
+        });
+
+        let bytes = output.0.lock().unwrap().clone();
+        let text = String::from_utf8(bytes).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("log line is valid JSON"))
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["message"], "request completed");
+        assert_eq!(lines[0]["status"], 200);
+        assert_eq!(lines[0]["span"]["tenant_id"], "tenant-a");
+        assert_eq!(lines[1]["level"], "WARN");
+    }
+}
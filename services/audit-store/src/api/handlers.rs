@@ -1,40 +1,221 @@
 use std::sync::Arc;
 
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
 use serde::Deserialize;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::signing::SigningError;
 use crate::storage::database::LogFilter;
-use crate::storage::policy_bundles::PolicyBundleRecord;
+use crate::storage::policy_bundles::{
+    ActivationReservation, BundleChange, BundleHistoryEntry, PolicyBundleRecord,
+    BUNDLE_STATUS_DRAFT, BUNDLE_STATUS_STAGED,
+};
 use crate::storage::tenant_registry::TenantRecord;
+use crate::storage::{redact_audit_entry, StorageError};
 
+use super::scope::TenantScope;
 use super::types::{
-    AuditLogEntry, AuditLogRequest, AuditLogResponse, ErrorResponse, MarkUploadedRequest,
-    QueryLogsRequest, QueryLogsResponse, TenantRequest, TenantResponse, UnuploadedQuery,
-    UpdateTenantRequest,
+    ActivateBundleRequest, AuditLogEntry, AuditLogRequest, AuditLogResponse, BulkIngestResponse,
+    BulkLineError, DeadLetterQuery, DeadLetterResponse, DslBundleRequest, ErrorResponse,
+    MarkUploadedRequest, PromoteBundleRequest, QueryLogsRequest, QueryLogsResponse,
+    RetryDeadLetterRequest, RetryDeadLetterResponse, TenantRequest, TenantResponse,
+    UnuploadedQuery, UpdateTenantRequest, VerifyBatchRequest, VerifyBatchResponse,
 };
 use super::ApiState;
 
 type ApiResult<T> = Result<Json<T>, (StatusCode, Json<ErrorResponse>)>;
 
+/// Header a client sets to make bundle activation safe to retry.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// How long a retry waits for a concurrent activation holding its
+/// idempotency key before answering `409`.
+const IDEMPOTENCY_WAIT: std::time::Duration = std::time::Duration::from_secs(5);
+const IDEMPOTENCY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
 pub async fn write_audit_log(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Json(request): Json<AuditLogRequest>,
-) -> ApiResult<AuditLogResponse> {
-    if state
+) -> Result<Json<AuditLogResponse>, Response> {
+    let tenant = registered_tenant(&state, &scope, &request.tenant_id)
+        .map_err(IntoResponse::into_response)?;
+    let slot = state
+        .ingest_limiter
+        .try_acquire(&tenant.tenant_id)
+        .map_err(|retry_after| {
+            warn!(tenant_id = %tenant.tenant_id, "audit log ingestion rate limit exceeded");
+            too_many_requests(retry_after)
+        })?;
+
+    // Only stored entries count toward the limit.
+    store_audit_log(&state, &tenant, request).map_err(|rejection| {
+        state.ingest_limiter.release(slot);
+        rejection.into_response()
+    })
+}
+
+/// Longest NDJSON line `POST /api/logs/bulk` buffers before rejecting it.
+pub const MAX_BULK_LINE_BYTES: usize = 1024 * 1024;
+
+/// Stores an NDJSON stream of audit entries, signing and writing each line as
+/// it arrives instead of buffering the whole body. Lines that cannot be
+/// stored are reported in the summary without stopping the import.
+pub async fn bulk_write_audit_logs(
+    State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
+    body: Body,
+) -> ApiResult<BulkIngestResponse> {
+    let mut summary = BulkIngestResponse::default();
+    let mut stream = body.into_data_stream();
+    let mut pending = Vec::new();
+    let mut line = 0;
+    // Set while the rest of an over-long line is being skipped.
+    let mut oversized = false;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| {
+            bad_request(
+                "invalid_body",
+                &format!("failed to read request body: {}", err),
+            )
+        })?;
+
+        for segment in chunk.split_inclusive(|byte| *byte == b'\n') {
+            if !oversized {
+                pending.extend_from_slice(segment);
+                oversized = pending.len() > MAX_BULK_LINE_BYTES;
+            }
+            if !segment.ends_with(b"\n") {
+                continue;
+            }
+
+            line += 1;
+            ingest_bulk_line(
+                &state,
+                &scope,
+                line,
+                (!oversized).then_some(&pending[..]),
+                &mut summary,
+            );
+            pending.clear();
+            oversized = false;
+        }
+    }
+
+    if oversized || !pending.is_empty() {
+        line += 1;
+        ingest_bulk_line(
+            &state,
+            &scope,
+            line,
+            (!oversized).then_some(&pending[..]),
+            &mut summary,
+        );
+    }
+
+    info!(
+        accepted = summary.accepted,
+        rejected = summary.rejected,
+        "bulk audit log import finished"
+    );
+    Ok(Json(summary))
+}
+
+/// Stores one bulk line, or records why it was rejected. `None` stands for a
+/// line longer than [`MAX_BULK_LINE_BYTES`]; blank lines are skipped.
+fn ingest_bulk_line(
+    state: &ApiState,
+    scope: &TenantScope,
+    line: usize,
+    bytes: Option<&[u8]>,
+    summary: &mut BulkIngestResponse,
+) {
+    let mut reject = |code: &str, error: String| {
+        summary.rejected += 1;
+        summary.errors.push(BulkLineError {
+            line,
+            code: code.to_string(),
+            error,
+        });
+    };
+
+    let Some(bytes) = bytes else {
+        reject(
+            "line_too_long",
+            format!("line exceeds {} bytes", MAX_BULK_LINE_BYTES),
+        );
+        return;
+    };
+    if bytes.trim_ascii().is_empty() {
+        return;
+    }
+
+    let request: AuditLogRequest = match serde_json::from_slice(bytes) {
+        Ok(request) => request,
+        Err(err) => {
+            reject("invalid_json", err.to_string());
+            return;
+        }
+    };
+
+    let tenant = match registered_tenant(state, scope, &request.tenant_id) {
+        Ok(tenant) => tenant,
+        Err((_, Json(err))) => {
+            reject(&err.code, err.error);
+            return;
+        }
+    };
+    let Ok(slot) = state.ingest_limiter.try_acquire(&tenant.tenant_id) else {
+        reject(
+            "rate_limited",
+            "audit log ingestion rate limit exceeded".to_string(),
+        );
+        return;
+    };
+
+    match store_audit_log(state, &tenant, request) {
+        Ok(_) => summary.accepted += 1,
+        Err((_, Json(err))) => {
+            state.ingest_limiter.release(slot);
+            reject(&err.code, err.error);
+        }
+    }
+}
+
+/// The registered tenant an audit write is for, checked against the caller's
+/// scope. Runs before the rate limiter so unknown tenants never get a window.
+fn registered_tenant(
+    state: &ApiState,
+    scope: &TenantScope,
+    tenant_id: &str,
+) -> Result<TenantRecord, (StatusCode, Json<ErrorResponse>)> {
+    scope.check(tenant_id)?;
+
+    match state
         .tenant_registry
-        .get_tenant(&request.tenant_id)
+        .get_tenant(tenant_id)
         .map_err(|err| internal_error(err))?
-        .is_none()
     {
-        return Err(not_found("tenant_not_found", "tenant not registered"));
+        Some(tenant) => Ok(tenant),
+        None => Err(not_found("tenant_not_found", "tenant not registered")),
     }
+}
+
+fn store_audit_log(
+    state: &ApiState,
+    tenant: &TenantRecord,
+    request: AuditLogRequest,
+) -> ApiResult<AuditLogResponse> {
 
     // Validate timestamp format
     if let Err(e) = DateTime::parse_from_rfc3339(&request.timestamp) {
@@ -63,6 +244,17 @@ pub async fn write_audit_log(
         uploaded: false,
     };
 
+    // Redact before signing so the stored signature covers what is persisted.
+    let redacted = redact_audit_entry(&mut entry, &tenant.audit_redaction_paths());
+    if redacted > 0 {
+        debug!(
+            tenant_id = %request.tenant_id,
+            log_id = %log_id,
+            redacted,
+            "redacted audit log attributes"
+        );
+    }
+
     entry.signature = state
         .signer
         .sign_audit_log(&entry)
@@ -89,8 +281,11 @@ pub async fn write_audit_log(
 
 pub async fn query_audit_logs(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Query(request): Query<QueryLogsRequest>,
 ) -> ApiResult<QueryLogsResponse> {
+    scope.check(&request.tenant_id)?;
+
     if state
         .tenant_registry
         .get_tenant(&request.tenant_id)
@@ -118,8 +313,11 @@ pub async fn query_audit_logs(
 
 pub async fn get_unuploaded_logs(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Query(query): Query<UnuploadedQuery>,
 ) -> ApiResult<QueryLogsResponse> {
+    scope.check(&query.tenant_id)?;
+
     if state
         .tenant_registry
         .get_tenant(&query.tenant_id)
@@ -140,8 +338,11 @@ pub async fn get_unuploaded_logs(
 
 pub async fn mark_uploaded(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Json(request): Json<MarkUploadedRequest>,
 ) -> ApiResult<QueryLogsResponse> {
+    scope.check(&request.tenant_id)?;
+
     if request.log_ids.is_empty() {
         return Err(bad_request(
             "invalid_request",
@@ -157,10 +358,99 @@ pub async fn mark_uploaded(
     Ok(Json(QueryLogsResponse { logs: vec![] }))
 }
 
+pub async fn list_dead_letter_logs(
+    State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
+    Query(query): Query<DeadLetterQuery>,
+) -> ApiResult<DeadLetterResponse> {
+    scope.check(&query.tenant_id)?;
+
+    if state
+        .tenant_registry
+        .get_tenant(&query.tenant_id)
+        .map_err(|err| internal_error(err))?
+        .is_none()
+    {
+        return Err(not_found("tenant_not_found", "tenant not registered"));
+    }
+
+    let limit = query.limit.unwrap_or(state.config.upload_batch_size);
+    let logs = state
+        .database
+        .list_dead_letter_logs(&query.tenant_id, limit)
+        .map_err(|err| internal_error(err))?;
+
+    Ok(Json(DeadLetterResponse { logs }))
+}
+
+pub async fn retry_dead_letter_logs(
+    State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
+    Json(request): Json<RetryDeadLetterRequest>,
+) -> ApiResult<RetryDeadLetterResponse> {
+    scope.check(&request.tenant_id)?;
+
+    if state
+        .tenant_registry
+        .get_tenant(&request.tenant_id)
+        .map_err(|err| internal_error(err))?
+        .is_none()
+    {
+        return Err(not_found("tenant_not_found", "tenant not registered"));
+    }
+
+    let requeued = state
+        .database
+        .requeue_dead_letter_logs(&request.tenant_id)
+        .map_err(|err| internal_error(err))?;
+
+    Ok(Json(RetryDeadLetterResponse {
+        tenant_id: request.tenant_id,
+        requeued,
+    }))
+}
+
+pub async fn verify_upload_batch(
+    State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
+    Json(request): Json<VerifyBatchRequest>,
+) -> ApiResult<VerifyBatchResponse> {
+    scope.check(&request.manifest.tenant_id)?;
+
+    let manifest = request.manifest;
+    let reason = match manifest.verify(&state.signer, &request.logs) {
+        Ok(()) => None,
+        Err(err @ (SigningError::SignatureMismatch | SigningError::ManifestMismatch(_))) => {
+            Some(err.to_string())
+        }
+        Err(SigningError::EncodingError(message)) => {
+            return Err(bad_request("invalid_manifest", &message));
+        }
+        Err(other) => return Err(internal_error(other)),
+    };
+
+    info!(
+        tenant_id = %manifest.tenant_id,
+        entries = manifest.entries.len(),
+        valid = reason.is_none(),
+        "verified audit batch manifest"
+    );
+
+    Ok(Json(VerifyBatchResponse {
+        valid: reason.is_none(),
+        tenant_id: manifest.tenant_id,
+        entries: manifest.entries.len(),
+        reason,
+    }))
+}
+
 pub async fn create_tenant(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Json(request): Json<TenantRequest>,
 ) -> ApiResult<TenantResponse> {
+    scope.check(&request.tenant_id)?;
+
     if request.tenant_id.trim().is_empty() {
         return Err(bad_request("invalid_tenant_id", "tenant_id cannot be empty"));
     }
@@ -178,7 +468,13 @@ pub async fn create_tenant(
     state
         .tenant_registry
         .create_tenant(&record)
-        .map_err(|err| internal_error(err))?;
+        .map_err(|err| match err {
+            StorageError::TenantAlreadyExists(tenant_id) => conflict(
+                "tenant_exists",
+                &format!("tenant {tenant_id} already exists"),
+            ),
+            other => internal_error(other),
+        })?;
 
     info!(tenant_id = %request.tenant_id, "registered tenant");
 
@@ -187,8 +483,11 @@ pub async fn create_tenant(
 
 pub async fn get_tenant(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Path(tenant_id): Path<String>,
 ) -> ApiResult<TenantResponse> {
+    scope.check(&tenant_id)?;
+
     let tenant = state
         .tenant_registry
         .get_tenant(&tenant_id)
@@ -202,9 +501,12 @@ pub async fn get_tenant(
 
 pub async fn update_tenant(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Path(tenant_id): Path<String>,
     Json(request): Json<UpdateTenantRequest>,
 ) -> ApiResult<TenantResponse> {
+    scope.check(&tenant_id)?;
+
     let existing = state
         .tenant_registry
         .get_tenant(&tenant_id)
@@ -242,14 +544,18 @@ pub async fn update_tenant(
         .map_err(|err| internal_error(err))?;
 
     info!(tenant_id = %tenant_id, "updated tenant");
+    state.tenant_notifier.notify_tenant_updated(&tenant_id);
 
     Ok(Json(TenantResponse::from(record)))
 }
 
 pub async fn delete_tenant(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Path(tenant_id): Path<String>,
 ) -> ApiResult<serde_json::Value> {
+    scope.check(&tenant_id)?;
+
     let existing = state
         .tenant_registry
         .get_tenant(&tenant_id)
@@ -270,6 +576,7 @@ pub async fn delete_tenant(
         .map_err(|err| internal_error(err))?;
 
     info!(tenant_id = %tenant_id, "deleted tenant (soft delete)");
+    state.tenant_notifier.notify_tenant_updated(&tenant_id);
 
     Ok(Json(serde_json::json!({
         "status": "deleted",
@@ -287,10 +594,20 @@ pub struct PolicyBundlesQuery {
     pub tenant_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SelectBundleQuery {
+    pub tenant_id: String,
+    pub subject: String,
+}
+
+/// Lists registered tenants; a scoped caller only sees its own tenant.
 pub async fn list_tenants(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Query(query): Query<ListTenantsQuery>,
 ) -> ApiResult<Vec<TenantResponse>> {
+    let scoped_tenant = scope.tenant()?;
+
     let tenants = state
         .tenant_registry
         .list_tenants(query.status.as_deref())
@@ -299,6 +616,7 @@ pub async fn list_tenants(
     Ok(Json(
         tenants
             .into_iter()
+            .filter(|tenant| scoped_tenant.map_or(true, |scope| scope == tenant.tenant_id))
             .map(TenantResponse::from)
             .collect::<Vec<_>>(),
     ))
@@ -306,8 +624,11 @@ pub async fn list_tenants(
 
 pub async fn create_policy_bundle(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Json(request): Json<PolicyBundleRecord>,
 ) -> ApiResult<PolicyBundleRecord> {
+    scope.check(&request.tenant_id)?;
+
     if state
         .tenant_registry
         .get_tenant(&request.tenant_id)
@@ -338,10 +659,84 @@ pub async fn create_policy_bundle(
     Ok(Json(stored))
 }
 
+/// Compiles a DSL policy and stores it as a draft bundle, keeping the DSL
+/// source in the bundle metadata next to the generated Rego.
+pub async fn create_policy_bundle_from_dsl(
+    State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
+    Json(request): Json<DslBundleRequest>,
+) -> ApiResult<PolicyBundleRecord> {
+    scope.check(&request.tenant_id)?;
+
+    if state
+        .tenant_registry
+        .get_tenant(&request.tenant_id)
+        .map_err(internal_error)?
+        .is_none()
+    {
+        return Err(not_found("tenant_not_found", "tenant not registered"));
+    }
+
+    let mut metadata = match request.metadata {
+        Some(serde_json::Value::Object(map)) => map,
+        None => serde_json::Map::new(),
+        Some(_) => {
+            return Err(bad_request(
+                "invalid_metadata",
+                "metadata must be a JSON object",
+            ))
+        }
+    };
+
+    let compiled = edge_policy_dsl::compile_policy(&request.dsl_source, &request.tenant_id, None)
+        .map_err(|err| bad_request("invalid_policy", &err.to_string()))?;
+
+    metadata.insert(
+        "dsl_source".to_string(),
+        serde_json::Value::String(request.dsl_source),
+    );
+
+    let record = PolicyBundleRecord {
+        bundle_id: Uuid::new_v4().to_string(),
+        tenant_id: request.tenant_id,
+        version: 0,
+        rego_code: compiled.rego,
+        metadata: Some(serde_json::Value::Object(metadata)),
+        status: BUNDLE_STATUS_DRAFT.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        activated_at: None,
+        author: request.author,
+        change_reason: request.change_reason,
+    };
+
+    state
+        .bundle_store
+        .store_bundle(&record)
+        .map_err(internal_error)?;
+
+    let stored = state
+        .bundle_store
+        .get_bundle(&record.bundle_id)
+        .map_err(internal_error)?
+        .ok_or_else(|| internal_error("failed to load stored bundle"))?;
+
+    info!(
+        tenant_id = %stored.tenant_id,
+        bundle_id = %stored.bundle_id,
+        version = %stored.version,
+        "stored policy bundle compiled from DSL"
+    );
+
+    Ok(Json(stored))
+}
+
 pub async fn list_policy_bundles(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Query(query): Query<PolicyBundlesQuery>,
 ) -> ApiResult<Vec<PolicyBundleRecord>> {
+    scope.check(&query.tenant_id)?;
+
     if state
         .tenant_registry
         .get_tenant(&query.tenant_id)
@@ -359,46 +754,260 @@ pub async fn list_policy_bundles(
     Ok(Json(bundles))
 }
 
+/// Chronological creations and status changes of a tenant's bundles, with
+/// who made each change and why.
+pub async fn list_bundle_history(
+    State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
+    Query(query): Query<PolicyBundlesQuery>,
+) -> ApiResult<Vec<BundleHistoryEntry>> {
+    scope.check(&query.tenant_id)?;
+
+    if state
+        .tenant_registry
+        .get_tenant(&query.tenant_id)
+        .map_err(internal_error)?
+        .is_none()
+    {
+        return Err(not_found("tenant_not_found", "tenant not registered"));
+    }
+
+    let history = state
+        .bundle_store
+        .bundle_history(&query.tenant_id)
+        .map_err(internal_error)?;
+
+    Ok(Json(history))
+}
+
 pub async fn get_policy_bundle(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Path(bundle_id): Path<String>,
 ) -> ApiResult<PolicyBundleRecord> {
+    scoped_bundle(&state, &scope, &bundle_id).map(Json)
+}
+
+/// Loads `bundle_id` and rejects callers scoped to another tenant than the
+/// bundle's, since bundle routes name only the bundle.
+fn scoped_bundle(
+    state: &ApiState,
+    scope: &TenantScope,
+    bundle_id: &str,
+) -> Result<PolicyBundleRecord, (StatusCode, Json<ErrorResponse>)> {
     let bundle = state
         .bundle_store
-        .get_bundle(&bundle_id)
-        .map_err(|err| internal_error(err))?;
-
-    match bundle {
-        Some(record) => Ok(Json(record)),
-        None => Err(not_found("bundle_not_found", "policy bundle not found")),
-    }
+        .get_bundle(bundle_id)
+        .map_err(internal_error)?
+        .ok_or_else(|| not_found("bundle_not_found", "policy bundle not found"))?;
+    scope.check(&bundle.tenant_id)?;
+    Ok(bundle)
 }
 
+/// Activates a bundle or starts its canary rollout.
+///
+/// With an `Idempotency-Key` header the key is reserved for the tenant before
+/// activating and the response recorded under it, so a retry carrying the
+/// same key gets that response back without activating again. A retry that
+/// arrives while the first activation is still running waits for its
+/// response.
 pub async fn activate_policy_bundle(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Path(bundle_id): Path<String>,
+    headers: HeaderMap,
+    request: Option<Json<ActivateBundleRequest>>,
 ) -> ApiResult<serde_json::Value> {
-    state
+    let bundle = scoped_bundle(&state, &scope, &bundle_id)?;
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+
+    let idempotency_key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let Some(key) = idempotency_key else {
+        return activate_bundle(&state, &bundle, request).map(Json);
+    };
+
+    let started = std::time::Instant::now();
+    loop {
+        let reservation = state
+            .bundle_store
+            .reserve_activation(&bundle.tenant_id, key, &bundle_id)
+            .map_err(internal_error)?;
+        let holder = match reservation {
+            ActivationReservation::Reserved => break,
+            ActivationReservation::Recorded(recorded) if recorded.bundle_id == bundle_id => {
+                info!(bundle_id = %bundle_id, "replayed policy bundle activation for idempotency key");
+                return Ok(Json(recorded.response));
+            }
+            ActivationReservation::Recorded(recorded) => recorded.bundle_id,
+            ActivationReservation::InProgress { bundle_id: holder } if holder == bundle_id => {
+                if started.elapsed() >= IDEMPOTENCY_WAIT {
+                    return Err(conflict(
+                        "idempotency_key_in_progress",
+                        "an activation with this idempotency key is still running",
+                    ));
+                }
+                tokio::time::sleep(IDEMPOTENCY_POLL_INTERVAL).await;
+                continue;
+            }
+            ActivationReservation::InProgress { bundle_id: holder } => holder,
+        };
+        return Err(conflict(
+            "idempotency_key_reused",
+            &format!("idempotency key was already used to activate bundle {holder}"),
+        ));
+    }
+
+    match activate_bundle(&state, &bundle, request) {
+        Ok(response) => {
+            state
+                .bundle_store
+                .record_activation(&bundle.tenant_id, key, &response)
+                .map_err(internal_error)?;
+            Ok(Json(response))
+        }
+        Err(rejection) => {
+            state
+                .bundle_store
+                .release_activation(&bundle.tenant_id, key)
+                .map_err(internal_error)?;
+            Err(rejection)
+        }
+    }
+}
+
+fn activate_bundle(
+    state: &ApiState,
+    bundle: &PolicyBundleRecord,
+    request: ActivateBundleRequest,
+) -> Result<serde_json::Value, (StatusCode, Json<ErrorResponse>)> {
+    let bundle_id = &bundle.bundle_id;
+    let rollout_percent = request.rollout_percent.unwrap_or(100);
+    let change = BundleChange {
+        author: request.author,
+        change_reason: request.change_reason,
+    };
+
+    if state.config.bundle_promotion_workflow && bundle.status != BUNDLE_STATUS_STAGED {
+        return Err(conflict(
+            "invalid_transition",
+            &format!(
+                "bundle is '{}'; only staged bundles can be activated",
+                bundle.status
+            ),
+        ));
+    }
+
+    let rollout = state
         .bundle_store
-        .activate_bundle(&bundle_id)
-        .map_err(|err| internal_error(err))?;
+        .start_rollout(bundle_id, rollout_percent, &change)
+        .map_err(|err| match err {
+            StorageError::InvalidRollout(message) => bad_request("invalid_rollout", &message),
+            StorageError::BundleNotFound(_) => {
+                not_found("bundle_not_found", "policy bundle not found")
+            }
+            other => internal_error(other),
+        })?;
+
+    let response = match rollout {
+        Some(rollout) => {
+            info!(
+                bundle_id = %bundle_id,
+                rollout_percent,
+                "started policy bundle rollout via API"
+            );
+
+            serde_json::json!({
+                "status": "canary",
+                "bundle_id": bundle_id,
+                "stable_bundle_id": rollout.stable_bundle_id,
+                "rollout_percent": rollout.rollout_percent
+            })
+        }
+        None => {
+            info!(bundle_id = %bundle_id, "activated policy bundle via API");
 
-    info!(bundle_id = %bundle_id, "activated policy bundle via API");
+            serde_json::json!({
+                "status": "activated",
+                "bundle_id": bundle_id
+            })
+        }
+    };
 
-    Ok(Json(serde_json::json!({
-        "status": "activated",
-        "bundle_id": bundle_id
-    })))
+    Ok(response)
+}
+
+/// Moves a bundle from `draft` to `staged` or from `staged` to `active`.
+pub async fn promote_policy_bundle(
+    State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
+    Path(bundle_id): Path<String>,
+    Json(request): Json<PromoteBundleRequest>,
+) -> ApiResult<PolicyBundleRecord> {
+    if request.promoted_by.trim().is_empty() {
+        return Err(bad_request("invalid_promoted_by", "promoted_by cannot be empty"));
+    }
+
+    scoped_bundle(&state, &scope, &bundle_id)?;
+
+    let bundle = state
+        .bundle_store
+        .promote_bundle(&bundle_id, &request.to, request.promoted_by.trim())
+        .map_err(|err| match err {
+            StorageError::InvalidTransition(message) => conflict("invalid_transition", &message),
+            StorageError::BundleNotFound(_) => {
+                not_found("bundle_not_found", "policy bundle not found")
+            }
+            other => internal_error(other),
+        })?;
+
+    info!(
+        bundle_id = %bundle_id,
+        status = %bundle.status,
+        promoted_by = %request.promoted_by,
+        "promoted policy bundle via API"
+    );
+
+    Ok(Json(bundle))
+}
+
+pub async fn select_policy_bundle(
+    State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
+    Query(query): Query<SelectBundleQuery>,
+) -> ApiResult<PolicyBundleRecord> {
+    scope.check(&query.tenant_id)?;
+
+    let bundle = state
+        .bundle_store
+        .select_bundle_for_subject(&query.tenant_id, &query.subject)
+        .map_err(internal_error)?;
+
+    match bundle {
+        Some(record) => Ok(Json(record)),
+        None => Err(not_found("bundle_not_found", "no active policy bundle for tenant")),
+    }
 }
 
 pub async fn archive_policy_bundle(
     State(state): State<Arc<ApiState>>,
+    scope: TenantScope,
     Path(bundle_id): Path<String>,
 ) -> ApiResult<serde_json::Value> {
+    scoped_bundle(&state, &scope, &bundle_id)?;
+
     state
         .bundle_store
         .archive_bundle(&bundle_id)
-        .map_err(|err| internal_error(err))?;
+        .map_err(|err| match err {
+            StorageError::BundleNotFound(_) => {
+                not_found("bundle_not_found", "policy bundle not found")
+            }
+            other => internal_error(other),
+        })?;
 
     info!(bundle_id = %bundle_id, "archived policy bundle via API");
 
@@ -408,11 +1017,63 @@ pub async fn archive_policy_bundle(
     })))
 }
 
-pub async fn health_check() -> ApiResult<serde_json::Value> {
-    Ok(Json(serde_json::json!({
-        "status": "healthy",
-        "service": "audit-store"
-    })))
+/// Returns the effective configuration with secrets redacted.
+pub async fn get_config(State(state): State<Arc<ApiState>>) -> Json<serde_json::Value> {
+    Json(state.config.dump())
+}
+
+/// Reports service health with the open status of each database.
+///
+/// Audit logs and the tenant registry are needed to accept writes, so either
+/// being unavailable returns `503`; a failing bundle store only degrades.
+pub async fn health_check(
+    State(state): State<Arc<ApiState>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let checks = [
+        ("audit_logs", true, state.database.check_available()),
+        ("tenant_registry", true, state.tenant_registry.ping()),
+        ("policy_bundles", false, state.bundle_store.ping()),
+    ];
+
+    let mut dependencies = serde_json::Map::new();
+    let mut critical_down = false;
+    let mut degraded = false;
+    for (name, critical, result) in checks {
+        let entry = match result {
+            Ok(()) => serde_json::json!({ "status": "up", "critical": critical }),
+            Err(err) => {
+                warn!(dependency = name, error = %err, "health check dependency down");
+                if critical {
+                    critical_down = true;
+                } else {
+                    degraded = true;
+                }
+                serde_json::json!({
+                    "status": "down",
+                    "critical": critical,
+                    "error": err.to_string()
+                })
+            }
+        };
+        dependencies.insert(name.to_string(), entry);
+    }
+
+    let (status, label) = if critical_down {
+        (StatusCode::SERVICE_UNAVAILABLE, "unhealthy")
+    } else if degraded {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "healthy")
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "status": label,
+            "service": "audit-store",
+            "dependencies": dependencies
+        })),
+    )
 }
 
 fn bad_request(code: &str, message: &str) -> (StatusCode, Json<ErrorResponse>) {
@@ -437,6 +1098,31 @@ fn not_found(code: &str, message: &str) -> (StatusCode, Json<ErrorResponse>) {
     )
 }
 
+fn conflict(code: &str, message: &str) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::CONFLICT,
+        Json(ErrorResponse {
+            error: message.to_string(),
+            code: code.to_string(),
+            details: None,
+        }),
+    )
+}
+
+fn too_many_requests(retry_after: std::time::Duration) -> Response {
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after_secs.to_string())],
+        Json(ErrorResponse {
+            error: "audit log ingestion rate limit exceeded".to_string(),
+            code: "rate_limited".to_string(),
+            details: Some(serde_json::json!({ "retry_after_secs": retry_after_secs })),
+        }),
+    )
+        .into_response()
+}
+
 fn internal_error<E: std::fmt::Display>(err: E) -> (StatusCode, Json<ErrorResponse>) {
     error!(error = %err, "internal error");
     (
@@ -448,3 +1134,1037 @@ fn internal_error<E: std::fmt::Display>(err: E) -> (StatusCode, Json<ErrorRespon
         }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AuditStoreConfig;
+    use crate::api::scope::TENANT_SCOPE_HEADER;
+    use crate::storage::memory::MemoryAuditStore;
+    use crate::storage::AuditStore;
+    use axum::extract::FromRequestParts;
+
+    async fn scope_from_header(state: &Arc<ApiState>, tenant_scope: Option<&str>) -> TenantScope {
+        let mut request = axum::http::Request::builder();
+        if let Some(value) = tenant_scope {
+            request = request.header(TENANT_SCOPE_HEADER, value);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+        TenantScope::from_request_parts(&mut parts, state).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn config_dump_redacts_secrets() {
+        let temp = tempfile::tempdir().unwrap();
+        let secret = "config-dump-secret-key-of-32-bytes!";
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: secret.to_string(),
+            upload_batch_size: 250,
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+
+        let Json(dump) = get_config(State(state)).await;
+
+        assert_eq!(dump["upload_batch_size"], 250);
+        assert_eq!(dump["server_port"], 8182);
+        assert_eq!(dump["hmac_secret_key"], crate::config::REDACTED_SECRET);
+        assert!(!dump.to_string().contains(secret));
+    }
+
+    #[tokio::test]
+    async fn duplicate_tenant_is_rejected_and_original_kept() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "duplicate-tenant-secret-key-32-bytes".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        let request = |name: &str| TenantRequest {
+            tenant_id: "tenant-a".to_string(),
+            name: name.to_string(),
+            config: None,
+        };
+
+        create_tenant(
+            State(Arc::clone(&state)),
+            scope_from_header(&state, None).await,
+            Json(request("Tenant A")),
+        )
+        .await
+        .unwrap();
+        let (status, Json(body)) = create_tenant(
+            State(Arc::clone(&state)),
+            scope_from_header(&state, None).await,
+            Json(request("Impostor")),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body.code, "tenant_exists");
+
+        let stored = state
+            .tenant_registry
+            .get_tenant("tenant-a")
+            .unwrap()
+            .expect("original tenant should remain");
+        assert_eq!(stored.name, "Tenant A");
+    }
+
+    #[tokio::test]
+    async fn dsl_bundle_stores_source_and_rego() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "dsl-bundle-secret-key-of-32-bytes!!".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+
+        let dsl_source = r#"allow read sensor_data if subject.tenant_id == "tenant-a""#;
+        let scope = scope_from_header(&state, None).await;
+        let Json(created) = create_policy_bundle_from_dsl(
+            State(Arc::clone(&state)),
+            scope,
+            Json(DslBundleRequest {
+                tenant_id: "tenant-a".to_string(),
+                dsl_source: dsl_source.to_string(),
+                metadata: Some(serde_json::json!({ "author": "ops" })),
+                author: None,
+                change_reason: None,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let stored = state
+            .bundle_store
+            .get_bundle(&created.bundle_id)
+            .unwrap()
+            .expect("bundle should be stored");
+        assert_eq!(stored.status, BUNDLE_STATUS_DRAFT);
+        assert!(stored.rego_code.starts_with("package tenants.tenant-a"));
+        assert!(stored.rego_code.contains("allow if"));
+        let metadata = stored.metadata.expect("metadata should be stored");
+        assert_eq!(metadata["dsl_source"], dsl_source);
+        assert_eq!(metadata["author"], "ops");
+
+        let scope = scope_from_header(&state, None).await;
+        let (status, Json(body)) = create_policy_bundle_from_dsl(
+            State(Arc::clone(&state)),
+            scope,
+            Json(DslBundleRequest {
+                tenant_id: "tenant-a".to_string(),
+                dsl_source: "allow read if".to_string(),
+                metadata: None,
+                author: None,
+                change_reason: None,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.code, "invalid_policy");
+    }
+
+    #[tokio::test]
+    async fn mismatched_tenant_scope_is_rejected() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "scope-test-secret-key-of-32-bytes!!".to_string(),
+            enforce_tenant_scope: true,
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+
+        let query = || UnuploadedQuery {
+            tenant_id: "tenant-a".to_string(),
+            limit: None,
+        };
+
+        let scope = scope_from_header(&state, Some("tenant-b")).await;
+        let (status, Json(body)) = get_unuploaded_logs(State(Arc::clone(&state)), scope, Query(query()))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body.code, "tenant_scope_mismatch");
+
+        let scope = scope_from_header(&state, None).await;
+        let (status, _) = get_unuploaded_logs(State(Arc::clone(&state)), scope, Query(query()))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let scope = scope_from_header(&state, Some("tenant-a")).await;
+        let Json(response) = get_unuploaded_logs(State(state), scope, Query(query()))
+            .await
+            .unwrap();
+        assert!(response.logs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn bundle_and_tenant_routes_respect_tenant_scope() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "scope-routes-secret-key-of-32-bytes".to_string(),
+            enforce_tenant_scope: true,
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        for tenant_id in ["tenant-a", "tenant-b"] {
+            state
+                .tenant_registry
+                .create_tenant(&TenantRecord {
+                    tenant_id: tenant_id.to_string(),
+                    name: tenant_id.to_string(),
+                    status: "active".to_string(),
+                    created_at: "2025-01-01T00:00:00Z".to_string(),
+                    updated_at: "2025-01-01T00:00:00Z".to_string(),
+                    config: None,
+                })
+                .unwrap();
+        }
+        state
+            .bundle_store
+            .store_bundle(&PolicyBundleRecord {
+                bundle_id: "bundle-b".to_string(),
+                tenant_id: "tenant-b".to_string(),
+                version: 0,
+                rego_code: "package tenants.tenant_b".to_string(),
+                metadata: None,
+                status: "draft".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                activated_at: None,
+                author: None,
+                change_reason: None,
+            })
+            .unwrap();
+        let bundle = || Path("bundle-b".to_string());
+
+        let scope = scope_from_header(&state, Some("tenant-a")).await;
+        let (status, _) = get_policy_bundle(State(Arc::clone(&state)), scope, bundle())
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let scope = scope_from_header(&state, Some("tenant-a")).await;
+        let (status, _) = activate_policy_bundle(
+            State(Arc::clone(&state)),
+            scope,
+            bundle(),
+            HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let scope = scope_from_header(&state, Some("tenant-a")).await;
+        let (status, _) = archive_policy_bundle(State(Arc::clone(&state)), scope, bundle())
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let scope = scope_from_header(&state, Some("tenant-a")).await;
+        let (status, _) = promote_policy_bundle(
+            State(Arc::clone(&state)),
+            scope,
+            bundle(),
+            Json(PromoteBundleRequest {
+                to: "staged".to_string(),
+                promoted_by: "intruder".to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let stored = state.bundle_store.get_bundle("bundle-b").unwrap().unwrap();
+        assert_eq!(stored.status, "draft");
+
+        let scope = scope_from_header(&state, Some("tenant-b")).await;
+        let Json(stored) = get_policy_bundle(State(Arc::clone(&state)), scope, bundle())
+            .await
+            .unwrap();
+        assert_eq!(stored.tenant_id, "tenant-b");
+
+        let scope = scope_from_header(&state, Some("tenant-a")).await;
+        let Json(tenants) = list_tenants(
+            State(Arc::clone(&state)),
+            scope,
+            Query(ListTenantsQuery { status: None }),
+        )
+        .await
+        .unwrap();
+        let listed: Vec<_> = tenants.iter().map(|tenant| tenant.tenant_id.as_str()).collect();
+        assert_eq!(listed, vec!["tenant-a"]);
+
+        let scope = scope_from_header(&state, None).await;
+        let (status, _) = list_tenants(
+            State(Arc::clone(&state)),
+            scope,
+            Query(ListTenantsQuery { status: None }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let scope = scope_from_header(&state, Some("tenant-a")).await;
+        let (status, Json(body)) = create_tenant(
+            State(state),
+            scope,
+            Json(TenantRequest {
+                tenant_id: "tenant-c".to_string(),
+                name: "Tenant C".to_string(),
+                config: None,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body.code, "tenant_scope_mismatch");
+    }
+
+    #[tokio::test]
+    async fn audit_log_writes_over_the_rate_limit_are_rejected() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "limit-test-secret-key-of-32-bytes!!".to_string(),
+            ingest_rate_limit: 2,
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+
+        let request = |timestamp: &str| AuditLogRequest {
+            tenant_id: "tenant-a".to_string(),
+            timestamp: timestamp.to_string(),
+            decision: "allow".to_string(),
+            protocol: "http".to_string(),
+            subject: serde_json::json!({"user_id": "u1"}),
+            action: "read".to_string(),
+            resource: serde_json::json!({"type": "sensor_data"}),
+            environment: serde_json::json!({}),
+            policy_version: None,
+            reason: None,
+        };
+        let write = |request: AuditLogRequest| {
+            let state = Arc::clone(&state);
+            async move {
+                let scope = scope_from_header(&state, None).await;
+                write_audit_log(State(state), scope, Json(request)).await
+            }
+        };
+
+        // A rejected write does not use up the limit.
+        assert!(write(request("not-a-timestamp")).await.is_err());
+        assert!(write(request("2025-01-01T00:00:00Z")).await.is_ok());
+        assert!(write(request("2025-01-01T00:00:01Z")).await.is_ok());
+
+        let response = write(request("2025-01-01T00:00:02Z")).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&retry_after));
+
+        // Writes for unknown tenants are rejected before they get a window.
+        for index in 0..10 {
+            let mut unknown = request("2025-01-01T00:00:03Z");
+            unknown.tenant_id = format!("unknown-{index}");
+            let response = write(unknown).await.unwrap_err();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+        assert_eq!(state.ingest_limiter.tracked_tenants(), 1);
+    }
+
+    #[tokio::test]
+    async fn audit_logs_are_written_and_queried_through_the_audit_store() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "memory-store-secret-key-of-32-bytes".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let store = Arc::new(MemoryAuditStore::new());
+        let state = Arc::new(
+            ApiState::new(config)
+                .unwrap()
+                .with_audit_store(Arc::clone(&store) as Arc<dyn AuditStore>),
+        );
+        state
+            .tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+
+        let scope = scope_from_header(&state, None).await;
+        let Json(written) = write_audit_log(
+            State(Arc::clone(&state)),
+            scope,
+            Json(AuditLogRequest {
+                tenant_id: "tenant-a".to_string(),
+                timestamp: "2025-01-01T00:00:00Z".to_string(),
+                decision: "deny".to_string(),
+                protocol: "mqtt".to_string(),
+                subject: serde_json::json!({"device_id": "d1"}),
+                action: "publish".to_string(),
+                resource: serde_json::json!({"topic": "tenant-a/telemetry"}),
+                environment: serde_json::json!({}),
+                policy_version: None,
+                reason: Some("quota exceeded".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+
+        // The entry lives only in the memory store, not in SQLite.
+        let stored = store
+            .query_logs("tenant-a", &LogFilter::default())
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].log_id, written.log_id);
+        assert!(!temp.path().join("audit").join("tenant-a").exists());
+
+        let scope = scope_from_header(&state, None).await;
+        let Json(response) = query_audit_logs(
+            State(Arc::clone(&state)),
+            scope,
+            Query(QueryLogsRequest {
+                tenant_id: "tenant-a".to_string(),
+                start_time: None,
+                end_time: None,
+                decision: Some("deny".to_string()),
+                protocol: None,
+                limit: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.logs.len(), 1);
+        assert_eq!(response.logs[0].signature, written.signature);
+
+        let (_, Json(health)) = health_check(State(state)).await;
+        assert_eq!(health["dependencies"]["audit_logs"]["status"], "up");
+    }
+
+    #[tokio::test]
+    async fn encrypted_tenant_logs_are_ciphertext_at_rest() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "encrypt-test-secret-key-of-32-bytes".to_string(),
+            encryption_master_key: Some("encrypt-test-master-key-of-32-bytes".to_string()),
+            encrypted_tenants: vec!["tenant-a".to_string()],
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+
+        let request = AuditLogRequest {
+            tenant_id: "tenant-a".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            decision: "allow".to_string(),
+            protocol: "http".to_string(),
+            subject: serde_json::json!({"user_id": "patient-42"}),
+            action: "read".to_string(),
+            resource: serde_json::json!({"type": "medical_record"}),
+            environment: serde_json::json!({"country": "DE"}),
+            policy_version: None,
+            reason: None,
+        };
+        let scope = scope_from_header(&state, None).await;
+        write_audit_log(State(Arc::clone(&state)), scope, Json(request))
+            .await
+            .unwrap();
+
+        let conn = rusqlite::Connection::open(
+            temp.path()
+                .join("audit")
+                .join("tenant-a")
+                .join(crate::storage::AUDIT_DB_FILENAME),
+        )
+        .unwrap();
+        let (subject, resource, environment): (String, String, String) = conn
+            .query_row(
+                "SELECT subject, resource, environment FROM audit_logs",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        for column in [&subject, &resource, &environment] {
+            assert!(column.starts_with(crate::storage::encryption::ENCRYPTED_PREFIX));
+        }
+        assert!(!subject.contains("patient-42"));
+
+        let scope = scope_from_header(&state, None).await;
+        let Json(response) = query_audit_logs(
+            State(Arc::clone(&state)),
+            scope,
+            Query(QueryLogsRequest {
+                tenant_id: "tenant-a".to_string(),
+                start_time: None,
+                end_time: None,
+                decision: None,
+                protocol: None,
+                limit: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.logs.len(), 1);
+        let entry = &response.logs[0];
+        assert_eq!(entry.subject, serde_json::json!({"user_id": "patient-42"}));
+        assert_eq!(entry.environment, serde_json::json!({"country": "DE"}));
+        assert!(state.signer.verify_audit_log(entry).unwrap());
+    }
+
+    #[tokio::test]
+    async fn workflow_requires_staging_before_activation() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "workflow-test-secret-key-of-32-bytes".to_string(),
+            bundle_promotion_workflow: true,
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .bundle_store
+            .store_bundle(&PolicyBundleRecord {
+                bundle_id: "bundle-1".to_string(),
+                tenant_id: "tenant-a".to_string(),
+                version: 0,
+                rego_code: "package tenants.tenant_a".to_string(),
+                metadata: None,
+                status: "draft".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                activated_at: None,
+                author: None,
+                change_reason: None,
+            })
+            .unwrap();
+
+        let (status, Json(body)) = activate_policy_bundle(
+            State(Arc::clone(&state)),
+            scope_from_header(&state, None).await,
+            Path("bundle-1".to_string()),
+            HeaderMap::new(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body.code, "invalid_transition");
+
+        let promote = |to: &str| PromoteBundleRequest {
+            to: to.to_string(),
+            promoted_by: "reviewer".to_string(),
+        };
+        let Json(staged) = promote_policy_bundle(
+            State(Arc::clone(&state)),
+            scope_from_header(&state, None).await,
+            Path("bundle-1".to_string()),
+            Json(promote("staged")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(staged.status, "staged");
+
+        let Json(active) = promote_policy_bundle(
+            State(Arc::clone(&state)),
+            scope_from_header(&state, None).await,
+            Path("bundle-1".to_string()),
+            Json(promote("active")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(active.status, "active");
+        assert_eq!(
+            active.metadata.unwrap()["promotions"][1]["promoted_by"],
+            "reviewer"
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_bundles_are_not_found() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "not-found-test-secret-key-32-bytes!".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+
+        let (status, Json(body)) = activate_policy_bundle(
+            State(Arc::clone(&state)),
+            scope_from_header(&state, None).await,
+            Path("missing".to_string()),
+            HeaderMap::new(),
+            Some(Json(ActivateBundleRequest {
+                rollout_percent: Some(10),
+                ..ActivateBundleRequest::default()
+            })),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body.code, "bundle_not_found");
+
+        let scope = scope_from_header(&state, None).await;
+        let (status, Json(body)) =
+            archive_policy_bundle(State(state), scope, Path("missing".to_string()))
+                .await
+                .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body.code, "bundle_not_found");
+    }
+
+    #[tokio::test]
+    async fn activation_retry_with_idempotency_key_is_a_no_op() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "idempotency-secret-key-of-32-bytes!".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        for bundle_id in ["bundle-1", "bundle-2"] {
+            state
+                .bundle_store
+                .store_bundle(&PolicyBundleRecord {
+                    bundle_id: bundle_id.to_string(),
+                    tenant_id: "tenant-a".to_string(),
+                    version: 0,
+                    rego_code: "package tenants.tenant_a".to_string(),
+                    metadata: None,
+                    status: "draft".to_string(),
+                    created_at: "2025-01-01T00:00:00Z".to_string(),
+                    activated_at: None,
+                    author: None,
+                    change_reason: None,
+                })
+                .unwrap();
+        }
+        state
+            .bundle_store
+            .activate_bundle("bundle-1", &BundleChange::default())
+            .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "retry-1".parse().unwrap());
+        let activate = |bundle_id: &str| {
+            let bundle_id = bundle_id.to_string();
+            let (state, headers) = (Arc::clone(&state), headers.clone());
+            async move {
+                let scope = scope_from_header(&state, None).await;
+                activate_policy_bundle(State(state), scope, Path(bundle_id), headers, None).await
+            }
+        };
+
+        let Json(first) = activate("bundle-2").await.unwrap();
+        let bundle_1 = state.bundle_store.get_bundle("bundle-1").unwrap().unwrap();
+        assert_eq!(bundle_1.status, "inactive");
+
+        // Bring the old bundle back so a second activation would demote it.
+        state
+            .bundle_store
+            .activate_bundle("bundle-1", &BundleChange::default())
+            .unwrap();
+
+        let Json(retried) = activate("bundle-2").await.unwrap();
+        assert_eq!(retried, first);
+        let bundle_1 = state.bundle_store.get_bundle("bundle-1").unwrap().unwrap();
+        assert_eq!(bundle_1.status, "active");
+        let bundle_2 = state.bundle_store.get_bundle("bundle-2").unwrap().unwrap();
+        assert_eq!(bundle_2.status, "inactive");
+
+        let (status, Json(body)) = activate("bundle-1").await.unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body.code, "idempotency_key_reused");
+    }
+
+    #[tokio::test]
+    async fn concurrent_retry_waits_for_the_activation_holding_the_key() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "concurrent-retry-secret-key-32-bytes".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .bundle_store
+            .store_bundle(&PolicyBundleRecord {
+                bundle_id: "bundle-1".to_string(),
+                tenant_id: "tenant-a".to_string(),
+                version: 0,
+                rego_code: "package tenants.tenant_a".to_string(),
+                metadata: None,
+                status: "draft".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                activated_at: None,
+                author: None,
+                change_reason: None,
+            })
+            .unwrap();
+
+        // The first request holds the key and finishes while the retry waits.
+        let first = serde_json::json!({ "status": "activated", "bundle_id": "bundle-1" });
+        state
+            .bundle_store
+            .reserve_activation("tenant-a", "retry-1", "bundle-1")
+            .unwrap();
+        let finisher = {
+            let (state, first) = (Arc::clone(&state), first.clone());
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                state
+                    .bundle_store
+                    .record_activation("tenant-a", "retry-1", &first)
+                    .unwrap();
+            })
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "retry-1".parse().unwrap());
+        let scope = scope_from_header(&state, None).await;
+        let Json(retried) = activate_policy_bundle(
+            State(Arc::clone(&state)),
+            scope,
+            Path("bundle-1".to_string()),
+            headers,
+            None,
+        )
+        .await
+        .unwrap();
+        finisher.await.unwrap();
+
+        assert_eq!(retried, first);
+        // Only the first request activates; the retry did not run again.
+        let bundle = state.bundle_store.get_bundle("bundle-1").unwrap().unwrap();
+        assert_eq!(bundle.status, "draft");
+    }
+
+    #[tokio::test]
+    async fn bundle_history_attributes_each_deployment() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "history-test-secret-key-of-32-bytes".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+
+        let deployments = [
+            ("bundle-1", "alice", "initial policy"),
+            ("bundle-2", "bob", "restrict exports"),
+            ("bundle-3", "carol", "allow EU reads"),
+        ];
+        for (bundle_id, author, reason) in deployments {
+            create_policy_bundle(
+                State(Arc::clone(&state)),
+                scope_from_header(&state, None).await,
+                Json(PolicyBundleRecord {
+                    bundle_id: bundle_id.to_string(),
+                    tenant_id: "tenant-a".to_string(),
+                    version: 0,
+                    rego_code: "package tenants.tenant_a".to_string(),
+                    metadata: None,
+                    status: BUNDLE_STATUS_DRAFT.to_string(),
+                    created_at: "2025-01-01T00:00:00Z".to_string(),
+                    activated_at: None,
+                    author: Some(author.to_string()),
+                    change_reason: Some(reason.to_string()),
+                }),
+            )
+            .await
+            .unwrap();
+            activate_policy_bundle(
+                State(Arc::clone(&state)),
+                scope_from_header(&state, None).await,
+                Path(bundle_id.to_string()),
+                HeaderMap::new(),
+                Some(Json(ActivateBundleRequest {
+                    author: Some(author.to_string()),
+                    ..ActivateBundleRequest::default()
+                })),
+            )
+            .await
+            .unwrap();
+        }
+
+        let Json(history) = list_bundle_history(
+            State(Arc::clone(&state)),
+            scope_from_header(&state, None).await,
+            Query(PolicyBundlesQuery {
+                tenant_id: "tenant-a".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let steps: Vec<(i64, &str, Option<&str>)> = history
+            .iter()
+            .map(|entry| (entry.version, entry.event.as_str(), entry.author.as_deref()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (1, "created", Some("alice")),
+                (1, "active", Some("alice")),
+                (2, "created", Some("bob")),
+                (2, "active", Some("bob")),
+                (3, "created", Some("carol")),
+                (3, "active", Some("carol")),
+            ]
+        );
+        assert_eq!(
+            history[2].change_reason.as_deref(),
+            Some("restrict exports")
+        );
+        assert_eq!(history[2].bundle_id, "bundle-2");
+
+        let bundle = state.bundle_store.get_bundle("bundle-3").unwrap().unwrap();
+        assert_eq!(bundle.author.as_deref(), Some("carol"));
+        assert_eq!(bundle.change_reason.as_deref(), Some("allow EU reads"));
+    }
+
+    #[tokio::test]
+    async fn bulk_ingestion_reports_accepted_and_rejected_lines() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "bulk-ingest-secret-key-of-32-bytes!".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+
+        let entry = |tenant_id: &str, timestamp: &str| {
+            serde_json::json!({
+                "tenant_id": tenant_id,
+                "timestamp": timestamp,
+                "decision": "allow",
+                "protocol": "http",
+                "subject": {},
+                "action": "read",
+                "resource": {},
+                "environment": {},
+                "policy_version": null,
+                "reason": null
+            })
+            .to_string()
+        };
+        let body = format!(
+            "{}\n{{not json\n{}\n\n{}\n{}",
+            entry("tenant-a", "2025-01-01T00:00:00Z"),
+            entry("tenant-a", "yesterday"),
+            entry("tenant-b", "2025-01-01T00:00:00Z"),
+            entry("tenant-a", "2025-01-01T00:00:01Z"),
+        );
+        // Chunk boundaries fall mid-line, as they would on the wire.
+        let chunks: Vec<Result<Vec<u8>, std::io::Error>> = body
+            .as_bytes()
+            .chunks(7)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect();
+
+        let scope = scope_from_header(&state, None).await;
+        let Json(summary) = bulk_write_audit_logs(
+            State(Arc::clone(&state)),
+            scope,
+            Body::from_stream(futures_util::stream::iter(chunks)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.accepted, 2);
+        assert_eq!(summary.rejected, 3);
+        let errors: Vec<(usize, &str)> = summary
+            .errors
+            .iter()
+            .map(|error| (error.line, error.code.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (2, "invalid_json"),
+                (3, "invalid_timestamp"),
+                (5, "tenant_not_found"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn health_check_reports_unavailable_audit_storage() {
+        let temp = tempfile::tempdir().unwrap();
+        let data_dir = temp.path().join("audit");
+        let config = AuditStoreConfig {
+            data_dir: data_dir.clone(),
+            hmac_secret_key: "health-test-secret-key-of-32-bytes!".to_string(),
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+
+        let (status, Json(body)) = health_check(State(Arc::clone(&state))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "healthy");
+        assert_eq!(body["dependencies"]["audit_logs"]["status"], "up");
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
+
+        let (status, Json(body)) = health_check(State(state)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "unhealthy");
+        assert_eq!(body["dependencies"]["audit_logs"]["status"], "down");
+        assert_eq!(body["dependencies"]["audit_logs"]["critical"], true);
+    }
+
+    #[tokio::test]
+    async fn tenant_update_triggers_enforcer_reload() {
+        use std::sync::Mutex;
+        use std::time::Duration;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        // Mock enforcer recording the request line of each call
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let enforcer_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::<String>::new()));
+        let seen = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 8192];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                if let Some(line) = String::from_utf8_lossy(&buf[..n]).lines().next() {
+                    seen.lock().unwrap().push(line.to_string());
+                }
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{}")
+                    .await;
+            }
+        });
+
+        let temp = tempfile::tempdir().unwrap();
+        let config = AuditStoreConfig {
+            data_dir: temp.path().join("audit"),
+            hmac_secret_key: "notify-test-secret-key-of-32-bytes!".to_string(),
+            tenant_notify_urls: vec![format!("{enforcer_url}/v1/tenants/{{tenant_id}}/reload")],
+            ..AuditStoreConfig::default()
+        };
+        let state = Arc::new(ApiState::new(config).unwrap());
+        state
+            .tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+
+        let scope = scope_from_header(&state, None).await;
+        let request = UpdateTenantRequest {
+            name: None,
+            status: None,
+            config: Some(serde_json::json!({"quotas": {"message_limit": 500}})),
+        };
+        update_tenant(State(Arc::clone(&state)), scope, Path("tenant-a".to_string()), Json(request))
+            .await
+            .unwrap();
+
+        // The notification is sent in the background
+        for _ in 0..50 {
+            if !requests.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(
+            requests[0].starts_with("POST /v1/tenants/tenant-a/reload "),
+            "{}",
+            requests[0]
+        );
+    }
+}
@@ -1,9 +1,12 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 
 pub mod handlers;
+pub mod rate_limit;
 pub mod router;
+pub mod scope;
 pub mod types;
 
 pub use handlers::*;
@@ -11,31 +14,65 @@ pub use router::create_router;
 pub use types::*;
 
 use crate::config::AuditStoreConfig;
+use crate::notify::TenantChangeNotifier;
+use rate_limit::IngestionRateLimiter;
 use crate::signing::Signer;
-use crate::storage::{AuditDatabase, PolicyBundleStore, TenantRegistry};
+use crate::storage::{AuditDatabase, AuditStore, FieldCipher, PolicyBundleStore, TenantRegistry};
 
 pub struct ApiState {
-    pub database: Arc<AuditDatabase>,
+    pub database: Arc<dyn AuditStore>,
     pub tenant_registry: Arc<TenantRegistry>,
     pub bundle_store: Arc<PolicyBundleStore>,
     pub signer: Arc<Signer>,
+    pub tenant_notifier: TenantChangeNotifier,
+    pub ingest_limiter: IngestionRateLimiter,
     pub config: Arc<AuditStoreConfig>,
 }
 
 impl ApiState {
     pub fn new(config: AuditStoreConfig) -> Result<Self> {
         let data_dir = config.data_dir.clone();
-        let database = Arc::new(AuditDatabase::new(data_dir.clone())?);
+        let mut database = AuditDatabase::new(data_dir.clone())?;
+        if let Some(master_key) = &config.encryption_master_key {
+            let cipher = FieldCipher::new(master_key)?
+                .with_tenants(config.encrypted_tenants.iter().cloned());
+            database = database.with_encryption(cipher);
+        }
+        let database: Arc<dyn AuditStore> = Arc::new(database);
         let tenant_registry = Arc::new(TenantRegistry::new(&data_dir)?);
         let bundle_store = Arc::new(PolicyBundleStore::new(&data_dir)?);
-        let signer = Arc::new(Signer::new(&config.hmac_secret_key)?);
+        let mut signer = Signer::new(&config.hmac_secret_key)?;
+        if let Some(key_id) = &config.hmac_key_id {
+            signer = signer.with_key_id(key_id)?;
+        }
+        for (key_id, secret) in &config.retired_hmac_keys {
+            signer = signer.with_retired_key(key_id, secret)?;
+        }
+        if let Some(key) = &config.ed25519_signing_key {
+            signer = signer.with_ed25519_key(key)?;
+        }
+        let signer = Arc::new(signer);
+        let tenant_notifier = TenantChangeNotifier::new(&config);
+        let ingest_limiter = IngestionRateLimiter::new(
+            config.ingest_rate_limit,
+            Duration::from_secs(config.ingest_rate_window_secs),
+        );
 
         Ok(Self {
             database,
             tenant_registry,
             bundle_store,
             signer,
+            tenant_notifier,
+            ingest_limiter,
             config: Arc::new(config),
         })
     }
+
+    /// Replaces the SQLite audit log storage, e.g. with an in-memory store in
+    /// tests.
+    pub fn with_audit_store(mut self, database: Arc<dyn AuditStore>) -> Self {
+        self.database = database;
+        self
+    }
 }
@@ -0,0 +1,136 @@
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+
+struct Window {
+    started: Instant,
+    accepted: u32,
+}
+
+/// A write reserved by [`IngestionRateLimiter::try_acquire`], remembering
+/// the window it was counted in.
+#[derive(Debug)]
+#[must_use]
+pub struct IngestSlot {
+    tenant_id: String,
+    window_started: Option<Instant>,
+}
+
+/// Per-tenant cap on audit log writes within a fixed window.
+///
+/// A slot is reserved before the write and handed back with [`release`] when
+/// the write is not stored, so only accepted entries count toward the limit.
+/// Callers reserve only for registered tenants, which bounds the number of
+/// windows kept. A limit of `0` disables the check.
+///
+/// [`release`]: IngestionRateLimiter::release
+pub struct IngestionRateLimiter {
+    limit: u32,
+    window: Duration,
+    windows: DashMap<String, Window>,
+}
+
+impl IngestionRateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            windows: DashMap::new(),
+        }
+    }
+
+    /// Reserves a write for `tenant_id`, or returns how long until the
+    /// current window ends.
+    pub fn try_acquire(&self, tenant_id: &str) -> Result<IngestSlot, Duration> {
+        if self.limit == 0 {
+            return Ok(IngestSlot {
+                tenant_id: tenant_id.to_string(),
+                window_started: None,
+            });
+        }
+
+        let now = Instant::now();
+        let mut window = self
+            .windows
+            .entry(tenant_id.to_string())
+            .or_insert_with(|| Window {
+                started: now,
+                accepted: 0,
+            });
+
+        let elapsed = now.duration_since(window.started);
+        if elapsed >= self.window {
+            window.started = now;
+            window.accepted = 0;
+        } else if window.accepted >= self.limit {
+            return Err(self.window - elapsed);
+        }
+
+        window.accepted += 1;
+        Ok(IngestSlot {
+            tenant_id: tenant_id.to_string(),
+            window_started: Some(window.started),
+        })
+    }
+
+    /// Number of tenants with a window.
+    #[cfg(test)]
+    pub(crate) fn tracked_tenants(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Hands back a slot reserved by a write that was not stored. A slot
+    /// from a window that has since been reset is dropped, since the new
+    /// window never counted it.
+    pub fn release(&self, slot: IngestSlot) {
+        let Some(started) = slot.window_started else {
+            return;
+        };
+        if let Some(mut window) = self.windows.get_mut(&slot.tenant_id) {
+            if window.started == started {
+                window.accepted = window.accepted.saturating_sub(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_recovers_after_the_window() {
+        let limiter = IngestionRateLimiter::new(2, Duration::from_millis(50));
+
+        let _ = limiter.try_acquire("tenant-a").unwrap();
+        let _ = limiter.try_acquire("tenant-a").unwrap();
+        let retry_after = limiter.try_acquire("tenant-a").unwrap_err();
+        assert!(retry_after <= Duration::from_millis(50));
+        assert!(limiter.try_acquire("tenant-b").is_ok());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(limiter.try_acquire("tenant-a").is_ok());
+    }
+
+    #[test]
+    fn released_writes_do_not_count() {
+        let limiter = IngestionRateLimiter::new(1, Duration::from_secs(60));
+
+        let slot = limiter.try_acquire("tenant-a").unwrap();
+        limiter.release(slot);
+        assert!(limiter.try_acquire("tenant-a").is_ok());
+        assert!(limiter.try_acquire("tenant-a").is_err());
+    }
+
+    #[test]
+    fn slots_from_an_earlier_window_are_not_released_into_the_next() {
+        let limiter = IngestionRateLimiter::new(1, Duration::from_millis(50));
+
+        let stale = limiter.try_acquire("tenant-a").unwrap();
+        std::thread::sleep(Duration::from_millis(60));
+        let _current = limiter.try_acquire("tenant-a").unwrap();
+
+        limiter.release(stale);
+        assert!(limiter.try_acquire("tenant-a").is_err());
+    }
+}
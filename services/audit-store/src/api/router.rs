@@ -18,6 +18,7 @@ pub fn create_router(state: Arc<ApiState>) -> Router {
 
     Router::new()
         .route("/api/audit/logs", post(handlers::write_audit_log).get(handlers::query_audit_logs))
+        .route("/api/logs/bulk", post(handlers::bulk_write_audit_logs))
         .route(
             "/api/audit/logs/unuploaded",
             get(handlers::get_unuploaded_logs),
@@ -26,6 +27,18 @@ pub fn create_router(state: Arc<ApiState>) -> Router {
             "/api/audit/logs/mark-uploaded",
             post(handlers::mark_uploaded),
         )
+        .route(
+            "/api/upload/verify-batch",
+            post(handlers::verify_upload_batch),
+        )
+        .route(
+            "/api/upload/dead-letter",
+            get(handlers::list_dead_letter_logs),
+        )
+        .route(
+            "/api/upload/retry-dead-letter",
+            post(handlers::retry_dead_letter_logs),
+        )
         .route("/api/tenants", post(handlers::create_tenant).get(handlers::list_tenants))
         .route(
             "/api/tenants/:tenant_id",
@@ -35,6 +48,18 @@ pub fn create_router(state: Arc<ApiState>) -> Router {
             "/api/bundles",
             post(handlers::create_policy_bundle).get(handlers::list_policy_bundles),
         )
+        .route(
+            "/api/bundles/from-dsl",
+            post(handlers::create_policy_bundle_from_dsl),
+        )
+        .route(
+            "/api/bundles/select",
+            get(handlers::select_policy_bundle),
+        )
+        .route(
+            "/api/bundles/history",
+            get(handlers::list_bundle_history),
+        )
         .route(
             "/api/bundles/:bundle_id",
             get(handlers::get_policy_bundle),
@@ -43,10 +68,15 @@ pub fn create_router(state: Arc<ApiState>) -> Router {
             "/api/bundles/:bundle_id/activate",
             post(handlers::activate_policy_bundle),
         )
+        .route(
+            "/api/bundles/:bundle_id/promote",
+            post(handlers::promote_policy_bundle),
+        )
         .route(
             "/api/bundles/:bundle_id/archive",
             post(handlers::archive_policy_bundle),
         )
+        .route("/api/config", get(handlers::get_config))
         .route("/health", get(handlers::health_check))
         .with_state(state)
         .layer(middleware)
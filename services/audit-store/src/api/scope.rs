@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    Json,
+};
+use tracing::warn;
+
+use super::types::ErrorResponse;
+use super::ApiState;
+
+/// Header naming the tenant a caller is allowed to act on.
+pub const TENANT_SCOPE_HEADER: &str = "x-tenant-scope";
+
+/// Tenant scope of the caller, read from `X-Tenant-Scope`.
+///
+/// Checks only apply when `enforce_tenant_scope` is enabled; otherwise every
+/// tenant in the request is accepted as before.
+#[derive(Debug, Clone)]
+pub struct TenantScope {
+    enforced: bool,
+    tenant_id: Option<String>,
+}
+
+impl TenantScope {
+    /// Rejects the request with `403` unless it is scoped to `tenant_id`.
+    pub fn check(&self, tenant_id: &str) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+        if !self.enforced {
+            return Ok(());
+        }
+
+        match self.tenant_id.as_deref() {
+            Some(scope) if scope == tenant_id => Ok(()),
+            Some(scope) => {
+                warn!(scope, tenant_id, "rejected request outside tenant scope");
+                Err(forbidden(
+                    "tenant_scope_mismatch",
+                    "request tenant_id does not match the tenant scope",
+                ))
+            }
+            None => Err(forbidden(
+                "tenant_scope_required",
+                "X-Tenant-Scope header is required",
+            )),
+        }
+    }
+
+    /// The single tenant the caller may see, or `None` when scope checks are
+    /// off. Rejects with `403` when checks are on and no scope was sent.
+    pub fn tenant(&self) -> Result<Option<&str>, (StatusCode, Json<ErrorResponse>)> {
+        if !self.enforced {
+            return Ok(None);
+        }
+
+        match self.tenant_id.as_deref() {
+            Some(scope) => Ok(Some(scope)),
+            None => Err(forbidden(
+                "tenant_scope_required",
+                "X-Tenant-Scope header is required",
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<ApiState>> for TenantScope {
+    type Rejection = (StatusCode, Json<ErrorResponse>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<ApiState>,
+    ) -> Result<Self, Self::Rejection> {
+        let tenant_id = parts
+            .headers
+            .get(TENANT_SCOPE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string);
+
+        Ok(Self {
+            enforced: state.config.enforce_tenant_scope,
+            tenant_id,
+        })
+    }
+}
+
+fn forbidden(code: &str, message: &str) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ErrorResponse {
+            error: message.to_string(),
+            code: code.to_string(),
+            details: None,
+        }),
+    )
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::signing::BatchManifest;
+use crate::storage::database::DeadLetterLog;
 use crate::storage::tenant_registry::TenantRecord;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +107,92 @@ pub struct UnuploadedQuery {
     pub limit: Option<usize>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterQuery {
+    pub tenant_id: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterResponse {
+    pub logs: Vec<DeadLetterLog>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryDeadLetterRequest {
+    pub tenant_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryDeadLetterResponse {
+    pub tenant_id: String,
+    pub requeued: usize,
+}
+
+/// Summary returned by `POST /api/logs/bulk`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BulkIngestResponse {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub errors: Vec<BulkLineError>,
+}
+
+/// Why one NDJSON line of a bulk import was not stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkLineError {
+    /// 1-based line number within the request body.
+    pub line: usize,
+    pub code: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ActivateBundleRequest {
+    /// Percentage of subjects routed to the bundle; omitted or 100 activates it fully.
+    pub rollout_percent: Option<u8>,
+    /// Who activated the bundle, recorded in the bundle history.
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub change_reason: Option<String>,
+}
+
+/// DSL policy compiled and stored by `POST /api/bundles/from-dsl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DslBundleRequest {
+    pub tenant_id: String,
+    pub dsl_source: String,
+    /// Bundle metadata object; the DSL source is stored in it as `dsl_source`.
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub change_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromoteBundleRequest {
+    /// Target status: `staged` for a draft, `active` for a staged bundle.
+    pub to: String,
+    /// Reviewer recorded in the bundle's `metadata.promotions`.
+    pub promoted_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyBatchRequest {
+    pub manifest: BatchManifest,
+    pub logs: Vec<AuditLogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyBatchResponse {
+    pub valid: bool,
+    pub tenant_id: String,
+    pub entries: usize,
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
@@ -1,22 +1,62 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
 use uuid::Uuid;
 
-#[derive(Debug, Clone)]
+use crate::upload::UploadCompression;
+
+/// Shown in place of secret values in the configuration dump.
+pub const REDACTED_SECRET: &str = "***";
+/// Environment variable naming a TOML file read by [`AuditStoreConfig::load`].
+pub const CONFIG_FILE_ENV: &str = "AUDIT_CONFIG_FILE";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct AuditStoreConfig {
     pub server_host: String,
     pub server_port: u16,
     pub data_dir: PathBuf,
     pub hmac_secret_key: String,
+    /// Id recorded in signatures made with `hmac_secret_key`.
+    pub hmac_key_id: Option<String>,
+    /// Earlier HMAC secrets by key id, kept to verify the entries they
+    /// signed.
+    pub retired_hmac_keys: BTreeMap<String, String>,
+    /// Base64 Ed25519 seed; when set, new entries are signed with Ed25519 and
+    /// the HMAC key is only used to verify older entries.
+    pub ed25519_signing_key: Option<String>,
+    /// Master key for encrypting audit log fields at rest; each tenant's key
+    /// is derived from it. Unset disables encryption.
+    pub encryption_master_key: Option<String>,
+    /// Tenants whose audit logs are encrypted; empty means every tenant once
+    /// a master key is set.
+    pub encrypted_tenants: Vec<String>,
+    /// Audit log writes accepted per tenant within each ingestion window;
+    /// `0` disables the limit.
+    pub ingest_rate_limit: u32,
+    pub ingest_rate_window_secs: u64,
     pub enable_deferred_upload: bool,
     pub upload_batch_size: usize,
     pub upload_interval_secs: u64,
     pub upload_endpoint: Option<String>,
+    /// Failed upload cycles after which a batch is moved to the dead-letter set.
+    pub upload_max_attempts: u32,
+    pub upload_compression: UploadCompression,
     pub max_log_age_days: u64,
+    /// Require `X-Tenant-Scope` to match the tenant a request acts on.
+    pub enforce_tenant_scope: bool,
+    /// Only let `staged` bundles be activated, so each activation goes
+    /// through `POST /api/bundles/:bundle_id/promote`.
+    pub bundle_promotion_workflow: bool,
+    /// URLs posted to when a tenant record changes; `{tenant_id}` is replaced
+    /// with the tenant.
+    pub tenant_notify_urls: Vec<String>,
     pub log_level: String,
 }
 
@@ -27,19 +67,56 @@ impl Default for AuditStoreConfig {
             server_port: 8182,
             data_dir: PathBuf::from("data/audit"),
             hmac_secret_key: String::new(),
+            hmac_key_id: None,
+            retired_hmac_keys: BTreeMap::new(),
+            ed25519_signing_key: None,
+            encryption_master_key: None,
+            encrypted_tenants: Vec::new(),
+            ingest_rate_limit: 0,
+            ingest_rate_window_secs: 60,
             enable_deferred_upload: true,
             upload_batch_size: 1_000,
             upload_interval_secs: 300,
             upload_endpoint: None,
+            upload_max_attempts: 5,
+            upload_compression: UploadCompression::None,
             max_log_age_days: 90,
+            enforce_tenant_scope: false,
+            bundle_promotion_workflow: false,
+            tenant_notify_urls: Vec::new(),
             log_level: "info".to_string(),
         }
     }
 }
 
 impl AuditStoreConfig {
+    /// Loads the TOML file named by `AUDIT_CONFIG_FILE` when it is set,
+    /// otherwise the environment alone.
+    pub fn load() -> Result<Self> {
+        match env::var(CONFIG_FILE_ENV) {
+            Ok(path) if !path.trim().is_empty() => Self::from_file(path),
+            _ => Self::from_env(),
+        }
+    }
+
     pub fn from_env() -> Result<Self> {
-        let mut cfg = Self::default();
+        Self::default().with_env_overrides()
+    }
+
+    /// Loads a TOML file keyed by field name, e.g. `server_port = 8182`.
+    /// Fields the file leaves out keep their defaults, and environment
+    /// variables override both.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        let cfg: Self = toml::from_str(&raw)
+            .with_context(|| format!("failed to parse config file {}", path.display()))?;
+        cfg.with_env_overrides()
+    }
+
+    fn with_env_overrides(self) -> Result<Self> {
+        let mut cfg = self;
 
         if let Ok(host) = env::var("AUDIT_HOST") {
             cfg.server_host = host;
@@ -50,15 +127,50 @@ impl AuditStoreConfig {
         if let Ok(dir) = env::var("AUDIT_DATA_DIR") {
             cfg.data_dir = PathBuf::from(dir);
         }
-        cfg.hmac_secret_key = env::var("AUDIT_HMAC_SECRET").unwrap_or_else(|_| generate_secret());
+        match env::var("AUDIT_HMAC_SECRET") {
+            Ok(secret) => cfg.hmac_secret_key = secret,
+            Err(_) if cfg.hmac_secret_key.is_empty() => cfg.hmac_secret_key = generate_secret(),
+            Err(_) => {}
+        }
+        if let Ok(key_id) = env::var("AUDIT_HMAC_KEY_ID") {
+            cfg.hmac_key_id = Some(key_id.trim().to_string()).filter(|id| !id.is_empty());
+        }
+        if let Ok(keys) = env::var("AUDIT_RETIRED_HMAC_KEYS") {
+            cfg.retired_hmac_keys = parse_retired_keys(&keys)?;
+        }
 
+        if let Ok(key) = env::var("AUDIT_ED25519_SIGNING_KEY") {
+            cfg.ed25519_signing_key = Some(key.trim().to_string()).filter(|key| !key.is_empty());
+        }
+        if let Ok(key) = env::var("AUDIT_ENCRYPTION_MASTER_KEY") {
+            cfg.encryption_master_key = Some(key).filter(|key| !key.trim().is_empty());
+        }
+        if let Ok(tenants) = env::var("AUDIT_ENCRYPTED_TENANTS") {
+            cfg.encrypted_tenants = tenants
+                .split(',')
+                .map(str::trim)
+                .filter(|tenant| !tenant.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Ok(limit) = env::var("AUDIT_INGEST_RATE_LIMIT") {
+            cfg.ingest_rate_limit = limit
+                .parse()
+                .context("AUDIT_INGEST_RATE_LIMIT must be a non-negative integer")?;
+        }
+        if let Ok(window) = env::var("AUDIT_INGEST_RATE_WINDOW_SECS") {
+            cfg.ingest_rate_window_secs = window
+                .parse()
+                .context("AUDIT_INGEST_RATE_WINDOW_SECS must be a positive integer")?;
+        }
         if let Ok(flag) = env::var("ENABLE_DEFERRED_UPLOAD") {
             cfg.enable_deferred_upload = parse_bool(&flag)
                 .with_context(|| format!("ENABLE_DEFERRED_UPLOAD is invalid: {flag}"))?;
         }
         if let Ok(size) = env::var("UPLOAD_BATCH_SIZE") {
-            cfg.upload_batch_size =
-                size.parse().context("UPLOAD_BATCH_SIZE must be a positive integer")?;
+            cfg.upload_batch_size = size
+                .parse()
+                .context("UPLOAD_BATCH_SIZE must be a positive integer")?;
         }
         if let Ok(interval) = env::var("UPLOAD_INTERVAL_SECS") {
             cfg.upload_interval_secs = interval
@@ -72,9 +184,36 @@ impl AuditStoreConfig {
                 Some(endpoint)
             };
         }
+        if let Ok(attempts) = env::var("UPLOAD_MAX_ATTEMPTS") {
+            cfg.upload_max_attempts = attempts
+                .parse()
+                .context("UPLOAD_MAX_ATTEMPTS must be a positive integer")?;
+        }
+        if let Ok(compression) = env::var("UPLOAD_COMPRESSION") {
+            cfg.upload_compression = compression
+                .parse()
+                .map_err(|err: String| anyhow::anyhow!("UPLOAD_COMPRESSION is invalid: {err}"))?;
+        }
         if let Ok(age) = env::var("MAX_LOG_AGE_DAYS") {
-            cfg.max_log_age_days =
-                age.parse().context("MAX_LOG_AGE_DAYS must be a positive integer")?;
+            cfg.max_log_age_days = age
+                .parse()
+                .context("MAX_LOG_AGE_DAYS must be a positive integer")?;
+        }
+        if let Ok(flag) = env::var("ENFORCE_TENANT_SCOPE") {
+            cfg.enforce_tenant_scope = parse_bool(&flag)
+                .with_context(|| format!("ENFORCE_TENANT_SCOPE is invalid: {flag}"))?;
+        }
+        if let Ok(flag) = env::var("BUNDLE_PROMOTION_WORKFLOW") {
+            cfg.bundle_promotion_workflow = parse_bool(&flag)
+                .with_context(|| format!("BUNDLE_PROMOTION_WORKFLOW is invalid: {flag}"))?;
+        }
+        if let Ok(urls) = env::var("TENANT_NOTIFY_URLS") {
+            cfg.tenant_notify_urls = urls
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect();
         }
         if let Ok(level) = env::var("LOG_LEVEL") {
             cfg.log_level = level;
@@ -84,18 +223,69 @@ impl AuditStoreConfig {
         Ok(cfg)
     }
 
+    /// Effective configuration as served by `GET /api/config`, with secrets
+    /// replaced by [`REDACTED_SECRET`]. Fields are listed explicitly so a new
+    /// secret is never exposed by default.
+    pub fn dump(&self) -> Value {
+        json!({
+            "server_host": self.server_host,
+            "server_port": self.server_port,
+            "data_dir": self.data_dir,
+            "hmac_secret_key": REDACTED_SECRET,
+            "hmac_key_id": self.hmac_key_id,
+            "retired_hmac_keys": self
+                .retired_hmac_keys
+                .keys()
+                .map(|key_id| (key_id.clone(), REDACTED_SECRET))
+                .collect::<BTreeMap<_, _>>(),
+            "ed25519_signing_key": self.ed25519_signing_key.as_ref().map(|_| REDACTED_SECRET),
+            "encryption_master_key": self.encryption_master_key.as_ref().map(|_| REDACTED_SECRET),
+            "encrypted_tenants": self.encrypted_tenants,
+            "ingest_rate_limit": self.ingest_rate_limit,
+            "ingest_rate_window_secs": self.ingest_rate_window_secs,
+            "enable_deferred_upload": self.enable_deferred_upload,
+            "upload_batch_size": self.upload_batch_size,
+            "upload_interval_secs": self.upload_interval_secs,
+            "upload_endpoint": self.upload_endpoint,
+            "upload_max_attempts": self.upload_max_attempts,
+            "upload_compression": self.upload_compression.to_string(),
+            "max_log_age_days": self.max_log_age_days,
+            "enforce_tenant_scope": self.enforce_tenant_scope,
+            "bundle_promotion_workflow": self.bundle_promotion_workflow,
+            "tenant_notify_urls": self.tenant_notify_urls,
+            "log_level": self.log_level,
+        })
+    }
+
     pub fn validate(&self) -> Result<()> {
         ensure_directory(&self.data_dir)?;
+        ensure_writable(&self.data_dir)?;
 
         if self.hmac_secret_key.trim().is_empty() {
             anyhow::bail!("AUDIT_HMAC_SECRET must be provided or auto-generated");
         }
+        if !self.encrypted_tenants.is_empty() && self.encryption_master_key.is_none() {
+            anyhow::bail!("AUDIT_ENCRYPTED_TENANTS requires AUDIT_ENCRYPTION_MASTER_KEY");
+        }
+        if self.ingest_rate_window_secs == 0 {
+            anyhow::bail!("AUDIT_INGEST_RATE_WINDOW_SECS must be greater than zero");
+        }
         if self.upload_batch_size == 0 {
             anyhow::bail!("UPLOAD_BATCH_SIZE must be greater than zero");
         }
         if self.upload_interval_secs == 0 {
             anyhow::bail!("UPLOAD_INTERVAL_SECS must be greater than zero");
         }
+        if self.upload_max_attempts == 0 {
+            anyhow::bail!("UPLOAD_MAX_ATTEMPTS must be greater than zero");
+        }
+        if let Some(url) = self
+            .tenant_notify_urls
+            .iter()
+            .find(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            anyhow::bail!("TENANT_NOTIFY_URLS entry must start with http:// or https://: {url}");
+        }
         Ok(())
     }
 }
@@ -112,6 +302,16 @@ fn ensure_directory(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Writes and removes a probe file so a read-only data directory is reported
+/// before the service starts rather than on its first write.
+fn ensure_writable(path: &Path) -> Result<()> {
+    let probe = path.join(".write-check");
+    fs::write(&probe, b"")
+        .with_context(|| format!("data directory {} is not writable", path.display()))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
 fn parse_bool(value: &str) -> Result<bool> {
     match value.to_ascii_lowercase().as_str() {
         "true" | "1" | "yes" | "y" => Ok(true),
@@ -120,6 +320,22 @@ fn parse_bool(value: &str) -> Result<bool> {
     }
 }
 
+/// Parses `key_id=secret` pairs separated by commas. Secrets may contain `=`
+/// (base64 padding); key ids may not.
+fn parse_retired_keys(value: &str) -> Result<BTreeMap<String, String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key_id, secret) = pair.split_once('=').with_context(|| {
+                format!("AUDIT_RETIRED_HMAC_KEYS entries must be key_id=secret, got '{pair}'")
+            })?;
+            Ok((key_id.trim().to_string(), secret.trim().to_string()))
+        })
+        .collect()
+}
+
 fn generate_secret() -> String {
     let seed = format!(
         "{}:{}",
@@ -128,3 +344,54 @@ fn generate_secret() -> String {
     );
     base64::encode(seed)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_file_values_load_and_env_overrides_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join("audit");
+        let config_path = dir.path().join("audit-store.toml");
+        fs::write(
+            &config_path,
+            format!(
+                r#"
+server_port = 9182
+data_dir = "{}"
+hmac_secret_key = "file-secret"
+upload_compression = "gzip"
+encryption_master_key = "audit-encryption-master-key-32-bytes!"
+encrypted_tenants = ["tenant-a"]
+
+[retired_hmac_keys]
+k1 = "old-secret"
+"#,
+                data_dir.display()
+            ),
+        )
+        .unwrap();
+
+        let cfg = AuditStoreConfig::from_file(&config_path).unwrap();
+        assert_eq!(cfg.server_port, 9182);
+        assert_eq!(cfg.data_dir, data_dir);
+        assert_eq!(cfg.hmac_secret_key, "file-secret");
+        assert_eq!(cfg.upload_compression, UploadCompression::Gzip);
+        assert_eq!(cfg.encrypted_tenants, vec!["tenant-a"]);
+        assert_eq!(
+            cfg.retired_hmac_keys.get("k1").map(String::as_str),
+            Some("old-secret")
+        );
+        // Left out of the file, so the default applies
+        assert_eq!(cfg.upload_batch_size, 1_000);
+
+        env::set_var("AUDIT_PORT", "9282");
+        let cfg = AuditStoreConfig::from_file(&config_path);
+        env::remove_var("AUDIT_PORT");
+
+        let cfg = cfg.unwrap();
+        assert_eq!(cfg.server_port, 9282);
+        assert_eq!(cfg.hmac_secret_key, "file-secret");
+    }
+}
@@ -1,5 +1,6 @@
 mod api;
 mod config;
+mod notify;
 mod signing;
 mod storage;
 mod upload;
@@ -10,7 +11,8 @@ use std::sync::Arc;
 use anyhow::Result;
 use axum::Server;
 use tracing::{info, warn};
-use tracing_subscriber::{fmt, EnvFilter};
+use edge_policy_telemetry::LogFormat;
+use tracing_subscriber::EnvFilter;
 
 use api::ApiState;
 use config::AuditStoreConfig;
@@ -18,9 +20,15 @@ use upload::UploadQueue;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if std::env::args().skip(1).any(|arg| arg == "--check-config") {
+        let config = AuditStoreConfig::load()?;
+        println!("configuration ok (data_dir: {})", config.data_dir.display());
+        return Ok(());
+    }
+
     init_tracing()?;
 
-    let config = AuditStoreConfig::from_env()?;
+    let config = AuditStoreConfig::load()?;
     let host = config.server_host.clone();
     let port = config.server_port;
 
@@ -38,6 +46,7 @@ async fn main() -> Result<()> {
         let queue = UploadQueue::new(
             Arc::clone(&state.database),
             Arc::clone(&state.tenant_registry),
+            Arc::clone(&state.signer),
             &state.config,
         );
         queue.start();
@@ -59,7 +68,7 @@ async fn main() -> Result<()> {
 
 fn init_tracing() -> Result<()> {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    fmt().with_env_filter(filter).try_init()?;
+    edge_policy_telemetry::try_init_tracing(filter, LogFormat::from_env()?)?;
     Ok(())
 }
 
@@ -0,0 +1,7 @@
+pub mod tenant;
+
+pub use tenant::TenantChangeNotifier;
+
+/// Placeholder replaced with the tenant id in notification URLs.
+pub const TENANT_ID_PLACEHOLDER: &str = "{tenant_id}";
+pub const DEFAULT_NOTIFY_TIMEOUT_SECS: u64 = 5;
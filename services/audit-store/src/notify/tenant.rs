@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::json;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::config::AuditStoreConfig;
+
+use super::{DEFAULT_NOTIFY_TIMEOUT_SECS, TENANT_ID_PLACEHOLDER};
+
+/// Tells dependent services that a tenant's record changed so they refresh
+/// it without waiting for a manual reload.
+///
+/// Each target URL is posted to once per change, with `{tenant_id}` replaced
+/// by the tenant. Pointing a target at the enforcer's
+/// `/v1/tenants/{tenant_id}/reload` reuses its existing reload call.
+#[derive(Clone)]
+pub struct TenantChangeNotifier {
+    http_client: Client,
+    target_urls: Vec<String>,
+}
+
+impl TenantChangeNotifier {
+    pub fn new(config: &AuditStoreConfig) -> Self {
+        let client = Client::builder()
+            .user_agent("edge-policy-audit-store/0.1.0")
+            .timeout(Duration::from_secs(DEFAULT_NOTIFY_TIMEOUT_SECS))
+            .build()
+            .expect("failed to build HTTP client");
+
+        Self {
+            http_client: client,
+            target_urls: config.tenant_notify_urls.clone(),
+        }
+    }
+
+    /// Posts a `tenant_updated` notification to every target in the
+    /// background. Failures are logged and never fail the caller's request.
+    pub fn notify_tenant_updated(&self, tenant_id: &str) -> Option<JoinHandle<()>> {
+        if self.target_urls.is_empty() {
+            return None;
+        }
+
+        let notifier = self.clone();
+        let tenant_id = tenant_id.to_string();
+        Some(tokio::spawn(async move {
+            for target in &notifier.target_urls {
+                notifier.post(target, &tenant_id).await;
+            }
+        }))
+    }
+
+    async fn post(&self, target: &str, tenant_id: &str) {
+        let url = target.replace(TENANT_ID_PLACEHOLDER, tenant_id);
+        let body = json!({
+            "event": "tenant_updated",
+            "tenant_id": tenant_id,
+        });
+
+        match self.http_client.post(&url).json(&body).send().await {
+            Ok(response) if response.status().is_success() => {
+                debug!(tenant_id, url = %url, "notified tenant change");
+            }
+            Ok(response) => {
+                warn!(
+                    tenant_id,
+                    url = %url,
+                    status = %response.status(),
+                    "tenant change notification rejected"
+                );
+            }
+            Err(err) => {
+                warn!(tenant_id, url = %url, error = %err, "tenant change notification failed");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_targets_means_no_notification() {
+        let notifier = TenantChangeNotifier::new(&AuditStoreConfig::default());
+        assert!(notifier.notify_tenant_updated("tenant-a").is_none());
+    }
+}
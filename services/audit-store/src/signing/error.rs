@@ -8,4 +8,8 @@ pub enum SigningError {
     SignatureMismatch,
     #[error("encoding error: {0}")]
     EncodingError(String),
+    #[error("unsupported signature version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("batch manifest mismatch: {0}")]
+    ManifestMismatch(String),
 }
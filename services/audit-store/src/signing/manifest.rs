@@ -0,0 +1,225 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::api::types::AuditLogEntry;
+
+use super::error::SigningError;
+use super::scheme::SignatureScheme;
+use super::signer::{audit_log_digest, Signer};
+
+/// A single audit log covered by a batch manifest.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub log_id: String,
+    /// Hex-encoded SHA-256 of the entry's canonical payload.
+    pub hash: String,
+}
+
+/// Describes one uploaded batch of audit logs.
+///
+/// The batch hash commits to the tenant and the ordered list of entries, and
+/// the signature covers the batch hash, so a verifier holding the same key can
+/// detect entries that were dropped, reordered or altered within the batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchManifest {
+    pub tenant_id: String,
+    pub entries: Vec<ManifestEntry>,
+    pub batch_hash: String,
+    pub signature: String,
+    pub algorithm: String,
+    pub version: u8,
+    pub created_at: String,
+}
+
+impl BatchManifest {
+    pub fn build(
+        signer: &Signer,
+        tenant_id: &str,
+        logs: &[AuditLogEntry],
+    ) -> Result<Self, SigningError> {
+        let entries = logs
+            .iter()
+            .map(|log| {
+                Ok(ManifestEntry {
+                    log_id: log.log_id.clone(),
+                    hash: audit_log_digest(log)?,
+                })
+            })
+            .collect::<Result<Vec<_>, SigningError>>()?;
+
+        let batch_hash = batch_hash(tenant_id, &entries);
+        let scheme = signer.scheme();
+        let signature = signer.sign(batch_hash.as_bytes())?;
+
+        debug!(
+            tenant_id = %tenant_id,
+            entries = entries.len(),
+            "built audit batch manifest"
+        );
+
+        Ok(Self {
+            tenant_id: tenant_id.to_string(),
+            entries,
+            batch_hash,
+            signature,
+            algorithm: scheme.algorithm().to_string(),
+            version: scheme.version(),
+            created_at: Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Checks the manifest signature and that `logs` is exactly the batch it
+    /// describes.
+    pub fn verify(&self, signer: &Signer, logs: &[AuditLogEntry]) -> Result<(), SigningError> {
+        if batch_hash(&self.tenant_id, &self.entries) != self.batch_hash {
+            return Err(SigningError::ManifestMismatch(
+                "batch hash does not match manifest entries".into(),
+            ));
+        }
+
+        let scheme = SignatureScheme::from_version(self.version)?;
+        if scheme.algorithm() != self.algorithm {
+            return Err(SigningError::ManifestMismatch(format!(
+                "algorithm {} does not match signature version {}",
+                self.algorithm, self.version
+            )));
+        }
+        if !signer.verify(scheme, self.batch_hash.as_bytes(), &self.signature)? {
+            return Err(SigningError::SignatureMismatch);
+        }
+
+        for entry in &self.entries {
+            let log = logs
+                .iter()
+                .find(|log| log.log_id == entry.log_id)
+                .ok_or_else(|| {
+                    SigningError::ManifestMismatch(format!(
+                        "log {} is missing from the batch",
+                        entry.log_id
+                    ))
+                })?;
+
+            if log.tenant_id != self.tenant_id || audit_log_digest(log)? != entry.hash {
+                return Err(SigningError::ManifestMismatch(format!(
+                    "log {} does not match its manifest hash",
+                    entry.log_id
+                )));
+            }
+        }
+
+        if logs.len() != self.entries.len() {
+            return Err(SigningError::ManifestMismatch(format!(
+                "batch contains {} logs but the manifest lists {}",
+                logs.len(),
+                self.entries.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn batch_hash(tenant_id: &str, entries: &[ManifestEntry]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(tenant_id.as_bytes());
+    for entry in entries {
+        hasher.update(b"\n");
+        hasher.update(entry.log_id.as_bytes());
+        hasher.update(b":");
+        hasher.update(entry.hash.as_bytes());
+    }
+    hex_encode(&hasher.finalize())
+}
+
+pub(crate) fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "manifest-test-secret-key-of-32-bytes!";
+
+    fn log(log_id: &str) -> AuditLogEntry {
+        AuditLogEntry {
+            log_id: log_id.to_string(),
+            tenant_id: "tenant-a".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            decision: "allow".to_string(),
+            protocol: "http".to_string(),
+            subject: serde_json::json!({"user_id": "u1"}),
+            action: "read".to_string(),
+            resource: serde_json::json!({"type": "sensor_data"}),
+            environment: serde_json::json!({}),
+            policy_version: Some(1),
+            reason: None,
+            signature: String::new(),
+            uploaded: false,
+        }
+    }
+
+    #[test]
+    fn manifest_verifies_against_its_batch() {
+        let signer = Signer::new(SECRET).unwrap();
+        let logs = vec![log("log-1"), log("log-2"), log("log-3")];
+
+        let manifest = BatchManifest::build(&signer, "tenant-a", &logs).unwrap();
+
+        assert_eq!(manifest.entries.len(), 3);
+        manifest.verify(&signer, &logs).unwrap();
+    }
+
+    #[test]
+    fn removed_entry_is_detected() {
+        let signer = Signer::new(SECRET).unwrap();
+        let mut logs = vec![log("log-1"), log("log-2"), log("log-3")];
+        let manifest = BatchManifest::build(&signer, "tenant-a", &logs).unwrap();
+
+        logs.remove(1);
+        assert!(matches!(
+            manifest.verify(&signer, &logs),
+            Err(SigningError::ManifestMismatch(_))
+        ));
+
+        let mut trimmed = manifest.clone();
+        trimmed.entries.remove(1);
+        assert!(matches!(
+            trimmed.verify(&signer, &logs),
+            Err(SigningError::ManifestMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn manifest_signed_before_rotation_still_verifies() {
+        let logs = vec![log("log-1"), log("log-2")];
+        let manifest =
+            BatchManifest::build(&Signer::new(SECRET).unwrap(), "tenant-a", &logs).unwrap();
+
+        let rotated = Signer::new(SECRET)
+            .unwrap()
+            .with_ed25519_key("AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA=")
+            .unwrap();
+        manifest.verify(&rotated, &logs).unwrap();
+
+        let rotated_manifest = BatchManifest::build(&rotated, "tenant-a", &logs).unwrap();
+        assert_eq!(rotated_manifest.version, 2);
+        assert_eq!(rotated_manifest.algorithm, "Ed25519");
+        rotated_manifest.verify(&rotated, &logs).unwrap();
+    }
+
+    #[test]
+    fn tampered_signature_is_rejected() {
+        let signer = Signer::new(SECRET).unwrap();
+        let other = Signer::new("another-manifest-secret-key-32-bytes!").unwrap();
+        let logs = vec![log("log-1")];
+        let manifest = BatchManifest::build(&other, "tenant-a", &logs).unwrap();
+
+        assert!(matches!(
+            manifest.verify(&signer, &logs),
+            Err(SigningError::SignatureMismatch)
+        ));
+    }
+}
@@ -1,8 +1,8 @@
 pub mod error;
+pub mod manifest;
+pub mod scheme;
 pub mod signer;
 
 pub use error::SigningError;
+pub use manifest::BatchManifest;
 pub use signer::Signer;
-
-pub const SIGNATURE_ALGORITHM: &str = "HMAC-SHA256";
-pub const SIGNATURE_VERSION: u8 = 1;
@@ -0,0 +1,68 @@
+use super::error::SigningError;
+
+/// Signing algorithms the store can produce or verify, identified by version.
+///
+/// Each signature records the version it was made with, so entries signed
+/// before a key rotation keep verifying after the active scheme changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// Version 1: HMAC-SHA256 over the canonical payload with the shared secret.
+    HmacSha256,
+    /// Version 2: Ed25519 over the canonical payload.
+    Ed25519,
+}
+
+impl SignatureScheme {
+    pub fn version(self) -> u8 {
+        match self {
+            SignatureScheme::HmacSha256 => 1,
+            SignatureScheme::Ed25519 => 2,
+        }
+    }
+
+    pub fn algorithm(self) -> &'static str {
+        match self {
+            SignatureScheme::HmacSha256 => "HMAC-SHA256",
+            SignatureScheme::Ed25519 => "Ed25519",
+        }
+    }
+
+    pub fn from_version(version: u8) -> Result<Self, SigningError> {
+        match version {
+            1 => Ok(SignatureScheme::HmacSha256),
+            2 => Ok(SignatureScheme::Ed25519),
+            other => Err(SigningError::UnsupportedVersion(other)),
+        }
+    }
+
+    /// Prefixes `signature` with this scheme's version, e.g. `v2:<base64>`.
+    pub fn tag(self, signature: &str) -> String {
+        format!("v{}:{}", self.version(), signature)
+    }
+
+    /// Like [`tag`](Self::tag), also naming the key that made the signature,
+    /// e.g. `v1:<key_id>:<base64>`.
+    pub fn tag_with_key(self, key_id: &str, signature: &str) -> String {
+        format!("v{}:{}:{}", self.version(), key_id, signature)
+    }
+
+    /// Splits a stored signature into its scheme, key id and base64 part.
+    /// Signatures without a version prefix predate versioning and are
+    /// HMAC-SHA256; those without a key id predate key rotation.
+    pub fn parse_tagged(signature: &str) -> Result<(Self, Option<&str>, &str), SigningError> {
+        let Some((tag, rest)) = signature.split_once(':') else {
+            return Ok((SignatureScheme::HmacSha256, None, signature));
+        };
+        let version = tag
+            .strip_prefix('v')
+            .and_then(|version| version.parse::<u8>().ok())
+            .ok_or_else(|| {
+                SigningError::EncodingError(format!("invalid signature version tag '{}'", tag))
+            })?;
+        let scheme = Self::from_version(version)?;
+        Ok(match rest.split_once(':') {
+            Some((key_id, encoded)) => (scheme, Some(key_id), encoded),
+            None => (scheme, None, rest),
+        })
+    }
+}
@@ -1,65 +1,223 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _};
 use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use tracing::debug;
 use std::collections::BTreeMap;
 
 use crate::api::types::AuditLogEntry;
 
 use super::error::SigningError;
+use super::manifest::hex_encode;
+use super::scheme::SignatureScheme;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Signs with the active scheme and verifies signatures of every scheme it
+/// holds a key for.
+///
+/// The HMAC secret is always present so version 1 signatures written before a
+/// rotation stay verifiable. Adding an Ed25519 key makes version 2 the active
+/// scheme for new signatures.
+///
+/// HMAC secrets can be rotated as well: the current secret is given a key id
+/// that new signatures record, and earlier secrets are kept as retired keys
+/// under the ids their signatures carry.
 pub struct Signer {
     key: Vec<u8>,
+    key_id: Option<String>,
+    retired_keys: BTreeMap<String, Vec<u8>>,
+    ed25519_key: Option<SigningKey>,
 }
 
 impl Signer {
     pub fn new(secret: &str) -> Result<Self, SigningError> {
-        if secret.trim().is_empty() {
-            return Err(SigningError::InvalidKey("secret cannot be empty".into()));
-        }
+        Ok(Self {
+            key: decode_hmac_secret(secret)?,
+            key_id: None,
+            retired_keys: BTreeMap::new(),
+            ed25519_key: None,
+        })
+    }
 
-        let decoded = base64::decode(secret).unwrap_or_else(|_| secret.as_bytes().to_vec());
-        if decoded.len() < 32 {
-            return Err(SigningError::InvalidKey(
-                "signing key must be at least 32 bytes".into(),
-            ));
-        }
+    /// Records `key_id` in new HMAC signatures so verification can tell
+    /// which secret made them.
+    pub fn with_key_id(mut self, key_id: &str) -> Result<Self, SigningError> {
+        self.key_id = Some(validate_key_id(key_id)?.to_string());
+        Ok(self)
+    }
+
+    /// Keeps a rotated-out HMAC secret for verifying entries it signed.
+    pub fn with_retired_key(mut self, key_id: &str, secret: &str) -> Result<Self, SigningError> {
+        let key_id = validate_key_id(key_id)?;
+        self.retired_keys
+            .insert(key_id.to_string(), decode_hmac_secret(secret)?);
+        Ok(self)
+    }
+
+    /// Signs new entries with Ed25519 using a base64-encoded 32-byte seed.
+    pub fn with_ed25519_key(mut self, seed: &str) -> Result<Self, SigningError> {
+        let decoded = BASE64.decode(seed.trim())
+            .map_err(|err| SigningError::InvalidKey(format!("Ed25519 key is not base64: {}", err)))?;
+        let seed: [u8; 32] = decoded.as_slice().try_into().map_err(|_| {
+            SigningError::InvalidKey(format!(
+                "Ed25519 key must be 32 bytes, got {}",
+                decoded.len()
+            ))
+        })?;
+        self.ed25519_key = Some(SigningKey::from_bytes(&seed));
+        Ok(self)
+    }
 
-        Ok(Self { key: decoded })
+    /// Scheme used for new signatures.
+    pub fn scheme(&self) -> SignatureScheme {
+        if self.ed25519_key.is_some() {
+            SignatureScheme::Ed25519
+        } else {
+            SignatureScheme::HmacSha256
+        }
     }
 
+    /// Signs `data` with the active scheme and returns the bare base64
+    /// signature.
     pub fn sign(&self, data: &[u8]) -> Result<String, SigningError> {
-        let mut mac =
-            HmacSha256::new_from_slice(&self.key).map_err(|err| SigningError::InvalidKey(err.to_string()))?;
-        mac.update(data);
-        let result = mac.finalize().into_bytes();
-        Ok(base64::encode(result))
+        match &self.ed25519_key {
+            Some(key) => Ok(BASE64.encode(key.sign(data).to_bytes())),
+            None => {
+                let mut mac = HmacSha256::new_from_slice(&self.key)
+                    .map_err(|err| SigningError::InvalidKey(err.to_string()))?;
+                mac.update(data);
+                let result = mac.finalize().into_bytes();
+                Ok(BASE64.encode(result))
+            }
+        }
     }
 
-    pub fn verify(&self, data: &[u8], signature: &str) -> Result<bool, SigningError> {
-        let decoded = base64::decode(signature)
+    /// Verifies a bare base64 signature made with `scheme`, trying every
+    /// HMAC key held.
+    pub fn verify(
+        &self,
+        scheme: SignatureScheme,
+        data: &[u8],
+        signature: &str,
+    ) -> Result<bool, SigningError> {
+        self.verify_with_key_id(scheme, None, data, signature)
+    }
+
+    /// Like [`verify`](Self::verify), trying the HMAC key named `key_id`
+    /// first.
+    fn verify_with_key_id(
+        &self,
+        scheme: SignatureScheme,
+        key_id: Option<&str>,
+        data: &[u8],
+        signature: &str,
+    ) -> Result<bool, SigningError> {
+        let decoded = BASE64.decode(signature)
             .map_err(|err| SigningError::EncodingError(err.to_string()))?;
-        let mut mac =
-            HmacSha256::new_from_slice(&self.key).map_err(|err| SigningError::InvalidKey(err.to_string()))?;
-        mac.update(data);
 
-        Ok(mac
-            .verify_slice(&decoded)
-            .map(|_| true)
-            .unwrap_or(false))
+        match scheme {
+            SignatureScheme::HmacSha256 => {
+                for key in self.hmac_keys(key_id) {
+                    let mut mac = HmacSha256::new_from_slice(key)
+                        .map_err(|err| SigningError::InvalidKey(err.to_string()))?;
+                    mac.update(data);
+                    if mac.verify_slice(&decoded).is_ok() {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            SignatureScheme::Ed25519 => {
+                let key = self.ed25519_key.as_ref().ok_or_else(|| {
+                    SigningError::InvalidKey("no Ed25519 key configured".into())
+                })?;
+                let Ok(signature) = Signature::from_slice(&decoded) else {
+                    return Ok(false);
+                };
+                Ok(key.verifying_key().verify(data, &signature).is_ok())
+            }
+        }
     }
 
+    /// Signs an entry with the active scheme. The result carries the scheme
+    /// version, e.g. `v2:<base64>`, and for HMAC the key id when one is set.
     pub fn sign_audit_log(&self, log: &AuditLogEntry) -> Result<String, SigningError> {
         let payload = canonical_payload(log)?;
+        let scheme = self.scheme();
         let signature = self.sign(payload.as_bytes())?;
+        let signature = match (scheme, &self.key_id) {
+            (SignatureScheme::HmacSha256, Some(key_id)) => scheme.tag_with_key(key_id, &signature),
+            _ => scheme.tag(&signature),
+        };
         debug!(
             tenant_id = %log.tenant_id,
             log_id = %log.log_id,
+            version = scheme.version(),
             "generated audit log signature"
         );
         Ok(signature)
     }
+
+    /// Verifies an entry with the scheme recorded in its signature.
+    pub fn verify_audit_log(&self, log: &AuditLogEntry) -> Result<bool, SigningError> {
+        let payload = canonical_payload(log)?;
+        let (scheme, key_id, signature) = SignatureScheme::parse_tagged(&log.signature)?;
+        self.verify_with_key_id(scheme, key_id, payload.as_bytes(), signature)
+    }
+
+    /// HMAC keys to try for a signature: the one named `key_id` first, then
+    /// the current secret and the retired ones.
+    fn hmac_keys(&self, key_id: Option<&str>) -> Vec<&[u8]> {
+        let mut keys: Vec<(Option<&str>, &[u8])> =
+            std::iter::once((self.key_id.as_deref(), &self.key[..]))
+                .chain(
+                    self.retired_keys
+                        .iter()
+                        .map(|(id, key)| (Some(id.as_str()), &key[..])),
+                )
+                .collect();
+        if key_id.is_some() {
+            keys.sort_by_key(|(id, _)| *id != key_id);
+        }
+        keys.into_iter().map(|(_, key)| key).collect()
+    }
+}
+
+fn decode_hmac_secret(secret: &str) -> Result<Vec<u8>, SigningError> {
+    if secret.trim().is_empty() {
+        return Err(SigningError::InvalidKey("secret cannot be empty".into()));
+    }
+
+    let decoded = BASE64
+        .decode(secret)
+        .unwrap_or_else(|_| secret.as_bytes().to_vec());
+    if decoded.len() < 32 {
+        return Err(SigningError::InvalidKey(
+            "signing key must be at least 32 bytes".into(),
+        ));
+    }
+    Ok(decoded)
+}
+
+/// Key ids are stored inside `:`-separated signatures.
+fn validate_key_id(key_id: &str) -> Result<&str, SigningError> {
+    let key_id = key_id.trim();
+    if key_id.is_empty() || key_id.contains(':') {
+        return Err(SigningError::InvalidKey(format!(
+            "key id '{}' must be non-empty and cannot contain ':'",
+            key_id
+        )));
+    }
+    Ok(key_id)
+}
+
+/// Hex-encoded SHA-256 of an entry's canonical payload, used to reference
+/// individual logs from a batch manifest.
+pub fn audit_log_digest(log: &AuditLogEntry) -> Result<String, SigningError> {
+    let payload = canonical_payload(log)?;
+    Ok(hex_encode(&Sha256::digest(payload.as_bytes())))
 }
 
 fn canonical_payload(log: &AuditLogEntry) -> Result<String, SigningError> {
@@ -113,3 +271,122 @@ fn sort_json_keys(value: &serde_json::Value) -> serde_json::Value {
         other => other.clone(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "signer-test-secret-key-of-32-bytes!!";
+    const ROTATED_SECRET: &str = "rotated-signer-secret-key-of-32-bytes";
+    const ED25519_SEED: &str = "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA=";
+
+    fn log(log_id: &str) -> AuditLogEntry {
+        AuditLogEntry {
+            log_id: log_id.to_string(),
+            tenant_id: "tenant-a".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            decision: "allow".to_string(),
+            protocol: "http".to_string(),
+            subject: serde_json::json!({"user_id": "u1"}),
+            action: "read".to_string(),
+            resource: serde_json::json!({"type": "sensor_data"}),
+            environment: serde_json::json!({}),
+            policy_version: Some(1),
+            reason: None,
+            signature: String::new(),
+            uploaded: false,
+        }
+    }
+
+    #[test]
+    fn hmac_and_ed25519_entries_verify_after_rotation() {
+        let before = Signer::new(SECRET).unwrap();
+        let mut v1 = log("log-1");
+        v1.signature = before.sign_audit_log(&v1).unwrap();
+        assert!(v1.signature.starts_with("v1:"));
+
+        let rotated = Signer::new(SECRET)
+            .unwrap()
+            .with_ed25519_key(ED25519_SEED)
+            .unwrap();
+        assert_eq!(rotated.scheme(), SignatureScheme::Ed25519);
+        let mut v2 = log("log-2");
+        v2.signature = rotated.sign_audit_log(&v2).unwrap();
+        assert!(v2.signature.starts_with("v2:"));
+
+        assert!(rotated.verify_audit_log(&v1).unwrap());
+        assert!(rotated.verify_audit_log(&v2).unwrap());
+
+        v2.decision = "deny".to_string();
+        assert!(!rotated.verify_audit_log(&v2).unwrap());
+    }
+
+    #[test]
+    fn untagged_signatures_are_read_as_hmac() {
+        let signer = Signer::new(SECRET).unwrap();
+        let mut legacy = log("log-1");
+        let payload = canonical_payload(&legacy).unwrap();
+        legacy.signature = signer.sign(payload.as_bytes()).unwrap();
+
+        assert!(signer.verify_audit_log(&legacy).unwrap());
+    }
+
+    #[test]
+    fn new_entries_are_signed_with_the_primary_key_id() {
+        let signer = Signer::new(ROTATED_SECRET)
+            .unwrap()
+            .with_key_id("2025-06")
+            .unwrap()
+            .with_retired_key("2025-01", SECRET)
+            .unwrap();
+        let mut entry = log("log-1");
+        entry.signature = signer.sign_audit_log(&entry).unwrap();
+
+        assert!(entry.signature.starts_with("v1:2025-06:"));
+        assert!(signer.verify_audit_log(&entry).unwrap());
+
+        // The primary secret made it, not the retired one.
+        let retired_only = Signer::new(SECRET).unwrap();
+        assert!(!retired_only.verify_audit_log(&entry).unwrap());
+    }
+
+    #[test]
+    fn entries_signed_under_a_retired_key_still_verify() {
+        let before = Signer::new(SECRET).unwrap().with_key_id("2025-01").unwrap();
+        let mut keyed = log("log-1");
+        keyed.signature = before.sign_audit_log(&keyed).unwrap();
+        // Signed before key ids were recorded.
+        let mut unkeyed = log("log-2");
+        unkeyed.signature = Signer::new(SECRET)
+            .unwrap()
+            .sign_audit_log(&unkeyed)
+            .unwrap();
+
+        let rotated = Signer::new(ROTATED_SECRET)
+            .unwrap()
+            .with_key_id("2025-06")
+            .unwrap()
+            .with_retired_key("2025-01", SECRET)
+            .unwrap();
+        assert!(rotated.verify_audit_log(&keyed).unwrap());
+        assert!(rotated.verify_audit_log(&unkeyed).unwrap());
+
+        let without_retired = Signer::new(ROTATED_SECRET).unwrap();
+        assert!(!without_retired.verify_audit_log(&keyed).unwrap());
+
+        keyed.decision = "deny".to_string();
+        assert!(!rotated.verify_audit_log(&keyed).unwrap());
+    }
+
+    #[test]
+    fn unknown_signature_version_is_rejected() {
+        let signer = Signer::new(SECRET).unwrap();
+        let mut entry = log("log-1");
+        entry.signature = "v9:AAAA".to_string();
+
+        assert!(matches!(
+            signer.verify_audit_log(&entry),
+            Err(SigningError::UnsupportedVersion(9))
+        ));
+    }
+}
@@ -2,14 +2,19 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
+use chrono::Utc;
 use dashmap::DashMap;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tracing::{debug, info};
 
 use crate::api::types::AuditLogEntry;
 
+use super::encryption::{open_plain, FieldCipher};
 use super::error::StorageError;
-use super::{schema::init_database, AUDIT_DB_FILENAME};
+use super::schema::{init_database, migrate_audit_logs};
+use super::AUDIT_DB_FILENAME;
 
 #[derive(Clone, Debug, Default)]
 pub struct LogFilter {
@@ -20,9 +25,23 @@ pub struct LogFilter {
     pub limit: Option<usize>,
 }
 
+/// A log entry the upload queue gave up on after repeated failures.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeadLetterLog {
+    pub log_id: String,
+    pub tenant_id: String,
+    pub timestamp: String,
+    pub upload_attempts: u32,
+    pub last_upload_error: Option<String>,
+    pub dead_lettered_at: Option<String>,
+}
+
+const LOG_COLUMNS: &str = "log_id, tenant_id, timestamp, decision, protocol, subject, action, resource, environment, policy_version, reason, signature, uploaded";
+
 pub struct AuditDatabase {
     data_dir: PathBuf,
     connections: DashMap<String, Arc<Mutex<Connection>>>,
+    cipher: Option<FieldCipher>,
 }
 
 impl AuditDatabase {
@@ -31,9 +50,30 @@ impl AuditDatabase {
         Ok(Self {
             data_dir,
             connections: DashMap::new(),
+            cipher: None,
         })
     }
 
+    /// Encrypts the `subject`, `resource` and `environment` columns of new
+    /// entries and decrypts them on read. Signatures are computed before the
+    /// entry reaches storage, so they still cover the plaintext.
+    pub fn with_encryption(mut self, cipher: FieldCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Confirms the data directory holding per-tenant databases is reachable.
+    pub fn check_data_dir(&self) -> Result<(), StorageError> {
+        let metadata = std::fs::metadata(&self.data_dir)?;
+        if !metadata.is_dir() {
+            return Err(StorageError::IoError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} is not a directory", self.data_dir.display()),
+            )));
+        }
+        Ok(())
+    }
+
     fn tenant_dir(&self, tenant_id: &str) -> PathBuf {
         self.data_dir.join(tenant_id)
     }
@@ -57,6 +97,8 @@ impl AuditDatabase {
         if is_new {
             init_database(&conn)?;
             info!(tenant_id, "initialized audit database");
+        } else {
+            migrate_audit_logs(&conn)?;
         }
 
         let conn = Arc::new(Mutex::new(conn));
@@ -75,9 +117,9 @@ impl AuditDatabase {
             .lock()
             .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
 
-        let subject = serde_json::to_string(&log.subject)?;
-        let resource = serde_json::to_string(&log.resource)?;
-        let environment = serde_json::to_string(&log.environment)?;
+        let subject = self.seal_field(tenant_id, &log.subject)?;
+        let resource = self.seal_field(tenant_id, &log.resource)?;
+        let environment = self.seal_field(tenant_id, &log.environment)?;
 
         conn.execute(
             r#"
@@ -150,7 +192,7 @@ impl AuditDatabase {
         }
 
         let mut sql = format!(
-            "SELECT log_id, tenant_id, timestamp, decision, protocol, subject, action, resource, environment, policy_version, reason, signature, uploaded FROM audit_logs WHERE {} ORDER BY timestamp DESC",
+            "SELECT {LOG_COLUMNS} FROM audit_logs WHERE {} ORDER BY timestamp DESC",
             conditions.join(" AND ")
         );
 
@@ -167,40 +209,12 @@ impl AuditDatabase {
                 .map(|(k, v)| (k.as_str(), v))
                 .collect::<Vec<_>>()
                 .as_slice(),
-            |row| {
-                let subject: String = row.get(5)?;
-                let resource: String = row.get(7)?;
-                let environment: String = row.get(8)?;
-
-                Ok(AuditLogEntry {
-                    log_id: row.get(0)?,
-                    tenant_id: row.get(1)?,
-                    timestamp: row.get(2)?,
-                    decision: row.get(3)?,
-                    protocol: row.get(4)?,
-                    subject: serde_json::from_str(&subject)?,
-                    action: row.get(6)?,
-                    resource: serde_json::from_str(&resource)?,
-                    environment: serde_json::from_str(&environment)?,
-                    policy_version: row
-                        .get::<_, Option<i64>>(9)?
-                        .and_then(|value| {
-                            if value < 0 {
-                                None
-                            } else {
-                                u32::try_from(value).ok()
-                            }
-                        }),
-                    reason: row.get(10)?,
-                    signature: row.get(11)?,
-                    uploaded: row.get::<_, i64>(12)? != 0,
-                })
-            },
+            stored_log,
         )?;
 
         let mut results = Vec::new();
         for row in rows {
-            results.push(row?);
+            results.push(self.open_log(tenant_id, row?)?);
         }
 
         Ok(results)
@@ -215,49 +229,21 @@ impl AuditDatabase {
         let conn = conn
             .lock()
             .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT log_id, tenant_id, timestamp, decision, protocol, subject, action, resource, environment, policy_version, reason, signature, uploaded
+            SELECT {LOG_COLUMNS}
             FROM audit_logs
-            WHERE tenant_id = ?1 AND uploaded = 0
-            ORDER BY timestamp ASC
+            WHERE tenant_id = ?1 AND uploaded = 0 AND dead_letter = 0
+            ORDER BY timestamp ASC, log_id ASC
             LIMIT ?2
-            "#,
-        )?;
+            "#
+        ))?;
 
-        let rows = stmt.query_map(params![tenant_id, limit as i64], |row| {
-            let subject: String = row.get(5)?;
-            let resource: String = row.get(7)?;
-            let environment: String = row.get(8)?;
-
-            Ok(AuditLogEntry {
-                log_id: row.get(0)?,
-                tenant_id: row.get(1)?,
-                timestamp: row.get(2)?,
-                decision: row.get(3)?,
-                protocol: row.get(4)?,
-                subject: serde_json::from_str(&subject)?,
-                action: row.get(6)?,
-                resource: serde_json::from_str(&resource)?,
-                environment: serde_json::from_str(&environment)?,
-                policy_version: row
-                    .get::<_, Option<i64>>(9)?
-                    .and_then(|value| {
-                        if value < 0 {
-                            None
-                        } else {
-                            u32::try_from(value).ok()
-                        }
-                    }),
-                reason: row.get(10)?,
-                signature: row.get(11)?,
-                uploaded: row.get::<_, i64>(12)? != 0,
-            })
-        })?;
+        let rows = stmt.query_map(params![tenant_id, limit as i64], stored_log)?;
 
         let mut logs = Vec::new();
         for row in rows {
-            logs.push(row?);
+            logs.push(self.open_log(tenant_id, row?)?);
         }
         Ok(logs)
     }
@@ -294,4 +280,174 @@ impl AuditDatabase {
         tx.commit()?;
         Ok(())
     }
+
+    /// Records a failed upload of `log_ids`. Entries that have now failed
+    /// `max_attempts` times are moved to the dead-letter set and skipped by
+    /// [`get_unuploaded_logs`](Self::get_unuploaded_logs). Returns how many
+    /// entries were dead-lettered by this call.
+    pub fn record_upload_failure(
+        &self,
+        tenant_id: &str,
+        log_ids: &[String],
+        error: &str,
+        max_attempts: u32,
+    ) -> Result<usize, StorageError> {
+        if log_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let conn = self.get_or_create_connection(tenant_id)?;
+        let mut conn = conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        let now = Utc::now().to_rfc3339();
+        let tx = conn.transaction()?;
+        let mut dead_lettered = 0usize;
+
+        {
+            let mut bump = tx.prepare(
+                r#"
+                UPDATE audit_logs
+                SET upload_attempts = upload_attempts + 1, last_upload_error = ?2
+                WHERE log_id = ?1 AND uploaded = 0 AND dead_letter = 0
+                "#,
+            )?;
+            let mut dead_letter = tx.prepare(
+                r#"
+                UPDATE audit_logs
+                SET dead_letter = 1, dead_lettered_at = ?2
+                WHERE log_id = ?1 AND dead_letter = 0 AND upload_attempts >= ?3
+                "#,
+            )?;
+
+            for log_id in log_ids {
+                bump.execute(params![log_id, error])?;
+                dead_lettered += dead_letter.execute(params![log_id, now, max_attempts])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(dead_lettered)
+    }
+
+    pub fn list_dead_letter_logs(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+    ) -> Result<Vec<DeadLetterLog>, StorageError> {
+        let conn = self.get_or_create_connection(tenant_id)?;
+        let conn = conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT log_id, tenant_id, timestamp, upload_attempts, last_upload_error, dead_lettered_at
+            FROM audit_logs
+            WHERE tenant_id = ?1 AND dead_letter = 1
+            ORDER BY timestamp ASC
+            LIMIT ?2
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![tenant_id, limit as i64], |row| {
+            Ok(DeadLetterLog {
+                log_id: row.get(0)?,
+                tenant_id: row.get(1)?,
+                timestamp: row.get(2)?,
+                upload_attempts: row.get(3)?,
+                last_upload_error: row.get(4)?,
+                dead_lettered_at: row.get(5)?,
+            })
+        })?;
+
+        let mut logs = Vec::new();
+        for row in rows {
+            logs.push(row?);
+        }
+        Ok(logs)
+    }
+
+    /// Returns dead-lettered entries to the upload queue with a fresh attempt
+    /// budget. Returns the number of entries requeued.
+    pub fn requeue_dead_letter_logs(&self, tenant_id: &str) -> Result<usize, StorageError> {
+        let conn = self.get_or_create_connection(tenant_id)?;
+        let conn = conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+
+        let requeued = conn.execute(
+            r#"
+            UPDATE audit_logs
+            SET dead_letter = 0, dead_lettered_at = NULL, upload_attempts = 0, last_upload_error = NULL
+            WHERE tenant_id = ?1 AND dead_letter = 1
+            "#,
+            params![tenant_id],
+        )?;
+
+        if requeued > 0 {
+            info!(tenant_id, requeued, "requeued dead-lettered audit logs");
+        }
+        Ok(requeued)
+    }
+
+    fn seal_field(&self, tenant_id: &str, value: &Value) -> Result<String, StorageError> {
+        match &self.cipher {
+            Some(cipher) => cipher.seal(tenant_id, value),
+            None => Ok(serde_json::to_string(value)?),
+        }
+    }
+
+    fn open_field(&self, tenant_id: &str, stored: &str) -> Result<Value, StorageError> {
+        match &self.cipher {
+            Some(cipher) => cipher.open(tenant_id, stored),
+            None => open_plain(stored),
+        }
+    }
+
+    fn open_log(&self, tenant_id: &str, stored: StoredLog) -> Result<AuditLogEntry, StorageError> {
+        let mut entry = stored.entry;
+        entry.subject = self.open_field(tenant_id, &stored.subject)?;
+        entry.resource = self.open_field(tenant_id, &stored.resource)?;
+        entry.environment = self.open_field(tenant_id, &stored.environment)?;
+        Ok(entry)
+    }
+}
+
+/// A row of `audit_logs` whose JSON columns have not been decoded yet, as
+/// they may be encrypted.
+struct StoredLog {
+    entry: AuditLogEntry,
+    subject: String,
+    resource: String,
+    environment: String,
+}
+
+/// Maps a row selected with [`LOG_COLUMNS`].
+fn stored_log(row: &Row<'_>) -> rusqlite::Result<StoredLog> {
+    Ok(StoredLog {
+        entry: AuditLogEntry {
+            log_id: row.get(0)?,
+            tenant_id: row.get(1)?,
+            timestamp: row.get(2)?,
+            decision: row.get(3)?,
+            protocol: row.get(4)?,
+            subject: Value::Null,
+            action: row.get(6)?,
+            resource: Value::Null,
+            environment: Value::Null,
+            policy_version: row.get::<_, Option<i64>>(9)?.and_then(|value| {
+                if value < 0 {
+                    None
+                } else {
+                    u32::try_from(value).ok()
+                }
+            }),
+            reason: row.get(10)?,
+            signature: row.get(11)?,
+            uploaded: row.get::<_, i64>(12)? != 0,
+        },
+        subject: row.get(5)?,
+        resource: row.get(7)?,
+        environment: row.get(8)?,
+    })
 }
@@ -0,0 +1,155 @@
+use std::collections::BTreeSet;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use hkdf::Hkdf;
+use serde_json::Value;
+use sha2::Sha256;
+
+use super::error::StorageError;
+
+/// Prefix marking a column value as ciphertext.
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+/// Shortest master key accepted, in bytes.
+pub const MIN_MASTER_KEY_BYTES: usize = 32;
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypts the `subject`, `resource` and `environment` columns of audit logs
+/// with AES-256-GCM.
+///
+/// Each tenant gets its own key, derived from the master key with HKDF-SHA256
+/// and the tenant id as context, so one tenant's key never decrypts another
+/// tenant's rows. Stored values are `enc:v1:<base64 nonce || ciphertext>`;
+/// values without the prefix are read as plain JSON, which keeps rows written
+/// before encryption was enabled readable.
+pub struct FieldCipher {
+    master_key: Vec<u8>,
+    /// Tenants whose new rows are encrypted; empty means every tenant.
+    tenants: BTreeSet<String>,
+}
+
+impl FieldCipher {
+    pub fn new(master_key: &str) -> Result<Self, StorageError> {
+        if master_key.len() < MIN_MASTER_KEY_BYTES {
+            return Err(StorageError::EncryptionError(format!(
+                "master key must be at least {MIN_MASTER_KEY_BYTES} bytes"
+            )));
+        }
+        Ok(Self {
+            master_key: master_key.as_bytes().to_vec(),
+            tenants: BTreeSet::new(),
+        })
+    }
+
+    /// Restricts encryption of new rows to `tenants`.
+    pub fn with_tenants(mut self, tenants: impl IntoIterator<Item = String>) -> Self {
+        self.tenants = tenants.into_iter().collect();
+        self
+    }
+
+    pub fn applies_to(&self, tenant_id: &str) -> bool {
+        self.tenants.is_empty() || self.tenants.contains(tenant_id)
+    }
+
+    /// Serializes `value`, encrypting it when the tenant is covered.
+    pub fn seal(&self, tenant_id: &str, value: &Value) -> Result<String, StorageError> {
+        let plaintext = serde_json::to_string(value)?;
+        if !self.applies_to(tenant_id) {
+            return Ok(plaintext);
+        }
+
+        let cipher = self.tenant_cipher(tenant_id)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| StorageError::EncryptionError("encryption failed".into()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{ENCRYPTED_PREFIX}{}", BASE64.encode(sealed)))
+    }
+
+    /// Reads a column written by [`seal`](Self::seal) or stored as plain JSON.
+    pub fn open(&self, tenant_id: &str, stored: &str) -> Result<Value, StorageError> {
+        let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(serde_json::from_str(stored)?);
+        };
+
+        let sealed = BASE64
+            .decode(encoded)
+            .map_err(|err| StorageError::EncryptionError(format!("invalid ciphertext: {err}")))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(StorageError::EncryptionError(
+                "ciphertext is truncated".into(),
+            ));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into().expect("split at the nonce length");
+
+        let plaintext = self
+            .tenant_cipher(tenant_id)?
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| StorageError::EncryptionError("decryption failed".into()))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn tenant_cipher(&self, tenant_id: &str) -> Result<Aes256Gcm, StorageError> {
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(None, &self.master_key)
+            .expand(format!("audit-log:{tenant_id}").as_bytes(), &mut key)
+            .map_err(|_| StorageError::EncryptionError("key derivation failed".into()))?;
+        Ok(Aes256Gcm::new(&Key::<Aes256Gcm>::from(key)))
+    }
+}
+
+/// Reads a column that may hold ciphertext when no cipher is configured.
+pub fn open_plain(stored: &str) -> Result<Value, StorageError> {
+    if stored.starts_with(ENCRYPTED_PREFIX) {
+        return Err(StorageError::EncryptionError(
+            "row is encrypted but no encryption key is configured".into(),
+        ));
+    }
+    Ok(serde_json::from_str(stored)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const MASTER_KEY: &str = "audit-encryption-master-key-32-bytes!";
+
+    #[test]
+    fn sealed_values_round_trip_per_tenant() {
+        let cipher = FieldCipher::new(MASTER_KEY).unwrap();
+        let value = json!({ "user_id": "alice", "email": "alice@example.com" });
+
+        let sealed = cipher.seal("tenant-a", &value).unwrap();
+        assert!(sealed.starts_with(ENCRYPTED_PREFIX));
+        assert!(!sealed.contains("alice"));
+        assert_eq!(cipher.open("tenant-a", &sealed).unwrap(), value);
+        assert!(cipher.open("tenant-b", &sealed).is_err());
+    }
+
+    #[test]
+    fn uncovered_tenants_and_legacy_rows_stay_plain() {
+        let cipher = FieldCipher::new(MASTER_KEY)
+            .unwrap()
+            .with_tenants(["tenant-a".to_string()]);
+        let value = json!({ "user_id": "bob" });
+
+        let stored = cipher.seal("tenant-b", &value).unwrap();
+        assert_eq!(stored, value.to_string());
+        assert_eq!(cipher.open("tenant-b", &stored).unwrap(), value);
+        assert!(open_plain(&cipher.seal("tenant-a", &value).unwrap()).is_err());
+    }
+
+    #[test]
+    fn short_master_keys_are_rejected() {
+        assert!(FieldCipher::new("too-short").is_err());
+    }
+}
@@ -10,8 +10,18 @@ pub enum StorageError {
     DatabaseError(#[from] rusqlite::Error),
     #[error("tenant {0} not found")]
     TenantNotFound(String),
+    #[error("tenant {0} already exists")]
+    TenantAlreadyExists(String),
     #[error("invalid log entry: {0}")]
     InvalidLogEntry(String),
+    #[error("policy bundle {0} not found")]
+    BundleNotFound(String),
+    #[error("invalid rollout: {0}")]
+    InvalidRollout(String),
+    #[error("invalid bundle transition: {0}")]
+    InvalidTransition(String),
+    #[error("encryption error: {0}")]
+    EncryptionError(String),
     #[error("signature verification failed")]
     SignatureVerificationFailed,
     #[error("io error: {0}")]
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::Utc;
+
+use crate::api::types::AuditLogEntry;
+
+use super::database::{DeadLetterLog, LogFilter};
+use super::error::StorageError;
+use super::store::AuditStore;
+
+/// Audit log entries held in memory, keyed by tenant. Nothing is persisted.
+#[derive(Default)]
+pub struct MemoryAuditStore {
+    logs: Mutex<HashMap<String, Vec<MemoryLog>>>,
+}
+
+/// An entry with the upload bookkeeping SQLite keeps in extra columns.
+struct MemoryLog {
+    entry: AuditLogEntry,
+    upload_attempts: u32,
+    last_upload_error: Option<String>,
+    dead_lettered_at: Option<String>,
+}
+
+impl MemoryAuditStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_logs<T>(
+        &self,
+        f: impl FnOnce(&mut HashMap<String, Vec<MemoryLog>>) -> Result<T, StorageError>,
+    ) -> Result<T, StorageError> {
+        let mut logs = self
+            .logs
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("store poisoned".into()))?;
+        f(&mut logs)
+    }
+}
+
+impl AuditStore for MemoryAuditStore {
+    fn check_available(&self) -> Result<(), StorageError> {
+        self.with_logs(|_| Ok(()))
+    }
+
+    fn write_audit_log(&self, tenant_id: &str, log: &AuditLogEntry) -> Result<(), StorageError> {
+        self.with_logs(|logs| {
+            let tenant_logs = logs.entry(tenant_id.to_string()).or_default();
+            if tenant_logs.iter().any(|stored| stored.entry.log_id == log.log_id) {
+                return Err(StorageError::InvalidLogEntry(format!(
+                    "duplicate log_id {}",
+                    log.log_id
+                )));
+            }
+            tenant_logs.push(MemoryLog {
+                entry: log.clone(),
+                upload_attempts: 0,
+                last_upload_error: None,
+                dead_lettered_at: None,
+            });
+            Ok(())
+        })
+    }
+
+    fn query_logs(
+        &self,
+        tenant_id: &str,
+        filter: &LogFilter,
+    ) -> Result<Vec<AuditLogEntry>, StorageError> {
+        self.with_logs(|logs| {
+            let mut matching: Vec<AuditLogEntry> = logs
+                .get(tenant_id)
+                .into_iter()
+                .flatten()
+                .map(|stored| &stored.entry)
+                .filter(|log| {
+                    filter
+                        .start_time
+                        .as_ref()
+                        .is_none_or(|start| &log.timestamp >= start)
+                        && filter
+                            .end_time
+                            .as_ref()
+                            .is_none_or(|end| &log.timestamp <= end)
+                        && filter
+                            .decision
+                            .as_ref()
+                            .is_none_or(|decision| &log.decision == decision)
+                        && filter
+                            .protocol
+                            .as_ref()
+                            .is_none_or(|protocol| &log.protocol == protocol)
+                })
+                .cloned()
+                .collect();
+            matching.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            if let Some(limit) = filter.limit {
+                matching.truncate(limit);
+            }
+            Ok(matching)
+        })
+    }
+
+    fn get_unuploaded_logs(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+    ) -> Result<Vec<AuditLogEntry>, StorageError> {
+        self.with_logs(|logs| {
+            let mut pending: Vec<AuditLogEntry> = logs
+                .get(tenant_id)
+                .into_iter()
+                .flatten()
+                .filter(|stored| !stored.entry.uploaded && stored.dead_lettered_at.is_none())
+                .map(|stored| stored.entry.clone())
+                .collect();
+            pending.sort_by(|a, b| {
+                a.timestamp
+                    .cmp(&b.timestamp)
+                    .then_with(|| a.log_id.cmp(&b.log_id))
+            });
+            pending.truncate(limit);
+            Ok(pending)
+        })
+    }
+
+    fn mark_logs_uploaded(&self, tenant_id: &str, log_ids: &[String]) -> Result<(), StorageError> {
+        self.with_logs(|logs| {
+            for stored in logs.get_mut(tenant_id).into_iter().flatten() {
+                if log_ids.contains(&stored.entry.log_id) {
+                    stored.entry.uploaded = true;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn record_upload_failure(
+        &self,
+        tenant_id: &str,
+        log_ids: &[String],
+        error: &str,
+        max_attempts: u32,
+    ) -> Result<usize, StorageError> {
+        let now = Utc::now().to_rfc3339();
+        self.with_logs(|logs| {
+            let mut dead_lettered = 0usize;
+            for stored in logs.get_mut(tenant_id).into_iter().flatten() {
+                if stored.entry.uploaded
+                    || stored.dead_lettered_at.is_some()
+                    || !log_ids.contains(&stored.entry.log_id)
+                {
+                    continue;
+                }
+                stored.upload_attempts += 1;
+                stored.last_upload_error = Some(error.to_string());
+                if stored.upload_attempts >= max_attempts {
+                    stored.dead_lettered_at = Some(now.clone());
+                    dead_lettered += 1;
+                }
+            }
+            Ok(dead_lettered)
+        })
+    }
+
+    fn list_dead_letter_logs(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+    ) -> Result<Vec<DeadLetterLog>, StorageError> {
+        self.with_logs(|logs| {
+            let mut dead: Vec<DeadLetterLog> = logs
+                .get(tenant_id)
+                .into_iter()
+                .flatten()
+                .filter(|stored| stored.dead_lettered_at.is_some())
+                .map(|stored| DeadLetterLog {
+                    log_id: stored.entry.log_id.clone(),
+                    tenant_id: stored.entry.tenant_id.clone(),
+                    timestamp: stored.entry.timestamp.clone(),
+                    upload_attempts: stored.upload_attempts,
+                    last_upload_error: stored.last_upload_error.clone(),
+                    dead_lettered_at: stored.dead_lettered_at.clone(),
+                })
+                .collect();
+            dead.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+            dead.truncate(limit);
+            Ok(dead)
+        })
+    }
+
+    fn requeue_dead_letter_logs(&self, tenant_id: &str) -> Result<usize, StorageError> {
+        self.with_logs(|logs| {
+            let mut requeued = 0usize;
+            for stored in logs.get_mut(tenant_id).into_iter().flatten() {
+                if stored.dead_lettered_at.take().is_some() {
+                    stored.upload_attempts = 0;
+                    stored.last_upload_error = None;
+                    requeued += 1;
+                }
+            }
+            Ok(requeued)
+        })
+    }
+}
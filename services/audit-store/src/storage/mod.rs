@@ -1,12 +1,20 @@
 pub mod database;
+pub mod encryption;
 pub mod error;
+#[cfg(test)]
+pub mod memory;
 pub mod policy_bundles;
+pub mod redaction;
 pub mod schema;
+pub mod store;
 pub mod tenant_registry;
 
 pub use database::AuditDatabase;
+pub use encryption::FieldCipher;
 pub use error::StorageError;
 pub use policy_bundles::PolicyBundleStore;
+pub use redaction::redact_audit_entry;
+pub use store::AuditStore;
 pub use tenant_registry::TenantRegistry;
 
 pub const AUDIT_DB_FILENAME: &str = "audit.db";
@@ -3,14 +3,25 @@ use std::sync::Mutex;
 
 use anyhow::Result;
 use chrono::Utc;
-use rusqlite::{params, Connection, OptionalExtension};
+use edge_policy_dsl::rollout_bucket;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use super::error::StorageError;
-use super::schema::POLICY_BUNDLES_TABLE_SCHEMA;
+use super::schema::{
+    migrate_policy_bundles, BUNDLE_ACTIVATIONS_TABLE_SCHEMA, BUNDLE_HISTORY_TABLE_SCHEMA,
+    BUNDLE_ROLLOUTS_TABLE_SCHEMA, POLICY_BUNDLES_TABLE_SCHEMA,
+};
 use super::BUNDLES_DB_FILENAME;
 
+pub const BUNDLE_STATUS_DRAFT: &str = "draft";
+pub const BUNDLE_STATUS_STAGED: &str = "staged";
+pub const BUNDLE_STATUS_ACTIVE: &str = "active";
+
+/// `event` of the history entry written when a bundle is stored.
+pub const BUNDLE_EVENT_CREATED: &str = "created";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyBundleRecord {
     pub bundle_id: String,
@@ -21,6 +32,76 @@ pub struct PolicyBundleRecord {
     pub status: String,
     pub created_at: String,
     pub activated_at: Option<String>,
+    /// Who created this version.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Why this version was created.
+    #[serde(default)]
+    pub change_reason: Option<String>,
+}
+
+/// Who made a bundle change and why, recorded in the bundle history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BundleChange {
+    pub author: Option<String>,
+    pub change_reason: Option<String>,
+}
+
+/// One step in a tenant's policy history, as returned by
+/// `GET /api/bundles/history`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BundleHistoryEntry {
+    pub bundle_id: String,
+    pub tenant_id: String,
+    pub version: i64,
+    /// `created`, or the status the bundle moved to (`staged`, `canary`,
+    /// `active`).
+    pub event: String,
+    pub author: Option<String>,
+    pub change_reason: Option<String>,
+    pub recorded_at: String,
+}
+
+/// Response of an activation made with an idempotency key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedActivation {
+    pub bundle_id: String,
+    pub response: serde_json::Value,
+}
+
+/// Outcome of claiming an idempotency key before activating a bundle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActivationReservation {
+    /// The key was free and now belongs to the caller, who must either
+    /// record the response or release the key.
+    Reserved,
+    /// Another activation holds the key and has not finished yet.
+    InProgress { bundle_id: String },
+    /// An activation with this key already finished.
+    Recorded(RecordedActivation),
+}
+
+/// Canary/stable bundle pair for a tenant that is mid-rollout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleRollout {
+    pub tenant_id: String,
+    pub stable_bundle_id: String,
+    pub canary_bundle_id: String,
+    pub rollout_percent: u8,
+    pub updated_at: String,
+}
+
+impl BundleRollout {
+    /// Picks the bundle a subject should be evaluated against. The choice is
+    /// a pure function of tenant and subject, so a given subject stays on the
+    /// same side of the split for as long as the percentage is unchanged.
+    pub fn select_bundle_id(&self, subject_key: &str) -> &str {
+        if rollout_bucket(&self.tenant_id, subject_key) < self.rollout_percent {
+            &self.canary_bundle_id
+        } else {
+            &self.stable_bundle_id
+        }
+    }
 }
 
 pub struct PolicyBundleStore {
@@ -39,24 +120,41 @@ impl PolicyBundleStore {
         if is_new {
             conn.execute_batch(POLICY_BUNDLES_TABLE_SCHEMA)?;
         }
+        // Created unconditionally so databases predating these tables pick
+        // them up.
+        conn.execute_batch(BUNDLE_ROLLOUTS_TABLE_SCHEMA)?;
+        conn.execute_batch(BUNDLE_ACTIVATIONS_TABLE_SCHEMA)?;
+        conn.execute_batch(BUNDLE_HISTORY_TABLE_SCHEMA)?;
+        migrate_policy_bundles(&conn)?;
 
         Ok(Self {
             conn: Mutex::new(conn),
         })
     }
 
-    pub fn store_bundle(&self, bundle: &PolicyBundleRecord) -> Result<(), StorageError> {
+    /// Runs a trivial query to confirm the database connection is usable.
+    pub fn ping(&self) -> Result<(), StorageError> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
-        let next_version = query_next_version(&conn, &bundle.tenant_id)?;
+        conn.query_row("SELECT 1", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    pub fn store_bundle(&self, bundle: &PolicyBundleRecord) -> Result<(), StorageError> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        let tx = conn.transaction()?;
+        let next_version = query_next_version(&tx, &bundle.tenant_id)?;
         let metadata = match &bundle.metadata {
             Some(value) => Some(serde_json::to_string(value)?),
             None => None,
         };
 
-        conn.execute(
+        tx.execute(
             r#"
             INSERT INTO policy_bundles (
                 bundle_id,
@@ -66,8 +164,10 @@ impl PolicyBundleStore {
                 metadata,
                 status,
                 created_at,
-                activated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                activated_at,
+                author,
+                change_reason
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
             "#,
             params![
                 bundle.bundle_id,
@@ -78,8 +178,20 @@ impl PolicyBundleStore {
                 bundle.status,
                 bundle.created_at,
                 bundle.activated_at,
+                bundle.author,
+                bundle.change_reason,
             ],
         )?;
+        record_history(
+            &tx,
+            &bundle.bundle_id,
+            BUNDLE_EVENT_CREATED,
+            &BundleChange {
+                author: bundle.author.clone(),
+                change_reason: bundle.change_reason.clone(),
+            },
+        )?;
+        tx.commit()?;
 
         info!(
             tenant_id = %bundle.tenant_id,
@@ -101,7 +213,8 @@ impl PolicyBundleStore {
             .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
         let mut stmt = conn.prepare(
             r#"
-            SELECT bundle_id, tenant_id, version, rego_code, metadata, status, created_at, activated_at
+            SELECT bundle_id, tenant_id, version, rego_code, metadata, status, created_at,
+                   activated_at, author, change_reason
             FROM policy_bundles
             WHERE bundle_id = ?1
             "#,
@@ -121,6 +234,8 @@ impl PolicyBundleStore {
                     status: row.get(5)?,
                     created_at: row.get(6)?,
                     activated_at: row.get(7)?,
+                    author: row.get(8)?,
+                    change_reason: row.get(9)?,
                 })
             })
             .optional()?;
@@ -138,7 +253,8 @@ impl PolicyBundleStore {
             .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
         let mut stmt = conn.prepare(
             r#"
-            SELECT bundle_id, tenant_id, version, rego_code, metadata, status, created_at, activated_at
+            SELECT bundle_id, tenant_id, version, rego_code, metadata, status, created_at,
+                   activated_at, author, change_reason
             FROM policy_bundles
             WHERE tenant_id = ?1 AND status = 'active'
             ORDER BY version DESC
@@ -160,6 +276,8 @@ impl PolicyBundleStore {
                     status: row.get(5)?,
                     created_at: row.get(6)?,
                     activated_at: row.get(7)?,
+                    author: row.get(8)?,
+                    change_reason: row.get(9)?,
                 })
             })
             .optional()?;
@@ -177,7 +295,8 @@ impl PolicyBundleStore {
             .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
         let mut stmt = conn.prepare(
             r#"
-            SELECT bundle_id, tenant_id, version, rego_code, metadata, status, created_at, activated_at
+            SELECT bundle_id, tenant_id, version, rego_code, metadata, status, created_at,
+                   activated_at, author, change_reason
             FROM policy_bundles
             WHERE tenant_id = ?1
             ORDER BY version DESC
@@ -197,6 +316,8 @@ impl PolicyBundleStore {
                 status: row.get(5)?,
                 created_at: row.get(6)?,
                 activated_at: row.get(7)?,
+                author: row.get(8)?,
+                change_reason: row.get(9)?,
             })
         })?;
 
@@ -207,12 +328,279 @@ impl PolicyBundleStore {
         Ok(bundles)
     }
 
-    pub fn activate_bundle(&self, bundle_id: &str) -> Result<(), StorageError> {
+    pub fn activate_bundle(
+        &self,
+        bundle_id: &str,
+        change: &BundleChange,
+    ) -> Result<(), StorageError> {
         let bundle = self
             .get_bundle(bundle_id)?
-            .ok_or_else(|| StorageError::InvalidLogEntry("bundle not found".into()))?;
+            .ok_or_else(|| StorageError::BundleNotFound(bundle_id.to_string()))?;
         let tenant_id = bundle.tenant_id.clone();
 
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        let tx = conn.transaction()?;
+        activate_in(&tx, &tenant_id, bundle_id)?;
+        record_history(&tx, bundle_id, BUNDLE_STATUS_ACTIVE, change)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Creations and status changes of the tenant's bundles, oldest first.
+    pub fn bundle_history(&self, tenant_id: &str) -> Result<Vec<BundleHistoryEntry>, StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT bundle_id, tenant_id, version, event, author, change_reason, recorded_at
+            FROM bundle_history
+            WHERE tenant_id = ?1
+            ORDER BY id ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![tenant_id], |row| {
+            Ok(BundleHistoryEntry {
+                bundle_id: row.get(0)?,
+                tenant_id: row.get(1)?,
+                version: row.get(2)?,
+                event: row.get(3)?,
+                author: row.get(4)?,
+                change_reason: row.get(5)?,
+                recorded_at: row.get(6)?,
+            })
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
+    }
+
+    /// Claims `idempotency_key` for an activation of `bundle_id` in the
+    /// tenant, or reports who already holds it.
+    ///
+    /// The claim is an insert under the connection lock, so of two concurrent
+    /// retries exactly one gets [`ActivationReservation::Reserved`].
+    pub fn reserve_activation(
+        &self,
+        tenant_id: &str,
+        idempotency_key: &str,
+        bundle_id: &str,
+    ) -> Result<ActivationReservation, StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        let inserted = conn.execute(
+            r#"
+            INSERT OR IGNORE INTO bundle_activations (tenant_id, idempotency_key, bundle_id, response, created_at)
+            VALUES (?1, ?2, ?3, NULL, ?4)
+            "#,
+            params![tenant_id, idempotency_key, bundle_id, Utc::now().to_rfc3339()],
+        )?;
+        if inserted == 1 {
+            return Ok(ActivationReservation::Reserved);
+        }
+
+        let (bundle_id, response) = conn.query_row(
+            r#"
+            SELECT bundle_id, response FROM bundle_activations
+            WHERE tenant_id = ?1 AND idempotency_key = ?2
+            "#,
+            params![tenant_id, idempotency_key],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+        )?;
+        Ok(match response {
+            Some(response) => ActivationReservation::Recorded(RecordedActivation {
+                bundle_id,
+                response: serde_json::from_str(&response)?,
+            }),
+            None => ActivationReservation::InProgress { bundle_id },
+        })
+    }
+
+    /// Stores the response of the activation holding `idempotency_key`, so a
+    /// retry with the same key is answered without activating again.
+    pub fn record_activation(
+        &self,
+        tenant_id: &str,
+        idempotency_key: &str,
+        response: &serde_json::Value,
+    ) -> Result<(), StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        conn.execute(
+            r#"
+            UPDATE bundle_activations SET response = ?3
+            WHERE tenant_id = ?1 AND idempotency_key = ?2 AND response IS NULL
+            "#,
+            params![tenant_id, idempotency_key, serde_json::to_string(response)?],
+        )?;
+        Ok(())
+    }
+
+    /// Frees a reserved key whose activation failed, so a retry can run it.
+    pub fn release_activation(
+        &self,
+        tenant_id: &str,
+        idempotency_key: &str,
+    ) -> Result<(), StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        conn.execute(
+            r#"
+            DELETE FROM bundle_activations
+            WHERE tenant_id = ?1 AND idempotency_key = ?2 AND response IS NULL
+            "#,
+            params![tenant_id, idempotency_key],
+        )?;
+        Ok(())
+    }
+
+    /// Moves a bundle one step along draft → staged → active and records the
+    /// step under `metadata.promotions`.
+    ///
+    /// Only `draft` bundles can be staged and only `staged` bundles can be
+    /// promoted to `active`; anything else is an `InvalidTransition`.
+    pub fn promote_bundle(
+        &self,
+        bundle_id: &str,
+        target: &str,
+        promoted_by: &str,
+    ) -> Result<PolicyBundleRecord, StorageError> {
+        let bundle = self
+            .get_bundle(bundle_id)?
+            .ok_or_else(|| StorageError::BundleNotFound(bundle_id.to_string()))?;
+
+        let required = match target {
+            BUNDLE_STATUS_STAGED => BUNDLE_STATUS_DRAFT,
+            BUNDLE_STATUS_ACTIVE => BUNDLE_STATUS_STAGED,
+            other => {
+                return Err(StorageError::InvalidTransition(format!(
+                    "bundles cannot be promoted to '{other}'"
+                )))
+            }
+        };
+        if bundle.status != required {
+            return Err(StorageError::InvalidTransition(format!(
+                "bundle {bundle_id} is '{}'; only '{required}' bundles can be promoted to '{target}'",
+                bundle.status
+            )));
+        }
+
+        let mut metadata = match bundle.metadata {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        let promotions = metadata
+            .entry("promotions")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+        if let Some(promotions) = promotions.as_array_mut() {
+            promotions.push(serde_json::json!({
+                "from": bundle.status,
+                "to": target,
+                "promoted_by": promoted_by,
+                "promoted_at": Utc::now().to_rfc3339(),
+            }));
+        }
+        let metadata = serde_json::to_string(&metadata)?;
+
+        {
+            let mut conn = self
+                .conn
+                .lock()
+                .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+            let tx = conn.transaction()?;
+            if target == BUNDLE_STATUS_ACTIVE {
+                activate_in(&tx, &bundle.tenant_id, bundle_id)?;
+            } else {
+                tx.execute(
+                    "UPDATE policy_bundles SET status = ?2 WHERE bundle_id = ?1",
+                    params![bundle_id, target],
+                )?;
+            }
+            tx.execute(
+                "UPDATE policy_bundles SET metadata = ?2 WHERE bundle_id = ?1",
+                params![bundle_id, metadata],
+            )?;
+            record_history(
+                &tx,
+                bundle_id,
+                target,
+                &BundleChange {
+                    author: Some(promoted_by.to_string()),
+                    change_reason: None,
+                },
+            )?;
+            tx.commit()?;
+        }
+
+        info!(
+            tenant_id = %bundle.tenant_id,
+            bundle_id = %bundle_id,
+            target,
+            promoted_by,
+            "promoted policy bundle"
+        );
+
+        self.get_bundle(bundle_id)?
+            .ok_or_else(|| StorageError::BundleNotFound(bundle_id.to_string()))
+    }
+
+    /// Starts a canary rollout of `bundle_id` against the tenant's active bundle.
+    ///
+    /// A `rollout_percent` of 100 promotes the bundle directly, as does rolling
+    /// out to a tenant that has no active bundle yet. Returns the stored
+    /// rollout, or `None` when the bundle was fully activated.
+    pub fn start_rollout(
+        &self,
+        bundle_id: &str,
+        rollout_percent: u8,
+        change: &BundleChange,
+    ) -> Result<Option<BundleRollout>, StorageError> {
+        if rollout_percent == 0 || rollout_percent > 100 {
+            return Err(StorageError::InvalidRollout(
+                "rollout_percent must be between 1 and 100".into(),
+            ));
+        }
+
+        let bundle = self
+            .get_bundle(bundle_id)?
+            .ok_or_else(|| StorageError::BundleNotFound(bundle_id.to_string()))?;
+
+        let stable = match self.get_active_bundle(&bundle.tenant_id)? {
+            Some(stable) if rollout_percent < 100 => stable,
+            _ => {
+                self.activate_bundle(bundle_id, change)?;
+                return Ok(None);
+            }
+        };
+
+        if stable.bundle_id == bundle.bundle_id {
+            return Err(StorageError::InvalidRollout(format!(
+                "bundle {bundle_id} is already the active bundle"
+            )));
+        }
+
+        let rollout = BundleRollout {
+            tenant_id: bundle.tenant_id.clone(),
+            stable_bundle_id: stable.bundle_id,
+            canary_bundle_id: bundle.bundle_id.clone(),
+            rollout_percent,
+            updated_at: Utc::now().to_rfc3339(),
+        };
+
         let mut conn = self
             .conn
             .lock()
@@ -223,22 +611,97 @@ impl PolicyBundleStore {
             r#"
             UPDATE policy_bundles
             SET status = 'inactive', activated_at = NULL
-            WHERE tenant_id = ?1
+            WHERE tenant_id = ?1 AND status = 'canary'
             "#,
-            params![tenant_id],
+            params![rollout.tenant_id],
         )?;
 
         tx.execute(
             r#"
             UPDATE policy_bundles
-            SET status = 'active', activated_at = ?2
+            SET status = 'canary', activated_at = ?2
             WHERE bundle_id = ?1
             "#,
-            params![bundle_id, Utc::now().to_rfc3339()],
+            params![rollout.canary_bundle_id, rollout.updated_at],
+        )?;
+
+        tx.execute(
+            r#"
+            INSERT INTO bundle_rollouts (
+                tenant_id,
+                stable_bundle_id,
+                canary_bundle_id,
+                rollout_percent,
+                updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(tenant_id) DO UPDATE SET
+                stable_bundle_id = excluded.stable_bundle_id,
+                canary_bundle_id = excluded.canary_bundle_id,
+                rollout_percent = excluded.rollout_percent,
+                updated_at = excluded.updated_at
+            "#,
+            params![
+                rollout.tenant_id,
+                rollout.stable_bundle_id,
+                rollout.canary_bundle_id,
+                rollout.rollout_percent as i64,
+                rollout.updated_at,
+            ],
         )?;
+        record_history(&tx, bundle_id, "canary", change)?;
 
         tx.commit()?;
-        Ok(())
+
+        info!(
+            tenant_id = %rollout.tenant_id,
+            canary_bundle_id = %rollout.canary_bundle_id,
+            stable_bundle_id = %rollout.stable_bundle_id,
+            rollout_percent,
+            "started policy bundle rollout"
+        );
+
+        Ok(Some(rollout))
+    }
+
+    pub fn get_rollout(&self, tenant_id: &str) -> Result<Option<BundleRollout>, StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT tenant_id, stable_bundle_id, canary_bundle_id, rollout_percent, updated_at
+            FROM bundle_rollouts
+            WHERE tenant_id = ?1
+            "#,
+        )?;
+
+        let row = stmt
+            .query_row(params![tenant_id], |row| {
+                Ok(BundleRollout {
+                    tenant_id: row.get(0)?,
+                    stable_bundle_id: row.get(1)?,
+                    canary_bundle_id: row.get(2)?,
+                    rollout_percent: row.get::<_, i64>(3)?.clamp(0, 100) as u8,
+                    updated_at: row.get(4)?,
+                })
+            })
+            .optional()?;
+
+        Ok(row)
+    }
+
+    /// Resolves the bundle a subject should be evaluated against, taking any
+    /// in-progress rollout into account.
+    pub fn select_bundle_for_subject(
+        &self,
+        tenant_id: &str,
+        subject_key: &str,
+    ) -> Result<Option<PolicyBundleRecord>, StorageError> {
+        match self.get_rollout(tenant_id)? {
+            Some(rollout) => self.get_bundle(rollout.select_bundle_id(subject_key)),
+            None => self.get_active_bundle(tenant_id),
+        }
     }
 
     pub fn archive_bundle(&self, bundle_id: &str) -> Result<(), StorageError> {
@@ -257,7 +720,7 @@ impl PolicyBundleStore {
         )?;
 
         if updated == 0 {
-            return Err(StorageError::InvalidLogEntry("bundle not found".into()));
+            return Err(StorageError::BundleNotFound(bundle_id.to_string()));
         }
 
         Ok(())
@@ -265,6 +728,67 @@ impl PolicyBundleStore {
 
 }
 
+/// Makes `bundle_id` the tenant's only active bundle and ends any rollout.
+fn activate_in(tx: &Transaction<'_>, tenant_id: &str, bundle_id: &str) -> Result<(), StorageError> {
+    tx.execute(
+        r#"
+        UPDATE policy_bundles
+        SET status = 'inactive', activated_at = NULL
+        WHERE tenant_id = ?1 AND status IN ('active', 'canary')
+        "#,
+        params![tenant_id],
+    )?;
+
+    tx.execute(
+        r#"
+        UPDATE policy_bundles
+        SET status = 'active', activated_at = ?2
+        WHERE bundle_id = ?1
+        "#,
+        params![bundle_id, Utc::now().to_rfc3339()],
+    )?;
+
+    tx.execute(
+        "DELETE FROM bundle_rollouts WHERE tenant_id = ?1",
+        params![tenant_id],
+    )?;
+    Ok(())
+}
+
+/// Appends a history entry for `bundle_id`, taking its tenant and version from
+/// the stored bundle.
+fn record_history(
+    conn: &Connection,
+    bundle_id: &str,
+    event: &str,
+    change: &BundleChange,
+) -> Result<(), StorageError> {
+    conn.execute(
+        r#"
+        INSERT INTO bundle_history (
+            tenant_id,
+            bundle_id,
+            version,
+            event,
+            author,
+            change_reason,
+            recorded_at
+        )
+        SELECT tenant_id, bundle_id, version, ?2, ?3, ?4, ?5
+        FROM policy_bundles
+        WHERE bundle_id = ?1
+        "#,
+        params![
+            bundle_id,
+            event,
+            change.author,
+            change.change_reason,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
 fn query_next_version(conn: &Connection, tenant_id: &str) -> Result<i64, StorageError> {
     let mut stmt = conn.prepare(
         r#"
@@ -274,8 +798,149 @@ fn query_next_version(conn: &Connection, tenant_id: &str) -> Result<i64, Storage
         "#,
     )?;
 
+    // MAX over no rows yields a single NULL row, not an empty result.
     let current_version: Option<i64> = stmt
-        .query_row(params![tenant_id], |row| row.get(0))
-        .optional()?;
+        .query_row(params![tenant_id], |row| row.get::<_, Option<i64>>(0))
+        .optional()?
+        .flatten();
     Ok(current_version.unwrap_or(0) + 1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rollout(percent: u8) -> BundleRollout {
+        BundleRollout {
+            tenant_id: "tenant-a".to_string(),
+            stable_bundle_id: "stable".to_string(),
+            canary_bundle_id: "canary".to_string(),
+            rollout_percent: percent,
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn draft(bundle_id: &str) -> PolicyBundleRecord {
+        PolicyBundleRecord {
+            bundle_id: bundle_id.to_string(),
+            tenant_id: "tenant-a".to_string(),
+            version: 0,
+            rego_code: "package tenants.tenant_a\nallow := true".to_string(),
+            metadata: Some(serde_json::json!({ "author": "dev" })),
+            status: BUNDLE_STATUS_DRAFT.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            activated_at: None,
+            author: None,
+            change_reason: None,
+        }
+    }
+
+    #[test]
+    fn idempotency_keys_are_reserved_once_per_tenant() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = PolicyBundleStore::new(temp.path()).unwrap();
+        let response = serde_json::json!({ "status": "activated", "bundle_id": "bundle-1" });
+
+        assert_eq!(
+            store.reserve_activation("tenant-a", "retry-1", "bundle-1").unwrap(),
+            ActivationReservation::Reserved
+        );
+        assert_eq!(
+            store.reserve_activation("tenant-a", "retry-1", "bundle-1").unwrap(),
+            ActivationReservation::InProgress {
+                bundle_id: "bundle-1".to_string()
+            }
+        );
+        // Keys are per tenant.
+        assert_eq!(
+            store.reserve_activation("tenant-b", "retry-1", "bundle-9").unwrap(),
+            ActivationReservation::Reserved
+        );
+
+        store
+            .record_activation("tenant-a", "retry-1", &response)
+            .unwrap();
+        assert_eq!(
+            store.reserve_activation("tenant-a", "retry-1", "bundle-1").unwrap(),
+            ActivationReservation::Recorded(RecordedActivation {
+                bundle_id: "bundle-1".to_string(),
+                response,
+            })
+        );
+
+        store.release_activation("tenant-b", "retry-1").unwrap();
+        assert_eq!(
+            store.reserve_activation("tenant-b", "retry-1", "bundle-9").unwrap(),
+            ActivationReservation::Reserved
+        );
+    }
+
+    #[test]
+    fn promotion_moves_draft_through_staged_to_active() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = PolicyBundleStore::new(temp.path()).unwrap();
+        store.store_bundle(&draft("bundle-1")).unwrap();
+        store.store_bundle(&draft("bundle-2")).unwrap();
+
+        let err = store
+            .promote_bundle("bundle-1", BUNDLE_STATUS_ACTIVE, "reviewer")
+            .unwrap_err();
+        assert!(matches!(err, StorageError::InvalidTransition(_)));
+
+        let staged = store
+            .promote_bundle("bundle-1", BUNDLE_STATUS_STAGED, "alice")
+            .unwrap();
+        assert_eq!(staged.status, BUNDLE_STATUS_STAGED);
+        assert!(store.get_active_bundle("tenant-a").unwrap().is_none());
+
+        let active = store
+            .promote_bundle("bundle-1", BUNDLE_STATUS_ACTIVE, "bob")
+            .unwrap();
+        assert_eq!(active.status, BUNDLE_STATUS_ACTIVE);
+        assert!(active.activated_at.is_some());
+
+        let metadata = active.metadata.unwrap();
+        assert_eq!(metadata["author"], "dev");
+        let promotions = metadata["promotions"].as_array().unwrap();
+        assert_eq!(promotions.len(), 2);
+        assert_eq!(promotions[0]["to"], BUNDLE_STATUS_STAGED);
+        assert_eq!(promotions[0]["promoted_by"], "alice");
+        assert_eq!(promotions[1]["from"], BUNDLE_STATUS_STAGED);
+        assert_eq!(promotions[1]["promoted_by"], "bob");
+
+        // Activation leaves other drafts alone.
+        let other = store.get_bundle("bundle-2").unwrap().unwrap();
+        assert_eq!(other.status, BUNDLE_STATUS_DRAFT);
+    }
+
+    #[test]
+    fn fixed_subject_lands_on_same_bundle() {
+        let rollout = rollout(50);
+        let first = rollout.select_bundle_id("user-42").to_string();
+
+        for _ in 0..100 {
+            assert_eq!(rollout.select_bundle_id("user-42"), first);
+        }
+    }
+
+    #[test]
+    fn half_rollout_splits_subjects_evenly() {
+        let rollout = rollout(50);
+        let total = 10_000;
+        let canary = (0..total)
+            .filter(|i| rollout.select_bundle_id(&format!("subject-{i}")) == "canary")
+            .count();
+
+        let share = canary as f64 / total as f64;
+        assert!(
+            (0.45..=0.55).contains(&share),
+            "expected roughly half on canary, got {share}"
+        );
+    }
+
+    #[test]
+    fn full_rollout_always_selects_canary() {
+        let rollout = rollout(100);
+        assert!((0..1_000).all(|i| rollout.select_bundle_id(&format!("s-{i}")) == "canary"));
+    }
+}
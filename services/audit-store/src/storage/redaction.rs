@@ -0,0 +1,130 @@
+use serde_json::Value;
+
+use crate::api::types::AuditLogEntry;
+
+/// Tenant config key listing attribute paths to redact before storage.
+pub const AUDIT_REDACTION_PATHS_KEY: &str = "audit_redaction_paths";
+
+/// Replacement written in place of redacted values.
+pub const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
+
+/// Reads `audit_redaction_paths` from a tenant's config, ignoring entries that
+/// are not strings.
+pub fn audit_redaction_paths(config: Option<&Value>) -> Vec<String> {
+    config
+        .and_then(|config| config.get(AUDIT_REDACTION_PATHS_KEY))
+        .and_then(Value::as_array)
+        .map(|paths| {
+            paths
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Replaces the values at `paths` in the entry's `subject` and `resource` with
+/// [`REDACTED_PLACEHOLDER`].
+///
+/// Paths are dotted and rooted at either `subject` or `resource` (for example
+/// `subject.user_id`); paths with any other root or that do not resolve are
+/// skipped. Must run before the entry is signed so the signature covers the
+/// redacted form. Returns the number of values redacted.
+pub fn redact_audit_entry(entry: &mut AuditLogEntry, paths: &[String]) -> usize {
+    let mut redacted = 0usize;
+
+    for path in paths {
+        let mut segments = path.split('.');
+        let target = match segments.next() {
+            Some("subject") => &mut entry.subject,
+            Some("resource") => &mut entry.resource,
+            _ => continue,
+        };
+
+        let segments: Vec<&str> = segments.collect();
+        if !segments.is_empty() && redact_path(target, &segments) {
+            redacted += 1;
+        }
+    }
+
+    redacted
+}
+
+fn redact_path(value: &mut Value, segments: &[&str]) -> bool {
+    let (leaf, parents) = match segments.split_last() {
+        Some(split) => split,
+        None => return false,
+    };
+
+    let mut current = value;
+    for segment in parents {
+        current = match current.get_mut(*segment) {
+            Some(next) => next,
+            None => return false,
+        };
+    }
+
+    match current.get_mut(*leaf) {
+        Some(slot) => {
+            *slot = Value::String(REDACTED_PLACEHOLDER.to_string());
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::Signer;
+
+    fn entry() -> AuditLogEntry {
+        AuditLogEntry {
+            log_id: "log-1".to_string(),
+            tenant_id: "tenant-a".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            decision: "allow".to_string(),
+            protocol: "http".to_string(),
+            subject: serde_json::json!({"user_id": "alice", "device": {"serial": "SN-1"}}),
+            action: "read".to_string(),
+            resource: serde_json::json!({"type": "sensor_data", "owner_user": "bob"}),
+            environment: serde_json::json!({}),
+            policy_version: None,
+            reason: None,
+            signature: String::new(),
+            uploaded: false,
+        }
+    }
+
+    #[test]
+    fn configured_paths_are_redacted_and_signature_verifies() {
+        let config = serde_json::json!({
+            "audit_redaction_paths": ["subject.user_id", "subject.device.serial", "resource.missing"]
+        });
+        let paths = audit_redaction_paths(Some(&config));
+
+        let mut log = entry();
+        assert_eq!(redact_audit_entry(&mut log, &paths), 2);
+        assert_eq!(log.subject["user_id"], REDACTED_PLACEHOLDER);
+        assert_eq!(log.subject["device"]["serial"], REDACTED_PLACEHOLDER);
+        assert_eq!(log.resource["owner_user"], "bob");
+
+        let signer = Signer::new("redaction-test-secret-key-of-32-bytes!").unwrap();
+        log.signature = signer.sign_audit_log(&log).unwrap();
+        assert!(signer.verify_audit_log(&log).unwrap());
+
+        let mut unredacted = log.clone();
+        unredacted.subject["user_id"] = Value::String("alice".to_string());
+        assert!(!signer.verify_audit_log(&unredacted).unwrap());
+    }
+
+    #[test]
+    fn missing_config_redacts_nothing() {
+        let mut log = entry();
+        let paths = audit_redaction_paths(None);
+
+        assert_eq!(redact_audit_entry(&mut log, &paths), 0);
+        assert_eq!(log.subject["user_id"], "alice");
+    }
+}
@@ -22,10 +22,55 @@ CREATE TABLE IF NOT EXISTS policy_bundles (
     status TEXT NOT NULL,
     created_at TEXT NOT NULL,
     activated_at TEXT,
+    author TEXT,
+    change_reason TEXT,
     UNIQUE(tenant_id, version)
 );
 "#;
 
+/// Columns added to `policy_bundles` after the initial release.
+const POLICY_BUNDLES_ADDED_COLUMNS: &[(&str, &str)] =
+    &[("author", "TEXT"), ("change_reason", "TEXT")];
+
+/// Append-only record of bundle creations and status changes, in the order
+/// they happened.
+pub const BUNDLE_HISTORY_TABLE_SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS bundle_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id TEXT NOT NULL,
+    bundle_id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    author TEXT,
+    change_reason TEXT,
+    recorded_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_bundle_history_tenant ON bundle_history(tenant_id, id);
+"#;
+
+pub const BUNDLE_ROLLOUTS_TABLE_SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS bundle_rollouts (
+    tenant_id TEXT PRIMARY KEY,
+    stable_bundle_id TEXT NOT NULL,
+    canary_bundle_id TEXT NOT NULL,
+    rollout_percent INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
+"#;
+
+/// Activation results keyed by tenant and the caller's `Idempotency-Key`.
+/// `response` stays `NULL` while the activation holding the key is running.
+pub const BUNDLE_ACTIVATIONS_TABLE_SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS bundle_activations (
+    tenant_id TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    bundle_id TEXT NOT NULL,
+    response TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (tenant_id, idempotency_key)
+);
+"#;
+
 pub const AUDIT_LOGS_TABLE_SCHEMA: &str = r#"
 CREATE TABLE IF NOT EXISTS audit_logs (
     log_id TEXT PRIMARY KEY,
@@ -40,15 +85,29 @@ CREATE TABLE IF NOT EXISTS audit_logs (
     policy_version INTEGER,
     reason TEXT,
     signature TEXT NOT NULL,
-    uploaded INTEGER DEFAULT 0
+    uploaded INTEGER DEFAULT 0,
+    upload_attempts INTEGER NOT NULL DEFAULT 0,
+    last_upload_error TEXT,
+    dead_letter INTEGER NOT NULL DEFAULT 0,
+    dead_lettered_at TEXT
 );
 "#;
 
 pub const AUDIT_LOGS_INDEXES: &str = r#"
 CREATE INDEX IF NOT EXISTS idx_audit_tenant_timestamp ON audit_logs(tenant_id, timestamp);
 CREATE INDEX IF NOT EXISTS idx_audit_uploaded ON audit_logs(uploaded);
+CREATE INDEX IF NOT EXISTS idx_audit_dead_letter ON audit_logs(dead_letter);
 "#;
 
+/// Columns added to `audit_logs` after the initial release, with their
+/// definitions, so databases created earlier can be upgraded in place.
+const AUDIT_LOGS_ADDED_COLUMNS: &[(&str, &str)] = &[
+    ("upload_attempts", "INTEGER NOT NULL DEFAULT 0"),
+    ("last_upload_error", "TEXT"),
+    ("dead_letter", "INTEGER NOT NULL DEFAULT 0"),
+    ("dead_lettered_at", "TEXT"),
+];
+
 pub fn init_database(conn: &Connection) -> Result<()> {
     conn.execute_batch(TENANTS_TABLE_SCHEMA)?;
     conn.execute_batch(POLICY_BUNDLES_TABLE_SCHEMA)?;
@@ -56,3 +115,34 @@ pub fn init_database(conn: &Connection) -> Result<()> {
     conn.execute_batch(AUDIT_LOGS_INDEXES)?;
     Ok(())
 }
+
+/// Adds any `audit_logs` columns missing from an existing tenant database.
+pub fn migrate_audit_logs(conn: &Connection) -> rusqlite::Result<()> {
+    add_missing_columns(conn, "audit_logs", AUDIT_LOGS_ADDED_COLUMNS)?;
+    conn.execute_batch(AUDIT_LOGS_INDEXES)
+}
+
+/// Adds any `policy_bundles` columns missing from an existing bundle database.
+pub fn migrate_policy_bundles(conn: &Connection) -> rusqlite::Result<()> {
+    add_missing_columns(conn, "policy_bundles", POLICY_BUNDLES_ADDED_COLUMNS)
+}
+
+fn add_missing_columns(
+    conn: &Connection,
+    table: &str,
+    columns: &[(&str, &str)],
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let existing = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for (column, definition) in columns {
+        if !existing.iter().any(|name| name == column) {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+            ))?;
+        }
+    }
+    Ok(())
+}
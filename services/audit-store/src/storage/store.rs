@@ -0,0 +1,208 @@
+use crate::api::types::AuditLogEntry;
+
+use super::database::{AuditDatabase, DeadLetterLog, LogFilter};
+use super::error::StorageError;
+
+/// Storage for audit log entries, independent of the backing database.
+///
+/// The API handlers and the upload queue only go through this trait.
+/// [`AuditDatabase`] keeps one SQLite file per tenant; tests use the
+/// in-process `MemoryAuditStore`.
+pub trait AuditStore: Send + Sync {
+    /// Confirms the store can still be reached, for the health endpoint.
+    fn check_available(&self) -> Result<(), StorageError>;
+
+    fn write_audit_log(&self, tenant_id: &str, log: &AuditLogEntry) -> Result<(), StorageError>;
+
+    /// Entries matching `filter`, newest first.
+    fn query_logs(
+        &self,
+        tenant_id: &str,
+        filter: &LogFilter,
+    ) -> Result<Vec<AuditLogEntry>, StorageError>;
+
+    /// Up to `limit` entries not yet uploaded, oldest first.
+    fn get_unuploaded_logs(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+    ) -> Result<Vec<AuditLogEntry>, StorageError>;
+
+    fn mark_logs_uploaded(&self, tenant_id: &str, log_ids: &[String]) -> Result<(), StorageError>;
+
+    /// Records a failed upload of `log_ids`, dead-lettering entries that have
+    /// now failed `max_attempts` times. Returns how many were dead-lettered.
+    fn record_upload_failure(
+        &self,
+        tenant_id: &str,
+        log_ids: &[String],
+        error: &str,
+        max_attempts: u32,
+    ) -> Result<usize, StorageError>;
+
+    /// Up to `limit` dead-lettered entries, oldest first.
+    fn list_dead_letter_logs(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+    ) -> Result<Vec<DeadLetterLog>, StorageError>;
+
+    /// Returns dead-lettered entries to the upload queue with a fresh attempt
+    /// budget. Returns how many were requeued.
+    fn requeue_dead_letter_logs(&self, tenant_id: &str) -> Result<usize, StorageError>;
+}
+
+impl AuditStore for AuditDatabase {
+    fn check_available(&self) -> Result<(), StorageError> {
+        AuditDatabase::check_data_dir(self)
+    }
+
+    fn write_audit_log(&self, tenant_id: &str, log: &AuditLogEntry) -> Result<(), StorageError> {
+        AuditDatabase::write_audit_log(self, tenant_id, log)
+    }
+
+    fn query_logs(
+        &self,
+        tenant_id: &str,
+        filter: &LogFilter,
+    ) -> Result<Vec<AuditLogEntry>, StorageError> {
+        AuditDatabase::query_logs(self, tenant_id, filter)
+    }
+
+    fn get_unuploaded_logs(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+    ) -> Result<Vec<AuditLogEntry>, StorageError> {
+        AuditDatabase::get_unuploaded_logs(self, tenant_id, limit)
+    }
+
+    fn mark_logs_uploaded(&self, tenant_id: &str, log_ids: &[String]) -> Result<(), StorageError> {
+        AuditDatabase::mark_logs_uploaded(self, tenant_id, log_ids)
+    }
+
+    fn record_upload_failure(
+        &self,
+        tenant_id: &str,
+        log_ids: &[String],
+        error: &str,
+        max_attempts: u32,
+    ) -> Result<usize, StorageError> {
+        AuditDatabase::record_upload_failure(self, tenant_id, log_ids, error, max_attempts)
+    }
+
+    fn list_dead_letter_logs(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+    ) -> Result<Vec<DeadLetterLog>, StorageError> {
+        AuditDatabase::list_dead_letter_logs(self, tenant_id, limit)
+    }
+
+    fn requeue_dead_letter_logs(&self, tenant_id: &str) -> Result<usize, StorageError> {
+        AuditDatabase::requeue_dead_letter_logs(self, tenant_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::super::memory::MemoryAuditStore;
+    use super::*;
+
+    fn log(log_id: &str, timestamp: &str, decision: &str) -> AuditLogEntry {
+        AuditLogEntry {
+            log_id: log_id.to_string(),
+            tenant_id: "tenant-a".to_string(),
+            timestamp: timestamp.to_string(),
+            decision: decision.to_string(),
+            protocol: "http".to_string(),
+            subject: json!({ "user_id": "alice" }),
+            action: "read".to_string(),
+            resource: json!({ "type": "sensor_data" }),
+            environment: json!({}),
+            policy_version: Some(1),
+            reason: None,
+            signature: "sig".to_string(),
+            uploaded: false,
+        }
+    }
+
+    fn exercise(store: &dyn AuditStore) {
+        store
+            .write_audit_log("tenant-a", &log("log-1", "2024-01-01T00:00:00Z", "allow"))
+            .unwrap();
+        store
+            .write_audit_log("tenant-a", &log("log-2", "2024-01-02T00:00:00Z", "deny"))
+            .unwrap();
+        store
+            .write_audit_log("tenant-a", &log("log-3", "2024-01-03T00:00:00Z", "allow"))
+            .unwrap();
+
+        let ids = |logs: Vec<AuditLogEntry>| -> Vec<String> {
+            logs.into_iter().map(|log| log.log_id).collect()
+        };
+
+        let all = store.query_logs("tenant-a", &LogFilter::default()).unwrap();
+        assert_eq!(ids(all), vec!["log-3", "log-2", "log-1"]);
+
+        let filter = LogFilter {
+            decision: Some("allow".to_string()),
+            limit: Some(1),
+            ..LogFilter::default()
+        };
+        assert_eq!(
+            ids(store.query_logs("tenant-a", &filter).unwrap()),
+            vec!["log-3"]
+        );
+        assert!(store
+            .query_logs("tenant-b", &LogFilter::default())
+            .unwrap()
+            .is_empty());
+
+        let pending = store.get_unuploaded_logs("tenant-a", 2).unwrap();
+        assert_eq!(ids(pending), vec!["log-1", "log-2"]);
+
+        store
+            .mark_logs_uploaded("tenant-a", &["log-1".to_string(), "log-2".to_string()])
+            .unwrap();
+        let pending = store.get_unuploaded_logs("tenant-a", 10).unwrap();
+        assert_eq!(ids(pending), vec!["log-3"]);
+
+        let failed = ["log-3".to_string()];
+        assert_eq!(store.record_upload_failure("tenant-a", &failed, "timeout", 2).unwrap(), 0);
+        assert_eq!(store.record_upload_failure("tenant-a", &failed, "refused", 2).unwrap(), 1);
+        assert!(store.get_unuploaded_logs("tenant-a", 10).unwrap().is_empty());
+        let dead = store.list_dead_letter_logs("tenant-a", 10).unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].log_id, "log-3");
+        assert_eq!(dead[0].upload_attempts, 2);
+        assert_eq!(dead[0].last_upload_error.as_deref(), Some("refused"));
+
+        assert_eq!(store.requeue_dead_letter_logs("tenant-a").unwrap(), 1);
+        assert!(store.list_dead_letter_logs("tenant-a", 10).unwrap().is_empty());
+        let pending = store.get_unuploaded_logs("tenant-a", 10).unwrap();
+        assert_eq!(ids(pending), vec!["log-3"]);
+    }
+
+    #[test]
+    fn sqlite_store_implements_audit_store() {
+        let temp = tempfile::tempdir().unwrap();
+        let database = AuditDatabase::new(temp.path().to_path_buf()).unwrap();
+        exercise(&database);
+    }
+
+    #[test]
+    fn memory_store_implements_audit_store() {
+        exercise(&MemoryAuditStore::new());
+    }
+
+    #[test]
+    fn memory_store_rejects_duplicate_log_ids() {
+        let store = MemoryAuditStore::new();
+        let entry = log("log-1", "2024-01-01T00:00:00Z", "allow");
+        store.write_audit_log("tenant-a", &entry).unwrap();
+        assert!(store.write_audit_log("tenant-a", &entry).is_err());
+    }
+}
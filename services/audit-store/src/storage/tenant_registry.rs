@@ -7,6 +7,7 @@ use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 
 use super::error::StorageError;
+use super::redaction::audit_redaction_paths;
 use super::schema::TENANTS_TABLE_SCHEMA;
 use super::TENANT_DB_FILENAME;
 
@@ -20,6 +21,13 @@ pub struct TenantRecord {
     pub config: Option<serde_json::Value>,
 }
 
+impl TenantRecord {
+    /// Attribute paths this tenant requires redacted from stored audit logs.
+    pub fn audit_redaction_paths(&self) -> Vec<String> {
+        audit_redaction_paths(self.config.as_ref())
+    }
+}
+
 pub struct TenantRegistry {
     conn: Mutex<Connection>,
 }
@@ -42,6 +50,18 @@ impl TenantRegistry {
         })
     }
 
+    /// Runs a trivial query to confirm the database connection is usable.
+    pub fn ping(&self) -> Result<(), StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidLogEntry("connection poisoned".into()))?;
+        conn.query_row("SELECT 1", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    /// Registers a new tenant. An existing `tenant_id` is left untouched and
+    /// reported as [`StorageError::TenantAlreadyExists`].
     pub fn create_tenant(&self, tenant: &TenantRecord) -> Result<(), StorageError> {
         let conn = self
             .conn
@@ -68,7 +88,15 @@ impl TenantRegistry {
                 tenant.updated_at,
                 config
             ],
-        )?;
+        )
+        .map_err(|err| match err {
+            rusqlite::Error::SqliteFailure(failure, _)
+                if failure.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY =>
+            {
+                StorageError::TenantAlreadyExists(tenant.tenant_id.clone())
+            }
+            other => other.into(),
+        })?;
         Ok(())
     }
 
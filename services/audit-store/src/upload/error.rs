@@ -1,6 +1,7 @@
 use reqwest::Error as ReqwestError;
 use thiserror::Error;
 
+use crate::signing::SigningError;
 use crate::storage::error::StorageError;
 
 #[derive(Debug, Error)]
@@ -11,10 +12,14 @@ pub enum UploadError {
     AuthenticationError,
     #[error("invalid endpoint: {0}")]
     InvalidEndpoint(String),
+    #[error("compression error: {0}")]
+    CompressionError(#[from] std::io::Error),
     #[error("serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
     #[error("storage error: {0}")]
     DatabaseError(#[from] StorageError),
+    #[error("signing error: {0}")]
+    SigningError(#[from] SigningError),
 }
 
 impl From<ReqwestError> for UploadError {
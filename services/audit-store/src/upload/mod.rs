@@ -1,7 +1,9 @@
 pub mod error;
+pub mod payload;
 pub mod queue;
 
 pub use error::UploadError;
+pub use payload::UploadCompression;
 pub use queue::UploadQueue;
 
 pub const DEFAULT_BATCH_SIZE: usize = 1_000;
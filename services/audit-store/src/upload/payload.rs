@@ -0,0 +1,146 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::api::types::AuditLogEntry;
+
+use super::error::UploadError;
+
+/// Encoding applied to audit log batches before they are uploaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadCompression {
+    /// Batches are sent as a plain JSON array.
+    #[default]
+    None,
+    /// Batches are sent as gzip-compressed NDJSON, one entry per line.
+    Gzip,
+}
+
+impl fmt::Display for UploadCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadCompression::None => f.write_str("none"),
+            UploadCompression::Gzip => f.write_str("gzip"),
+        }
+    }
+}
+
+impl FromStr for UploadCompression {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(UploadCompression::None),
+            "gzip" => Ok(UploadCompression::Gzip),
+            other => Err(format!("unsupported upload compression {other}")),
+        }
+    }
+}
+
+/// Request body ready to be posted, with the headers describing it.
+#[derive(Debug, Clone)]
+pub struct UploadPayload {
+    pub body: Vec<u8>,
+    pub content_type: &'static str,
+    pub content_encoding: Option<&'static str>,
+}
+
+impl UploadPayload {
+    pub fn json<T: Serialize + ?Sized>(value: &T) -> Result<Self, UploadError> {
+        Ok(Self {
+            body: serde_json::to_vec(value)?,
+            content_type: "application/json",
+            content_encoding: None,
+        })
+    }
+
+    /// Encodes a batch of logs, preserving their order.
+    pub fn log_batch(
+        logs: &[AuditLogEntry],
+        compression: UploadCompression,
+    ) -> Result<Self, UploadError> {
+        match compression {
+            UploadCompression::None => Self::json(logs),
+            UploadCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                for log in logs {
+                    serde_json::to_writer(&mut encoder, log)?;
+                    encoder.write_all(b"\n")?;
+                }
+                let body = encoder.finish()?;
+
+                Ok(Self {
+                    body,
+                    content_type: "application/x-ndjson",
+                    content_encoding: Some("gzip"),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn log(log_id: &str, timestamp: &str) -> AuditLogEntry {
+        AuditLogEntry {
+            log_id: log_id.to_string(),
+            tenant_id: "tenant-a".to_string(),
+            timestamp: timestamp.to_string(),
+            decision: "allow".to_string(),
+            protocol: "http".to_string(),
+            subject: serde_json::json!({"user_id": "u1"}),
+            action: "read".to_string(),
+            resource: serde_json::json!({"type": "sensor_data"}),
+            environment: serde_json::json!({}),
+            policy_version: Some(1),
+            reason: None,
+            signature: "signature".to_string(),
+            uploaded: false,
+        }
+    }
+
+    #[test]
+    fn gzip_batches_round_trip_in_order() {
+        let logs = vec![
+            log("log-a", "2025-01-01T00:00:00Z"),
+            log("log-b", "2025-01-01T00:00:00Z"),
+            log("log-c", "2025-01-01T00:00:05Z"),
+        ];
+
+        let payload = UploadPayload::log_batch(&logs, UploadCompression::Gzip).unwrap();
+        assert_eq!(payload.content_encoding, Some("gzip"));
+        assert_eq!(payload.content_type, "application/x-ndjson");
+
+        let mut ndjson = String::new();
+        GzDecoder::new(payload.body.as_slice())
+            .read_to_string(&mut ndjson)
+            .unwrap();
+        let decoded: Vec<AuditLogEntry> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let ids: Vec<&str> = decoded.iter().map(|log| log.log_id.as_str()).collect();
+        assert_eq!(ids, ["log-a", "log-b", "log-c"]);
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&logs).unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_compression_setting() {
+        assert_eq!("gzip".parse::<UploadCompression>(), Ok(UploadCompression::Gzip));
+        assert_eq!(" None ".parse::<UploadCompression>(), Ok(UploadCompression::None));
+        assert!("brotli".parse::<UploadCompression>().is_err());
+    }
+}
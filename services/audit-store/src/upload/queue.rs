@@ -1,30 +1,38 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use reqwest::header::{CONTENT_ENCODING, CONTENT_TYPE};
 use reqwest::Client;
 use tokio::task::JoinHandle;
 use tokio::time::{interval, sleep, MissedTickBehavior};
 use tracing::{debug, info, warn};
 
+use crate::api::types::AuditLogEntry;
 use crate::config::AuditStoreConfig;
-use crate::storage::{AuditDatabase, TenantRegistry};
+use crate::signing::{BatchManifest, Signer};
+use crate::storage::{AuditStore, TenantRegistry};
 
 use super::error::UploadError;
+use super::payload::{UploadCompression, UploadPayload};
 
 #[derive(Clone)]
 pub struct UploadQueue {
-    database: Arc<AuditDatabase>,
+    database: Arc<dyn AuditStore>,
     tenant_registry: Arc<TenantRegistry>,
+    signer: Arc<Signer>,
     http_client: Client,
     upload_endpoint: Option<String>,
     batch_size: usize,
+    max_attempts: u32,
+    compression: UploadCompression,
     upload_interval: Duration,
 }
 
 impl UploadQueue {
     pub fn new(
-        database: Arc<AuditDatabase>,
+        database: Arc<dyn AuditStore>,
         tenant_registry: Arc<TenantRegistry>,
+        signer: Arc<Signer>,
         config: &AuditStoreConfig,
     ) -> Self {
         let client = Client::builder()
@@ -35,9 +43,12 @@ impl UploadQueue {
         Self {
             database,
             tenant_registry,
+            signer,
             http_client: client,
             upload_endpoint: config.upload_endpoint.clone(),
             batch_size: config.upload_batch_size,
+            max_attempts: config.upload_max_attempts,
+            compression: config.upload_compression,
             upload_interval: Duration::from_secs(config.upload_interval_secs),
         }
     }
@@ -91,6 +102,8 @@ impl UploadQueue {
                 continue;
             }
 
+            let log_ids: Vec<String> = logs.iter().map(|log| log.log_id.clone()).collect();
+
             if let Err(err) = self
                 .upload_batch(&endpoint, &tenant.tenant_id, &logs)
                 .await
@@ -100,10 +113,26 @@ impl UploadQueue {
                     error = %err,
                     "failed to upload audit batch"
                 );
+                let dead_lettered = self
+                    .database
+                    .record_upload_failure(
+                        &tenant.tenant_id,
+                        &log_ids,
+                        &err.to_string(),
+                        self.max_attempts,
+                    )
+                    .map_err(UploadError::from)?;
+                if dead_lettered > 0 {
+                    warn!(
+                        tenant_id = %tenant.tenant_id,
+                        count = dead_lettered,
+                        max_attempts = self.max_attempts,
+                        "moved audit logs to dead-letter after repeated upload failures"
+                    );
+                }
                 continue;
             }
 
-            let log_ids: Vec<String> = logs.iter().map(|log| log.log_id.clone()).collect();
             self.database
                 .mark_logs_uploaded(&tenant.tenant_id, &log_ids)
                 .map_err(UploadError::from)?;
@@ -117,18 +146,50 @@ impl UploadQueue {
         &self,
         endpoint: &str,
         tenant_id: &str,
-        logs: &[crate::api::types::AuditLogEntry],
+        logs: &[AuditLogEntry],
     ) -> Result<(), UploadError> {
         if logs.is_empty() {
             return Ok(());
         }
 
+        let manifest = BatchManifest::build(&self.signer, tenant_id, logs)?;
+
         let url = format!(
             "{}/tenants/{}/audit-logs",
             endpoint.trim_end_matches('/'),
             tenant_id
         );
 
+        // Logs arrive ordered by (timestamp, log_id); the payload keeps that order.
+        let payload = UploadPayload::log_batch(logs, self.compression)?;
+        self.post_with_retry(&url, tenant_id, &payload).await?;
+        debug!(
+            tenant_id = %tenant_id,
+            count = logs.len(),
+            bytes = payload.body.len(),
+            compression = %self.compression,
+            "uploaded audit logs batch"
+        );
+
+        // The manifest lets the remote side detect entries dropped from the batch.
+        let manifest_payload = UploadPayload::json(&manifest)?;
+        self.post_with_retry(&format!("{}/manifest", url), tenant_id, &manifest_payload)
+            .await?;
+        debug!(
+            tenant_id = %tenant_id,
+            batch_hash = %manifest.batch_hash,
+            "uploaded audit batch manifest"
+        );
+
+        Ok(())
+    }
+
+    async fn post_with_retry(
+        &self,
+        url: &str,
+        tenant_id: &str,
+        payload: &UploadPayload,
+    ) -> Result<(), UploadError> {
         // Exponential backoff configuration
         const MAX_RETRIES: u32 = 3;
         const INITIAL_BACKOFF_MS: u64 = 100;
@@ -138,14 +199,22 @@ impl UploadQueue {
         let mut backoff = INITIAL_BACKOFF_MS;
 
         loop {
-            let response = self.http_client.post(&url).json(&logs).send().await?;
+            let mut request = self
+                .http_client
+                .post(url)
+                .header(CONTENT_TYPE, payload.content_type)
+                .body(payload.body.clone());
+            if let Some(encoding) = payload.content_encoding {
+                request = request.header(CONTENT_ENCODING, encoding);
+            }
+            let response = request.send().await?;
 
             if response.status().is_success() {
                 debug!(
                     tenant_id = %tenant_id,
-                    count = logs.len(),
+                    url = %url,
                     attempts = attempt + 1,
-                    "uploaded audit logs batch"
+                    "upload request accepted"
                 );
                 return Ok(());
             } else if response.status().is_server_error() {
@@ -186,3 +255,84 @@ impl UploadQueue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tenant_registry::TenantRecord;
+    use crate::storage::AuditDatabase;
+
+    fn log(log_id: &str) -> AuditLogEntry {
+        AuditLogEntry {
+            log_id: log_id.to_string(),
+            tenant_id: "tenant-a".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            decision: "allow".to_string(),
+            protocol: "http".to_string(),
+            subject: serde_json::json!({"user_id": "u1"}),
+            action: "read".to_string(),
+            resource: serde_json::json!({"type": "sensor_data"}),
+            environment: serde_json::json!({}),
+            policy_version: Some(1),
+            reason: None,
+            signature: "signature".to_string(),
+            uploaded: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn persistent_upload_failure_moves_batch_to_dead_letter() {
+        let temp = tempfile::tempdir().unwrap();
+
+        // Reserve a port and release it so every upload is refused.
+        let endpoint = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+
+        let config = AuditStoreConfig {
+            data_dir: temp.path().to_path_buf(),
+            hmac_secret_key: "upload-test-secret-key-of-32-bytes!".to_string(),
+            upload_endpoint: Some(endpoint),
+            upload_max_attempts: 2,
+            ..AuditStoreConfig::default()
+        };
+        let database = Arc::new(AuditDatabase::new(config.data_dir.clone()).unwrap());
+        let tenant_registry = Arc::new(TenantRegistry::new(&config.data_dir).unwrap());
+        let signer = Arc::new(Signer::new(&config.hmac_secret_key).unwrap());
+
+        tenant_registry
+            .create_tenant(&TenantRecord {
+                tenant_id: "tenant-a".to_string(),
+                name: "Tenant A".to_string(),
+                status: "active".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                updated_at: "2025-01-01T00:00:00Z".to_string(),
+                config: None,
+            })
+            .unwrap();
+        database.write_audit_log("tenant-a", &log("log-1")).unwrap();
+        database.write_audit_log("tenant-a", &log("log-2")).unwrap();
+
+        let queue = UploadQueue::new(
+            database.clone(),
+            tenant_registry,
+            signer,
+            &config,
+        );
+
+        assert_eq!(queue.process_uploads().await.unwrap(), 0);
+        assert_eq!(database.get_unuploaded_logs("tenant-a", 10).unwrap().len(), 2);
+        assert!(database.list_dead_letter_logs("tenant-a", 10).unwrap().is_empty());
+
+        assert_eq!(queue.process_uploads().await.unwrap(), 0);
+        assert!(database.get_unuploaded_logs("tenant-a", 10).unwrap().is_empty());
+        let dead_letter = database.list_dead_letter_logs("tenant-a", 10).unwrap();
+        assert_eq!(dead_letter.len(), 2);
+        assert!(dead_letter.iter().all(|entry| entry.upload_attempts == 2));
+        assert!(dead_letter.iter().all(|entry| entry.last_upload_error.is_some()));
+
+        assert_eq!(database.requeue_dead_letter_logs("tenant-a").unwrap(), 2);
+        assert_eq!(database.get_unuploaded_logs("tenant-a", 10).unwrap().len(), 2);
+    }
+}
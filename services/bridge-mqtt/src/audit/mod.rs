@@ -0,0 +1,10 @@
+mod sink;
+
+pub use sink::{AuditEvent, AuditSink, BypassAuditEvent, TransformAuditEvent};
+
+/// Events queued for the audit store before new ones are dropped.
+pub const DEFAULT_AUDIT_QUEUE_CAPACITY: usize = 1024;
+
+/// Audit action recorded when an operation is allowed because the bridge
+/// failed open.
+pub const ENFORCEMENT_BYPASSED: &str = "ENFORCEMENT_BYPASSED";
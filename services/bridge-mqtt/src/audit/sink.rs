@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{debug, warn};
+
+use super::ENFORCEMENT_BYPASSED;
+use crate::auth::TenantContext;
+use crate::policy::BypassReason;
+use crate::transform::TransformDirective;
+
+/// Record of a publish payload the bridge rewrote before delivery.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransformAuditEvent {
+    pub tenant_id: String,
+    pub client_id: String,
+    pub user_id: Option<String>,
+    pub device_id: Option<String>,
+    pub topic: String,
+    pub directives: Vec<TransformDirective>,
+    pub timestamp: String,
+}
+
+impl TransformAuditEvent {
+    pub fn new(context: &TenantContext, topic: &str, directives: &[TransformDirective]) -> Self {
+        Self {
+            tenant_id: context.tenant_id.clone(),
+            client_id: context.client_id.clone(),
+            user_id: context.user_id.clone(),
+            device_id: context.device_id.clone(),
+            topic: topic.to_string(),
+            directives: directives.to_vec(),
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Body for the audit store's `POST /api/audit/logs`.
+    fn to_audit_log(&self) -> Value {
+        json!({
+            "tenant_id": self.tenant_id,
+            "timestamp": self.timestamp,
+            "decision": "allow",
+            "protocol": "mqtt",
+            "subject": {
+                "client_id": self.client_id,
+                "user_id": self.user_id,
+                "device_id": self.device_id,
+            },
+            "action": "transform",
+            "resource": {
+                "topic": self.topic,
+                "directives": self.directives,
+            },
+            "environment": {},
+            "policy_version": null,
+            "reason": "payload transformed before delivery",
+        })
+    }
+}
+
+/// Record of an MQTT operation allowed without an enforcer decision because
+/// the bridge failed open.
+#[derive(Debug, Clone, Serialize)]
+pub struct BypassAuditEvent {
+    pub tenant_id: String,
+    pub client_id: String,
+    pub user_id: Option<String>,
+    pub device_id: Option<String>,
+    /// `publish` or `subscribe`.
+    pub operation: String,
+    /// Topic, or topic filter for subscriptions.
+    pub topic: String,
+    pub reason: BypassReason,
+    pub timestamp: String,
+}
+
+impl BypassAuditEvent {
+    pub fn new(
+        context: &TenantContext,
+        operation: &str,
+        topic: &str,
+        reason: BypassReason,
+    ) -> Self {
+        Self {
+            tenant_id: context.tenant_id.clone(),
+            client_id: context.client_id.clone(),
+            user_id: context.user_id.clone(),
+            device_id: context.device_id.clone(),
+            operation: operation.to_string(),
+            topic: topic.to_string(),
+            reason,
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Body for the audit store's `POST /api/audit/logs`.
+    fn to_audit_log(&self) -> Value {
+        json!({
+            "tenant_id": self.tenant_id,
+            "timestamp": self.timestamp,
+            "decision": "allow",
+            "protocol": "mqtt",
+            "subject": {
+                "client_id": self.client_id,
+                "user_id": self.user_id,
+                "device_id": self.device_id,
+            },
+            "action": ENFORCEMENT_BYPASSED,
+            "resource": {
+                "operation": self.operation,
+                "topic": self.topic,
+            },
+            "environment": {},
+            "policy_version": null,
+            "reason": self.reason.to_string(),
+        })
+    }
+}
+
+/// Any event the bridge sends to the audit store.
+#[derive(Debug, Clone)]
+pub enum AuditEvent {
+    Transform(TransformAuditEvent),
+    EnforcementBypassed(BypassAuditEvent),
+}
+
+impl AuditEvent {
+    pub fn tenant_id(&self) -> &str {
+        match self {
+            AuditEvent::Transform(event) => &event.tenant_id,
+            AuditEvent::EnforcementBypassed(event) => &event.tenant_id,
+        }
+    }
+
+    pub fn topic(&self) -> &str {
+        match self {
+            AuditEvent::Transform(event) => &event.topic,
+            AuditEvent::EnforcementBypassed(event) => &event.topic,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            AuditEvent::Transform(_) => "transform",
+            AuditEvent::EnforcementBypassed(_) => "enforcement bypass",
+        }
+    }
+
+    fn to_audit_log(&self) -> Value {
+        match self {
+            AuditEvent::Transform(event) => event.to_audit_log(),
+            AuditEvent::EnforcementBypassed(event) => event.to_audit_log(),
+        }
+    }
+}
+
+impl From<TransformAuditEvent> for AuditEvent {
+    fn from(event: TransformAuditEvent) -> Self {
+        AuditEvent::Transform(event)
+    }
+}
+
+impl From<BypassAuditEvent> for AuditEvent {
+    fn from(event: BypassAuditEvent) -> Self {
+        AuditEvent::EnforcementBypassed(event)
+    }
+}
+
+/// Queues audit events without blocking the publish path.
+///
+/// Events are delivered from a background task; when the queue is full new
+/// events are dropped with a warning rather than delaying the publish.
+#[derive(Clone)]
+pub struct AuditSink {
+    sender: mpsc::Sender<AuditEvent>,
+}
+
+impl AuditSink {
+    /// A sink together with the receiving end of its queue.
+    pub fn channel(capacity: usize) -> (Self, mpsc::Receiver<AuditEvent>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        (Self { sender }, receiver)
+    }
+
+    /// A sink whose events are posted to the audit store at
+    /// `audit_store_url`. Must be called from within a Tokio runtime.
+    pub fn spawn(audit_store_url: &str, capacity: usize, timeout: Duration) -> Result<Self> {
+        let http_client = reqwest::Client::builder().timeout(timeout).build()?;
+        let url = format!("{}/api/audit/logs", audit_store_url.trim_end_matches('/'));
+        let (sink, mut receiver) = Self::channel(capacity);
+
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                match http_client
+                    .post(&url)
+                    .json(&event.to_audit_log())
+                    .send()
+                    .await
+                {
+                    Ok(response) if response.status().is_success() => {
+                        debug!(
+                            "Recorded {} event for tenant '{}' on '{}'",
+                            event.kind(),
+                            event.tenant_id(),
+                            event.topic()
+                        );
+                    }
+                    Ok(response) => {
+                        warn!(
+                            "Audit store rejected {} event for tenant '{}': {}",
+                            event.kind(),
+                            event.tenant_id(),
+                            response.status()
+                        );
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to send {} event for tenant '{}' to audit store: {}",
+                            event.kind(),
+                            event.tenant_id(),
+                            e
+                        );
+                    }
+                }
+            }
+        });
+
+        Ok(sink)
+    }
+
+    pub fn record(&self, event: impl Into<AuditEvent>) {
+        match self.sender.try_send(event.into()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => {
+                warn!(
+                    "Audit queue full, dropping {} event for tenant '{}' on '{}'",
+                    event.kind(),
+                    event.tenant_id(),
+                    event.topic()
+                );
+            }
+            Err(TrySendError::Closed(event)) => {
+                warn!(
+                    "Audit queue closed, dropping {} event for tenant '{}' on '{}'",
+                    event.kind(),
+                    event.tenant_id(),
+                    event.topic()
+                );
+            }
+        }
+    }
+}
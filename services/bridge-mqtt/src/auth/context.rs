@@ -1,5 +1,7 @@
 use std::net::IpAddr;
 
+use chrono::{DateTime, Utc};
+
 use super::AuthSource;
 
 #[derive(Debug, Clone)]
@@ -11,6 +13,7 @@ pub struct TenantContext {
     pub auth_source: AuthSource,
     pub client_ip: Option<IpAddr>,
     pub connection_id: String,
+    pub connected_at: DateTime<Utc>,
 }
 
 impl TenantContext {
@@ -23,6 +26,7 @@ impl TenantContext {
             auth_source,
             client_ip: None,
             connection_id: uuid::Uuid::new_v4().to_string(),
+            connected_at: Utc::now(),
         }
     }
 
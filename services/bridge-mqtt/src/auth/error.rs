@@ -8,6 +8,12 @@ pub enum AuthError {
     #[error("Invalid certificate: {0}")]
     InvalidCertificate(String),
 
+    #[error("Certificate expired at {0}")]
+    CertificateExpired(String),
+
+    #[error("Certificate not valid before {0}")]
+    CertificateNotYetValid(String),
+
     #[error("Invalid username format: {0}")]
     InvalidUsernameFormat(String),
 
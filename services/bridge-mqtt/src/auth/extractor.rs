@@ -5,12 +5,13 @@ use x509_parser::prelude::*;
 
 use crate::config::BridgeConfig;
 
-use super::{AuthError, AuthSource, TenantContext, CLIENTID_SEPARATOR, USERNAME_SEPARATOR};
+use super::{AuthError, AuthSource, TenantContext, TenantStrategy};
 
 pub struct TenantExtractor {
     enable_mtls: bool,
     cert_cn_as_username: bool,
     topic_namespace_pattern: String,
+    strategy: TenantStrategy,
 }
 
 impl TenantExtractor {
@@ -19,11 +20,13 @@ impl TenantExtractor {
             enable_mtls: config.enable_mtls,
             cert_cn_as_username: config.cert_cn_as_username,
             topic_namespace_pattern: config.topic_namespace_pattern.clone(),
+            strategy: config.tenant_strategy.clone(),
         }
     }
 
     pub fn extract_from_certificate(&self, cert_der: &[u8]) -> Result<String, AuthError> {
         let (_, cert) = parse_x509_certificate(cert_der)?;
+        check_validity(&cert)?;
 
         // Try extracting from Subject Alternative Name (SAN) with URI format tenant:{id}
         if let Ok(Some(san_ext)) = cert.subject_alternative_name() {
@@ -61,7 +64,7 @@ impl TenantExtractor {
         }
 
         // Parse username format: tenant_id:user_id or just tenant_id
-        let parts: Vec<&str> = username.split(USERNAME_SEPARATOR).collect();
+        let parts: Vec<&str> = username.split(self.strategy.username_separator).collect();
 
         match parts.len() {
             1 => {
@@ -102,7 +105,7 @@ impl TenantExtractor {
         }
 
         // Parse client ID format: tenant_id/device_id or just tenant_id
-        let parts: Vec<&str> = client_id.split(CLIENTID_SEPARATOR).collect();
+        let parts: Vec<&str> = client_id.split(self.strategy.client_id_separator).collect();
 
         match parts.len() {
             1 => {
@@ -147,72 +150,90 @@ impl TenantExtractor {
         cert_der: Option<&[u8]>,
         client_ip: Option<IpAddr>,
     ) -> Result<TenantContext, AuthError> {
-        let mut tenant_id: Option<String> = None;
-        let mut user_id: Option<String> = None;
+        let mut resolved: Option<(String, AuthSource)> = None;
+        let mut cert_tenant: Option<String> = None;
+        let mut username_identity: Option<(String, Option<String>)> = None;
         let mut device_id: Option<String> = None;
-        let mut auth_source: Option<AuthSource> = None;
-
-        // Try certificate extraction if mTLS enabled and cert provided
-        if self.enable_mtls {
-            if let Some(cert_bytes) = cert_der {
-                match self.extract_from_certificate(cert_bytes) {
-                    Ok(tid) => {
-                        tenant_id = Some(tid);
-                        auth_source = Some(AuthSource::Certificate);
-                    }
-                    Err(e) => {
-                        warn!("Failed to extract tenant from certificate: {}", e);
-                    }
-                }
-            }
-        }
 
-        // Try username extraction if username provided
-        if let Some(uname) = username {
-            match self.extract_from_username(uname) {
-                Ok((tid, uid)) => {
-                    // Verify consistency with certificate if both present
-                    if let Some(ref cert_tenant) = tenant_id {
-                        if cert_tenant != &tid {
-                            return Err(AuthError::TenantIdMismatch {
-                                cert_tenant: cert_tenant.clone(),
-                                username_tenant: tid,
-                            });
+        // Read every enabled source in the configured order; the first one
+        // that yields a tenant decides it.
+        for &source in &self.strategy.sources {
+            let tenant_id = match source {
+                AuthSource::Certificate => {
+                    let Some(cert_bytes) = cert_der.filter(|_| self.enable_mtls) else {
+                        continue;
+                    };
+                    match self.extract_from_certificate(cert_bytes) {
+                        Ok(tid) => {
+                            cert_tenant = Some(tid.clone());
+                            tid
+                        }
+                        // An expired or not-yet-valid certificate must not fall
+                        // back to the username or client ID.
+                        Err(
+                            e @ (AuthError::CertificateExpired(_)
+                            | AuthError::CertificateNotYetValid(_)),
+                        ) => {
+                            warn!("Rejected client certificate: {}", e);
+                            return Err(e);
+                        }
+                        Err(e) => {
+                            warn!("Failed to extract tenant from certificate: {}", e);
+                            continue;
                         }
-                    } else {
-                        tenant_id = Some(tid);
-                        auth_source = Some(AuthSource::Username);
                     }
-                    user_id = uid;
                 }
-                Err(e) => {
-                    warn!("Failed to extract tenant from username: {}", e);
+                AuthSource::Username => {
+                    let Some(uname) = username else {
+                        continue;
+                    };
+                    match self.extract_from_username(uname) {
+                        Ok((tid, uid)) => {
+                            username_identity = Some((tid.clone(), uid));
+                            tid
+                        }
+                        Err(e) => {
+                            warn!("Failed to extract tenant from username: {}", e);
+                            continue;
+                        }
+                    }
                 }
+                AuthSource::ClientId => match self.extract_from_client_id(client_id) {
+                    Ok((tid, did)) => {
+                        device_id = did;
+                        tid
+                    }
+                    Err(e) => {
+                        warn!("Failed to extract tenant from client_id: {}", e);
+                        continue;
+                    }
+                },
+            };
+
+            if resolved.is_none() {
+                resolved = Some((tenant_id, source));
             }
         }
 
-        // Try client ID extraction as fallback
-        if tenant_id.is_none() {
-            match self.extract_from_client_id(client_id) {
-                Ok((tid, did)) => {
-                    tenant_id = Some(tid);
-                    device_id = did;
-                    auth_source = Some(AuthSource::ClientId);
-                }
-                Err(e) => {
-                    warn!("Failed to extract tenant from client_id: {}", e);
-                }
-            }
-        } else {
-            // Extract device_id from client_id if tenant already known
-            if let Ok((_, did)) = self.extract_from_client_id(client_id) {
-                device_id = did;
+        // A certificate and a username naming different tenants is rejected
+        // whichever of the two is preferred.
+        if let (Some(cert_tenant), Some((username_tenant, _))) = (&cert_tenant, &username_identity)
+        {
+            if cert_tenant != username_tenant {
+                return Err(AuthError::TenantIdMismatch {
+                    cert_tenant: cert_tenant.clone(),
+                    username_tenant: username_tenant.clone(),
+                });
             }
         }
 
         // Return error if no tenant ID was extracted from any source
-        let final_tenant_id = tenant_id.ok_or(AuthError::TenantIdNotFound)?;
-        let final_auth_source = auth_source.ok_or(AuthError::TenantIdNotFound)?;
+        let (final_tenant_id, final_auth_source) = resolved.ok_or(AuthError::TenantIdNotFound)?;
+
+        // The username only contributes a user ID for the tenant it names.
+        let user_id = username_identity
+            .filter(|(tid, _)| *tid == final_tenant_id)
+            .and_then(|(_, uid)| uid);
 
         // Build TenantContext with all extracted information
         let mut context = TenantContext::new(final_tenant_id, client_id.to_string(), final_auth_source);
@@ -235,3 +256,21 @@ impl TenantExtractor {
         Ok(context)
     }
 }
+
+/// Rejects certificates whose `notBefore`/`notAfter` window does not contain
+/// the current time.
+fn check_validity(cert: &X509Certificate<'_>) -> Result<(), AuthError> {
+    let validity = cert.validity();
+    let now = ASN1Time::now();
+    if now < validity.not_before {
+        return Err(AuthError::CertificateNotYetValid(
+            validity.not_before.to_string(),
+        ));
+    }
+    if now > validity.not_after {
+        return Err(AuthError::CertificateExpired(
+            validity.not_after.to_string(),
+        ));
+    }
+    Ok(())
+}
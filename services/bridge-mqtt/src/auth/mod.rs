@@ -1,17 +1,24 @@
+use serde::{Deserialize, Serialize};
+
 mod context;
 mod error;
 mod extractor;
+mod strategy;
 
 pub use context::TenantContext;
 pub use error::AuthError;
 pub use extractor::TenantExtractor;
+pub use strategy::TenantStrategy;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AuthSource {
     Certificate,
     Username,
     ClientId,
 }
 
+/// Default separator for `tenant_id:user_id` usernames.
 pub const USERNAME_SEPARATOR: char = ':';
+/// Default separator for `tenant_id/device_id` client IDs.
 pub const CLIENTID_SEPARATOR: char = '/';
@@ -0,0 +1,96 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use super::{AuthSource, CLIENTID_SEPARATOR, USERNAME_SEPARATOR};
+
+/// Which sources may supply a client's tenant ID, in order of preference, and
+/// how usernames and client IDs are split.
+///
+/// The first enabled source that yields a tenant wins. Sources left out of
+/// `sources` are not read at all.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TenantStrategy {
+    pub sources: Vec<AuthSource>,
+    /// Splits `tenant_id{sep}user_id` usernames.
+    pub username_separator: char,
+    /// Splits `tenant_id{sep}device_id` client IDs.
+    pub client_id_separator: char,
+}
+
+impl Default for TenantStrategy {
+    fn default() -> Self {
+        Self {
+            sources: vec![
+                AuthSource::Certificate,
+                AuthSource::Username,
+                AuthSource::ClientId,
+            ],
+            username_separator: USERNAME_SEPARATOR,
+            client_id_separator: CLIENTID_SEPARATOR,
+        }
+    }
+}
+
+impl TenantStrategy {
+    /// Parses a comma-separated source list such as `username,client_id`.
+    pub fn parse_sources(raw: &str) -> Result<Vec<AuthSource>, String> {
+        raw.split(',')
+            .map(str::trim)
+            .filter(|source| !source.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sources.is_empty() {
+            return Err("at least one tenant source must be enabled".to_string());
+        }
+        for (index, source) in self.sources.iter().enumerate() {
+            if self.sources[..index].contains(source) {
+                return Err(format!(
+                    "tenant source '{}' is listed more than once",
+                    source
+                ));
+            }
+        }
+        validate_separator("username", self.username_separator)?;
+        validate_separator("client ID", self.client_id_separator)
+    }
+}
+
+/// Separators must not be able to appear inside a tenant ID.
+fn validate_separator(name: &str, separator: char) -> Result<(), String> {
+    if separator.is_alphanumeric() || separator.is_whitespace() || matches!(separator, '-' | '_') {
+        return Err(format!(
+            "{} separator '{}' may appear in tenant IDs",
+            name, separator
+        ));
+    }
+    Ok(())
+}
+
+impl fmt::Display for AuthSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthSource::Certificate => f.write_str("certificate"),
+            AuthSource::Username => f.write_str("username"),
+            AuthSource::ClientId => f.write_str("client_id"),
+        }
+    }
+}
+
+impl FromStr for AuthSource {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "certificate" | "cert" => Ok(AuthSource::Certificate),
+            "username" => Ok(AuthSource::Username),
+            "client_id" | "clientid" => Ok(AuthSource::ClientId),
+            other => Err(format!("unsupported tenant source: {}", other)),
+        }
+    }
+}
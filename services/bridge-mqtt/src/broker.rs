@@ -8,12 +8,13 @@ use rmqtt::context::ServerContext;
 use rmqtt::hook::{Handler, HookResult, Parameter, ReturnType, Type};
 use rmqtt::net::Builder;
 use rmqtt::server::MqttServer;
-use rmqtt::types::{PublishAclResult, SubscribeAclResult};
+use rmqtt::types::{PublishAclResult, Reason, SubscribeAclResult};
 use rmqtt::session::Session;
 use rmqtt::codec::v3;
+use rmqtt::codec::v5::SubscribeAckReason;
 
 use crate::config::BridgeConfig;
-use crate::hooks::{HookContext, PolicyHookHandler};
+use crate::hooks::{HookContext, HookRejection, PolicyHookHandler, RejectReason};
 
 pub struct MqttBroker {
     config: Arc<BridgeConfig>,
@@ -127,6 +128,16 @@ impl MqttBroker {
 
         info!("MQTT broker started successfully");
 
+        if let Some(port) = self.config.management_port {
+            let addr = format!("{}:{}", self.config.broker_host, port);
+            let hook_context = self.hook_context.clone();
+            tokio::spawn(async move {
+                if let Err(e) = crate::management::serve(&addr, hook_context).await {
+                    tracing::error!("Management API error: {:?}", e);
+                }
+            });
+        }
+
         // Wait for shutdown signal
         tokio::select! {
             _ = tokio::signal::ctrl_c() => {
@@ -178,6 +189,40 @@ impl RmqttPolicyAdapter {
     fn extract_peer_addr(session: &Session) -> Option<std::net::IpAddr> {
         session.id.remote_addr.map(|addr| addr.ip())
     }
+
+    /// Attach the rejection reason string to MQTT 5 sessions.
+    ///
+    /// RMQTT's ACL results only carry a reason code, so the reason string is
+    /// recorded on the session and sent with the DISCONNECT packet when the
+    /// broker closes the connection. MQTT 3.x sessions get nothing extra.
+    async fn record_rejection(session: &Session, rejection: &HookRejection, subscribe: bool) {
+        let protocol_level = session.protocol().await.unwrap_or_default();
+        let response = rejection.to_response(protocol_level);
+
+        if let Some(reason_string) = response.reason_string {
+            let reason = if subscribe {
+                Reason::SubscribeFailed(Some(reason_string.into()))
+            } else {
+                Reason::PublishFailed(reason_string.into())
+            };
+            if let Err(e) = session.disconnected_reason_add(reason).await {
+                debug!("Failed to record rejection reason for {:?}: {:?}", session.id.client_id, e);
+            }
+        }
+    }
+
+    fn subscribe_ack_reason(reason: RejectReason) -> SubscribeAckReason {
+        match reason {
+            RejectReason::NotAuthorized => SubscribeAckReason::NotAuthorized,
+            RejectReason::QuotaExceeded => SubscribeAckReason::QuotaExceeded,
+            RejectReason::WildcardSubscriptionsNotSupported => {
+                SubscribeAckReason::WildcardSubscriptionsNotSupported
+            }
+            RejectReason::PacketTooLarge | RejectReason::UnspecifiedError => {
+                SubscribeAckReason::UnspecifiedError
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -297,6 +342,7 @@ impl Handler for RmqttPolicyAdapter {
                     }
                     Err(e) => {
                         warn!("Message publish rejected: {} topic: {} - {}", client_id, topic, e);
+                        Self::record_rejection(session, &e, false).await;
                         (false, acc)
                     }
                 }
@@ -320,7 +366,10 @@ impl Handler for RmqttPolicyAdapter {
                     }
                     Err(e) => {
                         warn!("Subscribe rejected: {} topic: {} - {}", client_id, topic_filter, e);
-                        (false, None)
+                        Self::record_rejection(session, &e, true).await;
+                        (false, Some(HookResult::SubscribeAclResult(
+                            SubscribeAclResult::new_failure(Self::subscribe_ack_reason(e.reason))
+                        )))
                     }
                 }
             }
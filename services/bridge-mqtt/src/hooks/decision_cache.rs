@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use tracing::debug;
+
+use crate::policy::PolicyDecision;
+
+/// Publish attributes, besides client and topic, that the policy input
+/// carries. A cached decision only answers a publish with identical ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishAttributes {
+    pub qos: u8,
+    pub retain: bool,
+    pub payload_size: usize,
+    /// The tenant's message count when the decision was made.
+    pub message_count: u64,
+}
+
+struct CachedDecision {
+    tenant_id: String,
+    attributes: PublishAttributes,
+    decision: PolicyDecision,
+    expires_at: Instant,
+}
+
+/// Short-lived cache of publish decisions keyed by `(client_id, topic)`.
+///
+/// A cached decision is reused only while the publish keeps the same
+/// [`PublishAttributes`] and the TTL has not expired, so size- and
+/// count-based rules are always evaluated against the current values.
+/// Decisions made by failing open are never cached. A zero TTL disables
+/// caching.
+pub struct DecisionCache {
+    entries: DashMap<(String, String), CachedDecision>,
+    ttl: Duration,
+}
+
+impl DecisionCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: DashMap::new(),
+            ttl,
+        }
+    }
+
+    pub fn get(
+        &self,
+        client_id: &str,
+        topic: &str,
+        attributes: PublishAttributes,
+    ) -> Option<PolicyDecision> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+
+        let key = (client_id.to_string(), topic.to_string());
+        let entry = self.entries.get(&key)?;
+        if entry.expires_at <= Instant::now() || entry.attributes != attributes {
+            drop(entry);
+            self.entries.remove(&key);
+            return None;
+        }
+
+        Some(entry.decision.clone())
+    }
+
+    pub fn insert(
+        &self,
+        client_id: &str,
+        tenant_id: &str,
+        topic: &str,
+        attributes: PublishAttributes,
+        decision: PolicyDecision,
+    ) {
+        if self.ttl.is_zero() || decision.bypass_reason.is_some() {
+            return;
+        }
+
+        self.entries.insert(
+            (client_id.to_string(), topic.to_string()),
+            CachedDecision {
+                tenant_id: tenant_id.to_string(),
+                attributes,
+                decision,
+                expires_at: Instant::now() + self.ttl,
+            },
+        );
+    }
+
+    /// Drops every cached decision for `tenant_id`, e.g. after its policy bundle
+    /// was reloaded.
+    pub fn invalidate_tenant(&self, tenant_id: &str) {
+        self.entries.retain(|_, cached| cached.tenant_id != tenant_id);
+        debug!("Invalidated cached publish decisions for tenant '{}'", tenant_id);
+    }
+
+    pub fn remove_client(&self, client_id: &str) {
+        self.entries.retain(|(cached_client, _), _| cached_client != client_id);
+    }
+}
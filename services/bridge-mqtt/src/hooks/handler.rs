@@ -2,10 +2,12 @@ use std::sync::Arc;
 
 use tracing::{debug, error, warn, instrument};
 
-use crate::policy::MqttAbacInput;
-use crate::transform::TransformDirective;
+use crate::audit::{BypassAuditEvent, TransformAuditEvent};
+use crate::auth::TenantContext;
+use crate::policy::{BypassReason, MqttAbacInput, PolicyError};
+use crate::transform::TransformError;
 
-use super::HookContext;
+use super::{HookContext, HookRejection, PublishAttributes, RejectReason};
 
 /// PolicyHookHandler implements policy enforcement for MQTT operations.
 ///
@@ -91,6 +93,8 @@ impl PolicyHookHandler {
     pub fn handle_client_disconnected(&self, client_id: &str, reason: &str) {
         debug!("Handling client disconnection: {} (reason: {})", client_id, reason);
 
+        self.context.decision_cache.remove_client(client_id);
+
         if let Some(context) = self.context.session_store.remove_context(client_id) {
             debug!(
                 "Removed session for client '{}' with tenant '{}'",
@@ -99,6 +103,13 @@ impl PolicyHookHandler {
         }
     }
 
+    /// Handle tenant policy reload - drop cached publish decisions so the next
+    /// publish is evaluated against the new bundle
+    #[instrument(skip(self))]
+    pub fn handle_tenant_policy_reloaded(&self, tenant_id: &str) {
+        self.context.decision_cache.invalidate_tenant(tenant_id);
+    }
+
     /// Handle message publish - validate, query policy, transform if needed
     #[instrument(skip(self, payload))]
     pub async fn handle_message_publish(
@@ -108,7 +119,7 @@ impl PolicyHookHandler {
         qos: u8,
         retain: bool,
         payload: &[u8],
-    ) -> Result<Option<Vec<u8>>, String> {
+    ) -> Result<Option<Vec<u8>>, HookRejection> {
         debug!(
             "Handling message publish: client={}, topic={}, qos={}, retain={}, size={}",
             client_id, topic, qos, retain, payload.len()
@@ -121,7 +132,7 @@ impl PolicyHookHandler {
             .get_context(client_id)
             .ok_or_else(|| {
                 error!("No tenant context found for client '{}'", client_id);
-                "Client not authenticated".to_string()
+                HookRejection::new(RejectReason::NotAuthorized, "Client not authenticated")
             })?;
 
         // Validate topic namespace matches tenant
@@ -130,7 +141,10 @@ impl PolicyHookHandler {
                 "Topic namespace violation: client '{}' (tenant '{}') attempted to publish to '{}'",
                 client_id, tenant_context.tenant_id, topic
             );
-            return Err("Topic namespace violation".to_string());
+            return Err(HookRejection::new(
+                RejectReason::NotAuthorized,
+                "Topic namespace violation",
+            ));
         }
 
         // Fast-fail quota check before policy query
@@ -139,7 +153,10 @@ impl PolicyHookHandler {
                 "Quota exceeded for tenant '{}': {}",
                 tenant_context.tenant_id, e
             );
-            return Err(format!("Quota limit exceeded: {}", e));
+            return Err(HookRejection::new(
+                RejectReason::QuotaExceeded,
+                format!("Quota limit exceeded: {}", e),
+            ));
         }
 
         // Get current quota metrics
@@ -148,68 +165,99 @@ impl PolicyHookHandler {
             .get_metrics(&tenant_context.tenant_id)
             .unwrap_or_default();
 
-        // Build ABAC input for publish
-        let abac_input = MqttAbacInput::for_publish(
-            &tenant_context,
-            topic,
+        // Reuse a recent decision only for an identical policy input
+        let publish_attributes = PublishAttributes {
             qos,
             retain,
-            payload.len(),
-            metrics.message_count,
-        );
-
-        // Query policy
-        let policy_decision = self
+            payload_size: payload.len(),
+            message_count: metrics.message_count,
+        };
+        let cached_decision = self
             .context
-            .policy_client
-            .query_publish_policy(&tenant_context.tenant_id, abac_input)
-            .await
-            .map_err(|e| {
-                error!(
-                    "Policy query failed for client '{}' publishing to '{}': {}",
-                    client_id, topic, e
+            .decision_cache
+            .get(client_id, topic, publish_attributes);
+
+        let policy_decision = match cached_decision {
+            Some(decision) => {
+                debug!(
+                    "Using cached publish decision for client '{}' on '{}'",
+                    client_id, topic
                 );
-                format!("Policy enforcement error: {}", e)
-            })?;
+                decision
+            }
+            None => {
+                // Build ABAC input for publish
+                let abac_input = MqttAbacInput::for_publish(
+                    &tenant_context,
+                    topic,
+                    qos,
+                    retain,
+                    payload.len(),
+                    metrics.message_count,
+                )
+                .with_topic_template(self.context.config.topic_template.as_ref());
+
+                // Query policy
+                let decision = self
+                    .context
+                    .policy_client
+                    .query_publish_policy(&tenant_context.tenant_id, abac_input)
+                    .await
+                    .map_err(|e| {
+                        error!(
+                            "Policy query failed for client '{}' publishing to '{}': {}",
+                            client_id, topic, e
+                        );
+                        policy_rejection(e)
+                    })?;
+
+                self.context.decision_cache.insert(
+                    client_id,
+                    &tenant_context.tenant_id,
+                    topic,
+                    publish_attributes,
+                    decision.clone(),
+                );
+                decision
+            }
+        };
 
         debug!(
             "Policy decision for client '{}' publishing to '{}': allow={}",
             client_id, topic, policy_decision.allow
         );
+        if let Some(reason) = policy_decision.bypass_reason {
+            self.record_bypass(&tenant_context, "publish", topic, reason);
+        }
 
-        // Check if transformation is needed
-        let transformed_payload = if self.context.config.enable_payload_transformation {
-            let mut directives = Vec::new();
-
-            // Handle redact_fields (new field)
-            if let Some(redact_fields) = policy_decision.redact_fields {
-                if !redact_fields.is_empty() {
-                    debug!("Adding RedactFields directive: {:?}", redact_fields);
-                    directives.push(TransformDirective::RedactFields(redact_fields));
-                }
-            }
-
-            // Handle legacy redact field (maps to RemoveFields for backwards compatibility)
-            if let Some(remove_fields) = policy_decision.redact {
-                if !remove_fields.is_empty() {
-                    debug!("Adding RemoveFields directive from legacy 'redact': {:?}", remove_fields);
-                    directives.push(TransformDirective::RemoveFields(remove_fields));
-                }
-            }
-
-            // Handle remove_fields
-            if let Some(remove_fields) = policy_decision.remove_fields {
-                if !remove_fields.is_empty() {
-                    debug!("Adding RemoveFields directive: {:?}", remove_fields);
-                    directives.push(TransformDirective::RemoveFields(remove_fields));
-                }
+        // Enforce the tenant publish size cap, independent of the broker packet limit
+        let max_publish_bytes = policy_decision.max_publish_bytes.or_else(|| {
+            self.context
+                .config
+                .tenant_max_publish_bytes
+                .get(&tenant_context.tenant_id)
+                .copied()
+        });
+        if let Some(limit) = max_publish_bytes {
+            if payload.len() > limit {
+                warn!(
+                    "Publish size limit exceeded: client '{}' (tenant '{}') sent {} bytes to '{}', limit is {}",
+                    client_id, tenant_context.tenant_id, payload.len(), topic, limit
+                );
+                return Err(HookRejection::new(
+                    RejectReason::PacketTooLarge,
+                    format!(
+                        "Publish payload of {} bytes exceeds tenant limit of {} bytes",
+                        payload.len(),
+                        limit
+                    ),
+                ));
             }
+        }
 
-            // Handle strip_coordinates
-            if let Some(true) = policy_decision.strip_coordinates {
-                debug!("Adding StripCoordinates directive");
-                directives.push(TransformDirective::StripCoordinates);
-            }
+        // Check if transformation is needed
+        let transformed_payload = if self.context.config.enable_payload_transformation {
+            let directives = policy_decision.transform_directives();
 
             if !directives.is_empty() {
                 debug!(
@@ -218,7 +266,28 @@ impl PolicyHookHandler {
                 );
 
                 match self.context.payload_transformer.transform_payload(payload, &directives) {
-                    Ok(transformed) => Some(transformed),
+                    Ok(transformed) => {
+                        if let Some(audit_sink) = &self.context.audit_sink {
+                            audit_sink.record(TransformAuditEvent::new(
+                                &tenant_context,
+                                topic,
+                                &directives,
+                            ));
+                        }
+                        Some(transformed)
+                    }
+                    Err(e @ TransformError::MaxDepthExceeded(_)) => {
+                        // Forwarding the original would skip every directive,
+                        // including redactions, for the deeply nested fields.
+                        warn!(
+                            "Rejecting publish from client '{}' (tenant '{}') to '{}': {}",
+                            client_id, tenant_context.tenant_id, topic, e
+                        );
+                        return Err(HookRejection::new(
+                            RejectReason::UnspecifiedError,
+                            e.to_string(),
+                        ));
+                    }
                     Err(e) => {
                         warn!("Payload transformation failed: {}", e);
                         None
@@ -246,7 +315,7 @@ impl PolicyHookHandler {
         client_id: &str,
         topic_filter: &str,
         qos: u8,
-    ) -> Result<(), String> {
+    ) -> Result<(), HookRejection> {
         debug!(
             "Handling client subscribe: client={}, topic_filter={}, qos={}",
             client_id, topic_filter, qos
@@ -259,7 +328,7 @@ impl PolicyHookHandler {
             .get_context(client_id)
             .ok_or_else(|| {
                 error!("No tenant context found for client '{}'", client_id);
-                "Client not authenticated".to_string()
+                HookRejection::new(RejectReason::NotAuthorized, "Client not authenticated")
             })?;
 
         // Validate topic filter namespace
@@ -268,7 +337,10 @@ impl PolicyHookHandler {
                 "Topic filter namespace violation: client '{}' (tenant '{}') attempted to subscribe to '{}'",
                 client_id, tenant_context.tenant_id, topic_filter
             );
-            return Err("Topic filter namespace violation".to_string());
+            return Err(HookRejection::new(
+                RejectReason::NotAuthorized,
+                "Topic filter namespace violation",
+            ));
         }
 
         // Fast-fail quota check before policy query
@@ -277,7 +349,10 @@ impl PolicyHookHandler {
                 "Quota exceeded for tenant '{}': {}",
                 tenant_context.tenant_id, e
             );
-            return Err(format!("Quota limit exceeded: {}", e));
+            return Err(HookRejection::new(
+                RejectReason::QuotaExceeded,
+                format!("Quota limit exceeded: {}", e),
+            ));
         }
 
         // Check wildcard restrictions
@@ -287,7 +362,10 @@ impl PolicyHookHandler {
                     "Wildcard subscription denied for client '{}': {}",
                     client_id, topic_filter
                 );
-                return Err("Wildcard subscriptions not allowed".to_string());
+                return Err(HookRejection::new(
+                    RejectReason::WildcardSubscriptionsNotSupported,
+                    "Wildcard subscriptions not allowed",
+                ));
             }
         }
 
@@ -303,10 +381,12 @@ impl PolicyHookHandler {
             topic_filter,
             qos,
             metrics.message_count,
-        );
+        )
+        .with_topic_template(self.context.config.topic_template.as_ref());
 
         // Query policy
-        self.context
+        let decision = self
+            .context
             .policy_client
             .query_subscribe_policy(&tenant_context.tenant_id, abac_input)
             .await
@@ -315,8 +395,11 @@ impl PolicyHookHandler {
                     "Policy query failed for client '{}' subscribing to '{}': {}",
                     client_id, topic_filter, e
                 );
-                format!("Policy enforcement error: {}", e)
+                policy_rejection(e)
             })?;
+        if let Some(reason) = decision.bypass_reason {
+            self.record_bypass(&tenant_context, "subscribe", topic_filter, reason);
+        }
 
         debug!(
             "Subscribe allowed for client '{}' to topic filter '{}'",
@@ -326,6 +409,28 @@ impl PolicyHookHandler {
         Ok(())
     }
 
+    /// Audits an operation allowed without an enforcer decision.
+    fn record_bypass(
+        &self,
+        tenant_context: &TenantContext,
+        operation: &str,
+        topic: &str,
+        reason: BypassReason,
+    ) {
+        warn!(
+            "Enforcement bypassed for client '{}' (tenant '{}'): {} on '{}' allowed, {}",
+            tenant_context.client_id, tenant_context.tenant_id, operation, topic, reason
+        );
+        if let Some(audit_sink) = &self.context.audit_sink {
+            audit_sink.record(BypassAuditEvent::new(
+                tenant_context,
+                operation,
+                topic,
+                reason,
+            ));
+        }
+    }
+
     /// Validate that topic matches the tenant's namespace pattern
     /// Respects MQTT wildcard semantics: + (single-level), # (multi-level)
     fn validate_topic_namespace(&self, topic: &str, tenant_id: &str) -> bool {
@@ -409,3 +514,17 @@ impl PolicyHookHandler {
         }
     }
 }
+
+/// Maps a failed policy query to a rejection. Denials carry the policy's
+/// reason so MQTT 5 clients can see why they were refused.
+fn policy_rejection(err: PolicyError) -> HookRejection {
+    match err {
+        PolicyError::Denied { .. } => {
+            HookRejection::new(RejectReason::NotAuthorized, err.to_string())
+        }
+        other => HookRejection::new(
+            RejectReason::UnspecifiedError,
+            format!("Policy enforcement error: {}", other),
+        ),
+    }
+}
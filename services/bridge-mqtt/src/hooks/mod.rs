@@ -1,15 +1,25 @@
+mod decision_cache;
 mod handler;
+mod rejection;
 mod session;
 
+pub use decision_cache::{DecisionCache, PublishAttributes};
 pub use handler::PolicyHookHandler;
-pub use session::SessionStore;
+pub use rejection::{
+    HookRejection, RejectReason, RejectResponse, MQTT_V3_FAILURE_CODE, MQTT_V5_PROTOCOL_LEVEL,
+};
+pub use session::{SessionInfo, SessionStore};
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
+use edge_policy_enrichment::InputEnrichment;
 
 use crate::{
-    auth::TenantExtractor, config::BridgeConfig, policy::PolicyClient,
+    audit::{AuditSink, DEFAULT_AUDIT_QUEUE_CAPACITY},
+    auth::TenantExtractor, config::BridgeConfig,
+    policy::{PolicyClient, RetryPolicy},
     quota::QuotaTracker, transform::PayloadTransformer,
 };
 
@@ -20,6 +30,10 @@ pub struct HookContext {
     pub payload_transformer: Arc<PayloadTransformer>,
     pub quota_tracker: Arc<QuotaTracker>,
     pub session_store: Arc<SessionStore>,
+    pub decision_cache: Arc<DecisionCache>,
+    /// Receives a record of each payload transformation and each operation
+    /// allowed by failing open, when configured.
+    pub audit_sink: Option<AuditSink>,
     pub config: Arc<BridgeConfig>,
 }
 
@@ -30,13 +44,35 @@ impl HookContext {
             config.enforcer_url.clone(),
             config.request_timeout_secs,
             config.use_mqtt_endpoints,
-        )?);
-        let payload_transformer = Arc::new(PayloadTransformer::new());
+        )?
+        .with_retry_policy(RetryPolicy::new(
+            config.policy_max_retries,
+            Duration::from_millis(config.policy_retry_backoff_ms),
+        ))
+        .with_failure_mode(config.enforcer_failure_mode)
+        .with_enrichment(InputEnrichment::standard(config.default_region.clone())));
+        let payload_transformer =
+            Arc::new(PayloadTransformer::new().with_max_depth(config.max_transform_depth));
         let quota_tracker = Arc::new(QuotaTracker::new(
             config.message_limit,
             config.bandwidth_limit_gb,
         ));
         let session_store = Arc::new(SessionStore::new());
+        let decision_cache = Arc::new(DecisionCache::new(Duration::from_millis(
+            config.publish_decision_cache_ttl_ms,
+        )));
+
+        let audit_sink = config
+            .audit_store_url
+            .as_deref()
+            .map(|url| {
+                AuditSink::spawn(
+                    url,
+                    DEFAULT_AUDIT_QUEUE_CAPACITY,
+                    Duration::from_secs(config.request_timeout_secs),
+                )
+            })
+            .transpose()?;
 
         Ok(Self {
             tenant_extractor,
@@ -44,7 +80,15 @@ impl HookContext {
             payload_transformer,
             quota_tracker,
             session_store,
+            decision_cache,
+            audit_sink,
             config: Arc::new(config),
         })
     }
+
+    /// Sends audit records to `audit_sink`.
+    pub fn with_audit_sink(mut self, audit_sink: AuditSink) -> Self {
+        self.audit_sink = Some(audit_sink);
+        self
+    }
 }
@@ -0,0 +1,78 @@
+use std::fmt;
+
+/// Protocol level sent in the CONNECT packet by MQTT 5.0 clients.
+pub const MQTT_V5_PROTOCOL_LEVEL: u8 = 5;
+
+/// Reason code used for refusals sent to MQTT 3.x clients, which cannot carry
+/// anything more specific.
+pub const MQTT_V3_FAILURE_CODE: u8 = 0x80;
+
+/// Why the bridge refused a publish or subscribe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    NotAuthorized,
+    QuotaExceeded,
+    PacketTooLarge,
+    WildcardSubscriptionsNotSupported,
+    UnspecifiedError,
+}
+
+impl RejectReason {
+    /// MQTT 5 reason code for PUBACK, SUBACK and DISCONNECT packets.
+    pub fn mqtt5_reason_code(self) -> u8 {
+        match self {
+            RejectReason::NotAuthorized => 0x87,
+            RejectReason::QuotaExceeded => 0x97,
+            RejectReason::PacketTooLarge => 0x95,
+            RejectReason::WildcardSubscriptionsNotSupported => 0xA2,
+            RejectReason::UnspecifiedError => 0x80,
+        }
+    }
+}
+
+/// A refused MQTT operation, returned by the hook handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookRejection {
+    pub reason: RejectReason,
+    pub message: String,
+}
+
+/// Reason code and optional reason string to send back to the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectResponse {
+    pub reason_code: u8,
+    pub reason_string: Option<String>,
+}
+
+impl HookRejection {
+    pub fn new(reason: RejectReason, message: impl Into<String>) -> Self {
+        Self {
+            reason,
+            message: message.into(),
+        }
+    }
+
+    /// Builds the response for a client speaking `protocol_level`. Only MQTT 5
+    /// clients get the specific reason code and the message as reason string.
+    pub fn to_response(&self, protocol_level: u8) -> RejectResponse {
+        if protocol_level >= MQTT_V5_PROTOCOL_LEVEL {
+            RejectResponse {
+                reason_code: self.reason.mqtt5_reason_code(),
+                reason_string: Some(self.message.clone()),
+            }
+        } else {
+            RejectResponse {
+                reason_code: MQTT_V3_FAILURE_CODE,
+                reason_string: None,
+            }
+        }
+    }
+}
+
+impl fmt::Display for HookRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for HookRejection {}
@@ -1,9 +1,40 @@
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use serde::Serialize;
 use tracing::debug;
 
-use crate::auth::TenantContext;
+use crate::auth::{AuthSource, TenantContext};
+
+/// An active client session as listed by the management API.
+///
+/// Only what identifies the session is exposed; certificate details and the
+/// client's address stay out of the listing.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub client_id: String,
+    pub tenant_id: String,
+    pub auth_source: AuthSource,
+    pub user_id: Option<String>,
+    pub device_id: Option<String>,
+    pub connection_id: String,
+    pub connected_at: DateTime<Utc>,
+}
+
+impl From<&TenantContext> for SessionInfo {
+    fn from(context: &TenantContext) -> Self {
+        Self {
+            client_id: context.client_id.clone(),
+            tenant_id: context.tenant_id.clone(),
+            auth_source: context.auth_source,
+            user_id: context.user_id.clone(),
+            device_id: context.device_id.clone(),
+            connection_id: context.connection_id.clone(),
+            connected_at: context.connected_at,
+        }
+    }
+}
 
 pub struct SessionStore {
     sessions: Arc<DashMap<String, TenantContext>>,
@@ -44,6 +75,18 @@ impl SessionStore {
         tenants.dedup();
         tenants
     }
+
+    /// Every active session, ordered by client ID.
+    pub fn list_sessions(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self
+            .sessions
+            .iter()
+            .map(|entry| SessionInfo::from(entry.value()))
+            .collect();
+
+        sessions.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+        sessions
+    }
 }
 
 impl Default for SessionStore {
@@ -1,7 +1,9 @@
+pub mod audit;
 pub mod auth;
 pub mod broker;
 pub mod config;
 pub mod hooks;
+pub mod management;
 pub mod policy;
 pub mod quota;
 pub mod transform;
@@ -1,14 +1,22 @@
 use edge_policy_bridge_mqtt::{broker::MqttBroker, config::BridgeConfig};
 use tracing::info;
-use tracing_subscriber::{fmt, EnvFilter};
+use edge_policy_telemetry::LogFormat;
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Load configuration from environment
-    let config = BridgeConfig::from_env()?;
+    // Load configuration from BRIDGE_CONFIG_FILE, if set, and the environment
+    let config = BridgeConfig::load()?;
+
+    // Validate and exit without starting the broker when only checking the configuration
+    if std::env::args().skip(1).any(|arg| arg == "--check-config") {
+        config.validate()?;
+        println!("configuration ok");
+        return Ok(());
+    }
 
     // Initialize tracing with configured log level
-    init_tracing(&config.log_level);
+    init_tracing(&config.log_level)?;
 
     info!("edge-policy-bridge-mqtt service starting");
     info!("Configuration:");
@@ -22,12 +30,18 @@ async fn main() -> anyhow::Result<()> {
     info!("  Enforcer URL: {}", config.enforcer_url);
     info!("  Use MQTT endpoints: {}", config.use_mqtt_endpoints);
     info!("  Topic namespace pattern: {}", config.topic_namespace_pattern);
+    if let Some(template) = &config.topic_template {
+        info!("  Topic template: {}", template);
+    }
     info!("  Allow wildcard subscriptions: {}", config.allow_wildcard_subscriptions);
     info!("  Max payload size: {} bytes", config.max_payload_size_bytes);
     info!("  Payload transformation enabled: {}", config.enable_payload_transformation);
     info!("  Request timeout: {} seconds", config.request_timeout_secs);
     info!("  Message limit: {} msg/day", config.message_limit);
     info!("  Bandwidth limit: {} GB/day", config.bandwidth_limit_gb);
+    if let Some(port) = config.management_port {
+        info!("  Management API port: {}", port);
+    }
 
     // Validate configuration
     config.validate()?;
@@ -43,12 +57,9 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn init_tracing(log_level: &str) {
+fn init_tracing(log_level: &str) -> anyhow::Result<()> {
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(log_level));
-    fmt()
-        .with_env_filter(filter)
-        .with_target(false)
-        .compact()
-        .init();
+    edge_policy_telemetry::try_init_tracing(filter, LogFormat::from_env()?)?;
+    Ok(())
 }
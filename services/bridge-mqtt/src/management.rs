@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use tracing::info;
+
+use crate::hooks::{HookContext, PolicyHookHandler, SessionInfo};
+
+#[derive(Clone)]
+struct ManagementState {
+    context: Arc<HookContext>,
+    hook_handler: Arc<PolicyHookHandler>,
+}
+
+/// HTTP management API served next to the MQTT listener.
+///
+/// `GET /sessions` lists connected clients with their resolved tenant, how
+/// the tenant was resolved, and when they connected. `GET /config` returns
+/// the effective configuration with secrets redacted.
+/// `POST /tenants/:tenant_id/reload` drops the tenant's cached publish
+/// decisions; it is the target for audit-store tenant change notifications.
+pub fn router(context: Arc<HookContext>) -> Router {
+    let hook_handler = Arc::new(PolicyHookHandler::new(context.clone()));
+    Router::new()
+        .route("/sessions", get(list_sessions))
+        .route("/config", get(get_config))
+        .route("/tenants/:tenant_id/reload", post(reload_tenant))
+        .with_state(ManagementState {
+            context,
+            hook_handler,
+        })
+}
+
+/// Serves [`router`] on `addr` until the listener fails.
+pub async fn serve(addr: &str, context: Arc<HookContext>) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind management API on {}", addr))?;
+    info!("Management API listening on {}", addr);
+
+    axum::serve(listener, router(context))
+        .await
+        .context("Management API server error")
+}
+
+async fn list_sessions(State(state): State<ManagementState>) -> Json<Vec<SessionInfo>> {
+    Json(state.context.session_store.list_sessions())
+}
+
+async fn get_config(State(state): State<ManagementState>) -> Json<serde_json::Value> {
+    Json(state.context.config.dump())
+}
+
+async fn reload_tenant(
+    State(state): State<ManagementState>,
+    Path(tenant_id): Path<String>,
+) -> StatusCode {
+    info!("Tenant '{}' changed, dropping its cached publish decisions", tenant_id);
+    state.hook_handler.handle_tenant_policy_reloaded(&tenant_id);
+    StatusCode::NO_CONTENT
+}
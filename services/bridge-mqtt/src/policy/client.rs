@@ -1,11 +1,13 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use edge_policy_enrichment::InputEnrichment;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use super::{MqttAbacInput, PolicyError};
+use super::{BypassReason, FailureMode, MqttAbacInput, PolicyError, RetryPolicy};
+use crate::transform::TransformDirective;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyQueryRequest {
@@ -28,8 +30,60 @@ pub struct PolicyDecision {
     pub remove_fields: Option<Vec<String>>,
     #[serde(default)]
     pub strip_coordinates: Option<bool>,
+    /// Largest publish payload the policy accepts for this request.
+    #[serde(default)]
+    pub max_publish_bytes: Option<usize>,
     #[serde(default)]
     pub reason: Option<String>,
+    /// Missing from enforcers that predate versioned decisions, which send
+    /// version 1.
+    #[serde(default)]
+    pub decision_schema_version: Option<u32>,
+    /// Set when the operation was allowed by failing open during an
+    /// enforcer outage. Never read from the enforcer.
+    #[serde(skip)]
+    pub bypass_reason: Option<BypassReason>,
+}
+
+impl PolicyDecision {
+    /// Payload transformations the decision asks for, in the order they
+    /// are applied.
+    ///
+    /// Version 1 decisions may mix the legacy `redact` list, applied as a
+    /// removal, with `redact_fields` and `remove_fields`. From version 2
+    /// the enforcer already reports legacy paths in `remove_fields`, so
+    /// `redact` is ignored.
+    pub fn transform_directives(&self) -> Vec<TransformDirective> {
+        let legacy = matches!(self.decision_schema_version, None | Some(0) | Some(1));
+        let mut directives = Vec::new();
+
+        if let Some(redact_fields) = self.redact_fields.clone().filter(|f| !f.is_empty()) {
+            debug!("Adding RedactFields directive: {:?}", redact_fields);
+            directives.push(TransformDirective::RedactFields(redact_fields));
+        }
+
+        if legacy {
+            if let Some(remove_fields) = self.redact.clone().filter(|f| !f.is_empty()) {
+                debug!(
+                    "Adding RemoveFields directive from legacy 'redact': {:?}",
+                    remove_fields
+                );
+                directives.push(TransformDirective::RemoveFields(remove_fields));
+            }
+        }
+
+        if let Some(remove_fields) = self.remove_fields.clone().filter(|f| !f.is_empty()) {
+            debug!("Adding RemoveFields directive: {:?}", remove_fields);
+            directives.push(TransformDirective::RemoveFields(remove_fields));
+        }
+
+        if self.strip_coordinates == Some(true) {
+            debug!("Adding StripCoordinates directive");
+            directives.push(TransformDirective::StripCoordinates);
+        }
+
+        directives
+    }
 }
 
 pub struct PolicyClient {
@@ -37,6 +91,9 @@ pub struct PolicyClient {
     enforcer_base_url: String,
     timeout: Duration,
     use_mqtt_endpoints: bool,
+    retry_policy: RetryPolicy,
+    failure_mode: FailureMode,
+    enrichment: InputEnrichment,
 }
 
 impl PolicyClient {
@@ -58,9 +115,31 @@ impl PolicyClient {
             enforcer_base_url: enforcer_url.trim_end_matches('/').to_string(),
             timeout: Duration::from_secs(timeout_secs),
             use_mqtt_endpoints,
+            retry_policy: RetryPolicy::default(),
+            failure_mode: FailureMode::default(),
+            enrichment: InputEnrichment::default(),
         })
     }
 
+    /// Sets how transient enforcer failures are retried. Retries never run
+    /// past the request timeout.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets whether an enforcer outage allows or denies the MQTT operation.
+    pub fn with_failure_mode(mut self, failure_mode: FailureMode) -> Self {
+        self.failure_mode = failure_mode;
+        self
+    }
+
+    /// Enriches every policy input before it is sent to the enforcer.
+    pub fn with_enrichment(mut self, enrichment: InputEnrichment) -> Self {
+        self.enrichment = enrichment;
+        self
+    }
+
     pub async fn query_publish_policy(
         &self,
         tenant_id: &str,
@@ -75,7 +154,7 @@ impl PolicyClient {
                 self.enforcer_base_url, tenant_id
             );
 
-            let result = self.query_policy(&mqtt_url, &input).await;
+            let result = self.query_policy_with_retry(&mqtt_url, &input).await;
 
             // Fallback to generic allow endpoint if MQTT-specific not found
             match result {
@@ -85,9 +164,9 @@ impl PolicyClient {
                         "{}/v1/data/tenants/{}/allow",
                         self.enforcer_base_url, tenant_id
                     );
-                    self.query_policy(&generic_url, &input).await?
+                    self.query_policy_with_retry(&generic_url, &input).await
                 }
-                other => other?,
+                other => other,
             }
         } else {
             // Directly use generic allow endpoint
@@ -95,8 +174,9 @@ impl PolicyClient {
                 "{}/v1/data/tenants/{}/allow",
                 self.enforcer_base_url, tenant_id
             );
-            self.query_policy(&generic_url, &input).await?
+            self.query_policy_with_retry(&generic_url, &input).await
         };
+        let decision = self.apply_failure_mode(tenant_id, decision)?;
 
         let latency = start.elapsed();
         debug!(
@@ -121,7 +201,7 @@ impl PolicyClient {
                 self.enforcer_base_url, tenant_id
             );
 
-            let result = self.query_policy(&mqtt_url, &input).await;
+            let result = self.query_policy_with_retry(&mqtt_url, &input).await;
 
             // Fallback to generic allow endpoint if MQTT-specific not found
             match result {
@@ -131,9 +211,9 @@ impl PolicyClient {
                         "{}/v1/data/tenants/{}/allow",
                         self.enforcer_base_url, tenant_id
                     );
-                    self.query_policy(&generic_url, &input).await?
+                    self.query_policy_with_retry(&generic_url, &input).await
                 }
-                other => other?,
+                other => other,
             }
         } else {
             // Directly use generic allow endpoint
@@ -141,8 +221,9 @@ impl PolicyClient {
                 "{}/v1/data/tenants/{}/allow",
                 self.enforcer_base_url, tenant_id
             );
-            self.query_policy(&generic_url, &input).await?
+            self.query_policy_with_retry(&generic_url, &input).await
         };
+        let decision = self.apply_failure_mode(tenant_id, decision)?;
 
         let latency = start.elapsed();
         debug!(
@@ -153,16 +234,92 @@ impl PolicyClient {
         Ok(decision)
     }
 
+    /// Queries `url`, retrying transient failures with backoff until the
+    /// retries run out or the next attempt would start past the request
+    /// timeout. Policy queries have no side effects, so repeating them is safe.
+    async fn query_policy_with_retry(
+        &self,
+        url: &str,
+        input: &MqttAbacInput,
+    ) -> Result<PolicyDecision, PolicyError> {
+        let deadline = Instant::now() + self.timeout;
+        let mut retry = 0;
+
+        loop {
+            let err = match self.query_policy(url, input).await {
+                Err(err) if err.is_transient() => err,
+                result => return result,
+            };
+
+            let backoff = self.retry_policy.backoff(retry);
+            if retry >= self.retry_policy.max_retries || Instant::now() + backoff >= deadline {
+                return Err(err);
+            }
+
+            retry += 1;
+            debug!(
+                "Transient enforcer failure for {}: {}; retry {}/{} in {:?}",
+                url, err, retry, self.retry_policy.max_retries, backoff
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    /// Turns an enforcer outage into a decision according to the failure
+    /// mode. Other errors, including denials, pass through unchanged.
+    fn apply_failure_mode(
+        &self,
+        tenant_id: &str,
+        result: Result<PolicyDecision, PolicyError>,
+    ) -> Result<PolicyDecision, PolicyError> {
+        match result {
+            Err(err) if err.is_transient() => match self.failure_mode {
+                FailureMode::Open => {
+                    warn!(
+                        "Enforcer unavailable for tenant {}, failing open: {}",
+                        tenant_id, err
+                    );
+                    let bypass_reason = match err {
+                        PolicyError::EvaluationTimeout => BypassReason::EnforcerTimeout,
+                        PolicyError::EnforcerUnreachable(_) => BypassReason::EnforcerUnreachable,
+                        _ => BypassReason::EnforcerError,
+                    };
+                    Ok(PolicyDecision {
+                        allow: true,
+                        redact: None,
+                        redact_fields: None,
+                        remove_fields: None,
+                        strip_coordinates: None,
+                        max_publish_bytes: None,
+                        reason: Some("enforcer unavailable, failing open".to_string()),
+                        decision_schema_version: None,
+                        bypass_reason: Some(bypass_reason),
+                    })
+                }
+                FailureMode::Closed => {
+                    warn!(
+                        "Enforcer unavailable for tenant {}, failing closed: {}",
+                        tenant_id, err
+                    );
+                    Err(PolicyError::Denied {
+                        reason: Some(format!("enforcer unavailable: {}", err)),
+                    })
+                }
+            },
+            other => other,
+        }
+    }
+
     async fn query_policy(
         &self,
         url: &str,
         input: &MqttAbacInput,
     ) -> Result<PolicyDecision, PolicyError> {
-        let request = PolicyQueryRequest {
-            input: serde_json::to_value(input).map_err(|e| {
-                PolicyError::InvalidResponse(format!("Failed to serialize input: {}", e))
-            })?,
-        };
+        let mut input = serde_json::to_value(input).map_err(|e| {
+            PolicyError::InvalidResponse(format!("Failed to serialize input: {}", e))
+        })?;
+        self.enrichment.apply(&mut input);
+        let request = PolicyQueryRequest { input };
 
         let response = self
             .http_client
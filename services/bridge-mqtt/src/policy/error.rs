@@ -21,6 +21,18 @@ pub enum PolicyError {
     Denied { reason: Option<String> },
 }
 
+impl PolicyError {
+    /// Whether the failure points at the enforcer being unavailable rather
+    /// than at the query, so retrying it may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PolicyError::EnforcerUnreachable(_) | PolicyError::EvaluationTimeout => true,
+            PolicyError::EnforcerError { status, .. } => (502..=504).contains(status),
+            _ => false,
+        }
+    }
+}
+
 impl From<reqwest::Error> for PolicyError {
     fn from(err: reqwest::Error) -> Self {
         if err.is_timeout() {
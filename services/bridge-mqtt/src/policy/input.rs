@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
+
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+use super::TopicTemplate;
 use crate::auth::TenantContext;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +34,9 @@ pub struct MqttResourceAttributes {
     pub qos: u8,
     pub retain: bool,
     pub owner_tenant: String,
+    /// Named topic segments from the configured [`TopicTemplate`].
+    #[serde(flatten, default)]
+    pub attributes: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +81,7 @@ impl MqttAbacInput {
                 qos,
                 retain,
                 owner_tenant,
+                attributes: BTreeMap::new(),
             },
             environment: MqttEnvironmentAttributes {
                 time: Utc::now().to_rfc3339(),
@@ -114,6 +121,7 @@ impl MqttAbacInput {
                 qos,
                 retain: false,
                 owner_tenant,
+                attributes: BTreeMap::new(),
             },
             environment: MqttEnvironmentAttributes {
                 time: Utc::now().to_rfc3339(),
@@ -123,4 +131,13 @@ impl MqttAbacInput {
             },
         }
     }
+
+    /// Adds the attributes `template` extracts from the resource topic; a
+    /// topic that does not match adds none.
+    pub fn with_topic_template(mut self, template: Option<&TopicTemplate>) -> Self {
+        if let Some(attributes) = template.and_then(|template| template.extract(&self.resource.topic)) {
+            self.resource.attributes = attributes;
+        }
+        self
+    }
 }
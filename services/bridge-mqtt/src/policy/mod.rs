@@ -1,10 +1,16 @@
 mod client;
 mod error;
 mod input;
+mod retry;
+mod topic;
 
-pub use client::PolicyClient;
+pub use client::{PolicyClient, PolicyDecision};
 pub use error::PolicyError;
 pub use input::{MqttAbacInput, MqttEnvironmentAttributes, MqttResourceAttributes, SubjectAttributes};
+pub use retry::{
+    BypassReason, FailureMode, RetryPolicy, DEFAULT_POLICY_MAX_RETRIES, DEFAULT_POLICY_RETRY_BACKOFF_MS,
+};
+pub use topic::TopicTemplate;
 
 pub const DEFAULT_ENFORCER_TIMEOUT_SECS: u64 = 5;
 pub const MQTT_PUBLISH_POLICY_PATH: &str = "/v1/data/tenants/{tenant_id}/mqtt/publish";
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_POLICY_MAX_RETRIES: u32 = 2;
+pub const DEFAULT_POLICY_RETRY_BACKOFF_MS: u64 = 100;
+
+/// How the bridge answers MQTT operations when the enforcer stays
+/// unreachable after retries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailureMode {
+    /// Allow the operation and log the outage.
+    Open,
+    /// Deny the operation.
+    #[default]
+    Closed,
+}
+
+impl fmt::Display for FailureMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureMode::Open => f.write_str("open"),
+            FailureMode::Closed => f.write_str("closed"),
+        }
+    }
+}
+
+impl FromStr for FailureMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "open" => Ok(FailureMode::Open),
+            "closed" => Ok(FailureMode::Closed),
+            other => Err(format!("unsupported enforcer failure mode: {}", other)),
+        }
+    }
+}
+
+/// Why an operation was allowed without an enforcer decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BypassReason {
+    EnforcerTimeout,
+    EnforcerUnreachable,
+    /// The enforcer answered with a gateway error status.
+    EnforcerError,
+}
+
+impl fmt::Display for BypassReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BypassReason::EnforcerTimeout => f.write_str("enforcer_timeout"),
+            BypassReason::EnforcerUnreachable => f.write_str("enforcer_unreachable"),
+            BypassReason::EnforcerError => f.write_str("enforcer_error"),
+        }
+    }
+}
+
+/// Bounded exponential backoff for policy queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each one after.
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_POLICY_MAX_RETRIES,
+            initial_backoff: Duration::from_millis(DEFAULT_POLICY_RETRY_BACKOFF_MS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, initial_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_backoff,
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 0).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.min(16)))
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// Resource fields the bridge always sets, which template attributes may
+/// not shadow.
+const RESERVED_ATTRIBUTES: [&str; 5] = ["type", "topic", "qos", "retain", "owner_tenant"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Attribute(String),
+}
+
+/// Maps topic segments to named resource attributes, e.g.
+/// `tenant/{tenant}/dev/{device_id}/{sensor_type}`.
+///
+/// A topic matches when it has as many segments as the template and every
+/// literal segment is equal. Each `{name}` segment then becomes a resource
+/// attribute holding that topic segment. Subscription filters may match too;
+/// a `+` in an attribute position leaves that attribute out, and a filter
+/// containing `#` never matches.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TopicTemplate {
+    segments: Vec<Segment>,
+}
+
+impl TopicTemplate {
+    /// Attributes named by the template, or `None` when `topic` does not
+    /// match it.
+    pub fn extract(&self, topic: &str) -> Option<BTreeMap<String, String>> {
+        let parts: Vec<&str> = topic.split('/').collect();
+        if parts.len() != self.segments.len() || parts.contains(&"#") {
+            return None;
+        }
+
+        let mut attributes = BTreeMap::new();
+        for (segment, part) in self.segments.iter().zip(parts) {
+            match segment {
+                Segment::Literal(literal) if literal != part => return None,
+                Segment::Literal(_) => {}
+                Segment::Attribute(_) if part.is_empty() || part == "+" => {}
+                Segment::Attribute(name) => {
+                    attributes.insert(name.clone(), part.to_string());
+                }
+            }
+        }
+        Some(attributes)
+    }
+}
+
+impl FromStr for TopicTemplate {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err("topic template is empty".to_string());
+        }
+
+        let mut segments = Vec::new();
+        for part in raw.split('/') {
+            let segment = match part.strip_prefix('{').and_then(|rest| rest.strip_suffix('}')) {
+                Some(name) => {
+                    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                        return Err(format!("invalid attribute name '{}' in topic template", name));
+                    }
+                    if RESERVED_ATTRIBUTES.contains(&name) {
+                        return Err(format!("attribute '{}' would replace a built-in resource field", name));
+                    }
+                    if segments.contains(&Segment::Attribute(name.to_string())) {
+                        return Err(format!("attribute '{}' appears more than once in topic template", name));
+                    }
+                    Segment::Attribute(name.to_string())
+                }
+                None => {
+                    if part.contains(['{', '}', '+', '#']) {
+                        return Err(format!("invalid topic template segment '{}'", part));
+                    }
+                    Segment::Literal(part.to_string())
+                }
+            };
+            segments.push(segment);
+        }
+
+        if !segments.iter().any(|segment| matches!(segment, Segment::Attribute(_))) {
+            return Err("topic template names no attributes".to_string());
+        }
+        Ok(Self { segments })
+    }
+}
+
+impl TryFrom<String> for TopicTemplate {
+    type Error = String;
+
+    fn try_from(raw: String) -> Result<Self, Self::Error> {
+        raw.parse()
+    }
+}
+
+impl fmt::Display for TopicTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, segment) in self.segments.iter().enumerate() {
+            if index > 0 {
+                f.write_str("/")?;
+            }
+            match segment {
+                Segment::Literal(literal) => f.write_str(literal)?,
+                Segment::Attribute(name) => write!(f, "{{{}}}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -8,8 +8,8 @@ pub enum TransformError {
     #[error("Invalid field path: {0}")]
     InvalidPath(String),
 
-    #[error("Maximum transformation depth exceeded")]
-    MaxDepthExceeded,
+    #[error("Payload nests deeper than the transformation limit of {0} levels")]
+    MaxDepthExceeded(usize),
 
     #[error("Unsupported payload format")]
     UnsupportedFormat,
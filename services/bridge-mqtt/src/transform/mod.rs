@@ -4,12 +4,16 @@ mod transformer;
 pub use error::TransformError;
 pub use transformer::PayloadTransformer;
 
-#[derive(Debug, Clone)]
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "directive", content = "fields", rename_all = "snake_case")]
 pub enum TransformDirective {
     RemoveFields(Vec<String>),
     RedactFields(Vec<String>),
     StripCoordinates,
 }
 
+/// Default nesting depth a payload may reach before transformation fails.
 pub const MAX_TRANSFORM_DEPTH: usize = 10;
 pub const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
@@ -3,11 +3,22 @@ use tracing::{debug, warn};
 
 use super::{TransformDirective, TransformError, MAX_TRANSFORM_DEPTH, REDACTED_PLACEHOLDER};
 
-pub struct PayloadTransformer;
+pub struct PayloadTransformer {
+    max_depth: usize,
+}
 
 impl PayloadTransformer {
     pub fn new() -> Self {
-        Self
+        Self {
+            max_depth: MAX_TRANSFORM_DEPTH,
+        }
+    }
+
+    /// Nesting depth past which a directive fails with
+    /// [`TransformError::MaxDepthExceeded`] instead of being applied.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
     }
 
     pub fn transform_payload(
@@ -74,8 +85,8 @@ impl PayloadTransformer {
         path: &str,
         depth: usize,
     ) -> Result<usize, TransformError> {
-        if depth > MAX_TRANSFORM_DEPTH {
-            return Err(TransformError::MaxDepthExceeded);
+        if depth > self.max_depth {
+            return Err(TransformError::MaxDepthExceeded(self.max_depth));
         }
 
         let parts: Vec<&str> = path.split('.').collect();
@@ -155,8 +166,8 @@ impl PayloadTransformer {
         path: &str,
         depth: usize,
     ) -> Result<usize, TransformError> {
-        if depth > MAX_TRANSFORM_DEPTH {
-            return Err(TransformError::MaxDepthExceeded);
+        if depth > self.max_depth {
+            return Err(TransformError::MaxDepthExceeded(self.max_depth));
         }
 
         let parts: Vec<&str> = path.split('.').collect();
@@ -228,8 +239,8 @@ impl PayloadTransformer {
     }
 
     fn strip_gps_recursive(&self, value: &mut Value, depth: usize) -> Result<usize, TransformError> {
-        if depth > MAX_TRANSFORM_DEPTH {
-            return Err(TransformError::MaxDepthExceeded);
+        if depth > self.max_depth {
+            return Err(TransformError::MaxDepthExceeded(self.max_depth));
         }
 
         let mut stripped_count = 0;
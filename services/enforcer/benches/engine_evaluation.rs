@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use edge_policy_enforcer::policy::TenantEngine;
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+const TENANT_ID: &str = "bench_tenant";
+
+const POLICY: &str = r#"
+package tenants.bench_tenant
+
+default allow := false
+
+allow if {
+  input.subject.tenant_id == input.resource.owner_tenant
+  input.subject.clearance_level >= 3
+  input.resource.region == "EU"
+  input.environment.bandwidth_used < 80
+}
+"#;
+
+fn bench_engine_evaluation(c: &mut Criterion) {
+    let engine = TenantEngine::new(
+        TENANT_ID.to_string(),
+        vec![("policy.rego".to_string(), POLICY.to_string())],
+        None,
+    )
+    .expect("bench policy compiles")
+    .with_eval_timeout(Duration::from_secs(1));
+
+    let input = json!({
+        "subject": { "tenant_id": TENANT_ID, "clearance_level": 4 },
+        "resource": { "type": "sensor_data", "owner_tenant": TENANT_ID, "region": "EU" },
+        "action": "read",
+        "environment": { "bandwidth_used": 40 }
+    });
+    let runtime = Runtime::new().expect("tokio runtime");
+
+    let mut group = c.benchmark_group("engine_evaluation");
+
+    // Report the same duration `/v1/data` puts in its evaluation metrics.
+    group.bench_function(BenchmarkId::new("evaluate_timed", "allow"), |b| {
+        b.iter_custom(|iters| {
+            let mut total = Duration::ZERO;
+            for _ in 0..iters {
+                let (decision, micros) = runtime
+                    .block_on(engine.evaluate_timed(input.clone()))
+                    .expect("policy evaluation");
+                black_box(decision);
+                total += Duration::from_micros(micros);
+            }
+            total
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(engine_evaluation, bench_engine_evaluation);
+criterion_main!(engine_evaluation);
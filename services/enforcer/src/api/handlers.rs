@@ -1,31 +1,42 @@
 use std::{sync::Arc, time::Instant};
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::Utc;
 use serde_json::{json, Value};
 use tokio::sync::broadcast;
-use tracing::{error, info, instrument};
+use tracing::{debug, error, info, instrument, Level};
 use uuid::Uuid;
 
 const MAX_SANITIZE_DEPTH: usize = 10;
 const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
+/// Iterations run by the benchmark endpoint when none are requested.
+const DEFAULT_BENCH_ITERATIONS: usize = 100;
+/// Upper bound on benchmark iterations, so one request cannot tie up an
+/// evaluation thread for long.
+const MAX_BENCH_ITERATIONS: usize = 10_000;
 
 use crate::{
-    policy::{PolicyError, PolicyManager},
+    policy::{PolicyError, PolicyManager, EXPLAIN_FEATURE, SANDBOX_TENANT_ID},
     tenant::{validate_tenant_id_format, validate_tenant_match, TenantValidationError},
 };
 
 use super::types::{
-    DecisionEvent, ErrorResponse, EvaluationMetrics, PolicyQueryRequest, PolicyQueryResponse,
+    BundlePolicySource, DecisionEvent, DecisionReplayRequest, DecisionReplayResponse,
+    DecisionReplayResult, ErrorResponse, EvaluationMetrics, PolicyBenchQuery, PolicyBenchResponse,
+    PolicyQueryParams, PolicyQueryRequest, PolicyQueryResponse, PolicyTestRequest,
+    PolicyTestResponse, PolicyTestResult, RecentDenialsResponse, ReloadAllResponse, ReplayDecision,
+    SandboxEvaluateRequest, TenantBundleResponse, TenantReloadResult,
 };
 
-#[instrument(skip(policy_manager, request), fields(tenant_id = %tenant_id))]
+#[instrument(skip(policy_manager, params, request), fields(tenant_id = %tenant_id))]
 pub async fn query_policy(
     Path(tenant_id): Path<String>,
+    Query(params): Query<PolicyQueryParams>,
     State((policy_manager, event_tx)): State<(
         Arc<PolicyManager>,
         Arc<broadcast::Sender<DecisionEvent>>,
@@ -36,21 +47,43 @@ pub async fn query_policy(
     let raw_input = request.input;
     validate_tenant_match(&tenant_id, &raw_input).map_err(|err| map_validation_error(err))?;
 
-    let eval_start = Instant::now();
-    let decision = policy_manager
-        .evaluate(&tenant_id, raw_input.clone())
+    if tracing::enabled!(Level::DEBUG) {
+        let logged_input =
+            sanitize_input(raw_input.clone(), Some(policy_manager.log_redact_paths()));
+        debug!(tenant = %tenant_id, input = %logged_input, "policy query received");
+    }
+
+    let (decision, eval_duration_micros) = policy_manager
+        .evaluate_timed(&tenant_id, raw_input.clone())
         .await
         .map_err(|err| map_policy_error(err))?;
-    let eval_duration = eval_start.elapsed();
 
     info!(
         tenant = %tenant_id,
-        elapsed_us = eval_duration.as_micros(),
+        elapsed_us = eval_duration_micros,
         "policy query handled"
     );
 
+    // Explaining re-evaluates with coverage tracking, so tenants opt in.
+    let explain = params.explain
+        && policy_manager
+            .feature_enabled(&tenant_id, EXPLAIN_FEATURE)
+            .map_err(map_policy_error)?;
+    if params.explain && !explain {
+        debug!(tenant = %tenant_id, "explain requested but not enabled for tenant");
+    }
+    let explanation = if explain {
+        let (_, coverage) = policy_manager
+            .evaluate_with_coverage(&tenant_id, vec![raw_input.clone()])
+            .await
+            .map_err(map_policy_error)?;
+        Some(coverage)
+    } else {
+        None
+    };
+
     let metrics = EvaluationMetrics {
-        eval_duration_micros: eval_duration.as_micros() as u64,
+        eval_duration_micros,
         tenant_id: tenant_id.clone(),
     };
 
@@ -65,27 +98,291 @@ pub async fn query_policy(
         metrics: metrics.clone(),
     };
 
+    if !event.decision.allow {
+        let mut denial = event.clone();
+        denial.input = sanitize_input(denial.input, Some(policy_manager.log_redact_paths()));
+        policy_manager.record_denial(denial);
+    }
+
     let _ = event_tx.send(event);
 
     Ok(Json(PolicyQueryResponse {
         result: decision,
         metrics: Some(metrics),
+        explanation,
     }))
 }
 
+/// Evaluates an ad-hoc policy once without loading it for any tenant.
+///
+/// Sandbox decisions are not published to the decision stream.
+#[instrument(skip(policy_manager, request))]
+pub async fn sandbox_evaluate(
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+    Json(request): Json<SandboxEvaluateRequest>,
+) -> Result<Json<PolicyQueryResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let eval_start = Instant::now();
+    let decision = policy_manager
+        .evaluate_sandbox(request.rego, request.entrypoint, request.input)
+        .await
+        .map_err(map_sandbox_error)?;
+    let eval_duration = eval_start.elapsed();
+
+    info!(
+        allow = decision.allow,
+        elapsed_us = eval_duration.as_micros(),
+        "sandbox evaluation handled"
+    );
+
+    Ok(Json(PolicyQueryResponse {
+        result: decision,
+        metrics: Some(EvaluationMetrics {
+            eval_duration_micros: eval_duration.as_micros() as u64,
+            tenant_id: SANDBOX_TENANT_ID.to_string(),
+        }),
+        explanation: None,
+    }))
+}
+
+/// Runs test cases against a tenant's loaded policy and reports which rule
+/// bodies they exercised.
+///
+/// Test decisions are not published to the decision stream.
+#[instrument(skip(policy_manager, request), fields(tenant_id = %tenant_id))]
+pub async fn run_policy_tests(
+    Path(tenant_id): Path<String>,
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+    Json(request): Json<PolicyTestRequest>,
+) -> Result<Json<PolicyTestResponse>, (StatusCode, Json<ErrorResponse>)> {
+    validate_tenant_id_format(&tenant_id).map_err(map_validation_error)?;
+
+    let inputs = request
+        .cases
+        .iter()
+        .map(|case| case.input.clone())
+        .collect();
+    let (decisions, coverage) = policy_manager
+        .evaluate_with_coverage(&tenant_id, inputs)
+        .await
+        .map_err(map_policy_error)?;
+
+    let results: Vec<PolicyTestResult> = request
+        .cases
+        .into_iter()
+        .zip(decisions)
+        .map(|(case, decision)| PolicyTestResult {
+            passed: decision.allow == case.expect_allow,
+            name: case.name,
+            allow: decision.allow,
+        })
+        .collect();
+    let passed = results.iter().filter(|result| result.passed).count();
+    let failed = results.len() - passed;
+
+    info!(tenant = %tenant_id, passed, failed, "policy tests handled");
+
+    Ok(Json(PolicyTestResponse {
+        tenant_id,
+        passed,
+        failed,
+        results,
+        coverage,
+    }))
+}
+
+/// Re-evaluates recorded decisions against the tenant's current policy and
+/// flags every record whose outcome changed, so a policy update can be
+/// checked against real traffic before it is relied on.
+///
+/// Replayed decisions are not published to the decision stream.
+#[instrument(skip(policy_manager, request), fields(tenant_id = %tenant_id))]
+pub async fn replay_decisions(
+    Path(tenant_id): Path<String>,
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+    Json(request): Json<DecisionReplayRequest>,
+) -> Result<Json<DecisionReplayResponse>, (StatusCode, Json<ErrorResponse>)> {
+    validate_tenant_id_format(&tenant_id).map_err(map_validation_error)?;
+
+    let mut results = Vec::with_capacity(request.records.len());
+    for record in request.records {
+        let decision = policy_manager
+            .evaluate(&tenant_id, record.input)
+            .await
+            .map_err(map_policy_error)?;
+        let current = ReplayDecision::from(decision.allow);
+        results.push(DecisionReplayResult {
+            id: record.id,
+            recorded: record.decision,
+            current,
+            regression: current != record.decision,
+            reason: decision.reason,
+        });
+    }
+
+    let newly_denied = results
+        .iter()
+        .filter(|result| result.regression && result.current == ReplayDecision::Deny)
+        .count();
+    let newly_allowed = results
+        .iter()
+        .filter(|result| result.regression && result.current == ReplayDecision::Allow)
+        .count();
+
+    info!(
+        tenant = %tenant_id,
+        replayed = results.len(),
+        newly_denied,
+        newly_allowed,
+        "decision replay handled"
+    );
+
+    Ok(Json(DecisionReplayResponse {
+        tenant_id,
+        replayed: results.len(),
+        regressions: newly_denied + newly_allowed,
+        newly_denied,
+        newly_allowed,
+        results,
+    }))
+}
+
+/// Evaluates a fixed synthetic input against the tenant's policy
+/// `iterations` times and reports how long evaluation took, so expensive
+/// policies can be spotted on constrained hardware.
+///
+/// Benchmark decisions are neither published nor recorded.
+#[instrument(skip(policy_manager, query), fields(tenant_id = %tenant_id))]
+pub async fn bench_policy(
+    Path(tenant_id): Path<String>,
+    Query(query): Query<PolicyBenchQuery>,
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+) -> Result<Json<PolicyBenchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    validate_tenant_id_format(&tenant_id).map_err(map_validation_error)?;
+
+    let iterations = query.iterations.unwrap_or(DEFAULT_BENCH_ITERATIONS);
+    if iterations == 0 || iterations > MAX_BENCH_ITERATIONS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("iterations must be between 1 and {}", MAX_BENCH_ITERATIONS),
+                code: "INVALID_ITERATIONS".to_string(),
+                details: None,
+            }),
+        ));
+    }
+
+    let input = bench_input(&tenant_id);
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let (_, micros) = policy_manager
+            .evaluate_timed(&tenant_id, input.clone())
+            .await
+            .map_err(map_policy_error)?;
+        samples.push(micros);
+    }
+    samples.sort_unstable();
+
+    let response = PolicyBenchResponse {
+        tenant_id,
+        iterations,
+        min_micros: samples[0],
+        median_micros: percentile(&samples, 50),
+        p99_micros: percentile(&samples, 99),
+        max_micros: samples[samples.len() - 1],
+    };
+    info!(
+        tenant = %response.tenant_id,
+        iterations,
+        median_micros = response.median_micros,
+        p99_micros = response.p99_micros,
+        "policy benchmark handled"
+    );
+
+    Ok(Json(response))
+}
+
+/// Request shaped like the proxy's ABAC input, fixed so runs are comparable.
+fn bench_input(tenant_id: &str) -> Value {
+    json!({
+        "subject": {
+            "tenant_id": tenant_id,
+            "user_id": "bench-user",
+            "roles": ["reader"],
+            "clearance_level": 1
+        },
+        "action": "read",
+        "resource": {
+            "type": "sensor_data",
+            "id": "bench-1",
+            "classification": "internal",
+            "region": "EU",
+            "owner_tenant": tenant_id,
+            "method": "GET",
+            "path": "/api/sensor_data/bench-1",
+            "query": {}
+        },
+        "environment": {
+            "time": "2025-01-01T00:00:00Z",
+            "country": "DE"
+        }
+    })
+}
+
+/// Nearest-rank percentile of ascending, non-empty `samples`.
+fn percentile(samples: &[u64], pct: usize) -> u64 {
+    let rank = (samples.len() * pct).div_ceil(100).max(1);
+    samples[rank - 1]
+}
+
+/// Reports service health along with the state of the bundles directory.
+///
+/// Bundles cannot be loaded or reloaded without the directory, so losing
+/// access to it is treated as critical and answered with `503`.
 #[instrument(skip(policy_manager))]
 pub async fn health_check(
     State((policy_manager, _event_tx)): State<(
         Arc<PolicyManager>,
         Arc<broadcast::Sender<DecisionEvent>>,
     )>,
-) -> Json<Value> {
+) -> (StatusCode, Json<Value>) {
     let tenant_count = policy_manager.list_tenants().len();
-    Json(json!({
-        "status": "healthy",
-        "service": "edge-policy-enforcer",
-        "tenant_count": tenant_count
-    }))
+    let bundles_dir = match policy_manager.check_bundles_dir() {
+        Ok(()) => json!({ "status": "up", "critical": true }),
+        Err(err) => {
+            error!(error = ?err, "bundles directory health check failed");
+            json!({ "status": "down", "critical": true, "error": format!("{:#}", err) })
+        }
+    };
+
+    let (status, label) = if bundles_dir["status"] == "up" {
+        (StatusCode::OK, "healthy")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unhealthy")
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": label,
+            "service": "edge-policy-enforcer",
+            "tenant_count": tenant_count,
+            "dependencies": {
+                "bundles_dir": bundles_dir
+            }
+        })),
+    )
 }
 
 #[instrument(skip(policy_manager), fields(tenant_id = %tenant_id))]
@@ -110,6 +407,155 @@ pub async fn reload_tenant(
     })))
 }
 
+/// Returns the Rego sources, version and checksum of the tenant's loaded
+/// bundle, so operators can see exactly what the enforcer is running.
+#[instrument(skip(policy_manager), fields(tenant_id = %tenant_id))]
+pub async fn tenant_bundle(
+    Path(tenant_id): Path<String>,
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+) -> Result<Json<TenantBundleResponse>, (StatusCode, Json<ErrorResponse>)> {
+    validate_tenant_id_format(&tenant_id).map_err(map_validation_error)?;
+
+    let bundle = policy_manager
+        .active_bundle(&tenant_id)
+        .map_err(map_policy_error)?;
+
+    Ok(Json(TenantBundleResponse {
+        tenant_id,
+        version: bundle.version.clone(),
+        checksum: bundle.checksum.clone(),
+        policies: bundle
+            .policies
+            .iter()
+            .map(|(path, source)| BundlePolicySource {
+                path: path.clone(),
+                source: source.clone(),
+            })
+            .collect(),
+    }))
+}
+
+/// Exports the tenant's loaded bundle as an OPA bundle tarball, so the same
+/// policies can be served by a stock OPA.
+#[instrument(skip(policy_manager), fields(tenant_id = %tenant_id))]
+pub async fn tenant_bundle_archive(
+    Path(tenant_id): Path<String>,
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    validate_tenant_id_format(&tenant_id).map_err(map_validation_error)?;
+
+    let bundle = policy_manager
+        .active_bundle(&tenant_id)
+        .map_err(map_policy_error)?;
+    let archive = bundle.to_opa_archive(&tenant_id).map_err(|err| {
+        error!(tenant = %tenant_id, error = ?err, "bundle export failed");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: "bundle export failed".to_string(),
+                code: "BUNDLE_EXPORT_ERROR".to_string(),
+                details: Some(json!({ "tenant_id": tenant_id })),
+            }),
+        )
+    })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{tenant_id}-bundle.tar.gz\""),
+            ),
+        ],
+        archive,
+    )
+        .into_response())
+}
+
+/// Lists the tenant's most recent denied queries, newest first, with inputs
+/// masked by both the decision's redact paths and the configured log redaction.
+#[instrument(skip(policy_manager), fields(tenant_id = %tenant_id))]
+pub async fn recent_denials(
+    Path(tenant_id): Path<String>,
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+) -> Result<Json<RecentDenialsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    validate_tenant_id_format(&tenant_id).map_err(map_validation_error)?;
+
+    let denials = policy_manager.recent_denials(&tenant_id);
+    Ok(Json(RecentDenialsResponse { tenant_id, denials }))
+}
+
+/// Unloads a tenant's engine; its bundle is read again on the next query.
+#[instrument(skip(policy_manager), fields(tenant_id = %tenant_id))]
+pub async fn evict_tenant(
+    Path(tenant_id): Path<String>,
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
+    validate_tenant_id_format(&tenant_id).map_err(map_validation_error)?;
+
+    let evicted = policy_manager.evict_tenant(&tenant_id);
+
+    Ok(Json(json!({
+        "status": "ok",
+        "tenant_id": tenant_id,
+        "evicted": evicted
+    })))
+}
+
+#[instrument(skip(policy_manager))]
+pub async fn reload_all_tenants(
+    State((policy_manager, _event_tx)): State<(
+        Arc<PolicyManager>,
+        Arc<broadcast::Sender<DecisionEvent>>,
+    )>,
+) -> Result<Json<ReloadAllResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let results = policy_manager.reload_all_tenants().map_err(|err| {
+        error!(error = ?err, "bulk tenant reload failed");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: "failed to enumerate tenant bundles".to_string(),
+                code: "RELOAD_ERROR".to_string(),
+                details: Some(json!({ "message": err.to_string() })),
+            }),
+        )
+    })?;
+
+    let tenants: Vec<TenantReloadResult> = results
+        .into_iter()
+        .map(|(tenant_id, result)| TenantReloadResult {
+            tenant_id,
+            success: result.is_ok(),
+            error: result.err().map(|err| {
+                let (_, Json(body)) = map_policy_error(err);
+                body
+            }),
+        })
+        .collect();
+    let reloaded = tenants.iter().filter(|tenant| tenant.success).count();
+    let failed = tenants.len() - reloaded;
+
+    info!(reloaded, failed, "bulk tenant reload completed");
+
+    Ok(Json(ReloadAllResponse {
+        reloaded,
+        failed,
+        tenants,
+    }))
+}
+
 fn map_validation_error(err: TenantValidationError) -> (StatusCode, Json<ErrorResponse>) {
     let (status, code) = match err {
         TenantValidationError::Mismatch { .. } => (StatusCode::FORBIDDEN, "TENANT_MISMATCH"),
@@ -152,6 +598,20 @@ fn map_policy_error(err: PolicyError) -> (StatusCode, Json<ErrorResponse>) {
                 }),
             )
         }
+        PolicyError::EvaluationTimeout {
+            tenant_id,
+            timeout_ms,
+        } => {
+            error!(tenant = %tenant_id, timeout_ms, "policy evaluation timed out");
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(ErrorResponse {
+                    error: format!("policy evaluation timed out after {} ms", timeout_ms),
+                    code: "EVAL_TIMEOUT".to_string(),
+                    details: Some(json!({ "tenant_id": tenant_id, "timeout_ms": timeout_ms })),
+                }),
+            )
+        }
         PolicyError::BundleLoadError { tenant_id, source } => {
             error!(
                 tenant = %tenant_id,
@@ -185,6 +645,30 @@ fn map_policy_error(err: PolicyError) -> (StatusCode, Json<ErrorResponse>) {
     }
 }
 
+/// Sandbox failures stem from the caller's policy, so they are reported as
+/// client errors with the underlying reason instead of a bare `500`.
+fn map_sandbox_error(err: PolicyError) -> (StatusCode, Json<ErrorResponse>) {
+    match err {
+        PolicyError::InvalidPolicy { reason, .. } => (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "invalid policy".to_string(),
+                code: "INVALID_POLICY".to_string(),
+                details: Some(json!({ "reason": reason })),
+            }),
+        ),
+        PolicyError::EvaluationFailed { source, .. } => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: "policy evaluation failed".to_string(),
+                code: "EVALUATION_ERROR".to_string(),
+                details: Some(json!({ "reason": format!("{:#}", source) })),
+            }),
+        ),
+        other => map_policy_error(other),
+    }
+}
+
 fn sanitize_input(mut input: Value, redact_paths: Option<&[String]>) -> Value {
     if let Some(paths) = redact_paths {
         for path in paths {
@@ -4,40 +4,120 @@ use axum::{
     body::Body,
     http::HeaderName,
     middleware::{self, Next},
-    routing::{get, post},
-    Router,
+    routing::{delete, get, post},
+    Json, Router,
 };
 use tokio::sync::broadcast;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{
+    compression::{
+        predicate::{DefaultPredicate, NotForContentType, Predicate, SizeAbove},
+        CompressionLayer,
+    },
+    cors::CorsLayer,
+    trace::TraceLayer,
+};
 use uuid::Uuid;
 
+use crate::config::EnforcerConfig;
 use crate::policy::PolicyManager;
 
 mod handlers;
 mod types;
 mod websocket;
 
-pub use handlers::{health_check, query_policy, reload_tenant};
+pub use handlers::{
+    bench_policy, evict_tenant, health_check, query_policy, recent_denials, reload_all_tenants,
+    reload_tenant, replay_decisions, run_policy_tests, sandbox_evaluate, tenant_bundle,
+    tenant_bundle_archive,
+};
 pub use types::{
-    DecisionEvent, ErrorResponse, EvaluationMetrics, PolicyDecision, PolicyQueryRequest,
-    PolicyQueryResponse, StreamFilter,
+    BundlePolicySource, DecisionEvent, DecisionReplayRecord, DecisionReplayRequest,
+    DecisionReplayResponse, DecisionReplayResult, ErrorResponse, EvaluationMetrics,
+    PolicyBenchQuery, PolicyBenchResponse, PolicyDecision, PolicyQueryParams, PolicyQueryRequest,
+    PolicyQueryResponse, PolicyTestCase, PolicyTestRequest, PolicyTestResponse, PolicyTestResult,
+    RecentDenialsResponse, ReloadAllResponse, ReplayDecision, RuleCoverage, SandboxEvaluateRequest,
+    StreamFilter, TenantBundleResponse, TenantReloadResult, DECISION_SCHEMA_VERSION,
 };
 pub use websocket::ws_decision_stream;
 
 const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
 
+/// Smallest response body gzipped unless configured otherwise.
+pub const DEFAULT_COMPRESSION_MIN_BYTES: usize = 1024;
+
 /// Builds the HTTP router and wires the decision broadcast channel used by WebSocket clients.
+///
+/// Responses of at least [`DEFAULT_COMPRESSION_MIN_BYTES`] are gzipped for
+/// clients that accept it.
 pub fn create_router(
     policy_manager: Arc<PolicyManager>,
     event_tx: Arc<broadcast::Sender<DecisionEvent>>,
 ) -> Router {
-    Router::new()
+    build_router(
+        policy_manager,
+        event_tx,
+        None,
+        Some(DEFAULT_COMPRESSION_MIN_BYTES),
+    )
+}
+
+/// Like [`create_router`], and also serves `config` at `GET /v1/config` for
+/// diagnostics. Response compression follows `config`.
+pub fn create_router_with_config(
+    policy_manager: Arc<PolicyManager>,
+    event_tx: Arc<broadcast::Sender<DecisionEvent>>,
+    config: &EnforcerConfig,
+) -> Router {
+    let compression_min_bytes = config
+        .compress_responses
+        .then_some(config.compression_min_bytes);
+    build_router(
+        policy_manager,
+        event_tx,
+        Some(config.dump()),
+        compression_min_bytes,
+    )
+}
+
+fn build_router(
+    policy_manager: Arc<PolicyManager>,
+    event_tx: Arc<broadcast::Sender<DecisionEvent>>,
+    config_dump: Option<serde_json::Value>,
+    compression_min_bytes: Option<usize>,
+) -> Router {
+    let mut router = Router::new()
         .route("/v1/data/tenants/:tenant_id/allow", post(query_policy))
         .route("/health", get(health_check))
+        .route("/v1/tenants/:tenant_id", delete(evict_tenant))
         .route("/v1/tenants/:tenant_id/reload", post(reload_tenant))
-        .route("/v1/stream/decisions", get(ws_decision_stream))
+        .route("/v1/tenants/:tenant_id/bundle", get(tenant_bundle))
+        .route(
+            "/v1/tenants/:tenant_id/bundle.tar.gz",
+            get(tenant_bundle_archive),
+        )
+        .route("/v1/tenants/:tenant_id/test", post(run_policy_tests))
+        .route("/v1/tenants/:tenant_id/recent-denials", get(recent_denials))
+        .route("/v1/tenants/:tenant_id/replay", post(replay_decisions))
+        .route("/v1/tenants/:tenant_id/bench", get(bench_policy))
+        .route("/v1/reload", post(reload_all_tenants))
+        .route("/v1/sandbox/evaluate", post(sandbox_evaluate))
+        .route("/v1/stream/decisions", get(ws_decision_stream));
+    if let Some(dump) = config_dump {
+        router = router.route("/v1/config", get(move || async move { Json(dump) }));
+    }
+
+    let mut router = router
         .with_state((policy_manager, event_tx))
-        .layer(middleware::from_fn(set_request_id))
+        .layer(middleware::from_fn(set_request_id));
+    if let Some(min_bytes) = compression_min_bytes {
+        // Gzip archives such as the bundle export are already compressed.
+        let predicate = DefaultPredicate::new()
+            .and(SizeAbove::new(u16::try_from(min_bytes).unwrap_or(u16::MAX)))
+            .and(NotForContentType::const_new("application/gzip"));
+        router = router.layer(CompressionLayer::new().compress_when(predicate));
+    }
+
+    router
         .layer(TraceLayer::new_for_http())
         .layer(CorsLayer::permissive())
 }
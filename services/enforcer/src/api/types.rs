@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -6,20 +8,164 @@ pub struct PolicyQueryRequest {
     pub input: Value,
 }
 
+/// Ad-hoc policy evaluated by the sandbox endpoint without being persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxEvaluateRequest {
+    pub rego: String,
+    /// Rule to evaluate, e.g. `data.sandbox.allow`.
+    pub entrypoint: String,
+    pub input: Value,
+}
+
+/// Test cases run against a tenant's loaded policy by the test-runner endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyTestRequest {
+    pub cases: Vec<PolicyTestCase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyTestCase {
+    pub name: String,
+    pub input: Value,
+    /// Decision the case expects from the tenant's `allow` rule.
+    pub expect_allow: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyTestResult {
+    pub name: String,
+    pub passed: bool,
+    pub allow: bool,
+}
+
+/// Whether one rule body was exercised by the test cases.
+///
+/// Coverage is tracked per line: a body counts as covered once evaluation
+/// reached every expression in it, so bodies that always fail on an earlier
+/// condition show up as uncovered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleCoverage {
+    pub rule: String,
+    pub file: String,
+    /// 1-based line of the rule head.
+    pub line: u32,
+    pub covered: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyTestResponse {
+    pub tenant_id: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub results: Vec<PolicyTestResult>,
+    pub coverage: Vec<RuleCoverage>,
+}
+
+/// Recorded decisions replayed against a tenant's current policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionReplayRequest {
+    pub records: Vec<DecisionReplayRecord>,
+}
+
+/// One historical decision, typically rebuilt from an audit-log entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionReplayRecord {
+    /// Caller's reference for the record, e.g. the audit `log_id`.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub input: Value,
+    pub decision: ReplayDecision,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayDecision {
+    Allow,
+    Deny,
+}
+
+impl From<bool> for ReplayDecision {
+    fn from(allow: bool) -> Self {
+        if allow {
+            ReplayDecision::Allow
+        } else {
+            ReplayDecision::Deny
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionReplayResult {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub recorded: ReplayDecision,
+    pub current: ReplayDecision,
+    /// The current policy decides differently from the recorded decision.
+    pub regression: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionReplayResponse {
+    pub tenant_id: String,
+    pub replayed: usize,
+    pub regressions: usize,
+    /// Recorded allows the current policy denies.
+    pub newly_denied: usize,
+    /// Recorded denials the current policy allows.
+    pub newly_allowed: usize,
+    pub results: Vec<DecisionReplayResult>,
+}
+
+/// Query string accepted by the policy query endpoint.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PolicyQueryParams {
+    /// Report which rule bodies the input reached. Honoured only for tenants
+    /// with the `explain` feature.
+    #[serde(default)]
+    pub explain: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyQueryResponse {
     pub result: PolicyDecision,
     pub metrics: Option<EvaluationMetrics>,
+    /// Every rule body in the tenant's policy, marked covered when this
+    /// input reached it. Present only for explained queries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Vec<RuleCoverage>>,
 }
 
+/// Version of the decision fields reported in [`PolicyDecision`].
+///
+/// Version 1 decisions carry a single `redact` list of paths to remove.
+/// Version 2 adds `redact_fields` (mask) and `remove_fields` (remove); a
+/// policy's legacy `redact` paths are reported in `remove_fields` as well, so
+/// version 2 consumers can ignore `redact`.
+pub const DECISION_SCHEMA_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyDecision {
     pub allow: bool,
     /// JSON path selectors used to redact sensitive input fields.
     #[serde(default)]
     pub redact: Option<Vec<String>>,
+    /// Paths whose values are masked. Version 2 and later.
+    #[serde(default)]
+    pub redact_fields: Option<Vec<String>>,
+    /// Paths removed outright. Version 2 and later.
+    #[serde(default)]
+    pub remove_fields: Option<Vec<String>>,
+    /// Paths to remove only when their flag is `true`; the policy evaluates
+    /// each condition so consumers just check the flag.
+    #[serde(default)]
+    pub redact_if: Option<BTreeMap<String, bool>>,
     #[serde(default)]
     pub reason: Option<String>,
+    /// Absent on decisions recorded before versioning, which are version 1.
+    #[serde(default)]
+    pub decision_schema_version: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +193,48 @@ pub struct DecisionEvent {
     pub metrics: EvaluationMetrics,
 }
 
+/// Query string of the policy benchmark endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyBenchQuery {
+    #[serde(default)]
+    pub iterations: Option<usize>,
+}
+
+/// Evaluation times of a tenant's policy over a fixed synthetic input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyBenchResponse {
+    pub tenant_id: String,
+    pub iterations: usize,
+    pub min_micros: u64,
+    pub median_micros: u64,
+    pub p99_micros: u64,
+    pub max_micros: u64,
+}
+
+/// The Rego a tenant's loaded engine is running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantBundleResponse {
+    pub tenant_id: String,
+    /// From the bundle's `metadata.json`, when present.
+    pub version: Option<String>,
+    pub checksum: String,
+    pub policies: Vec<BundlePolicySource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundlePolicySource {
+    /// Path relative to the tenant's bundle directory.
+    pub path: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentDenialsResponse {
+    pub tenant_id: String,
+    /// Newest first.
+    pub denials: Vec<DecisionEvent>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamFilter {
     #[serde(default)]
@@ -55,3 +243,18 @@ pub struct StreamFilter {
     #[serde(default)]
     pub decision: Option<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantReloadResult {
+    pub tenant_id: String,
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<ErrorResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadAllResponse {
+    pub reloaded: usize,
+    pub failed: usize,
+    pub tenants: Vec<TenantReloadResult>,
+}
@@ -206,7 +206,11 @@ mod tests {
             decision: PolicyDecision {
                 allow,
                 redact: None,
+                redact_fields: None,
+                remove_fields: None,
+                redact_if: None,
                 reason: None,
+                decision_schema_version: None,
             },
             input: json!({}),
             metrics: EvaluationMetrics {
@@ -1,13 +1,26 @@
 use std::{
+    collections::BTreeMap,
     env, fs,
     path::{Path, PathBuf},
 };
 
 use anyhow::{anyhow, Context, Result};
+use edge_policy_telemetry::process_env;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use tracing::info;
 
+use crate::api::DEFAULT_COMPRESSION_MIN_BYTES;
+use crate::policy::{
+    DEFAULT_MAX_BUNDLE_BYTES, DEFAULT_MAX_CONCURRENT_EVALUATIONS, DEFAULT_MAX_RULES,
+    DEFAULT_RECENT_DENIALS, MAX_EVAL_TIME_MS,
+};
+
+/// Environment variable naming a TOML file read by [`EnforcerConfig::load`].
+pub const CONFIG_FILE_ENV: &str = "ENFORCER_CONFIG_FILE";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EnforcerConfig {
     pub server_host: String,
     pub server_port: u16,
@@ -15,6 +28,43 @@ pub struct EnforcerConfig {
     pub enable_hot_reload: bool,
     pub reload_interval_secs: u64,
     pub log_level: String,
+    /// Attribute defaults merged into the policy input before evaluation,
+    /// keyed by dotted path (e.g. `environment.region`).
+    #[serde(default)]
+    pub default_attributes: BTreeMap<String, JsonValue>,
+    /// Largest combined size of a tenant bundle's policy and data files.
+    #[serde(default = "default_max_bundle_bytes")]
+    pub max_bundle_bytes: u64,
+    /// Largest number of top-level rules accepted in a tenant bundle.
+    #[serde(default = "default_max_rules")]
+    pub max_rules: usize,
+    /// Dotted input paths masked when a query is logged. Evaluation always
+    /// sees the unmodified input.
+    #[serde(default)]
+    pub log_redact_paths: Vec<String>,
+    /// Longest a single policy evaluation may run before the query fails.
+    #[serde(default = "default_eval_timeout_ms")]
+    pub eval_timeout_ms: u64,
+    /// Most policy evaluations running at once across all tenants.
+    /// Evaluations that time out hold their slot until they finish, so a
+    /// runaway policy cannot exhaust the blocking thread pool.
+    #[serde(default = "default_max_concurrent_evaluations")]
+    pub max_concurrent_evaluations: usize,
+    /// Most tenant engines kept in memory; the least recently evaluated
+    /// tenant is evicted beyond this and reloaded on demand. Unset means no
+    /// cap.
+    #[serde(default)]
+    pub max_loaded_tenants: Option<usize>,
+    /// Denied decisions kept in memory per tenant for
+    /// `GET /v1/tenants/{tenant_id}/recent-denials`; `0` disables the list.
+    #[serde(default = "default_recent_denials")]
+    pub recent_denials_per_tenant: usize,
+    /// Gzip responses for clients that send `Accept-Encoding: gzip`.
+    #[serde(default = "default_compress_responses")]
+    pub compress_responses: bool,
+    /// Responses smaller than this many bytes are sent uncompressed.
+    #[serde(default = "default_compression_min_bytes")]
+    pub compression_min_bytes: usize,
 }
 
 impl Default for EnforcerConfig {
@@ -26,6 +76,16 @@ impl Default for EnforcerConfig {
             enable_hot_reload: true,
             reload_interval_secs: 5,
             log_level: "info".to_string(),
+            default_attributes: BTreeMap::new(),
+            max_bundle_bytes: DEFAULT_MAX_BUNDLE_BYTES,
+            max_rules: DEFAULT_MAX_RULES,
+            log_redact_paths: Vec::new(),
+            eval_timeout_ms: MAX_EVAL_TIME_MS,
+            max_concurrent_evaluations: DEFAULT_MAX_CONCURRENT_EVALUATIONS,
+            max_loaded_tenants: None,
+            recent_denials_per_tenant: DEFAULT_RECENT_DENIALS,
+            compress_responses: true,
+            compression_min_bytes: DEFAULT_COMPRESSION_MIN_BYTES,
         }
     }
 }
@@ -42,22 +102,66 @@ impl EnforcerConfig {
         config_dir.join("config").join("tenants.d")
     }
 
+    /// Effective configuration as served by `GET /v1/config`. The enforcer
+    /// holds no credentials, so every field is included.
+    pub fn dump(&self) -> JsonValue {
+        serde_json::to_value(self).unwrap_or(JsonValue::Null)
+    }
+
+    /// Loads the TOML file named by `ENFORCER_CONFIG_FILE` when it is set,
+    /// otherwise the environment alone.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&process_env)
+    }
+
+    /// Like [`EnforcerConfig::load`], reading variables through `env` instead
+    /// of the process environment.
+    pub fn load_from(env: &dyn Fn(&str) -> Option<String>) -> Result<Self> {
+        match env(CONFIG_FILE_ENV) {
+            Some(path) if !path.trim().is_empty() => Self::from_file_with(path, env),
+            _ => EnforcerConfig::default().with_env_overrides(env),
+        }
+    }
+
     pub fn from_env() -> Result<Self> {
-        let mut config = EnforcerConfig::default();
+        EnforcerConfig::default().with_env_overrides(&process_env)
+    }
 
-        if let Ok(host) = env::var("ENFORCER_HOST") {
+    /// Loads a TOML file whose keys are the field names, e.g.
+    /// `server_port = 8181`. Fields the file leaves out keep their defaults,
+    /// and environment variables override both.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_file_with(path, &process_env)
+    }
+
+    fn from_file_with(
+        path: impl AsRef<Path>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file '{}'", path.display()))?;
+        let config: EnforcerConfig = toml::from_str(&raw)
+            .with_context(|| format!("failed to parse config file '{}'", path.display()))?;
+        config.with_env_overrides(env)
+    }
+
+    fn with_env_overrides(self, env: &dyn Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut config = self;
+
+        if let Some(host) = env("ENFORCER_HOST") {
             if !host.trim().is_empty() {
                 config.server_host = host;
             }
         }
 
-        if let Ok(port) = env::var("ENFORCER_PORT") {
+        if let Some(port) = env("ENFORCER_PORT") {
             config.server_port = port
                 .parse::<u16>()
                 .context("failed to parse ENFORCER_PORT as u16")?;
         }
 
-        if let Ok(dir) = env::var("BUNDLES_DIR") {
+        if let Some(dir) = env("BUNDLES_DIR") {
             if !dir.trim().is_empty() {
                 let path = PathBuf::from(&dir);
                 // Normalize to absolute path if relative
@@ -71,23 +175,81 @@ impl EnforcerConfig {
             }
         }
 
-        if let Ok(flag) = env::var("ENABLE_HOT_RELOAD") {
+        if let Some(flag) = env("ENABLE_HOT_RELOAD") {
             config.enable_hot_reload =
                 parse_bool(&flag).context("failed to parse ENABLE_HOT_RELOAD as bool")?;
         }
 
-        if let Ok(interval) = env::var("RELOAD_INTERVAL_SECS") {
+        if let Some(interval) = env("RELOAD_INTERVAL_SECS") {
             config.reload_interval_secs = interval
                 .parse::<u64>()
                 .context("failed to parse RELOAD_INTERVAL_SECS as u64")?;
         }
 
-        if let Ok(level) = env::var("LOG_LEVEL") {
+        if let Some(level) = env("LOG_LEVEL") {
             if !level.trim().is_empty() {
                 config.log_level = level;
             }
         }
 
+        if let Some(raw) = env("DEFAULT_ATTRIBUTES") {
+            if !raw.trim().is_empty() {
+                config.default_attributes =
+                    parse_default_attributes(&raw).context("failed to parse DEFAULT_ATTRIBUTES")?;
+            }
+        }
+
+        (config.max_bundle_bytes, config.max_rules) =
+            bundle_limits_with_env(config.max_bundle_bytes, config.max_rules, env)?;
+
+        if let Some(paths) = env("LOG_REDACT_PATHS") {
+            config.log_redact_paths = paths
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        if let Some(timeout) = env("EVAL_TIMEOUT_MS") {
+            config.eval_timeout_ms = timeout
+                .parse::<u64>()
+                .context("failed to parse EVAL_TIMEOUT_MS as u64")?;
+        }
+
+        if let Some(max) = env("MAX_CONCURRENT_EVALUATIONS") {
+            config.max_concurrent_evaluations = max
+                .parse::<usize>()
+                .context("failed to parse MAX_CONCURRENT_EVALUATIONS as usize")?;
+        }
+
+        if let Some(max) = env("MAX_LOADED_TENANTS") {
+            if !max.trim().is_empty() {
+                config.max_loaded_tenants = Some(
+                    max.trim()
+                        .parse::<usize>()
+                        .context("failed to parse MAX_LOADED_TENANTS as usize")?,
+                );
+            }
+        }
+
+        if let Some(count) = env("RECENT_DENIALS_PER_TENANT") {
+            config.recent_denials_per_tenant = count
+                .parse::<usize>()
+                .context("failed to parse RECENT_DENIALS_PER_TENANT as usize")?;
+        }
+
+        if let Some(flag) = env("COMPRESS_RESPONSES") {
+            config.compress_responses =
+                parse_bool(&flag).context("failed to parse COMPRESS_RESPONSES as bool")?;
+        }
+
+        if let Some(bytes) = env("COMPRESSION_MIN_BYTES") {
+            config.compression_min_bytes = bytes
+                .parse::<usize>()
+                .context("failed to parse COMPRESSION_MIN_BYTES as usize")?;
+        }
+
         config.validate()?;
 
         // Log the resolved bundles directory
@@ -99,12 +261,91 @@ impl EnforcerConfig {
         Ok(config)
     }
 
+    /// Reads `MAX_BUNDLE_BYTES` and `MAX_RULES`, falling back to the defaults.
+    pub fn bundle_limits_from_env() -> Result<(u64, usize)> {
+        bundle_limits_with_env(DEFAULT_MAX_BUNDLE_BYTES, DEFAULT_MAX_RULES, &process_env)
+    }
+
     pub fn validate(&self) -> Result<()> {
         validate_bundles_dir(&self.bundles_dir)?;
+
+        if self.max_bundle_bytes == 0 {
+            return Err(anyhow!("max_bundle_bytes must be greater than zero"));
+        }
+        if self.max_rules == 0 {
+            return Err(anyhow!("max_rules must be greater than zero"));
+        }
+        if self.eval_timeout_ms == 0 {
+            return Err(anyhow!("eval_timeout_ms must be greater than zero"));
+        }
+        if self.max_concurrent_evaluations == 0 {
+            return Err(anyhow!(
+                "max_concurrent_evaluations must be greater than zero"
+            ));
+        }
+        if self.compression_min_bytes > usize::from(u16::MAX) {
+            return Err(anyhow!(
+                "compression_min_bytes must be at most {}",
+                u16::MAX
+            ));
+        }
+        if self.max_loaded_tenants == Some(0) {
+            return Err(anyhow!("max_loaded_tenants must be greater than zero"));
+        }
+
         Ok(())
     }
 }
 
+/// `MAX_BUNDLE_BYTES` and `MAX_RULES` when set, otherwise the given limits.
+fn bundle_limits_with_env(
+    mut max_bundle_bytes: u64,
+    mut max_rules: usize,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<(u64, usize)> {
+    if let Some(bytes) = env("MAX_BUNDLE_BYTES") {
+        max_bundle_bytes = bytes
+            .parse::<u64>()
+            .context("failed to parse MAX_BUNDLE_BYTES as u64")?;
+    }
+
+    if let Some(rules) = env("MAX_RULES") {
+        max_rules = rules
+            .parse::<usize>()
+            .context("failed to parse MAX_RULES as usize")?;
+    }
+
+    Ok((max_bundle_bytes, max_rules))
+}
+
+fn default_max_bundle_bytes() -> u64 {
+    DEFAULT_MAX_BUNDLE_BYTES
+}
+
+fn default_max_rules() -> usize {
+    DEFAULT_MAX_RULES
+}
+
+fn default_eval_timeout_ms() -> u64 {
+    MAX_EVAL_TIME_MS
+}
+
+fn default_max_concurrent_evaluations() -> usize {
+    DEFAULT_MAX_CONCURRENT_EVALUATIONS
+}
+
+fn default_recent_denials() -> usize {
+    DEFAULT_RECENT_DENIALS
+}
+
+fn default_compress_responses() -> bool {
+    true
+}
+
+fn default_compression_min_bytes() -> usize {
+    DEFAULT_COMPRESSION_MIN_BYTES
+}
+
 fn parse_bool(value: &str) -> Result<bool> {
     value.parse::<bool>().or_else(|_| match value {
         "1" => Ok(true),
@@ -113,6 +354,20 @@ fn parse_bool(value: &str) -> Result<bool> {
     })
 }
 
+fn parse_default_attributes(raw: &str) -> Result<BTreeMap<String, JsonValue>> {
+    let defaults: BTreeMap<String, JsonValue> =
+        serde_json::from_str(raw).context("expected a JSON object of attribute paths")?;
+
+    if let Some(path) = defaults
+        .keys()
+        .find(|path| path.split('.').any(|segment| segment.trim().is_empty()))
+    {
+        return Err(anyhow!("invalid attribute path '{}'", path));
+    }
+
+    Ok(defaults)
+}
+
 fn validate_bundles_dir(path: &Path) -> Result<()> {
     let metadata = fs::metadata(path).with_context(|| {
         format!(
@@ -128,5 +383,8 @@ fn validate_bundles_dir(path: &Path) -> Result<()> {
         ));
     }
 
+    fs::read_dir(path)
+        .with_context(|| format!("bundles directory '{}' is not readable", path.display()))?;
+
     Ok(())
 }
@@ -1,11 +1,17 @@
 pub mod api;
 pub mod config;
 pub mod policy;
+pub mod reload;
 pub mod tenant;
 
 pub use api::{
-    create_router, ws_decision_stream, DecisionEvent, ErrorResponse, EvaluationMetrics,
-    PolicyDecision, PolicyQueryRequest, PolicyQueryResponse, StreamFilter,
+    create_router, create_router_with_config, ws_decision_stream, BundlePolicySource,
+    DecisionEvent, DecisionReplayRecord, DecisionReplayRequest, DecisionReplayResponse,
+    DecisionReplayResult, ErrorResponse, EvaluationMetrics, PolicyBenchQuery, PolicyBenchResponse,
+    PolicyDecision, PolicyQueryParams, PolicyQueryRequest, PolicyQueryResponse, PolicyTestCase,
+    PolicyTestRequest, PolicyTestResponse, PolicyTestResult, RecentDenialsResponse,
+    ReloadAllResponse, ReplayDecision, RuleCoverage, SandboxEvaluateRequest, StreamFilter,
+    TenantBundleResponse, TenantReloadResult, DECISION_SCHEMA_VERSION,
 };
 pub use policy::{PolicyError, PolicyManager};
 pub use tenant::{validate_tenant_id_format, validate_tenant_match, TenantValidationError};
@@ -3,11 +3,16 @@ use std::{
     net::SocketAddr,
     path::{Component, Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{Context, Result};
 use axum::serve;
-use edge_policy_enforcer::{config::EnforcerConfig, create_router, DecisionEvent, PolicyManager};
+use edge_policy_enforcer::{
+    config::EnforcerConfig, create_router_with_config, policy::BundleLoader,
+    reload::LogReloadHandle, DecisionEvent, PolicyManager,
+};
+use edge_policy_telemetry::{process_env, LogFormat};
 use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
 use tokio::{
     net::TcpListener,
@@ -15,16 +20,66 @@ use tokio::{
     sync::{broadcast, mpsc},
 };
 use tracing::{error, info, warn};
-use tracing_subscriber::{fmt, EnvFilter};
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let config = EnforcerConfig::from_env().context("failed to load configuration")?;
-    init_tracing(&config);
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("--validate") => {
+            let bundles_dir = args
+                .next()
+                .context("--validate requires a bundle directory")?;
+            let all_valid = validate_bundles(Path::new(&bundles_dir))?;
+            std::process::exit(if all_valid { 0 } else { 1 });
+        }
+        Some("--check-config") => {
+            let config = EnforcerConfig::load().context("invalid configuration")?;
+            println!(
+                "configuration ok (bundles_dir: {})",
+                config.bundles_dir.display()
+            );
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let config = EnforcerConfig::load().context("failed to load configuration")?;
+    let log_handle = init_tracing(&config)?;
 
     info!("edge-policy-enforcer starting");
 
-    let policy_manager = Arc::new(PolicyManager::new(config.bundles_dir.clone()));
+    #[cfg(unix)]
+    {
+        let active = config.clone();
+        tokio::spawn(async move {
+            if let Err(err) =
+                edge_policy_enforcer::reload::reload_on_sighup(active, log_handle, process_env)
+                    .await
+            {
+                error!(error = ?err, "SIGHUP reload handler stopped");
+            }
+        });
+    }
+    #[cfg(not(unix))]
+    drop(log_handle);
+
+    let policy_manager = Arc::new(
+        PolicyManager::new(config.bundles_dir.clone())
+            .with_default_attributes(config.default_attributes.clone())
+            .with_bundle_limits(config.max_bundle_bytes, config.max_rules)
+            .with_log_redact_paths(config.log_redact_paths.clone())
+            .with_eval_timeout(Duration::from_millis(config.eval_timeout_ms))
+            .with_max_concurrent_evaluations(config.max_concurrent_evaluations)
+            .with_max_loaded_tenants(config.max_loaded_tenants)
+            .with_recent_denials(config.recent_denials_per_tenant),
+    );
+    if !config.default_attributes.is_empty() {
+        info!(
+            attributes = ?config.default_attributes.keys().collect::<Vec<_>>(),
+            "default input attributes configured"
+        );
+    }
     let tenants_loaded = policy_manager
         .load_all_tenants()
         .context("failed to load tenant bundles")?;
@@ -43,7 +98,8 @@ async fn main() -> Result<()> {
         info!("hot reload watcher disabled by configuration");
     }
 
-    let router = create_router(Arc::clone(&policy_manager), Arc::clone(&event_tx));
+    let router =
+        create_router_with_config(Arc::clone(&policy_manager), Arc::clone(&event_tx), &config);
 
     let addr: SocketAddr = format!("{}:{}", config.server_host, config.server_port)
         .parse()
@@ -66,16 +122,37 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn init_tracing(config: &EnforcerConfig) {
+fn init_tracing(config: &EnforcerConfig) -> Result<LogReloadHandle> {
     let filter = EnvFilter::try_from_default_env()
         .or_else(|_| std::env::var("LOG_LEVEL").map(EnvFilter::new))
         .unwrap_or_else(|_| EnvFilter::new(config.log_level.clone()));
+    let format = LogFormat::from_env().context("invalid log format")?;
+
+    Ok(edge_policy_telemetry::init_tracing(filter, format))
+}
+
+/// Compiles every tenant bundle under `bundles_dir` and prints one line per
+/// tenant. Returns whether all bundles are valid.
+fn validate_bundles(bundles_dir: &Path) -> Result<bool> {
+    let (max_bundle_bytes, max_rules) = EnforcerConfig::bundle_limits_from_env()?;
+    let results = BundleLoader::new()
+        .with_limits(max_bundle_bytes, max_rules)
+        .validate_all(bundles_dir)?;
+
+    let total = results.len();
+    let mut failed = 0;
+    for (tenant_id, result) in results {
+        match result {
+            Ok(()) => println!("ok      {}", tenant_id),
+            Err(err) => {
+                failed += 1;
+                println!("FAILED  {}: {:#}", tenant_id, anyhow::Error::from(err));
+            }
+        }
+    }
+    println!("{} bundle(s) validated, {} failed", total - failed, failed);
 
-    fmt()
-        .with_env_filter(filter)
-        .with_target(false)
-        .compact()
-        .init();
+    Ok(failed == 0)
 }
 
 fn spawn_hot_reload_watcher(
@@ -0,0 +1,84 @@
+use regorus::coverage::Report;
+
+use crate::api::RuleCoverage;
+
+/// Top-level statements that start with an identifier but are not rule bodies.
+const NON_RULE_KEYWORDS: [&str; 3] = ["package", "import", "default"];
+
+/// Summarizes a line coverage report per rule body.
+///
+/// Regorus only records which expression lines were evaluated, so bodies are
+/// recovered from the source layout: every top-level statement starts a new
+/// span, and a body is covered when at least one of its expression lines was
+/// evaluated and none were skipped. Files the evaluation never touched are
+/// missing from the report and therefore from the summary.
+pub(crate) fn rule_coverage(report: &Report) -> Vec<RuleCoverage> {
+    let mut rules = Vec::new();
+    for file in &report.files {
+        for (rule, start, end) in rule_spans(&file.code) {
+            let reached = (start..=end).any(|line| file.covered.contains(&line));
+            let skipped = (start..=end).any(|line| file.not_covered.contains(&line));
+            rules.push(RuleCoverage {
+                rule,
+                file: file.path.clone(),
+                line: start,
+                covered: reached && !skipped,
+            });
+        }
+    }
+    rules
+}
+
+/// Returns `(rule name, first line, last line)` for each rule body, with
+/// 1-based line numbers.
+fn rule_spans(code: &str) -> Vec<(String, u32, u32)> {
+    let mut spans = Vec::new();
+    let mut current: Option<(String, u32)> = None;
+    let mut last_line = 0;
+
+    for (index, line) in code.lines().enumerate() {
+        let number = index as u32 + 1;
+        last_line = number;
+        if !starts_statement(line) {
+            continue;
+        }
+        if let Some((rule, start)) = current.take() {
+            spans.push((rule, start, number - 1));
+        }
+
+        let name: String = line
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+        if !name.is_empty() && !NON_RULE_KEYWORDS.contains(&name.as_str()) {
+            current = Some((name, number));
+        }
+    }
+
+    if let Some((rule, start)) = current {
+        spans.push((rule, start, last_line));
+    }
+    spans
+}
+
+/// Closing brackets and comments in the first column continue the previous
+/// statement; anything else unindented starts a new one.
+fn starts_statement(line: &str) -> bool {
+    line.chars()
+        .next()
+        .is_some_and(|c| !c.is_whitespace() && !matches!(c, '}' | ']' | ')' | '#'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_cover_each_rule_body() {
+        let code = "package tenants.a\n\ndefault allow := false\n\nallow if {\n    input.x\n}\n\nallow if input.y\n";
+        assert_eq!(
+            rule_spans(code),
+            vec![("allow".to_string(), 5, 8), ("allow".to_string(), 9, 9)]
+        );
+    }
+}
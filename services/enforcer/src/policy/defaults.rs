@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value as JsonValue};
+use tracing::{debug, warn};
+
+/// Merges configured default attributes into a policy input.
+///
+/// Keys are dotted attribute paths relative to the input root (for example
+/// `environment.region`). A default is only applied when the attribute is
+/// absent or `null`; values supplied by the caller always win. Missing
+/// intermediate objects are created on the way down.
+///
+/// Returns the number of defaults that were applied.
+pub fn apply_default_attributes(
+    tenant_id: &str,
+    input: &mut JsonValue,
+    defaults: &BTreeMap<String, JsonValue>,
+) -> usize {
+    if defaults.is_empty() {
+        return 0;
+    }
+
+    if input.is_null() {
+        *input = JsonValue::Object(Map::new());
+    }
+
+    let mut applied = 0usize;
+    for (path, value) in defaults {
+        if apply_default(input, path, value) {
+            debug!(
+                tenant = %tenant_id,
+                attribute = %path,
+                default = %value,
+                "applied default input attribute"
+            );
+            applied += 1;
+        }
+    }
+
+    applied
+}
+
+fn apply_default(input: &mut JsonValue, path: &str, value: &JsonValue) -> bool {
+    let segments: Vec<&str> = path.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        warn!(attribute = %path, "ignoring default attribute with empty path segment");
+        return false;
+    }
+
+    let (leaf, parents) = match segments.split_last() {
+        Some(split) => split,
+        None => return false,
+    };
+
+    let mut current = input;
+    for segment in parents {
+        let map = match current {
+            JsonValue::Object(map) => map,
+            _ => return false,
+        };
+
+        let entry = map
+            .entry(segment.to_string())
+            .or_insert_with(|| JsonValue::Object(Map::new()));
+        if entry.is_null() {
+            *entry = JsonValue::Object(Map::new());
+        }
+        current = entry;
+    }
+
+    let map = match current {
+        JsonValue::Object(map) => map,
+        _ => return false,
+    };
+
+    match map.get(*leaf) {
+        Some(existing) if !existing.is_null() => false,
+        _ => {
+            map.insert(leaf.to_string(), value.clone());
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn defaults(entries: &[(&str, JsonValue)]) -> BTreeMap<String, JsonValue> {
+        entries
+            .iter()
+            .map(|(path, value)| (path.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn fills_missing_nested_attribute() {
+        let mut input = json!({"subject": {"tenant_id": "tenant_a"}});
+        let applied = apply_default_attributes(
+            "tenant_a",
+            &mut input,
+            &defaults(&[("environment.region", json!("EU"))]),
+        );
+
+        assert_eq!(applied, 1);
+        assert_eq!(input["environment"]["region"], json!("EU"));
+    }
+
+    #[test]
+    fn keeps_caller_supplied_values() {
+        let mut input = json!({"environment": {"region": "US"}});
+        let applied = apply_default_attributes(
+            "tenant_a",
+            &mut input,
+            &defaults(&[("environment.region", json!("EU"))]),
+        );
+
+        assert_eq!(applied, 0);
+        assert_eq!(input["environment"]["region"], json!("US"));
+    }
+
+    #[test]
+    fn replaces_null_values() {
+        let mut input = json!({"environment": {"region": null}});
+        apply_default_attributes(
+            "tenant_a",
+            &mut input,
+            &defaults(&[("environment.region", json!("EU"))]),
+        );
+
+        assert_eq!(input["environment"]["region"], json!("EU"));
+    }
+
+    #[test]
+    fn skips_when_parent_is_not_an_object() {
+        let mut input = json!({"environment": "edge"});
+        let applied = apply_default_attributes(
+            "tenant_a",
+            &mut input,
+            &defaults(&[("environment.region", json!("EU"))]),
+        );
+
+        assert_eq!(applied, 0);
+        assert_eq!(input["environment"], json!("edge"));
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use super::TenantId;
+use crate::api::DecisionEvent;
+
+/// Denials kept per tenant unless configured otherwise.
+pub const DEFAULT_RECENT_DENIALS: usize = 50;
+
+/// Ring buffer of the most recent denied decisions for each tenant, so
+/// operators can look up why a request was refused after the fact.
+///
+/// Each tenant keeps at most `capacity` entries; older ones are dropped as new
+/// denials arrive. A capacity of `0` records nothing.
+pub struct RecentDenials {
+    capacity: usize,
+    by_tenant: Mutex<HashMap<TenantId, VecDeque<DecisionEvent>>>,
+}
+
+impl RecentDenials {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            by_tenant: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stores a denied decision. Callers redact the input beforehand.
+    pub fn record(&self, event: DecisionEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        let Ok(mut by_tenant) = self.by_tenant.lock() else {
+            return;
+        };
+        let denials = by_tenant.entry(event.tenant_id.clone()).or_default();
+        if denials.len() == self.capacity {
+            denials.pop_front();
+        }
+        denials.push_back(event);
+    }
+
+    /// The tenant's recorded denials, newest first.
+    pub fn list(&self, tenant_id: &str) -> Vec<DecisionEvent> {
+        self.by_tenant
+            .lock()
+            .ok()
+            .and_then(|by_tenant| {
+                by_tenant
+                    .get(tenant_id)
+                    .map(|denials| denials.iter().rev().cloned().collect())
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Default for RecentDenials {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECENT_DENIALS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::{EvaluationMetrics, PolicyDecision};
+
+    fn denial(tenant_id: &str, event_id: &str) -> DecisionEvent {
+        DecisionEvent {
+            event_id: event_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            decision: PolicyDecision {
+                allow: false,
+                redact: None,
+                redact_fields: None,
+                remove_fields: None,
+                redact_if: None,
+                reason: None,
+                decision_schema_version: None,
+            },
+            input: json!({}),
+            metrics: EvaluationMetrics {
+                eval_duration_micros: 1,
+                tenant_id: tenant_id.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn oldest_denials_are_dropped_at_capacity() {
+        let denials = RecentDenials::new(2);
+        denials.record(denial("tenant-a", "1"));
+        denials.record(denial("tenant-a", "2"));
+        denials.record(denial("tenant-a", "3"));
+        denials.record(denial("tenant-b", "4"));
+
+        let ids: Vec<String> = denials
+            .list("tenant-a")
+            .into_iter()
+            .map(|event| event.event_id)
+            .collect();
+        assert_eq!(ids, vec!["3", "2"]);
+        assert_eq!(denials.list("tenant-b").len(), 1);
+        assert!(denials.list("tenant-c").is_empty());
+    }
+}
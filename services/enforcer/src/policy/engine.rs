@@ -1,14 +1,21 @@
-use std::time::Duration;
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use anyhow::{anyhow, Context};
+use anyhow::Context;
 use regorus::{Engine as RegoEngine, Value as RegoValue};
 use serde_json::{json, Value as JsonValue};
-use tokio::{task::spawn_blocking, time::timeout};
+use tokio::{sync::Semaphore, task::spawn_blocking, time::timeout};
 use tracing::{debug, instrument};
 
 use crate::{
-    api::PolicyDecision,
-    policy::{PolicyError, DEFAULT_ENTRYPOINT_TEMPLATE, MAX_EVAL_TIME_MS},
+    api::{PolicyDecision, RuleCoverage, DECISION_SCHEMA_VERSION},
+    policy::{
+        coverage::rule_coverage, helpers::shared_helpers, PolicyError, DEFAULT_ENTRYPOINT_TEMPLATE,
+        DEFAULT_MAX_CONCURRENT_EVALUATIONS, MAX_EVAL_TIME_MS,
+    },
 };
 
 #[derive(Clone)]
@@ -16,6 +23,8 @@ pub struct TenantEngine {
     engine: RegoEngine,
     tenant_id: String,
     entrypoint: String,
+    eval_timeout: Duration,
+    eval_slots: Arc<Semaphore>,
 }
 
 impl TenantEngine {
@@ -26,7 +35,8 @@ impl TenantEngine {
     ) -> Result<Self, PolicyError> {
         let mut engine = RegoEngine::default();
 
-        for (filename, content) in policies {
+        let helpers = shared_helpers(&policies);
+        for (filename, content) in policies.into_iter().chain(helpers) {
             let policy_name = filename.clone();
             engine
                 .add_policy(filename, content)
@@ -64,9 +74,40 @@ impl TenantEngine {
             engine,
             tenant_id,
             entrypoint,
+            eval_timeout: Duration::from_millis(MAX_EVAL_TIME_MS),
+            eval_slots: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_EVALUATIONS)),
         })
     }
 
+    /// Evaluates `entrypoint` instead of the tenant's default `allow` rule.
+    pub fn with_entrypoint(mut self, entrypoint: impl Into<String>) -> Self {
+        self.entrypoint = entrypoint.into();
+        self
+    }
+
+    /// Sets how long a single evaluation may run before it is abandoned.
+    pub fn with_eval_timeout(mut self, eval_timeout: Duration) -> Self {
+        self.eval_timeout = eval_timeout;
+        self
+    }
+
+    /// Shares `eval_slots` with other engines so they draw on one limit of
+    /// evaluations running at once.
+    pub fn with_eval_slots(mut self, eval_slots: Arc<Semaphore>) -> Self {
+        self.eval_slots = eval_slots;
+        self
+    }
+
+    /// Evaluates `input` against the entrypoint within the evaluation timeout.
+    ///
+    /// Each evaluation holds one of the engine's evaluation slots until it
+    /// finishes, and waiting for a free slot counts against the timeout. On
+    /// timeout the caller gets [`PolicyError::EvaluationTimeout`] straight
+    /// away; the blocking evaluation cannot be interrupted and finishes on the
+    /// blocking pool with its result discarded, still holding its slot. A
+    /// policy that keeps timing out therefore ties up at most as many
+    /// blocking threads as there are slots, and further queries time out
+    /// waiting instead of piling more work onto the pool.
     #[instrument(skip(self, input), fields(tenant_id = %self.tenant_id))]
     pub async fn evaluate(&self, input: JsonValue) -> Result<PolicyDecision, PolicyError> {
         let mut engine = self.engine.clone();
@@ -88,15 +129,21 @@ impl TenantEngine {
         let tenant_id = self.tenant_id.clone();
         let entrypoint = self.entrypoint.clone();
 
-        let result = match timeout(
-            Duration::from_millis(MAX_EVAL_TIME_MS),
+        let slots = Arc::clone(&self.eval_slots);
+        let evaluation = async move {
+            let slot = slots
+                .acquire_owned()
+                .await
+                .expect("evaluation slots are never closed");
             spawn_blocking(move || {
+                let _slot = slot;
                 let mut engine = engine;
                 engine.eval_rule(entrypoint)
-            }),
-        )
-        .await
-        {
+            })
+            .await
+        };
+
+        let result = match timeout(self.eval_timeout, evaluation).await {
             Ok(Ok(Ok(value))) => value,
             Ok(Ok(Err(err))) => {
                 return Err(PolicyError::EvaluationFailed {
@@ -111,10 +158,9 @@ impl TenantEngine {
                 })
             }
             Err(_) => {
-                return Err(PolicyError::EvaluationFailed {
+                return Err(PolicyError::EvaluationTimeout {
                     tenant_id,
-                    source: anyhow!("policy evaluation timed out after {} ms", MAX_EVAL_TIME_MS)
-                        .into(),
+                    timeout_ms: self.eval_timeout.as_millis() as u64,
                 })
             }
         };
@@ -130,6 +176,70 @@ impl TenantEngine {
         Ok(decision)
     }
 
+    /// Like [`TenantEngine::evaluate`], also returning how long evaluation
+    /// took in microseconds. Query metrics and benchmarks both measure
+    /// through this so they report the same span.
+    pub async fn evaluate_timed(
+        &self,
+        input: JsonValue,
+    ) -> Result<(PolicyDecision, u64), PolicyError> {
+        let started = Instant::now();
+        let decision = self.evaluate(input).await?;
+        Ok((decision, started.elapsed().as_micros() as u64))
+    }
+
+    /// Evaluates each input in turn with coverage tracking enabled, returning
+    /// the decisions in order and which rule bodies they exercised.
+    ///
+    /// The whole run takes one evaluation slot and gets one evaluation
+    /// timeout per input.
+    #[instrument(skip(self, inputs), fields(tenant_id = %self.tenant_id))]
+    pub async fn evaluate_with_coverage(
+        &self,
+        inputs: Vec<JsonValue>,
+    ) -> Result<(Vec<PolicyDecision>, Vec<RuleCoverage>), PolicyError> {
+        let mut engine = self.engine.clone();
+        let entrypoint = self.entrypoint.clone();
+        let tenant_id = self.tenant_id.clone();
+        let budget = self.eval_timeout * inputs.len().max(1) as u32;
+
+        let slots = Arc::clone(&self.eval_slots);
+        let run = async move {
+            let slot = slots
+                .acquire_owned()
+                .await
+                .expect("evaluation slots are never closed");
+            spawn_blocking(move || -> anyhow::Result<_> {
+                let _slot = slot;
+                engine.set_enable_coverage(true);
+                let mut decisions = Vec::with_capacity(inputs.len());
+                for input in inputs {
+                    engine.set_input_json(&serde_json::to_string(&input)?)?;
+                    decisions.push(parse_decision(engine.eval_rule(entrypoint.clone())?));
+                }
+                let report = engine.get_coverage_report()?;
+                Ok((decisions, rule_coverage(&report)))
+            })
+            .await
+        };
+
+        match timeout(budget, run).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(err))) => Err(PolicyError::EvaluationFailed {
+                tenant_id,
+                source: err,
+            }),
+            Ok(Err(join_err)) => Err(PolicyError::EvaluationFailed {
+                tenant_id,
+                source: join_err.into(),
+            }),
+            Err(_) => Err(PolicyError::EvaluationTimeout {
+                tenant_id,
+                timeout_ms: budget.as_millis() as u64,
+            }),
+        }
+    }
+
     pub fn verify_entrypoint(&self) -> Result<(), PolicyError> {
         let mut engine = self.engine.clone();
         let tenant_id = self.tenant_id.clone();
@@ -162,24 +272,19 @@ impl TenantEngine {
 
 fn parse_decision(result: RegoValue) -> PolicyDecision {
     match serde_json::to_value(&result) {
-        Ok(JsonValue::Bool(allow)) => PolicyDecision {
-            allow,
-            redact: None,
-            reason: None,
-        },
+        Ok(JsonValue::Bool(allow)) => decision(allow, None),
         Ok(JsonValue::Object(map)) => {
             let allow = map.get("allow").and_then(|v| v.as_bool()).unwrap_or(false);
+            let redact = path_list(&map, "redact");
 
-            let redact = map
-                .get("redact")
-                .and_then(|v| v.as_array())
-                .map(|items| {
-                    items
-                        .iter()
-                        .filter_map(|item| item.as_str().map(|s| s.to_string()))
-                        .collect::<Vec<String>>()
-                })
-                .filter(|items| !items.is_empty());
+            // Legacy `redact` paths were always removed, so version 2
+            // consumers get them in `remove_fields`.
+            let mut remove_fields = path_list(&map, "remove_fields").unwrap_or_default();
+            for path in redact.iter().flatten() {
+                if !remove_fields.contains(path) {
+                    remove_fields.push(path.clone());
+                }
+            }
 
             let reason = map
                 .get("reason")
@@ -187,15 +292,191 @@ fn parse_decision(result: RegoValue) -> PolicyDecision {
                 .map(|s| s.to_string());
 
             PolicyDecision {
-                allow,
                 redact,
-                reason,
+                redact_fields: path_list(&map, "redact_fields"),
+                remove_fields: (!remove_fields.is_empty()).then_some(remove_fields),
+                redact_if: path_flags(&map, "redact_if"),
+                ..decision(allow, reason)
             }
         }
-        _ => PolicyDecision {
-            allow: false,
-            redact: None,
-            reason: Some("policy returned undefined result".to_string()),
-        },
+        _ => decision(false, Some("policy returned undefined result".to_string())),
+    }
+}
+
+fn decision(allow: bool, reason: Option<String>) -> PolicyDecision {
+    PolicyDecision {
+        allow,
+        redact: None,
+        redact_fields: None,
+        remove_fields: None,
+        redact_if: None,
+        reason,
+        decision_schema_version: Some(DECISION_SCHEMA_VERSION),
+    }
+}
+
+/// Non-empty list of the string entries under `key`.
+fn path_list(map: &serde_json::Map<String, JsonValue>, key: &str) -> Option<Vec<String>> {
+    map.get(key)
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                .collect::<Vec<String>>()
+        })
+        .filter(|items| !items.is_empty())
+}
+
+/// Non-empty map of the boolean entries under `key`.
+fn path_flags(
+    map: &serde_json::Map<String, JsonValue>,
+    key: &str,
+) -> Option<BTreeMap<String, bool>> {
+    map.get(key)
+        .and_then(|v| v.as_object())
+        .map(|flags| {
+            flags
+                .iter()
+                .filter_map(|(path, flag)| flag.as_bool().map(|flag| (path.clone(), flag)))
+                .collect::<BTreeMap<String, bool>>()
+        })
+        .filter(|flags| !flags.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn evaluate_timed_reports_elapsed_micros() {
+        let policy = r#"
+package tenants.timed
+
+default allow := false
+
+allow if {
+  count([x | some x in numbers.range(1, 2000); x % 7 == 0]) > 0
+}
+"#;
+        let engine = TenantEngine::new(
+            "timed".to_string(),
+            vec![("policy.rego".to_string(), policy.to_string())],
+            None,
+        )
+        .unwrap()
+        .with_eval_timeout(Duration::from_secs(5));
+
+        let started = Instant::now();
+        let (decision, micros) = engine.evaluate_timed(json!({})).await.unwrap();
+        let wall_micros = started.elapsed().as_micros() as u64;
+
+        assert!(decision.allow);
+        assert!(micros > 0);
+        assert!(micros <= wall_micros);
+    }
+
+    const SLOW_POLICY: &str = r#"
+package tenants.slow
+
+default allow := false
+
+allow if {
+  count([x | some a in numbers.range(1, 300); some b in numbers.range(1, 300); x := a * b]) > 0
+}
+"#;
+
+    fn slow_engine(eval_slots: Arc<Semaphore>) -> TenantEngine {
+        TenantEngine::new(
+            "slow".to_string(),
+            vec![("policy.rego".to_string(), SLOW_POLICY.to_string())],
+            None,
+        )
+        .unwrap()
+        .with_eval_timeout(Duration::from_millis(1))
+        .with_eval_slots(eval_slots)
+    }
+
+    #[tokio::test]
+    async fn timed_out_evaluations_keep_their_slot_until_they_finish() {
+        let slots = Arc::new(Semaphore::new(2));
+        let engine = slow_engine(Arc::clone(&slots));
+
+        // Far more queries than slots: each times out, but only two
+        // evaluations are ever started on the blocking pool.
+        let results =
+            futures_util::future::join_all((0..16).map(|_| engine.evaluate(json!({})))).await;
+        for result in results {
+            assert!(matches!(result, Err(PolicyError::EvaluationTimeout { .. })));
+        }
+        assert!(slots.available_permits() < 2);
+
+        // Once the abandoned evaluations finish their slots are free again.
+        let released = timeout(Duration::from_secs(30), slots.acquire_many(2)).await;
+        assert!(released.is_ok());
+    }
+
+    #[tokio::test]
+    async fn waiting_for_a_slot_counts_against_the_timeout() {
+        let slots = Arc::new(Semaphore::new(1));
+        let engine = TenantEngine::new(
+            "timed".to_string(),
+            vec![(
+                "policy.rego".to_string(),
+                "package tenants.timed\n\nallow := true\n".to_string(),
+            )],
+            None,
+        )
+        .unwrap()
+        .with_eval_timeout(Duration::from_millis(20))
+        .with_eval_slots(Arc::clone(&slots));
+
+        let held = slots.acquire().await.unwrap();
+        assert!(matches!(
+            engine.evaluate(json!({})).await,
+            Err(PolicyError::EvaluationTimeout { .. })
+        ));
+
+        drop(held);
+        assert!(engine.evaluate(json!({})).await.unwrap().allow);
+    }
+
+    #[test]
+    fn legacy_redact_is_reported_as_removal_in_v2() {
+        let result = RegoValue::from_json_str(
+            r#"{"allow": true, "redact": ["pii.email"], "remove_fields": ["gps"], "redact_fields": ["name"]}"#,
+        )
+        .unwrap();
+
+        let decision = parse_decision(result);
+
+        assert_eq!(
+            decision.decision_schema_version,
+            Some(DECISION_SCHEMA_VERSION)
+        );
+        assert_eq!(decision.redact, Some(vec!["pii.email".to_string()]));
+        assert_eq!(decision.redact_fields, Some(vec!["name".to_string()]));
+        assert_eq!(
+            decision.remove_fields,
+            Some(vec!["gps".to_string(), "pii.email".to_string()])
+        );
+    }
+
+    #[test]
+    fn redact_if_keeps_only_boolean_flags() {
+        let result = RegoValue::from_json_str(
+            r#"{"allow": true, "redact_if": {"pii.email": true, "pii.phone": false, "gps": "yes"}}"#,
+        )
+        .unwrap();
+
+        let decision = parse_decision(result);
+
+        assert_eq!(
+            decision.redact_if,
+            Some(BTreeMap::from([
+                ("pii.email".to_string(), true),
+                ("pii.phone".to_string(), false),
+            ]))
+        );
     }
 }
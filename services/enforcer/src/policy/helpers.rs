@@ -0,0 +1,70 @@
+use edge_policy_rego_bundles::{list_helpers, load_helper};
+
+/// The shared `lib/` helper modules a bundle's policies import, as
+/// `(path, source)` pairs sorted by path.
+///
+/// A helper is included when some policy references `data.lib.<name>` and no
+/// policy in the bundle declares `package lib.<name>` itself, so a bundle
+/// that ships its own copy of a helper keeps using it.
+pub(crate) fn shared_helpers(policies: &[(String, String)]) -> Vec<(String, String)> {
+    list_helpers()
+        .into_iter()
+        .filter(|name| {
+            let reference = format!("data.lib.{name}");
+            let package = format!("package lib.{name}");
+            policies
+                .iter()
+                .any(|(_, source)| references(source, &reference))
+                && !policies
+                    .iter()
+                    .any(|(_, source)| source.lines().any(|line| line.trim() == package))
+        })
+        .filter_map(|name| {
+            let source = load_helper(name)?;
+            Some((format!("lib/{name}.rego"), source.to_string()))
+        })
+        .collect()
+}
+
+/// Whether `source` mentions `reference` as a whole path, so `data.lib.geo`
+/// is not found in `data.lib.geography`.
+fn references(source: &str, reference: &str) -> bool {
+    source.match_indices(reference).any(|(index, _)| {
+        source[index + reference.len()..]
+            .chars()
+            .next()
+            .is_none_or(|next| !(next.is_ascii_alphanumeric() || next == '_'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(source: &str) -> Vec<(String, String)> {
+        vec![("policy.rego".to_string(), source.to_string())]
+    }
+
+    #[test]
+    fn includes_only_imported_helpers() {
+        let helpers = shared_helpers(&policy(
+            "package tenants.tenant_a\n\nimport data.lib.geo\nimport data.lib.geography\n",
+        ));
+        let paths: Vec<_> = helpers.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["lib/geo.rego"]);
+        assert!(helpers[0].1.contains("package lib.geo"));
+
+        assert!(shared_helpers(&policy("package tenants.tenant_a\n")).is_empty());
+    }
+
+    #[test]
+    fn bundled_copy_of_a_helper_wins() {
+        let mut policies = policy("package tenants.tenant_a\n\nimport data.lib.geo\n");
+        policies.push((
+            "vendor/geo.rego".to_string(),
+            "package lib.geo\n\nallowed(_, _) := true\n".to_string(),
+        ));
+
+        assert!(shared_helpers(&policies).is_empty());
+    }
+}
@@ -1,23 +1,60 @@
 use std::{
+    collections::BTreeMap,
     fs,
+    io::{self, Write},
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
+use flate2::{write::GzEncoder, Compression};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
 use tracing::debug;
 
-use super::PolicyError;
+use super::{helpers::shared_helpers, PolicyError, TenantEngine, TenantId};
+
+/// Default upper bound on the combined size of a bundle's policy and data files.
+pub const DEFAULT_MAX_BUNDLE_BYTES: u64 = 8 * 1024 * 1024;
+/// Default upper bound on the number of top-level rules across a bundle.
+pub const DEFAULT_MAX_RULES: usize = 10_000;
+/// Subdirectory of a tenant bundle holding a canary bundle that serves
+/// `rollout_percent` of the tenant's subjects.
+pub const CANARY_DIR: &str = "canary";
 
 #[derive(Debug, Clone)]
-pub struct BundleLoader;
+pub struct BundleLoader {
+    max_bundle_bytes: u64,
+    max_rules: usize,
+}
+
+impl Default for BundleLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl BundleLoader {
     pub fn new() -> Self {
-        Self
+        Self {
+            max_bundle_bytes: DEFAULT_MAX_BUNDLE_BYTES,
+            max_rules: DEFAULT_MAX_RULES,
+        }
     }
 
+    /// Sets the size and rule-count limits checked before a bundle is compiled.
+    pub fn with_limits(mut self, max_bundle_bytes: u64, max_rules: usize) -> Self {
+        self.max_bundle_bytes = max_bundle_bytes;
+        self.max_rules = max_rules;
+        self
+    }
+
+    /// Loads the bundle at `bundle_path`.
+    ///
+    /// Bundles whose files exceed `max_bundle_bytes` in total, or whose policies
+    /// declare more than `max_rules` rules, are rejected with
+    /// [`PolicyError::InvalidPolicy`]. File sizes are checked before contents are
+    /// read so an oversized bundle is never pulled into memory.
     pub fn load_bundle(&self, bundle_path: &Path) -> Result<PolicyBundle> {
         let metadata = fs::metadata(bundle_path).with_context(|| {
             format!(
@@ -26,13 +63,11 @@ impl BundleLoader {
             )
         })?;
 
+        let tenant_id = bundle_tenant_id(bundle_path);
+
         if !metadata.is_dir() {
             return Err(PolicyError::BundleLoadError {
-                tenant_id: bundle_path
-                    .file_name()
-                    .and_then(|name| name.to_str())
-                    .unwrap_or_default()
-                    .to_string(),
+                tenant_id,
                 source: anyhow::anyhow!(
                     "bundle path '{}' is not a directory",
                     bundle_path.display()
@@ -41,11 +76,24 @@ impl BundleLoader {
             .into());
         }
 
+        let data_path = bundle_path.join("data.json");
+        let metadata_path = bundle_path.join("metadata.json");
+        let mut total_bytes = optional_file_len(&data_path)? + optional_file_len(&metadata_path)?;
+        self.check_bundle_bytes(&tenant_id, total_bytes)?;
+
         let mut policies = Vec::new();
-        collect_rego_files(bundle_path, bundle_path, &mut policies)?;
+        self.collect_rego_files(
+            &tenant_id,
+            bundle_path,
+            bundle_path,
+            &mut policies,
+            &mut total_bytes,
+        )?;
+
+        let rule_count = self.check_rule_count(&tenant_id, &policies)?;
 
-        let data = load_optional_json(bundle_path.join("data.json"))?;
-        let metadata = load_optional_json(bundle_path.join("metadata.json"))?;
+        let data = load_optional_json(data_path)?;
+        let metadata = load_optional_json(metadata_path)?;
 
         let metadata = match metadata {
             Some(json) => Some(serde_json::from_value(json).context("invalid metadata.json")?),
@@ -55,6 +103,8 @@ impl BundleLoader {
         debug!(
             tenant_bundle = %bundle_path.display(),
             policies = policies.len(),
+            rules = rule_count,
+            bytes = total_bytes,
             has_data = data.is_some(),
             has_metadata = metadata.is_some(),
             "loaded tenant policy bundle"
@@ -66,39 +116,238 @@ impl BundleLoader {
             metadata,
         })
     }
-}
 
-fn collect_rego_files(
-    directory: &Path,
-    root: &Path,
-    policies: &mut Vec<(String, String)>,
-) -> Result<()> {
-    for entry in fs::read_dir(directory)
-        .with_context(|| format!("failed to read directory '{}'", directory.display()))?
-    {
-        let entry = entry?;
-        let path = entry.path();
+    /// Loads the canary bundle under `bundle_path/canary`, if there is one,
+    /// together with the `rollout_percent` from its `metadata.json`.
+    ///
+    /// The canary is a complete bundle held to the same limits as the stable
+    /// one; a canary without a `rollout_percent` between 1 and 100 is
+    /// rejected with [`PolicyError::InvalidPolicy`].
+    pub fn load_canary(&self, bundle_path: &Path) -> Result<Option<(PolicyBundle, u8)>> {
+        let canary_path = bundle_path.join(CANARY_DIR);
+        if !canary_path.is_dir() {
+            return Ok(None);
+        }
 
-        if entry.file_type()?.is_dir() {
-            collect_rego_files(&path, root, policies)?;
-            continue;
+        let bundle = self.load_bundle(&canary_path)?;
+        let rollout_percent = bundle
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.rollout_percent)
+            .filter(|percent| (1..=100).contains(percent))
+            .ok_or_else(|| PolicyError::InvalidPolicy {
+                tenant_id: bundle_tenant_id(&canary_path),
+                reason: "canary metadata.json must set rollout_percent between 1 and 100"
+                    .to_string(),
+            })?;
+
+        Ok(Some((bundle, rollout_percent)))
+    }
+
+    /// Applies the bundle limits to policy sources that did not come from disk.
+    pub fn check_sources(
+        &self,
+        tenant_id: &str,
+        policies: &[(String, String)],
+    ) -> Result<(), PolicyError> {
+        let total_bytes = policies.iter().map(|(_, source)| source.len() as u64).sum();
+        self.check_bundle_bytes(tenant_id, total_bytes)?;
+        self.check_rule_count(tenant_id, policies)?;
+        Ok(())
+    }
+
+    fn check_rule_count(
+        &self,
+        tenant_id: &str,
+        policies: &[(String, String)],
+    ) -> Result<usize, PolicyError> {
+        let rule_count: usize = policies.iter().map(|(_, source)| count_rules(source)).sum();
+        if rule_count > self.max_rules {
+            return Err(PolicyError::InvalidPolicy {
+                tenant_id: tenant_id.to_string(),
+                reason: format!(
+                    "bundle declares {} rules, exceeding the limit of {}",
+                    rule_count, self.max_rules
+                ),
+            });
         }
 
-        if path.extension().and_then(|ext| ext.to_str()) != Some("rego") {
+        Ok(rule_count)
+    }
+
+    /// Loads and compiles the bundle for `tenant_id`, and its canary if it has
+    /// one, without installing them, failing if the tenant's `allow`
+    /// entrypoint is not defined.
+    pub fn validate(&self, tenant_id: &str, bundle_path: &Path) -> Result<(), PolicyError> {
+        let bundle = self
+            .load_bundle(bundle_path)
+            .map_err(|err| bundle_load_error(tenant_id, err))?;
+        let canary = self
+            .load_canary(bundle_path)
+            .map_err(|err| bundle_load_error(tenant_id, err))?;
+
+        TenantEngine::new(tenant_id.to_string(), bundle.policies, bundle.data)?
+            .verify_entrypoint()?;
+        if let Some((canary, _)) = canary {
+            TenantEngine::new(tenant_id.to_string(), canary.policies, canary.data)?
+                .verify_entrypoint()?;
+        }
+
+        Ok(())
+    }
+
+    /// Validates every tenant bundle under `bundles_dir`, continuing past
+    /// failures. Results are sorted by tenant id.
+    pub fn validate_all(
+        &self,
+        bundles_dir: &Path,
+    ) -> Result<Vec<(TenantId, Result<(), PolicyError>)>> {
+        let results = tenant_dirs(bundles_dir)?
+            .into_iter()
+            .map(|tenant_id| {
+                let result = self.validate(&tenant_id, &bundles_dir.join(&tenant_id));
+                (tenant_id, result)
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    fn check_bundle_bytes(&self, tenant_id: &str, total_bytes: u64) -> Result<(), PolicyError> {
+        if total_bytes > self.max_bundle_bytes {
+            return Err(PolicyError::InvalidPolicy {
+                tenant_id: tenant_id.to_string(),
+                reason: format!(
+                    "bundle size of {} bytes exceeds the limit of {} bytes",
+                    total_bytes, self.max_bundle_bytes
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn collect_rego_files(
+        &self,
+        tenant_id: &str,
+        directory: &Path,
+        root: &Path,
+        policies: &mut Vec<(String, String)>,
+        total_bytes: &mut u64,
+    ) -> Result<()> {
+        for entry in fs::read_dir(directory)
+            .with_context(|| format!("failed to read directory '{}'", directory.display()))?
+        {
+            let entry = entry?;
+            let path = entry.path();
+
+            if entry.file_type()?.is_dir() {
+                // A canary bundle is loaded on its own by `load_canary`.
+                if directory == root && entry.file_name() == CANARY_DIR {
+                    continue;
+                }
+                self.collect_rego_files(tenant_id, &path, root, policies, total_bytes)?;
+                continue;
+            }
+
+            if path.extension().and_then(|ext| ext.to_str()) != Some("rego") {
+                continue;
+            }
+
+            *total_bytes += entry.metadata()?.len();
+            self.check_bundle_bytes(tenant_id, *total_bytes)?;
+
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read policy file '{}'", path.display()))?;
+
+            let relative_path = path.strip_prefix(root).unwrap_or(&path);
+
+            policies.push((relative_path.to_string_lossy().to_string(), content));
+        }
+
+        Ok(())
+    }
+}
+
+/// Lists tenant bundle directories under `bundles_dir`, sorted by tenant id.
+pub(super) fn tenant_dirs(bundles_dir: &Path) -> Result<Vec<TenantId>> {
+    let mut tenant_ids = Vec::new();
+
+    for entry in fs::read_dir(bundles_dir).with_context(|| {
+        format!(
+            "failed to read bundles directory '{}'",
+            bundles_dir.display()
+        )
+    })? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
             continue;
         }
 
-        let content = fs::read_to_string(&path)
-            .with_context(|| format!("failed to read policy file '{}'", path.display()))?;
+        tenant_ids.push(entry.file_name().to_string_lossy().to_string());
+    }
+    tenant_ids.sort();
 
-        let relative_path = path.strip_prefix(root).unwrap_or_else(|_| path.as_path());
+    Ok(tenant_ids)
+}
 
-        policies.push((relative_path.to_string_lossy().to_string(), content));
+/// Surfaces limit violations from the loader as-is and wraps everything else
+/// as a [`PolicyError::BundleLoadError`].
+pub(super) fn bundle_load_error(tenant_id: &str, err: anyhow::Error) -> PolicyError {
+    match err.downcast::<PolicyError>() {
+        Ok(invalid @ PolicyError::InvalidPolicy { .. }) => invalid,
+        Ok(other) => PolicyError::BundleLoadError {
+            tenant_id: tenant_id.to_string(),
+            source: other.into(),
+        },
+        Err(err) => PolicyError::BundleLoadError {
+            tenant_id: tenant_id.to_string(),
+            source: err,
+        },
     }
+}
 
-    Ok(())
+fn bundle_tenant_id(bundle_path: &Path) -> String {
+    let tenant_path = match bundle_path.file_name() {
+        Some(name) if name == CANARY_DIR => bundle_path.parent().unwrap_or(bundle_path),
+        _ => bundle_path,
+    };
+    tenant_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string()
 }
 
+fn optional_file_len(path: &Path) -> Result<u64> {
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    let metadata =
+        fs::metadata(path).with_context(|| format!("failed to stat '{}'", path.display()))?;
+    Ok(metadata.len())
+}
+
+/// Counts top-level rule heads in a Rego source.
+///
+/// Every non-indented statement other than `package`, `import`, comments and
+/// closing braces is treated as a rule, which is what OPA compiles into a
+/// separate rule entry.
+fn count_rules(source: &str) -> usize {
+    source
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .map(str::trim_end)
+        .filter(|line| {
+            !line.is_empty()
+                && !line.starts_with('#')
+                && !line.starts_with('}')
+                && !line.starts_with("package ")
+                && !line.starts_with("import ")
+        })
+        .count()
+}
 fn load_optional_json(path: PathBuf) -> Result<Option<JsonValue>> {
     if !path.exists() {
         return Ok(None);
@@ -118,6 +367,15 @@ pub struct BundleMetadata {
     pub author: Option<String>,
     pub description: Option<String>,
     pub created_at: Option<String>,
+    /// Optional decision features switched on for the tenant, keyed by name
+    /// such as [`EXPLAIN_FEATURE`](super::EXPLAIN_FEATURE). Features left out
+    /// are off.
+    #[serde(default)]
+    pub features: BTreeMap<String, bool>,
+    /// Share of subjects, from 1 to 100, served by this bundle when it is a
+    /// tenant's [`CANARY_DIR`] bundle. Ignored on stable bundles.
+    #[serde(default)]
+    pub rollout_percent: Option<u8>,
 }
 
 #[derive(Debug, Clone)]
@@ -126,3 +384,127 @@ pub struct PolicyBundle {
     pub data: Option<JsonValue>,
     pub metadata: Option<BundleMetadata>,
 }
+
+/// The Rego sources a tenant engine was compiled from, kept for inspection.
+#[derive(Debug, Clone)]
+pub struct ActiveBundle {
+    /// `(path relative to the bundle, source)`, sorted by path.
+    pub policies: Vec<(String, String)>,
+    pub version: Option<String>,
+    /// Hex SHA-256 over each policy's path and source, in path order.
+    pub checksum: String,
+    /// Contents of the bundle's `data.json`, if it had one.
+    pub data: Option<JsonValue>,
+    /// Decision features from the bundle's `metadata.json`.
+    pub features: BTreeMap<String, bool>,
+}
+
+impl ActiveBundle {
+    pub fn from_bundle(bundle: &PolicyBundle) -> Self {
+        let mut policies = bundle.policies.clone();
+        policies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut hasher = Sha256::new();
+        for (path, source) in &policies {
+            hasher.update(path.as_bytes());
+            hasher.update([0u8]);
+            hasher.update(source.as_bytes());
+            hasher.update([0u8]);
+        }
+
+        Self {
+            version: bundle
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.version.clone()),
+            checksum: format!("{:x}", hasher.finalize()),
+            policies,
+            data: bundle.data.clone(),
+            features: bundle
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.features.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn feature_enabled(&self, feature: &str) -> bool {
+        self.features.get(feature).copied().unwrap_or(false)
+    }
+
+    /// Packs the bundle as a gzipped OPA bundle tarball: a `.manifest`, each
+    /// policy at its bundle path, the shared `lib/` helpers the policies
+    /// import and a `data.json`.
+    ///
+    /// Data is nested under `tenants.{tenant_id}` the same way the enforcer
+    /// exposes it to policies. The manifest claims that root plus
+    /// `lib/{helper}` for each included helper, so an archive without helpers
+    /// can be loaded into OPA next to other tenants' bundles.
+    pub fn to_opa_archive(&self, tenant_id: &str) -> io::Result<Vec<u8>> {
+        let helpers = shared_helpers(&self.policies);
+        let mut roots = vec![format!("tenants/{tenant_id}")];
+        roots.extend(
+            helpers
+                .iter()
+                .map(|(path, _)| path.trim_end_matches(".rego").to_string()),
+        );
+        let manifest = serde_json::json!({
+            "revision": self.version.as_deref().unwrap_or(&self.checksum),
+            "roots": roots,
+        });
+        let data = serde_json::json!({
+            "tenants": {
+                tenant_id: self.data.clone().unwrap_or_else(|| serde_json::json!({})),
+            }
+        });
+
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        append_file(&mut archive, ".manifest", manifest.to_string().as_bytes())?;
+        for (path, source) in self.policies.iter().chain(&helpers) {
+            append_file(&mut archive, path, source.as_bytes())?;
+        }
+        append_file(&mut archive, "data.json", data.to_string().as_bytes())?;
+
+        archive.into_inner()?.finish()
+    }
+}
+
+fn append_file<W: Write>(
+    archive: &mut tar::Builder<W>,
+    path: &str,
+    contents: &[u8],
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(0);
+    archive.append_data(&mut header, path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_top_level_rules_only() {
+        let source = r#"
+package tenants.tenant_a
+
+import rego.v1
+
+# EU regions
+eu_regions := {
+    "DE",
+    "FR",
+}
+
+default allow := false
+
+allow if {
+    input.resource.region in eu_regions
+}
+"#;
+
+        assert_eq!(count_rules(source), 3);
+    }
+}
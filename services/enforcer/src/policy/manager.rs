@@ -1,89 +1,235 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     path::PathBuf,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
 };
 
 use anyhow::{anyhow, Context, Result};
+use edge_policy_dsl::rollout_bucket;
 use serde_json::Value as JsonValue;
+use tokio::sync::Semaphore;
 use tracing::{error, info};
 
 use super::{
-    loader::{BundleLoader, PolicyBundle},
-    PolicyError, TenantEngine, TenantId,
+    defaults::apply_default_attributes,
+    loader::{bundle_load_error, tenant_dirs, ActiveBundle, BundleLoader, PolicyBundle},
+    PolicyError, RecentDenials, TenantEngine, TenantId, DEFAULT_MAX_CONCURRENT_EVALUATIONS,
+    MAX_EVAL_TIME_MS, SANDBOX_TENANT_ID,
 };
-use crate::api::PolicyDecision;
+use crate::api::{DecisionEvent, PolicyDecision, RuleCoverage};
+
+/// A loaded engine together with when it was last used for evaluation.
+struct LoadedEngine {
+    engine: TenantEngine,
+    bundle: Arc<ActiveBundle>,
+    canary: Option<CanaryEngine>,
+    last_used: AtomicU64,
+}
+
+/// The engine built from a tenant's canary bundle and the share of subjects
+/// it serves.
+struct CanaryEngine {
+    engine: TenantEngine,
+    rollout_percent: u8,
+}
 
 pub struct PolicyManager {
-    engines: Arc<RwLock<HashMap<TenantId, TenantEngine>>>,
+    engines: Arc<RwLock<HashMap<TenantId, LoadedEngine>>>,
+    /// Tenants dropped from memory, either explicitly or by the LRU cap;
+    /// their bundles are loaded again on the next query.
+    evicted: RwLock<HashSet<TenantId>>,
+    /// Monotonic counter used to order engines by recency of use.
+    use_clock: AtomicU64,
+    max_loaded_tenants: Option<usize>,
     bundles_dir: PathBuf,
     loader: BundleLoader,
+    default_attributes: BTreeMap<String, JsonValue>,
+    log_redact_paths: Vec<String>,
+    eval_timeout: Duration,
+    /// Evaluation slots shared by every engine the manager builds.
+    eval_slots: Arc<Semaphore>,
+    recent_denials: RecentDenials,
 }
 
 impl PolicyManager {
     pub fn new(bundles_dir: PathBuf) -> Self {
         Self {
             engines: Arc::new(RwLock::new(HashMap::new())),
+            evicted: RwLock::new(HashSet::new()),
+            use_clock: AtomicU64::new(0),
+            max_loaded_tenants: None,
             bundles_dir,
             loader: BundleLoader::new(),
+            default_attributes: BTreeMap::new(),
+            log_redact_paths: Vec::new(),
+            eval_timeout: Duration::from_millis(MAX_EVAL_TIME_MS),
+            eval_slots: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_EVALUATIONS)),
+            recent_denials: RecentDenials::default(),
         }
     }
 
-    pub fn load_all_tenants(&self) -> Result<usize> {
-        let mut count = 0usize;
+    /// Sets the attributes merged into every input before evaluation.
+    ///
+    /// Keys are dotted paths such as `environment.region`; see
+    /// [`apply_default_attributes`] for the merge rules.
+    pub fn with_default_attributes(mut self, defaults: BTreeMap<String, JsonValue>) -> Self {
+        self.default_attributes = defaults;
+        self
+    }
 
-        for entry in fs::read_dir(&self.bundles_dir).with_context(|| {
-            format!(
-                "failed to read bundles directory '{}'",
-                self.bundles_dir.display()
-            )
-        })? {
-            let entry = entry?;
-            if !entry.file_type()?.is_dir() {
-                continue;
-            }
+    /// Sets the per-query evaluation timeout for engines built from now on.
+    pub fn with_eval_timeout(mut self, eval_timeout: Duration) -> Self {
+        self.eval_timeout = eval_timeout;
+        self
+    }
+
+    /// Caps how many evaluations run on the blocking pool at once, across
+    /// all tenants. Evaluations that time out keep their slot until they
+    /// finish; see [`TenantEngine::evaluate`].
+    pub fn with_max_concurrent_evaluations(mut self, max: usize) -> Self {
+        self.eval_slots = Arc::new(Semaphore::new(max));
+        self
+    }
 
-            let tenant_id = entry.file_name().to_string_lossy().to_string();
+    /// Sets the input paths masked whenever a query input is logged.
+    pub fn with_log_redact_paths(mut self, paths: Vec<String>) -> Self {
+        self.log_redact_paths = paths;
+        self
+    }
 
-            match self.load_tenant(&tenant_id) {
-                Ok(_) => {
-                    count += 1;
-                    info!(tenant = %tenant_id, "loaded tenant policy");
-                }
-                Err(err) => {
-                    error!(tenant = %tenant_id, error = ?err, "failed to load tenant policy");
+    /// Caps how many tenant engines stay in memory at once. When the cap is
+    /// exceeded the least recently evaluated tenant is evicted and reloaded
+    /// on its next query. `None` keeps every tenant loaded.
+    pub fn with_max_loaded_tenants(mut self, max_loaded_tenants: Option<usize>) -> Self {
+        self.max_loaded_tenants = max_loaded_tenants;
+        self
+    }
+
+    pub fn log_redact_paths(&self) -> &[String] {
+        &self.log_redact_paths
+    }
+
+    /// Sets how many recent denials are kept per tenant; `0` keeps none.
+    pub fn with_recent_denials(mut self, per_tenant: usize) -> Self {
+        self.recent_denials = RecentDenials::new(per_tenant);
+        self
+    }
+
+    /// Remembers a denied decision for `GET /v1/tenants/{tenant_id}/recent-denials`.
+    /// The event input must already be redacted.
+    pub fn record_denial(&self, event: DecisionEvent) {
+        self.recent_denials.record(event);
+    }
+
+    /// The tenant's recent denials, newest first.
+    pub fn recent_denials(&self, tenant_id: &str) -> Vec<DecisionEvent> {
+        self.recent_denials.list(tenant_id)
+    }
+
+    /// Sets the bundle size and rule-count limits enforced before compilation.
+    pub fn with_bundle_limits(mut self, max_bundle_bytes: u64, max_rules: usize) -> Self {
+        self.loader = self.loader.with_limits(max_bundle_bytes, max_rules);
+        self
+    }
+
+    pub fn load_all_tenants(&self) -> Result<usize> {
+        let results = self.reload_all_tenants()?;
+        Ok(results.iter().filter(|(_, result)| result.is_ok()).count())
+    }
+
+    /// Loads every tenant bundle under the bundles directory, continuing past
+    /// failures so one broken bundle does not block the rest.
+    ///
+    /// Returns one entry per tenant directory, sorted by tenant id. A tenant
+    /// whose reload fails keeps serving its previously loaded engine, if any.
+    pub fn reload_all_tenants(&self) -> Result<Vec<(TenantId, Result<(), PolicyError>)>> {
+        let results = tenant_dirs(&self.bundles_dir)?
+            .into_iter()
+            .map(|tenant_id| {
+                let result = self.load_tenant(&tenant_id);
+                match &result {
+                    Ok(_) => info!(tenant = %tenant_id, "loaded tenant policy"),
+                    Err(err) => {
+                        error!(tenant = %tenant_id, error = ?err, "failed to load tenant policy")
+                    }
                 }
-            }
-        }
+                (tenant_id, result)
+            })
+            .collect();
 
-        Ok(count)
+        Ok(results)
     }
 
     pub fn load_tenant(&self, tenant_id: &str) -> Result<(), PolicyError> {
         let bundle_path = self.bundles_dir.join(tenant_id);
-        let bundle =
-            self.loader
-                .load_bundle(&bundle_path)
-                .map_err(|err| PolicyError::BundleLoadError {
-                    tenant_id: tenant_id.to_string(),
-                    source: err,
-                })?;
+        let bundle = self
+            .loader
+            .load_bundle(&bundle_path)
+            .map_err(|err| bundle_load_error(tenant_id, err))?;
+        let canary = self
+            .loader
+            .load_canary(&bundle_path)
+            .map_err(|err| bundle_load_error(tenant_id, err))?;
 
-        self.install_tenant_engine(tenant_id, bundle)
+        self.install_tenant_engine(tenant_id, bundle, canary)
     }
 
     pub fn reload_tenant(&self, tenant_id: &str) -> Result<(), PolicyError> {
         let bundle_path = self.bundles_dir.join(tenant_id);
-        let bundle =
-            self.loader
-                .load_bundle(&bundle_path)
-                .map_err(|err| PolicyError::BundleLoadError {
-                    tenant_id: tenant_id.to_string(),
-                    source: err,
-                })?;
+        let bundle = self
+            .loader
+            .load_bundle(&bundle_path)
+            .map_err(|err| bundle_load_error(tenant_id, err))?;
+        let canary = self
+            .loader
+            .load_canary(&bundle_path)
+            .map_err(|err| bundle_load_error(tenant_id, err))?;
+
+        self.install_tenant_engine(tenant_id, bundle, canary)
+    }
+
+    /// Drops the tenant's engine from memory without touching its bundle on
+    /// disk. The next query for the tenant loads the bundle again.
+    ///
+    /// Returns whether the tenant was loaded.
+    pub fn evict_tenant(&self, tenant_id: &str) -> bool {
+        let Ok(mut engines) = self.engines.write() else {
+            return false;
+        };
+        let removed = engines.remove(tenant_id).is_some();
 
-        self.install_tenant_engine(tenant_id, bundle)
+        if removed {
+            if let Ok(mut evicted) = self.evicted.write() {
+                evicted.insert(tenant_id.to_string());
+            }
+            drop(engines);
+            info!(tenant = %tenant_id, "evicted tenant policy from memory");
+        }
+        removed
+    }
+
+    /// The sources, version and checksum of the bundle the tenant's engine
+    /// was built from, loading the bundle again if it was evicted.
+    pub fn active_bundle(&self, tenant_id: &str) -> Result<Arc<ActiveBundle>, PolicyError> {
+        match self.loaded_bundle(tenant_id)? {
+            Some(bundle) => Ok(bundle),
+            None if self.was_evicted(tenant_id) => {
+                self.load_tenant(tenant_id)?;
+                self.loaded_bundle(tenant_id)?
+                    .ok_or_else(|| PolicyError::TenantNotFound(tenant_id.to_string()))
+            }
+            None => Err(PolicyError::TenantNotFound(tenant_id.to_string())),
+        }
+    }
+
+    /// Whether the tenant's bundle metadata switches `feature` on.
+    pub fn feature_enabled(&self, tenant_id: &str, feature: &str) -> Result<bool, PolicyError> {
+        Ok(self.active_bundle(tenant_id)?.feature_enabled(feature))
     }
 
     pub async fn evaluate(
@@ -91,23 +237,78 @@ impl PolicyManager {
         tenant_id: &str,
         input: JsonValue,
     ) -> Result<PolicyDecision, PolicyError> {
-        let engine = {
-            let guard = self
-                .engines
-                .read()
-                .map_err(|_| PolicyError::EvaluationFailed {
-                    tenant_id: tenant_id.to_string(),
-                    source: anyhow!("engine map poisoned"),
-                })?;
-            guard
-                .get(tenant_id)
-                .cloned()
-                .ok_or_else(|| PolicyError::TenantNotFound(tenant_id.to_string()))?
-        };
+        self.evaluate_timed(tenant_id, input)
+            .await
+            .map(|(decision, _)| decision)
+    }
+
+    /// Evaluates like [`PolicyManager::evaluate`], also returning the time
+    /// the tenant engine spent on the query in microseconds.
+    ///
+    /// While the tenant has a canary bundle, subjects whose rollout bucket
+    /// falls below its `rollout_percent` are evaluated against the canary.
+    pub async fn evaluate_timed(
+        &self,
+        tenant_id: &str,
+        mut input: JsonValue,
+    ) -> Result<(PolicyDecision, u64), PolicyError> {
+        apply_default_attributes(tenant_id, &mut input, &self.default_attributes);
+
+        let engine = self.query_engine(tenant_id, rollout_subject(&input))?;
+
+        engine.evaluate_timed(input).await
+    }
+
+    /// Evaluates every input against the tenant's policy with coverage
+    /// tracking, for the test-runner endpoint. Inputs get the same default
+    /// attributes as regular queries.
+    pub async fn evaluate_with_coverage(
+        &self,
+        tenant_id: &str,
+        mut inputs: Vec<JsonValue>,
+    ) -> Result<(Vec<PolicyDecision>, Vec<RuleCoverage>), PolicyError> {
+        let engine = self.query_engine(tenant_id, None)?;
+
+        for input in &mut inputs {
+            apply_default_attributes(tenant_id, input, &self.default_attributes);
+        }
+
+        engine.evaluate_with_coverage(inputs).await
+    }
+
+    /// Compiles `rego` into a throwaway engine, evaluates `input` against
+    /// `entrypoint` once and drops the engine.
+    ///
+    /// The source is held to the same size and rule-count limits as tenant
+    /// bundles and is never added to the loaded tenants.
+    pub async fn evaluate_sandbox(
+        &self,
+        rego: String,
+        entrypoint: String,
+        input: JsonValue,
+    ) -> Result<PolicyDecision, PolicyError> {
+        let policies = vec![("sandbox.rego".to_string(), rego)];
+        self.loader.check_sources(SANDBOX_TENANT_ID, &policies)?;
+
+        let engine = TenantEngine::new(SANDBOX_TENANT_ID.to_string(), policies, None)?
+            .with_entrypoint(entrypoint)
+            .with_eval_timeout(self.eval_timeout)
+            .with_eval_slots(Arc::clone(&self.eval_slots));
 
         engine.evaluate(input).await
     }
 
+    /// Confirms the bundles directory can still be listed.
+    pub fn check_bundles_dir(&self) -> Result<()> {
+        fs::read_dir(&self.bundles_dir).with_context(|| {
+            format!(
+                "failed to read bundles directory '{}'",
+                self.bundles_dir.display()
+            )
+        })?;
+        Ok(())
+    }
+
     pub fn list_tenants(&self) -> Vec<String> {
         self.engines
             .read()
@@ -115,12 +316,136 @@ impl PolicyManager {
             .unwrap_or_default()
     }
 
+    /// The tenant's engine for `rollout_subject`: the canary when the subject
+    /// falls inside its rollout, otherwise the stable engine.
+    fn loaded_engine(
+        &self,
+        tenant_id: &str,
+        rollout_subject: Option<&str>,
+    ) -> Result<Option<TenantEngine>, PolicyError> {
+        let guard = self
+            .engines
+            .read()
+            .map_err(|_| PolicyError::EvaluationFailed {
+                tenant_id: tenant_id.to_string(),
+                source: anyhow!("engine map poisoned"),
+            })?;
+        Ok(guard.get(tenant_id).map(|loaded| {
+            loaded.last_used.store(self.tick(), Ordering::Relaxed);
+            match (&loaded.canary, rollout_subject) {
+                (Some(canary), Some(subject))
+                    if rollout_bucket(tenant_id, subject) < canary.rollout_percent =>
+                {
+                    canary.engine.clone()
+                }
+                _ => loaded.engine.clone(),
+            }
+        }))
+    }
+
+    fn loaded_bundle(&self, tenant_id: &str) -> Result<Option<Arc<ActiveBundle>>, PolicyError> {
+        let guard = self
+            .engines
+            .read()
+            .map_err(|_| PolicyError::EvaluationFailed {
+                tenant_id: tenant_id.to_string(),
+                source: anyhow!("engine map poisoned"),
+            })?;
+        Ok(guard.get(tenant_id).map(|loaded| loaded.bundle.clone()))
+    }
+
+    /// The tenant's engine, loading the bundle again if it was evicted.
+    fn query_engine(
+        &self,
+        tenant_id: &str,
+        rollout_subject: Option<&str>,
+    ) -> Result<TenantEngine, PolicyError> {
+        match self.loaded_engine(tenant_id, rollout_subject)? {
+            Some(engine) => Ok(engine),
+            None if self.was_evicted(tenant_id) => {
+                self.load_tenant(tenant_id)?;
+                info!(tenant = %tenant_id, "reloaded evicted tenant policy");
+                self.loaded_engine(tenant_id, rollout_subject)?
+                    .ok_or_else(|| PolicyError::TenantNotFound(tenant_id.to_string()))
+            }
+            None => Err(PolicyError::TenantNotFound(tenant_id.to_string())),
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        self.use_clock.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn was_evicted(&self, tenant_id: &str) -> bool {
+        self.evicted
+            .read()
+            .map(|evicted| evicted.contains(tenant_id))
+            .unwrap_or(false)
+    }
+
     fn install_tenant_engine(
         &self,
         tenant_id: &str,
         bundle: PolicyBundle,
+        canary: Option<(PolicyBundle, u8)>,
     ) -> Result<(), PolicyError> {
-        let engine = TenantEngine::new(tenant_id.to_string(), bundle.policies, bundle.data)?;
+        let active = Arc::new(ActiveBundle::from_bundle(&bundle));
+        let engine = self.build_engine(tenant_id, bundle)?;
+        let canary = match canary {
+            Some((bundle, rollout_percent)) => {
+                info!(
+                    tenant = %tenant_id,
+                    version = ?bundle.metadata.as_ref().map(|metadata| &metadata.version),
+                    rollout_percent,
+                    "loaded canary policy bundle"
+                );
+                Some(CanaryEngine {
+                    engine: self.build_engine(tenant_id, bundle)?,
+                    rollout_percent,
+                })
+            }
+            None => None,
+        };
+
+        let mut guard = self
+            .engines
+            .write()
+            .map_err(|_| PolicyError::BundleLoadError {
+                tenant_id: tenant_id.to_string(),
+                source: anyhow!("engine map poisoned"),
+            })?;
+
+        guard.insert(
+            tenant_id.to_string(),
+            LoadedEngine {
+                engine,
+                bundle: active,
+                canary,
+                last_used: AtomicU64::new(self.tick()),
+            },
+        );
+        let displaced = self.enforce_loaded_cap(&mut guard, tenant_id);
+
+        // Record displaced tenants before releasing the engine map so a
+        // concurrent query never sees a tenant that is neither loaded nor
+        // marked for reload.
+        if let Ok(mut evicted) = self.evicted.write() {
+            evicted.remove(tenant_id);
+            evicted.extend(displaced);
+        }
+        drop(guard);
+
+        Ok(())
+    }
+
+    fn build_engine(
+        &self,
+        tenant_id: &str,
+        bundle: PolicyBundle,
+    ) -> Result<TenantEngine, PolicyError> {
+        let engine = TenantEngine::new(tenant_id.to_string(), bundle.policies, bundle.data)?
+            .with_eval_timeout(self.eval_timeout)
+            .with_eval_slots(Arc::clone(&self.eval_slots));
 
         if let Err(err) = engine.verify_entrypoint() {
             error!(
@@ -131,16 +456,44 @@ impl PolicyManager {
             return Err(err);
         }
 
-        let mut guard = self
-            .engines
-            .write()
-            .map_err(|_| PolicyError::BundleLoadError {
-                tenant_id: tenant_id.to_string(),
-                source: anyhow!("engine map poisoned"),
-            })?;
+        Ok(engine)
+    }
 
-        guard.insert(tenant_id.to_string(), engine);
+    /// Removes least recently used engines until the map fits within
+    /// `max_loaded_tenants`, never removing `keep`. Queries already holding
+    /// a cloned engine finish against it unaffected.
+    fn enforce_loaded_cap(
+        &self,
+        engines: &mut HashMap<TenantId, LoadedEngine>,
+        keep: &str,
+    ) -> Vec<TenantId> {
+        let Some(cap) = self.max_loaded_tenants else {
+            return Vec::new();
+        };
 
-        Ok(())
+        let mut displaced = Vec::new();
+        while engines.len() > cap {
+            let oldest = engines
+                .iter()
+                .filter(|(tenant, _)| tenant.as_str() != keep)
+                .min_by_key(|(_, loaded)| loaded.last_used.load(Ordering::Relaxed))
+                .map(|(tenant, _)| tenant.clone());
+            let Some(oldest) = oldest else { break };
+
+            engines.remove(&oldest);
+            info!(tenant = %oldest, cap, "evicted least recently used tenant policy");
+            displaced.push(oldest);
+        }
+        displaced
     }
 }
+
+/// The key a query is assigned to a rollout bucket by: the subject's
+/// `user_id`, or its `device_id` for device traffic. Queries with neither
+/// always use the stable bundle.
+fn rollout_subject(input: &JsonValue) -> Option<&str> {
+    let subject = input.get("subject")?;
+    ["user_id", "device_id"]
+        .iter()
+        .find_map(|key| subject.get(key)?.as_str())
+}
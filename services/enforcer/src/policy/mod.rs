@@ -1,18 +1,34 @@
 use anyhow::Error as AnyhowError;
 use thiserror::Error;
 
+mod coverage;
+mod defaults;
+mod denials;
 mod engine;
+mod helpers;
 mod loader;
 mod manager;
 
+pub use defaults::apply_default_attributes;
+pub use denials::{RecentDenials, DEFAULT_RECENT_DENIALS};
 pub use engine::TenantEngine;
-pub use loader::{BundleLoader, BundleMetadata, PolicyBundle};
+pub use loader::{
+    ActiveBundle, BundleLoader, BundleMetadata, PolicyBundle, CANARY_DIR, DEFAULT_MAX_BUNDLE_BYTES,
+    DEFAULT_MAX_RULES,
+};
 pub use manager::PolicyManager;
 
 pub type TenantId = String;
 
 pub const DEFAULT_ENTRYPOINT_TEMPLATE: &str = "data.tenants.{tenant_id}.allow";
+/// Default evaluation budget for a single policy query.
 pub const MAX_EVAL_TIME_MS: u64 = 10;
+/// Default number of policy evaluations allowed on the blocking pool at once.
+pub const DEFAULT_MAX_CONCURRENT_EVALUATIONS: usize = 64;
+/// Tenant label attached to errors and metrics from sandbox evaluations.
+pub const SANDBOX_TENANT_ID: &str = "sandbox";
+/// Tenant feature that allows `?explain=true` on policy queries.
+pub const EXPLAIN_FEATURE: &str = "explain";
 
 #[derive(Debug, Error)]
 pub enum PolicyError {
@@ -24,6 +40,8 @@ pub enum PolicyError {
         #[source]
         source: AnyhowError,
     },
+    #[error("policy evaluation for tenant '{tenant_id}' timed out after {timeout_ms} ms")]
+    EvaluationTimeout { tenant_id: String, timeout_ms: u64 },
     #[error("failed to load bundle for tenant '{tenant_id}'")]
     BundleLoadError {
         tenant_id: String,
@@ -39,6 +57,7 @@ impl PolicyError {
         match self {
             PolicyError::TenantNotFound(tenant_id) => Some(tenant_id.as_str()),
             PolicyError::EvaluationFailed { tenant_id, .. } => Some(tenant_id.as_str()),
+            PolicyError::EvaluationTimeout { tenant_id, .. } => Some(tenant_id.as_str()),
             PolicyError::BundleLoadError { tenant_id, .. } => Some(tenant_id.as_str()),
             PolicyError::InvalidPolicy { tenant_id, .. } => Some(tenant_id.as_str()),
         }
@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use edge_policy_telemetry::EnvFile;
+use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
+
+use crate::config::EnforcerConfig;
+
+/// Environment variable naming an optional `KEY=VALUE` file that is re-read
+/// on SIGHUP before the configuration is reloaded.
+pub const RELOAD_ENV_FILE_VAR: &str = "RELOAD_ENV_FILE";
+
+pub use edge_policy_telemetry::LogReloadHandle;
+
+/// Re-reads the configuration through `env` and applies the log level if it
+/// changed. Values in the file named by `RELOAD_ENV_FILE` take precedence over
+/// `env`.
+///
+/// Everything else in the enforcer configuration only takes effect on
+/// restart; differences from `active` are logged so operators know a restart
+/// is pending. Returns the newly applied log level, if any.
+pub fn reload_log_level(
+    active: &mut EnforcerConfig,
+    log_handle: &LogReloadHandle,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<Option<String>> {
+    let env_file = match env(RELOAD_ENV_FILE_VAR) {
+        Some(path) => EnvFile::read(path).context("failed to read reload env file")?,
+        None => EnvFile::default(),
+    };
+
+    let reloaded =
+        EnforcerConfig::load_from(&env_file.over(env)).context("failed to reload configuration")?;
+
+    for field in restart_only_changes(active, &reloaded) {
+        warn!(
+            field,
+            "configuration change requires a restart to take effect"
+        );
+    }
+
+    if reloaded.log_level == active.log_level {
+        return Ok(None);
+    }
+
+    let filter = EnvFilter::try_new(&reloaded.log_level)
+        .with_context(|| format!("invalid LOG_LEVEL '{}'", reloaded.log_level))?;
+    log_handle
+        .reload(filter)
+        .context("failed to apply reloaded log level")?;
+
+    info!(
+        previous = %active.log_level,
+        current = %reloaded.log_level,
+        "log level reloaded"
+    );
+    active.log_level = reloaded.log_level.clone();
+
+    Ok(Some(reloaded.log_level))
+}
+
+/// Reloads the log level every time the process receives SIGHUP, reading
+/// variables through `env` (normally [`edge_policy_telemetry::process_env`]).
+#[cfg(unix)]
+pub async fn reload_on_sighup<F>(
+    mut active: EnforcerConfig,
+    log_handle: LogReloadHandle,
+    env: F,
+) -> Result<()>
+where
+    F: Fn(&str) -> Option<String> + Send,
+{
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = signal(SignalKind::hangup()).context("failed to install SIGHUP handler")?;
+    while hangup.recv().await.is_some() {
+        info!("SIGHUP received, reloading runtime settings");
+        match reload_log_level(&mut active, &log_handle, &env) {
+            Ok(Some(_)) => {}
+            Ok(None) => info!("no runtime settings changed"),
+            Err(err) => error!(error = ?err, "failed to reload runtime settings"),
+        }
+    }
+
+    Ok(())
+}
+
+fn restart_only_changes(active: &EnforcerConfig, reloaded: &EnforcerConfig) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if active.server_host != reloaded.server_host {
+        fields.push("server_host");
+    }
+    if active.server_port != reloaded.server_port {
+        fields.push("server_port");
+    }
+    if active.bundles_dir != reloaded.bundles_dir {
+        fields.push("bundles_dir");
+    }
+    if active.enable_hot_reload != reloaded.enable_hot_reload {
+        fields.push("enable_hot_reload");
+    }
+    if active.default_attributes != reloaded.default_attributes {
+        fields.push("default_attributes");
+    }
+    if active.max_bundle_bytes != reloaded.max_bundle_bytes {
+        fields.push("max_bundle_bytes");
+    }
+    if active.max_rules != reloaded.max_rules {
+        fields.push("max_rules");
+    }
+    if active.eval_timeout_ms != reloaded.eval_timeout_ms {
+        fields.push("eval_timeout_ms");
+    }
+    if active.max_concurrent_evaluations != reloaded.max_concurrent_evaluations {
+        fields.push("max_concurrent_evaluations");
+    }
+    if active.log_redact_paths != reloaded.log_redact_paths {
+        fields.push("log_redact_paths");
+    }
+    if active.max_loaded_tenants != reloaded.max_loaded_tenants {
+        fields.push("max_loaded_tenants");
+    }
+    fields
+}
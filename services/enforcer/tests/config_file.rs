@@ -0,0 +1,54 @@
+use std::fs;
+
+use edge_policy_enforcer::config::EnforcerConfig;
+use serde_json::json;
+use tempfile::TempDir;
+
+#[test]
+fn config_file_values_load_and_env_overrides_them() {
+    let dir = TempDir::new().unwrap();
+    let bundles_dir = dir.path().join("tenants.d");
+    fs::create_dir(&bundles_dir).unwrap();
+    let config_path = dir.path().join("enforcer.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+server_host = "0.0.0.0"
+server_port = 9191
+bundles_dir = '{}'
+max_rules = 25
+log_redact_paths = ["subject.email"]
+
+[default_attributes]
+"environment.region" = "eu-west-1"
+"#,
+            bundles_dir.display()
+        ),
+    )
+    .unwrap();
+
+    let config = EnforcerConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.server_host, "0.0.0.0");
+    assert_eq!(config.server_port, 9191);
+    assert_eq!(config.bundles_dir, bundles_dir);
+    assert_eq!(config.max_rules, 25);
+    assert_eq!(config.log_redact_paths, vec!["subject.email"]);
+    assert_eq!(
+        config.default_attributes.get("environment.region"),
+        Some(&json!("eu-west-1"))
+    );
+    // Left out of the file, so the default applies
+    assert_eq!(config.reload_interval_secs, 5);
+
+    std::env::set_var("ENFORCER_PORT", "9292");
+    std::env::set_var("MAX_BUNDLE_BYTES", "4096");
+    let config = EnforcerConfig::from_file(&config_path);
+    std::env::remove_var("ENFORCER_PORT");
+    std::env::remove_var("MAX_BUNDLE_BYTES");
+
+    let config = config.unwrap();
+    assert_eq!(config.server_port, 9292);
+    assert_eq!(config.max_bundle_bytes, 4096);
+    assert_eq!(config.max_rules, 25);
+}
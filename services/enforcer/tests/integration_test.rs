@@ -1,11 +1,32 @@
-use std::{fs, path::Path};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read,
+    path::Path,
+    process::Command,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use edge_policy_dsl::{compile_policy, rollout_bucket};
 use edge_policy_enforcer::{
-    policy::PolicyManager,
+    config::EnforcerConfig,
+    create_router, create_router_with_config,
+    policy::{ActiveBundle, BundleLoader, PolicyError, PolicyManager, CANARY_DIR},
     tenant::{validate_tenant_match, TenantValidationError},
+    DecisionEvent, DecisionReplayResponse, PolicyBenchResponse, PolicyQueryResponse,
+    PolicyTestResponse, RecentDenialsResponse, ReloadAllResponse, ReplayDecision,
+    TenantBundleResponse,
 };
 use serde_json::json;
 use tempfile::tempdir;
+use tokio::sync::broadcast;
+use tower::ServiceExt;
+use tracing_subscriber::fmt::MakeWriter;
 
 #[tokio::test]
 async fn test_load_tenant_bundle() {
@@ -151,6 +172,1169 @@ async fn test_hot_reload() {
     assert!(updated.allow);
 }
 
+#[tokio::test]
+async fn test_decision_follows_data_json_changes() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("data_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &allowlist_policy("data_tenant"));
+    fs::write(
+        tenant_dir.join("data.json"),
+        json!({"allowed_devices": ["gw-1"]}).to_string(),
+    )
+    .unwrap();
+
+    let manager = PolicyManager::new(temp.path().to_path_buf());
+    manager.load_tenant("data_tenant").unwrap();
+
+    let input = json!({
+        "subject": {"tenant_id": "data_tenant", "device_id": "gw-2"},
+        "action": "read",
+    });
+    let before = manager
+        .evaluate("data_tenant", input.clone())
+        .await
+        .expect("evaluation should succeed");
+    assert!(!before.allow);
+
+    fs::write(
+        tenant_dir.join("data.json"),
+        json!({"allowed_devices": ["gw-1", "gw-2"]}).to_string(),
+    )
+    .unwrap();
+    manager.reload_tenant("data_tenant").unwrap();
+
+    let after = manager
+        .evaluate("data_tenant", input)
+        .await
+        .expect("evaluation should succeed");
+    assert!(after.allow);
+}
+
+#[tokio::test]
+async fn test_default_attributes_fill_missing_region() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("region_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &region_policy("region_tenant"));
+
+    let input = json!({
+        "subject": {"tenant_id": "region_tenant"},
+        "action": "read",
+    });
+
+    let without_defaults = PolicyManager::new(temp.path().to_path_buf());
+    without_defaults.load_tenant("region_tenant").unwrap();
+    let decision = without_defaults
+        .evaluate("region_tenant", input.clone())
+        .await
+        .expect("evaluation should succeed");
+    assert!(!decision.allow);
+
+    let mut defaults = BTreeMap::new();
+    defaults.insert("environment.region".to_string(), json!("EU"));
+    let manager = PolicyManager::new(temp.path().to_path_buf()).with_default_attributes(defaults);
+    manager.load_tenant("region_tenant").unwrap();
+
+    let decision = manager
+        .evaluate("region_tenant", input)
+        .await
+        .expect("evaluation should succeed");
+    assert!(decision.allow);
+
+    let explicit = json!({
+        "subject": {"tenant_id": "region_tenant"},
+        "action": "read",
+        "environment": {"region": "US"},
+    });
+    let decision = manager
+        .evaluate("region_tenant", explicit)
+        .await
+        .expect("evaluation should succeed");
+    assert!(!decision.allow);
+}
+
+#[tokio::test]
+async fn test_bundle_size_limit() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let small_dir = temp.path().join("small_tenant");
+    let large_dir = temp.path().join("large_tenant");
+    fs::create_dir_all(&small_dir).unwrap();
+    fs::create_dir_all(&large_dir).unwrap();
+    write_policy(&small_dir, &allow_policy("small_tenant"));
+    write_policy(&large_dir, &allow_policy("large_tenant"));
+    fs::write(
+        large_dir.join("data.json"),
+        json!({"padding": "x".repeat(4096)}).to_string(),
+    )
+    .unwrap();
+
+    let manager = PolicyManager::new(temp.path().to_path_buf()).with_bundle_limits(1024, 100);
+
+    manager
+        .load_tenant("small_tenant")
+        .expect("bundle within limits should load");
+
+    let err = manager
+        .load_tenant("large_tenant")
+        .expect_err("oversized bundle should be rejected");
+    assert!(
+        matches!(err, PolicyError::InvalidPolicy { ref tenant_id, .. } if tenant_id == "large_tenant")
+    );
+    assert!(!manager.list_tenants().contains(&"large_tenant".to_string()));
+}
+
+#[tokio::test]
+async fn test_bundle_rule_limit() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("rules_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &allow_policy("rules_tenant"));
+
+    // allow_policy declares two rules: the default and the allow body.
+    let manager = PolicyManager::new(temp.path().to_path_buf()).with_bundle_limits(1024 * 1024, 1);
+    let err = manager
+        .load_tenant("rules_tenant")
+        .expect_err("bundle over the rule limit should be rejected");
+    assert!(matches!(err, PolicyError::InvalidPolicy { .. }));
+}
+
+#[tokio::test]
+async fn test_reload_all_reports_per_tenant_results() {
+    let temp = tempdir().expect("failed to create temp dir");
+    for tenant in ["good_a", "broken", "good_b"] {
+        fs::create_dir_all(temp.path().join(tenant)).unwrap();
+    }
+    write_policy(&temp.path().join("good_a"), &allow_policy("good_a"));
+    write_policy(&temp.path().join("good_b"), &allow_policy("good_b"));
+    write_policy(
+        &temp.path().join("broken"),
+        "package tenants.broken\n\nallow if {",
+    );
+
+    let manager = Arc::new(PolicyManager::new(temp.path().to_path_buf()));
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(Arc::clone(&manager), Arc::new(event_tx));
+
+    let response = router
+        .oneshot(
+            Request::post("/v1/reload")
+                .body(Body::empty())
+                .expect("request should build"),
+        )
+        .await
+        .expect("router should respond");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: ReloadAllResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(report.reloaded, 2);
+    assert_eq!(report.failed, 1);
+    let broken = report
+        .tenants
+        .iter()
+        .find(|tenant| tenant.tenant_id == "broken")
+        .expect("broken tenant should be reported");
+    assert!(!broken.success);
+    assert!(broken.error.is_some());
+    assert!(report
+        .tenants
+        .iter()
+        .filter(|tenant| tenant.tenant_id != "broken")
+        .all(|tenant| tenant.success));
+
+    let mut tenants = manager.list_tenants();
+    tenants.sort();
+    assert_eq!(tenants, vec!["good_a".to_string(), "good_b".to_string()]);
+}
+
+#[tokio::test]
+async fn test_health_reports_unavailable_bundles_dir() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let bundles_dir = temp.path().join("tenants.d");
+    fs::create_dir_all(&bundles_dir).unwrap();
+
+    let manager = Arc::new(PolicyManager::new(bundles_dir.clone()));
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(manager, Arc::new(event_tx));
+
+    let health = |router: axum::Router| async move {
+        let response = router
+            .oneshot(
+                Request::get("/health")
+                    .body(Body::empty())
+                    .expect("request should build"),
+            )
+            .await
+            .expect("router should respond");
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+        )
+    };
+
+    let (status, body) = health(router.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "healthy");
+    assert_eq!(body["dependencies"]["bundles_dir"]["status"], "up");
+
+    fs::remove_dir_all(&bundles_dir).unwrap();
+
+    let (status, body) = health(router).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "unhealthy");
+    assert_eq!(body["dependencies"]["bundles_dir"]["status"], "down");
+}
+
+#[tokio::test]
+async fn test_config_endpoint_reports_effective_config() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let config = EnforcerConfig {
+        bundles_dir: temp.path().to_path_buf(),
+        max_rules: 42,
+        log_redact_paths: vec!["subject.user_id".to_string()],
+        ..EnforcerConfig::default()
+    };
+
+    let manager = Arc::new(PolicyManager::new(config.bundles_dir.clone()));
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router_with_config(manager, Arc::new(event_tx), &config);
+
+    let response = router
+        .oneshot(
+            Request::get("/v1/config")
+                .body(Body::empty())
+                .expect("request should build"),
+        )
+        .await
+        .expect("router should respond");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let dump: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(dump["max_rules"], 42);
+    assert_eq!(dump["server_port"], 8181);
+    assert_eq!(dump["log_redact_paths"], json!(["subject.user_id"]));
+    assert_eq!(dump["bundles_dir"], json!(temp.path()));
+}
+
+#[tokio::test]
+async fn test_sandbox_evaluates_without_loading_tenant() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let manager = Arc::new(PolicyManager::new(temp.path().to_path_buf()));
+    let (event_tx, mut event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(manager.clone(), Arc::new(event_tx));
+
+    let evaluate = |router: axum::Router, input: serde_json::Value| async move {
+        let request = json!({
+            "rego": allow_policy("scratch"),
+            "entrypoint": "data.tenants.scratch.allow",
+            "input": input,
+        });
+        let response = router
+            .oneshot(
+                Request::post("/v1/sandbox/evaluate")
+                    .header("content-type", "application/json")
+                    .body(Body::from(request.to_string()))
+                    .expect("request should build"),
+            )
+            .await
+            .expect("router should respond");
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+        )
+    };
+
+    let (status, body) = evaluate(
+        router.clone(),
+        json!({"subject": {"tenant_id": "scratch"}, "action": "read"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["result"]["allow"], true);
+
+    let (status, body) = evaluate(
+        router,
+        json!({"subject": {"tenant_id": "scratch"}, "action": "delete"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["result"]["allow"], false);
+
+    assert!(manager.list_tenants().is_empty());
+    assert!(fs::read_dir(temp.path()).unwrap().next().is_none());
+    assert!(event_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_policy_tests_report_uncovered_branch() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("branchy");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &two_branch_policy("branchy"));
+
+    let manager = Arc::new(PolicyManager::new(temp.path().to_path_buf()));
+    manager.load_tenant("branchy").unwrap();
+    let (event_tx, mut event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(manager, Arc::new(event_tx));
+
+    let request = json!({
+        "cases": [
+            {
+                "name": "admin reads",
+                "input": {"subject": {"role": "admin"}, "action": "read"},
+                "expect_allow": true
+            },
+            {
+                "name": "admin deletes",
+                "input": {"subject": {"role": "admin"}, "action": "delete"},
+                "expect_allow": true
+            }
+        ]
+    });
+    let response = router
+        .oneshot(
+            Request::post("/v1/tenants/branchy/test")
+                .header("content-type", "application/json")
+                .body(Body::from(request.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: PolicyTestResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(report.passed, 1);
+    assert_eq!(report.failed, 1);
+    assert!(report.results[0].passed);
+    assert!(!report.results[1].allow);
+
+    let branches: Vec<(u32, bool)> = report
+        .coverage
+        .iter()
+        .filter(|rule| rule.rule == "allow")
+        .map(|rule| (rule.line, rule.covered))
+        .collect();
+    assert_eq!(branches, vec![(6, true), (11, false)]);
+    assert!(event_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_replay_flags_decisions_changed_by_new_policy() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("replay_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &allow_policy("replay_tenant"));
+
+    let manager = Arc::new(PolicyManager::new(temp.path().to_path_buf()));
+    manager.load_tenant("replay_tenant").unwrap();
+
+    let inputs = [
+        json!({"subject": {"tenant_id": "replay_tenant", "role": "admin"}, "action": "read"}),
+        json!({"subject": {"tenant_id": "replay_tenant", "role": "viewer"}, "action": "read"}),
+        json!({"subject": {"tenant_id": "replay_tenant", "role": "operator"}, "action": "write"}),
+    ];
+    let mut records = Vec::new();
+    for (index, input) in inputs.into_iter().enumerate() {
+        let recorded = manager
+            .evaluate("replay_tenant", input.clone())
+            .await
+            .unwrap();
+        records.push(json!({
+            "id": format!("log-{}", index),
+            "input": input,
+            "decision": if recorded.allow { "allow" } else { "deny" },
+        }));
+    }
+
+    write_policy(&tenant_dir, &two_branch_policy("replay_tenant"));
+    manager.reload_tenant("replay_tenant").unwrap();
+
+    let (event_tx, mut event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(manager, Arc::new(event_tx));
+    let response = router
+        .oneshot(
+            Request::post("/v1/tenants/replay_tenant/replay")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "records": records }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: DecisionReplayResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(report.replayed, 3);
+    assert_eq!(report.regressions, 2);
+    assert_eq!(report.newly_denied, 1);
+    assert_eq!(report.newly_allowed, 1);
+
+    let outcomes: Vec<(Option<&str>, ReplayDecision, ReplayDecision, bool)> = report
+        .results
+        .iter()
+        .map(|result| {
+            (
+                result.id.as_deref(),
+                result.recorded,
+                result.current,
+                result.regression,
+            )
+        })
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            (
+                Some("log-0"),
+                ReplayDecision::Allow,
+                ReplayDecision::Allow,
+                false
+            ),
+            (
+                Some("log-1"),
+                ReplayDecision::Allow,
+                ReplayDecision::Deny,
+                true
+            ),
+            (
+                Some("log-2"),
+                ReplayDecision::Deny,
+                ReplayDecision::Allow,
+                true
+            ),
+        ]
+    );
+    assert!(event_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_sandbox_enforces_bundle_limits() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let manager = PolicyManager::new(temp.path().to_path_buf()).with_bundle_limits(1024 * 1024, 1);
+
+    let err = manager
+        .evaluate_sandbox(
+            allow_policy("scratch"),
+            "data.tenants.scratch.allow".to_string(),
+            json!({}),
+        )
+        .await
+        .expect_err("sandbox policy should exceed the rule limit");
+    assert!(matches!(err, PolicyError::InvalidPolicy { .. }));
+}
+
+#[tokio::test]
+async fn test_slow_policy_times_out_with_structured_error() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("slow_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &slow_policy("slow_tenant"));
+
+    let manager =
+        PolicyManager::new(temp.path().to_path_buf()).with_eval_timeout(Duration::from_millis(5));
+    manager.load_tenant("slow_tenant").unwrap();
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(Arc::new(manager), Arc::new(event_tx));
+
+    let request = json!({
+        "input": {"subject": {"tenant_id": "slow_tenant"}, "spin": true}
+    });
+    let response = router
+        .oneshot(
+            Request::post("/v1/data/tenants/slow_tenant/allow")
+                .header("content-type", "application/json")
+                .body(Body::from(request.to_string()))
+                .expect("request should build"),
+        )
+        .await
+        .expect("router should respond");
+
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "EVAL_TIMEOUT");
+    assert_eq!(body["details"]["tenant_id"], "slow_tenant");
+    assert_eq!(body["details"]["timeout_ms"], 5);
+}
+
+#[test]
+fn test_validate_cli_reports_each_bundle_and_fails_on_bad_one() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let good_dir = temp.path().join("good_tenant");
+    let bad_dir = temp.path().join("bad_tenant");
+    fs::create_dir_all(&good_dir).unwrap();
+    fs::create_dir_all(&bad_dir).unwrap();
+    write_policy(&good_dir, &allow_policy("good_tenant"));
+    write_policy(&bad_dir, "package tenants.bad_tenant\n\nallow if {");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_edge-policy-enforcer"))
+        .arg("--validate")
+        .arg(temp.path())
+        .output()
+        .expect("enforcer binary should run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.code(), Some(1), "stdout: {}", stdout);
+    assert!(stdout.contains("ok      good_tenant"));
+    assert!(stdout.contains("FAILED  bad_tenant"));
+    assert!(stdout.contains("1 bundle(s) validated, 1 failed"));
+
+    fs::remove_dir_all(&bad_dir).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_edge-policy-enforcer"))
+        .arg("--validate")
+        .arg(temp.path())
+        .output()
+        .expect("enforcer binary should run");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_config_validation_rejects_missing_bundles_dir() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let config = EnforcerConfig {
+        bundles_dir: temp.path().join("missing"),
+        ..EnforcerConfig::default()
+    };
+    let err = config.validate().unwrap_err();
+    assert!(err.to_string().contains("does not exist"), "{:#}", err);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_edge-policy-enforcer"))
+        .arg("--check-config")
+        .env("BUNDLES_DIR", temp.path().join("missing"))
+        .output()
+        .expect("enforcer binary should run");
+    assert!(!output.status.success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_edge-policy-enforcer"))
+        .arg("--check-config")
+        .env("BUNDLES_DIR", temp.path())
+        .output()
+        .expect("enforcer binary should run");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("configuration ok"));
+}
+
+#[tokio::test]
+async fn test_large_replay_response_is_gzip_compressed() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("gzip_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &allow_policy("gzip_tenant"));
+
+    let manager = Arc::new(PolicyManager::new(temp.path().to_path_buf()));
+    manager.load_tenant("gzip_tenant").unwrap();
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(manager, Arc::new(event_tx));
+
+    let records: Vec<_> = (0..200)
+        .map(|index| {
+            json!({
+                "id": format!("log-{}", index),
+                "input": {"subject": {"tenant_id": "gzip_tenant"}, "action": "read"},
+                "decision": "allow",
+            })
+        })
+        .collect();
+    let replay = |accept_encoding: Option<&str>| {
+        let mut request = Request::post("/v1/tenants/gzip_tenant/replay")
+            .header("content-type", "application/json");
+        if let Some(accept_encoding) = accept_encoding {
+            request = request.header("accept-encoding", accept_encoding);
+        }
+        request
+            .body(Body::from(json!({ "records": records }).to_string()))
+            .unwrap()
+    };
+
+    let response = router
+        .clone()
+        .oneshot(replay(Some("br;q=1.0, gzip;q=0.8")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let mut json_body = String::new();
+    flate2::read::GzDecoder::new(&body[..])
+        .read_to_string(&mut json_body)
+        .unwrap();
+    assert!(body.len() < json_body.len());
+    let report: DecisionReplayResponse = serde_json::from_str(&json_body).unwrap();
+    assert_eq!(report.replayed, 200);
+
+    let response = router.clone().oneshot(replay(None)).await.unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(body.len(), json_body.len());
+
+    let response = router
+        .oneshot(
+            Request::get("/health")
+                .header("accept-encoding", "gzip")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+}
+
+#[tokio::test]
+async fn test_explain_requires_tenant_feature() {
+    let temp = tempdir().expect("failed to create temp dir");
+    for tenant in ["plain_tenant", "explain_tenant"] {
+        let tenant_dir = temp.path().join(tenant);
+        fs::create_dir_all(&tenant_dir).unwrap();
+        write_policy(&tenant_dir, &allow_policy(tenant));
+    }
+    fs::write(
+        temp.path().join("explain_tenant").join("metadata.json"),
+        json!({"version": "1.0.0", "features": {"explain": true}}).to_string(),
+    )
+    .unwrap();
+
+    let manager = PolicyManager::new(temp.path().to_path_buf());
+    manager.load_all_tenants().unwrap();
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(Arc::new(manager), Arc::new(event_tx));
+
+    let explain = |tenant: &str| {
+        router.clone().oneshot(
+            Request::post(format!("/v1/data/tenants/{tenant}/allow?explain=true"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": {"subject": {"tenant_id": tenant}, "action": "read"}})
+                        .to_string(),
+                ))
+                .unwrap(),
+        )
+    };
+
+    let response = explain("plain_tenant").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["result"]["allow"], true);
+    assert!(body.get("explanation").is_none());
+
+    let response = explain("explain_tenant").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: PolicyQueryResponse = serde_json::from_slice(&body).unwrap();
+    assert!(body.result.allow);
+    let explanation = body
+        .explanation
+        .expect("explain tenant should be explained");
+    assert!(explanation
+        .iter()
+        .any(|rule| rule.rule == "allow" && rule.covered));
+}
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[tokio::test]
+async fn test_query_log_redacts_configured_paths() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("pii_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &allow_policy("pii_tenant"));
+
+    let manager = PolicyManager::new(temp.path().to_path_buf())
+        .with_log_redact_paths(vec!["subject.email".to_string()]);
+    manager.load_tenant("pii_tenant").unwrap();
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(Arc::new(manager), Arc::new(event_tx));
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(logs.clone())
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let request = json!({
+        "input": {
+            "subject": {"tenant_id": "pii_tenant", "email": "alice@example.com"},
+            "action": "read",
+        }
+    });
+    let response = router
+        .oneshot(
+            Request::post("/v1/data/tenants/pii_tenant/allow")
+                .header("content-type", "application/json")
+                .body(Body::from(request.to_string()))
+                .expect("request should build"),
+        )
+        .await
+        .expect("router should respond");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["result"]["allow"], true);
+
+    let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("policy query received"));
+    assert!(output.contains("[REDACTED]"));
+    assert!(!output.contains("alice@example.com"));
+}
+
+#[tokio::test]
+async fn test_recent_denials_list_only_denied_queries() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("audit_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &allow_policy("audit_tenant"));
+
+    let manager = PolicyManager::new(temp.path().to_path_buf())
+        .with_log_redact_paths(vec!["subject.email".to_string()]);
+    manager.load_tenant("audit_tenant").unwrap();
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(Arc::new(manager), Arc::new(event_tx));
+
+    for action in ["read", "delete"] {
+        let request = json!({
+            "input": {
+                "subject": {"tenant_id": "audit_tenant", "email": "ops@example.com"},
+                "action": action,
+            }
+        });
+        let response = router
+            .clone()
+            .oneshot(
+                Request::post("/v1/data/tenants/audit_tenant/allow")
+                    .header("content-type", "application/json")
+                    .body(Body::from(request.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = router
+        .oneshot(
+            Request::get("/v1/tenants/audit_tenant/recent-denials")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let listed: RecentDenialsResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(listed.tenant_id, "audit_tenant");
+    assert_eq!(listed.denials.len(), 1);
+    let denial = &listed.denials[0];
+    assert!(!denial.decision.allow);
+    assert_eq!(denial.input["action"], "delete");
+    assert_ne!(denial.input["subject"]["email"], "ops@example.com");
+}
+
+#[tokio::test]
+async fn test_bench_reports_evaluation_percentiles() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("bench_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &allow_policy("bench_tenant"));
+
+    let manager = PolicyManager::new(temp.path().to_path_buf());
+    manager.load_tenant("bench_tenant").unwrap();
+    let (event_tx, mut event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(Arc::new(manager), Arc::new(event_tx));
+
+    let bench = |query: &str| {
+        router.clone().oneshot(
+            Request::get(format!("/v1/tenants/bench_tenant/bench{query}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    let response = bench("?iterations=25").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: PolicyBenchResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(report.tenant_id, "bench_tenant");
+    assert_eq!(report.iterations, 25);
+    assert!(report.max_micros > 0);
+    assert!(report.min_micros <= report.median_micros);
+    assert!(report.median_micros <= report.p99_micros);
+    assert!(report.p99_micros <= report.max_micros);
+    assert!(
+        event_rx.try_recv().is_err(),
+        "bench decisions are not published"
+    );
+
+    for query in ["?iterations=0", "?iterations=10001"] {
+        let response = bench(query).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_bundle_endpoint_returns_active_rego() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("debug_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    let policy = allow_policy("debug_tenant");
+    write_policy(&tenant_dir, &policy);
+    fs::write(
+        tenant_dir.join("metadata.json"),
+        json!({"version": "2.3.0"}).to_string(),
+    )
+    .unwrap();
+
+    let manager = PolicyManager::new(temp.path().to_path_buf());
+    manager.load_tenant("debug_tenant").unwrap();
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(Arc::new(manager), Arc::new(event_tx));
+
+    let fetch = |tenant: &str| {
+        router.clone().oneshot(
+            Request::get(format!("/v1/tenants/{tenant}/bundle"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    let response = fetch("debug_tenant").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let bundle: TenantBundleResponse = serde_json::from_slice(&body).unwrap();
+
+    let deployed = BundleLoader::new().load_bundle(&tenant_dir).unwrap();
+    assert_eq!(bundle.tenant_id, "debug_tenant");
+    assert_eq!(bundle.version.as_deref(), Some("2.3.0"));
+    assert_eq!(
+        bundle.checksum,
+        ActiveBundle::from_bundle(&deployed).checksum
+    );
+    assert_eq!(bundle.policies.len(), 1);
+    assert_eq!(bundle.policies[0].path, "policy.rego");
+    assert_eq!(bundle.policies[0].source, policy);
+
+    let response = fetch("unknown_tenant").await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_bundle_archive_is_a_valid_opa_bundle() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("export_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    let policy = allow_policy("export_tenant");
+    write_policy(&tenant_dir, &policy);
+    fs::write(
+        tenant_dir.join("data.json"),
+        json!({"allowed_devices": ["gw-1"]}).to_string(),
+    )
+    .unwrap();
+    fs::write(
+        tenant_dir.join("metadata.json"),
+        json!({"version": "1.4.0"}).to_string(),
+    )
+    .unwrap();
+
+    let manager = PolicyManager::new(temp.path().to_path_buf());
+    manager.load_tenant("export_tenant").unwrap();
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(Arc::new(manager), Arc::new(event_tx));
+
+    let response = router
+        .oneshot(
+            Request::get("/v1/tenants/export_tenant/bundle.tar.gz")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "application/gzip"
+    );
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+    let mut entries = BTreeMap::new();
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(&body[..]));
+    for entry in archive.entries().expect("archive should be gzipped tar") {
+        let mut entry = entry.unwrap();
+        let path = entry.path().unwrap().to_string_lossy().into_owned();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+        entries.insert(path, contents);
+    }
+
+    let manifest: serde_json::Value = serde_json::from_str(&entries[".manifest"]).unwrap();
+    assert_eq!(manifest["revision"], "1.4.0");
+    assert_eq!(manifest["roots"], json!(["tenants/export_tenant"]));
+    assert_eq!(entries["policy.rego"], policy);
+    let data: serde_json::Value = serde_json::from_str(&entries["data.json"]).unwrap();
+    assert_eq!(
+        data,
+        json!({"tenants": {"export_tenant": {"allowed_devices": ["gw-1"]}}})
+    );
+}
+
+#[tokio::test]
+async fn test_evicted_tenant_reloads_on_next_query() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("idle_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    write_policy(&tenant_dir, &allow_policy("idle_tenant"));
+
+    let manager = Arc::new(PolicyManager::new(temp.path().to_path_buf()));
+    manager.load_tenant("idle_tenant").unwrap();
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(manager.clone(), Arc::new(event_tx));
+
+    let evict = |router: axum::Router| async move {
+        let response = router
+            .oneshot(
+                Request::delete("/v1/tenants/idle_tenant")
+                    .body(Body::empty())
+                    .expect("request should build"),
+            )
+            .await
+            .expect("router should respond");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+    };
+
+    let body = evict(router.clone()).await;
+    assert_eq!(body["evicted"], true);
+    assert!(manager.list_tenants().is_empty());
+    assert!(tenant_dir.join("policy.rego").exists());
+
+    let body = evict(router.clone()).await;
+    assert_eq!(body["evicted"], false);
+
+    let request = json!({
+        "input": {"subject": {"tenant_id": "idle_tenant"}, "action": "read"}
+    });
+    let response = router
+        .oneshot(
+            Request::post("/v1/data/tenants/idle_tenant/allow")
+                .header("content-type", "application/json")
+                .body(Body::from(request.to_string()))
+                .expect("request should build"),
+        )
+        .await
+        .expect("router should respond");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["result"]["allow"], true);
+    assert_eq!(manager.list_tenants(), vec!["idle_tenant".to_string()]);
+}
+
+#[tokio::test]
+async fn test_loaded_tenant_cap_evicts_least_recently_used() {
+    let temp = tempdir().expect("failed to create temp dir");
+    for tenant in ["tenant_a", "tenant_b", "tenant_c"] {
+        let tenant_dir = temp.path().join(tenant);
+        fs::create_dir_all(&tenant_dir).unwrap();
+        write_policy(&tenant_dir, &allow_policy(tenant));
+    }
+
+    let manager =
+        Arc::new(PolicyManager::new(temp.path().to_path_buf()).with_max_loaded_tenants(Some(2)));
+    manager.load_tenant("tenant_a").unwrap();
+    manager.load_tenant("tenant_b").unwrap();
+    manager.load_tenant("tenant_c").unwrap();
+
+    let mut loaded = manager.list_tenants();
+    loaded.sort();
+    assert_eq!(loaded, vec!["tenant_b".to_string(), "tenant_c".to_string()]);
+
+    let (event_tx, _event_rx) = broadcast::channel::<DecisionEvent>(16);
+    let router = create_router(manager.clone(), Arc::new(event_tx));
+    let request = json!({
+        "input": {"subject": {"tenant_id": "tenant_a"}, "action": "read"}
+    });
+    let response = router
+        .oneshot(
+            Request::post("/v1/data/tenants/tenant_a/allow")
+                .header("content-type", "application/json")
+                .body(Body::from(request.to_string()))
+                .expect("request should build"),
+        )
+        .await
+        .expect("router should respond");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["result"]["allow"], true);
+
+    let mut loaded = manager.list_tenants();
+    loaded.sort();
+    assert_eq!(loaded, vec!["tenant_a".to_string(), "tenant_c".to_string()]);
+}
+
+#[tokio::test]
+async fn test_dsl_policy_importing_geo_helper_is_enforced_and_exported() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("geo_tenant");
+    fs::create_dir_all(&tenant_dir).unwrap();
+    let compiled = compile_policy(
+        "allow transfer data if geo.allowed(resource.origin, environment.destination)",
+        "geo_tenant",
+        None,
+    )
+    .expect("DSL policy should compile");
+    write_policy(&tenant_dir, &compiled.rego);
+    fs::write(
+        tenant_dir.join("data.json"),
+        json!({"residency_matrix": {"EU": ["UK"]}}).to_string(),
+    )
+    .unwrap();
+
+    let manager = Arc::new(PolicyManager::new(temp.path().to_path_buf()));
+    manager.load_tenant("geo_tenant").unwrap();
+
+    let transfer = |origin: &str, destination: &str| {
+        json!({
+            "subject": {"tenant_id": "geo_tenant", "user_id": "user-1"},
+            "action": "transfer",
+            "resource": {"type": "data", "origin": origin},
+            "environment": {"destination": destination},
+        })
+    };
+    for (origin, destination, allowed) in [
+        ("EU", "UK", true),
+        ("EU", "EU", true),
+        ("EU", "US", false),
+        ("UK", "EU", false),
+    ] {
+        let decision = manager
+            .evaluate("geo_tenant", transfer(origin, destination))
+            .await
+            .expect("evaluation should succeed");
+        assert_eq!(decision.allow, allowed, "{origin} -> {destination}");
+    }
+
+    let archive = manager
+        .active_bundle("geo_tenant")
+        .unwrap()
+        .to_opa_archive("geo_tenant")
+        .unwrap();
+    let mut entries = BTreeMap::new();
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(&archive[..]));
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let path = entry.path().unwrap().to_string_lossy().into_owned();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+        entries.insert(path, contents);
+    }
+    let manifest: serde_json::Value = serde_json::from_str(&entries[".manifest"]).unwrap();
+    assert_eq!(manifest["roots"], json!(["tenants/geo_tenant", "lib/geo"]));
+    assert!(entries["lib/geo.rego"].starts_with("# Geographic validation"));
+    assert!(!entries.contains_key("lib/quota.rego"));
+}
+
+#[tokio::test]
+async fn test_canary_bundle_serves_its_rollout_share() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("tenant_a");
+    let canary_dir = tenant_dir.join(CANARY_DIR);
+    fs::create_dir_all(&canary_dir).unwrap();
+    write_policy(&tenant_dir, &deny_policy("tenant_a"));
+    write_policy(&canary_dir, &allow_policy("tenant_a"));
+    fs::write(
+        canary_dir.join("metadata.json"),
+        json!({"version": "2.0.0", "rollout_percent": 50}).to_string(),
+    )
+    .unwrap();
+
+    let manager = PolicyManager::new(temp.path().to_path_buf());
+    manager.load_tenant("tenant_a").unwrap();
+
+    let active = manager.active_bundle("tenant_a").unwrap();
+    assert_eq!(active.policies.len(), 1);
+    assert_eq!(active.policies[0].0, "policy.rego");
+
+    let evaluate = |user_id: String| {
+        let input = json!({
+            "subject": {"tenant_id": "tenant_a", "user_id": user_id},
+            "action": "read",
+        });
+        let manager = &manager;
+        async move { manager.evaluate("tenant_a", input).await.unwrap().allow }
+    };
+
+    let first = evaluate("user-7".to_string()).await;
+    for _ in 0..10 {
+        assert_eq!(evaluate("user-7".to_string()).await, first);
+    }
+
+    let mut on_canary = 0;
+    for index in 0..1000 {
+        let user_id = format!("user-{index}");
+        let allow = evaluate(user_id.clone()).await;
+        assert_eq!(allow, rollout_bucket("tenant_a", &user_id) < 50);
+        if allow {
+            on_canary += 1;
+        }
+    }
+    assert!(
+        (400..=600).contains(&on_canary),
+        "{on_canary} of 1000 subjects on the canary"
+    );
+
+    let anonymous = json!({"subject": {"tenant_id": "tenant_a"}, "action": "read"});
+    assert!(!manager.evaluate("tenant_a", anonymous).await.unwrap().allow);
+}
+
+#[tokio::test]
+async fn test_canary_without_rollout_percent_is_rejected() {
+    let temp = tempdir().expect("failed to create temp dir");
+    let tenant_dir = temp.path().join("tenant_a");
+    let canary_dir = tenant_dir.join(CANARY_DIR);
+    fs::create_dir_all(&canary_dir).unwrap();
+    write_policy(&tenant_dir, &allow_policy("tenant_a"));
+    write_policy(&canary_dir, &allow_policy("tenant_a"));
+
+    let manager = PolicyManager::new(temp.path().to_path_buf());
+    let err = manager.load_tenant("tenant_a").unwrap_err();
+    assert!(
+        matches!(err, PolicyError::InvalidPolicy { ref tenant_id, .. } if tenant_id == "tenant_a")
+    );
+    assert!(manager.list_tenants().is_empty());
+}
+
 fn write_policy(dir: &Path, content: &str) {
     fs::write(dir.join("policy.rego"), content).expect("failed to write policy");
 }
@@ -181,3 +1365,74 @@ default allow = false
         tenant = tenant
     )
 }
+
+fn two_branch_policy(tenant: &str) -> String {
+    format!(
+        r#"
+package tenants.{tenant}
+
+default allow = false
+
+allow if {{
+    input.subject.role == "admin"
+    input.action == "read"
+}}
+
+allow if {{
+    input.subject.role == "operator"
+    input.action == "write"
+}}
+"#,
+        tenant = tenant
+    )
+}
+
+/// Allows devices listed in the tenant's `data.json`.
+fn allowlist_policy(tenant: &str) -> String {
+    format!(
+        r#"
+package tenants.{tenant}
+
+default allow = false
+
+allow if {{
+    input.subject.device_id in data.tenants.{tenant}.allowed_devices
+}}
+"#,
+        tenant = tenant
+    )
+}
+
+fn region_policy(tenant: &str) -> String {
+    format!(
+        r#"
+package tenants.{tenant}
+
+default allow = false
+
+allow if {{
+    input.subject.tenant_id == "{tenant}"
+    input.environment.region == "EU"
+}}
+"#,
+        tenant = tenant
+    )
+}
+
+/// Only spins when `input.spin` is set so the entrypoint check at load time
+/// stays fast.
+fn slow_policy(tenant: &str) -> String {
+    format!(
+        r#"
+package tenants.{tenant}
+
+default allow = false
+
+allow if {{
+    input.spin
+    count([x | some a in numbers.range(1, 300); some b in numbers.range(1, 300); x := a * b]) > 0
+}}
+"#,
+        tenant = tenant
+    )
+}
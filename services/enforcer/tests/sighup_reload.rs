@@ -0,0 +1,65 @@
+#![cfg(unix)]
+
+use std::{collections::HashMap, process::Command, time::Duration};
+
+use edge_policy_enforcer::{
+    config::EnforcerConfig,
+    reload::{reload_on_sighup, RELOAD_ENV_FILE_VAR},
+};
+use tempfile::TempDir;
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    time::sleep,
+};
+use tracing::{dispatcher, Dispatch, Level};
+use tracing_subscriber::{prelude::*, reload, EnvFilter};
+
+fn debug_enabled(dispatch: &Dispatch) -> bool {
+    dispatcher::with_default(dispatch, || tracing::enabled!(Level::DEBUG))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sighup_updates_log_level() {
+    // Registering a listener up front keeps an early SIGHUP from terminating
+    // the test process before the reload task has installed its own.
+    let _guard = signal(SignalKind::hangup()).unwrap();
+
+    let bundles = TempDir::new().unwrap();
+    let env_dir = TempDir::new().unwrap();
+    let env_file = env_dir.path().join("enforcer.env");
+    std::fs::write(&env_file, "LOG_LEVEL=warn\n").unwrap();
+    // The variables are passed through a lookup rather than set on the
+    // process, which other tests running in parallel also read.
+    let vars = HashMap::from([
+        (
+            RELOAD_ENV_FILE_VAR.to_string(),
+            env_file.display().to_string(),
+        ),
+        (
+            "BUNDLES_DIR".to_string(),
+            bundles.path().display().to_string(),
+        ),
+        ("LOG_LEVEL".to_string(), "warn".to_string()),
+    ]);
+    let env = move |name: &str| vars.get(name).cloned();
+
+    let config = EnforcerConfig::load_from(&env).unwrap();
+    let (filter, log_handle) = reload::Layer::new(EnvFilter::new(&config.log_level));
+    let dispatch = Dispatch::new(tracing_subscriber::registry().with(filter));
+    assert!(!debug_enabled(&dispatch));
+
+    let task = tokio::spawn(reload_on_sighup(config, log_handle, env));
+    std::fs::write(&env_file, "LOG_LEVEL=debug\n").unwrap();
+
+    let pid = std::process::id().to_string();
+    for _ in 0..50 {
+        Command::new("kill").args(["-HUP", &pid]).status().unwrap();
+        sleep(Duration::from_millis(50)).await;
+        if debug_enabled(&dispatch) {
+            break;
+        }
+    }
+
+    assert!(debug_enabled(&dispatch));
+    task.abort();
+}
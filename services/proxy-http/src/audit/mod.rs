@@ -0,0 +1,6 @@
+mod sink;
+
+pub use sink::{AuditSink, BypassAuditEvent};
+
+/// Events queued for the audit store before new ones are dropped.
+pub const DEFAULT_AUDIT_QUEUE_CAPACITY: usize = 1024;
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{debug, warn};
+
+use crate::auth::TenantContext;
+use crate::policy::ENFORCEMENT_BYPASSED;
+
+/// Record of a request allowed without an enforcer decision because the
+/// proxy failed open.
+#[derive(Debug, Clone, Serialize)]
+pub struct BypassAuditEvent {
+    pub tenant_id: String,
+    pub user_id: Option<String>,
+    pub device_id: Option<String>,
+    pub request_id: String,
+    pub method: String,
+    pub path: String,
+    /// `enforcer_timeout`, `enforcer_unreachable` or `malformed_decision`.
+    pub reason: String,
+    pub timestamp: String,
+}
+
+impl BypassAuditEvent {
+    pub fn new(context: &TenantContext, method: &str, path: &str, reason: &str) -> Self {
+        Self {
+            tenant_id: context.tenant_id.clone(),
+            user_id: context.user_id.clone(),
+            device_id: context.device_id.clone(),
+            request_id: context.request_id.clone(),
+            method: method.to_string(),
+            path: path.to_string(),
+            reason: reason.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Body for the audit store's `POST /api/audit/logs`.
+    pub fn to_audit_log(&self) -> Value {
+        json!({
+            "tenant_id": self.tenant_id,
+            "timestamp": self.timestamp,
+            "decision": "allow",
+            "protocol": "http",
+            "subject": {
+                "user_id": self.user_id,
+                "device_id": self.device_id,
+            },
+            "action": ENFORCEMENT_BYPASSED,
+            "resource": {
+                "method": self.method,
+                "path": self.path,
+            },
+            "environment": {
+                "request_id": self.request_id,
+            },
+            "policy_version": null,
+            "reason": self.reason,
+        })
+    }
+}
+
+/// Queues audit events without blocking the request path.
+///
+/// Events are delivered from a background task; when the queue is full new
+/// events are dropped with a warning rather than delaying the request.
+#[derive(Clone)]
+pub struct AuditSink {
+    sender: mpsc::Sender<BypassAuditEvent>,
+}
+
+impl AuditSink {
+    /// A sink together with the receiving end of its queue.
+    pub fn channel(capacity: usize) -> (Self, mpsc::Receiver<BypassAuditEvent>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        (Self { sender }, receiver)
+    }
+
+    /// A sink whose events are posted to the audit store at
+    /// `audit_store_url`. Must be called from within a Tokio runtime.
+    pub fn spawn(audit_store_url: &str, capacity: usize, timeout: Duration) -> Result<Self> {
+        let http_client = reqwest::Client::builder().timeout(timeout).build()?;
+        let url = format!("{}/api/audit/logs", audit_store_url.trim_end_matches('/'));
+        let (sink, mut receiver) = Self::channel(capacity);
+
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                match http_client
+                    .post(&url)
+                    .json(&event.to_audit_log())
+                    .send()
+                    .await
+                {
+                    Ok(response) if response.status().is_success() => {
+                        debug!(
+                            tenant_id = %event.tenant_id,
+                            request_id = %event.request_id,
+                            "Recorded enforcement bypass event"
+                        );
+                    }
+                    Ok(response) => {
+                        warn!(
+                            tenant_id = %event.tenant_id,
+                            status = %response.status(),
+                            "Audit store rejected enforcement bypass event"
+                        );
+                    }
+                    Err(e) => {
+                        warn!(
+                            tenant_id = %event.tenant_id,
+                            error = %e,
+                            "Failed to send enforcement bypass event to audit store"
+                        );
+                    }
+                }
+            }
+        });
+
+        Ok(sink)
+    }
+
+    pub fn record(&self, event: BypassAuditEvent) {
+        match self.sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => {
+                warn!(
+                    tenant_id = %event.tenant_id,
+                    request_id = %event.request_id,
+                    "Audit queue full, dropping enforcement bypass event"
+                );
+            }
+            Err(TrySendError::Closed(event)) => {
+                warn!(
+                    tenant_id = %event.tenant_id,
+                    request_id = %event.request_id,
+                    "Audit queue closed, dropping enforcement bypass event"
+                );
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use http::StatusCode;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -8,6 +9,12 @@ pub enum AuthError {
     #[error("Invalid certificate: {0}")]
     InvalidCertificate(String),
 
+    #[error("Certificate expired at {0}")]
+    CertificateExpired(String),
+
+    #[error("Certificate not valid before {0}")]
+    CertificateNotYetValid(String),
+
     #[error("Invalid JWT: {0}")]
     InvalidJwt(String),
 
@@ -22,6 +29,12 @@ pub enum AuthError {
         cert_tenant: String,
         jwt_tenant: String,
     },
+
+    #[error("Tenant ID mismatch: {authenticated_tenant} authenticated, tenant header has {header_tenant}")]
+    TenantHeaderMismatch {
+        authenticated_tenant: String,
+        header_tenant: String,
+    },
 }
 
 impl From<jsonwebtoken::errors::Error> for AuthError {
@@ -48,3 +61,35 @@ impl From<x509_parser::nom::Err<x509_parser::error::X509Error>> for AuthError {
         }
     }
 }
+
+impl AuthError {
+    pub fn to_status_code(&self) -> StatusCode {
+        match self {
+            AuthError::TenantIdMismatch { .. } | AuthError::TenantHeaderMismatch { .. } => {
+                StatusCode::FORBIDDEN
+            }
+            AuthError::TenantIdNotFound
+            | AuthError::InvalidCertificate(_)
+            | AuthError::CertificateExpired(_)
+            | AuthError::CertificateNotYetValid(_)
+            | AuthError::InvalidJwt(_)
+            | AuthError::MissingAuthHeader
+            | AuthError::UnsupportedAuthMethod => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// Stable machine-readable code returned in the `error` field of responses.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            AuthError::TenantIdNotFound => "TENANT_NOT_FOUND",
+            AuthError::InvalidCertificate(_) => "INVALID_CERTIFICATE",
+            AuthError::CertificateExpired(_) => "CERTIFICATE_EXPIRED",
+            AuthError::CertificateNotYetValid(_) => "CERTIFICATE_NOT_YET_VALID",
+            AuthError::InvalidJwt(_) => "INVALID_JWT",
+            AuthError::MissingAuthHeader => "MISSING_AUTH_HEADER",
+            AuthError::UnsupportedAuthMethod => "UNSUPPORTED_AUTH_METHOD",
+            AuthError::TenantIdMismatch { .. } => "TENANT_MISMATCH",
+            AuthError::TenantHeaderMismatch { .. } => "TENANT_HEADER_MISMATCH",
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use super::{AuthError, AuthMethod, TenantContext, AUTHORIZATION_HEADER, TENANT_ID_HEADER};
+use super::{AuthError, AuthMethod, TenantContext, AUTHORIZATION_HEADER};
 use crate::config::{JwtAlgorithm, ProxyConfig};
 use anyhow::Context;
-use http::HeaderMap;
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use http::{HeaderMap, HeaderName};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use tracing::{debug, info, warn};
 use x509_parser::prelude::*;
@@ -22,79 +23,106 @@ struct JwtClaims {
     exp: Option<usize>,
 }
 
+/// Key and validation rules for tokens signed with one algorithm.
+struct JwtVerifier {
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
 pub struct TenantExtractor {
     enable_mtls: bool,
     enable_jwt: bool,
-    jwt_decoding_key: Option<DecodingKey>,
-    jwt_validation: Option<Validation>,
+    /// One verifier per allowed algorithm, chosen by the token's `alg`.
+    jwt_verifiers: HashMap<Algorithm, JwtVerifier>,
+    tenant_id_header: HeaderName,
 }
 
 impl TenantExtractor {
     pub fn new(config: &ProxyConfig) -> anyhow::Result<Self> {
-        let (jwt_decoding_key, jwt_validation) = if config.enable_jwt {
-            let algorithm = match config.jwt_algorithm {
-                JwtAlgorithm::HS256 => Algorithm::HS256,
-                JwtAlgorithm::HS384 => Algorithm::HS384,
-                JwtAlgorithm::HS512 => Algorithm::HS512,
-                JwtAlgorithm::RS256 => Algorithm::RS256,
-                JwtAlgorithm::RS384 => Algorithm::RS384,
-                JwtAlgorithm::RS512 => Algorithm::RS512,
-                JwtAlgorithm::ES256 => Algorithm::ES256,
-                JwtAlgorithm::ES384 => Algorithm::ES384,
-            };
-
-            let decoding_key = match config.jwt_algorithm {
-                JwtAlgorithm::HS256 | JwtAlgorithm::HS384 | JwtAlgorithm::HS512 => {
-                    let secret = config
-                        .jwt_secret
-                        .as_ref()
-                        .context("JWT secret missing for HMAC algorithm")?;
-                    DecodingKey::from_secret(secret.as_bytes())
-                }
-                JwtAlgorithm::RS256 | JwtAlgorithm::RS384 | JwtAlgorithm::RS512 => {
-                    let key_path = config
-                        .jwt_public_key_path
-                        .as_ref()
-                        .context("JWT public key path missing for RSA algorithm")?;
-                    let key_data = fs::read(key_path)?;
-                    DecodingKey::from_rsa_pem(&key_data)?
+        let mut jwt_verifiers = HashMap::new();
+        if config.enable_jwt {
+            for configured in config.allowed_jwt_algorithms() {
+                let algorithm = match configured {
+                    JwtAlgorithm::HS256 => Algorithm::HS256,
+                    JwtAlgorithm::HS384 => Algorithm::HS384,
+                    JwtAlgorithm::HS512 => Algorithm::HS512,
+                    JwtAlgorithm::RS256 => Algorithm::RS256,
+                    JwtAlgorithm::RS384 => Algorithm::RS384,
+                    JwtAlgorithm::RS512 => Algorithm::RS512,
+                    JwtAlgorithm::ES256 => Algorithm::ES256,
+                    JwtAlgorithm::ES384 => Algorithm::ES384,
+                };
+
+                let decoding_key = match configured {
+                    JwtAlgorithm::HS256 | JwtAlgorithm::HS384 | JwtAlgorithm::HS512 => {
+                        let secret = config
+                            .jwt_secret
+                            .as_ref()
+                            .context("JWT secret missing for HMAC algorithm")?;
+                        DecodingKey::from_secret(secret.as_bytes())
+                    }
+                    JwtAlgorithm::RS256 | JwtAlgorithm::RS384 | JwtAlgorithm::RS512 => {
+                        let key_path = config
+                            .jwt_public_key_path_for(configured)
+                            .context("JWT public key path missing for RSA algorithm")?;
+                        let key_data = fs::read(key_path)?;
+                        DecodingKey::from_rsa_pem(&key_data)?
+                    }
+                    JwtAlgorithm::ES256 | JwtAlgorithm::ES384 => {
+                        let key_path = config
+                            .jwt_public_key_path_for(configured)
+                            .context("JWT public key path missing for ECDSA algorithm")?;
+                        let key_data = fs::read(key_path)?;
+                        DecodingKey::from_ec_pem(&key_data)?
+                    }
+                };
+
+                let mut validation = Validation::new(algorithm);
+                validation.algorithms = vec![algorithm];
+                validation.leeway = config.jwt_leeway_secs;
+                validation.validate_nbf = true;
+
+                let mut required_claims = Vec::new();
+                if config.jwt_require_exp {
+                    required_claims.push("exp");
                 }
-                JwtAlgorithm::ES256 | JwtAlgorithm::ES384 => {
-                    let key_path = config
-                        .jwt_public_key_path
-                        .as_ref()
-                        .context("JWT public key path missing for ECDSA algorithm")?;
-                    let key_data = fs::read(key_path)?;
-                    DecodingKey::from_ec_pem(&key_data)?
+                if config.jwt_require_nbf {
+                    required_claims.push("nbf");
                 }
-            };
+                validation.set_required_spec_claims(&required_claims);
 
-            let mut validation = Validation::new(algorithm);
-            validation.algorithms = vec![algorithm];
+                if let Some(issuer) = &config.jwt_issuer {
+                    validation.set_issuer(&[issuer]);
+                }
 
-            if let Some(issuer) = &config.jwt_issuer {
-                validation.set_issuer(&[issuer]);
-            }
+                if let Some(audience) = &config.jwt_audience {
+                    validation.set_audience(&[audience]);
+                }
 
-            if let Some(audience) = &config.jwt_audience {
-                validation.set_audience(&[audience]);
+                jwt_verifiers.insert(
+                    algorithm,
+                    JwtVerifier {
+                        decoding_key,
+                        validation,
+                    },
+                );
             }
+        }
 
-            (Some(decoding_key), Some(validation))
-        } else {
-            (None, None)
-        };
+        let tenant_id_header = HeaderName::from_bytes(config.tenant_id_header.as_bytes())
+            .with_context(|| format!("Invalid tenant ID header '{}'", config.tenant_id_header))?;
 
         Ok(Self {
             enable_mtls: config.enable_mtls,
             enable_jwt: config.enable_jwt,
-            jwt_decoding_key,
-            jwt_validation,
+            jwt_verifiers,
+            tenant_id_header,
         })
     }
 
     pub fn extract_from_certificate(&self, cert_der: &[u8]) -> Result<TenantContext, AuthError> {
         let (_, cert) = parse_x509_certificate(cert_der)?;
+        check_validity(&cert)?;
 
         debug!("Parsing X.509 certificate for tenant ID");
 
@@ -128,19 +156,21 @@ impl TenantExtractor {
     }
 
     pub fn extract_from_jwt(&self, token: &str) -> Result<TenantContext, AuthError> {
-        let decoding_key = self
-            .jwt_decoding_key
-            .as_ref()
-            .ok_or(AuthError::UnsupportedAuthMethod)?;
+        if self.jwt_verifiers.is_empty() {
+            return Err(AuthError::UnsupportedAuthMethod);
+        }
 
-        let validation = self
-            .jwt_validation
-            .as_ref()
-            .ok_or(AuthError::UnsupportedAuthMethod)?;
+        // The header's `alg` only selects among the allowed algorithms, so a
+        // token cannot pick how it is verified. `none` is not an algorithm
+        // the header parser accepts.
+        let header = decode_header(token)?;
+        let verifier = self.jwt_verifiers.get(&header.alg).ok_or_else(|| {
+            AuthError::InvalidJwt(format!("algorithm {:?} is not allowed", header.alg))
+        })?;
 
-        debug!("Decoding JWT token");
+        debug!(algorithm = ?header.alg, "Decoding JWT token");
 
-        let token_data = decode::<JwtClaims>(token, decoding_key, validation)?;
+        let token_data = decode::<JwtClaims>(token, &verifier.decoding_key, &verifier.validation)?;
         let claims = token_data.claims;
 
         // Extract tenant ID from claims (try multiple fields)
@@ -195,6 +225,15 @@ impl TenantExtractor {
                             debug!("Successfully extracted context from mTLS certificate");
                             mtls_context = Some(ctx);
                         }
+                        // A certificate outside its validity window must not
+                        // fall back to a weaker identity.
+                        Err(
+                            e @ (AuthError::CertificateExpired(_)
+                            | AuthError::CertificateNotYetValid(_)),
+                        ) => {
+                            warn!(error = %e, "Rejected client certificate");
+                            return Err(e);
+                        }
                         Err(e) => {
                             warn!(error = %e, "Failed to extract tenant from certificate");
                         }
@@ -223,7 +262,7 @@ impl TenantExtractor {
         }
 
         // Merge contexts or validate consistency
-        match (mtls_context, jwt_context) {
+        let context = match (mtls_context, jwt_context) {
             (Some(mtls_ctx), Some(jwt_ctx)) => {
                 // Both methods succeeded, verify tenant IDs match
                 if mtls_ctx.tenant_id != jwt_ctx.tenant_id {
@@ -243,18 +282,22 @@ impl TenantExtractor {
                 if !jwt_ctx.roles.is_empty() {
                     merged.roles = jwt_ctx.roles;
                 }
-                Ok(merged)
+                merged
             }
             (Some(ctx), None) | (None, Some(ctx)) => {
                 // One method succeeded
-                Ok(ctx)
+                ctx
             }
             (None, None) => {
                 // Neither method succeeded, try fallback header for testing
                 if !self.enable_mtls && !self.enable_jwt {
-                    if let Some(tenant_header) = headers.get(TENANT_ID_HEADER) {
+                    if let Some(tenant_header) = headers.get(&self.tenant_id_header) {
                         if let Ok(tenant_id) = tenant_header.to_str() {
-                            info!(tenant_id = %tenant_id, "Using X-Tenant-ID header (testing mode)");
+                            info!(
+                                tenant_id = %tenant_id,
+                                header = %self.tenant_id_header,
+                                "Using tenant ID header (testing mode)"
+                            );
                             return Ok(TenantContext::new(
                                 tenant_id.to_string(),
                                 AuthMethod::Header,
@@ -262,29 +305,79 @@ impl TenantExtractor {
                         }
                     }
                 }
-                Err(AuthError::TenantIdNotFound)
+                return Err(AuthError::TenantIdNotFound);
+            }
+        };
+
+        // A tenant header sent alongside a certificate or token must name the
+        // same tenant, so it can never override the cryptographic identity
+        if let Some(tenant_header) = headers.get(&self.tenant_id_header) {
+            let header_tenant = tenant_header.to_str().unwrap_or_default();
+            if header_tenant != context.tenant_id {
+                warn!(
+                    tenant_id = %context.tenant_id,
+                    header_tenant = %header_tenant,
+                    "Tenant header disagrees with authenticated identity"
+                );
+                return Err(AuthError::TenantHeaderMismatch {
+                    authenticated_tenant: context.tenant_id,
+                    header_tenant: header_tenant.to_string(),
+                });
             }
         }
+
+        Ok(context)
     }
 }
 
+/// Rejects certificates whose `notBefore`/`notAfter` window does not contain
+/// the current time.
+fn check_validity(cert: &X509Certificate<'_>) -> Result<(), AuthError> {
+    let validity = cert.validity();
+    let now = ASN1Time::now();
+    if now < validity.not_before {
+        return Err(AuthError::CertificateNotYetValid(
+            validity.not_before.to_string(),
+        ));
+    }
+    if now > validity.not_after {
+        return Err(AuthError::CertificateExpired(
+            validity.not_after.to_string(),
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::ProxyConfig;
+    use crate::auth::TENANT_ID_HEADER;
+    use crate::config::{
+        EnforcerFailureMode, MalformedDecisionMode, ProxyConfig, DEFAULT_DEBUG_BODY_MAX_BYTES,
+    };
+    use crate::policy::DEFAULT_CACHE_WARMUP_INPUTS;
+    use crate::redaction::MAX_REDACTION_DEPTH;
     use chrono::{Duration, Utc};
     use jsonwebtoken::{encode, EncodingKey, Header};
+    use std::collections::{BTreeMap, BTreeSet};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
     const RSA_PRIVATE_KEY: &str = include_str!("fixtures/test-rsa-private.pem");
     const RSA_PUBLIC_KEY: &str = include_str!("fixtures/test-rsa-public.pem");
+    const EC_PRIVATE_KEY: &str = include_str!("fixtures/test-ec-private.pem");
+    const EC_PUBLIC_KEY: &str = include_str!("fixtures/test-ec-public.pem");
+    /// Self-signed client certificate with `CN=tenant-a`.
+    const CLIENT_CERT_DER: &[u8] = include_bytes!("fixtures/test-client-cert.der");
+    /// Self-signed `CN=tenant-a` certificate valid only during 2020.
+    const EXPIRED_CLIENT_CERT_DER: &[u8] = include_bytes!("fixtures/test-expired-client-cert.der");
 
     fn base_config() -> ProxyConfig {
         ProxyConfig {
             host: "127.0.0.1".to_string(),
             port: 0,
             upstream_url: "http://localhost:9000".to_string(),
+            tenant_upstreams: BTreeMap::new(),
             request_timeout_secs: 5,
             max_body_size_bytes: 1024,
             enforcer_url: "http://localhost:8181".to_string(),
@@ -298,11 +391,31 @@ mod tests {
             jwt_issuer: None,
             jwt_audience: None,
             jwt_algorithm: JwtAlgorithm::RS256,
+            jwt_algorithms: Vec::new(),
+            jwt_ec_public_key_path: None,
+            jwt_leeway_secs: 60,
+            jwt_require_exp: true,
+            jwt_require_nbf: false,
             forward_auth_header: false,
             log_level: "warn".to_string(),
             quota_tracker_url: None,
             quota_tracker_token: None,
             default_region: None,
+            policy_cache_ttl_ms: 0,
+            policy_cache_obligations: false,
+            policy_cache_warmup: false,
+            policy_cache_warmup_inputs: DEFAULT_CACHE_WARMUP_INPUTS,
+            audit_store_url: None,
+            malformed_decision_mode: MalformedDecisionMode::Deny,
+            enforcer_failure_mode: EnforcerFailureMode::Closed,
+            strict_redaction: false,
+            redaction_warnings_header: false,
+            max_redaction_depth: MAX_REDACTION_DEPTH,
+            tenant_id_header: TENANT_ID_HEADER.to_string(),
+            shadow_mode_tenants: BTreeSet::new(),
+            debug_body_tenants: BTreeSet::new(),
+            debug_body_max_bytes: DEFAULT_DEBUG_BODY_MAX_BYTES,
+            bypass_paths: Vec::new(),
         }
     }
 
@@ -401,4 +514,271 @@ mod tests {
         let result = extractor.extract_from_jwt(&mismatched);
         assert!(matches!(result, Err(AuthError::InvalidJwt(_))));
     }
+
+    #[test]
+    fn mixed_allowlist_selects_verifier_from_token_header() {
+        let mut rsa_key_file = NamedTempFile::new().expect("RSA public key file");
+        rsa_key_file
+            .write_all(RSA_PUBLIC_KEY.as_bytes())
+            .expect("write RSA public key");
+        let mut ec_key_file = NamedTempFile::new().expect("EC public key file");
+        ec_key_file
+            .write_all(EC_PUBLIC_KEY.as_bytes())
+            .expect("write EC public key");
+
+        let mut config = base_config();
+        config.enable_jwt = true;
+        config.jwt_algorithms = vec![JwtAlgorithm::RS256, JwtAlgorithm::ES256];
+        config.jwt_public_key_path = Some(rsa_key_file.path().to_path_buf());
+        config.jwt_ec_public_key_path = Some(ec_key_file.path().to_path_buf());
+        config.validate().expect("mixed allowlist should validate");
+
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+        let claims = JwtClaims {
+            sub: Some("user-1".to_string()),
+            tenant_id: Some("tenant-mixed".to_string()),
+            tid: None,
+            organization_id: None,
+            roles: None,
+            scope: None,
+            device_id: None,
+            iss: None,
+            aud: None,
+            exp: Some((Utc::now() + Duration::hours(1)).timestamp() as usize),
+        };
+
+        let rs256_token = encode(
+            &Header::new(Algorithm::RS256),
+            &claims,
+            &EncodingKey::from_rsa_pem(RSA_PRIVATE_KEY.as_bytes()).expect("load RSA key"),
+        )
+        .expect("token should encode");
+        let context = extractor
+            .extract_from_jwt(&rs256_token)
+            .expect("RS256 token should validate");
+        assert_eq!(context.tenant_id, "tenant-mixed");
+
+        let es256_token = encode(
+            &Header::new(Algorithm::ES256),
+            &claims,
+            &EncodingKey::from_ec_pem(EC_PRIVATE_KEY.as_bytes()).expect("load EC key"),
+        )
+        .expect("token should encode");
+        let context = extractor
+            .extract_from_jwt(&es256_token)
+            .expect("ES256 token should validate");
+        assert_eq!(context.tenant_id, "tenant-mixed");
+
+        // HMAC signed with the RSA public key, the classic algorithm confusion
+        let hs256_token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(RSA_PUBLIC_KEY.as_bytes()),
+        )
+        .expect("token should encode");
+        let result = extractor.extract_from_jwt(&hs256_token);
+        assert!(matches!(result, Err(AuthError::InvalidJwt(_))));
+
+        let payload = rs256_token.split('.').nth(1).expect("token has a payload");
+        let unsigned_token = format!("eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0.{}.", payload);
+        let result = extractor.extract_from_jwt(&unsigned_token);
+        assert!(matches!(result, Err(AuthError::InvalidJwt(_))));
+    }
+
+    fn hs256_config(leeway_secs: u64) -> ProxyConfig {
+        let mut config = base_config();
+        config.enable_jwt = true;
+        config.jwt_algorithm = JwtAlgorithm::HS256;
+        config.jwt_secret = Some("super-secret".to_string());
+        config.jwt_leeway_secs = leeway_secs;
+        config
+    }
+
+    fn hs256_token(config: &ProxyConfig, exp: Option<usize>) -> String {
+        let claims = JwtClaims {
+            sub: None,
+            tenant_id: Some("tenant-skew".to_string()),
+            tid: None,
+            organization_id: None,
+            roles: None,
+            scope: None,
+            device_id: None,
+            iss: None,
+            aud: None,
+            exp,
+        };
+
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(config.jwt_secret.as_ref().unwrap().as_bytes()),
+        )
+        .expect("token should encode")
+    }
+
+    #[test]
+    fn expired_tokens_are_accepted_within_leeway() {
+        let config = hs256_config(120);
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+
+        let exp = (Utc::now() - Duration::seconds(60)).timestamp() as usize;
+        let context = extractor
+            .extract_from_jwt(&hs256_token(&config, Some(exp)))
+            .expect("token within leeway should validate");
+        assert_eq!(context.tenant_id, "tenant-skew");
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected_beyond_leeway() {
+        let config = hs256_config(120);
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+
+        let exp = (Utc::now() - Duration::seconds(300)).timestamp() as usize;
+        let result = extractor.extract_from_jwt(&hs256_token(&config, Some(exp)));
+        assert!(matches!(result, Err(AuthError::InvalidJwt(_))));
+    }
+
+    #[test]
+    fn tokens_without_exp_are_rejected_when_required() {
+        let mut config = hs256_config(0);
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+
+        let result = extractor.extract_from_jwt(&hs256_token(&config, None));
+        assert!(matches!(result, Err(AuthError::InvalidJwt(_))));
+
+        config.jwt_require_exp = false;
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+        let context = extractor
+            .extract_from_jwt(&hs256_token(&config, None))
+            .expect("exp should be optional when not required");
+        assert_eq!(context.tenant_id, "tenant-skew");
+    }
+
+    #[test]
+    fn unsigned_tokens_are_rejected() {
+        let config = hs256_config(0);
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+
+        let exp = (Utc::now() + Duration::hours(1)).timestamp() as usize;
+        let token = hs256_token(&config, Some(exp));
+        extractor
+            .extract_from_jwt(&token)
+            .expect("signed token should validate");
+
+        let payload = token.split('.').nth(1).expect("token has a payload");
+        // {"alg":"none","typ":"JWT"} and {"alg":"None","typ":"JWT"}
+        let alg_none = format!("eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0.{}.", payload);
+        let alg_none_capitalized = format!("eyJhbGciOiJOb25lIiwidHlwIjoiSldUIn0.{}.", payload);
+        // An allowed algorithm in the header with the signature stripped
+        let (signed_part, _) = token.rsplit_once('.').expect("token has a signature");
+        let stripped = format!("{}.", signed_part);
+
+        for unsigned in [alg_none, alg_none_capitalized, stripped] {
+            let result = extractor.extract_from_jwt(&unsigned);
+            assert!(
+                matches!(result, Err(AuthError::InvalidJwt(_))),
+                "unsigned token was not rejected: {}",
+                unsigned
+            );
+        }
+    }
+
+    #[test]
+    fn tenant_header_name_is_configurable() {
+        let mut config = base_config();
+        config.tenant_id_header = "X-Org-ID".to_string();
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(TENANT_ID_HEADER, "tenant-default".parse().unwrap());
+        assert!(matches!(
+            extractor.extract_from_request(&headers, None),
+            Err(AuthError::TenantIdNotFound)
+        ));
+
+        headers.insert("x-org-id", "tenant-org".parse().unwrap());
+        let context = extractor
+            .extract_from_request(&headers, None)
+            .expect("tenant should come from the custom header");
+        assert_eq!(context.tenant_id, "tenant-org");
+        assert_eq!(context.auth_method, AuthMethod::Header);
+    }
+
+    #[test]
+    fn tenant_header_must_match_certificate() {
+        let mut config = base_config();
+        config.enable_mtls = true;
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+        let certs = vec![CLIENT_CERT_DER.to_vec()];
+
+        let mut headers = HeaderMap::new();
+        let context = extractor
+            .extract_from_request(&headers, Some(&certs))
+            .expect("certificate alone should authenticate");
+        assert_eq!(context.tenant_id, "tenant-a");
+
+        headers.insert(TENANT_ID_HEADER, "tenant-a".parse().unwrap());
+        assert!(extractor
+            .extract_from_request(&headers, Some(&certs))
+            .is_ok());
+
+        headers.insert(TENANT_ID_HEADER, "tenant-b".parse().unwrap());
+        match extractor.extract_from_request(&headers, Some(&certs)) {
+            Err(AuthError::TenantHeaderMismatch {
+                authenticated_tenant,
+                header_tenant,
+            }) => {
+                assert_eq!(authenticated_tenant, "tenant-a");
+                assert_eq!(header_tenant, "tenant-b");
+            }
+            other => panic!("expected header mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn tenant_header_must_match_jwt() {
+        let config = hs256_config(0);
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+        let exp = (Utc::now() + Duration::hours(1)).timestamp() as usize;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION_HEADER,
+            format!("Bearer {}", hs256_token(&config, Some(exp)))
+                .parse()
+                .unwrap(),
+        );
+        headers.insert(TENANT_ID_HEADER, "tenant-spoofed".parse().unwrap());
+
+        assert!(matches!(
+            extractor.extract_from_request(&headers, None),
+            Err(AuthError::TenantHeaderMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn certificates_outside_validity_window_are_rejected() {
+        let mut config = base_config();
+        config.enable_mtls = true;
+        let extractor = TenantExtractor::new(&config).expect("extractor should initialize");
+
+        let context = extractor
+            .extract_from_certificate(CLIENT_CERT_DER)
+            .expect("valid certificate should authenticate");
+        assert_eq!(context.tenant_id, "tenant-a");
+
+        match extractor.extract_from_certificate(EXPIRED_CLIENT_CERT_DER) {
+            Err(AuthError::CertificateExpired(not_after)) => assert!(not_after.contains("2021")),
+            other => panic!("expected expired certificate, got {other:?}"),
+        }
+
+        // The tenant header must not stand in for a rejected certificate.
+        let mut headers = HeaderMap::new();
+        headers.insert(TENANT_ID_HEADER, "tenant-a".parse().unwrap());
+        let certs = vec![EXPIRED_CLIENT_CERT_DER.to_vec()];
+        assert!(matches!(
+            extractor.extract_from_request(&headers, Some(&certs)),
+            Err(AuthError::CertificateExpired(_))
+        ));
+    }
 }
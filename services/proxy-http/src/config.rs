@@ -1,9 +1,28 @@
+use crate::auth::TENANT_ID_HEADER;
+use crate::policy::DEFAULT_CACHE_WARMUP_INPUTS;
+use crate::proxy::BypassPaths;
+use crate::redaction::MAX_REDACTION_DEPTH;
 use anyhow::{Context, Result};
+use edge_policy_telemetry::process_env;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+/// Environment variable naming a TOML file read by [`ProxyConfig::load`]
+pub const CONFIG_FILE_ENV: &str = "PROXY_CONFIG_FILE";
+
+/// Default cap on each body written by tenant debug body logging
+pub const DEFAULT_DEBUG_BODY_MAX_BYTES: usize = 4096;
+
+/// Shown in place of secret values in the configuration dump
+pub const REDACTED_SECRET: &str = "***";
+
+/// Fields replaced by [`REDACTED_SECRET`] in [`ProxyConfig::dump`]
+const SECRET_FIELDS: &[&str] = &["jwt_secret", "quota_tracker_token"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProxyConfig {
     /// Listen host address
     pub host: String,
@@ -14,6 +33,10 @@ pub struct ProxyConfig {
     /// Upstream backend URL
     pub upstream_url: String,
 
+    /// Per-tenant upstream URLs, falling back to `upstream_url` when absent
+    #[serde(default)]
+    pub tenant_upstreams: BTreeMap<String, String>,
+
     /// Request timeout in seconds
     pub request_timeout_secs: u64,
 
@@ -53,6 +76,25 @@ pub struct ProxyConfig {
     /// JWT algorithm (HS256, RS256, ES256)
     pub jwt_algorithm: JwtAlgorithm,
 
+    /// Algorithms accepted in the token header; empty accepts only
+    /// `jwt_algorithm`
+    #[serde(default)]
+    pub jwt_algorithms: Vec<JwtAlgorithm>,
+
+    /// Public key for ECDSA tokens when RSA tokens are also accepted;
+    /// falls back to `jwt_public_key_path`
+    #[serde(default)]
+    pub jwt_ec_public_key_path: Option<PathBuf>,
+
+    /// Clock-skew allowance in seconds for JWT `exp` and `nbf` checks
+    pub jwt_leeway_secs: u64,
+
+    /// Reject JWTs without an `exp` claim
+    pub jwt_require_exp: bool,
+
+    /// Reject JWTs without an `nbf` claim
+    pub jwt_require_nbf: bool,
+
     /// Forward Authorization header to upstream
     pub forward_auth_header: bool,
 
@@ -67,9 +109,136 @@ pub struct ProxyConfig {
 
     /// Default region for requests
     pub default_region: Option<String>,
+
+    /// How long enforcer decisions are cached in milliseconds (0 disables caching)
+    #[serde(default)]
+    pub policy_cache_ttl_ms: u64,
+
+    /// Cache decisions that carry obligations such as redaction paths
+    #[serde(default)]
+    pub policy_cache_obligations: bool,
+
+    /// Replay recent policy inputs from the audit store at startup to fill
+    /// the decision cache
+    #[serde(default)]
+    pub policy_cache_warmup: bool,
+
+    /// Distinct recent inputs replayed per tenant during cache warm-up
+    #[serde(default = "default_policy_cache_warmup_inputs")]
+    pub policy_cache_warmup_inputs: usize,
+
+    /// Audit store URL, read for recent policy inputs during cache warm-up
+    /// and sent `ENFORCEMENT_BYPASSED` events
+    #[serde(default)]
+    pub audit_store_url: Option<String>,
+
+    /// Decision applied when the enforcer answers without a usable `result.allow`
+    #[serde(default)]
+    pub malformed_decision_mode: MalformedDecisionMode,
+
+    /// Decision applied when the enforcer is unreachable or times out
+    #[serde(default)]
+    pub enforcer_failure_mode: EnforcerFailureMode,
+
+    /// Log a warning for redaction paths that match nothing in the response
+    #[serde(default)]
+    pub strict_redaction: bool,
+
+    /// In strict redaction mode, list unmatched paths in `X-Redaction-Warnings`
+    #[serde(default)]
+    pub redaction_warnings_header: bool,
+
+    /// Deepest JSON nesting searched for redaction paths; fields below it are
+    /// reported as possibly unredacted
+    #[serde(default = "default_max_redaction_depth")]
+    pub max_redaction_depth: usize,
+
+    /// Header read for the tenant ID when neither mTLS nor JWT is enabled
+    #[serde(default = "default_tenant_id_header")]
+    pub tenant_id_header: String,
+
+    /// Tenants whose denials are logged but not enforced
+    #[serde(default)]
+    pub shadow_mode_tenants: BTreeSet<String>,
+
+    /// Tenants whose request and redacted response bodies are logged for
+    /// debugging; empty disables body logging
+    #[serde(default)]
+    pub debug_body_tenants: BTreeSet<String>,
+
+    /// Bytes of each body written to the debug log before it is cut off
+    #[serde(default = "default_debug_body_max_bytes")]
+    pub debug_body_max_bytes: usize,
+
+    /// Upstream paths such as `/healthz` forwarded without tenant extraction
+    /// or policy evaluation; see [`BypassPaths`]
+    #[serde(default)]
+    pub bypass_paths: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+fn default_tenant_id_header() -> String {
+    TENANT_ID_HEADER.to_string()
+}
+
+fn default_policy_cache_warmup_inputs() -> usize {
+    DEFAULT_CACHE_WARMUP_INPUTS
+}
+
+fn default_max_redaction_depth() -> usize {
+    MAX_REDACTION_DEPTH
+}
+
+fn default_debug_body_max_bytes() -> usize {
+    DEFAULT_DEBUG_BODY_MAX_BYTES
+}
+
+/// How the proxy treats an enforcer response that has no usable decision.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MalformedDecisionMode {
+    /// Reject the request (fail closed).
+    #[default]
+    Deny,
+    /// Forward the request as if it had been allowed (fail open).
+    Allow,
+}
+
+impl std::str::FromStr for MalformedDecisionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "deny" => Ok(MalformedDecisionMode::Deny),
+            "allow" => Ok(MalformedDecisionMode::Allow),
+            _ => anyhow::bail!("Unsupported malformed decision mode: {}", s),
+        }
+    }
+}
+
+/// How the proxy treats a request whose policy query got no answer.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EnforcerFailureMode {
+    /// Reject the request with `503 Service Unavailable`.
+    #[default]
+    Closed,
+    /// Forward the request as if it had been allowed.
+    Open,
+}
+
+impl std::str::FromStr for EnforcerFailureMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "closed" => Ok(EnforcerFailureMode::Closed),
+            "open" => Ok(EnforcerFailureMode::Open),
+            _ => anyhow::bail!("Unsupported enforcer failure mode: {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum JwtAlgorithm {
     HS256,
     HS384,
@@ -81,6 +250,19 @@ pub enum JwtAlgorithm {
     ES384,
 }
 
+impl JwtAlgorithm {
+    pub fn is_hmac(self) -> bool {
+        matches!(
+            self,
+            JwtAlgorithm::HS256 | JwtAlgorithm::HS384 | JwtAlgorithm::HS512
+        )
+    }
+
+    pub fn is_ecdsa(self) -> bool {
+        matches!(self, JwtAlgorithm::ES256 | JwtAlgorithm::ES384)
+    }
+}
+
 impl Default for JwtAlgorithm {
     fn default() -> Self {
         JwtAlgorithm::RS256
@@ -105,95 +287,206 @@ impl std::str::FromStr for JwtAlgorithm {
     }
 }
 
-impl ProxyConfig {
-    /// Load configuration from environment variables
-    pub fn from_env() -> Result<Self> {
-        let host = std::env::var("PROXY_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
-        let port = std::env::var("PROXY_PORT")
-            .unwrap_or_else(|_| "8080".to_string())
-            .parse()
-            .context("Invalid PROXY_PORT")?;
-
-        let upstream_url =
-            std::env::var("UPSTREAM_URL").unwrap_or_else(|_| "http://localhost:8000".to_string());
-
-        let request_timeout_secs = std::env::var("REQUEST_TIMEOUT_SECS")
-            .unwrap_or_else(|_| "30".to_string())
-            .parse()
-            .context("Invalid REQUEST_TIMEOUT_SECS")?;
-
-        let max_body_size_bytes = std::env::var("MAX_BODY_SIZE_BYTES")
-            .unwrap_or_else(|_| "10485760".to_string()) // 10MB
-            .parse()
-            .context("Invalid MAX_BODY_SIZE_BYTES")?;
-
-        let enforcer_url =
-            std::env::var("ENFORCER_URL").unwrap_or_else(|_| "http://127.0.0.1:8181".to_string());
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 8080,
+            upstream_url: "http://localhost:8000".to_string(),
+            tenant_upstreams: BTreeMap::new(),
+            request_timeout_secs: 30,
+            max_body_size_bytes: 10 * 1024 * 1024,
+            enforcer_url: "http://127.0.0.1:8181".to_string(),
+            enable_mtls: false,
+            tls_cert_path: None,
+            tls_key_path: None,
+            tls_client_ca_path: None,
+            enable_jwt: false,
+            jwt_secret: None,
+            jwt_public_key_path: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            jwt_algorithm: JwtAlgorithm::default(),
+            jwt_algorithms: Vec::new(),
+            jwt_ec_public_key_path: None,
+            jwt_leeway_secs: 60,
+            jwt_require_exp: true,
+            jwt_require_nbf: false,
+            forward_auth_header: false,
+            log_level: "info".to_string(),
+            quota_tracker_url: None,
+            quota_tracker_token: None,
+            default_region: None,
+            policy_cache_ttl_ms: 0,
+            policy_cache_obligations: false,
+            policy_cache_warmup: false,
+            policy_cache_warmup_inputs: DEFAULT_CACHE_WARMUP_INPUTS,
+            audit_store_url: None,
+            malformed_decision_mode: MalformedDecisionMode::default(),
+            enforcer_failure_mode: EnforcerFailureMode::default(),
+            strict_redaction: false,
+            redaction_warnings_header: false,
+            max_redaction_depth: MAX_REDACTION_DEPTH,
+            tenant_id_header: default_tenant_id_header(),
+            shadow_mode_tenants: BTreeSet::new(),
+            debug_body_tenants: BTreeSet::new(),
+            debug_body_max_bytes: DEFAULT_DEBUG_BODY_MAX_BYTES,
+            bypass_paths: Vec::new(),
+        }
+    }
+}
 
-        let enable_mtls = std::env::var("ENABLE_MTLS")
-            .unwrap_or_else(|_| "false".to_string())
-            .parse()
-            .context("Invalid ENABLE_MTLS")?;
+impl ProxyConfig {
+    /// Load the TOML file named by `PROXY_CONFIG_FILE` when it is set,
+    /// otherwise configuration from environment variables alone
+    pub fn load() -> Result<Self> {
+        Self::load_from(&process_env)
+    }
 
-        let tls_cert_path = std::env::var("TLS_CERT_PATH").ok().map(PathBuf::from);
+    /// Like [`ProxyConfig::load`], reading variables through `env` instead of
+    /// the process environment
+    pub fn load_from(env: &dyn Fn(&str) -> Option<String>) -> Result<Self> {
+        match env(CONFIG_FILE_ENV) {
+            Some(path) if !path.trim().is_empty() => Self::from_file_with(path, env),
+            _ => Self::default().with_env_overrides(env),
+        }
+    }
 
-        let tls_key_path = std::env::var("TLS_KEY_PATH").ok().map(PathBuf::from);
+    /// Load configuration from environment variables
+    pub fn from_env() -> Result<Self> {
+        Self::default().with_env_overrides(&process_env)
+    }
 
-        let tls_client_ca_path = std::env::var("TLS_CLIENT_CA_PATH").ok().map(PathBuf::from);
+    /// Load a TOML file keyed by field name, e.g. `upstream_url = "..."`.
+    /// Fields missing from the file keep their defaults and environment
+    /// variables override both.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_file_with(path, &process_env)
+    }
 
-        let enable_jwt = std::env::var("ENABLE_JWT")
-            .unwrap_or_else(|_| "false".to_string())
-            .parse()
-            .context("Invalid ENABLE_JWT")?;
+    fn from_file_with(
+        path: impl AsRef<Path>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {:?}", path))?;
+        let config: Self =
+            toml::from_str(&raw).with_context(|| format!("Invalid config file {:?}", path))?;
+        config.with_env_overrides(env)
+    }
 
-        let jwt_secret = std::env::var("JWT_SECRET").ok();
+    fn with_env_overrides(mut self, env: &dyn Fn(&str) -> Option<String>) -> Result<Self> {
+        override_from_env(env, &mut self.host, "PROXY_HOST")?;
+        override_from_env(env, &mut self.port, "PROXY_PORT")?;
+        override_from_env(env, &mut self.upstream_url, "UPSTREAM_URL")?;
 
-        let jwt_public_key_path = std::env::var("JWT_PUBLIC_KEY_PATH").ok().map(PathBuf::from);
+        if let Some(raw) = env("TENANT_UPSTREAMS") {
+            if !raw.trim().is_empty() {
+                self.tenant_upstreams = serde_json::from_str(&raw).context(
+                    "Invalid TENANT_UPSTREAMS: expected a JSON object of tenant IDs to URLs",
+                )?;
+            }
+        }
 
-        let jwt_issuer = std::env::var("JWT_ISSUER").ok();
+        override_from_env(env, &mut self.request_timeout_secs, "REQUEST_TIMEOUT_SECS")?;
+        override_from_env(env, &mut self.max_body_size_bytes, "MAX_BODY_SIZE_BYTES")?;
+        override_from_env(env, &mut self.enforcer_url, "ENFORCER_URL")?;
+        override_from_env(env, &mut self.enable_mtls, "ENABLE_MTLS")?;
+        override_optional_from_env(env, &mut self.tls_cert_path, "TLS_CERT_PATH")?;
+        override_optional_from_env(env, &mut self.tls_key_path, "TLS_KEY_PATH")?;
+        override_optional_from_env(env, &mut self.tls_client_ca_path, "TLS_CLIENT_CA_PATH")?;
+        override_from_env(env, &mut self.enable_jwt, "ENABLE_JWT")?;
+        override_optional_from_env(env, &mut self.jwt_secret, "JWT_SECRET")?;
+        override_optional_from_env(env, &mut self.jwt_public_key_path, "JWT_PUBLIC_KEY_PATH")?;
+        override_optional_from_env(env, &mut self.jwt_issuer, "JWT_ISSUER")?;
+        override_optional_from_env(env, &mut self.jwt_audience, "JWT_AUDIENCE")?;
+        override_from_env(env, &mut self.jwt_algorithm, "JWT_ALGORITHM")?;
+
+        if let Some(raw) = env("JWT_ALGORITHMS") {
+            self.jwt_algorithms = raw
+                .split(',')
+                .map(str::trim)
+                .filter(|alg| !alg.is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<_>>>()
+                .context("Invalid JWT_ALGORITHMS")?;
+        }
 
-        let jwt_audience = std::env::var("JWT_AUDIENCE").ok();
+        override_optional_from_env(
+            env,
+            &mut self.jwt_ec_public_key_path,
+            "JWT_EC_PUBLIC_KEY_PATH",
+        )?;
+        override_from_env(env, &mut self.jwt_leeway_secs, "JWT_LEEWAY_SECS")?;
+        override_from_env(env, &mut self.jwt_require_exp, "JWT_REQUIRE_EXP")?;
+        override_from_env(env, &mut self.jwt_require_nbf, "JWT_REQUIRE_NBF")?;
+        override_from_env(env, &mut self.forward_auth_header, "FORWARD_AUTH_HEADER")?;
+        override_from_env(env, &mut self.log_level, "LOG_LEVEL")?;
+        override_optional_from_env(env, &mut self.quota_tracker_url, "QUOTA_TRACKER_URL")?;
+        override_optional_from_env(env, &mut self.quota_tracker_token, "QUOTA_TRACKER_TOKEN")?;
+        override_optional_from_env(env, &mut self.default_region, "DEFAULT_REGION")?;
+        override_from_env(env, &mut self.policy_cache_ttl_ms, "POLICY_CACHE_TTL_MS")?;
+        override_from_env(
+            env,
+            &mut self.policy_cache_obligations,
+            "POLICY_CACHE_OBLIGATIONS",
+        )?;
+        override_from_env(env, &mut self.policy_cache_warmup, "POLICY_CACHE_WARMUP")?;
+        override_from_env(
+            env,
+            &mut self.policy_cache_warmup_inputs,
+            "POLICY_CACHE_WARMUP_INPUTS",
+        )?;
+        override_optional_from_env(env, &mut self.audit_store_url, "AUDIT_STORE_URL")?;
+        override_from_env(
+            env,
+            &mut self.malformed_decision_mode,
+            "POLICY_MALFORMED_DECISION",
+        )?;
+        override_from_env(
+            env,
+            &mut self.enforcer_failure_mode,
+            "ENFORCER_FAILURE_MODE",
+        )?;
+        override_from_env(env, &mut self.strict_redaction, "STRICT_REDACTION")?;
+        override_from_env(
+            env,
+            &mut self.redaction_warnings_header,
+            "REDACTION_WARNINGS_HEADER",
+        )?;
+        override_from_env(env, &mut self.max_redaction_depth, "MAX_REDACTION_DEPTH")?;
+        override_from_env(env, &mut self.tenant_id_header, "TENANT_ID_HEADER")?;
+
+        if let Some(raw) = env("SHADOW_MODE_TENANTS") {
+            self.shadow_mode_tenants = raw
+                .split(',')
+                .map(str::trim)
+                .filter(|tenant| !tenant.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
 
-        let jwt_algorithm = std::env::var("JWT_ALGORITHM")
-            .unwrap_or_else(|_| "RS256".to_string())
-            .parse()?;
+        if let Some(raw) = env("DEBUG_BODY_TENANTS") {
+            self.debug_body_tenants = raw
+                .split(',')
+                .map(str::trim)
+                .filter(|tenant| !tenant.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        override_from_env(env, &mut self.debug_body_max_bytes, "DEBUG_BODY_MAX_BYTES")?;
+
+        if let Some(raw) = env("BYPASS_PATHS") {
+            self.bypass_paths = raw
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
 
-        let forward_auth_header = std::env::var("FORWARD_AUTH_HEADER")
-            .unwrap_or_else(|_| "false".to_string())
-            .parse()
-            .context("Invalid FORWARD_AUTH_HEADER")?;
-
-        let log_level = std::env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string());
-
-        let quota_tracker_url = std::env::var("QUOTA_TRACKER_URL").ok();
-
-        let quota_tracker_token = std::env::var("QUOTA_TRACKER_TOKEN").ok();
-
-        let default_region = std::env::var("DEFAULT_REGION").ok();
-
-        Ok(Self {
-            host,
-            port,
-            upstream_url,
-            request_timeout_secs,
-            max_body_size_bytes,
-            enforcer_url,
-            enable_mtls,
-            tls_cert_path,
-            tls_key_path,
-            tls_client_ca_path,
-            enable_jwt,
-            jwt_secret,
-            jwt_public_key_path,
-            jwt_issuer,
-            jwt_audience,
-            jwt_algorithm,
-            forward_auth_header,
-            log_level,
-            quota_tracker_url,
-            quota_tracker_token,
-            default_region,
-        })
+        Ok(self)
     }
 
     /// Validate the configuration
@@ -230,35 +523,39 @@ impl ProxyConfig {
 
         // Validate JWT configuration
         if self.enable_jwt {
-            match self.jwt_algorithm {
-                JwtAlgorithm::HS256 | JwtAlgorithm::HS384 | JwtAlgorithm::HS512 => {
+            for algorithm in self.allowed_jwt_algorithms() {
+                if algorithm.is_hmac() {
                     if self.jwt_secret.is_none() {
                         anyhow::bail!(
                             "JWT_SECRET is required for HMAC algorithms (HS256/HS384/HS512)"
                         );
                     }
+                    continue;
                 }
-                JwtAlgorithm::RS256
-                | JwtAlgorithm::RS384
-                | JwtAlgorithm::RS512
-                | JwtAlgorithm::ES256
-                | JwtAlgorithm::ES384 => {
-                    if self.jwt_public_key_path.is_none() {
-                        anyhow::bail!("JWT_PUBLIC_KEY_PATH is required for RSA/ECDSA algorithms");
+                match self.jwt_public_key_path_for(algorithm) {
+                    None => {
+                        anyhow::bail!("JWT_PUBLIC_KEY_PATH is required for RSA/ECDSA algorithms")
                     }
-                    if let Some(ref path) = self.jwt_public_key_path {
-                        if !path.exists() {
-                            anyhow::bail!("JWT public key file not found: {:?}", path);
-                        }
+                    Some(path) if !path.exists() => {
+                        anyhow::bail!("JWT public key file not found: {:?}", path)
                     }
+                    Some(_) => {}
                 }
             }
         }
 
-        // Validate upstream URL
+        // Validate upstream URLs
         if self.upstream_url.is_empty() {
             anyhow::bail!("UPSTREAM_URL cannot be empty");
         }
+        validate_upstream_url(&self.upstream_url).context("Invalid UPSTREAM_URL")?;
+        for (tenant_id, url) in &self.tenant_upstreams {
+            if tenant_id.trim().is_empty() {
+                anyhow::bail!("TENANT_UPSTREAMS contains an empty tenant ID");
+            }
+            validate_upstream_url(url)
+                .with_context(|| format!("Invalid upstream URL for tenant {}", tenant_id))?;
+        }
 
         // Validate enforcer URL
         if self.enforcer_url.is_empty() {
@@ -275,7 +572,38 @@ impl ProxyConfig {
             anyhow::bail!("MAX_BODY_SIZE_BYTES must be greater than 0");
         }
 
+        if self.max_redaction_depth == 0 {
+            anyhow::bail!("MAX_REDACTION_DEPTH must be greater than 0");
+        }
+
+        if !self.debug_body_tenants.is_empty() && self.debug_body_max_bytes == 0 {
+            anyhow::bail!(
+                "DEBUG_BODY_MAX_BYTES must be greater than 0 when DEBUG_BODY_TENANTS is set"
+            );
+        }
+
+        BypassPaths::new(&self.bypass_paths).context("Invalid BYPASS_PATHS")?;
+
+        if self.policy_cache_warmup {
+            if self.audit_store_url.is_none() {
+                anyhow::bail!("AUDIT_STORE_URL is required when POLICY_CACHE_WARMUP is true");
+            }
+            if self.policy_cache_ttl_ms == 0 {
+                anyhow::bail!("POLICY_CACHE_WARMUP requires POLICY_CACHE_TTL_MS greater than 0");
+            }
+            if self.policy_cache_warmup_inputs == 0 {
+                anyhow::bail!("POLICY_CACHE_WARMUP_INPUTS must be greater than 0");
+            }
+        }
+
         // Validate quota tracker configuration
+        http::HeaderName::from_bytes(self.tenant_id_header.as_bytes()).with_context(|| {
+            format!(
+                "Invalid TENANT_ID_HEADER: '{}' is not a valid header name",
+                self.tenant_id_header
+            )
+        })?;
+
         match (
             self.quota_tracker_url.as_ref(),
             self.quota_tracker_token.as_ref(),
@@ -295,15 +623,114 @@ impl ProxyConfig {
         Ok(())
     }
 
+    /// Get policy decision cache TTL as Duration
+    pub fn policy_cache_ttl(&self) -> Duration {
+        Duration::from_millis(self.policy_cache_ttl_ms)
+    }
+
     /// Get request timeout as Duration
     pub fn request_timeout(&self) -> Duration {
         Duration::from_secs(self.request_timeout_secs)
     }
 
     /// Get the listen address
+    /// Algorithms a token header may name; `jwt_algorithm` alone unless
+    /// `jwt_algorithms` is set.
+    pub fn allowed_jwt_algorithms(&self) -> Vec<JwtAlgorithm> {
+        if self.jwt_algorithms.is_empty() {
+            vec![self.jwt_algorithm]
+        } else {
+            self.jwt_algorithms.clone()
+        }
+    }
+
+    /// Public key file verifying tokens signed with `algorithm`.
+    pub fn jwt_public_key_path_for(&self, algorithm: JwtAlgorithm) -> Option<&PathBuf> {
+        if algorithm.is_ecdsa() {
+            self.jwt_ec_public_key_path
+                .as_ref()
+                .or(self.jwt_public_key_path.as_ref())
+        } else {
+            self.jwt_public_key_path.as_ref()
+        }
+    }
+
+    /// Whether policy denials for `tenant_id` are only logged.
+    pub fn is_shadow_mode(&self, tenant_id: &str) -> bool {
+        self.shadow_mode_tenants.contains(tenant_id)
+    }
+
+    /// Whether request and response bodies for `tenant_id` are logged.
+    pub fn logs_bodies_for(&self, tenant_id: &str) -> bool {
+        self.debug_body_tenants.contains(tenant_id)
+    }
+
     pub fn listen_addr(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
+
+    /// Effective configuration as served on the config dump path, with
+    /// secrets that are set replaced by [`REDACTED_SECRET`]
+    pub fn dump(&self) -> serde_json::Value {
+        let mut dump = serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
+        if let Some(fields) = dump.as_object_mut() {
+            for field in SECRET_FIELDS {
+                if let Some(value) = fields.get_mut(*field).filter(|value| !value.is_null()) {
+                    *value = serde_json::Value::from(REDACTED_SECRET);
+                }
+            }
+        }
+        dump
+    }
+}
+
+/// Replace `target` with the parsed value of `name` when it is set.
+fn override_from_env<T>(
+    env: &dyn Fn(&str) -> Option<String>,
+    target: &mut T,
+    name: &str,
+) -> Result<()>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    if let Some(raw) = env(name) {
+        *target = raw
+            .parse()
+            .map_err(|err| anyhow::anyhow!("Invalid {}: {}", name, err))?;
+    }
+    Ok(())
+}
+
+/// Like [`override_from_env`], for settings that may be left unset.
+fn override_optional_from_env<T>(
+    env: &dyn Fn(&str) -> Option<String>,
+    target: &mut Option<T>,
+    name: &str,
+) -> Result<()>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    if let Some(raw) = env(name) {
+        *target = Some(
+            raw.parse()
+                .map_err(|err| anyhow::anyhow!("Invalid {}: {}", name, err))?,
+        );
+    }
+    Ok(())
+}
+
+fn validate_upstream_url(raw: &str) -> Result<()> {
+    let url = url::Url::parse(raw).with_context(|| format!("'{}' is not a valid URL", raw))?;
+    match url.scheme() {
+        "http" | "https" => {}
+        other => anyhow::bail!("unsupported scheme '{}' in '{}'", other, raw),
+    }
+    if url.host_str().is_none() {
+        anyhow::bail!("'{}' has no host", raw);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -327,12 +754,63 @@ mod tests {
         assert!("INVALID".parse::<JwtAlgorithm>().is_err());
     }
 
+    #[test]
+    fn test_malformed_decision_mode_from_str() {
+        assert_eq!(
+            "deny".parse::<MalformedDecisionMode>().unwrap(),
+            MalformedDecisionMode::Deny
+        );
+        assert_eq!(
+            "ALLOW".parse::<MalformedDecisionMode>().unwrap(),
+            MalformedDecisionMode::Allow
+        );
+        assert!("maybe".parse::<MalformedDecisionMode>().is_err());
+    }
+
+    #[test]
+    fn test_enforcer_failure_mode_from_str() {
+        assert_eq!(
+            "closed".parse::<EnforcerFailureMode>().unwrap(),
+            EnforcerFailureMode::Closed
+        );
+        assert_eq!(
+            "OPEN".parse::<EnforcerFailureMode>().unwrap(),
+            EnforcerFailureMode::Open
+        );
+        assert!("ajar".parse::<EnforcerFailureMode>().is_err());
+    }
+
+    #[test]
+    fn test_dump_redacts_secrets() {
+        let config = ProxyConfig {
+            port: 9090,
+            jwt_secret: Some("jwt-dump-secret".to_string()),
+            quota_tracker_url: Some("http://quota.local".to_string()),
+            quota_tracker_token: Some("quota-dump-token".to_string()),
+            ..ProxyConfig::default()
+        };
+
+        let dump = config.dump();
+
+        assert_eq!(dump["port"], 9090);
+        assert_eq!(dump["quota_tracker_url"], "http://quota.local");
+        assert_eq!(dump["jwt_secret"], REDACTED_SECRET);
+        assert_eq!(dump["quota_tracker_token"], REDACTED_SECRET);
+        let text = dump.to_string();
+        assert!(!text.contains("jwt-dump-secret"));
+        assert!(!text.contains("quota-dump-token"));
+
+        // Unset secrets stay null rather than suggesting a value is configured
+        assert!(ProxyConfig::default().dump()["jwt_secret"].is_null());
+    }
+
     #[test]
     fn test_config_validation() {
         let mut config = ProxyConfig {
             host: "0.0.0.0".to_string(),
             port: 8080,
             upstream_url: "http://localhost:8000".to_string(),
+            tenant_upstreams: BTreeMap::new(),
             request_timeout_secs: 30,
             max_body_size_bytes: 10485760,
             enforcer_url: "http://localhost:8181".to_string(),
@@ -346,21 +824,58 @@ mod tests {
             jwt_issuer: None,
             jwt_audience: None,
             jwt_algorithm: JwtAlgorithm::RS256,
+            jwt_algorithms: Vec::new(),
+            jwt_ec_public_key_path: None,
+            jwt_leeway_secs: 60,
+            jwt_require_exp: true,
+            jwt_require_nbf: false,
             forward_auth_header: false,
             log_level: "info".to_string(),
             quota_tracker_url: None,
             quota_tracker_token: None,
             default_region: None,
+            policy_cache_ttl_ms: 0,
+            policy_cache_obligations: false,
+            policy_cache_warmup: false,
+            policy_cache_warmup_inputs: DEFAULT_CACHE_WARMUP_INPUTS,
+            audit_store_url: None,
+            malformed_decision_mode: MalformedDecisionMode::Deny,
+            enforcer_failure_mode: EnforcerFailureMode::Closed,
+            strict_redaction: false,
+            redaction_warnings_header: false,
+            max_redaction_depth: MAX_REDACTION_DEPTH,
+            tenant_id_header: TENANT_ID_HEADER.to_string(),
+            shadow_mode_tenants: BTreeSet::new(),
+            debug_body_tenants: BTreeSet::new(),
+            debug_body_max_bytes: DEFAULT_DEBUG_BODY_MAX_BYTES,
+            bypass_paths: Vec::new(),
         };
 
         // Valid configuration
         assert!(config.validate().is_ok());
 
+        // Invalid: tenant header that is not a header name
+        config.tenant_id_header = "X Org ID".to_string();
+        assert!(config.validate().is_err());
+        config.tenant_id_header = "X-Org-ID".to_string();
+        assert!(config.validate().is_ok());
+
         // Invalid: empty upstream URL
         config.upstream_url = "".to_string();
         assert!(config.validate().is_err());
         config.upstream_url = "http://localhost:8000".to_string();
 
+        // Invalid: tenant upstream that is not an http(s) URL
+        config
+            .tenant_upstreams
+            .insert("tenant-a".to_string(), "ftp://backend.local".to_string());
+        assert!(config.validate().is_err());
+        config.tenant_upstreams.insert(
+            "tenant-a".to_string(),
+            "http://backend-a.local:9000".to_string(),
+        );
+        assert!(config.validate().is_ok());
+
         // Invalid: zero timeout
         config.request_timeout_secs = 0;
         assert!(config.validate().is_err());
@@ -371,6 +886,21 @@ mod tests {
         assert!(config.validate().is_err());
         config.enable_mtls = false;
 
+        // Invalid: bypass path covering every request
+        config.bypass_paths = vec!["/*".to_string()];
+        assert!(config.validate().is_err());
+        config.bypass_paths = vec!["/healthz".to_string(), "/metrics/*".to_string()];
+        assert!(config.validate().is_ok());
+
+        // Invalid: cache warm-up without an audit store or a cache
+        config.policy_cache_warmup = true;
+        assert!(config.validate().is_err());
+        config.audit_store_url = Some("http://audit.local".to_string());
+        assert!(config.validate().is_err());
+        config.policy_cache_ttl_ms = 1000;
+        assert!(config.validate().is_ok());
+        config.policy_cache_warmup = false;
+
         // Invalid: quota URL without token
         config.quota_tracker_url = Some("http://quota.local".to_string());
         assert!(config.validate().is_err());
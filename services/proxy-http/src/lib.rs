@@ -1,7 +1,9 @@
+pub mod audit;
 pub mod auth;
 pub mod config;
 pub mod policy;
 pub mod proxy;
 pub mod quota;
 pub mod redaction;
+pub mod reload;
 pub mod server;
@@ -1,17 +1,28 @@
 use anyhow::{Context, Result};
 use edge_policy_proxy_http::config::ProxyConfig;
+use edge_policy_proxy_http::reload::LogReloadHandle;
 use edge_policy_proxy_http::server::ProxyServer;
+use edge_policy_telemetry::{process_env, LogFormat};
 use tokio::signal;
 use tracing::{error, info};
-use tracing_subscriber::{fmt, EnvFilter};
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load configuration
-    let config = ProxyConfig::from_env().context("Failed to load configuration")?;
+    let config = ProxyConfig::load().context("Failed to load configuration")?;
+
+    // Validate and exit without serving when asked to only check the configuration
+    if std::env::args().skip(1).any(|arg| arg == "--check-config") {
+        config
+            .validate()
+            .context("Configuration validation failed")?;
+        println!("configuration ok");
+        return Ok(());
+    }
 
     // Initialize tracing with the configured log level
-    init_tracing(&config.log_level);
+    let log_handle = init_tracing(&config.log_level)?;
 
     info!("edge-policy-proxy-http service starting");
     info!(
@@ -30,8 +41,29 @@ async fn main() -> Result<()> {
     }
 
     // Create and start server
+    let startup_config = config.clone();
     let server = ProxyServer::new(config).context("Failed to create proxy server")?;
 
+    // Log level, timeouts and body size limits can be reloaded via SIGHUP
+    #[cfg(unix)]
+    {
+        let settings = server.runtime_settings();
+        tokio::spawn(async move {
+            if let Err(e) = edge_policy_proxy_http::reload::reload_on_sighup(
+                settings,
+                Some(log_handle),
+                startup_config,
+                process_env,
+            )
+            .await
+            {
+                error!("SIGHUP reload handler stopped: {:#}", e);
+            }
+        });
+    }
+    #[cfg(not(unix))]
+    let _ = (log_handle, startup_config);
+
     // Run server with graceful shutdown
     tokio::select! {
         result = server.run() => {
@@ -49,16 +81,11 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn init_tracing(log_level: &str) {
+fn init_tracing(log_level: &str) -> Result<LogReloadHandle> {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(log_level));
+    let format = LogFormat::from_env().context("Invalid LOG_FORMAT")?;
 
-    fmt()
-        .with_env_filter(filter)
-        .with_target(false)
-        .with_thread_ids(false)
-        .with_line_number(true)
-        .compact()
-        .init();
+    Ok(edge_policy_telemetry::init_tracing(filter, format))
 }
 
 async fn shutdown_signal() {
@@ -0,0 +1,157 @@
+use super::client::PolicyDecision;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Upper bound on cached decisions; expired entries are purged when reached.
+const MAX_CACHED_DECISIONS: usize = 10_000;
+
+struct CachedDecision {
+    canonical_input: String,
+    decision: PolicyDecision,
+    expires_at: Instant,
+}
+
+/// Short-lived cache of enforcer decisions keyed on tenant and input.
+pub struct DecisionCache {
+    ttl: Duration,
+    cache_obligations: bool,
+    entries: Mutex<HashMap<(String, u64), CachedDecision>>,
+}
+
+impl DecisionCache {
+    pub fn new(ttl: Duration, cache_obligations: bool) -> Self {
+        Self {
+            ttl,
+            cache_obligations,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Serialize the policy input with volatile attributes removed so that
+    /// otherwise identical requests share a cache entry.
+    pub fn canonical_input(input: &serde_json::Value) -> String {
+        let mut canonical = input.clone();
+        if let Some(environment) = canonical
+            .get_mut("environment")
+            .and_then(|value| value.as_object_mut())
+        {
+            environment.remove("time");
+        }
+        canonical.to_string()
+    }
+
+    pub fn get(&self, tenant_id: &str, canonical_input: &str) -> Option<PolicyDecision> {
+        let key = cache_key(tenant_id, canonical_input);
+        let mut entries = self.lock();
+
+        match entries.get(&key) {
+            Some(entry) if entry.expires_at <= Instant::now() => {
+                entries.remove(&key);
+                None
+            }
+            // Guard against hash collisions between distinct inputs
+            Some(entry) if entry.canonical_input == canonical_input => Some(entry.decision.clone()),
+            _ => None,
+        }
+    }
+
+    /// Store a decision unless it carries obligations and those are not cacheable.
+    pub fn insert(&self, tenant_id: &str, canonical_input: String, decision: &PolicyDecision) {
+        if !self.cache_obligations && has_obligations(decision) {
+            return;
+        }
+
+        let key = cache_key(tenant_id, &canonical_input);
+        let now = Instant::now();
+        let mut entries = self.lock();
+
+        if entries.len() >= MAX_CACHED_DECISIONS {
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= MAX_CACHED_DECISIONS {
+                entries.clear();
+            }
+        }
+
+        entries.insert(
+            key,
+            CachedDecision {
+                canonical_input,
+                decision: decision.clone(),
+                expires_at: now + self.ttl,
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, u64), CachedDecision>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn cache_key(tenant_id: &str, canonical_input: &str) -> (String, u64) {
+    let mut hasher = DefaultHasher::new();
+    canonical_input.hash(&mut hasher);
+    (tenant_id.to_string(), hasher.finish())
+}
+
+fn has_obligations(decision: &PolicyDecision) -> bool {
+    decision.redaction_paths().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn decision(redact: Option<Vec<String>>) -> PolicyDecision {
+        PolicyDecision {
+            allow: true,
+            reason: None,
+            redact,
+            redact_fields: None,
+            remove_fields: None,
+            redact_if: None,
+            decision_schema_version: None,
+            bypass_reason: None,
+        }
+    }
+
+    #[test]
+    fn canonical_input_ignores_evaluation_time() {
+        let first = json!({"action": "read", "environment": {"time": "2024-01-01T00:00:00Z"}});
+        let second = json!({"action": "read", "environment": {"time": "2024-01-01T00:00:01Z"}});
+
+        assert_eq!(
+            DecisionCache::canonical_input(&first),
+            DecisionCache::canonical_input(&second)
+        );
+    }
+
+    #[test]
+    fn decisions_with_obligations_are_skipped_unless_enabled() {
+        let redacting = decision(Some(vec!["pii.email".to_string()]));
+
+        let cache = DecisionCache::new(Duration::from_secs(60), false);
+        cache.insert("tenant-a", "input".to_string(), &redacting);
+        assert!(cache.get("tenant-a", "input").is_none());
+
+        let cache = DecisionCache::new(Duration::from_secs(60), true);
+        cache.insert("tenant-a", "input".to_string(), &redacting);
+        assert!(cache.get("tenant-a", "input").is_some());
+        assert!(cache.get("tenant-b", "input").is_none());
+    }
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let cache = DecisionCache::new(Duration::from_millis(20), false);
+        cache.insert("tenant-a", "input".to_string(), &decision(None));
+        assert!(cache.get("tenant-a", "input").is_some());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get("tenant-a", "input").is_none());
+    }
+}
@@ -1,8 +1,12 @@
+use super::cache::DecisionCache;
 use super::{AbacInput, PolicyError};
+use crate::config::{EnforcerFailureMode, MalformedDecisionMode};
+use edge_policy_enrichment::InputEnrichment;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Duration;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyQueryRequest {
@@ -21,11 +25,61 @@ pub struct PolicyDecision {
     pub reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub redact: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact_fields: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remove_fields: Option<Vec<String>>,
+    /// Conditional removals: each path is removed only when the policy set
+    /// its flag to `true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact_if: Option<BTreeMap<String, bool>>,
+    /// Missing from enforcers that predate versioned decisions, which send
+    /// version 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision_schema_version: Option<u32>,
+    /// Set when the proxy failed open instead of using an enforcer
+    /// decision. Never read from the enforcer.
+    #[serde(skip)]
+    pub bypass_reason: Option<String>,
+}
+
+impl PolicyDecision {
+    /// Paths to remove from the response body, if any.
+    ///
+    /// Version 1 decisions only carry `redact`. From version 2 the
+    /// enforcer reports removals in `remove_fields` and ignores `redact`;
+    /// the proxy cannot mask values, so `redact_fields` are removed too.
+    /// Paths in `redact_if` are added when their flag is set, whatever the
+    /// version.
+    pub fn redaction_paths(&self) -> Option<Vec<String>> {
+        let mut paths = match self.decision_schema_version {
+            None | Some(0) | Some(1) => self.redact.clone().unwrap_or_default(),
+            Some(_) => {
+                let mut paths = self.remove_fields.clone().unwrap_or_default();
+                for path in self.redact_fields.iter().flatten() {
+                    if !paths.contains(path) {
+                        paths.push(path.clone());
+                    }
+                }
+                paths
+            }
+        };
+        for (path, _) in self.redact_if.iter().flatten().filter(|(_, flag)| **flag) {
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+        (!paths.is_empty()).then_some(paths)
+    }
 }
 
 pub struct PolicyClient {
     http_client: Client,
     enforcer_base_url: String,
+    decision_cache: Option<DecisionCache>,
+    malformed_decision_mode: MalformedDecisionMode,
+    enforcer_failure_mode: EnforcerFailureMode,
+    enrichment: InputEnrichment,
 }
 
 impl PolicyClient {
@@ -38,9 +92,68 @@ impl PolicyClient {
         Ok(Self {
             http_client,
             enforcer_base_url: enforcer_url.trim_end_matches('/').to_string(),
+            decision_cache: None,
+            malformed_decision_mode: MalformedDecisionMode::default(),
+            enforcer_failure_mode: EnforcerFailureMode::default(),
+            enrichment: InputEnrichment::default(),
         })
     }
 
+    /// Cache decisions for `ttl`; a zero TTL leaves caching disabled.
+    ///
+    /// Decisions carrying obligations such as redaction paths are only cached
+    /// when `cache_obligations` is set.
+    pub fn with_decision_cache(mut self, ttl: Duration, cache_obligations: bool) -> Self {
+        self.decision_cache = (!ttl.is_zero()).then(|| DecisionCache::new(ttl, cache_obligations));
+        self
+    }
+
+    /// Whether decisions are cached at all.
+    pub fn caches_decisions(&self) -> bool {
+        self.decision_cache.is_some()
+    }
+
+    /// Choose what a successful enforcer response without a usable
+    /// `result.allow` means. Such responses are never cached.
+    pub fn with_malformed_decision_mode(mut self, mode: MalformedDecisionMode) -> Self {
+        self.malformed_decision_mode = mode;
+        self
+    }
+
+    /// Choose what an unreachable or timed-out enforcer means. Requests
+    /// allowed this way are never cached.
+    pub fn with_enforcer_failure_mode(mut self, mode: EnforcerFailureMode) -> Self {
+        self.enforcer_failure_mode = mode;
+        self
+    }
+
+    /// Enrich every policy input before it is cached or sent to the enforcer.
+    pub fn with_enrichment(mut self, enrichment: InputEnrichment) -> Self {
+        self.enrichment = enrichment;
+        self
+    }
+
+    /// Check that the enforcer answers its health endpoint.
+    pub async fn check_health(&self) -> Result<(), PolicyError> {
+        let url = format!("{}/health", self.enforcer_base_url);
+        let response = self
+            .http_client
+            .get(&url)
+            .timeout(crate::proxy::HEALTH_CHECK_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| PolicyError::EnforcerUnreachable(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(PolicyError::EnforcerError {
+                status: http::StatusCode::from_u16(status.as_u16()).unwrap(),
+                message: "health check failed".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     #[instrument(skip(self, input), fields(tenant_id = %tenant_id))]
     pub async fn query_policy(
         &self,
@@ -54,31 +167,41 @@ impl PolicyClient {
 
         debug!("Querying policy enforcer at {}", url);
 
-        let input_value = serde_json::to_value(&input).map_err(|e| {
+        let mut input_value = serde_json::to_value(&input).map_err(|e| {
             PolicyError::InvalidResponse(format!("Failed to serialize ABAC input: {}", e))
         })?;
+        self.enrichment.apply(&mut input_value);
+
+        let canonical_input = self
+            .decision_cache
+            .as_ref()
+            .map(|_| DecisionCache::canonical_input(&input_value));
+
+        if let (Some(cache), Some(canonical)) = (&self.decision_cache, &canonical_input) {
+            if let Some(decision) = cache.get(tenant_id, canonical) {
+                debug!(allow = decision.allow, "Policy decision served from cache");
+                return decision_result(decision);
+            }
+        }
 
         let request = PolicyQueryRequest { input: input_value };
 
         let start = std::time::Instant::now();
 
-        let response = self
-            .http_client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(PolicyError::from)?;
+        let response = match self.http_client.post(&url).json(&request).send().await {
+            Ok(response) => response,
+            Err(e) => return self.enforcer_unavailable(tenant_id, e.into()),
+        };
 
         let latency = start.elapsed();
         let status = response.status();
 
         if status.is_success() {
-            let policy_response: PolicyQueryResponse = response.json().await.map_err(|e| {
-                PolicyError::InvalidResponse(format!("Failed to parse policy response: {}", e))
-            })?;
-
-            let decision = policy_response.result;
+            let body = response.bytes().await.map_err(PolicyError::from)?;
+            let decision = match serde_json::from_slice::<PolicyQueryResponse>(&body) {
+                Ok(policy_response) => policy_response.result,
+                Err(e) => return self.malformed_decision(tenant_id, e.to_string()),
+            };
 
             info!(
                 tenant_id = %tenant_id,
@@ -87,13 +210,11 @@ impl PolicyClient {
                 "Policy query completed"
             );
 
-            if !decision.allow {
-                return Err(PolicyError::Denied {
-                    reason: decision.reason.clone(),
-                });
+            if let (Some(cache), Some(canonical)) = (&self.decision_cache, canonical_input) {
+                cache.insert(tenant_id, canonical, &decision);
             }
 
-            Ok(decision)
+            decision_result(decision)
         } else if status == reqwest::StatusCode::FORBIDDEN {
             // 403 Forbidden maps to policy denial
             let error_message = response
@@ -118,4 +239,65 @@ impl PolicyClient {
             })
         }
     }
+
+    fn malformed_decision(
+        &self,
+        tenant_id: &str,
+        detail: String,
+    ) -> Result<PolicyDecision, PolicyError> {
+        match self.malformed_decision_mode {
+            MalformedDecisionMode::Deny => {
+                warn!(tenant_id = %tenant_id, error = %detail, "Malformed policy decision, denying");
+                Err(PolicyError::MalformedDecision(detail))
+            }
+            MalformedDecisionMode::Allow => {
+                warn!(tenant_id = %tenant_id, error = %detail, "Malformed policy decision, allowing");
+                Ok(bypass_decision(
+                    format!("malformed enforcer decision: {}", detail),
+                    "malformed_decision",
+                ))
+            }
+        }
+    }
+
+    fn enforcer_unavailable(
+        &self,
+        tenant_id: &str,
+        err: PolicyError,
+    ) -> Result<PolicyDecision, PolicyError> {
+        if self.enforcer_failure_mode == EnforcerFailureMode::Closed {
+            return Err(err);
+        }
+        let bypass_reason = match &err {
+            PolicyError::EvaluationTimeout => "enforcer_timeout",
+            PolicyError::EnforcerUnreachable(_) => "enforcer_unreachable",
+            _ => return Err(err),
+        };
+        warn!(tenant_id = %tenant_id, error = %err, "Policy enforcer unavailable, allowing");
+        Ok(bypass_decision(err.to_string(), bypass_reason))
+    }
+}
+
+/// Allow decision for a request forwarded because the proxy failed open.
+fn bypass_decision(reason: String, bypass_reason: &str) -> PolicyDecision {
+    PolicyDecision {
+        allow: true,
+        reason: Some(reason),
+        redact: None,
+        redact_fields: None,
+        remove_fields: None,
+        redact_if: None,
+        decision_schema_version: None,
+        bypass_reason: Some(bypass_reason.to_string()),
+    }
+}
+
+fn decision_result(decision: PolicyDecision) -> Result<PolicyDecision, PolicyError> {
+    if !decision.allow {
+        return Err(PolicyError::Denied {
+            reason: decision.reason,
+        });
+    }
+
+    Ok(decision)
 }
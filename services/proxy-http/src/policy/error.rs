@@ -18,6 +18,9 @@ pub enum PolicyError {
     #[error("Invalid response from enforcer: {0}")]
     InvalidResponse(String),
 
+    #[error("Enforcer returned no usable decision: {0}")]
+    MalformedDecision(String),
+
     #[error("Request denied by policy{}", .reason.as_ref().map(|r| format!(": {}", r)).unwrap_or_default())]
     Denied { reason: Option<String> },
 }
@@ -40,6 +43,7 @@ impl PolicyError {
             PolicyError::EnforcerUnreachable(_) | PolicyError::EvaluationTimeout => {
                 StatusCode::SERVICE_UNAVAILABLE
             }
+            PolicyError::MalformedDecision(_) => StatusCode::BAD_GATEWAY,
             PolicyError::EnforcerError { .. } | PolicyError::InvalidResponse(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
@@ -2,6 +2,7 @@ use crate::auth::TenantContext;
 use chrono::Utc;
 use http::{HeaderMap, Method};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use url::form_urlencoded;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +35,10 @@ pub struct ResourceAttributes {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub region: Option<String>,
     pub owner_tenant: String,
+    pub method: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub query: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +55,12 @@ pub struct EnvironmentAttributes {
 }
 
 impl AbacInput {
+    /// Starts an input for a subject of `tenant_id`, who also owns the
+    /// resource unless [`AbacInputBuilder::owner_tenant`] says otherwise.
+    pub fn builder(tenant_id: impl Into<String>) -> AbacInputBuilder {
+        AbacInputBuilder::new(tenant_id.into())
+    }
+
     pub fn from_request(
         ctx: &TenantContext,
         method: &Method,
@@ -57,34 +68,80 @@ impl AbacInput {
         query: Option<&str>,
         headers: &HeaderMap,
     ) -> Self {
-        // Build subject from TenantContext
-        let subject = SubjectAttributes {
-            tenant_id: ctx.tenant_id.clone(),
-            user_id: ctx.user_id.clone(),
-            device_id: ctx.device_id.clone(),
-            roles: ctx.roles.clone(),
-            clearance_level: 1, // Default clearance level
-        };
+        AbacInputBuilder::from_request(ctx, method, path, query, headers).build()
+    }
+}
 
+/// Builds an [`AbacInput`] attribute by attribute, in the shape the enforcer
+/// evaluates tenant policies against.
+///
+/// Unset attributes keep their defaults: clearance level `1`, action and
+/// resource type `unknown`, a `GET` of `/` and the current time.
+#[derive(Debug, Clone)]
+pub struct AbacInputBuilder {
+    input: AbacInput,
+}
+
+impl AbacInputBuilder {
+    fn new(tenant_id: String) -> Self {
+        Self {
+            input: AbacInput {
+                subject: SubjectAttributes {
+                    tenant_id: tenant_id.clone(),
+                    user_id: None,
+                    device_id: None,
+                    roles: Vec::new(),
+                    clearance_level: 1,
+                },
+                action: "unknown".to_string(),
+                resource: ResourceAttributes {
+                    r#type: "unknown".to_string(),
+                    id: None,
+                    classification: None,
+                    region: None,
+                    owner_tenant: tenant_id,
+                    method: Method::GET.as_str().to_string(),
+                    path: "/".to_string(),
+                    query: BTreeMap::new(),
+                },
+                environment: EnvironmentAttributes {
+                    time: Utc::now().to_rfc3339(),
+                    country: None,
+                    network: None,
+                    risk_score: None,
+                    bandwidth_used: None,
+                },
+            },
+        }
+    }
+
+    /// Attributes taken from the request line, headers and authenticated
+    /// tenant context.
+    pub fn from_request(
+        ctx: &TenantContext,
+        method: &Method,
+        path: &str,
+        query: Option<&str>,
+        headers: &HeaderMap,
+    ) -> Self {
         // Map HTTP method to action
         let action = match *method {
             Method::GET | Method::HEAD | Method::OPTIONS => "read",
             Method::POST | Method::PUT | Method::PATCH => "write",
             Method::DELETE => "delete",
             _ => "unknown",
-        }
-        .to_string();
-
-        // Extract resource type from path
-        let resource_type = extract_resource_type(path);
-        let resource_id = extract_resource_id(path);
+        };
 
         let mut query_id: Option<String> = None;
         let mut query_region: Option<String> = None;
         let mut query_classification: Option<String> = None;
+        let mut query_params: BTreeMap<String, String> = BTreeMap::new();
 
         if let Some(query_str) = query {
             for (key, value) in form_urlencoded::parse(query_str.as_bytes()) {
+                query_params
+                    .entry(key.to_string())
+                    .or_insert_with(|| value.to_string());
                 let key_lower = key.to_string().to_ascii_lowercase();
                 let value_trimmed = value.trim();
                 if value_trimmed.is_empty() {
@@ -112,29 +169,116 @@ impl AbacInput {
             }
         }
 
-        let resource = ResourceAttributes {
-            r#type: resource_type,
-            id: resource_id.or(query_id),
-            classification: header_value(headers, "x-classification").or(query_classification),
-            region: header_value(headers, "x-region").or(query_region),
-            owner_tenant: ctx.tenant_id.clone(),
-        };
+        AbacInput::builder(ctx.tenant_id.clone())
+            .user_id(ctx.user_id.clone())
+            .device_id(ctx.device_id.clone())
+            .roles(ctx.roles.clone())
+            .action(action)
+            .resource_type(extract_resource_type(path))
+            .resource_id(extract_resource_id(path).or(query_id))
+            .classification(header_value(headers, "x-classification").or(query_classification))
+            .region(header_value(headers, "x-region").or(query_region))
+            .method(method.as_str())
+            .path(path)
+            .query(query_params)
+            .country(header_value(headers, "x-geo-country"))
+            .network(ctx.client_ip.map(|ip| ip.to_string()))
+    }
 
-        // Build environment attributes
-        let environment = EnvironmentAttributes {
-            time: Utc::now().to_rfc3339(),
-            country: header_value(headers, "x-geo-country"),
-            network: ctx.client_ip.map(|ip| ip.to_string()),
-            risk_score: None,
-            bandwidth_used: None,
-        };
+    pub fn user_id(mut self, user_id: impl Into<Option<String>>) -> Self {
+        self.input.subject.user_id = user_id.into();
+        self
+    }
 
-        Self {
-            subject,
-            action,
-            resource,
-            environment,
-        }
+    pub fn device_id(mut self, device_id: impl Into<Option<String>>) -> Self {
+        self.input.subject.device_id = device_id.into();
+        self
+    }
+
+    pub fn roles(mut self, roles: Vec<String>) -> Self {
+        self.input.subject.roles = roles;
+        self
+    }
+
+    pub fn clearance_level(mut self, clearance_level: u32) -> Self {
+        self.input.subject.clearance_level = clearance_level;
+        self
+    }
+
+    pub fn action(mut self, action: impl Into<String>) -> Self {
+        self.input.action = action.into();
+        self
+    }
+
+    pub fn resource_type(mut self, resource_type: impl Into<String>) -> Self {
+        self.input.resource.r#type = resource_type.into();
+        self
+    }
+
+    pub fn resource_id(mut self, id: impl Into<Option<String>>) -> Self {
+        self.input.resource.id = id.into();
+        self
+    }
+
+    pub fn classification(mut self, classification: impl Into<Option<String>>) -> Self {
+        self.input.resource.classification = classification.into();
+        self
+    }
+
+    pub fn region(mut self, region: impl Into<Option<String>>) -> Self {
+        self.input.resource.region = region.into();
+        self
+    }
+
+    pub fn owner_tenant(mut self, owner_tenant: impl Into<String>) -> Self {
+        self.input.resource.owner_tenant = owner_tenant.into();
+        self
+    }
+
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.input.resource.method = method.into();
+        self
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.input.resource.path = path.into();
+        self
+    }
+
+    pub fn query(mut self, query: BTreeMap<String, String>) -> Self {
+        self.input.resource.query = query;
+        self
+    }
+
+    /// Request time, RFC 3339.
+    pub fn time(mut self, time: impl Into<String>) -> Self {
+        self.input.environment.time = time.into();
+        self
+    }
+
+    pub fn country(mut self, country: impl Into<Option<String>>) -> Self {
+        self.input.environment.country = country.into();
+        self
+    }
+
+    pub fn network(mut self, network: impl Into<Option<String>>) -> Self {
+        self.input.environment.network = network.into();
+        self
+    }
+
+    pub fn risk_score(mut self, risk_score: impl Into<Option<f64>>) -> Self {
+        self.input.environment.risk_score = risk_score.into();
+        self
+    }
+
+    /// Bytes the tenant has used in the current quota period.
+    pub fn bandwidth_used(mut self, bandwidth_used: impl Into<Option<f64>>) -> Self {
+        self.input.environment.bandwidth_used = bandwidth_used.into();
+        self
+    }
+
+    pub fn build(self) -> AbacInput {
+        self.input
     }
 }
 
@@ -258,4 +402,33 @@ mod tests {
         assert_eq!(input.resource.region.as_deref(), Some("eu-central-1"));
         assert_eq!(input.resource.classification.as_deref(), Some("restricted"));
     }
+
+    #[test]
+    fn test_from_request_populates_method_path_and_query() {
+        let ctx = TenantContext::new("tenant-c".to_string(), AuthMethod::Header);
+        let headers = HeaderMap::new();
+
+        let input = AbacInput::from_request(
+            &ctx,
+            &Method::DELETE,
+            "/admin/users/7",
+            Some("force=true&force=false&reason=cleanup"),
+            &headers,
+        );
+
+        assert_eq!(input.resource.method, "DELETE");
+        assert_eq!(input.resource.path, "/admin/users/7");
+        assert_eq!(
+            input.resource.query.get("force").map(String::as_str),
+            Some("true")
+        );
+        assert_eq!(
+            input.resource.query.get("reason").map(String::as_str),
+            Some("cleanup")
+        );
+
+        let serialized = serde_json::to_value(&input).unwrap();
+        assert_eq!(serialized["resource"]["method"], "DELETE");
+        assert_eq!(serialized["resource"]["query"]["reason"], "cleanup");
+    }
 }
@@ -1,10 +1,21 @@
+mod cache;
 mod client;
 mod error;
 mod input;
+mod warmup;
 
 pub use client::PolicyClient;
 pub use error::PolicyError;
-pub use input::{AbacInput, EnvironmentAttributes, ResourceAttributes, SubjectAttributes};
+pub use input::{
+    AbacInput, AbacInputBuilder, EnvironmentAttributes, ResourceAttributes, SubjectAttributes,
+};
+pub use warmup::CacheWarmup;
 
 pub const DEFAULT_ENFORCER_TIMEOUT_SECS: u64 = 5;
+/// Distinct recent inputs replayed per tenant when warming the decision cache.
+pub const DEFAULT_CACHE_WARMUP_INPUTS: usize = 50;
 pub const POLICY_QUERY_PATH: &str = "/v1/data/tenants/{tenant_id}/allow";
+/// Response header naming the decision for tenants in shadow mode.
+pub const SHADOW_DECISION_HEADER: &str = "x-policy-shadow-decision";
+/// Audit event logged whenever a request is allowed by failing open.
+pub const ENFORCEMENT_BYPASSED: &str = "ENFORCEMENT_BYPASSED";
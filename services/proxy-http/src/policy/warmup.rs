@@ -0,0 +1,172 @@
+use super::cache::DecisionCache;
+use super::{AbacInput, PolicyClient, PolicyError};
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Header the audit store reads to scope a request to one tenant.
+const TENANT_SCOPE_HEADER: &str = "X-Tenant-Scope";
+
+/// Audit entries read for each distinct input wanted, since recent traffic
+/// tends to repeat the same requests.
+const AUDIT_ENTRIES_PER_INPUT: usize = 5;
+
+#[derive(Debug, Deserialize)]
+struct TenantRecord {
+    tenant_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditLogPage {
+    logs: Vec<AuditLogRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditLogRecord {
+    subject: Value,
+    action: String,
+    resource: Value,
+    environment: Value,
+}
+
+impl AuditLogRecord {
+    /// The policy input this entry was decided on, if it was recorded by an
+    /// HTTP proxy and still parses as one.
+    fn into_input(self) -> Option<AbacInput> {
+        serde_json::from_value(json!({
+            "subject": self.subject,
+            "action": self.action,
+            "resource": self.resource,
+            "environment": self.environment,
+        }))
+        .ok()
+    }
+}
+
+/// Fills the decision cache at startup by replaying each tenant's most
+/// recent distinct HTTP policy inputs from the audit store.
+///
+/// Replayed inputs go through [`PolicyClient::query_policy`], so they are
+/// enriched and cached exactly like live requests. Entries expire after the
+/// usual cache TTL.
+pub struct CacheWarmup {
+    http_client: Client,
+    audit_store_url: String,
+    inputs_per_tenant: usize,
+}
+
+impl CacheWarmup {
+    pub fn new(audit_store_url: String, inputs_per_tenant: usize) -> Result<Self> {
+        let http_client = Client::builder()
+            .timeout(Duration::from_secs(super::DEFAULT_ENFORCER_TIMEOUT_SECS))
+            .build()
+            .context("Failed to build audit store client")?;
+
+        Ok(Self {
+            http_client,
+            audit_store_url: audit_store_url.trim_end_matches('/').to_string(),
+            inputs_per_tenant,
+        })
+    }
+
+    /// Replay recent inputs for every active tenant and return how many
+    /// decisions were fetched. Failures are logged and skipped.
+    pub async fn run(&self, policy_client: &PolicyClient) -> usize {
+        if !policy_client.caches_decisions() {
+            debug!("Decision cache disabled, skipping warm-up");
+            return 0;
+        }
+
+        let tenants = match self.tenants().await {
+            Ok(tenants) => tenants,
+            Err(e) => {
+                warn!("Decision cache warm-up skipped: {:#}", e);
+                return 0;
+            }
+        };
+
+        let mut warmed = 0;
+        for tenant_id in tenants {
+            let inputs = match self.recent_inputs(&tenant_id).await {
+                Ok(inputs) => inputs,
+                Err(e) => {
+                    warn!(tenant_id = %tenant_id, "Failed to read recent policy inputs: {:#}", e);
+                    continue;
+                }
+            };
+
+            for input in inputs {
+                match policy_client.query_policy(&tenant_id, input).await {
+                    Ok(_) | Err(PolicyError::Denied { .. }) => warmed += 1,
+                    Err(e) => {
+                        debug!(tenant_id = %tenant_id, error = %e, "Replayed policy input failed");
+                    }
+                }
+            }
+        }
+
+        info!(decisions = warmed, "Decision cache warm-up finished");
+        warmed
+    }
+
+    async fn tenants(&self) -> Result<Vec<String>> {
+        let url = format!("{}/api/tenants", self.audit_store_url);
+        let tenants: Vec<TenantRecord> = self
+            .http_client
+            .get(&url)
+            .query(&[("status", "active")])
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach audit store at {}", url))?
+            .error_for_status()
+            .context("Audit store rejected the tenant listing")?
+            .json()
+            .await
+            .context("Invalid tenant listing from audit store")?;
+
+        Ok(tenants.into_iter().map(|tenant| tenant.tenant_id).collect())
+    }
+
+    /// Up to `inputs_per_tenant` distinct inputs, most recent first.
+    async fn recent_inputs(&self, tenant_id: &str) -> Result<Vec<AbacInput>> {
+        let url = format!("{}/api/audit/logs", self.audit_store_url);
+        let limit = self
+            .inputs_per_tenant
+            .saturating_mul(AUDIT_ENTRIES_PER_INPUT);
+        let page: AuditLogPage = self
+            .http_client
+            .get(&url)
+            .header(TENANT_SCOPE_HEADER, tenant_id)
+            .query(&[
+                ("tenant_id", tenant_id),
+                ("protocol", "http"),
+                ("limit", &limit.to_string()),
+            ])
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach audit store at {}", url))?
+            .error_for_status()
+            .context("Audit store rejected the log query")?
+            .json()
+            .await
+            .context("Invalid audit logs from audit store")?;
+
+        let mut seen = HashSet::new();
+        let inputs = page
+            .logs
+            .into_iter()
+            .filter_map(AuditLogRecord::into_input)
+            .filter(|input| input.subject.tenant_id == tenant_id)
+            .filter(|input| {
+                let value = serde_json::to_value(input).unwrap_or_default();
+                seen.insert(DecisionCache::canonical_input(&value))
+            })
+            .take(self.inputs_per_tenant)
+            .collect();
+        Ok(inputs)
+    }
+}
@@ -0,0 +1,144 @@
+use super::config_dump::CONFIG_PATH;
+use super::health::HEALTH_PATH;
+use anyhow::Result;
+use http::Method;
+use std::collections::BTreeSet;
+
+/// Paths forwarded straight to the default upstream, skipping tenant
+/// extraction and policy evaluation, e.g. upstream `/healthz` or `/metrics`.
+///
+/// An entry matches its path exactly, or with a trailing `/*` any path below
+/// it (`/metrics/*` matches `/metrics/jvm` but not `/metrics` or
+/// `/metrics-admin`). Only `GET` and `HEAD` requests are bypassed, and only
+/// when the request path has no empty or dot segments and no
+/// percent-encoding, so a path the upstream would normalize to a different
+/// resource is always enforced.
+#[derive(Debug, Clone, Default)]
+pub struct BypassPaths {
+    exact: BTreeSet<String>,
+    prefixes: Vec<String>,
+}
+
+impl BypassPaths {
+    pub fn new(entries: &[String]) -> Result<Self> {
+        let mut paths = Self::default();
+        for entry in entries {
+            let entry = entry.trim();
+            if !entry.starts_with('/') {
+                anyhow::bail!("bypass path '{}' must start with '/'", entry);
+            }
+            let (path, prefix) = match entry.strip_suffix("/*") {
+                Some(base) => (base, true),
+                None => (entry, false),
+            };
+            if path.is_empty() || path == "/" {
+                anyhow::bail!(
+                    "bypass path '{}' would skip enforcement for every path",
+                    entry
+                );
+            }
+            if !is_plain_path(path) || path.contains('*') {
+                anyhow::bail!(
+                    "bypass path '{}' must be a plain path, optionally ending in '/*'",
+                    entry
+                );
+            }
+            if path == HEALTH_PATH || path == CONFIG_PATH {
+                anyhow::bail!("{} is answered by the proxy and cannot be bypassed", path);
+            }
+
+            if prefix {
+                paths.prefixes.push(format!("{}/", path));
+            } else {
+                paths.exact.insert(path.to_string());
+            }
+        }
+        Ok(paths)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.prefixes.is_empty()
+    }
+
+    /// Whether a `method` request for `path` skips enforcement.
+    pub fn matches(&self, method: &Method, path: &str) -> bool {
+        if self.is_empty() || (method != Method::GET && method != Method::HEAD) {
+            return false;
+        }
+        if !is_plain_path(path) {
+            return false;
+        }
+        self.exact.contains(path)
+            || self.prefixes.iter().any(|prefix| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| !rest.is_empty())
+            })
+    }
+}
+
+/// An absolute path with no empty, `.` or `..` segments and nothing an
+/// upstream would decode.
+fn is_plain_path(path: &str) -> bool {
+    match path.strip_prefix('/') {
+        Some(rest) => {
+            !path.contains(['%', '\\', '?', '#'])
+                && rest
+                    .split('/')
+                    .all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bypass(entries: &[&str]) -> BypassPaths {
+        let entries: Vec<String> = entries.iter().map(|entry| entry.to_string()).collect();
+        BypassPaths::new(&entries).unwrap()
+    }
+
+    #[test]
+    fn exact_entries_match_only_their_path() {
+        let paths = bypass(&["/healthz"]);
+        assert!(paths.matches(&Method::GET, "/healthz"));
+        assert!(paths.matches(&Method::HEAD, "/healthz"));
+        assert!(!paths.matches(&Method::POST, "/healthz"));
+        assert!(!paths.matches(&Method::GET, "/healthz/"));
+        assert!(!paths.matches(&Method::GET, "/healthz/../admin"));
+        assert!(!paths.matches(&Method::GET, "/healthzz"));
+    }
+
+    #[test]
+    fn prefix_entries_match_below_a_segment_boundary() {
+        let paths = bypass(&["/metrics/*"]);
+        assert!(paths.matches(&Method::GET, "/metrics/jvm"));
+        assert!(!paths.matches(&Method::GET, "/metrics"));
+        assert!(!paths.matches(&Method::GET, "/metrics/"));
+        assert!(!paths.matches(&Method::GET, "/metrics-admin"));
+        assert!(!paths.matches(&Method::GET, "/metrics/../tenants"));
+        assert!(!paths.matches(&Method::GET, "/metrics/%2e%2e/tenants"));
+        assert!(!paths.matches(&Method::GET, "/metrics//jvm"));
+    }
+
+    #[test]
+    fn broad_or_ambiguous_entries_are_rejected() {
+        for entry in [
+            "/",
+            "/*",
+            "metrics",
+            "/api/*/health",
+            "/a/../b",
+            "/a%2fb",
+            "/health",
+            "/proxy/config",
+        ] {
+            assert!(
+                BypassPaths::new(&[entry.to_string()]).is_err(),
+                "{} was accepted",
+                entry
+            );
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use super::ProxyState;
+use bytes::Bytes;
+use http::{Response, StatusCode};
+use http_body_util::Full;
+
+/// Path the proxy answers with its effective configuration for diagnostics.
+pub const CONFIG_PATH: &str = "/proxy/config";
+
+/// Render the configuration loaded at startup, with secrets redacted by
+/// [`crate::config::ProxyConfig::dump`].
+pub fn config_response(state: &ProxyState) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .body(Full::new(Bytes::from(state.config.dump().to_string())))
+        .unwrap()
+}
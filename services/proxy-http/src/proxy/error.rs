@@ -26,12 +26,21 @@ pub enum ProxyError {
 
     #[error("Request timeout")]
     Timeout,
+
+    #[error("Quota exceeded: {quota_type} limit of {limit} reached")]
+    QuotaExceeded {
+        quota_type: String,
+        limit: u64,
+        remaining: u64,
+        /// Seconds until usage goes down, when the quota tracker knows.
+        retry_after_secs: Option<u64>,
+    },
 }
 
 impl ProxyError {
     pub fn to_response(&self, request_id: Option<&str>) -> Response<Full<Bytes>> {
         let (status, error_code, message) = match self {
-            ProxyError::Auth(e) => (StatusCode::UNAUTHORIZED, "AUTH_ERROR", e.to_string()),
+            ProxyError::Auth(e) => (e.to_status_code(), e.error_code(), e.to_string()),
             ProxyError::Policy(e) => (
                 e.to_status_code(),
                 match e {
@@ -39,6 +48,7 @@ impl ProxyError {
                     PolicyError::TenantNotFound(_) => "TENANT_NOT_FOUND",
                     PolicyError::EnforcerUnreachable(_) => "ENFORCER_UNREACHABLE",
                     PolicyError::EvaluationTimeout => "EVALUATION_TIMEOUT",
+                    PolicyError::MalformedDecision(_) => "MALFORMED_DECISION",
                     _ => "POLICY_ERROR",
                 },
                 e.to_string(),
@@ -59,6 +69,11 @@ impl ProxyError {
                 "TIMEOUT",
                 "Request timeout".to_string(),
             ),
+            ProxyError::QuotaExceeded { .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "QUOTA_EXCEEDED",
+                self.to_string(),
+            ),
         };
 
         let body_json = json!({
@@ -67,12 +82,108 @@ impl ProxyError {
             "request_id": request_id,
         });
 
-        Response::builder()
+        let mut builder = Response::builder()
             .status(status)
-            .header("Content-Type", "application/json")
+            .header("Content-Type", "application/json");
+        if let ProxyError::QuotaExceeded {
+            limit,
+            remaining,
+            retry_after_secs,
+            ..
+        } = self
+        {
+            builder = builder
+                .header("X-Quota-Limit", *limit)
+                .header("X-Quota-Remaining", *remaining);
+            if let Some(seconds) = retry_after_secs {
+                builder = builder.header(http::header::RETRY_AFTER, *seconds);
+            }
+        }
+
+        builder
             .body(Full::new(Bytes::from(
                 serde_json::to_string(&body_json).unwrap(),
             )))
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn status_and_body(err: AuthError) -> (StatusCode, serde_json::Value) {
+        use http_body_util::BodyExt;
+
+        let response = ProxyError::Auth(err).to_response(Some("req-1"));
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn auth_errors_map_to_distinct_codes() {
+        let cases = [
+            (
+                AuthError::TenantIdNotFound,
+                StatusCode::UNAUTHORIZED,
+                "TENANT_NOT_FOUND",
+            ),
+            (
+                AuthError::TenantIdMismatch {
+                    cert_tenant: "tenant-a".to_string(),
+                    jwt_tenant: "tenant-b".to_string(),
+                },
+                StatusCode::FORBIDDEN,
+                "TENANT_MISMATCH",
+            ),
+            (
+                AuthError::TenantHeaderMismatch {
+                    authenticated_tenant: "tenant-a".to_string(),
+                    header_tenant: "tenant-b".to_string(),
+                },
+                StatusCode::FORBIDDEN,
+                "TENANT_HEADER_MISMATCH",
+            ),
+            (
+                AuthError::InvalidJwt("ExpiredSignature".to_string()),
+                StatusCode::UNAUTHORIZED,
+                "INVALID_JWT",
+            ),
+            (
+                AuthError::UnsupportedAuthMethod,
+                StatusCode::UNAUTHORIZED,
+                "UNSUPPORTED_AUTH_METHOD",
+            ),
+            (
+                AuthError::MissingAuthHeader,
+                StatusCode::UNAUTHORIZED,
+                "MISSING_AUTH_HEADER",
+            ),
+            (
+                AuthError::InvalidCertificate("bad der".to_string()),
+                StatusCode::UNAUTHORIZED,
+                "INVALID_CERTIFICATE",
+            ),
+            (
+                AuthError::CertificateExpired("Jan  1 00:00:00 2021 +00:00".to_string()),
+                StatusCode::UNAUTHORIZED,
+                "CERTIFICATE_EXPIRED",
+            ),
+            (
+                AuthError::CertificateNotYetValid("Jan  1 00:00:00 2100 +00:00".to_string()),
+                StatusCode::UNAUTHORIZED,
+                "CERTIFICATE_NOT_YET_VALID",
+            ),
+        ];
+
+        for (err, expected_status, expected_code) in cases {
+            let message = err.to_string();
+            let (status, body) = status_and_body(err).await;
+            assert_eq!(status, expected_status, "status for {}", expected_code);
+            assert_eq!(body["error"], expected_code);
+            assert_eq!(body["message"], message);
+            assert_eq!(body["request_id"], "req-1");
+        }
+    }
+}
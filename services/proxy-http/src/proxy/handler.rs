@@ -1,11 +1,19 @@
+use super::config_dump::{config_response, CONFIG_PATH};
+use super::health::{health_response, HEALTH_PATH};
 use super::{ProxyError, ProxyState};
+use crate::audit::BypassAuditEvent;
 use crate::config::ProxyConfig;
-use crate::policy::AbacInput;
+use crate::policy::{
+    AbacInputBuilder, PolicyClient, PolicyError, ENFORCEMENT_BYPASSED, SHADOW_DECISION_HEADER,
+};
+use crate::redaction::{RedactionEngine, REDACTION_WARNINGS_HEADER};
+use crate::reload::SharedSettings;
 use crate::server::PeerInfo;
 use bytes::Bytes;
 use http::{HeaderValue, Request, Response};
 use http_body_util::{BodyExt, Full};
 use hyper::body::{Body, Incoming};
+use std::convert::Infallible;
 use std::sync::Arc;
 use tracing::{debug, error, info, instrument, warn};
 
@@ -19,23 +27,85 @@ impl ProxyHandler {
         Ok(Self { state })
     }
 
+    /// Runtime settings used by this handler; updates apply to new requests.
+    pub fn settings(&self) -> SharedSettings {
+        self.state.settings.clone()
+    }
+
+    /// Client whose decision cache this handler reads.
+    pub(crate) fn policy_client(&self) -> Arc<PolicyClient> {
+        self.state.policy_client.clone()
+    }
+
+    /// Serve a request, rendering pipeline failures as JSON error responses
+    /// instead of dropping the connection.
+    pub async fn serve(
+        &self,
+        req: Request<Incoming>,
+        peer_info: Option<Arc<PeerInfo>>,
+    ) -> Result<Response<Full<Bytes>>, Infallible> {
+        match self.handle_request(req, peer_info).await {
+            Ok(response) => Ok(response),
+            Err(err) => {
+                debug!(error = %err, "Request rejected");
+                Ok(err.to_response(None))
+            }
+        }
+    }
+
     #[instrument(skip(self, req), fields(request_id))]
     pub async fn handle_request(
         &self,
         req: Request<Incoming>,
         peer_info: Option<Arc<PeerInfo>>,
     ) -> Result<Response<Full<Bytes>>, ProxyError> {
+        // Health checks and the config dump are answered by the proxy itself,
+        // without tenant context
+        if req.method() == http::Method::GET && req.uri().path() == HEALTH_PATH {
+            return Ok(health_response(&self.state).await);
+        }
+        if req.method() == http::Method::GET && req.uri().path() == CONFIG_PATH {
+            return Ok(config_response(&self.state));
+        }
+
         // Wrap entire pipeline in timeout
-        let timeout_duration = self.state.config.request_timeout();
+        let timeout_duration = self.state.settings.current().request_timeout();
+        let bypassed = self
+            .state
+            .bypass_paths
+            .matches(req.method(), req.uri().path());
+        let pipeline = async {
+            if bypassed {
+                self.forward_bypassed(req).await
+            } else {
+                self.handle_request_inner(req, peer_info).await
+            }
+        };
 
-        match tokio::time::timeout(timeout_duration, self.handle_request_inner(req, peer_info))
-            .await
-        {
+        match tokio::time::timeout(timeout_duration, pipeline).await {
             Ok(result) => result,
             Err(_) => Err(ProxyError::Timeout),
         }
     }
 
+    /// Forward a request on a configured bypass path to the default upstream
+    /// with no tenant extraction, quota check, policy query or redaction.
+    async fn forward_bypassed(
+        &self,
+        req: Request<Incoming>,
+    ) -> Result<Response<Full<Bytes>>, ProxyError> {
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+        let forwarded = self.state.upstream_client.forward_bypassed(req).await?;
+        debug!(
+            method = %method,
+            path = %path,
+            status = forwarded.response.status().as_u16(),
+            "Bypass path forwarded without enforcement"
+        );
+        Ok(forwarded.response)
+    }
+
     async fn handle_request_inner(
         &self,
         req: Request<Incoming>,
@@ -65,6 +135,37 @@ impl ProxyHandler {
             tenant_context.client_ip = Some(ip);
         }
 
+        // Quota pre-flight: tenants over a limit are turned away before the
+        // policy query. An unreachable quota tracker does not block traffic.
+        if let Some(quota_client) = &self.state.quota_client {
+            match quota_client.check(&tenant_context.tenant_id).await {
+                Ok(check) => {
+                    if let Some(usage) = check.exceeded_usage() {
+                        info!(
+                            tenant_id = %tenant_context.tenant_id,
+                            quota_type = ?check.quota_type,
+                            used = usage.used,
+                            limit = usage.limit,
+                            "Request denied by quota"
+                        );
+                        return Err(ProxyError::QuotaExceeded {
+                            quota_type: check.quota_type.clone().unwrap_or_default(),
+                            limit: usage.limit,
+                            remaining: usage.remaining.unwrap_or(0),
+                            retry_after_secs: usage.resets_in_seconds,
+                        });
+                    }
+                }
+                Err(err) => {
+                    warn!(
+                        tenant_id = %tenant_context.tenant_id,
+                        error = %err,
+                        "Failed to check quota; proceeding without quota enforcement"
+                    );
+                }
+            }
+        }
+
         let quota_usage_bytes = if let Some(quota_client) = &self.state.quota_client {
             match quota_client.get_usage(&tenant_context.tenant_id).await {
                 Ok(usage) => Some(usage.bandwidth_bytes),
@@ -97,46 +198,97 @@ impl ProxyHandler {
         let path = req.uri().path().to_string();
         let query = req.uri().query().map(|q| q.to_string());
 
-        let mut abac_input = AbacInput::from_request(
+        let abac_input = AbacInputBuilder::from_request(
             &tenant_context,
             &method,
             &path,
             query.as_deref(),
             req.headers(),
-        );
-
-        if let Some(bytes) = quota_usage_bytes {
-            abac_input.environment.bandwidth_used = Some(bytes as f64);
-        }
+        )
+        .bandwidth_used(quota_usage_bytes.map(|bytes| bytes as f64))
+        .build();
 
         debug!(abac_input = ?abac_input, "ABAC input prepared");
 
-        // Step 3: Query policy enforcer
+        // Step 3: Query policy enforcer. The input only carries attributes
+        // taken from the request line and headers, so a denial answers `403`
+        // before any of the request body has been read. Tenants in shadow
+        // mode have denials logged and the request forwarded anyway.
         debug!("Step 3: Querying policy enforcer");
+        let shadow_mode = self.state.config.is_shadow_mode(&tenant_context.tenant_id);
         let policy_start = std::time::Instant::now();
-        let policy_decision = self
+        let policy_decision = match self
             .state
             .policy_client
             .query_policy(&tenant_context.tenant_id, abac_input)
-            .await?;
+            .await
+        {
+            Ok(decision) => Some(decision),
+            Err(PolicyError::Denied { reason }) if shadow_mode => {
+                warn!(
+                    tenant_id = %tenant_context.tenant_id,
+                    method = %method,
+                    path = %path,
+                    reason = ?reason,
+                    "Shadow mode: request would be denied, forwarding to upstream"
+                );
+                None
+            }
+            Err(err) => return Err(err.into()),
+        };
         let policy_latency = policy_start.elapsed();
+        let allowed = policy_decision.is_some();
+        if let Some(bypass_reason) = policy_decision
+            .as_ref()
+            .and_then(|decision| decision.bypass_reason.as_deref())
+        {
+            warn!(
+                audit_event = ENFORCEMENT_BYPASSED,
+                tenant_id = %tenant_context.tenant_id,
+                request_id = %request_id,
+                method = %method,
+                path = %path,
+                bypass_reason,
+                "Policy enforcement bypassed, request allowed without an enforcer decision"
+            );
+            if let Some(audit_sink) = &self.state.audit_sink {
+                audit_sink.record(BypassAuditEvent::new(
+                    &tenant_context,
+                    method.as_str(),
+                    &path,
+                    bypass_reason,
+                ));
+            }
+        }
+        let redaction_paths = policy_decision
+            .as_ref()
+            .and_then(|decision| decision.redaction_paths());
 
         info!(
             tenant_id = %tenant_context.tenant_id,
-            allow = policy_decision.allow,
-            redact_paths = ?policy_decision.redact,
+            allow = allowed,
+            shadow_mode,
+            redact_paths = ?redaction_paths,
             policy_latency_ms = policy_latency.as_millis(),
             "Policy decision received"
         );
 
-        // Step 4: Forward request to upstream
+        // Step 4: Forward request to upstream; only allowed requests have
+        // their body buffered
         debug!("Step 4: Forwarding request to upstream");
         let upstream_start = std::time::Instant::now();
-        let forwarded = self.state.upstream_client.forward_request(req).await?;
+        let forwarded = self
+            .state
+            .upstream_client
+            .forward_request(&tenant_context.tenant_id, req)
+            .await?;
         let upstream_latency = upstream_start.elapsed();
         let mut upstream_response = forwarded.response;
         let request_body_bytes = forwarded.request_body_bytes;
         let mut response_body_bytes = forwarded.response_body_bytes;
+        // Body of `upstream_response`, kept for debug logging without reading
+        // the response again
+        let mut response_body = forwarded.response_body;
 
         debug!(
             status = upstream_response.status().as_u16(),
@@ -145,7 +297,7 @@ impl ProxyHandler {
         );
 
         // Step 5: Apply redaction if needed
-        if let Some(redact_paths) = &policy_decision.redact {
+        if let Some(redact_paths) = &redaction_paths {
             if !redact_paths.is_empty() {
                 debug!("Step 5: Applying redaction");
 
@@ -169,10 +321,11 @@ impl ProxyHandler {
 
                     // Check body size limit
                     let body_len = body_bytes.len();
-                    if body_len > self.state.config.max_body_size_bytes {
+                    let max_body_size_bytes = self.state.settings.current().max_body_size_bytes;
+                    if body_len > max_body_size_bytes {
                         warn!(
                             size = body_len,
-                            limit = self.state.config.max_body_size_bytes,
+                            limit = max_body_size_bytes,
                             "Response body exceeds max size, skipping redaction"
                         );
                         response_body_bytes = body_len;
@@ -182,10 +335,10 @@ impl ProxyHandler {
                         match self
                             .state
                             .redaction_engine
-                            .redact_fields(&body_bytes, redact_paths)
+                            .redact_fields_with_report(&body_bytes, redact_paths)
                         {
-                            Ok(redacted_bytes) => {
-                                let redacted = Bytes::from(redacted_bytes);
+                            Ok(outcome) => {
+                                let redacted = Bytes::from(outcome.body);
                                 let redacted_len = redacted.len();
                                 info!(
                                     original_size = body_len,
@@ -195,6 +348,7 @@ impl ProxyHandler {
                                 );
 
                                 // Rebuild response with redacted body
+                                response_body = redacted.clone();
                                 let mut response = Response::from_parts(parts, Full::new(redacted));
 
                                 // Update Content-Length header
@@ -204,6 +358,34 @@ impl ProxyHandler {
                                     HeaderValue::from_str(&body_len.to_string()).unwrap(),
                                 );
 
+                                if !outcome.depth_limited_paths.is_empty() {
+                                    warn!(
+                                        tenant_id = %tenant_context.tenant_id,
+                                        depth_limited_paths = ?outcome.depth_limited_paths,
+                                        max_depth = self.state.config.max_redaction_depth,
+                                        "Response nests deeper than the redaction depth limit, fields below it may be unredacted"
+                                    );
+                                }
+
+                                if self.state.config.strict_redaction
+                                    && !outcome.unmatched_paths.is_empty()
+                                {
+                                    warn!(
+                                        tenant_id = %tenant_context.tenant_id,
+                                        unmatched_paths = ?outcome.unmatched_paths,
+                                        "Redaction paths matched nothing in the response"
+                                    );
+                                    if self.state.config.redaction_warnings_header {
+                                        if let Ok(value) = HeaderValue::from_str(
+                                            &outcome.unmatched_paths.join(", "),
+                                        ) {
+                                            response
+                                                .headers_mut()
+                                                .insert(REDACTION_WARNINGS_HEADER, value);
+                                        }
+                                    }
+                                }
+
                                 response_body_bytes = redacted_len;
                                 upstream_response = response;
                             }
@@ -225,6 +407,19 @@ impl ProxyHandler {
             }
         }
 
+        if self.state.config.logs_bodies_for(&tenant_context.tenant_id) {
+            let paths = redaction_paths.as_deref().unwrap_or_default();
+            let max_bytes = self.state.config.debug_body_max_bytes;
+            let engine = &self.state.redaction_engine;
+            info!(
+                tenant_id = %tenant_context.tenant_id,
+                request_id = %request_id,
+                request_body = %debug_body(engine, &forwarded.request_body, paths, max_bytes),
+                response_body = %debug_body(engine, &response_body, paths, max_bytes),
+                "Request and response bodies"
+            );
+        }
+
         // Step 6: Log bandwidth usage (for future quota integration)
         let response_size = upstream_response
             .body()
@@ -260,8 +455,8 @@ impl ProxyHandler {
             method = %method,
             path = %path,
             status = upstream_response.status().as_u16(),
-            policy_decision = "allow",
-            redaction_applied = policy_decision.redact.is_some(),
+            policy_decision = if allowed { "allow" } else { "shadow_deny" },
+            redaction_applied = redaction_paths.is_some(),
             policy_latency_ms = policy_latency.as_millis(),
             upstream_latency_ms = upstream_latency.as_millis(),
             total_latency_ms = total_latency.as_millis(),
@@ -274,7 +469,39 @@ impl ProxyHandler {
         parts
             .headers
             .insert("x-request-id", HeaderValue::from_str(&request_id).unwrap());
+        if shadow_mode {
+            parts.headers.insert(
+                SHADOW_DECISION_HEADER,
+                HeaderValue::from_static(if allowed { "allow" } else { "deny" }),
+            );
+        }
 
         Ok(Response::from_parts(parts, body))
     }
 }
+
+/// Render a body for tenant debug logging: redacted with the decision's
+/// paths and cut to `max_bytes`. When there are paths to apply, bodies that
+/// are not JSON or that nest past the redaction depth are left out, since
+/// they could still hold the fields the paths name.
+fn debug_body(engine: &RedactionEngine, body: &[u8], paths: &[String], max_bytes: usize) -> String {
+    let redacted = if body.is_empty() || paths.is_empty() {
+        body.to_vec()
+    } else if serde_json::from_slice::<serde_json::Value>(body).is_err() {
+        return format!("<{} bytes omitted: not JSON>", body.len());
+    } else {
+        match engine.redact_fields(body, paths) {
+            Ok(redacted) => redacted,
+            Err(e) => return format!("<{} bytes omitted: {}>", body.len(), e),
+        }
+    };
+
+    if redacted.len() <= max_bytes {
+        return String::from_utf8_lossy(&redacted).into_owned();
+    }
+    format!(
+        "{}<{} more bytes>",
+        String::from_utf8_lossy(&redacted[..max_bytes]),
+        redacted.len() - max_bytes
+    )
+}
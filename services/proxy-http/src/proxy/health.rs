@@ -0,0 +1,61 @@
+use super::ProxyState;
+use bytes::Bytes;
+use http::{Response, StatusCode};
+use http_body_util::Full;
+use serde_json::{json, Map, Value};
+
+pub const HEALTH_PATH: &str = "/health";
+
+/// Probe downstream dependencies and render the `/health` response.
+///
+/// The enforcer is critical: the proxy cannot authorize anything without it,
+/// so an unreachable enforcer yields `503`. The quota tracker only enriches
+/// policy input, so losing it reports `degraded` with `200`.
+pub async fn health_response(state: &ProxyState) -> Response<Full<Bytes>> {
+    let mut dependencies = Map::new();
+    let mut critical_down = false;
+    let mut degraded = false;
+
+    let enforcer = state.policy_client.check_health().await;
+    critical_down |= enforcer.is_err();
+    dependencies.insert(
+        "enforcer".to_string(),
+        dependency_status(true, enforcer.map_err(|e| e.to_string())),
+    );
+
+    if let Some(quota_client) = &state.quota_client {
+        let quota = quota_client.check_health().await;
+        degraded |= quota.is_err();
+        dependencies.insert(
+            "quota_tracker".to_string(),
+            dependency_status(false, quota.map_err(|e| format!("{:#}", e))),
+        );
+    }
+
+    let (status, label) = if critical_down {
+        (StatusCode::SERVICE_UNAVAILABLE, "unhealthy")
+    } else if degraded {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "healthy")
+    };
+
+    let body = json!({
+        "status": label,
+        "service": "edge-policy-proxy-http",
+        "dependencies": dependencies,
+    });
+
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .unwrap()
+}
+
+fn dependency_status(critical: bool, result: Result<(), String>) -> Value {
+    match result {
+        Ok(()) => json!({ "status": "up", "critical": critical }),
+        Err(error) => json!({ "status": "down", "critical": critical, "error": error }),
+    }
+}
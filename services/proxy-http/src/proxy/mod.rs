@@ -1,26 +1,42 @@
+mod bypass;
+mod config_dump;
 mod error;
 pub(crate) mod handler;
+mod health;
 mod upstream;
 
+pub use bypass::BypassPaths;
+pub use config_dump::CONFIG_PATH;
 pub use error::ProxyError;
 pub use handler::ProxyHandler;
 pub use upstream::UpstreamClient;
 
+use crate::audit::{AuditSink, DEFAULT_AUDIT_QUEUE_CAPACITY};
 use crate::auth::TenantExtractor;
 use crate::config::ProxyConfig;
 use crate::policy::PolicyClient;
 use crate::quota::QuotaClient;
 use crate::redaction::RedactionEngine;
+use crate::reload::{RuntimeSettings, SharedSettings};
+use edge_policy_enrichment::InputEnrichment;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout for dependency probes made while answering `/health`.
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
 
 #[derive(Clone)]
 pub struct ProxyState {
     pub config: Arc<ProxyConfig>,
+    pub settings: SharedSettings,
     pub tenant_extractor: Arc<TenantExtractor>,
     pub policy_client: Arc<PolicyClient>,
     pub redaction_engine: Arc<RedactionEngine>,
     pub upstream_client: Arc<UpstreamClient>,
     pub quota_client: Option<Arc<QuotaClient>>,
+    pub bypass_paths: Arc<BypassPaths>,
+    /// Receives `ENFORCEMENT_BYPASSED` events; set when `AUDIT_STORE_URL` is.
+    pub audit_sink: Option<AuditSink>,
 }
 
 impl ProxyState {
@@ -28,17 +44,33 @@ impl ProxyState {
         use anyhow::Context;
 
         let tenant_extractor = Arc::new(TenantExtractor::new(&config)?);
-        let policy_client = Arc::new(PolicyClient::new(
-            config.enforcer_url.clone(),
-            crate::policy::DEFAULT_ENFORCER_TIMEOUT_SECS,
-        )?);
-        let redaction_engine = Arc::new(RedactionEngine::new());
-        let upstream_client = Arc::new(UpstreamClient::new(
-            config.upstream_url.clone(),
-            config.request_timeout_secs,
-            config.max_body_size_bytes,
-            config.forward_auth_header,
-        )?);
+        let bypass_paths =
+            Arc::new(BypassPaths::new(&config.bypass_paths).context("Invalid BYPASS_PATHS")?);
+        let policy_client = Arc::new(
+            PolicyClient::new(
+                config.enforcer_url.clone(),
+                crate::policy::DEFAULT_ENFORCER_TIMEOUT_SECS,
+            )?
+            .with_decision_cache(config.policy_cache_ttl(), config.policy_cache_obligations)
+            .with_malformed_decision_mode(config.malformed_decision_mode)
+            .with_enforcer_failure_mode(config.enforcer_failure_mode)
+            .with_enrichment(InputEnrichment::standard(config.default_region.clone())),
+        );
+        let settings = SharedSettings::new(RuntimeSettings::from_config(&config));
+        let redaction_engine =
+            Arc::new(RedactionEngine::new().with_max_depth(config.max_redaction_depth));
+        let upstream_client = Arc::new(
+            UpstreamClient::new(
+                config.upstream_url.clone(),
+                settings.clone(),
+                config.forward_auth_header,
+            )?
+            .with_tenant_upstreams(&config.tenant_upstreams)
+            .with_tenant_id_header(
+                http::HeaderName::from_bytes(config.tenant_id_header.as_bytes())
+                    .context("Invalid TENANT_ID_HEADER")?,
+            ),
+        );
         let quota_client = if let Some(url) = config.quota_tracker_url.clone() {
             let token = config
                 .quota_tracker_token
@@ -49,13 +81,25 @@ impl ProxyState {
             None
         };
 
+        let audit_sink = match config.audit_store_url.as_deref() {
+            Some(url) => Some(AuditSink::spawn(
+                url,
+                DEFAULT_AUDIT_QUEUE_CAPACITY,
+                Duration::from_secs(crate::policy::DEFAULT_ENFORCER_TIMEOUT_SECS),
+            )?),
+            None => None,
+        };
+
         Ok(Self {
             config: Arc::new(config),
+            settings,
             tenant_extractor,
             policy_client,
             redaction_engine,
             upstream_client,
             quota_client,
+            bypass_paths,
+            audit_sink,
         })
     }
 }
@@ -1,36 +1,51 @@
 use super::ProxyError;
+use crate::reload::SharedSettings;
 use bytes::Bytes;
-use http::{HeaderMap, Request, Response};
+use http::{HeaderMap, HeaderName, Request, Response};
 use http_body_util::{BodyExt, Full};
 use hyper::body::Incoming;
 use reqwest::Client;
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, info, instrument};
 
 pub struct ForwardedResponse {
     pub response: Response<Full<Bytes>>,
+    /// Request body as sent upstream
+    pub request_body: Bytes,
     pub request_body_bytes: usize,
+    /// Response body as received from upstream, shared with `response`
+    pub response_body: Bytes,
     pub response_body_bytes: usize,
 }
 
 pub struct UpstreamClient {
     http_client: Client,
     upstream_base_url: String,
-    max_body_size_bytes: usize,
+    tenant_upstreams: HashMap<String, String>,
+    settings: SharedSettings,
     forward_auth_header: bool,
+    tenant_id_header: Option<HeaderName>,
+}
+
+/// Map a reqwest failure, reporting timeouts as a gateway timeout.
+fn upstream_error(context: &str, err: reqwest::Error) -> ProxyError {
+    if err.is_timeout() {
+        ProxyError::Timeout
+    } else {
+        ProxyError::Upstream(format!("{}: {}", context, err))
+    }
 }
 
 impl UpstreamClient {
     pub fn new(
         upstream_url: String,
-        timeout_secs: u64,
-        max_body_size_bytes: usize,
+        settings: SharedSettings,
         forward_auth_header: bool,
     ) -> anyhow::Result<Self> {
         // Build client with both HTTP/1.1 and HTTP/2 support
         // Protocol negotiation via ALPN or upgrade
+        // Timeouts are applied per request so reloaded settings take effect
         let http_client = Client::builder()
-            .timeout(Duration::from_secs(timeout_secs))
             .pool_max_idle_per_host(20)
             // Removed .http2_prior_knowledge() to support both HTTP/1.1 and HTTP/2
             .build()?;
@@ -38,15 +53,59 @@ impl UpstreamClient {
         Ok(Self {
             http_client,
             upstream_base_url: upstream_url.trim_end_matches('/').to_string(),
-            max_body_size_bytes,
+            tenant_upstreams: HashMap::new(),
+            settings,
             forward_auth_header,
+            tenant_id_header: None,
         })
     }
 
+    /// Also strip the configured tenant ID header from forwarded requests,
+    /// so a client-supplied value never reaches the upstream.
+    pub fn with_tenant_id_header(mut self, header: HeaderName) -> Self {
+        self.tenant_id_header = Some(header);
+        self
+    }
+
+    /// Route the given tenants to their own upstreams instead of the default one.
+    pub fn with_tenant_upstreams(mut self, upstreams: &BTreeMap<String, String>) -> Self {
+        self.tenant_upstreams = upstreams
+            .iter()
+            .map(|(tenant_id, url)| (tenant_id.clone(), url.trim_end_matches('/').to_string()))
+            .collect();
+        self
+    }
+
+    /// Base URL requests for `tenant_id` are forwarded to.
+    pub fn upstream_for(&self, tenant_id: &str) -> &str {
+        self.tenant_upstreams
+            .get(tenant_id)
+            .map(String::as_str)
+            .unwrap_or(&self.upstream_base_url)
+    }
+
     #[instrument(skip(self, req), fields(method = %req.method(), path = %req.uri().path()))]
     pub async fn forward_request(
+        &self,
+        tenant_id: &str,
+        req: Request<Incoming>,
+    ) -> Result<ForwardedResponse, ProxyError> {
+        self.forward_to(self.upstream_for(tenant_id), req).await
+    }
+
+    /// Forward a request that carries no tenant to the default upstream.
+    #[instrument(skip(self, req), fields(method = %req.method(), path = %req.uri().path()))]
+    pub async fn forward_bypassed(
         &self,
         req: Request<Incoming>,
+    ) -> Result<ForwardedResponse, ProxyError> {
+        self.forward_to(&self.upstream_base_url, req).await
+    }
+
+    async fn forward_to(
+        &self,
+        base_url: &str,
+        req: Request<Incoming>,
     ) -> Result<ForwardedResponse, ProxyError> {
         let (parts, body) = req.into_parts();
 
@@ -57,7 +116,7 @@ impl UpstreamClient {
             .map(|pq| pq.as_str())
             .unwrap_or("/");
 
-        let upstream_url = format!("{}{}", self.upstream_base_url, path_and_query);
+        let upstream_url = format!("{}{}", base_url, path_and_query);
 
         debug!(upstream_url = %upstream_url, "Forwarding request to upstream");
 
@@ -69,20 +128,26 @@ impl UpstreamClient {
             .to_bytes();
 
         // Check body size limit
-        if body_bytes.len() > self.max_body_size_bytes {
+        let settings = self.settings.current();
+        if body_bytes.len() > settings.max_body_size_bytes {
             return Err(ProxyError::BodyTooLarge {
                 size: body_bytes.len(),
-                limit: self.max_body_size_bytes,
+                limit: settings.max_body_size_bytes,
             });
         }
 
         // Sanitize headers
-        let headers = Self::sanitize_headers(&parts.headers, self.forward_auth_header);
+        let headers = Self::sanitize_headers(
+            &parts.headers,
+            self.forward_auth_header,
+            self.tenant_id_header.as_ref(),
+        );
 
         // Build upstream request
         let mut upstream_req = self
             .http_client
-            .request(parts.method.clone(), &upstream_url);
+            .request(parts.method.clone(), &upstream_url)
+            .timeout(settings.request_timeout());
 
         // Add headers
         for (name, value) in headers.iter() {
@@ -105,7 +170,7 @@ impl UpstreamClient {
         let upstream_response = upstream_req
             .send()
             .await
-            .map_err(|e| ProxyError::Upstream(format!("Upstream request failed: {}", e)))?;
+            .map_err(|e| upstream_error("Upstream request failed", e))?;
 
         let latency = start.elapsed();
         let status = upstream_response.status();
@@ -125,23 +190,30 @@ impl UpstreamClient {
         }
 
         // Get body
-        let response_body = upstream_response.bytes().await.map_err(|e| {
-            ProxyError::Upstream(format!("Failed to read upstream response: {}", e))
-        })?;
+        let response_body = upstream_response
+            .bytes()
+            .await
+            .map_err(|e| upstream_error("Failed to read upstream response", e))?;
 
         let response_body_len = response_body.len();
         let response = response_builder
-            .body(Full::new(response_body))
+            .body(Full::new(response_body.clone()))
             .map_err(|e| ProxyError::Upstream(format!("Failed to build response: {}", e)))?;
 
         Ok(ForwardedResponse {
             response,
             request_body_bytes: body_bytes.len(),
+            request_body: body_bytes,
+            response_body,
             response_body_bytes: response_body_len,
         })
     }
 
-    fn sanitize_headers(headers: &HeaderMap, forward_auth: bool) -> HeaderMap {
+    fn sanitize_headers(
+        headers: &HeaderMap,
+        forward_auth: bool,
+        tenant_id_header: Option<&HeaderName>,
+    ) -> HeaderMap {
         let mut sanitized = HeaderMap::new();
 
         // List of hop-by-hop headers to remove
@@ -183,7 +255,7 @@ impl UpstreamClient {
                 continue;
             }
 
-            if INTERNAL.contains(&name_lower.as_str()) {
+            if INTERNAL.contains(&name_lower.as_str()) || Some(name) == tenant_id_header {
                 continue;
             }
 
@@ -201,13 +273,13 @@ impl UpstreamClient {
 #[cfg(test)]
 mod tests {
     use super::UpstreamClient;
-    use http::{HeaderMap, HeaderValue};
+    use http::{HeaderMap, HeaderName, HeaderValue};
 
     #[test]
     fn sanitize_headers_preserves_authorization_when_forward_enabled() {
         let mut headers = HeaderMap::new();
         headers.insert("authorization", HeaderValue::from_static("Bearer token"));
-        let sanitized = UpstreamClient::sanitize_headers(&headers, true);
+        let sanitized = UpstreamClient::sanitize_headers(&headers, true, None);
         assert_eq!(
             sanitized.get("authorization").and_then(|v| v.to_str().ok()),
             Some("Bearer token")
@@ -218,7 +290,7 @@ mod tests {
     fn sanitize_headers_strips_authorization_when_forward_disabled() {
         let mut headers = HeaderMap::new();
         headers.insert("authorization", HeaderValue::from_static("Bearer token"));
-        let sanitized = UpstreamClient::sanitize_headers(&headers, false);
+        let sanitized = UpstreamClient::sanitize_headers(&headers, false, None);
         assert!(!sanitized.contains_key("authorization"));
     }
 
@@ -233,10 +305,24 @@ mod tests {
         headers.insert("upgrade", HeaderValue::from_static("websocket"));
         headers.insert("content-type", HeaderValue::from_static("application/json"));
 
-        let sanitized = UpstreamClient::sanitize_headers(&headers, true);
+        let sanitized = UpstreamClient::sanitize_headers(&headers, true, None);
         assert!(!sanitized.contains_key("connection"));
         assert!(!sanitized.contains_key("keep-alive"));
         assert!(!sanitized.contains_key("upgrade"));
         assert!(sanitized.contains_key("content-type"));
     }
+
+    #[test]
+    fn sanitize_headers_strips_the_configured_tenant_id_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-org-id", HeaderValue::from_static("tenant-b"));
+        headers.insert("x-tenant-id", HeaderValue::from_static("tenant-b"));
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+
+        let tenant_id_header = HeaderName::from_static("x-org-id");
+        let sanitized = UpstreamClient::sanitize_headers(&headers, true, Some(&tenant_id_header));
+        assert!(!sanitized.contains_key("x-org-id"));
+        assert!(!sanitized.contains_key("x-tenant-id"));
+        assert!(sanitized.contains_key("content-type"));
+    }
 }
@@ -8,6 +8,40 @@ pub struct Usage {
     pub bandwidth_bytes: u64,
 }
 
+/// Answer from the quota tracker's `POST /api/quota/check`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuotaCheck {
+    pub exceeded: bool,
+    pub quota_type: Option<String>,
+    pub messages: QuotaUsage,
+    pub bandwidth: QuotaUsage,
+}
+
+impl QuotaCheck {
+    /// Usage of the dimension that is over its limit, if any.
+    pub fn exceeded_usage(&self) -> Option<&QuotaUsage> {
+        if !self.exceeded {
+            return None;
+        }
+        match self.quota_type.as_deref() {
+            Some(BANDWIDTH_QUOTA_TYPE) => Some(&self.bandwidth),
+            _ => Some(&self.messages),
+        }
+    }
+}
+
+/// Usage of one quota dimension; bandwidth is counted in bytes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuotaUsage {
+    pub used: u64,
+    pub limit: u64,
+    pub remaining: Option<u64>,
+    pub resets_in_seconds: Option<u64>,
+}
+
+/// `quota_type` the tracker reports for bandwidth denials.
+const BANDWIDTH_QUOTA_TYPE: &str = "bandwidth";
+
 pub struct QuotaClient {
     http_client: Client,
     base_url: String,
@@ -28,6 +62,23 @@ impl QuotaClient {
         })
     }
 
+    /// Check that the quota tracker answers its health endpoint.
+    pub async fn check_health(&self) -> Result<()> {
+        let url = format!("{}/health", self.base_url);
+        let response = self
+            .http_client
+            .get(&url)
+            .timeout(crate::proxy::HEALTH_CHECK_TIMEOUT)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach quota tracker at {}", url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Quota tracker health check returned {}", response.status());
+        }
+        Ok(())
+    }
+
     pub async fn get_usage(&self, tenant_id: &str) -> Result<Usage> {
         let url = format!("{}/tenants/{}/usage", self.base_url, tenant_id);
         let response = self
@@ -58,6 +109,38 @@ impl QuotaClient {
         }
     }
 
+    /// Read the tenant's current quota state without counting against it.
+    pub async fn check(&self, tenant_id: &str) -> Result<QuotaCheck> {
+        let url = format!("{}/api/quota/check", self.base_url);
+        let response = self
+            .http_client
+            .post(&url)
+            .bearer_auth(&self.token)
+            .json(&CheckRequest { tenant_id })
+            .send()
+            .await
+            .with_context(|| format!("Failed to check quota at {}", url))?;
+
+        if response.status().is_success() {
+            response
+                .json::<QuotaCheck>()
+                .await
+                .context("Failed to parse quota check response")
+        } else {
+            let status = response.status();
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "unable to read error body".to_string());
+            anyhow::bail!(
+                "Quota service responded with {} for POST {}: {}",
+                status,
+                url,
+                body
+            );
+        }
+    }
+
     pub async fn increment(&self, tenant_id: &str, bytes: u64, request_id: &str) -> Result<()> {
         let url = format!("{}/tenants/{}/usage", self.base_url, tenant_id);
         let payload = IncrementRequest { bytes };
@@ -95,6 +178,11 @@ impl QuotaClient {
     }
 }
 
+#[derive(Debug, Serialize)]
+struct CheckRequest<'a> {
+    tenant_id: &'a str,
+}
+
 #[derive(Debug, Serialize)]
 struct IncrementRequest {
     pub bytes: u64,
@@ -1,3 +1,3 @@
 mod client;
 
-pub use client::{QuotaClient, Usage};
+pub use client::{QuotaCheck, QuotaClient, QuotaUsage, Usage};
@@ -2,34 +2,89 @@ use super::{RedactionError, MAX_REDACTION_DEPTH};
 use serde_json::Value;
 use tracing::{debug, info};
 
-pub struct RedactionEngine;
+pub struct RedactionEngine {
+    max_depth: usize,
+}
+
+/// Redacted body together with the paths that matched nothing in it.
+#[derive(Debug, Clone)]
+pub struct RedactionOutcome {
+    pub body: Vec<u8>,
+    pub unmatched_paths: Vec<String>,
+    /// Paths whose search stopped at the depth limit while the body nested
+    /// further, so matches below the limit may still be in `body`.
+    pub depth_limited_paths: Vec<String>,
+}
 
 impl RedactionEngine {
     pub fn new() -> Self {
-        Self
+        Self {
+            max_depth: MAX_REDACTION_DEPTH,
+        }
     }
 
+    /// Deepest nesting level searched for redaction paths.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Removes every path from the body, failing with
+    /// [`RedactionError::MaxDepthExceeded`] if the body nests deeper than
+    /// the depth limit where a path could still match.
     pub fn redact_fields(
         &self,
         json_body: &[u8],
         paths: &[String],
     ) -> Result<Vec<u8>, RedactionError> {
+        let outcome = self.redact_fields_with_report(json_body, paths)?;
+        if let Some(path) = outcome.depth_limited_paths.into_iter().next() {
+            return Err(RedactionError::MaxDepthExceeded {
+                path,
+                max_depth: self.max_depth,
+            });
+        }
+        Ok(outcome.body)
+    }
+
+    /// Like `redact_fields`, but reports which paths removed nothing or were
+    /// cut short by the depth limit instead of failing.
+    ///
+    /// Bodies that are not valid JSON are returned unchanged with no
+    /// unmatched paths, since no path could be checked against them.
+    pub fn redact_fields_with_report(
+        &self,
+        json_body: &[u8],
+        paths: &[String],
+    ) -> Result<RedactionOutcome, RedactionError> {
         // Try to parse as JSON
         let mut value: Value = match serde_json::from_slice(json_body) {
             Ok(v) => v,
             Err(_) => {
                 debug!("Response body is not valid JSON, skipping redaction");
-                return Ok(json_body.to_vec());
+                return Ok(RedactionOutcome {
+                    body: json_body.to_vec(),
+                    unmatched_paths: Vec::new(),
+                    depth_limited_paths: Vec::new(),
+                });
             }
         };
 
         let mut fields_removed = 0;
+        let mut unmatched_paths = Vec::new();
+        let mut depth_limited_paths = Vec::new();
 
         // Apply each redaction path
         for path in paths {
-            if Self::remove_field_by_path(&mut value, path) {
+            let mut depth_limited = false;
+            if self.remove_field_by_path(&mut value, path, &mut depth_limited) {
                 fields_removed += 1;
                 debug!(path = %path, "Removed field");
+            } else {
+                unmatched_paths.push(path.clone());
+            }
+            if depth_limited {
+                depth_limited_paths.push(path.clone());
             }
         }
 
@@ -37,10 +92,21 @@ impl RedactionEngine {
 
         // Serialize back to JSON
         let redacted_bytes = serde_json::to_vec(&value)?;
-        Ok(redacted_bytes)
+        Ok(RedactionOutcome {
+            body: redacted_bytes,
+            unmatched_paths,
+            depth_limited_paths,
+        })
     }
 
-    fn remove_field_by_path(value: &mut Value, path: &str) -> bool {
+    /// Sets `depth_limited` when the search reached a nested object or array
+    /// beyond the depth limit.
+    fn remove_field_by_path(
+        &self,
+        value: &mut Value,
+        path: &str,
+        depth_limited: &mut bool,
+    ) -> bool {
         let parts: Vec<&str> = path.split('.').collect();
 
         if parts.is_empty() {
@@ -48,16 +114,34 @@ impl RedactionEngine {
         }
 
         // Try to match the path starting from current level
-        if Self::remove_field_recursive(value, &parts, 0) {
+        if self.remove_field_recursive(value, &parts, 0, depth_limited) {
             return true;
         }
 
         // If not matched at current level, try matching at any nested level (depth-first search)
-        Self::remove_field_at_any_depth(value, &parts, 0)
+        self.remove_field_at_any_depth(value, &parts, 0, depth_limited)
+    }
+
+    /// Whether `value` sits past the depth limit. Only containers count,
+    /// since a scalar cannot hold a match.
+    fn beyond_limit(&self, value: &Value, depth: usize, depth_limited: &mut bool) -> bool {
+        if depth <= self.max_depth {
+            return false;
+        }
+        if value.is_object() || value.is_array() {
+            *depth_limited = true;
+        }
+        true
     }
 
-    fn remove_field_recursive(value: &mut Value, path_parts: &[&str], depth: usize) -> bool {
-        if depth > MAX_REDACTION_DEPTH {
+    fn remove_field_recursive(
+        &self,
+        value: &mut Value,
+        path_parts: &[&str],
+        depth: usize,
+        depth_limited: &mut bool,
+    ) -> bool {
+        if self.beyond_limit(value, depth, depth_limited) {
             return false;
         }
 
@@ -76,7 +160,12 @@ impl RedactionEngine {
                 } else {
                     // Navigate deeper following the exact path
                     if let Some(nested_value) = map.get_mut(current_key) {
-                        Self::remove_field_recursive(nested_value, remaining_parts, depth + 1)
+                        self.remove_field_recursive(
+                            nested_value,
+                            remaining_parts,
+                            depth + 1,
+                            depth_limited,
+                        )
                     } else {
                         false
                     }
@@ -86,7 +175,7 @@ impl RedactionEngine {
                 // Apply redaction to all array elements
                 let mut any_removed = false;
                 for item in arr.iter_mut() {
-                    if Self::remove_field_recursive(item, path_parts, depth + 1) {
+                    if self.remove_field_recursive(item, path_parts, depth + 1, depth_limited) {
                         any_removed = true;
                     }
                 }
@@ -97,8 +186,14 @@ impl RedactionEngine {
     }
 
     /// Try to match the path at any depth in the JSON structure
-    fn remove_field_at_any_depth(value: &mut Value, path_parts: &[&str], depth: usize) -> bool {
-        if depth > MAX_REDACTION_DEPTH {
+    fn remove_field_at_any_depth(
+        &self,
+        value: &mut Value,
+        path_parts: &[&str],
+        depth: usize,
+        depth_limited: &mut bool,
+    ) -> bool {
+        if self.beyond_limit(value, depth, depth_limited) {
             return false;
         }
 
@@ -111,12 +206,21 @@ impl RedactionEngine {
                 for key in keys {
                     if let Some(nested_value) = map.get_mut(&key) {
                         // Try exact match from this point
-                        if Self::remove_field_recursive(nested_value, path_parts, depth + 1) {
+                        if self.remove_field_recursive(
+                            nested_value,
+                            path_parts,
+                            depth + 1,
+                            depth_limited,
+                        ) {
                             any_removed = true;
                         } else {
                             // Continue searching deeper
-                            if Self::remove_field_at_any_depth(nested_value, path_parts, depth + 1)
-                            {
+                            if self.remove_field_at_any_depth(
+                                nested_value,
+                                path_parts,
+                                depth + 1,
+                                depth_limited,
+                            ) {
                                 any_removed = true;
                             }
                         }
@@ -126,7 +230,7 @@ impl RedactionEngine {
             Value::Array(arr) => {
                 // Search in array elements
                 for item in arr.iter_mut() {
-                    if Self::remove_field_at_any_depth(item, path_parts, depth + 1) {
+                    if self.remove_field_at_any_depth(item, path_parts, depth + 1, depth_limited) {
                         any_removed = true;
                     }
                 }
@@ -138,8 +242,8 @@ impl RedactionEngine {
     }
 
     #[allow(dead_code)]
-    fn remove_field_by_name(value: &mut Value, field_name: &str, depth: usize) -> usize {
-        if depth > MAX_REDACTION_DEPTH {
+    fn remove_field_by_name(&self, value: &mut Value, field_name: &str, depth: usize) -> usize {
+        if depth > self.max_depth {
             return 0;
         }
 
@@ -154,13 +258,13 @@ impl RedactionEngine {
 
                 // Recurse into nested objects and arrays
                 for (_, nested_value) in map.iter_mut() {
-                    count += Self::remove_field_by_name(nested_value, field_name, depth + 1);
+                    count += self.remove_field_by_name(nested_value, field_name, depth + 1);
                 }
             }
             Value::Array(arr) => {
                 // Recurse into array elements
                 for item in arr.iter_mut() {
-                    count += Self::remove_field_by_name(item, field_name, depth + 1);
+                    count += self.remove_field_by_name(item, field_name, depth + 1);
                 }
             }
             _ => {}
@@ -273,6 +377,57 @@ mod tests {
         assert_eq!(users[1].get("name").unwrap(), "Bob");
     }
 
+    #[test]
+    fn test_report_lists_unmatched_paths() {
+        let engine = RedactionEngine::new();
+        let body = json!({
+            "name": "Alice",
+            "email": "alice@example.com"
+        });
+
+        let body_bytes = serde_json::to_vec(&body).unwrap();
+        let paths = vec!["email".to_string(), "ssn".to_string()];
+
+        let outcome = engine
+            .redact_fields_with_report(&body_bytes, &paths)
+            .unwrap();
+        let redacted: Value = serde_json::from_slice(&outcome.body).unwrap();
+
+        assert!(redacted.get("email").is_none());
+        assert_eq!(outcome.unmatched_paths, vec!["ssn".to_string()]);
+    }
+
+    #[test]
+    fn test_depth_limit_is_configurable() {
+        let engine = RedactionEngine::new().with_max_depth(3);
+        let paths = vec!["a.b.c.email".to_string()];
+
+        // `email` sits in the object at depth 3, the limit itself
+        let body = json!({"a": {"b": {"c": {"email": "alice@example.com", "name": "Alice"}}}});
+        let outcome = engine
+            .redact_fields_with_report(&serde_json::to_vec(&body).unwrap(), &paths)
+            .unwrap();
+        let redacted: Value = serde_json::from_slice(&outcome.body).unwrap();
+        assert!(redacted["a"]["b"]["c"].get("email").is_none());
+        assert_eq!(redacted["a"]["b"]["c"]["name"], "Alice");
+        assert!(outcome.depth_limited_paths.is_empty());
+
+        // One level deeper is reported instead of silently skipped
+        let paths = vec!["a.b.c.d.email".to_string()];
+        let body = json!({"a": {"b": {"c": {"d": {"email": "alice@example.com"}}}}});
+        let body_bytes = serde_json::to_vec(&body).unwrap();
+        let outcome = engine
+            .redact_fields_with_report(&body_bytes, &paths)
+            .unwrap();
+        assert_eq!(outcome.depth_limited_paths, paths);
+
+        let err = engine.redact_fields(&body_bytes, &paths).unwrap_err();
+        assert!(matches!(
+            err,
+            RedactionError::MaxDepthExceeded { ref path, max_depth: 3 } if path == "a.b.c.d.email"
+        ));
+    }
+
     #[test]
     fn test_non_json_passthrough() {
         let engine = RedactionEngine::new();
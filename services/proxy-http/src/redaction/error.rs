@@ -8,8 +8,8 @@ pub enum RedactionError {
     #[error("JSON parse error: {0}")]
     JsonParseError(String),
 
-    #[error("Maximum redaction depth exceeded")]
-    MaxDepthExceeded,
+    #[error("Redaction path '{path}' may match below the maximum depth of {max_depth}")]
+    MaxDepthExceeded { path: String, max_depth: usize },
 }
 
 impl From<serde_json::Error> for RedactionError {
@@ -1,10 +1,15 @@
 mod engine;
 mod error;
 
-pub use engine::RedactionEngine;
+pub use engine::{RedactionEngine, RedactionOutcome};
 pub use error::RedactionError;
 
 pub type RedactionPath = String;
 
+/// Default nesting depth searched for redaction paths.
 pub const MAX_REDACTION_DEPTH: usize = 10;
 pub const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
+
+/// Response header listing redaction paths that matched nothing, set in
+/// strict redaction mode when enabled.
+pub const REDACTION_WARNINGS_HEADER: &str = "x-redaction-warnings";
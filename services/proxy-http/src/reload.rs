@@ -0,0 +1,220 @@
+use crate::config::ProxyConfig;
+use anyhow::{Context, Result};
+use edge_policy_telemetry::EnvFile;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable naming an optional `KEY=VALUE` file that is re-read
+/// on SIGHUP before the configuration is reloaded.
+pub const RELOAD_ENV_FILE_VAR: &str = "RELOAD_ENV_FILE";
+
+pub use edge_policy_telemetry::LogReloadHandle;
+
+/// Subset of the configuration that can be changed without a restart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeSettings {
+    pub log_level: String,
+    pub request_timeout_secs: u64,
+    pub max_body_size_bytes: usize,
+}
+
+impl RuntimeSettings {
+    pub fn from_config(config: &ProxyConfig) -> Self {
+        Self {
+            log_level: config.log_level.clone(),
+            request_timeout_secs: config.request_timeout_secs,
+            max_body_size_bytes: config.max_body_size_bytes,
+        }
+    }
+
+    /// Get request timeout as Duration
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    /// Describe every field that differs from `previous` as `name: old -> new`.
+    pub fn changes_from(&self, previous: &Self) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.log_level != previous.log_level {
+            changes.push(format!(
+                "log_level: {} -> {}",
+                previous.log_level, self.log_level
+            ));
+        }
+        if self.request_timeout_secs != previous.request_timeout_secs {
+            changes.push(format!(
+                "request_timeout_secs: {} -> {}",
+                previous.request_timeout_secs, self.request_timeout_secs
+            ));
+        }
+        if self.max_body_size_bytes != previous.max_body_size_bytes {
+            changes.push(format!(
+                "max_body_size_bytes: {} -> {}",
+                previous.max_body_size_bytes, self.max_body_size_bytes
+            ));
+        }
+        changes
+    }
+}
+
+/// Runtime settings shared between the request pipeline and the reload task.
+#[derive(Debug, Clone)]
+pub struct SharedSettings {
+    inner: Arc<RwLock<RuntimeSettings>>,
+}
+
+impl SharedSettings {
+    pub fn new(settings: RuntimeSettings) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(settings)),
+        }
+    }
+
+    pub fn current(&self) -> RuntimeSettings {
+        self.inner
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn replace(&self, settings: RuntimeSettings) -> RuntimeSettings {
+        let mut guard = self
+            .inner
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::replace(&mut *guard, settings)
+    }
+}
+
+/// Re-read the configuration through `env` and apply the runtime-tunable
+/// settings. Values in the file named by `RELOAD_ENV_FILE` take precedence
+/// over `env`.
+///
+/// Listener and TLS settings are compared against `startup` and only logged
+/// when they differ, since they take effect on restart. Returns the list of
+/// applied changes.
+pub fn reload_runtime_settings(
+    settings: &SharedSettings,
+    log_handle: Option<&LogReloadHandle>,
+    startup: &ProxyConfig,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<Vec<String>> {
+    let env_file = match env(RELOAD_ENV_FILE_VAR) {
+        Some(path) => EnvFile::read(path).context("Failed to read reload env file")?,
+        None => EnvFile::default(),
+    };
+
+    let config =
+        ProxyConfig::load_from(&env_file.over(env)).context("Failed to reload configuration")?;
+    config
+        .validate()
+        .context("Reloaded configuration is invalid")?;
+
+    for field in restart_only_changes(startup, &config) {
+        warn!(
+            field,
+            "Configuration change requires a restart to take effect"
+        );
+    }
+
+    let next = RuntimeSettings::from_config(&config);
+    let previous = settings.current();
+    let changes = next.changes_from(&previous);
+
+    if next.log_level != previous.log_level {
+        if let Some(handle) = log_handle {
+            let filter = EnvFilter::try_new(&next.log_level)
+                .with_context(|| format!("Invalid LOG_LEVEL '{}'", next.log_level))?;
+            handle
+                .reload(filter)
+                .context("Failed to apply reloaded log level")?;
+        }
+    }
+
+    settings.replace(next);
+    for change in &changes {
+        info!(change = %change, "Runtime setting reloaded");
+    }
+
+    Ok(changes)
+}
+
+/// Reload runtime settings every time the process receives SIGHUP, reading
+/// variables through `env` (normally [`edge_policy_telemetry::process_env`]).
+#[cfg(unix)]
+pub async fn reload_on_sighup<F>(
+    settings: SharedSettings,
+    log_handle: Option<LogReloadHandle>,
+    startup: ProxyConfig,
+    env: F,
+) -> Result<()>
+where
+    F: Fn(&str) -> Option<String> + Send,
+{
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = signal(SignalKind::hangup()).context("Failed to install SIGHUP handler")?;
+    while hangup.recv().await.is_some() {
+        info!("SIGHUP received, reloading runtime settings");
+        match reload_runtime_settings(&settings, log_handle.as_ref(), &startup, &env) {
+            Ok(changes) if changes.is_empty() => info!("No runtime settings changed"),
+            Ok(_) => {}
+            Err(e) => error!("Failed to reload runtime settings: {:#}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn restart_only_changes(startup: &ProxyConfig, reloaded: &ProxyConfig) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if startup.host != reloaded.host {
+        fields.push("host");
+    }
+    if startup.port != reloaded.port {
+        fields.push("port");
+    }
+    if startup.enable_mtls != reloaded.enable_mtls {
+        fields.push("enable_mtls");
+    }
+    if startup.tls_cert_path != reloaded.tls_cert_path {
+        fields.push("tls_cert_path");
+    }
+    if startup.tls_key_path != reloaded.tls_key_path {
+        fields.push("tls_key_path");
+    }
+    if startup.tls_client_ca_path != reloaded.tls_client_ca_path {
+        fields.push("tls_client_ca_path");
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(log_level: &str, timeout: u64, body: usize) -> RuntimeSettings {
+        RuntimeSettings {
+            log_level: log_level.to_string(),
+            request_timeout_secs: timeout,
+            max_body_size_bytes: body,
+        }
+    }
+
+    #[test]
+    fn test_changes_from_lists_only_modified_fields() {
+        let previous = settings("info", 30, 1024);
+        let next = settings("debug", 30, 2048);
+
+        assert_eq!(
+            next.changes_from(&previous),
+            vec![
+                "log_level: info -> debug".to_string(),
+                "max_body_size_bytes: 1024 -> 2048".to_string(),
+            ]
+        );
+        assert!(previous.changes_from(&previous).is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::config::ProxyConfig;
+use crate::policy::CacheWarmup;
 use crate::proxy::handler::ProxyHandler;
+use crate::reload::SharedSettings;
 use anyhow::{Context, Result};
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
@@ -38,6 +40,11 @@ impl ProxyServer {
         })
     }
 
+    /// Runtime settings that can be reloaded while the server is running
+    pub fn runtime_settings(&self) -> SharedSettings {
+        self.handler.settings()
+    }
+
     /// Create TLS acceptor with optional client authentication
     fn create_tls_acceptor(config: &ProxyConfig) -> Result<TlsAcceptor> {
         use rustls::pki_types::CertificateDer;
@@ -118,6 +125,17 @@ impl ProxyServer {
             addr, self.config.enable_mtls
         );
 
+        // Warm the decision cache in the background so startup does not wait on it
+        if self.config.policy_cache_warmup {
+            if let Some(url) = self.config.audit_store_url.clone() {
+                let warmup = CacheWarmup::new(url, self.config.policy_cache_warmup_inputs)?;
+                let policy_client = self.handler.policy_client();
+                tokio::spawn(async move {
+                    warmup.run(&policy_client).await;
+                });
+            }
+        }
+
         let server = Arc::new(self);
 
         loop {
@@ -179,7 +197,7 @@ impl ProxyServer {
             let service = service_fn(move |req| {
                 let handler = Arc::clone(&handler);
                 let peer_info = Arc::clone(&peer_info_clone);
-                async move { handler.handle_request(req, Some(peer_info)).await }
+                async move { handler.serve(req, Some(peer_info)).await }
             });
 
             http1::Builder::new()
@@ -199,7 +217,7 @@ impl ProxyServer {
             let service = service_fn(move |req| {
                 let handler = Arc::clone(&handler);
                 let peer_info = Arc::clone(&peer_info);
-                async move { handler.handle_request(req, Some(peer_info)).await }
+                async move { handler.serve(req, Some(peer_info)).await }
             });
 
             http1::Builder::new()
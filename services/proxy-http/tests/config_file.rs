@@ -0,0 +1,55 @@
+use std::fs;
+
+use edge_policy_proxy_http::config::{JwtAlgorithm, MalformedDecisionMode, ProxyConfig};
+use tempfile::TempDir;
+
+#[test]
+fn config_file_values_load_and_env_overrides_them() {
+    let dir = TempDir::new().unwrap();
+    let config_path = dir.path().join("proxy.toml");
+    fs::write(
+        &config_path,
+        r#"
+port = 9090
+upstream_url = "http://backend.local:8000"
+jwt_algorithms = ["RS256", "ES256"]
+malformed_decision_mode = "allow"
+shadow_mode_tenants = ["tenant-a"]
+
+[tenant_upstreams]
+tenant-b = "http://backend-b.local:8000"
+"#,
+    )
+    .unwrap();
+
+    let config = ProxyConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.port, 9090);
+    assert_eq!(config.upstream_url, "http://backend.local:8000");
+    assert_eq!(
+        config.jwt_algorithms,
+        vec![JwtAlgorithm::RS256, JwtAlgorithm::ES256]
+    );
+    assert_eq!(config.malformed_decision_mode, MalformedDecisionMode::Allow);
+    assert!(config.is_shadow_mode("tenant-a"));
+    assert_eq!(
+        config.tenant_upstreams.get("tenant-b").map(String::as_str),
+        Some("http://backend-b.local:8000")
+    );
+    // Left out of the file, so the default applies
+    assert_eq!(config.request_timeout_secs, 30);
+    config.validate().unwrap();
+
+    std::env::set_var("PROXY_PORT", "9191");
+    std::env::set_var("QUOTA_TRACKER_URL", "http://quota.local");
+    let config = ProxyConfig::from_file(&config_path);
+    std::env::remove_var("PROXY_PORT");
+    std::env::remove_var("QUOTA_TRACKER_URL");
+
+    let config = config.unwrap();
+    assert_eq!(config.port, 9191);
+    assert_eq!(
+        config.quota_tracker_url.as_deref(),
+        Some("http://quota.local")
+    );
+    assert_eq!(config.upstream_url, "http://backend.local:8000");
+}
@@ -1,14 +1,27 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::Result;
-use edge_policy_proxy_http::config::{JwtAlgorithm, ProxyConfig};
+use edge_policy_enforcer::PolicyManager;
+use edge_policy_proxy_http::config::{
+    EnforcerFailureMode, JwtAlgorithm, MalformedDecisionMode, ProxyConfig,
+    DEFAULT_DEBUG_BODY_MAX_BYTES, REDACTED_SECRET,
+};
+use edge_policy_proxy_http::policy::{
+    AbacInput, CacheWarmup, PolicyClient, DEFAULT_CACHE_WARMUP_INPUTS,
+};
+use edge_policy_proxy_http::proxy::CONFIG_PATH;
+use edge_policy_proxy_http::redaction::MAX_REDACTION_DEPTH;
 use edge_policy_proxy_http::server::ProxyServer;
 use reqwest::Client;
 use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
-use wiremock::matchers::{method, path};
+use tracing_subscriber::fmt::MakeWriter;
+use wiremock::matchers::{body_partial_json, method, path, path_regex, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 const TENANT_HEADER: &str = "X-Tenant-ID";
@@ -26,6 +39,7 @@ fn base_config(enforcer_url: String, upstream_url: String, port: u16) -> ProxyCo
         host: "127.0.0.1".to_string(),
         port,
         upstream_url,
+        tenant_upstreams: BTreeMap::new(),
         request_timeout_secs: 2,
         max_body_size_bytes: 1024 * 1024,
         enforcer_url,
@@ -39,11 +53,31 @@ fn base_config(enforcer_url: String, upstream_url: String, port: u16) -> ProxyCo
         jwt_issuer: None,
         jwt_audience: None,
         jwt_algorithm: JwtAlgorithm::RS256,
+        jwt_algorithms: Vec::new(),
+        jwt_ec_public_key_path: None,
+        jwt_leeway_secs: 60,
+        jwt_require_exp: true,
+        jwt_require_nbf: false,
         forward_auth_header: false,
         log_level: "warn".to_string(),
         quota_tracker_url: None,
         quota_tracker_token: None,
         default_region: None,
+        policy_cache_ttl_ms: 0,
+        policy_cache_obligations: false,
+        policy_cache_warmup: false,
+        policy_cache_warmup_inputs: DEFAULT_CACHE_WARMUP_INPUTS,
+        audit_store_url: None,
+        malformed_decision_mode: MalformedDecisionMode::Deny,
+        enforcer_failure_mode: EnforcerFailureMode::Closed,
+        strict_redaction: false,
+        redaction_warnings_header: false,
+        max_redaction_depth: MAX_REDACTION_DEPTH,
+        tenant_id_header: TENANT_HEADER.to_string(),
+        shadow_mode_tenants: BTreeSet::new(),
+        debug_body_tenants: BTreeSet::new(),
+        debug_body_max_bytes: DEFAULT_DEBUG_BODY_MAX_BYTES,
+        bypass_paths: Vec::new(),
     }
 }
 
@@ -152,6 +186,417 @@ async fn denied_requests_do_not_hit_upstream() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn shadow_mode_forwards_denied_requests_and_reports_would_deny() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/v1/data/tenants/[^/]+/allow$"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": false, "reason": "blocked" }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "ok" })))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config
+        .shadow_mode_tenants
+        .insert(tenant_header_value().to_string());
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .get(format!("{}/data", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response
+            .headers()
+            .get("x-policy-shadow-decision")
+            .and_then(|value| value.to_str().ok()),
+        Some("deny")
+    );
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body, json!({ "status": "ok" }));
+
+    // Tenants outside shadow mode are still enforced.
+    let response = client
+        .get(format!("{}/data", base_url))
+        .header(TENANT_HEADER, "tenant-enforced")
+        .send()
+        .await?;
+    assert_eq!(response.status(), 403);
+    assert!(response.headers().get("x-policy-shadow-decision").is_none());
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn header_only_denial_returns_before_body_is_read() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": false, "reason": "uploads blocked" }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/upload"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+
+    // Announce a large body but send only a few bytes of it and keep the
+    // connection open. A proxy that buffers first would wait for the rest
+    // until the request timeout.
+    let mut stream = tokio::net::TcpStream::connect(base_url.trim_start_matches("http://")).await?;
+    let head = format!(
+        "POST /upload HTTP/1.1\r\nHost: localhost\r\n{}: {}\r\nContent-Type: application/octet-stream\r\nContent-Length: 524288\r\n\r\npartial-body",
+        TENANT_HEADER,
+        tenant_header_value()
+    );
+    stream.write_all(head.as_bytes()).await?;
+
+    let mut response = vec![0u8; 1024];
+    let read = tokio::time::timeout(Duration::from_secs(1), stream.read(&mut response))
+        .await
+        .expect("denial should not wait for the request body")?;
+    let response = String::from_utf8_lossy(&response[..read]);
+    assert!(
+        response.starts_with("HTTP/1.1 403"),
+        "unexpected response: {response}"
+    );
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn default_region_is_injected_when_request_omits_it() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .and(body_partial_json(json!({
+            "input": { "environment": { "region": "eu-central-1" } }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": true }
+        })))
+        .expect(1)
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.default_region = Some("eu-central-1".to_string());
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .get(format!("{}/data", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 200);
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn method_based_policy_denies_delete_but_allows_get() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .and(body_partial_json(json!({
+            "input": { "resource": { "method": "DELETE", "path": "/admin/users" } }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": false, "reason": "DELETE is not permitted" }
+        })))
+        .with_priority(1)
+        .mount(&enforcer)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .and(body_partial_json(json!({
+            "input": { "resource": { "method": "GET", "path": "/admin/users" } }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": true }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/admin/users"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "users": [] })))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/admin/users"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+
+    let response = client
+        .delete(format!("{}/admin/users", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+    assert_eq!(response.status(), 403);
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(payload["error"], json!("POLICY_DENIED"));
+
+    let response = client
+        .get(format!("{}/admin/users", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+    assert_eq!(response.status(), 200);
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tenants_route_to_their_configured_upstreams() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/v1/data/tenants/[^/]+/allow$"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": true }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let default_upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&default_upstream)
+        .await;
+
+    let alpha_upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "backend": "alpha" })))
+        .expect(1)
+        .mount(&alpha_upstream)
+        .await;
+
+    let beta_upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "backend": "beta" })))
+        .expect(1)
+        .mount(&beta_upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), default_upstream.uri(), port);
+    config.tenant_upstreams = BTreeMap::from([
+        ("tenant-alpha".to_string(), alpha_upstream.uri()),
+        ("tenant-beta".to_string(), beta_upstream.uri()),
+    ]);
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+
+    for tenant in ["tenant-alpha", "tenant-beta"] {
+        let response = client
+            .get(format!("{}/data", base_url))
+            .header(TENANT_HEADER, tenant)
+            .send()
+            .await?;
+
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await?;
+        assert_eq!(body["backend"], json!(tenant.trim_start_matches("tenant-")));
+    }
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn identical_requests_within_ttl_reuse_cached_decision() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": true }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "ok" })))
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.policy_cache_ttl_ms = 300;
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let send = || {
+        client
+            .get(format!("{}/data", base_url))
+            .header(TENANT_HEADER, tenant_header_value())
+            .send()
+    };
+    let enforcer_calls = || async {
+        enforcer
+            .received_requests()
+            .await
+            .map(|requests| requests.len())
+            .unwrap_or_default()
+    };
+
+    assert_eq!(send().await?.status(), 200);
+    assert_eq!(send().await?.status(), 200);
+    assert_eq!(enforcer_calls().await, 1);
+
+    sleep(Duration::from_millis(400)).await;
+    assert_eq!(send().await?.status(), 200);
+    assert_eq!(enforcer_calls().await, 2);
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cache_warmup_replays_recent_audit_inputs() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": true }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let input = |path: &str| {
+        AbacInput::builder(tenant_header_value())
+            .action("read")
+            .method("GET")
+            .path(path)
+            .build()
+    };
+    let audit_entry = |input: &AbacInput, timestamp: &str| {
+        let mut input = serde_json::to_value(input).unwrap();
+        input["environment"]["time"] = json!(timestamp);
+        json!({
+            "log_id": uuid::Uuid::new_v4().to_string(),
+            "tenant_id": tenant_header_value(),
+            "timestamp": timestamp,
+            "decision": "allow",
+            "protocol": "http",
+            "subject": input["subject"],
+            "action": input["action"],
+            "resource": input["resource"],
+            "environment": input["environment"],
+            "policy_version": 1,
+            "reason": null,
+            "signature": "sig",
+            "uploaded": false,
+        })
+    };
+    let sensors = input("/api/sensors");
+    let devices = input("/api/devices");
+
+    let audit_store = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/tenants"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "tenant_id": tenant_header_value(), "name": "Integration", "status": "active" }
+        ])))
+        .mount(&audit_store)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/audit/logs"))
+        .and(query_param("tenant_id", tenant_header_value()))
+        .and(query_param("protocol", "http"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "logs": [
+                audit_entry(&sensors, "2024-01-01T00:00:02Z"),
+                audit_entry(&sensors, "2024-01-01T00:00:01Z"),
+                audit_entry(&devices, "2024-01-01T00:00:00Z"),
+            ]
+        })))
+        .mount(&audit_store)
+        .await;
+
+    let policy_client =
+        PolicyClient::new(enforcer.uri(), 2)?.with_decision_cache(Duration::from_secs(60), false);
+    let warmup = CacheWarmup::new(audit_store.uri(), DEFAULT_CACHE_WARMUP_INPUTS)?;
+
+    // Repeated inputs are replayed once
+    assert_eq!(warmup.run(&policy_client).await, 2);
+    let enforcer_calls = enforcer.received_requests().await.unwrap_or_default().len();
+    assert_eq!(enforcer_calls, 2);
+
+    // Both replayed inputs are now answered from the cache
+    assert!(
+        policy_client
+            .query_policy(tenant_header_value(), sensors)
+            .await?
+            .allow
+    );
+    assert!(
+        policy_client
+            .query_policy(tenant_header_value(), devices)
+            .await?
+            .allow
+    );
+    assert_eq!(
+        enforcer.received_requests().await.unwrap_or_default().len(),
+        enforcer_calls
+    );
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn redaction_is_applied_to_json_responses() -> Result<()> {
     let enforcer = MockServer::start().await;
@@ -180,86 +625,482 @@ async fn redaction_is_applied_to_json_responses() -> Result<()> {
         .await;
 
     let port = unused_port();
-    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .get(format!("{}/profile", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 200);
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(
+        payload,
+        json!({
+            "name": "Alice",
+            "pii": { "phone": "+15551234567" }
+        })
+    );
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn v2_decisions_redact_fields_and_ignore_legacy_redact() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": {
+                "allow": true,
+                "decision_schema_version": 2,
+                "redact": ["name"],
+                "redact_fields": ["pii.email"],
+                "remove_fields": ["pii.phone"]
+            }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/profile"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "Alice",
+            "pii": {
+                "email": "alice@example.com",
+                "phone": "+15551234567"
+            }
+        })))
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .get(format!("{}/profile", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 200);
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(payload, json!({ "name": "Alice", "pii": {} }));
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn redact_if_flags_redact_per_subject() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    for (user, redact_email) in [("auditor", false), ("contractor", true)] {
+        Mock::given(method("POST"))
+            .and(path("/v1/data/tenants/tenant-integration/allow"))
+            .and(body_partial_json(json!({
+                "input": { "subject": { "user_id": user } }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "result": {
+                    "allow": true,
+                    "decision_schema_version": 2,
+                    "redact_if": { "pii.email": redact_email }
+                }
+            })))
+            .mount(&enforcer)
+            .await;
+    }
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/profile"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "Alice",
+            "pii": { "email": "alice@example.com" }
+        })))
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.enable_jwt = true;
+    config.jwt_algorithm = JwtAlgorithm::HS256;
+    config.jwt_secret = Some("integration-secret".to_string());
+    let (handle, base_url) = start_proxy(config).await;
+
+    let token = |user: &str| {
+        let claims = json!({
+            "sub": user,
+            "tenant_id": tenant_header_value(),
+            "exp": chrono::Utc::now().timestamp() + 300,
+        });
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(b"integration-secret"),
+        )
+        .expect("token should encode")
+    };
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let fetch = |user: &str| {
+        client
+            .get(format!("{}/profile", base_url))
+            .bearer_auth(token(user))
+            .send()
+    };
+
+    let response = fetch("auditor").await?;
+    assert_eq!(response.status(), 200);
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(
+        payload,
+        json!({ "name": "Alice", "pii": { "email": "alice@example.com" } })
+    );
+
+    let response = fetch("contractor").await?;
+    assert_eq!(response.status(), 200);
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(payload, json!({ "name": "Alice", "pii": {} }));
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn strict_redaction_reports_unmatched_paths() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": {
+                "allow": true,
+                "redact": ["pii.email", "pii.ssn"]
+            }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/profile"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "Alice",
+            "pii": { "email": "alice@example.com" }
+        })))
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.strict_redaction = true;
+    config.redaction_warnings_header = true;
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .get(format!("{}/profile", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response
+            .headers()
+            .get("x-redaction-warnings")
+            .and_then(|value| value.to_str().ok()),
+        Some("pii.ssn")
+    );
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(payload, json!({ "name": "Alice", "pii": {} }));
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+// Single-threaded so the proxy's tasks log through the captured subscriber.
+#[tokio::test]
+async fn debug_body_logging_redacts_bodies_without_changing_traffic() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": {
+                "allow": true,
+                "redact": ["pii.email"]
+            }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/profile"))
+        .and(body_partial_json(json!({
+            "pii": { "email": "alice@example.com" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "name": "Alice",
+            "pii": {
+                "email": "alice@example.com",
+                "phone": "+15551234567"
+            }
+        })))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(logs.clone())
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.debug_body_tenants = BTreeSet::from([tenant_header_value().to_string()]);
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .post(format!("{}/profile", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .json(&json!({
+            "name": "Alice",
+            "pii": { "email": "alice@example.com" }
+        }))
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 200);
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(
+        payload,
+        json!({
+            "name": "Alice",
+            "pii": { "phone": "+15551234567" }
+        })
+    );
+
+    teardown(handle).await;
+
+    let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("Request and response bodies"));
+    assert!(output.contains("+15551234567"));
+    assert!(!output.contains("alice@example.com"));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn non_json_responses_are_not_redacted() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": {
+                "allow": true,
+                "redact": ["pii.email"]
+            }
+        })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/binary"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "application/octet-stream")
+                .set_body_bytes(vec![1, 2, 3, 4]),
+        )
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let bytes = client
+        .get(format!("{}/binary", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?
+        .bytes()
+        .await?;
+
+    assert_eq!(bytes.as_ref(), &[1, 2, 3, 4]);
+
+    teardown(handle).await;
+    Ok(())
+}
+
+async fn start_malformed_enforcer() -> MockServer {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/data/tenants/tenant-integration/allow"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "reason": "no allow field" }
+        })))
+        .mount(&enforcer)
+        .await;
+    enforcer
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn malformed_decision_is_denied_by_default() -> Result<()> {
+    let enforcer = start_malformed_enforcer().await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .get(format!("{}/data", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 502);
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(payload["error"], json!("MALFORMED_DECISION"));
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn malformed_decision_is_allowed_when_configured_fail_open() -> Result<()> {
+    let enforcer = start_malformed_enforcer().await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.malformed_decision_mode = MalformedDecisionMode::Allow;
+    let (handle, base_url) = start_proxy(config).await;
 
     let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
     let response = client
-        .get(format!("{}/profile", base_url))
+        .get(format!("{}/data", base_url))
         .header(TENANT_HEADER, tenant_header_value())
         .send()
         .await?;
 
     assert_eq!(response.status(), 200);
-    let payload: serde_json::Value = response.json().await?;
-    assert_eq!(
-        payload,
-        json!({
-            "name": "Alice",
-            "pii": { "phone": "+15551234567" }
-        })
-    );
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body, json!({ "ok": true }));
 
     teardown(handle).await;
     Ok(())
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn non_json_responses_are_not_redacted() -> Result<()> {
-    let enforcer = MockServer::start().await;
-    Mock::given(method("POST"))
-        .and(path("/v1/data/tenants/tenant-integration/allow"))
-        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
-            "result": {
-                "allow": true,
-                "redact": ["pii.email"]
-            }
-        })))
-        .mount(&enforcer)
-        .await;
-
+async fn enforcer_unavailable_yields_service_unavailable() -> Result<()> {
     let upstream = MockServer::start().await;
     Mock::given(method("GET"))
-        .and(path("/binary"))
-        .respond_with(
-            ResponseTemplate::new(200)
-                .insert_header("content-type", "application/octet-stream")
-                .set_body_bytes(vec![1, 2, 3, 4]),
-        )
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
         .mount(&upstream)
         .await;
 
+    let enforcer_port = unused_port();
+    let enforcer_url = format!("http://127.0.0.1:{}", enforcer_port);
+
     let port = unused_port();
-    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+    let (handle, base_url) = start_proxy(base_config(enforcer_url, upstream.uri(), port)).await;
 
     let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
-    let bytes = client
-        .get(format!("{}/binary", base_url))
+    let response = client
+        .get(format!("{}/data", base_url))
         .header(TENANT_HEADER, tenant_header_value())
         .send()
-        .await?
-        .bytes()
         .await?;
 
-    assert_eq!(bytes.as_ref(), &[1, 2, 3, 4]);
+    assert_eq!(response.status(), 503);
+    let payload: serde_json::Value = response.json().await?;
+    assert_eq!(payload["error"], json!("ENFORCER_UNREACHABLE"));
 
     teardown(handle).await;
     Ok(())
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn enforcer_unavailable_yields_service_unavailable() -> Result<()> {
+async fn enforcer_outage_is_allowed_and_audited_when_configured_fail_open() -> Result<()> {
     let upstream = MockServer::start().await;
     Mock::given(method("GET"))
         .and(path("/data"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+        .expect(1)
         .mount(&upstream)
         .await;
 
-    let enforcer_port = unused_port();
-    let enforcer_url = format!("http://127.0.0.1:{}", enforcer_port);
+    let audit_store = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/audit/logs"))
+        .and(body_partial_json(json!({
+            "tenant_id": tenant_header_value(),
+            "decision": "allow",
+            "protocol": "http",
+            "action": "ENFORCEMENT_BYPASSED",
+            "resource": { "method": "GET", "path": "/data" },
+            "reason": "enforcer_unreachable",
+        })))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(1)
+        .mount(&audit_store)
+        .await;
+
+    let enforcer_url = format!("http://127.0.0.1:{}", unused_port());
 
     let port = unused_port();
-    let (handle, base_url) = start_proxy(base_config(enforcer_url, upstream.uri(), port)).await;
+    let mut config = base_config(enforcer_url, upstream.uri(), port);
+    config.enforcer_failure_mode = EnforcerFailureMode::Open;
+    config.audit_store_url = Some(audit_store.uri());
+    let (handle, base_url) = start_proxy(config).await;
 
     let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
     let response = client
@@ -268,9 +1109,23 @@ async fn enforcer_unavailable_yields_service_unavailable() -> Result<()> {
         .send()
         .await?;
 
-    assert_eq!(response.status(), 503);
-    let payload: serde_json::Value = response.json().await?;
-    assert_eq!(payload["error"], json!("ENFORCER_UNREACHABLE"));
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body, json!({ "ok": true }));
+
+    // The event is delivered in the background after the response is sent.
+    for _ in 0..20 {
+        if !audit_store
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .is_empty()
+        {
+            break;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    audit_store.verify().await;
 
     teardown(handle).await;
     Ok(())
@@ -390,3 +1245,337 @@ async fn request_body_over_limit_is_rejected() -> Result<()> {
     teardown(handle).await;
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn quota_denial_reports_limit_remaining_and_retry_after() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": true }
+        })))
+        .expect(0)
+        .mount(&enforcer)
+        .await;
+    let upstream = MockServer::start().await;
+
+    let quota_tracker = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/quota/check"))
+        .and(body_partial_json(
+            json!({ "tenant_id": "tenant-integration" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "exceeded": true,
+            "quota_type": "message_count",
+            "limit": 100,
+            "current": 100,
+            "messages": {
+                "used": 100,
+                "limit": 100,
+                "remaining": 0,
+                "resets_in_seconds": 3600
+            },
+            "bandwidth": {
+                "used": 2048,
+                "limit": 1073741824,
+                "remaining": 1073739776,
+                "resets_in_seconds": 86400
+            }
+        })))
+        .mount(&quota_tracker)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.quota_tracker_url = Some(quota_tracker.uri());
+    config.quota_tracker_token = Some("token".to_string());
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .get(format!("{}/data", base_url))
+        .header(TENANT_HEADER, tenant_header_value())
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 429);
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+    assert_eq!(header("X-Quota-Limit"), Some(100));
+    assert_eq!(header("X-Quota-Remaining"), Some(0));
+    assert_eq!(header("Retry-After"), Some(3600));
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body["error"], "QUOTA_EXCEEDED");
+
+    let upstream_requests = upstream.received_requests().await.unwrap_or_default();
+    assert!(upstream_requests.is_empty());
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bypass_paths_reach_upstream_without_tenant_or_enforcement() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": { "allow": true }
+        })))
+        .expect(0)
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/healthz"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "ok" })))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/metrics/jvm"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("jvm_threads 12"))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/data"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.bypass_paths = vec!["/healthz".to_string(), "/metrics/*".to_string()];
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+
+    let response = client.get(format!("{}/healthz", base_url)).send().await?;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.json::<serde_json::Value>().await?,
+        json!({ "status": "ok" })
+    );
+
+    let response = client
+        .get(format!("{}/metrics/jvm", base_url))
+        .send()
+        .await?;
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await?, "jvm_threads 12");
+
+    // Paths outside the list, and bypass paths with another method, still
+    // need a tenant
+    let response = client.get(format!("{}/data", base_url)).send().await?;
+    assert_eq!(response.status(), 401);
+    let response = client.post(format!("{}/healthz", base_url)).send().await?;
+    assert_eq!(response.status(), 401);
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn health_reports_reachable_dependencies() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "status": "healthy" })))
+        .mount(&enforcer)
+        .await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let (handle, base_url) = start_proxy(base_config(enforcer.uri(), upstream.uri(), port)).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client.get(format!("{}/health", base_url)).send().await?;
+
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body["status"], json!("healthy"));
+    assert_eq!(body["dependencies"]["enforcer"]["status"], json!("up"));
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn config_dump_redacts_secrets() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(CONFIG_PATH))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&upstream)
+        .await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.enable_jwt = true;
+    config.jwt_algorithm = JwtAlgorithm::HS256;
+    config.jwt_secret = Some("proxy-dump-jwt-secret".to_string());
+    config.quota_tracker_url = Some(enforcer.uri());
+    config.quota_tracker_token = Some("proxy-dump-quota-token".to_string());
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client
+        .get(format!("{}{}", base_url, CONFIG_PATH))
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body["port"], json!(port));
+    assert_eq!(body["upstream_url"], json!(upstream.uri()));
+    assert_eq!(body["jwt_secret"], json!(REDACTED_SECRET));
+    assert_eq!(body["quota_tracker_token"], json!(REDACTED_SECRET));
+    let text = body.to_string();
+    assert!(!text.contains("proxy-dump-jwt-secret"));
+    assert!(!text.contains("proxy-dump-quota-token"));
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn health_is_unavailable_when_enforcer_is_unreachable() -> Result<()> {
+    let upstream = MockServer::start().await;
+    let enforcer_url = format!("http://127.0.0.1:{}", unused_port());
+
+    let port = unused_port();
+    let (handle, base_url) = start_proxy(base_config(enforcer_url, upstream.uri(), port)).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client.get(format!("{}/health", base_url)).send().await?;
+
+    assert_eq!(response.status(), 503);
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body["status"], json!("unhealthy"));
+    assert_eq!(body["dependencies"]["enforcer"]["status"], json!("down"));
+    assert_eq!(body["dependencies"]["enforcer"]["critical"], json!(true));
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn health_is_degraded_when_quota_tracker_is_unreachable() -> Result<()> {
+    let enforcer = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&enforcer)
+        .await;
+    let upstream = MockServer::start().await;
+
+    let port = unused_port();
+    let mut config = base_config(enforcer.uri(), upstream.uri(), port);
+    config.quota_tracker_url = Some(format!("http://127.0.0.1:{}", unused_port()));
+    config.quota_tracker_token = Some("token".to_string());
+    let (handle, base_url) = start_proxy(config).await;
+
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let response = client.get(format!("{}/health", base_url)).send().await?;
+
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body["status"], json!("degraded"));
+    assert_eq!(
+        body["dependencies"]["quota_tracker"]["status"],
+        json!("down")
+    );
+
+    teardown(handle).await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn builder_input_matches_enforcer_policy_shape() -> Result<()> {
+    let bundles = tempfile::tempdir()?;
+    let tenant_dir = bundles.path().join("tenant_a");
+    std::fs::create_dir_all(&tenant_dir)?;
+    std::fs::write(
+        tenant_dir.join("policy.rego"),
+        r#"
+package tenants.tenant_a
+
+import rego.v1
+
+default allow := false
+
+allow if {
+    input.subject.tenant_id == "tenant_a"
+    input.subject.user_id == "alice"
+    "operator" in input.subject.roles
+    input.subject.clearance_level >= 2
+    input.action == "write"
+    input.resource.type == "sensors"
+    input.resource.id == "42"
+    input.resource.region == "EU"
+    input.resource.owner_tenant == input.subject.tenant_id
+    input.resource.method == "PUT"
+    input.resource.query.unit == "celsius"
+    input.environment.country == "DE"
+    input.environment.bandwidth_used < 1000
+}
+"#,
+    )?;
+
+    let manager = PolicyManager::new(bundles.path().to_path_buf());
+    manager.load_tenant("tenant_a")?;
+
+    let input = |roles: Vec<String>| {
+        AbacInput::builder("tenant_a")
+            .user_id("alice".to_string())
+            .roles(roles)
+            .clearance_level(2)
+            .action("write")
+            .resource_type("sensors")
+            .resource_id("42".to_string())
+            .region("EU".to_string())
+            .method("PUT")
+            .path("/api/sensors/42")
+            .query(BTreeMap::from([(
+                "unit".to_string(),
+                "celsius".to_string(),
+            )]))
+            .country("DE".to_string())
+            .bandwidth_used(512.0)
+            .build()
+    };
+
+    let allowed = manager
+        .evaluate(
+            "tenant_a",
+            serde_json::to_value(input(vec!["operator".to_string()]))?,
+        )
+        .await?;
+    assert!(allowed.allow, "builder output should satisfy the policy");
+
+    let denied = manager
+        .evaluate(
+            "tenant_a",
+            serde_json::to_value(input(vec!["viewer".to_string()]))?,
+        )
+        .await?;
+    assert!(!denied.allow);
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+#![cfg(unix)]
+
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::Result;
+use edge_policy_proxy_http::config::ProxyConfig;
+use edge_policy_proxy_http::reload::{
+    reload_on_sighup, RuntimeSettings, SharedSettings, RELOAD_ENV_FILE_VAR,
+};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::time::sleep;
+use tracing::{dispatcher, Dispatch, Level};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, EnvFilter};
+
+fn debug_enabled(dispatch: &Dispatch) -> bool {
+    dispatcher::with_default(dispatch, || tracing::enabled!(Level::DEBUG))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sighup_updates_effective_log_level() -> Result<()> {
+    // Keep a SIGHUP listener registered for the whole test so an early signal
+    // cannot fall through to the default action and terminate the process.
+    let _guard = signal(SignalKind::hangup())?;
+
+    let dir = tempfile::tempdir()?;
+    let env_file = dir.path().join("proxy.env");
+    std::fs::write(&env_file, "LOG_LEVEL=warn\n")?;
+    // Variables come from a lookup rather than the process environment,
+    // which other tests running in parallel also read.
+    let vars = HashMap::from([
+        (
+            RELOAD_ENV_FILE_VAR.to_string(),
+            env_file.display().to_string(),
+        ),
+        ("LOG_LEVEL".to_string(), "warn".to_string()),
+    ]);
+    let env = move |name: &str| vars.get(name).cloned();
+
+    let startup = ProxyConfig::load_from(&env)?;
+    let (filter, log_handle) = reload::Layer::new(EnvFilter::new(&startup.log_level));
+    let dispatch = Dispatch::new(tracing_subscriber::registry().with(filter));
+    let settings = SharedSettings::new(RuntimeSettings::from_config(&startup));
+
+    assert!(!debug_enabled(&dispatch));
+
+    let task = tokio::spawn(reload_on_sighup(
+        settings.clone(),
+        Some(log_handle),
+        startup,
+        env,
+    ));
+
+    std::fs::write(&env_file, "LOG_LEVEL=debug\nREQUEST_TIMEOUT_SECS=5\n")?;
+
+    let pid = std::process::id().to_string();
+    for _ in 0..50 {
+        Command::new("kill").args(["-HUP", &pid]).status()?;
+        sleep(Duration::from_millis(50)).await;
+        if settings.current().log_level == "debug" {
+            break;
+        }
+    }
+
+    let current = settings.current();
+    assert_eq!(current.log_level, "debug");
+    assert_eq!(current.request_timeout_secs, 5);
+    assert!(debug_enabled(&dispatch));
+
+    task.abort();
+    Ok(())
+}
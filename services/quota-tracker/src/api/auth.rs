@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header::AUTHORIZATION, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::warn;
+
+use super::types::ErrorResponse;
+use super::ApiState;
+
+/// Rejects requests whose `Authorization: Bearer` token does not match the
+/// configured `api_token`. Without a configured token every request passes.
+pub async fn require_bearer_token(
+    State(state): State<Arc<ApiState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(expected) = state.config.api_token.as_deref() else {
+        return next.run(request).await;
+    };
+
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if tokens_match(token.trim(), expected) => next.run(request).await,
+        Some(_) => {
+            warn!(path = %request.uri().path(), "rejected request with invalid api token");
+            unauthorized("invalid_token", "api token is invalid")
+        }
+        None => unauthorized("missing_token", "bearer token is required"),
+    }
+}
+
+/// Compares tokens without short-circuiting on the first differing byte.
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    let (provided, expected) = (provided.as_bytes(), expected.as_bytes());
+    if provided.len() != expected.len() {
+        return false;
+    }
+
+    provided
+        .iter()
+        .zip(expected)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+fn unauthorized(code: &str, message: &str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ErrorResponse {
+            error: message.to_string(),
+            code: code.to_string(),
+            details: None,
+        }),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::config::QuotaTrackerConfig;
+    use crate::storage::QuotaDatabase;
+    use crate::tracker::QuotaManager;
+    use axum::Router;
+    use tempfile::tempdir;
+    use tower::ServiceExt;
+
+    fn router(data_dir: &std::path::Path) -> Router {
+        let config = QuotaTrackerConfig {
+            data_dir: data_dir.to_path_buf(),
+            api_token: Some("secret-token".to_string()),
+            ..QuotaTrackerConfig::default()
+        };
+        let database = Arc::new(QuotaDatabase::new(data_dir.to_path_buf()).unwrap());
+        let manager = Arc::new(QuotaManager::new(database, &config));
+        create_router(Arc::new(ApiState::new(manager, config)))
+    }
+
+    async fn get(router: Router, path: &str, authorization: Option<&str>) -> StatusCode {
+        let mut request = Request::get(path);
+        if let Some(value) = authorization {
+            request = request.header(AUTHORIZATION, value);
+        }
+        router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn authorized_request_is_served() {
+        let dir = tempdir().unwrap();
+        let status = get(router(dir.path()), "/api/quota", Some("Bearer secret-token")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_rejected_but_health_stays_open() {
+        let dir = tempdir().unwrap();
+        let router = router(dir.path());
+
+        assert_eq!(
+            get(router.clone(), "/api/quota", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(get(router, "/health", None).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn wrong_token_is_rejected() {
+        let dir = tempdir().unwrap();
+        let status = get(router(dir.path()), "/api/quota", Some("Bearer other-token")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
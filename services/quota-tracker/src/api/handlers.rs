@@ -1,23 +1,28 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
+use chrono::Utc;
 use serde::Deserialize;
 use tracing::{error, info};
 
 use crate::tracker::QuotaError;
 
 use super::types::{
-    CheckQuotaRequest, CheckQuotaResponse, ErrorResponse, IncrementQuotaRequest,
-    IncrementQuotaResponse, SetLimitsRequest, SetLimitsResponse,
+    BulkLimitResult, BulkSetLimitsResponse, CheckQuotaRequest, CheckQuotaResponse, ErrorResponse,
+    IncrementQuotaRequest, IncrementQuotaResponse, QuotaMetricsPage, QuotaMetricsQuery, QuotaUsage,
+    SetLimitsRequest, SetLimitsResponse, TenantResetSchedule,
 };
 use super::ApiState;
 
 type ApiResult<T> = Result<Json<T>, (StatusCode, Json<ErrorResponse>)>;
 
+const DEFAULT_METRICS_PAGE_SIZE: usize = 100;
+const MAX_METRICS_PAGE_SIZE: usize = 1000;
+
 pub async fn increment_quota(
     State(state): State<Arc<ApiState>>,
     Json(request): Json<IncrementQuotaRequest>,
@@ -45,52 +50,78 @@ pub async fn get_quota(
     }
 }
 
+/// Reports whether the tenant is over a limit along with its current usage,
+/// without counting the check against the quota.
 pub async fn check_quota(
     State(state): State<Arc<ApiState>>,
     Json(request): Json<CheckQuotaRequest>,
 ) -> ApiResult<CheckQuotaResponse> {
-    match state.quota_manager.check_quota(&request.tenant_id) {
-        Ok(_) => Ok(Json(CheckQuotaResponse {
-            exceeded: false,
-            quota_type: None,
-            limit: None,
-            current: None,
-        })),
+    let exceeded = match state.quota_manager.check_quota(&request.tenant_id) {
+        Ok(_) => None,
         Err(QuotaError::LimitExceeded {
             tenant_id: _,
             quota_type,
             limit,
             current,
-        }) => Ok(Json(CheckQuotaResponse {
-            exceeded: true,
-            quota_type: Some(quota_type),
-            limit: Some(limit),
-            current: Some(current),
-        })),
-        Err(QuotaError::TenantNotFound(_)) => Err(not_found("tenant_not_found", "tenant not tracked")),
-        Err(err) => Err(internal_error(err)),
-    }
+        }) => Some((quota_type, limit, current)),
+        Err(QuotaError::TenantNotFound(_)) => {
+            return Err(not_found("tenant_not_found", "tenant not tracked"))
+        }
+        Err(err) => return Err(internal_error(err)),
+    };
+
+    let metrics = state
+        .quota_manager
+        .current_metrics(&request.tenant_id)
+        .map_err(|err| internal_error(err))?;
+
+    let now = Utc::now();
+    let auto_reset = state.config.enable_auto_reset;
+    let messages = QuotaUsage {
+        used: metrics.message_count,
+        limit: metrics.message_limit,
+        remaining: (metrics.message_limit > 0).then(|| metrics.remaining_messages()),
+        resets_in_seconds: metrics.message_reset_in(now, auto_reset),
+    };
+    let bandwidth = QuotaUsage {
+        used: metrics.bytes_sent,
+        limit: metrics.bandwidth_limit_bytes,
+        remaining: (metrics.bandwidth_limit_bytes > 0).then(|| {
+            metrics
+                .bandwidth_limit_bytes
+                .saturating_sub(metrics.bytes_sent)
+        }),
+        resets_in_seconds: metrics.bandwidth_reset_in(now, auto_reset),
+    };
+
+    let (quota_type, limit, current) = match exceeded {
+        Some((quota_type, limit, current)) => (Some(quota_type), Some(limit), Some(current)),
+        None => (None, None, None),
+    };
+
+    Ok(Json(CheckQuotaResponse {
+        exceeded: quota_type.is_some(),
+        quota_type,
+        limit,
+        current,
+        messages,
+        bandwidth,
+    }))
 }
 
 pub async fn set_limits(
     State(state): State<Arc<ApiState>>,
     Json(request): Json<SetLimitsRequest>,
 ) -> ApiResult<SetLimitsResponse> {
-    if request.message_limit == 0 {
-        return Err(bad_request("invalid_limit", "message_limit must be greater than zero"));
-    }
-    if request.bandwidth_limit_gb <= 0.0 {
-        return Err(bad_request(
-            "invalid_limit",
-            "bandwidth_limit_gb must be greater than zero",
-        ));
-    }
+    validate_limits(&request)?;
 
     state
         .quota_manager
         .set_limits(&request.tenant_id, request.message_limit, request.bandwidth_limit_gb)
         .map_err(|err| internal_error(err))?;
 
+    apply_limit_options(&state, &request).map_err(|err| internal_error(err))?;
+
     info!(
         tenant_id = %request.tenant_id,
         message_limit = request.message_limit,
@@ -101,6 +132,76 @@ pub async fn set_limits(
     Ok(Json(SetLimitsResponse { success: true }))
 }
 
+/// Validates every entry, writes the valid ones in one transaction and
+/// reports a result per entry. Invalid entries do not abort the batch.
+pub async fn set_limits_bulk(
+    State(state): State<Arc<ApiState>>,
+    Json(requests): Json<Vec<SetLimitsRequest>>,
+) -> ApiResult<BulkSetLimitsResponse> {
+    let mut results = Vec::with_capacity(requests.len());
+    let mut valid = Vec::new();
+
+    for (index, request) in requests.iter().enumerate() {
+        let error = validate_limits(request).err().map(|(_, Json(body))| body);
+        if error.is_none() {
+            valid.push((
+                request.tenant_id.clone(),
+                request.message_limit,
+                request.bandwidth_limit_gb,
+            ));
+        }
+        results.push(BulkLimitResult {
+            index,
+            tenant_id: request.tenant_id.clone(),
+            success: error.is_none(),
+            error,
+        });
+    }
+
+    state
+        .quota_manager
+        .set_limits_bulk(&valid)
+        .map_err(|err| internal_error(err))?;
+
+    for (request, result) in requests.iter().zip(results.iter_mut()) {
+        if !result.success {
+            continue;
+        }
+        if let Err(err) = apply_limit_options(&state, request) {
+            let (_, Json(body)) = internal_error(err);
+            result.success = false;
+            result.error = Some(body);
+        }
+    }
+
+    let updated = results.iter().filter(|result| result.success).count();
+    let failed = results.len() - updated;
+    info!(updated, failed, "bulk quota limits processed");
+
+    Ok(Json(BulkSetLimitsResponse {
+        updated,
+        failed,
+        results,
+    }))
+}
+
+/// Applies the optional per-tenant settings of a limits entry.
+fn apply_limit_options(state: &ApiState, request: &SetLimitsRequest) -> Result<(), QuotaError> {
+    if let Some(enabled) = request.token_bucket {
+        state
+            .quota_manager
+            .set_token_bucket(&request.tenant_id, enabled)?;
+    }
+    if request.message_reset_period.is_some() || request.bandwidth_reset_period.is_some() {
+        state.quota_manager.set_reset_periods(
+            &request.tenant_id,
+            request.message_reset_period,
+            request.bandwidth_reset_period,
+        )?;
+    }
+    Ok(())
+}
+
 pub async fn list_quotas(
     State(state): State<Arc<ApiState>>,
 ) -> ApiResult<Vec<crate::tracker::QuotaMetrics>> {
@@ -108,6 +209,61 @@ pub async fn list_quotas(
     Ok(Json(metrics))
 }
 
+/// Each tracked tenant's next reset per dimension with its current usage,
+/// soonest reset first, so operators can plan for load at reset boundaries.
+pub async fn list_reset_schedule(
+    State(state): State<Arc<ApiState>>,
+) -> ApiResult<Vec<TenantResetSchedule>> {
+    let auto_reset = state.config.enable_auto_reset;
+    let mut schedule: Vec<TenantResetSchedule> = state
+        .quota_manager
+        .all_metrics()
+        .iter()
+        .filter_map(|metrics| state.quota_manager.current_metrics(&metrics.tenant_id).ok())
+        .map(|metrics| TenantResetSchedule::new(&metrics, auto_reset))
+        .collect();
+    schedule.sort_by(|a, b| {
+        match (a.next_reset(), b.next_reset()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+        .then_with(|| a.tenant_id.cmp(&b.tenant_id))
+    });
+    Ok(Json(schedule))
+}
+
+/// Pages through tracked tenants by id, optionally keeping only those near
+/// or over a limit.
+pub async fn list_quota_metrics(
+    State(state): State<Arc<ApiState>>,
+    Query(query): Query<QuotaMetricsQuery>,
+) -> ApiResult<QuotaMetricsPage> {
+    let limit = query.limit.unwrap_or(DEFAULT_METRICS_PAGE_SIZE);
+    if limit == 0 || limit > MAX_METRICS_PAGE_SIZE {
+        return Err(bad_request("invalid_limit", "limit must be between 1 and 1000"));
+    }
+    if let Some(threshold) = query.over_threshold {
+        if !(threshold.is_finite() && threshold >= 0.0) {
+            return Err(bad_request(
+                "invalid_threshold",
+                "over_threshold must be a non-negative number",
+            ));
+        }
+    }
+
+    let (metrics, next_cursor) = state
+        .quota_manager
+        .metrics_page(query.cursor.as_deref(), limit, query.over_threshold)
+        .map_err(|err| internal_error(err))?;
+
+    Ok(Json(QuotaMetricsPage {
+        metrics,
+        next_cursor,
+    }))
+}
+
 pub async fn reset_quota(
     State(state): State<Arc<ApiState>>,
     Path(tenant_id): Path<String>,
@@ -120,6 +276,11 @@ pub async fn reset_quota(
     Ok(Json(SetLimitsResponse { success: true }))
 }
 
+/// Returns the effective configuration with secrets redacted.
+pub async fn get_config(State(state): State<Arc<ApiState>>) -> ApiResult<serde_json::Value> {
+    Ok(Json(state.config.dump()))
+}
+
 pub async fn health_check() -> ApiResult<serde_json::Value> {
     Ok(Json(serde_json::json!({
         "status": "healthy",
@@ -127,6 +288,22 @@ pub async fn health_check() -> ApiResult<serde_json::Value> {
     })))
 }
 
+fn validate_limits(request: &SetLimitsRequest) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if request.tenant_id.trim().is_empty() {
+        return Err(bad_request("invalid_tenant_id", "tenant_id cannot be empty"));
+    }
+    if request.message_limit == 0 {
+        return Err(bad_request("invalid_limit", "message_limit must be greater than zero"));
+    }
+    if !(request.bandwidth_limit_gb > 0.0 && request.bandwidth_limit_gb.is_finite()) {
+        return Err(bad_request(
+            "invalid_limit",
+            "bandwidth_limit_gb must be greater than zero",
+        ));
+    }
+    Ok(())
+}
+
 fn bad_request(code: &str, message: &str) -> (StatusCode, Json<ErrorResponse>) {
     (
         StatusCode::BAD_REQUEST,
@@ -160,3 +337,197 @@ fn internal_error<E: std::fmt::Display>(err: E) -> (StatusCode, Json<ErrorRespon
         }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::QuotaTrackerConfig;
+    use crate::storage::QuotaDatabase;
+    use crate::tracker::{QuotaManager, MESSAGE_QUOTA_TYPE};
+    use tempfile::tempdir;
+
+    fn limits(tenant_id: &str, message_limit: u64, bandwidth_limit_gb: f64) -> SetLimitsRequest {
+        SetLimitsRequest {
+            tenant_id: tenant_id.to_string(),
+            message_limit,
+            bandwidth_limit_gb,
+            token_bucket: None,
+            message_reset_period: None,
+            bandwidth_reset_period: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn bulk_limits_apply_valid_entries_and_report_invalid_ones() {
+        let dir = tempdir().unwrap();
+        let config = QuotaTrackerConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..QuotaTrackerConfig::default()
+        };
+        let database = Arc::new(QuotaDatabase::new(dir.path().to_path_buf()).unwrap());
+        let manager = Arc::new(QuotaManager::new(Arc::clone(&database), &config));
+        let state = Arc::new(ApiState::new(Arc::clone(&manager), config));
+
+        let Json(response) = set_limits_bulk(
+            State(state),
+            Json(vec![
+                limits("tenant-a", 1_000, 1.0),
+                limits("tenant-b", 0, 1.0),
+                limits("tenant-c", 5_000, 2.5),
+            ]),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.updated, 2);
+        assert_eq!(response.failed, 1);
+        assert!(response.results[0].success);
+        assert!(!response.results[1].success);
+        assert_eq!(
+            response.results[1].error.as_ref().unwrap().code,
+            "invalid_limit"
+        );
+        assert!(response.results[2].success);
+
+        assert_eq!(manager.get_metrics("tenant-a").unwrap().message_limit, 1_000);
+        assert_eq!(manager.get_metrics("tenant-c").unwrap().message_limit, 5_000);
+        assert!(manager.get_metrics("tenant-b").is_none());
+        assert!(database.get_quota_limits("tenant-b").unwrap().is_none());
+        assert_eq!(
+            database
+                .get_quota_limits("tenant-c")
+                .unwrap()
+                .unwrap()
+                .message_limit,
+            5_000
+        );
+    }
+
+    #[tokio::test]
+    async fn check_reports_usage_without_counting_it() {
+        let dir = tempdir().unwrap();
+        let config = QuotaTrackerConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..QuotaTrackerConfig::default()
+        };
+        let database = Arc::new(QuotaDatabase::new(dir.path().to_path_buf()).unwrap());
+        let manager = Arc::new(QuotaManager::new(database, &config));
+        let state = Arc::new(ApiState::new(Arc::clone(&manager), config));
+
+        manager.set_limits("tenant-a", 100, 1.0).unwrap();
+        manager.increment_message_count("tenant-a", 40, 1024);
+
+        let check = || {
+            let state = Arc::clone(&state);
+            let request = CheckQuotaRequest {
+                tenant_id: "tenant-a".to_string(),
+            };
+            async move { check_quota(State(state), Json(request)).await }
+        };
+
+        let Json(first) = check().await.unwrap();
+        assert!(!first.exceeded);
+        assert_eq!(first.messages.used, 40);
+        assert_eq!(first.messages.limit, 100);
+        assert_eq!(first.messages.remaining, Some(60));
+        assert_eq!(first.bandwidth.used, 1024);
+        assert_eq!(first.bandwidth.remaining, Some(1024 * 1024 * 1024 - 1024));
+        let until_midnight = first.messages.resets_in_seconds.unwrap();
+        assert!(until_midnight > 0 && until_midnight <= 24 * 60 * 60);
+
+        let Json(second) = check().await.unwrap();
+        assert_eq!(second.messages.used, 40);
+        assert_eq!(second.bandwidth.used, 1024);
+        let metrics = manager.get_metrics("tenant-a").unwrap();
+        assert_eq!(metrics.message_count, 40);
+        assert_eq!(metrics.bytes_sent, 1024);
+
+        manager.increment_message_count("tenant-a", 60, 0);
+        let Json(exceeded) = check().await.unwrap();
+        assert!(exceeded.exceeded);
+        assert_eq!(exceeded.quota_type.as_deref(), Some(MESSAGE_QUOTA_TYPE));
+        assert_eq!(exceeded.messages.remaining, Some(0));
+
+        let (status, _) = check_quota(
+            State(Arc::clone(&state)),
+            Json(CheckQuotaRequest {
+                tenant_id: "tenant-b".to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn config_dump_redacts_api_token() {
+        let dir = tempdir().unwrap();
+        let config = QuotaTrackerConfig {
+            data_dir: dir.path().to_path_buf(),
+            default_message_limit: 12_345,
+            api_token: Some("quota-api-secret".to_string()),
+            ..QuotaTrackerConfig::default()
+        };
+        let database = Arc::new(QuotaDatabase::new(dir.path().to_path_buf()).unwrap());
+        let manager = Arc::new(QuotaManager::new(database, &config));
+        let state = Arc::new(ApiState::new(manager, config));
+
+        let Json(dump) = get_config(State(state)).await.unwrap();
+
+        assert_eq!(dump["default_message_limit"], 12_345);
+        assert_eq!(dump["server_port"], 8183);
+        assert_eq!(dump["api_token"], crate::config::REDACTED_SECRET);
+        assert!(!dump.to_string().contains("quota-api-secret"));
+    }
+
+    #[tokio::test]
+    async fn metrics_page_filters_by_threshold_and_pages() {
+        let dir = tempdir().unwrap();
+        let config = QuotaTrackerConfig {
+            data_dir: dir.path().to_path_buf(),
+            ..QuotaTrackerConfig::default()
+        };
+        let database = Arc::new(QuotaDatabase::new(dir.path().to_path_buf()).unwrap());
+        let manager = Arc::new(QuotaManager::new(Arc::clone(&database), &config));
+        let state = Arc::new(ApiState::new(Arc::clone(&manager), config));
+
+        let usage = [("tenant-a", 95), ("tenant-b", 10), ("tenant-c", 90), ("tenant-d", 99)];
+        for (tenant_id, used) in usage {
+            manager.set_limits(tenant_id, 100, 1.0).unwrap();
+            manager.increment_message_count(tenant_id, used, 0);
+        }
+        manager.persist_all().unwrap();
+
+        let page = |cursor: Option<&str>, limit, over_threshold| {
+            let state = Arc::clone(&state);
+            let query = QuotaMetricsQuery {
+                limit: Some(limit),
+                cursor: cursor.map(str::to_string),
+                over_threshold,
+            };
+            async move { list_quota_metrics(State(state), Query(query)).await }
+        };
+        let tenants = |page: &QuotaMetricsPage| {
+            page.metrics
+                .iter()
+                .map(|metrics| metrics.tenant_id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let Json(first) = page(None, 2, Some(0.9)).await.unwrap();
+        assert_eq!(tenants(&first), vec!["tenant-a", "tenant-c"]);
+        assert_eq!(first.next_cursor.as_deref(), Some("tenant-c"));
+
+        let Json(second) = page(first.next_cursor.as_deref(), 2, Some(0.9)).await.unwrap();
+        assert_eq!(tenants(&second), vec!["tenant-d"]);
+        assert!(second.next_cursor.is_none());
+
+        let Json(all) = page(None, 10, None).await.unwrap();
+        assert_eq!(all.metrics.len(), 4);
+        assert!(all.next_cursor.is_none());
+
+        let (status, Json(body)) = page(None, 0, None).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.code, "invalid_limit");
+    }
+}
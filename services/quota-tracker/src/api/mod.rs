@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+pub mod auth;
 pub mod handlers;
 pub mod router;
 pub mod types;
@@ -2,12 +2,14 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
 use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
 
+use super::auth::require_bearer_token;
 use super::handlers;
 use super::ApiState;
 
@@ -20,9 +22,20 @@ pub fn create_router(state: Arc<ApiState>) -> Router {
         .route("/api/quota/increment", post(handlers::increment_quota))
         .route("/api/quota/check", post(handlers::check_quota))
         .route("/api/quota/limits", post(handlers::set_limits))
+        .route("/api/quota/limits/bulk", post(handlers::set_limits_bulk))
         .route("/api/quota", get(handlers::list_quotas))
+        .route("/api/quota/metrics", get(handlers::list_quota_metrics))
+        .route(
+            "/api/quota/reset-schedule",
+            get(handlers::list_reset_schedule),
+        )
         .route("/api/quota/:tenant_id", get(handlers::get_quota))
         .route("/api/quota/:tenant_id/reset", post(handlers::reset_quota))
+        .route("/api/config", get(handlers::get_config))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_bearer_token,
+        ))
         .route("/health", get(handlers::health_check))
         .with_state(state)
         .layer(middleware)
@@ -1,6 +1,7 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::tracker::QuotaMetrics;
+use crate::tracker::{QuotaMetrics, ResetPeriod};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IncrementQuotaRequest {
@@ -19,11 +20,37 @@ pub struct GetQuotaResponse {
     pub metrics: QuotaMetrics,
 }
 
+/// Query parameters for `GET /api/quota/metrics`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuotaMetricsQuery {
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page.
+    pub cursor: Option<String>,
+    /// Only return tenants whose message or bandwidth usage ratio is at least
+    /// this value, e.g. `0.9` for tenants at 90% of a limit.
+    pub over_threshold: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaMetricsPage {
+    pub metrics: Vec<QuotaMetrics>,
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetLimitsRequest {
     pub tenant_id: String,
     pub message_limit: u64,
     pub bandwidth_limit_gb: f64,
+    /// Opts the tenant in to (or out of) continuously refilling message quotas.
+    #[serde(default)]
+    pub token_bucket: Option<bool>,
+    /// How often the message counter resets; unchanged when omitted.
+    #[serde(default)]
+    pub message_reset_period: Option<ResetPeriod>,
+    /// How often the bandwidth counter resets; unchanged when omitted.
+    #[serde(default)]
+    pub bandwidth_reset_period: Option<ResetPeriod>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +58,22 @@ pub struct SetLimitsResponse {
     pub success: bool,
 }
 
+/// Outcome of one entry in a bulk limits request, in request order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkLimitResult {
+    pub index: usize,
+    pub tenant_id: String,
+    pub success: bool,
+    pub error: Option<ErrorResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkSetLimitsResponse {
+    pub updated: usize,
+    pub failed: usize,
+    pub results: Vec<BulkLimitResult>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckQuotaRequest {
     pub tenant_id: String,
@@ -42,6 +85,72 @@ pub struct CheckQuotaResponse {
     pub quota_type: Option<String>,
     pub limit: Option<u64>,
     pub current: Option<u64>,
+    pub messages: QuotaUsage,
+    pub bandwidth: QuotaUsage,
+}
+
+/// Usage of one quota dimension at the time of a check. Bandwidth is in bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub used: u64,
+    /// `0` when the dimension is unlimited.
+    pub limit: u64,
+    /// `None` when the dimension is unlimited.
+    pub remaining: Option<u64>,
+    /// Seconds until usage goes down: the next token for a token bucket,
+    /// otherwise the next counter reset. `None` when it never resets.
+    pub resets_in_seconds: Option<u64>,
+}
+
+/// When one quota dimension of a tenant next resets, with its usage so far.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DimensionResetSchedule {
+    pub reset_period: ResetPeriod,
+    pub period_start: DateTime<Utc>,
+    /// `None` when the counter never resets, e.g. for token buckets or with
+    /// `auto_reset` off.
+    pub next_reset: Option<DateTime<Utc>>,
+    pub used: u64,
+    /// `0` when the dimension is unlimited.
+    pub limit: u64,
+}
+
+/// Entry of `GET /api/quota/reset-schedule`. Bandwidth is in bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantResetSchedule {
+    pub tenant_id: String,
+    pub messages: DimensionResetSchedule,
+    pub bandwidth: DimensionResetSchedule,
+}
+
+impl TenantResetSchedule {
+    pub fn new(metrics: &QuotaMetrics, auto_reset: bool) -> Self {
+        Self {
+            tenant_id: metrics.tenant_id.clone(),
+            messages: DimensionResetSchedule {
+                reset_period: metrics.message_reset_period,
+                period_start: metrics.message_period_start,
+                next_reset: metrics.next_message_reset(auto_reset),
+                used: metrics.message_count,
+                limit: metrics.message_limit,
+            },
+            bandwidth: DimensionResetSchedule {
+                reset_period: metrics.bandwidth_reset_period,
+                period_start: metrics.bandwidth_period_start,
+                next_reset: metrics.next_bandwidth_reset(auto_reset),
+                used: metrics.bytes_sent,
+                limit: metrics.bandwidth_limit_bytes,
+            },
+        }
+    }
+
+    /// Earliest upcoming reset across both dimensions.
+    pub fn next_reset(&self) -> Option<DateTime<Utc>> {
+        match (self.messages.next_reset, self.bandwidth.next_reset) {
+            (Some(messages), Some(bandwidth)) => Some(messages.min(bandwidth)),
+            (messages, bandwidth) => messages.or(bandwidth),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -3,8 +3,16 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
 
-#[derive(Debug, Clone)]
+/// Shown in place of secret values in the configuration dump.
+pub const REDACTED_SECRET: &str = "***";
+/// Environment variable naming a TOML file read by [`QuotaTrackerConfig::load`].
+pub const CONFIG_FILE_ENV: &str = "QUOTA_CONFIG_FILE";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct QuotaTrackerConfig {
     pub server_host: String,
     pub server_port: u16,
@@ -14,6 +22,8 @@ pub struct QuotaTrackerConfig {
     pub default_bandwidth_limit_gb: f64,
     pub enable_auto_reset: bool,
     pub log_level: String,
+    /// Bearer token required on every `/api` route; unset leaves them open.
+    pub api_token: Option<String>,
 }
 
 impl Default for QuotaTrackerConfig {
@@ -27,13 +37,39 @@ impl Default for QuotaTrackerConfig {
             default_bandwidth_limit_gb: 100.0,
             enable_auto_reset: true,
             log_level: "info".to_string(),
+            api_token: None,
         }
     }
 }
 
 impl QuotaTrackerConfig {
+    /// Loads the TOML file named by `QUOTA_CONFIG_FILE` when it is set,
+    /// otherwise the environment alone.
+    pub fn load() -> Result<Self> {
+        match env::var(CONFIG_FILE_ENV) {
+            Ok(path) if !path.trim().is_empty() => Self::from_file(path),
+            _ => Self::from_env(),
+        }
+    }
+
     pub fn from_env() -> Result<Self> {
-        let mut cfg = Self::default();
+        Self::default().with_env_overrides()
+    }
+
+    /// Loads a TOML file keyed by field name, e.g. `server_port = 8183`.
+    /// Fields the file leaves out keep their defaults, and environment
+    /// variables override both.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        let cfg: Self = toml::from_str(&raw)
+            .with_context(|| format!("failed to parse config file {}", path.display()))?;
+        cfg.with_env_overrides()
+    }
+
+    fn with_env_overrides(self) -> Result<Self> {
+        let mut cfg = self;
 
         if let Ok(host) = env::var("QUOTA_HOST") {
             cfg.server_host = host;
@@ -66,13 +102,33 @@ impl QuotaTrackerConfig {
         if let Ok(level) = env::var("LOG_LEVEL") {
             cfg.log_level = level;
         }
+        if let Ok(token) = env::var("QUOTA_API_TOKEN") {
+            cfg.api_token = Some(token.trim().to_string()).filter(|token| !token.is_empty());
+        }
 
         cfg.validate()?;
         Ok(cfg)
     }
 
+    /// Effective configuration as served by `GET /api/config`, with secrets
+    /// replaced by [`REDACTED_SECRET`].
+    pub fn dump(&self) -> Value {
+        json!({
+            "server_host": self.server_host,
+            "server_port": self.server_port,
+            "data_dir": self.data_dir,
+            "persistence_interval_secs": self.persistence_interval_secs,
+            "default_message_limit": self.default_message_limit,
+            "default_bandwidth_limit_gb": self.default_bandwidth_limit_gb,
+            "enable_auto_reset": self.enable_auto_reset,
+            "log_level": self.log_level,
+            "api_token": self.api_token.as_ref().map(|_| REDACTED_SECRET),
+        })
+    }
+
     pub fn validate(&self) -> Result<()> {
         ensure_directory(&self.data_dir)?;
+        ensure_writable(&self.data_dir)?;
 
         if self.default_message_limit == 0 {
             anyhow::bail!("DEFAULT_MESSAGE_LIMIT must be greater than zero");
@@ -100,6 +156,16 @@ fn ensure_directory(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Writes and removes a probe file so a read-only data directory is reported
+/// before the service starts rather than on its first write.
+fn ensure_writable(path: &Path) -> Result<()> {
+    let probe = path.join(".write-check");
+    fs::write(&probe, b"")
+        .with_context(|| format!("data directory {} is not writable", path.display()))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
 fn parse_bool(value: &str) -> Result<bool> {
     match value.to_ascii_lowercase().as_str() {
         "true" | "1" | "yes" | "y" => Ok(true),
@@ -107,3 +173,46 @@ fn parse_bool(value: &str) -> Result<bool> {
         _ => anyhow::bail!("invalid boolean value {value}"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_file_values_load_and_env_overrides_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join("quota");
+        let config_path = dir.path().join("quota-tracker.toml");
+        fs::write(
+            &config_path,
+            format!(
+                r#"
+server_port = 9183
+data_dir = "{}"
+default_message_limit = 1000
+enable_auto_reset = false
+api_token = "file-token"
+"#,
+                data_dir.display()
+            ),
+        )
+        .unwrap();
+
+        let cfg = QuotaTrackerConfig::from_file(&config_path).unwrap();
+        assert_eq!(cfg.server_port, 9183);
+        assert_eq!(cfg.data_dir, data_dir);
+        assert_eq!(cfg.default_message_limit, 1000);
+        assert!(!cfg.enable_auto_reset);
+        assert_eq!(cfg.api_token.as_deref(), Some("file-token"));
+        // Left out of the file, so the default applies
+        assert_eq!(cfg.persistence_interval_secs, 60);
+
+        env::set_var("QUOTA_PORT", "9283");
+        let cfg = QuotaTrackerConfig::from_file(&config_path);
+        env::remove_var("QUOTA_PORT");
+
+        let cfg = cfg.unwrap();
+        assert_eq!(cfg.server_port, 9283);
+        assert_eq!(cfg.default_message_limit, 1000);
+    }
+}
@@ -9,7 +9,8 @@ use std::sync::Arc;
 use anyhow::Result;
 use axum::Server;
 use tracing::{info, warn};
-use tracing_subscriber::{fmt, EnvFilter};
+use edge_policy_telemetry::LogFormat;
+use tracing_subscriber::EnvFilter;
 
 use api::ApiState;
 use config::QuotaTrackerConfig;
@@ -18,9 +19,15 @@ use tracker::QuotaManager;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if std::env::args().skip(1).any(|arg| arg == "--check-config") {
+        let config = QuotaTrackerConfig::load()?;
+        println!("configuration ok (data_dir: {})", config.data_dir.display());
+        return Ok(());
+    }
+
     init_tracing()?;
 
-    let config = QuotaTrackerConfig::from_env()?;
+    let config = QuotaTrackerConfig::load()?;
     let host = config.server_host.clone();
     let port = config.server_port;
 
@@ -45,6 +52,10 @@ async fn main() -> Result<()> {
 
     let _persistence_task = manager.start_persistence_task();
 
+    if config.api_token.is_none() {
+        warn!("QUOTA_API_TOKEN is not set; quota API routes accept unauthenticated requests");
+    }
+
     let state = Arc::new(ApiState::new(Arc::clone(&manager), config));
     let router = api::create_router(Arc::clone(&state));
     let addr: SocketAddr = format!("{}:{}", host, port).parse()?;
@@ -60,7 +71,7 @@ async fn main() -> Result<()> {
 
 fn init_tracing() -> Result<()> {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    fmt().with_env_filter(filter).try_init()?;
+    edge_policy_telemetry::try_init_tracing(filter, LogFormat::from_env()?)?;
     Ok(())
 }
 
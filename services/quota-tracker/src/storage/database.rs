@@ -2,18 +2,27 @@ use std::path::PathBuf;
 use std::sync::Mutex;
 
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, OptionalExtension};
 
 use super::error::StorageError;
-use super::schema::init_database;
-use super::QUOTA_DB_FILENAME;
+use crate::tracker::period::{
+    default_bandwidth_reset_period, default_message_reset_period, ResetPeriod,
+};
+use crate::tracker::{BANDWIDTH_QUOTA_TYPE, MESSAGE_QUOTA_TYPE};
+use super::schema::{init_database, migrate_database};
+use super::{QUOTA_DB_FILENAME, TOKEN_BUCKET_PERIOD};
 
 #[derive(Debug, Clone)]
 pub struct QuotaLimits {
     pub tenant_id: String,
     pub message_limit: u64,
     pub bandwidth_limit_bytes: u64,
+    /// Whether message usage is metered by a refilling token bucket instead of
+    /// a daily counter.
+    pub token_bucket: bool,
+    pub message_reset_period: ResetPeriod,
+    pub bandwidth_reset_period: ResetPeriod,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -24,9 +33,26 @@ pub struct QuotaUsageRecord {
     pub period: String,
     pub quota_type: String,
     pub used: u64,
+    /// Remaining tokens for token-bucket records; `None` for period counters.
+    pub tokens: Option<f64>,
+    pub last_refill: Option<String>,
+    /// Start of the period this record counts; `None` for token-bucket records.
+    pub period_start: Option<String>,
     pub last_updated: String,
 }
 
+/// A tenant's limits next to its most recently persisted usage.
+#[derive(Debug, Clone)]
+pub struct QuotaUsageSummary {
+    pub tenant_id: String,
+    pub message_limit: u64,
+    pub bandwidth_limit_bytes: u64,
+    pub messages_used: u64,
+    pub bytes_used: u64,
+    /// Highest of the message and bandwidth usage ratios.
+    pub usage_ratio: f64,
+}
+
 pub struct QuotaDatabase {
     data_dir: PathBuf,
     conn: Mutex<Connection>,
@@ -44,6 +70,7 @@ impl QuotaDatabase {
         if is_new {
             init_database(&conn)?;
         }
+        migrate_database(&conn)?;
 
         Ok(Self {
             data_dir,
@@ -91,6 +118,55 @@ impl QuotaDatabase {
         Ok(())
     }
 
+    /// Upserts limits for several tenants in one transaction; either every
+    /// row is written or none are.
+    pub fn set_quota_limits_bulk(
+        &self,
+        limits: &[(String, u64, f64)],
+    ) -> Result<(), StorageError> {
+        if let Some((tenant_id, _, _)) = limits
+            .iter()
+            .find(|(_, messages, bandwidth_gb)| *messages == 0 || *bandwidth_gb <= 0.0)
+        {
+            return Err(StorageError::InvalidQuotaValue(format!(
+                "limits for tenant {tenant_id} must be greater than zero"
+            )));
+        }
+
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidQuotaValue("connection poisoned".into()))?;
+
+        let now = Utc::now().to_rfc3339();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT INTO quota_limits (tenant_id, message_limit, bandwidth_limit_bytes, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT(tenant_id) DO UPDATE SET
+                    message_limit = excluded.message_limit,
+                    bandwidth_limit_bytes = excluded.bandwidth_limit_bytes,
+                    updated_at = excluded.updated_at
+                "#,
+            )?;
+            for (tenant_id, message_limit, bandwidth_limit_gb) in limits {
+                let bytes_limit = (bandwidth_limit_gb * 1024.0 * 1024.0 * 1024.0) as u64;
+                stmt.execute(params![
+                    tenant_id,
+                    *message_limit as i64,
+                    bytes_limit as i64,
+                    now,
+                    now
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
     pub fn get_quota_limits(
         &self,
         tenant_id: &str,
@@ -102,7 +178,8 @@ impl QuotaDatabase {
 
         let mut stmt = conn.prepare(
             r#"
-            SELECT tenant_id, message_limit, bandwidth_limit_bytes, created_at, updated_at
+            SELECT tenant_id, message_limit, bandwidth_limit_bytes, token_bucket,
+                   message_reset_period, bandwidth_reset_period, created_at, updated_at
             FROM quota_limits
             WHERE tenant_id = ?1
             "#,
@@ -114,8 +191,17 @@ impl QuotaDatabase {
                     tenant_id: row.get(0)?,
                     message_limit: row.get::<_, i64>(1)? as u64,
                     bandwidth_limit_bytes: row.get::<_, i64>(2)? as u64,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
+                    token_bucket: row.get::<_, i64>(3)? != 0,
+                    message_reset_period: parse_reset_period(
+                        row.get(4)?,
+                        default_message_reset_period(),
+                    ),
+                    bandwidth_reset_period: parse_reset_period(
+                        row.get(5)?,
+                        default_bandwidth_reset_period(),
+                    ),
+                    created_at: row.get(6)?,
+                    updated_at: row.get(7)?,
                 })
             })
             .optional()?;
@@ -129,6 +215,7 @@ impl QuotaDatabase {
         period: &str,
         quota_type: &str,
         used: u64,
+        period_start: DateTime<Utc>,
     ) -> Result<(), StorageError> {
         let conn = self
             .conn
@@ -138,13 +225,21 @@ impl QuotaDatabase {
 
         conn.execute(
             r#"
-            INSERT INTO quota_usage (tenant_id, period, quota_type, used, last_updated)
-            VALUES (?1, ?2, ?3, ?4, ?5)
+            INSERT INTO quota_usage (tenant_id, period, quota_type, used, period_start, last_updated)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             ON CONFLICT(tenant_id, period, quota_type) DO UPDATE SET
                 used = excluded.used,
+                period_start = excluded.period_start,
                 last_updated = excluded.last_updated
             "#,
-            params![tenant_id, period, quota_type, used as i64, now],
+            params![
+                tenant_id,
+                period,
+                quota_type,
+                used as i64,
+                period_start.to_rfc3339(),
+                now
+            ],
         )?;
 
         Ok(())
@@ -176,6 +271,133 @@ impl QuotaDatabase {
         Ok(used.unwrap_or(0) as u64)
     }
 
+    pub fn set_reset_periods(
+        &self,
+        tenant_id: &str,
+        message_reset_period: ResetPeriod,
+        bandwidth_reset_period: ResetPeriod,
+    ) -> Result<(), StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidQuotaValue("connection poisoned".into()))?;
+
+        let updated = conn.execute(
+            r#"
+            UPDATE quota_limits
+            SET message_reset_period = ?2, bandwidth_reset_period = ?3, updated_at = ?4
+            WHERE tenant_id = ?1
+            "#,
+            params![
+                tenant_id,
+                message_reset_period.as_str(),
+                bandwidth_reset_period.as_str(),
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+
+        if updated == 0 {
+            return Err(StorageError::TenantNotFound(tenant_id.to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn set_token_bucket_enabled(
+        &self,
+        tenant_id: &str,
+        enabled: bool,
+    ) -> Result<(), StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidQuotaValue("connection poisoned".into()))?;
+
+        let updated = conn.execute(
+            r#"
+            UPDATE quota_limits
+            SET token_bucket = ?2, updated_at = ?3
+            WHERE tenant_id = ?1
+            "#,
+            params![tenant_id, enabled as i64, Utc::now().to_rfc3339()],
+        )?;
+
+        if updated == 0 {
+            return Err(StorageError::TenantNotFound(tenant_id.to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn save_token_bucket(
+        &self,
+        tenant_id: &str,
+        quota_type: &str,
+        used: u64,
+        tokens: f64,
+        last_refill: DateTime<Utc>,
+    ) -> Result<(), StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidQuotaValue("connection poisoned".into()))?;
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            r#"
+            INSERT INTO quota_usage (tenant_id, period, quota_type, used, tokens, last_refill, last_updated)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT(tenant_id, period, quota_type) DO UPDATE SET
+                used = excluded.used,
+                tokens = excluded.tokens,
+                last_refill = excluded.last_refill,
+                last_updated = excluded.last_updated
+            "#,
+            params![
+                tenant_id,
+                TOKEN_BUCKET_PERIOD,
+                quota_type,
+                used as i64,
+                tokens,
+                last_refill.to_rfc3339(),
+                now
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the stored `(tokens, last_refill)` for a tenant's token bucket.
+    pub fn load_token_bucket(
+        &self,
+        tenant_id: &str,
+        quota_type: &str,
+    ) -> Result<Option<(f64, DateTime<Utc>)>, StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidQuotaValue("connection poisoned".into()))?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT tokens, last_refill
+            FROM quota_usage
+            WHERE tenant_id = ?1 AND period = ?2 AND quota_type = ?3
+            "#,
+        )?;
+
+        let row = stmt
+            .query_row(params![tenant_id, TOKEN_BUCKET_PERIOD, quota_type], |row| {
+                Ok((row.get::<_, Option<f64>>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .optional()?;
+
+        Ok(match row {
+            Some((Some(tokens), Some(last_refill))) => DateTime::parse_from_rfc3339(&last_refill)
+                .ok()
+                .map(|at| (tokens, at.with_timezone(&Utc))),
+            _ => None,
+        })
+    }
+
     pub fn list_tenant_usage(
         &self,
         tenant_id: &str,
@@ -187,7 +409,7 @@ impl QuotaDatabase {
 
         let mut stmt = conn.prepare(
             r#"
-            SELECT tenant_id, period, quota_type, used, last_updated
+            SELECT tenant_id, period, quota_type, used, tokens, last_refill, period_start, last_updated
             FROM quota_usage
             WHERE tenant_id = ?1
             ORDER BY period DESC
@@ -200,7 +422,10 @@ impl QuotaDatabase {
                 period: row.get(1)?,
                 quota_type: row.get(2)?,
                 used: row.get::<_, i64>(3)? as u64,
-                last_updated: row.get(4)?,
+                tokens: row.get(4)?,
+                last_refill: row.get(5)?,
+                period_start: row.get(6)?,
+                last_updated: row.get(7)?,
             })
         })?;
 
@@ -219,7 +444,8 @@ impl QuotaDatabase {
 
         let mut stmt = conn.prepare(
             r#"
-            SELECT tenant_id, message_limit, bandwidth_limit_bytes, created_at, updated_at
+            SELECT tenant_id, message_limit, bandwidth_limit_bytes, token_bucket,
+                   message_reset_period, bandwidth_reset_period, created_at, updated_at
             FROM quota_limits
             "#,
         )?;
@@ -229,8 +455,17 @@ impl QuotaDatabase {
                 tenant_id: row.get(0)?,
                 message_limit: row.get::<_, i64>(1)? as u64,
                 bandwidth_limit_bytes: row.get::<_, i64>(2)? as u64,
-                created_at: row.get(3)?,
-                updated_at: row.get(4)?,
+                token_bucket: row.get::<_, i64>(3)? != 0,
+                message_reset_period: parse_reset_period(
+                    row.get(4)?,
+                    default_message_reset_period(),
+                ),
+                bandwidth_reset_period: parse_reset_period(
+                    row.get(5)?,
+                    default_bandwidth_reset_period(),
+                ),
+                created_at: row.get(6)?,
+                updated_at: row.get(7)?,
             })
         })?;
 
@@ -240,4 +475,76 @@ impl QuotaDatabase {
         }
         Ok(limits)
     }
+
+    /// Returns up to `limit` tenants ordered by id, starting after `after`.
+    /// With `over_threshold`, only tenants whose persisted usage ratio is at
+    /// least that value are returned.
+    pub fn list_usage_page(
+        &self,
+        after: Option<&str>,
+        limit: usize,
+        over_threshold: Option<f64>,
+    ) -> Result<Vec<QuotaUsageSummary>, StorageError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| StorageError::InvalidQuotaValue("connection poisoned".into()))?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT tenant_id, message_limit, bandwidth_limit_bytes, messages_used, bytes_used,
+                   MAX(messages_used * 1.0 / message_limit, bytes_used * 1.0 / bandwidth_limit_bytes)
+                       AS usage_ratio
+            FROM (
+                SELECT l.tenant_id, l.message_limit, l.bandwidth_limit_bytes,
+                       COALESCE((
+                           SELECT u.used FROM quota_usage u
+                           WHERE u.tenant_id = l.tenant_id AND u.quota_type = ?3
+                           ORDER BY u.last_updated DESC LIMIT 1
+                       ), 0) AS messages_used,
+                       COALESCE((
+                           SELECT u.used FROM quota_usage u
+                           WHERE u.tenant_id = l.tenant_id AND u.quota_type = ?4
+                           ORDER BY u.last_updated DESC LIMIT 1
+                       ), 0) AS bytes_used
+                FROM quota_limits l
+                WHERE ?1 IS NULL OR l.tenant_id > ?1
+            )
+            WHERE ?2 IS NULL OR usage_ratio >= ?2
+            ORDER BY tenant_id
+            LIMIT ?5
+            "#,
+        )?;
+
+        let rows = stmt.query_map(
+            params![
+                after,
+                over_threshold,
+                MESSAGE_QUOTA_TYPE,
+                BANDWIDTH_QUOTA_TYPE,
+                limit as i64
+            ],
+            |row| {
+                Ok(QuotaUsageSummary {
+                    tenant_id: row.get(0)?,
+                    message_limit: row.get::<_, i64>(1)? as u64,
+                    bandwidth_limit_bytes: row.get::<_, i64>(2)? as u64,
+                    messages_used: row.get::<_, i64>(3)? as u64,
+                    bytes_used: row.get::<_, i64>(4)? as u64,
+                    usage_ratio: row.get(5)?,
+                })
+            },
+        )?;
+
+        let mut page = Vec::new();
+        for row in rows {
+            page.push(row?);
+        }
+        Ok(page)
+    }
+}
+
+/// Falls back to the dimension's default when a stored value is unrecognised.
+fn parse_reset_period(value: String, default: ResetPeriod) -> ResetPeriod {
+    value.parse().unwrap_or(default)
 }
@@ -8,3 +8,5 @@ pub use error::StorageError;
 pub const QUOTA_DB_FILENAME: &str = "quotas.db";
 pub const QUOTA_LIMITS_TABLE: &str = "quota_limits";
 pub const QUOTA_USAGE_TABLE: &str = "quota_usage";
+/// Period key under which token-bucket state is stored in `quota_usage`.
+pub const TOKEN_BUCKET_PERIOD: &str = "bucket";
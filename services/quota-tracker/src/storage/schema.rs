@@ -6,6 +6,9 @@ CREATE TABLE IF NOT EXISTS quota_limits (
     tenant_id TEXT PRIMARY KEY,
     message_limit INTEGER NOT NULL,
     bandwidth_limit_bytes INTEGER NOT NULL,
+    token_bucket INTEGER NOT NULL DEFAULT 0,
+    message_reset_period TEXT NOT NULL DEFAULT 'daily',
+    bandwidth_reset_period TEXT NOT NULL DEFAULT 'monthly',
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL
 );
@@ -18,6 +21,9 @@ CREATE TABLE IF NOT EXISTS quota_usage (
     period TEXT NOT NULL,
     quota_type TEXT NOT NULL,
     used INTEGER NOT NULL,
+    tokens REAL,
+    last_refill TEXT,
+    period_start TEXT,
     last_updated TEXT NOT NULL,
     UNIQUE(tenant_id, period, quota_type)
 );
@@ -25,6 +31,7 @@ CREATE TABLE IF NOT EXISTS quota_usage (
 
 pub const QUOTA_USAGE_INDEXES: &str = r#"
 CREATE INDEX IF NOT EXISTS idx_usage_tenant_period ON quota_usage(tenant_id, period);
+CREATE INDEX IF NOT EXISTS idx_usage_tenant_type_updated ON quota_usage(tenant_id, quota_type, last_updated);
 "#;
 
 pub fn init_database(conn: &Connection) -> Result<()> {
@@ -33,3 +40,50 @@ pub fn init_database(conn: &Connection) -> Result<()> {
     conn.execute_batch(QUOTA_USAGE_INDEXES)?;
     Ok(())
 }
+
+/// Adds columns introduced after the initial schema to existing databases.
+pub fn migrate_database(conn: &Connection) -> Result<()> {
+    add_column_if_missing(
+        conn,
+        "quota_limits",
+        "token_bucket",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(conn, "quota_usage", "tokens", "REAL")?;
+    add_column_if_missing(conn, "quota_usage", "last_refill", "TEXT")?;
+    add_column_if_missing(
+        conn,
+        "quota_limits",
+        "message_reset_period",
+        "TEXT NOT NULL DEFAULT 'daily'",
+    )?;
+    add_column_if_missing(
+        conn,
+        "quota_limits",
+        "bandwidth_reset_period",
+        "TEXT NOT NULL DEFAULT 'monthly'",
+    )?;
+    add_column_if_missing(conn, "quota_usage", "period_start", "TEXT")?;
+    conn.execute_batch(QUOTA_USAGE_INDEXES)?;
+    Ok(())
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+        ))?;
+    }
+    Ok(())
+}
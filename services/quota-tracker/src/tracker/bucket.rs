@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A continuously refilling token bucket.
+///
+/// Message quotas use a capacity equal to the tenant's message limit and refill
+/// that many tokens evenly over a day, so usage recovers gradually instead of
+/// resetting at midnight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenBucket {
+    pub capacity: f64,
+    pub tokens: f64,
+    /// Tokens added per second of elapsed time.
+    pub refill_per_sec: f64,
+    pub last_refill: DateTime<Utc>,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket that refills `capacity` tokens every `refill_period`.
+    pub fn full(capacity: u64, refill_period: Duration, now: DateTime<Utc>) -> Self {
+        let capacity = capacity as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / refill_period.as_secs_f64().max(1.0),
+            last_refill: now,
+        }
+    }
+
+    /// Adds the tokens accrued since `last_refill`, capped at `capacity`.
+    pub fn refill(&mut self, now: DateTime<Utc>) {
+        let elapsed = (now - self.last_refill).num_milliseconds();
+        if elapsed <= 0 {
+            return;
+        }
+
+        let accrued = elapsed as f64 / 1000.0 * self.refill_per_sec;
+        self.tokens = (self.tokens + accrued).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Removes `amount` tokens, bottoming out at zero.
+    pub fn consume(&mut self, amount: u64) {
+        self.tokens = (self.tokens - amount as f64).max(0.0);
+    }
+
+    /// Changes the capacity and refill rate, keeping the current token count
+    /// within the new capacity.
+    pub fn resize(&mut self, capacity: u64, refill_period: Duration) {
+        let capacity = capacity as f64;
+        self.capacity = capacity;
+        self.refill_per_sec = capacity / refill_period.as_secs_f64().max(1.0);
+        self.tokens = self.tokens.min(capacity);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens < 1.0
+    }
+
+    /// Time until at least one whole token is available, zero when one already
+    /// is. `None` when the bucket never refills.
+    pub fn time_until_token(&self) -> Option<Duration> {
+        if !self.is_empty() {
+            return Some(Duration::ZERO);
+        }
+        if self.refill_per_sec <= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            (1.0 - self.tokens) / self.refill_per_sec,
+        ))
+    }
+
+    /// Tokens spent and not yet refilled, rounded to whole messages.
+    pub fn consumed(&self) -> u64 {
+        (self.capacity - self.tokens).max(0.0).round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn refills_continuously_over_time() {
+        let start = Utc::now();
+        let mut bucket = TokenBucket::full(10_000, DAY, start);
+
+        bucket.consume(10_000);
+        assert!(bucket.is_empty());
+
+        bucket.refill(start + ChronoDuration::hours(6));
+        assert!((bucket.tokens - 2_500.0).abs() < 1.0);
+        assert!(!bucket.is_empty());
+
+        bucket.refill(start + ChronoDuration::days(3));
+        assert_eq!(bucket.tokens, bucket.capacity);
+    }
+
+    #[test]
+    fn refill_ignores_clock_going_backwards() {
+        let start = Utc::now();
+        let mut bucket = TokenBucket::full(100, DAY, start);
+        bucket.consume(50);
+
+        bucket.refill(start - ChronoDuration::minutes(5));
+        assert_eq!(bucket.tokens, 50.0);
+        assert_eq!(bucket.last_refill, start);
+    }
+
+    #[test]
+    fn time_until_token_tracks_refill_rate() {
+        let start = Utc::now();
+        let mut bucket = TokenBucket::full(24, DAY, start);
+        assert_eq!(bucket.time_until_token(), Some(Duration::ZERO));
+
+        bucket.consume(24);
+        let wait = bucket.time_until_token().unwrap();
+        assert!((wait.as_secs_f64() - 3600.0).abs() < 1.0);
+    }
+}
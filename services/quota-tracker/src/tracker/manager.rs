@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use chrono::{Datelike, Utc};
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use tokio::task::JoinHandle;
 use tokio::time::{interval, MissedTickBehavior};
@@ -10,10 +10,15 @@ use tracing::{debug, error, info};
 use crate::config::QuotaTrackerConfig;
 use crate::storage::{QuotaDatabase, StorageError};
 
+use super::bucket::TokenBucket;
 use super::error::QuotaError;
 use super::metrics::QuotaMetrics;
+use super::period::{default_bandwidth_reset_period, default_message_reset_period, ResetPeriod};
 use super::{BANDWIDTH_QUOTA_TYPE, MESSAGE_QUOTA_TYPE};
 
+/// Time over which a token bucket refills from empty to its full message limit.
+const MESSAGE_REFILL_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[derive(Clone)]
 pub struct QuotaManager {
     cache: Arc<DashMap<String, QuotaMetrics>>,
@@ -39,26 +44,40 @@ impl QuotaManager {
     pub fn load_from_database(&self) -> Result<usize, StorageError> {
         let limits = self.database.list_limits()?;
         let mut loaded = 0usize;
-        let day_period = current_day_period();
-        let month_period = current_month_period();
+        let now = Utc::now();
 
         for limit in limits {
-            let message_used =
-                self.database
-                    .load_usage(&limit.tenant_id, &day_period, MESSAGE_QUOTA_TYPE)?;
-            let bandwidth_used =
-                self.database
-                    .load_usage(&limit.tenant_id, &month_period, BANDWIDTH_QUOTA_TYPE)?;
-
-            let metrics = QuotaMetrics {
+            let mut metrics = QuotaMetrics {
                 tenant_id: limit.tenant_id.clone(),
-                message_count: message_used,
-                bytes_sent: bandwidth_used,
                 message_limit: limit.message_limit,
                 bandwidth_limit_bytes: limit.bandwidth_limit_bytes,
-                last_reset: Utc::now(),
-                period: day_period.clone(),
+                last_reset: now,
+                message_reset_period: limit.message_reset_period,
+                bandwidth_reset_period: limit.bandwidth_reset_period,
+                ..QuotaMetrics::default()
             };
+            metrics.align_periods(now);
+
+            let message_used =
+                self.database
+                    .load_usage(&limit.tenant_id, &metrics.period, MESSAGE_QUOTA_TYPE)?;
+            let bandwidth_used = self.database.load_usage(
+                &limit.tenant_id,
+                &metrics.bandwidth_period(),
+                BANDWIDTH_QUOTA_TYPE,
+            )?;
+
+            let token_bucket = if limit.token_bucket {
+                Some(self.load_token_bucket(&limit.tenant_id, limit.message_limit)?)
+            } else {
+                None
+            };
+            metrics.message_count = token_bucket
+                .as_ref()
+                .map(TokenBucket::consumed)
+                .unwrap_or(message_used);
+            metrics.bytes_sent = bandwidth_used;
+            metrics.token_bucket = token_bucket;
 
             self.cache.insert(limit.tenant_id, metrics);
             loaded += 1;
@@ -73,30 +92,37 @@ impl QuotaManager {
         messages: u64,
         bytes: u64,
     ) -> QuotaMetrics {
-        self.ensure_entry(tenant_id);
+        self.increment_message_count_at(tenant_id, messages, bytes, Utc::now())
+    }
+
+    fn increment_message_count_at(
+        &self,
+        tenant_id: &str,
+        messages: u64,
+        bytes: u64,
+        now: DateTime<Utc>,
+    ) -> QuotaMetrics {
+        self.ensure_entry_at(tenant_id, now);
 
         let mut entry = self
             .cache
             .get_mut(tenant_id)
             .expect("entry must exist after ensure_entry");
 
-        let now = Utc::now();
-        let current_day = current_day_period();
-        let current_month = current_month_period();
-        let last_month = format!("{:04}-{:02}", entry.last_reset.year(), entry.last_reset.month());
-
-        if self.enable_auto_reset && entry.period != current_day {
-            entry.period = current_day.clone();
-            entry.message_count = 0;
-        }
-
-        if self.enable_auto_reset && current_month != last_month {
-            entry.bytes_sent = 0;
-            entry.last_reset = now;
+        if self.enable_auto_reset {
+            entry.roll_over(now);
         }
 
         let msg_inc = if messages == 0 { 1 } else { messages };
-        entry.message_count = entry.message_count.saturating_add(msg_inc);
+        let consumed = entry.token_bucket.as_mut().map(|bucket| {
+            bucket.refill(now);
+            bucket.consume(msg_inc);
+            bucket.consumed()
+        });
+        entry.message_count = match consumed {
+            Some(consumed) => consumed,
+            None => entry.message_count.saturating_add(msg_inc),
+        };
         entry.bytes_sent = entry.bytes_sent.saturating_add(bytes);
 
         entry.clone()
@@ -113,11 +139,72 @@ impl QuotaManager {
             .collect()
     }
 
+    /// Returns up to `limit` tenants ordered by id after `cursor`, plus the
+    /// cursor for the next page when more remain.
+    ///
+    /// `over_threshold` is matched against persisted usage, so it can lag the
+    /// returned live metrics by up to one persistence interval.
+    pub fn metrics_page(
+        &self,
+        cursor: Option<&str>,
+        limit: usize,
+        over_threshold: Option<f64>,
+    ) -> Result<(Vec<QuotaMetrics>, Option<String>), StorageError> {
+        let mut summaries = self
+            .database
+            .list_usage_page(cursor, limit.saturating_add(1), over_threshold)?;
+
+        let next_cursor = if summaries.len() > limit {
+            summaries.truncate(limit);
+            summaries.last().map(|summary| summary.tenant_id.clone())
+        } else {
+            None
+        };
+        let metrics = summaries
+            .iter()
+            .filter_map(|summary| self.get_metrics(&summary.tenant_id))
+            .collect();
+
+        Ok((metrics, next_cursor))
+    }
+
     pub fn check_quota(&self, tenant_id: &str) -> Result<(), QuotaError> {
-        let metrics = self
-            .get_metrics(tenant_id)
+        self.check_quota_at(tenant_id, Utc::now())
+    }
+
+    /// Current usage for `tenant_id` after any due period reset or token
+    /// refill, without counting anything against the quota.
+    pub fn current_metrics(&self, tenant_id: &str) -> Result<QuotaMetrics, QuotaError> {
+        self.current_metrics_at(tenant_id, Utc::now())
+    }
+
+    fn current_metrics_at(
+        &self,
+        tenant_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<QuotaMetrics, QuotaError> {
+        let mut entry = self
+            .cache
+            .get_mut(tenant_id)
             .ok_or_else(|| QuotaError::TenantNotFound(tenant_id.to_string()))?;
 
+        if self.enable_auto_reset {
+            entry.roll_over(now);
+        }
+
+        let consumed = entry.token_bucket.as_mut().map(|bucket| {
+            bucket.refill(now);
+            bucket.consumed()
+        });
+        if let Some(consumed) = consumed {
+            entry.message_count = consumed;
+        }
+        Ok(entry.clone())
+    }
+
+    fn check_quota_at(&self, tenant_id: &str, now: DateTime<Utc>) -> Result<(), QuotaError> {
+        let metrics = self.current_metrics_at(tenant_id, now)?;
+
         if metrics.is_message_limit_exceeded() {
             return Err(QuotaError::LimitExceeded {
                 tenant_id: tenant_id.to_string(),
@@ -147,20 +234,106 @@ impl QuotaManager {
     ) -> Result<(), QuotaError> {
         self.database
             .set_quota_limits(tenant_id, message_limit, bandwidth_limit_gb)?;
+        self.apply_limits(tenant_id, message_limit, bandwidth_limit_gb);
+
+        info!(
+            tenant_id,
+            message_limit,
+            bandwidth_limit_gb,
+            "updated quota limits"
+        );
+        Ok(())
+    }
+
+    /// Sets limits for several tenants with a single database transaction.
+    ///
+    /// Entries are `(tenant_id, message_limit, bandwidth_limit_gb)` and must
+    /// already be validated; if the write fails no cached limits change.
+    pub fn set_limits_bulk(&self, limits: &[(String, u64, f64)]) -> Result<(), QuotaError> {
+        self.database.set_quota_limits_bulk(limits)?;
 
+        for (tenant_id, message_limit, bandwidth_limit_gb) in limits {
+            self.apply_limits(tenant_id, *message_limit, *bandwidth_limit_gb);
+        }
+
+        info!(tenants = limits.len(), "updated quota limits in bulk");
+        Ok(())
+    }
+
+    fn apply_limits(&self, tenant_id: &str, message_limit: u64, bandwidth_limit_gb: f64) {
         let bytes_limit = (bandwidth_limit_gb * 1024.0 * 1024.0 * 1024.0) as u64;
         self.ensure_entry(tenant_id);
 
         if let Some(mut metrics) = self.cache.get_mut(tenant_id) {
             metrics.message_limit = message_limit;
             metrics.bandwidth_limit_bytes = bytes_limit;
+            if let Some(bucket) = metrics.token_bucket.as_mut() {
+                bucket.resize(message_limit, MESSAGE_REFILL_PERIOD);
+            }
+        }
+    }
+
+    /// Switches a tenant between the daily message counter and a token bucket
+    /// holding `message_limit` tokens that refill continuously over a day.
+    pub fn set_token_bucket(&self, tenant_id: &str, enabled: bool) -> Result<(), QuotaError> {
+        self.ensure_entry(tenant_id);
+        self.database.set_token_bucket_enabled(tenant_id, enabled)?;
+
+        if let Some(mut metrics) = self.cache.get_mut(tenant_id) {
+            match (enabled, metrics.token_bucket.is_some()) {
+                (true, false) => {
+                    let bucket =
+                        TokenBucket::full(metrics.message_limit, MESSAGE_REFILL_PERIOD, Utc::now());
+                    metrics.message_count = bucket.consumed();
+                    metrics.token_bucket = Some(bucket);
+                }
+                (false, true) => {
+                    metrics.token_bucket = None;
+                    metrics.message_count = 0;
+                    metrics.align_periods(Utc::now());
+                }
+                _ => {}
+            }
         }
 
+        info!(tenant_id, enabled, "updated token bucket mode");
+        Ok(())
+    }
+
+    /// Changes how often each dimension resets; `None` keeps the current
+    /// schedule. The new schedule applies to the period containing now and
+    /// existing usage is carried over.
+    pub fn set_reset_periods(
+        &self,
+        tenant_id: &str,
+        message_reset_period: Option<ResetPeriod>,
+        bandwidth_reset_period: Option<ResetPeriod>,
+    ) -> Result<(), QuotaError> {
+        self.ensure_entry(tenant_id);
+
+        let (message_reset_period, bandwidth_reset_period) = {
+            let mut metrics = self
+                .cache
+                .get_mut(tenant_id)
+                .ok_or_else(|| QuotaError::TenantNotFound(tenant_id.to_string()))?;
+            if let Some(period) = message_reset_period {
+                metrics.message_reset_period = period;
+            }
+            if let Some(period) = bandwidth_reset_period {
+                metrics.bandwidth_reset_period = period;
+            }
+            metrics.align_periods(Utc::now());
+            (metrics.message_reset_period, metrics.bandwidth_reset_period)
+        };
+
+        self.database
+            .set_reset_periods(tenant_id, message_reset_period, bandwidth_reset_period)?;
+
         info!(
             tenant_id,
-            message_limit,
-            bandwidth_limit_gb,
-            "updated quota limits"
+            message_reset_period = %message_reset_period,
+            bandwidth_reset_period = %bandwidth_reset_period,
+            "updated quota reset periods"
         );
         Ok(())
     }
@@ -168,39 +341,85 @@ impl QuotaManager {
     pub fn reset_quota(&self, tenant_id: &str) -> Result<(), QuotaError> {
         self.ensure_entry(tenant_id);
 
+        let mut refilled_bucket = None;
+        let mut periods = None;
         if let Some(mut metrics) = self.cache.get_mut(tenant_id) {
+            let now = Utc::now();
             metrics.message_count = 0;
             metrics.bytes_sent = 0;
-            metrics.period = current_day_period();
-            metrics.last_reset = Utc::now();
+            metrics.align_periods(now);
+            metrics.last_reset = now;
+            if let Some(bucket) = metrics.token_bucket.as_mut() {
+                bucket.tokens = bucket.capacity;
+                bucket.last_refill = now;
+                refilled_bucket = Some(bucket.clone());
+            }
+            periods = Some((
+                metrics.period.clone(),
+                metrics.message_period_start,
+                metrics.bandwidth_period(),
+                metrics.bandwidth_period_start,
+            ));
         }
 
-        self.database
-            .save_usage(tenant_id, &current_day_period(), MESSAGE_QUOTA_TYPE, 0)?;
-        self.database
-            .save_usage(tenant_id, &current_month_period(), BANDWIDTH_QUOTA_TYPE, 0)?;
+        if let Some(bucket) = refilled_bucket {
+            self.database.save_token_bucket(
+                tenant_id,
+                MESSAGE_QUOTA_TYPE,
+                0,
+                bucket.tokens,
+                bucket.last_refill,
+            )?;
+        }
+
+        if let Some((message_period, message_start, bandwidth_period, bandwidth_start)) = periods {
+            self.database.save_usage(
+                tenant_id,
+                &message_period,
+                MESSAGE_QUOTA_TYPE,
+                0,
+                message_start,
+            )?;
+            self.database.save_usage(
+                tenant_id,
+                &bandwidth_period,
+                BANDWIDTH_QUOTA_TYPE,
+                0,
+                bandwidth_start,
+            )?;
+        }
 
         Ok(())
     }
 
     pub fn persist_all(&self) -> Result<usize, StorageError> {
         let mut persisted = 0usize;
-        let month_period = current_month_period();
 
         for entry in self.cache.iter() {
             let tenant_id = entry.key().clone();
             let metrics = entry.value().clone();
+            match &metrics.token_bucket {
+                Some(bucket) => self.database.save_token_bucket(
+                    &tenant_id,
+                    MESSAGE_QUOTA_TYPE,
+                    bucket.consumed(),
+                    bucket.tokens,
+                    bucket.last_refill,
+                )?,
+                None => self.database.save_usage(
+                    &tenant_id,
+                    &metrics.period,
+                    MESSAGE_QUOTA_TYPE,
+                    metrics.message_count,
+                    metrics.message_period_start,
+                )?,
+            }
             self.database.save_usage(
                 &tenant_id,
-                &metrics.period,
-                MESSAGE_QUOTA_TYPE,
-                metrics.message_count,
-            )?;
-            self.database.save_usage(
-                &tenant_id,
-                &month_period,
+                &metrics.bandwidth_period(),
                 BANDWIDTH_QUOTA_TYPE,
                 metrics.bytes_sent,
+                metrics.bandwidth_period_start,
             )?;
             persisted += 1;
         }
@@ -232,16 +451,24 @@ impl QuotaManager {
     }
 
     fn ensure_entry(&self, tenant_id: &str) {
+        self.ensure_entry_at(tenant_id, Utc::now());
+    }
+
+    fn ensure_entry_at(&self, tenant_id: &str, now: DateTime<Utc>) {
         if self.cache.contains_key(tenant_id) {
             return;
         }
 
-        let day_period = current_day_period();
         let bytes_limit = (self.default_bandwidth_limit_gb * 1024.0 * 1024.0 * 1024.0) as u64;
         let limits = self.database.get_quota_limits(tenant_id).ok().flatten();
 
-        let (message_limit, bandwidth_limit_bytes) = match limits {
-            Some(limit) => (limit.message_limit, limit.bandwidth_limit_bytes),
+        let (message_limit, bandwidth_limit_bytes, token_bucket, reset_periods) = match limits {
+            Some(limit) => (
+                limit.message_limit,
+                limit.bandwidth_limit_bytes,
+                limit.token_bucket,
+                (limit.message_reset_period, limit.bandwidth_reset_period),
+            ),
             None => {
                 if let Err(err) = self.database.set_quota_limits(
                     tenant_id,
@@ -254,30 +481,160 @@ impl QuotaManager {
                         "failed to initialize quota limits from defaults"
                     );
                 }
-                (self.default_message_limit, bytes_limit)
+                (
+                    self.default_message_limit,
+                    bytes_limit,
+                    false,
+                    (
+                        default_message_reset_period(),
+                        default_bandwidth_reset_period(),
+                    ),
+                )
             }
         };
 
-        let metrics = QuotaMetrics {
+        let token_bucket = if token_bucket {
+            match self.load_token_bucket(tenant_id, message_limit) {
+                Ok(bucket) => Some(bucket),
+                Err(err) => {
+                    error!(tenant_id, error = %err, "failed to load token bucket state");
+                    Some(TokenBucket::full(message_limit, MESSAGE_REFILL_PERIOD, Utc::now()))
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut metrics = QuotaMetrics {
             tenant_id: tenant_id.to_string(),
-            message_count: 0,
+            message_count: token_bucket.as_ref().map(TokenBucket::consumed).unwrap_or(0),
             bytes_sent: 0,
             message_limit,
             bandwidth_limit_bytes,
-            last_reset: Utc::now(),
-            period: day_period,
+            last_reset: now,
+            message_reset_period: reset_periods.0,
+            bandwidth_reset_period: reset_periods.1,
+            token_bucket,
+            ..QuotaMetrics::default()
         };
+        metrics.align_periods(now);
 
         self.cache.insert(tenant_id.to_string(), metrics);
     }
-}
 
-fn current_day_period() -> String {
-    let now = Utc::now();
-    format!("{:04}-{:02}-{:02}", now.year(), now.month(), now.day())
+    /// Restores persisted bucket state, or starts a full bucket when none exists.
+    fn load_token_bucket(
+        &self,
+        tenant_id: &str,
+        message_limit: u64,
+    ) -> Result<TokenBucket, StorageError> {
+        let now = Utc::now();
+        let mut bucket = TokenBucket::full(message_limit, MESSAGE_REFILL_PERIOD, now);
+
+        if let Some((tokens, last_refill)) =
+            self.database.load_token_bucket(tenant_id, MESSAGE_QUOTA_TYPE)?
+        {
+            bucket.tokens = tokens.min(bucket.capacity);
+            bucket.last_refill = last_refill;
+        }
+
+        Ok(bucket)
+    }
 }
 
-fn current_month_period() -> String {
-    let now = Utc::now();
-    format!("{:04}-{:02}", now.year(), now.month())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration as ChronoDuration, TimeZone};
+    use tempfile::tempdir;
+
+    fn manager(data_dir: &std::path::Path) -> QuotaManager {
+        let config = QuotaTrackerConfig {
+            data_dir: data_dir.to_path_buf(),
+            ..QuotaTrackerConfig::default()
+        };
+        let database = Arc::new(QuotaDatabase::new(data_dir.to_path_buf()).unwrap());
+        QuotaManager::new(database, &config)
+    }
+
+    #[test]
+    fn message_counter_resets_daily_while_bandwidth_carries_over() {
+        let dir = tempdir().unwrap();
+        let manager = manager(dir.path());
+
+        let evening = Utc.with_ymd_and_hms(2024, 3, 14, 23, 0, 0).unwrap();
+        manager.increment_message_count_at("tenant-a", 40, 1_000, evening);
+
+        let next_morning = Utc.with_ymd_and_hms(2024, 3, 15, 1, 0, 0).unwrap();
+        let metrics = manager.increment_message_count_at("tenant-a", 5, 500, next_morning);
+
+        assert_eq!(metrics.message_count, 5);
+        assert_eq!(metrics.bytes_sent, 1_500);
+        assert_eq!(metrics.period, "2024-03-15");
+        assert_eq!(metrics.bandwidth_period(), "2024-03");
+    }
+
+    #[test]
+    fn daily_message_reset_falls_on_next_utc_midnight() {
+        let dir = tempdir().unwrap();
+        let manager = manager(dir.path());
+
+        let evening = Utc.with_ymd_and_hms(2024, 3, 14, 23, 0, 0).unwrap();
+        manager.increment_message_count_at("tenant-a", 40, 1_000, evening);
+
+        let morning = Utc.with_ymd_and_hms(2024, 3, 15, 9, 30, 0).unwrap();
+        let metrics = manager.current_metrics_at("tenant-a", morning).unwrap();
+        assert_eq!(metrics.message_reset_period, ResetPeriod::Daily);
+        assert_eq!(
+            metrics.next_message_reset(true),
+            Some(Utc.with_ymd_and_hms(2024, 3, 16, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            metrics.next_bandwidth_reset(true),
+            Some(Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(metrics.next_message_reset(false), None);
+    }
+
+    #[test]
+    fn token_bucket_denies_when_empty_and_refills_over_time() {
+        let dir = tempdir().unwrap();
+        let manager = manager(dir.path());
+        manager.set_limits("tenant-a", 100, 1.0).unwrap();
+        manager.set_token_bucket("tenant-a", true).unwrap();
+
+        let start = Utc::now();
+        manager.increment_message_count_at("tenant-a", 100, 0, start);
+        assert!(matches!(
+            manager.check_quota_at("tenant-a", start),
+            Err(QuotaError::LimitExceeded { .. })
+        ));
+
+        // A quarter of a day refills a quarter of the bucket.
+        let later = start + ChronoDuration::hours(6);
+        manager.check_quota_at("tenant-a", later).unwrap();
+        let metrics = manager.get_metrics("tenant-a").unwrap();
+        assert_eq!(metrics.remaining_messages(), 25);
+        assert_eq!(metrics.message_count, 75);
+    }
+
+    #[test]
+    fn token_bucket_state_survives_restart() {
+        let dir = tempdir().unwrap();
+
+        let first = manager(dir.path());
+        first.set_limits("tenant-a", 1_000, 1.0).unwrap();
+        first.set_token_bucket("tenant-a", true).unwrap();
+        first.increment_message_count("tenant-a", 400, 0);
+        first.persist_all().unwrap();
+        drop(first);
+
+        let restarted = manager(dir.path());
+        assert_eq!(restarted.load_from_database().unwrap(), 1);
+
+        let metrics = restarted.get_metrics("tenant-a").unwrap();
+        let bucket = metrics.token_bucket.expect("token bucket should be restored");
+        assert!((bucket.tokens - 600.0).abs() < 1.0, "tokens = {}", bucket.tokens);
+        assert_eq!(metrics.message_count, 400);
+    }
 }
@@ -1,6 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::bucket::TokenBucket;
+use super::period::{default_bandwidth_reset_period, default_message_reset_period, ResetPeriod};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuotaMetrics {
     pub tenant_id: String,
@@ -9,7 +12,22 @@ pub struct QuotaMetrics {
     pub message_limit: u64,
     pub bandwidth_limit_bytes: u64,
     pub last_reset: DateTime<Utc>,
+    /// Storage key of the message period currently being counted.
     pub period: String,
+    #[serde(default = "default_message_reset_period")]
+    pub message_reset_period: ResetPeriod,
+    #[serde(default = "default_bandwidth_reset_period")]
+    pub bandwidth_reset_period: ResetPeriod,
+    /// Start of the period `message_count` accumulates over.
+    #[serde(default)]
+    pub message_period_start: DateTime<Utc>,
+    /// Start of the period `bytes_sent` accumulates over.
+    #[serde(default)]
+    pub bandwidth_period_start: DateTime<Utc>,
+    /// Present when the tenant meters messages with a refilling token bucket;
+    /// `message_count` then reports tokens spent and not yet refilled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_bucket: Option<TokenBucket>,
 }
 
 impl Default for QuotaMetrics {
@@ -22,11 +40,59 @@ impl Default for QuotaMetrics {
             bandwidth_limit_bytes: 0,
             last_reset: Utc::now(),
             period: String::new(),
+            message_reset_period: default_message_reset_period(),
+            bandwidth_reset_period: default_bandwidth_reset_period(),
+            message_period_start: DateTime::<Utc>::UNIX_EPOCH,
+            bandwidth_period_start: DateTime::<Utc>::UNIX_EPOCH,
+            token_bucket: None,
         }
     }
 }
 
 impl QuotaMetrics {
+    /// Resets each dimension whose schedule has moved into a new period since
+    /// its counter started. Token-bucket message quotas refill continuously
+    /// and are left alone. Returns whether anything was reset.
+    pub fn roll_over(&mut self, now: DateTime<Utc>) -> bool {
+        let mut reset = false;
+
+        let message_start = self.message_reset_period.period_start(now);
+        if self.token_bucket.is_none() && message_start > self.message_period_start {
+            self.message_count = 0;
+            self.message_period_start = message_start;
+            self.period = self.message_reset_period.period_key(message_start);
+            reset = true;
+        }
+
+        let bandwidth_start = self.bandwidth_reset_period.period_start(now);
+        if bandwidth_start > self.bandwidth_period_start {
+            self.bytes_sent = 0;
+            self.bandwidth_period_start = bandwidth_start;
+            reset = true;
+        }
+
+        if reset {
+            self.last_reset = now;
+        }
+        reset
+    }
+
+    /// Points both dimensions at the periods containing `now` without
+    /// touching the counters.
+    pub fn align_periods(&mut self, now: DateTime<Utc>) {
+        self.message_period_start = self.message_reset_period.period_start(now);
+        self.bandwidth_period_start = self.bandwidth_reset_period.period_start(now);
+        self.period = self
+            .message_reset_period
+            .period_key(self.message_period_start);
+    }
+
+    /// Storage key of the bandwidth period currently being counted.
+    pub fn bandwidth_period(&self) -> String {
+        self.bandwidth_reset_period
+            .period_key(self.bandwidth_period_start)
+    }
+
     pub fn message_percentage(&self) -> f64 {
         if self.message_limit == 0 {
             return 0.0;
@@ -42,6 +108,9 @@ impl QuotaMetrics {
     }
 
     pub fn is_message_limit_exceeded(&self) -> bool {
+        if let Some(bucket) = &self.token_bucket {
+            return bucket.is_empty();
+        }
         self.message_limit > 0 && self.message_count >= self.message_limit
     }
 
@@ -50,9 +119,59 @@ impl QuotaMetrics {
     }
 
     pub fn remaining_messages(&self) -> u64 {
+        if let Some(bucket) = &self.token_bucket {
+            return bucket.tokens.floor() as u64;
+        }
         self.message_limit.saturating_sub(self.message_count)
     }
 
+    /// Seconds until messages can be sent again: until the next token for a
+    /// token bucket, otherwise until the counter resets. `None` when usage
+    /// never goes down, e.g. with `auto_reset` off.
+    pub fn message_reset_in(&self, now: DateTime<Utc>, auto_reset: bool) -> Option<u64> {
+        if let Some(bucket) = &self.token_bucket {
+            return bucket
+                .time_until_token()
+                .map(|wait| wait.as_secs_f64().ceil() as u64);
+        }
+        if !auto_reset {
+            return None;
+        }
+        self.message_reset_period
+            .next_period_start(now)
+            .map(|reset| seconds_until(now, reset))
+    }
+
+    /// Seconds until the bandwidth counter resets; `None` when it never does.
+    pub fn bandwidth_reset_in(&self, now: DateTime<Utc>, auto_reset: bool) -> Option<u64> {
+        if !auto_reset {
+            return None;
+        }
+        self.bandwidth_reset_period
+            .next_period_start(now)
+            .map(|reset| seconds_until(now, reset))
+    }
+
+    /// When the message counter next starts over, from the start of the
+    /// period it is counting. `None` for token buckets, which refill instead,
+    /// and when usage never resets.
+    pub fn next_message_reset(&self, auto_reset: bool) -> Option<DateTime<Utc>> {
+        if self.token_bucket.is_some() || !auto_reset {
+            return None;
+        }
+        self.message_reset_period
+            .next_period_start(self.message_period_start)
+    }
+
+    /// When the bandwidth counter next starts over; `None` when it never does.
+    pub fn next_bandwidth_reset(&self, auto_reset: bool) -> Option<DateTime<Utc>> {
+        if !auto_reset {
+            return None;
+        }
+        self.bandwidth_reset_period
+            .next_period_start(self.bandwidth_period_start)
+    }
+
     pub fn remaining_bandwidth_gb(&self) -> f64 {
         if self.bandwidth_limit_bytes == 0 {
             0.0
@@ -64,3 +183,9 @@ impl QuotaMetrics {
         }
     }
 }
+
+/// Whole seconds from `now` until `at`, rounded up.
+fn seconds_until(now: DateTime<Utc>, at: DateTime<Utc>) -> u64 {
+    let millis = (at - now).num_milliseconds().max(0) as u64;
+    millis.div_ceil(1000)
+}
@@ -1,10 +1,13 @@
+pub mod bucket;
 pub mod error;
 pub mod manager;
 pub mod metrics;
+pub mod period;
 
 pub use error::QuotaError;
 pub use manager::QuotaManager;
 pub use metrics::QuotaMetrics;
+pub use period::ResetPeriod;
 
 pub const MESSAGE_QUOTA_TYPE: &str = "message_count";
 pub const BANDWIDTH_QUOTA_TYPE: &str = "bandwidth";
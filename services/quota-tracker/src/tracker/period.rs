@@ -0,0 +1,91 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How often a quota dimension's usage counter starts over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+    Never,
+}
+
+impl ResetPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResetPeriod::Daily => "daily",
+            ResetPeriod::Weekly => "weekly",
+            ResetPeriod::Monthly => "monthly",
+            ResetPeriod::Never => "never",
+        }
+    }
+
+    /// Start of the period containing `now`. Weeks start on Monday (UTC).
+    pub fn period_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let date = now.date_naive();
+        let start = match self {
+            ResetPeriod::Daily => date,
+            ResetPeriod::Weekly => {
+                date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            ResetPeriod::Monthly => date.with_day(1).expect("day 1 exists in every month"),
+            ResetPeriod::Never => return DateTime::<Utc>::UNIX_EPOCH,
+        };
+        Utc.from_utc_datetime(&start.and_time(NaiveTime::MIN))
+    }
+
+    /// Start of the period after the one containing `now`; `None` for `never`.
+    pub fn next_period_start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = self.period_start(now);
+        match self {
+            ResetPeriod::Daily => Some(start + Duration::days(1)),
+            ResetPeriod::Weekly => Some(start + Duration::days(7)),
+            ResetPeriod::Monthly => start.checked_add_months(Months::new(1)),
+            ResetPeriod::Never => None,
+        }
+    }
+
+    /// Key under which usage for the period starting at `start` is stored.
+    pub fn period_key(&self, start: DateTime<Utc>) -> String {
+        match self {
+            ResetPeriod::Daily => start.format("%Y-%m-%d").to_string(),
+            ResetPeriod::Weekly => start.format("%G-W%V").to_string(),
+            ResetPeriod::Monthly => start.format("%Y-%m").to_string(),
+            ResetPeriod::Never => "lifetime".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ResetPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ResetPeriod {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "daily" => Ok(ResetPeriod::Daily),
+            "weekly" => Ok(ResetPeriod::Weekly),
+            "monthly" => Ok(ResetPeriod::Monthly),
+            "never" => Ok(ResetPeriod::Never),
+            other => Err(format!("unknown reset period {other}")),
+        }
+    }
+}
+
+/// Messages reset daily unless configured otherwise.
+pub fn default_message_reset_period() -> ResetPeriod {
+    ResetPeriod::Daily
+}
+
+/// Bandwidth resets monthly unless configured otherwise.
+pub fn default_bandwidth_reset_period() -> ResetPeriod {
+    ResetPeriod::Monthly
+}
@@ -106,6 +106,7 @@ pub struct TestHarness {
     service_blueprints: Vec<ServiceConfig>,
     pub services: HashMap<&'static str, ServiceProcess>,
     http_client: Client,
+    content_bundle_ids: bool,
 }
 
 impl TestHarness {
@@ -125,9 +126,17 @@ impl TestHarness {
             service_blueprints: Vec::new(),
             services: HashMap::new(),
             http_client,
+            content_bundle_ids: false,
         })
     }
 
+    /// Derive deployed bundle ids from the compiled Rego instead of a random
+    /// UUID, so deploying identical policies yields the same bundle id.
+    pub fn with_content_bundle_ids(mut self, enabled: bool) -> Self {
+        self.content_bundle_ids = enabled;
+        self
+    }
+
     pub fn ports(&self) -> &PortConfig {
         &self.ports
     }
@@ -399,11 +408,14 @@ impl TestHarness {
         Ok(())
     }
 
+    /// Compiles, registers and activates `dsl_source` for the tenant,
+    /// returning the bundle id. With content-derived ids, a bundle already
+    /// registered under the same id is activated without registering it again.
     pub async fn deploy_test_policy(
         &self,
         tenant_id: &str,
         dsl_source: &str,
-    ) -> Result<()> {
+    ) -> Result<String> {
         let bundle_dir = self.temp_dirs.enforcer.join(tenant_id);
         tokio::fs::create_dir_all(&bundle_dir)
             .await
@@ -423,13 +435,17 @@ impl TestHarness {
         )
         .map_err(|e| anyhow!("Failed to compile DSL policy: {}", e))?;
 
-        // Generate unique bundle_id and checksum before moving compiled.rego
-        let bundle_id = format!("bundle-{}", uuid::Uuid::new_v4());
+        // Generate bundle_id and checksum before moving compiled.rego
+        let bundle_id = if self.content_bundle_ids {
+            edge_policy_dsl::content_bundle_id(tenant_id, &compiled.rego)
+        } else {
+            format!("bundle-{}", uuid::Uuid::new_v4())
+        };
         let rego_checksum = format!("{:x}", md5::compute(compiled.rego.as_bytes()));
 
         // Write generated Rego to policy.rego
         let rego_path = bundle_dir.join("policy.rego");
-        tokio::fs::write(&rego_path, compiled.rego)
+        tokio::fs::write(&rego_path, &compiled.rego)
             .await
             .context("writing compiled rego")?;
 
@@ -457,6 +473,7 @@ impl TestHarness {
             "bundle_id": bundle_id,
             "tenant_id": tenant_id,
             "version": "test",
+            "rego_code": compiled.rego,
             "metadata": {
                 "dsl_source": dsl_source,
                 "rego_path": rego_path.display().to_string(),
@@ -464,31 +481,39 @@ impl TestHarness {
             }
         });
 
-        let url = format!("http://127.0.0.1:{}/api/bundles", self.ports.audit_store);
-        let response = self
-            .http_client
-            .post(&url)
-            .json(&bundle_payload)
-            .send()
-            .await
-            .context("registering bundle in audit-store")?;
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(anyhow!(
-                "failed to register bundle for {}: {} - {}",
-                tenant_id,
-                status,
-                body
-            ));
-        }
+        let already_registered = self.content_bundle_ids
+            && self
+                .bundle_registered(&bundle_id, tenant_id, &compiled.rego)
+                .await?;
+        let final_bundle_id = if already_registered {
+            bundle_id
+        } else {
+            let url = format!("http://127.0.0.1:{}/api/bundles", self.ports.audit_store);
+            let response = self
+                .http_client
+                .post(&url)
+                .json(&bundle_payload)
+                .send()
+                .await
+                .context("registering bundle in audit-store")?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(anyhow!(
+                    "failed to register bundle for {}: {} - {}",
+                    tenant_id,
+                    status,
+                    body
+                ));
+            }
 
-        // Parse response to get the actual bundle_id (server may override)
-        let response_body: serde_json::Value = response.json().await.unwrap_or_default();
-        let final_bundle_id = response_body["bundle_id"]
-            .as_str()
-            .unwrap_or(&bundle_id)
-            .to_string();
+            // Parse response to get the actual bundle_id (server may override)
+            let response_body: serde_json::Value = response.json().await.unwrap_or_default();
+            response_body["bundle_id"]
+                .as_str()
+                .unwrap_or(&bundle_id)
+                .to_string()
+        };
 
         // Activate bundle using the correct path format
         let activate_url = format!(
@@ -524,7 +549,43 @@ impl TestHarness {
             .error_for_status()
             .context("enforcer reload failed")?;
 
-        Ok(())
+        Ok(final_bundle_id)
+    }
+
+    /// Whether `bundle_id` is already stored with exactly this tenant and
+    /// Rego. A stored bundle with other content under the same id is a
+    /// collision and fails the registration.
+    async fn bundle_registered(
+        &self,
+        bundle_id: &str,
+        tenant_id: &str,
+        rego: &str,
+    ) -> Result<bool> {
+        let url = format!(
+            "http://127.0.0.1:{}/api/bundles/{bundle_id}",
+            self.ports.audit_store
+        );
+        let response = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .context("looking up bundle in audit-store")?;
+        if !response.status().is_success() {
+            return Ok(false);
+        }
+
+        let stored: serde_json::Value =
+            response.json().await.context("parsing stored bundle")?;
+        if stored["tenant_id"].as_str() != Some(tenant_id)
+            || stored["rego_code"].as_str() != Some(rego)
+        {
+            return Err(anyhow!(
+                "bundle {} is already stored with different content",
+                bundle_id
+            ));
+        }
+        Ok(true)
     }
 
     pub async fn stop_service(&mut self, name: &str) -> Result<()> {
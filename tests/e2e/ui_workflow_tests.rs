@@ -90,6 +90,27 @@ allow read sensor_data if subject.tenant_id == "tenant"
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_identical_deploys_share_content_bundle_id() -> Result<()> {
+    let mut harness = TestHarness::new().await?.with_content_bundle_ids(true);
+    harness.start_all_services().await?;
+    let tenant_id = random_tenant_id("tenant-ui");
+    harness
+        .create_test_tenant(&tenant_id, &default_tenant_config())
+        .await?;
+
+    let policy = r#"
+allow read sensor_data if subject.tenant_id == "tenant"
+"#;
+    let first = harness.deploy_test_policy(&tenant_id, policy).await?;
+    let second = harness.deploy_test_policy(&tenant_id, policy).await?;
+    assert_eq!(first, second, "identical deploys should share a bundle id");
+    assert!(first.starts_with("bundle-"));
+
+    harness.cleanup().await?;
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[ignore = "requires Tauri WebDriver environment"]
 async fn test_policy_test_simulator() -> Result<()> {